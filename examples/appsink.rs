@@ -21,9 +21,9 @@ fn main(){
 			match appsink.recv(){
 				Ok(gst::appsink::Message::NewPreroll(sample)) | Ok(gst::appsink::Message::NewSample(sample)) => {
 				    if let Some(buffer) = sample.buffer(){
-						let rms = buffer.map_read(|mapping| {
-							(mapping.iter::<f32>().fold(0.0f32, |rms, &sample| rms + sample*sample) / mapping.len::<f32>() as f32).sqrt()
-						}).unwrap();
+						let mapping = buffer.map_read().unwrap();
+						let samples = mapping.data::<f32>();
+						let rms = (samples.iter().fold(0.0f32, |rms, &sample| rms + sample*sample) / samples.len() as f32).sqrt();
 						for i in 0..80{
 							if (rms*80.0) as u32 > i{
 								print!("|");
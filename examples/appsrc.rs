@@ -13,7 +13,7 @@ fn main(){
 	let bus_receiver = bus.receiver();
 	let appsrc = pipeline.get_by_name("appsrc0").expect("Couldn't get appsrc from pipeline");
 	let mut appsrc = gst::AppSrc::new_from_element(appsrc);
-	let bufferpool = gst::BufferPool::new().unwrap();
+	let mut bufferpool = gst::BufferPool::new().unwrap();
 	let appsrc_caps = appsrc.caps().unwrap();
 	bufferpool.set_params(&appsrc_caps,640*480*3,0,0);
 	if bufferpool.set_active(true).is_err(){
@@ -28,11 +28,11 @@ fn main(){
 	    let mut gray = 0;
 		loop {
 		    if let Some(mut buffer) = bufferpool.acquire_buffer(){
-			    buffer.map_write(|mut mapping|{
-			        for c in mapping.iter_mut::<u8>(){
+			    if let Some(mut mapping) = buffer.map_write(){
+			        for c in mapping.iter_mut(){
 			            *c = gray;
 			        }
-			    }).ok();
+			    }
 			    gray += 1;
 			    gray %= 255;
 				appsrc.push_buffer(buffer);
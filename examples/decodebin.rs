@@ -1,21 +1,6 @@
 extern crate gst;
 
 use std::env;
-use std::os::raw::c_void;
-use std::mem;
-
-extern "C" fn signal_callback(_src: *mut gst::ffi::GstElement, pad: *mut gst::ffi::GstPad, sinkpad: &mut gst::Pad){
-    unsafe{
-        if !sinkpad.is_linked(){
-            let mut decodebin_pad = gst::Pad::new(pad).unwrap();
-            let caps = decodebin_pad.query_caps(None).unwrap();
-            let structure = caps.structure(0).unwrap();
-            if structure.name().starts_with("video") {
-                decodebin_pad.link(sinkpad).unwrap();
-            }
-        }
-    }
-}
 
 fn main(){
     gst::init();
@@ -32,9 +17,15 @@ fn main(){
     let mut decodebin = gst::Element::new("decodebin", "").unwrap();
     let mut sink = gst::Element::new("glimagesink", "").unwrap();
     let mut sink_pad = sink.static_pad("sink").unwrap();
-    unsafe{
-        decodebin.signal_connect("pad-added", mem::transmute(signal_callback as *mut c_void), &mut sink_pad);
-    }
+    decodebin.connect_pad_added(move |_decodebin, pad|{
+        if !sink_pad.is_linked(){
+            let caps = pad.query_caps(None).unwrap();
+            let structure = caps.structure(0).unwrap();
+            if structure.name().starts_with("video") {
+                pad.link(&mut sink_pad).unwrap();
+            }
+        }
+    });
     if !pipeline.add_and_link(filesrc, decodebin){
         panic!("couldn't link filesrc and decodebin");
     }
@@ -0,0 +1,267 @@
+use ffi::*;
+use element::Element;
+use error::Result;
+use playbin::PlayBin;
+use sample::Sample;
+use unwind;
+
+use std::ptr;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// One of the two compared pipelines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side{
+    A,
+    B,
+}
+
+struct Hooks{
+    video: VecDeque<(Side, GstClockTime, Sample)>,
+    tolerance: GstClockTime,
+    on_video_pair: Option<Box<dyn FnMut(&Sample, &Sample) + Send>>,
+    on_audio: Option<Box<dyn FnMut(Side, &Sample) + Send>>,
+    on_unmatched: Option<Box<dyn FnMut(Side, &Sample) + Send>>,
+}
+
+type SharedHooks = Arc<Mutex<Hooks>>;
+
+impl Hooks{
+    // pairs the frames of both sides with the same timestamp, a frame is
+    // unmatched once the other side has gone past its timestamp
+    fn push_video(&mut self, side: Side, pts: GstClockTime, sample: Sample){
+        self.video.push_back((side, pts, sample));
+        loop{
+            let (a, b) = match (self.video.iter().position(|f| f.0 == Side::A),
+                                self.video.iter().position(|f| f.0 == Side::B)){
+                (Some(a), Some(b)) => (a, b),
+                _ => return
+            };
+            let (pts_a, pts_b) = (self.video[a].1, self.video[b].1);
+            if pts_a + self.tolerance < pts_b{
+                let (side, _, frame) = self.video.remove(a).unwrap();
+                if let Some(ref mut on_unmatched) = self.on_unmatched{
+                    on_unmatched(side, &frame);
+                }
+            }else if pts_b + self.tolerance < pts_a{
+                let (side, _, frame) = self.video.remove(b).unwrap();
+                if let Some(ref mut on_unmatched) = self.on_unmatched{
+                    on_unmatched(side, &frame);
+                }
+            }else{
+                let (first, second) = if a < b { (b, a) } else { (a, b) };
+                let frame_1 = self.video.remove(first).unwrap();
+                let frame_2 = self.video.remove(second).unwrap();
+                let (frame_a, frame_b) = if frame_1.0 == Side::A { (frame_1, frame_2) } else { (frame_2, frame_1) };
+                if let Some(ref mut on_video_pair) = self.on_video_pair{
+                    on_video_pair(&frame_a.2, &frame_b.2);
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self){
+        self.video.clear();
+    }
+}
+
+// data of the probe on the filter of one side
+struct Tap{
+    hooks: SharedHooks,
+    side: Side,
+    video: bool,
+}
+
+/// Plays two files or streams in sync, for side by side or A/B
+/// evaluation of codecs and encoder settings, and calls hooks with the
+/// decoded frames of both so they can be compared.
+///
+/// Both sides are playbins that share the system clock and a base time,
+/// and every seek is done on both, exact to the frame, before starting
+/// them again at the same time. Their sinks can be configured through `a`
+/// and `b`.
+///
+/// Video frames are delivered in pairs with the same timestamp, so
+/// metrics like PSNR can be computed on them. Audio, whose buffers don't
+/// line up between encodings, is delivered per side, for example to
+/// compare loudness over time.
+pub struct AbComparison{
+    a: PlayBin,
+    b: PlayBin,
+    hooks: SharedHooks,
+    clock: *mut GstClock,
+}
+
+unsafe impl Sync for AbComparison {}
+unsafe impl Send for AbComparison {}
+
+impl AbComparison{
+    pub fn new(uri_a: &str, uri_b: &str) -> Result<AbComparison>{
+        let hooks = Arc::new(Mutex::new(Hooks{
+            video: VecDeque::new(),
+            tolerance: 1_000_000,
+            on_video_pair: None,
+            on_audio: None,
+            on_unmatched: None,
+        }));
+        let clock = unsafe{ gst_system_clock_obtain() };
+        let a = AbComparison::side(uri_a, Side::A, &hooks, clock)?;
+        let b = AbComparison::side(uri_b, Side::B, &hooks, clock)?;
+        Ok(AbComparison{ a: a, b: b, hooks: hooks, clock: clock })
+    }
+
+    fn side(uri: &str, side: Side, hooks: &SharedHooks, clock: *mut GstClock) -> Result<PlayBin>{
+        let mut playbin = PlayBin::new(if side == Side::A { "a" } else { "b" })?;
+        playbin.set_uri(uri);
+        let mut video_filter = Element::new("identity", "")?;
+        let mut audio_filter = Element::new("identity", "")?;
+        tap(&mut video_filter, hooks, side, true);
+        tap(&mut audio_filter, hooks, side, false);
+        playbin.set_video_filter(&video_filter);
+        playbin.set("audio-filter", &audio_filter);
+        unsafe{
+            gst_pipeline_use_clock(playbin.gst_pipeline_mut(), clock);
+            // the base time is distributed by us to both sides
+            gst_element_set_start_time(playbin.gst_element_mut(), GST_CLOCK_TIME_NONE);
+        }
+        Ok(playbin)
+    }
+
+    /// First pipeline, to set its sinks or query it
+    pub fn a(&mut self) -> &mut PlayBin{
+        &mut self.a
+    }
+
+    /// Second pipeline, to set its sinks or query it
+    pub fn b(&mut self) -> &mut PlayBin{
+        &mut self.b
+    }
+
+    /// Called from the streaming threads with the frames of both sides
+    /// that have the same timestamp, A first
+    pub fn on_video_pair<F: FnMut(&Sample, &Sample) + Send + 'static>(&mut self, f: F){
+        self.hooks.lock().unwrap().on_video_pair = Some(Box::new(f));
+    }
+
+    /// Called with the frames that have no frame with the same timestamp
+    /// on the other side, like when the frame rates differ or frames were
+    /// dropped
+    pub fn on_unmatched_frame<F: FnMut(Side, &Sample) + Send + 'static>(&mut self, f: F){
+        self.hooks.lock().unwrap().on_unmatched = Some(Box::new(f));
+    }
+
+    /// Called from the streaming threads with the decoded audio of each
+    /// side
+    pub fn on_audio<F: FnMut(Side, &Sample) + Send + 'static>(&mut self, f: F){
+        self.hooks.lock().unwrap().on_audio = Some(Box::new(f));
+    }
+
+    /// Maximum difference in nanoseconds between the timestamps of two
+    /// frames to consider them the same frame, 1ms by default
+    pub fn set_tolerance(&mut self, tolerance: GstClockTime){
+        self.hooks.lock().unwrap().tolerance = tolerance;
+    }
+
+    /// Starts or resumes both sides at the same time, from the position of
+    /// the first one
+    pub fn play(&mut self) -> bool{
+        let position = self.a.position_ns().unwrap_or(0);
+        self.seek_paused(position) && self.start()
+    }
+
+    pub fn pause(&mut self){
+        self.a.set_state(GST_STATE_PAUSED);
+        self.b.set_state(GST_STATE_PAUSED);
+    }
+
+    /// Moves both sides to the same frame, keeping them playing if they
+    /// were
+    pub fn seek(&mut self, position: i64) -> bool{
+        let playing = self.a.is_playing();
+        self.seek_paused(position) && (!playing || self.start())
+    }
+
+    pub fn stop(&mut self){
+        self.a.set_state(GST_STATE_NULL);
+        self.b.set_state(GST_STATE_NULL);
+        self.hooks.lock().unwrap().clear();
+    }
+
+    // a flushing seek also resets the running time of both to 0
+    fn seek_paused(&mut self, position: i64) -> bool{
+        self.pause();
+        if !self.wait_preroll(){
+            return false;
+        }
+        self.hooks.lock().unwrap().clear();
+        let flags = GST_SEEK_FLAG_FLUSH | GST_SEEK_FLAG_ACCURATE;
+        self.a.seek_simple(GST_FORMAT_TIME, flags, position) &&
+            self.b.seek_simple(GST_FORMAT_TIME, flags, position) &&
+            self.wait_preroll()
+    }
+
+    fn wait_preroll(&self) -> bool{
+        let (_, _, a) = self.a.get_state(GST_CLOCK_TIME_NONE);
+        let (_, _, b) = self.b.get_state(GST_CLOCK_TIME_NONE);
+        a != GST_STATE_CHANGE_FAILURE && b != GST_STATE_CHANGE_FAILURE
+    }
+
+    fn start(&mut self) -> bool{
+        unsafe{
+            let base_time = gst_clock_get_time(self.clock);
+            gst_element_set_base_time(self.a.gst_element_mut(), base_time);
+            gst_element_set_base_time(self.b.gst_element_mut(), base_time);
+        }
+        self.a.set_state(GST_STATE_PLAYING) != GST_STATE_CHANGE_FAILURE &&
+            self.b.set_state(GST_STATE_PLAYING) != GST_STATE_CHANGE_FAILURE
+    }
+}
+
+impl Drop for AbComparison{
+    fn drop(&mut self){
+        self.stop();
+        unsafe{
+            gst_object_unref(self.clock as gpointer);
+        }
+    }
+}
+
+fn tap(filter: &mut Element, hooks: &SharedHooks, side: Side, video: bool){
+    if let Some(mut src) = filter.static_pad("src"){
+        let data = Box::new(Tap{ hooks: hooks.clone(), side: side, video: video });
+        unsafe{
+            gst_pad_add_probe(src.gst_pad_mut(), GST_PAD_PROBE_TYPE_BUFFER,
+                              Some(tap_probe), Box::into_raw(data) as gpointer,
+                              Some(free_tap));
+        }
+    }
+}
+
+extern "C" fn tap_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        let tap = &*(data as *const Tap);
+        let buffer = (*info).data as *mut GstBuffer;
+        let caps = gst_pad_get_current_caps(pad);
+        let sample = Sample::new(gst_sample_new(buffer, caps, ptr::null(), ptr::null_mut()));
+        if caps != ptr::null_mut(){
+            gst_mini_object_unref(caps as *mut GstMiniObject);
+        }
+        if let Some(sample) = sample{
+            unwind::guard(pad as *mut GstObject, (), || {
+                let mut hooks = tap.hooks.lock().unwrap();
+                if tap.video{
+                    hooks.push_video(tap.side, (*buffer).pts, sample);
+                }else if let Some(ref mut on_audio) = hooks.on_audio{
+                    on_audio(tap.side, &sample);
+                }
+            });
+        }
+        GST_PAD_PROBE_OK
+    }
+}
+
+extern "C" fn free_tap(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Tap));
+    }
+}
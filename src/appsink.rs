@@ -7,7 +7,13 @@ use std::ops::{Deref, DerefMut};
 
 use sample::Sample;
 use element::Element;
+use error::Error;
 use caps::Caps;
+use videoframe::VideoFrame;
+use videoinfo::VideoInfo;
+use audioinfo::AudioInfo;
+use unwind;
+use cancellable::{self, CancellationToken};
 
 pub enum Message{
 	NewSample(Sample),
@@ -41,39 +47,48 @@ impl Message{
 unsafe impl Sync for AppSink {}
 unsafe impl Send for AppSink {}
 
-#[allow(dead_code)] // we need to keep the samples_sender around
+/// Wrapper for the appsink element, to get the samples of a pipeline into
+/// the application.
+///
+/// By default the samples are delivered through a channel read with
+/// `recv`. A closure can instead be called from the streaming thread with
+/// every new sample with `on_new_sample`, or the samples can be pulled
+/// with `pull_sample` after calling `disable_callbacks`.
 pub struct AppSink{
     appsink: Element,
     samples_receiver: Receiver<Message>,
-    samples_sender: Box<Sender<Message>>
+    samples_sender: Sender<Message>,
+    info: Option<SampleInfo>,
+}
+
+// formats parsed from the caps of the last samples, reused while the
+// samples keep the same caps
+struct SampleInfo{
+    caps: Caps,
+    video: Option<VideoInfo>,
+    audio: Option<AudioInfo>,
+}
+
+// data of the appsink callbacks, owned by the appsink
+struct Callbacks{
+    sender: Sender<Message>,
+    new_sample: Option<Box<dyn FnMut(Sample) -> GstFlowReturn + Send>>,
 }
 
 impl AppSink{
-    pub fn new(name: &str) -> Option<AppSink>{
-        let (sender,receiver) = channel();
-        let sender = Box::new(sender);
-        let appsink = Element::new("appsink",name);
-        unsafe{
-            match appsink{
-                Some(a) => {
-                    let mut gst_callbacks = GstAppSinkCallbacks{
-                                eos: Some(on_eos_from_source),
-                                new_preroll: Some(on_new_preroll_from_source),
-                                new_sample: Some(on_new_sample_from_source),
-                                _gst_reserved: [ptr::null_mut(); 4]
-                    };
-                    gst_app_sink_set_callbacks(a.gst_element() as *mut GstAppSink, &mut gst_callbacks, mem::transmute(&*sender), None);
-                    Some(AppSink{ appsink: a, samples_receiver: receiver, samples_sender: sender })
-                },
-
-                None => None
-            }
-        }
+    pub fn new(name: &str) -> Result<AppSink, Error>{
+        Element::new("appsink",name).map(AppSink::new_from_element)
     }
 
     pub fn new_from_element(element: Element) -> AppSink{
         let (sender,receiver) = channel();
-        let sender = Box::new(sender);
+        let mut appsink = AppSink{ appsink: element, samples_receiver: receiver, samples_sender: sender, info: None };
+        appsink.set_callbacks(None);
+        appsink
+    }
+
+    fn set_callbacks(&mut self, new_sample: Option<Box<dyn FnMut(Sample) -> GstFlowReturn + Send>>){
+        let callbacks = Box::new(Callbacks{ sender: self.samples_sender.clone(), new_sample: new_sample });
         unsafe{
             let mut gst_callbacks = GstAppSinkCallbacks{
                         eos: Some(on_eos_from_source),
@@ -81,9 +96,119 @@ impl AppSink{
                         new_sample: Some(on_new_sample_from_source),
                         _gst_reserved: [ptr::null_mut(); 4]
             };
-            gst_app_sink_set_callbacks(element.gst_element() as *mut GstAppSink, &mut gst_callbacks, mem::transmute(&*sender), None);
+            gst_app_sink_set_callbacks(self.gst_appsink_mut(), &mut gst_callbacks,
+                                       Box::into_raw(callbacks) as gpointer, Some(free_callbacks));
+        }
+    }
+
+    /// Calls `f` from the streaming thread with every new sample instead
+    /// of sending it to `recv`, prerolls and the end of stream are still
+    /// received there. The returned flow is returned upstream, so
+    /// returning an error or `GST_FLOW_EOS` stops the stream
+    pub fn on_new_sample<F: FnMut(Sample) -> GstFlowReturn + Send + 'static>(&mut self, f: F){
+        self.set_callbacks(Some(Box::new(f)));
+    }
+
+    /// Stops delivering samples to `recv` or the `on_new_sample` closure,
+    /// so they can be pulled with `pull_sample` and `pull_preroll`
+    pub fn disable_callbacks(&mut self){
+        unsafe{
+            let mut gst_callbacks = GstAppSinkCallbacks{
+                        eos: None,
+                        new_preroll: None,
+                        new_sample: None,
+                        _gst_reserved: [ptr::null_mut(); 4]
+            };
+            gst_app_sink_set_callbacks(self.gst_appsink_mut(), &mut gst_callbacks, ptr::null_mut(), None);
+        }
+    }
+
+    /// Blocks until a sample is available and returns it, or returns None
+    /// once the appsink is at the end of the stream or stopped. Samples
+    /// can only be pulled after `disable_callbacks`
+    pub fn pull_sample(&mut self) -> Option<Sample>{
+        unsafe{
+            Sample::new(gst_app_sink_pull_sample(self.gst_appsink_mut()))
+        }
+    }
+
+    /// Like `pull_sample` waiting at most `timeout` nanoseconds, None if
+    /// no sample arrived in time
+    pub fn try_pull_sample(&mut self, timeout: GstClockTime) -> Option<Sample>{
+        unsafe{
+            Sample::new(gst_app_sink_try_pull_sample(self.gst_appsink_mut(), timeout))
+        }
+    }
+
+    /// Like `try_pull_sample` but returns None as soon as `token` is
+    /// cancelled, GST_CLOCK_TIME_NONE waits until a sample arrives, the end
+    /// of the stream or the cancellation.
+    ///
+    /// appsink has no way to interrupt a pull, so this pulls with 20ms
+    /// timeouts checking the token in between: the thread wakes up every
+    /// 20ms while waiting and returns up to 20ms after the cancellation
+    pub fn pull_sample_cancellable(&mut self, timeout: GstClockTime, token: &CancellationToken) -> Option<Sample>{
+        cancellable::poll(token, timeout, |timeout| match self.try_pull_sample(timeout){
+            Some(sample) => Some(Some(sample)),
+            None if self.is_eos() => Some(None),
+            None => None
+        }).and_then(|sample| sample)
+    }
+
+    /// Like `pull_sample` but maps the buffer of the sample as a video
+    /// frame, returned with the format of the frames. The format is only
+    /// parsed from the caps when they change, not for every frame
+    pub fn pull_frame(&mut self) -> Option<(VideoFrame, &VideoInfo)>{
+        let sample = self.pull_sample()?;
+        let buffer = sample.buffer()?;
+        let info = self.sample_info(&sample)?.video.as_ref()?;
+        VideoFrame::map(&buffer, info).map(|frame| (frame, info))
+    }
+
+    /// The video format of `sample`, a sample of this appsink, parsed from
+    /// its caps only if they're not the caps of the previous samples. For
+    /// samples received with `recv` or `pull_sample`
+    pub fn video_info_of(&mut self, sample: &Sample) -> Option<&VideoInfo>{
+        self.sample_info(sample).and_then(|info| info.video.as_ref())
+    }
+
+    /// The audio format of `sample`, like `video_info_of`
+    pub fn audio_info_of(&mut self, sample: &Sample) -> Option<&AudioInfo>{
+        self.sample_info(sample).and_then(|info| info.audio.as_ref())
+    }
+
+    fn sample_info(&mut self, sample: &Sample) -> Option<&SampleInfo>{
+        let caps = unsafe{ gst_sample_get_caps(sample.gst_sample() as *mut GstSample) };
+        if caps == ptr::null_mut(){
+            return None;
+        }
+        // the cached caps are kept alive, so the same pointer means the
+        // same caps
+        let stale = match self.info{
+            Some(ref info) => unsafe{ info.caps.gst_caps() != caps as *const GstCaps },
+            None => true
+        };
+        if stale{
+            let caps = sample.caps()?;
+            self.info = Some(SampleInfo{ video: caps.video_info(), audio: caps.audio_info(), caps: caps });
+        }
+        self.info.as_ref()
+    }
+
+    /// Blocks until the appsink prerolls and returns the preroll sample, or
+    /// returns None at the end of the stream or when stopped. The preroll
+    /// sample is also returned by the following `pull_sample`
+    pub fn pull_preroll(&mut self) -> Option<Sample>{
+        unsafe{
+            Sample::new(gst_app_sink_pull_preroll(self.gst_appsink_mut()))
+        }
+    }
+
+    /// Like `pull_preroll` waiting at most `timeout` nanoseconds
+    pub fn try_pull_preroll(&mut self, timeout: GstClockTime) -> Option<Sample>{
+        unsafe{
+            Sample::new(gst_app_sink_try_pull_preroll(self.gst_appsink_mut(), timeout))
         }
-        AppSink{ appsink: element, samples_receiver: receiver, samples_sender: sender }
     }
 
     pub fn recv(&self) -> Result<Message,RecvError>{
@@ -111,7 +236,7 @@ impl AppSink{
 	pub fn get_caps(&self) -> Option<Caps>{
 		unsafe{
 			let caps = gst_app_sink_get_caps(mem::transmute(self.gst_appsink()));
-			Caps::new(caps)
+			Caps::new_from_gst_caps(caps)
 		}
 	}
 
@@ -160,11 +285,14 @@ impl AppSink{
 
 extern "C" fn on_new_sample_from_source (elt: *mut GstAppSink, data: gpointer ) -> GstFlowReturn{
     unsafe{
-		let sender = data as *mut Sender<Message>;
+		let callbacks = &mut *(data as *mut Callbacks);
         let sample = gst_app_sink_pull_sample (elt);
         match Sample::new(sample){
             Some(sample) => {
-		        match (*sender).send(Message::NewSample(sample)){
+                if let Some(ref mut new_sample) = callbacks.new_sample{
+                    return unwind::guard(elt as *mut GstObject, GST_FLOW_ERROR, || new_sample(sample));
+                }
+		        match callbacks.sender.send(Message::NewSample(sample)){
 					Ok(()) => GST_FLOW_OK,
 					Err(SendError(_msg)) => GST_FLOW_EOS
 				}
@@ -176,11 +304,11 @@ extern "C" fn on_new_sample_from_source (elt: *mut GstAppSink, data: gpointer )
 
 extern "C" fn on_new_preroll_from_source (elt: *mut GstAppSink, data: gpointer) -> GstFlowReturn{
     unsafe{
-		let sender = data as *mut Sender<Message>;
+		let callbacks = &*(data as *const Callbacks);
         let sample = gst_app_sink_pull_preroll (elt);
         match Sample::new(sample){
             Some(sample) => {
-		        match (*sender).send(Message::NewPreroll(sample)){
+		        match callbacks.sender.send(Message::NewPreroll(sample)){
 					Ok(()) => GST_FLOW_OK,
 					Err(SendError(_msg)) => GST_FLOW_EOS
 				}
@@ -192,8 +320,14 @@ extern "C" fn on_new_preroll_from_source (elt: *mut GstAppSink, data: gpointer)
 
 extern "C" fn on_eos_from_source (_elt: *mut GstAppSink, data: gpointer){
     unsafe{
-		let sender = data as *mut Sender<Message>;
-        (*sender).send(Message::Eos).unwrap();
+		let callbacks = &*(data as *const Callbacks);
+        let _ = callbacks.sender.send(Message::Eos);
+    }
+}
+
+extern "C" fn free_callbacks(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Callbacks));
     }
 }
 
@@ -2,29 +2,53 @@ use ffi::*;
 use ::Transfer;
 use ::Element;
 use ::Caps;
+use buffer_pool::BufferPool;
+use error::Result;
 use std::mem;
 use reference::Reference;
+use unwind;
 
 use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::{Arc, Mutex};
 
+/// Wrapper for the appsrc element, to feed data generated by the
+/// application into a pipeline.
+///
+/// Buffers are pushed with `push_buffer`, either from any thread or when
+/// the appsrc asks for them from the closure set with `on_need_data`.
 pub struct AppSrc{
-    appsrc: ::Element
+    appsrc: ::Element,
+    callbacks: Arc<Mutex<Callbacks>>,
+    // pool of acquire_write_buffer and the size of its buffers
+    pool: Arc<Mutex<Option<(usize, BufferPool)>>>,
+}
+
+// user data of the appsrc callbacks, so the AppSrc the closures receive
+// shares the closures and the buffer pool of the one that set them
+#[derive(Clone)]
+struct Shared{
+    callbacks: Arc<Mutex<Callbacks>>,
+    pool: Arc<Mutex<Option<(usize, BufferPool)>>>,
+}
+
+#[derive(Default)]
+struct Callbacks{
+    need_data: Option<Box<dyn FnMut(&mut AppSrc, u32) + Send>>,
+    enough_data: Option<Box<dyn FnMut(&mut AppSrc) + Send>>,
+    seek_data: Option<Box<dyn FnMut(&mut AppSrc, u64) -> bool + Send>>,
 }
 
 unsafe impl Sync for AppSrc {}
 unsafe impl Send for AppSrc {}
 
 impl AppSrc{
-    pub fn new(name: &str) -> Option<AppSrc>{
-        let appsrc = ::Element::new("appsrc",name);
-        match appsrc{
-            Some(appsrc) => Some(AppSrc{appsrc: appsrc}),
-            None => None
-        }
+    pub fn new(name: &str) -> Result<AppSrc>{
+        ::Element::new("appsrc",name).map(AppSrc::new_from_element)
     }
 
     pub fn new_from_element(element: ::Element) -> AppSrc{
-        AppSrc{appsrc: element}
+        AppSrc{appsrc: element, callbacks: Arc::new(Mutex::new(Callbacks::default())), pool: Arc::new(Mutex::new(None))}
     }
 
     /// Set the capabilities on the `AppSrc`. After calling this method, the source will only
@@ -44,7 +68,7 @@ impl AppSrc{
     pub fn caps(&self) -> Option<Caps>{
         unsafe{
 	        let gst_caps = gst_app_src_get_caps(mem::transmute(self.gst_appsrc()));
-	        Caps::new(gst_caps)
+	        Caps::new_from_gst_caps(gst_caps)
 	    }
     }
 
@@ -63,12 +87,159 @@ impl AppSrc{
         }
     }
 
+    /// Pushes all the buffers of `buffers` at once, as a single buffer
+    /// list, locking the appsrc and waking its streaming thread only once
+    /// instead of for every buffer. For feeding many small buffers, like
+    /// audio or RTP packets. Downstream elements that don't handle lists
+    /// get the buffers one by one
+    pub fn push_iter<I>(&mut self, buffers: I) -> GstFlowReturn
+        where I: IntoIterator<Item = ::Buffer>{
+        let buffers = buffers.into_iter();
+        unsafe{
+            let list = gst_buffer_list_new_sized(buffers.size_hint().0 as guint);
+            for buffer in buffers{
+                gst_buffer_list_insert(list, -1, buffer.transfer());
+            }
+            if gst_buffer_list_length(list) == 0{
+                gst_mini_object_unref(list as *mut GstMiniObject);
+                return GST_FLOW_OK;
+            }
+            gst_app_src_push_buffer_list(self.gst_appsrc_mut(), list)
+        }
+    }
+
+    /// Pushes `buffers` as a single buffer list, like `push_iter`
+    pub fn push_buffer_list(&mut self, buffers: Vec<::Buffer>) -> GstFlowReturn{
+        self.push_iter(buffers)
+    }
+
+    /// A writable buffer of `size` bytes to fill and push with
+    /// `push_buffer`, taken from a pool of buffers kept by the appsrc. Once
+    /// downstream releases it the buffer goes back to the pool instead of
+    /// being freed, so pushing frames of the same size at a high rate
+    /// doesn't allocate after the first ones. Asking for a different size
+    /// replaces the pool, the buffers of the old one are freed when
+    /// released. None if the pool can't be started
+    pub fn acquire_write_buffer(&mut self, size: usize) -> Option<::Buffer>{
+        let mut pool = self.pool.lock().unwrap();
+        let current = match *pool{
+            Some((pool_size, _)) => pool_size == size,
+            None => false
+        };
+        if !current{
+            if let Some((_, mut old)) = pool.take(){
+                old.set_active(false).ok();
+            }
+            let mut new = BufferPool::new()?;
+            let caps = self.caps().unwrap_or_else(Caps::new_empty);
+            // no maximum, acquiring never blocks the producer
+            new.set_params(&caps, size as u32, 0, 0);
+            new.set_active(true).ok()?;
+            *pool = Some((size, new));
+        }
+        pool.as_mut().and_then(|&mut (_, ref mut pool)| pool.acquire_buffer())
+    }
+
     pub fn end_of_stream(&mut self) -> GstFlowReturn{
         unsafe{
             gst_app_src_end_of_stream(self.gst_appsrc_mut())
         }
     }
 
+    /// Whether the data can be seeked, and how. `GST_APP_STREAM_TYPE_STREAM`
+    /// by default, for data that can only be pushed in order
+    pub fn set_stream_type(&mut self, stream_type: GstAppStreamType){
+        unsafe{
+            gst_app_src_set_stream_type(self.gst_appsrc_mut(), stream_type);
+        }
+    }
+
+    pub fn stream_type(&self) -> GstAppStreamType{
+        unsafe{
+            gst_app_src_get_stream_type(self.gst_appsrc() as *mut GstAppSrc)
+        }
+    }
+
+    /// Maximum number of bytes queued in the appsrc before it signals it
+    /// has enough data, 0 for no limit
+    pub fn set_max_bytes(&mut self, max: u64){
+        unsafe{
+            gst_app_src_set_max_bytes(self.gst_appsrc_mut(), max);
+        }
+    }
+
+    pub fn max_bytes(&self) -> u64{
+        unsafe{
+            gst_app_src_get_max_bytes(self.gst_appsrc() as *mut GstAppSrc)
+        }
+    }
+
+    /// Bytes currently queued in the appsrc
+    pub fn current_level_bytes(&self) -> u64{
+        unsafe{
+            gst_app_src_get_current_level_bytes(self.gst_appsrc() as *mut GstAppSrc)
+        }
+    }
+
+    /// Whether `push_buffer` blocks while the queue is full instead of
+    /// going over `max_bytes`
+    pub fn set_block(&mut self, block: bool){
+        self.appsrc.set("block", block as gboolean);
+    }
+
+    /// Size of the stream in bytes, -1 if unknown
+    pub fn set_size(&mut self, size: i64){
+        unsafe{
+            gst_app_src_set_size(self.gst_appsrc_mut(), size);
+        }
+    }
+
+    pub fn set_latency(&mut self, min: u64, max: u64){
+        unsafe{
+            gst_app_src_set_latency(self.gst_appsrc_mut(), min, max);
+        }
+    }
+
+    /// Called from the streaming thread when the appsrc needs more data,
+    /// with a hint of the amount of bytes it wants or -1 as u32 if it
+    /// doesn't know. The closure is expected to push buffers on the
+    /// appsrc it receives until `on_enough_data` is called. The enough-data
+    /// closure is then called from within `push_buffer`, on the same thread
+    pub fn on_need_data<F: FnMut(&mut AppSrc, u32) + Send + 'static>(&mut self, f: F){
+        self.callbacks.lock().unwrap_or_else(|err| err.into_inner()).need_data = Some(Box::new(f));
+        self.install_callbacks();
+    }
+
+    /// Called when the queue of the appsrc is full and the application
+    /// should stop pushing buffers
+    pub fn on_enough_data<F: FnMut(&mut AppSrc) + Send + 'static>(&mut self, f: F){
+        self.callbacks.lock().unwrap_or_else(|err| err.into_inner()).enough_data = Some(Box::new(f));
+        self.install_callbacks();
+    }
+
+    /// Called when the stream is seeked with the offset to push data from
+    /// next, in bytes or nanoseconds depending on the format of the
+    /// appsrc. Only called for seekable stream types, returns whether the
+    /// seek succeeded
+    pub fn on_seek_data<F: FnMut(&mut AppSrc, u64) -> bool + Send + 'static>(&mut self, f: F){
+        self.callbacks.lock().unwrap_or_else(|err| err.into_inner()).seek_data = Some(Box::new(f));
+        self.install_callbacks();
+    }
+
+    fn install_callbacks(&mut self){
+        let data = Box::new(Shared{ callbacks: self.callbacks.clone(), pool: self.pool.clone() });
+        unsafe{
+            let mut gst_callbacks = GstAppSrcCallbacks{
+                need_data: Some(on_need_data),
+                enough_data: Some(on_enough_data),
+                seek_data: Some(on_seek_data),
+                _gst_reserved: [ptr::null_mut(); 4]
+            };
+            gst_app_src_set_callbacks(self.gst_appsrc_mut(), &mut gst_callbacks,
+                                      Box::into_raw(data) as gpointer, Some(free_callbacks));
+        }
+    }
+
     pub unsafe fn gst_appsrc(&self) -> *const GstAppSrc{
         self.appsrc.gst_element() as *const GstAppSrc
     }
@@ -78,6 +249,71 @@ impl AppSrc{
     }
 }
 
+// the appsrc the callbacks are called from, with a new reference
+unsafe fn callback_appsrc(src: *mut GstAppSrc, shared: &Shared) -> AppSrc{
+    gst_object_ref(src as gpointer);
+    AppSrc{
+        appsrc: ::Element::new_from_gst_element(src as *mut GstElement).unwrap(),
+        callbacks: shared.callbacks.clone(),
+        pool: shared.pool.clone(),
+    }
+}
+
+// calls the closure of `slot` without keeping the callbacks locked, so the
+// closure can push buffers that make appsrc call the other callbacks from
+// the same thread, or replace the closures. The closure is put back
+// afterwards unless it was replaced meanwhile
+fn call_unlocked<T, R, F>(callbacks: &Mutex<Callbacks>, slot: fn(&mut Callbacks) -> &mut Option<T>, call: F) -> Option<R>
+    where F: FnOnce(&mut T) -> R{
+    let taken = slot(&mut callbacks.lock().unwrap_or_else(|err| err.into_inner())).take();
+    taken.map(|mut f| {
+        let ret = call(&mut f);
+        let mut callbacks = callbacks.lock().unwrap_or_else(|err| err.into_inner());
+        let slot = slot(&mut callbacks);
+        if slot.is_none(){
+            *slot = Some(f);
+        }
+        ret
+    })
+}
+
+extern "C" fn on_need_data(src: *mut GstAppSrc, length: guint, data: gpointer){
+    unsafe{
+        // cloned, the user data is freed if a closure sets the callbacks again
+        let shared = (*(data as *const Shared)).clone();
+        unwind::guard(src as *mut GstObject, (), || {
+            call_unlocked(&shared.callbacks, |c| &mut c.need_data, |need_data| need_data(&mut callback_appsrc(src, &shared), length));
+        });
+    }
+}
+
+extern "C" fn on_enough_data(src: *mut GstAppSrc, data: gpointer){
+    unsafe{
+        // cloned, the user data is freed if a closure sets the callbacks again
+        let shared = (*(data as *const Shared)).clone();
+        unwind::guard(src as *mut GstObject, (), || {
+            call_unlocked(&shared.callbacks, |c| &mut c.enough_data, |enough_data| enough_data(&mut callback_appsrc(src, &shared)));
+        });
+    }
+}
+
+extern "C" fn on_seek_data(src: *mut GstAppSrc, offset: guint64, data: gpointer) -> gboolean{
+    unsafe{
+        // cloned, the user data is freed if a closure sets the callbacks again
+        let shared = (*(data as *const Shared)).clone();
+        unwind::guard(src as *mut GstObject, 0, || {
+            call_unlocked(&shared.callbacks, |c| &mut c.seek_data, |seek_data| seek_data(&mut callback_appsrc(src, &shared), offset) as gboolean)
+                .unwrap_or(1)
+        })
+    }
+}
+
+extern "C" fn free_callbacks(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Shared));
+    }
+}
+
 impl AsRef<::Element> for AppSrc{
     fn as_ref(&self) -> &Element{
         &self.appsrc
@@ -117,6 +353,6 @@ impl ::Transfer for AppSrc{
 
 impl Reference for AppSrc{
     fn reference(&self) -> AppSrc{
-        AppSrc{ appsrc: self.appsrc.reference() }
+        AppSrc{ appsrc: self.appsrc.reference(), callbacks: self.callbacks.clone(), pool: self.pool.clone() }
     }
 }
@@ -0,0 +1,451 @@
+use ffi::*;
+use buffer::Buffer;
+use caps::Caps;
+use element::Element;
+use error::Result;
+use subclass::{self, ClassData, ElementMetadata};
+use unwind;
+
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::Mutex;
+
+/// The GstAudioDecoder base class of an element registered with
+/// `register_audio_decoder`, passed to its implementation to output
+/// decoded audio and negotiate
+pub struct AudioDecoder<'a>{
+    decoder: *mut GstAudioDecoder,
+    _lifetime: PhantomData<&'a mut GstAudioDecoder>,
+}
+
+impl<'a> AudioDecoder<'a>{
+    /// Pushes `buffer` downstream as the result of decoding `frames` input
+    /// buffers, usually 1. The base class timestamps it from the input
+    /// timestamps and the number of samples, so gaps and jitter in the
+    /// input don't show in the output. None discards the frames, like
+    /// headers or a packet that failed to decode
+    pub fn finish_frame(&mut self, buffer: Option<Buffer>, frames: i32) -> GstFlowReturn{
+        unsafe{
+            let buffer = buffer.map(|buffer| ::Transfer::transfer(buffer)).unwrap_or(ptr::null_mut());
+            gst_audio_decoder_finish_frame(self.decoder, buffer, frames)
+        }
+    }
+
+    /// Allocates an output buffer of `size` bytes from the negotiated
+    /// allocator
+    pub fn allocate_output_buffer(&mut self, size: usize) -> Option<Buffer>{
+        unsafe{
+            Buffer::new(gst_audio_decoder_allocate_output_buffer(self.decoder, size as gsize))
+        }
+    }
+
+    /// Sets the raw audio caps of the output, like
+    /// "audio/x-raw,format=F32LE,rate=48000,channels=2,layout=interleaved".
+    /// They're negotiated with downstream by `negotiate` or the next
+    /// `finish_frame`
+    pub fn set_output_caps(&mut self, caps: &Caps) -> bool{
+        unsafe{
+            gst_audio_decoder_set_output_caps(self.decoder, caps.gst_caps() as *mut GstCaps) != 0
+        }
+    }
+
+    /// Negotiates the output caps with downstream, false if they're not
+    /// accepted
+    pub fn negotiate(&mut self) -> bool{
+        unsafe{
+            gst_audio_decoder_negotiate(self.decoder) != 0
+        }
+    }
+
+    /// Latency introduced by the decoder
+    pub fn set_latency(&mut self, min: GstClockTime, max: GstClockTime){
+        unsafe{
+            gst_audio_decoder_set_latency(self.decoder, min, max);
+        }
+    }
+
+    /// Whether the input caps have to be received before any data
+    pub fn set_needs_format(&mut self, needs_format: bool){
+        unsafe{
+            gst_audio_decoder_set_needs_format(self.decoder, needs_format as gboolean);
+        }
+    }
+
+    /// Whether `handle_frame` is called with None at EOS and before
+    /// seeking to output the audio still in the decoder, true by default
+    pub fn set_drainable(&mut self, drainable: bool){
+        unsafe{
+            gst_audio_decoder_set_drainable(self.decoder, drainable as gboolean);
+        }
+    }
+
+    /// Whether the decoder conceals lost packets itself, like opus does.
+    /// When set `handle_frame` is called with an empty buffer for each gap
+    /// in the input
+    pub fn set_plc_aware(&mut self, plc_aware: bool){
+        unsafe{
+            gst_audio_decoder_set_plc_aware(self.decoder, plc_aware as gboolean);
+        }
+    }
+
+    /// The decoder as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.decoder as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// An audio decoder written in rust, like a wrapper for the audiopus
+/// crate, registered with `register_audio_decoder`.
+///
+/// The input is expected to be packetized, one encoded frame per buffer,
+/// as produced by the parsers and demuxers. All the methods are called
+/// from the streaming thread or during state changes, one at a time.
+pub trait AudioDecoderImpl: Send + 'static{
+    /// Called when going to PAUSED, to open the decoder
+    fn start(&mut self, _decoder: &mut AudioDecoder) -> bool{
+        true
+    }
+
+    /// Called when going back to READY, to close the decoder
+    fn stop(&mut self, _decoder: &mut AudioDecoder) -> bool{
+        true
+    }
+
+    /// Called with the caps of the input, before the first buffer and when
+    /// they change. Decoders that know the output format from the caps
+    /// call `set_output_caps` here, the others once they've decoded the
+    /// headers
+    fn set_format(&mut self, decoder: &mut AudioDecoder, caps: &Caps) -> bool;
+
+    /// Decodes `buffer` and outputs the result with `finish_frame`. None
+    /// asks to output the audio still in the decoder, at EOS or before
+    /// flushing
+    fn handle_frame(&mut self, decoder: &mut AudioDecoder, buffer: Option<Buffer>) -> GstFlowReturn;
+
+    /// Called when seeking, to discard the state of the decoder. `hard`
+    /// flushes also discard the codec setup, like after a caps change
+    fn flush(&mut self, _decoder: &mut AudioDecoder, _hard: bool){
+    }
+}
+
+/// The GstAudioEncoder base class of an element registered with
+/// `register_audio_encoder`, passed to its implementation to output
+/// encoded packets and negotiate
+pub struct AudioEncoder<'a>{
+    encoder: *mut GstAudioEncoder,
+    _lifetime: PhantomData<&'a mut GstAudioEncoder>,
+}
+
+impl<'a> AudioEncoder<'a>{
+    /// Pushes the packet `buffer` downstream as the result of encoding
+    /// `samples` samples per channel. The base class derives its
+    /// timestamp, duration and offsets, the granule position for ogg,
+    /// from the sample counts and the lookahead. None consumes the samples
+    /// without output
+    pub fn finish_frame(&mut self, buffer: Option<Buffer>, samples: i32) -> GstFlowReturn{
+        unsafe{
+            let buffer = buffer.map(|buffer| ::Transfer::transfer(buffer)).unwrap_or(ptr::null_mut());
+            gst_audio_encoder_finish_frame(self.encoder, buffer, samples)
+        }
+    }
+
+    /// Allocates an output buffer of `size` bytes from the negotiated
+    /// allocator
+    pub fn allocate_output_buffer(&mut self, size: usize) -> Option<Buffer>{
+        unsafe{
+            Buffer::new(gst_audio_encoder_allocate_output_buffer(self.encoder, size as gsize))
+        }
+    }
+
+    /// Sets the caps of the encoded stream, like "audio/x-opus". They're
+    /// negotiated with downstream by `negotiate` or the next `finish_frame`
+    pub fn set_output_format(&mut self, caps: &Caps) -> bool{
+        unsafe{
+            gst_audio_encoder_set_output_format(self.encoder, caps.gst_caps() as *mut GstCaps) != 0
+        }
+    }
+
+    /// Negotiates the output caps with downstream, false if they're not
+    /// accepted
+    pub fn negotiate(&mut self) -> bool{
+        unsafe{
+            gst_audio_encoder_negotiate(self.encoder) != 0
+        }
+    }
+
+    /// Number of samples per channel passed to each `handle_frame`, like
+    /// 960 for 20ms opus frames at 48kHz. With min and max equal the base
+    /// class buffers the input into frames of exactly that size, 0 accepts
+    /// whatever arrives
+    pub fn set_frame_samples(&mut self, min: i32, max: i32){
+        unsafe{
+            gst_audio_encoder_set_frame_samples_min(self.encoder, min);
+            gst_audio_encoder_set_frame_samples_max(self.encoder, max);
+        }
+    }
+
+    /// Maximum number of frames passed to a single `handle_frame`, 0 for
+    /// as many as available
+    pub fn set_frame_max(&mut self, frames: i32){
+        unsafe{
+            gst_audio_encoder_set_frame_max(self.encoder, frames);
+        }
+    }
+
+    /// Samples per channel the encoder needs before it outputs anything,
+    /// like the pre-skip of opus, so the timestamps of the output account
+    /// for them
+    pub fn set_lookahead(&mut self, samples: i32){
+        unsafe{
+            gst_audio_encoder_set_lookahead(self.encoder, samples);
+        }
+    }
+
+    /// Latency introduced by the encoder
+    pub fn set_latency(&mut self, min: GstClockTime, max: GstClockTime){
+        unsafe{
+            gst_audio_encoder_set_latency(self.encoder, min, max);
+        }
+    }
+
+    /// Header packets sent before the first encoded packet, like the
+    /// OpusHead and OpusTags packets muxers expect
+    pub fn set_headers(&mut self, headers: Vec<Buffer>){
+        unsafe{
+            let mut list: *mut GList = ptr::null_mut();
+            for header in headers{
+                let header: *mut GstBuffer = ::Transfer::transfer(header);
+                list = g_list_append(list, header as gpointer);
+            }
+            gst_audio_encoder_set_headers(self.encoder, list);
+        }
+    }
+
+    /// The encoder as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.encoder as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// An audio encoder written in rust, like a wrapper for the audiopus
+/// crate, registered with `register_audio_encoder`. All the methods are
+/// called from the streaming thread or during state changes, one at a
+/// time.
+pub trait AudioEncoderImpl: Send + 'static{
+    /// Called when going to PAUSED, to open the encoder
+    fn start(&mut self, _encoder: &mut AudioEncoder) -> bool{
+        true
+    }
+
+    /// Called when going back to READY, to close the encoder
+    fn stop(&mut self, _encoder: &mut AudioEncoder) -> bool{
+        true
+    }
+
+    /// Called with the raw audio caps of the input, before the first
+    /// buffer and when they change. The encoder is configured here, calls
+    /// `set_output_format` and usually `set_frame_samples` and
+    /// `set_lookahead`
+    fn set_format(&mut self, encoder: &mut AudioEncoder, caps: &Caps) -> bool;
+
+    /// Encodes the samples in `buffer` and outputs the packets with
+    /// `finish_frame`. None asks to output the audio still in the encoder,
+    /// at EOS
+    fn handle_frame(&mut self, encoder: &mut AudioEncoder, buffer: Option<Buffer>) -> GstFlowReturn;
+
+    /// Called when seeking, to discard the state of the encoder
+    fn flush(&mut self, _encoder: &mut AudioEncoder){
+    }
+}
+
+type NewDecoder = dyn Fn() -> Box<dyn AudioDecoderImpl> + Send + Sync;
+type DecoderImpl = Mutex<Box<dyn AudioDecoderImpl>>;
+type NewEncoder = dyn Fn() -> Box<dyn AudioEncoderImpl> + Send + Sync;
+type EncoderImpl = Mutex<Box<dyn AudioEncoderImpl>>;
+
+/// Registers a GstAudioDecoder subclass as the element `name`, so the
+/// decoder implemented by the objects returned by `new` can be used in
+/// pipelines and autoplugged by decodebin.
+///
+/// `sink_caps` are the encoded formats it accepts and `src_caps` the raw
+/// formats it outputs. `new` is called for each instance of the element.
+pub fn register_audio_decoder<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                    sink_caps: &Caps, src_caps: &Caps, new: F) -> Result<()>
+    where T: AudioDecoderImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewDecoder> = Box::new(move || Box::new(new()) as Box<dyn AudioDecoderImpl>);
+    let class_data = ClassData::new(metadata, sink_caps, src_caps, new);
+    unsafe{
+        subclass::register_element(gst_audio_decoder_get_type(), "GstRsAudioDecoder", name, rank, class_data,
+                                   Some(decoder_class_init), Some(decoder_instance_init)).map(|_| ())
+    }
+}
+
+/// Registers a GstAudioEncoder subclass as the element `name`, so the
+/// encoder implemented by the objects returned by `new` can be used in
+/// pipelines and by encodebin.
+///
+/// `sink_caps` are the raw formats it accepts and `src_caps` the encoded
+/// formats it outputs. `new` is called for each instance of the element.
+pub fn register_audio_encoder<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                    sink_caps: &Caps, src_caps: &Caps, new: F) -> Result<()>
+    where T: AudioEncoderImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewEncoder> = Box::new(move || Box::new(new()) as Box<dyn AudioEncoderImpl>);
+    let class_data = ClassData::new(metadata, sink_caps, src_caps, new);
+    unsafe{
+        subclass::register_element(gst_audio_encoder_get_type(), "GstRsAudioEncoder", name, rank, class_data,
+                                   Some(encoder_class_init), Some(encoder_instance_init)).map(|_| ())
+    }
+}
+
+// buffers passed to handle_frame are owned by the base classes
+unsafe fn input_buffer(buffer: *mut GstBuffer) -> Option<Buffer>{
+    if buffer == ptr::null_mut(){
+        None
+    }else{
+        Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+    }
+}
+
+extern "C" fn decoder_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewDecoder>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstAudioDecoderClass);
+        klass.start = Some(decoder_start);
+        klass.stop = Some(decoder_stop);
+        klass.set_format = Some(decoder_set_format);
+        klass.handle_frame = Some(decoder_handle_frame);
+        klass.flush = Some(decoder_flush);
+    }
+}
+
+extern "C" fn decoder_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewDecoder>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: DecoderImpl = Mutex::new((class_data.new)());
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_decoder<R, F>(decoder: *mut GstAudioDecoder, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn AudioDecoderImpl, &mut AudioDecoder) -> R{
+    match subclass::instance_impl::<DecoderImpl>(decoder as gpointer){
+        Some(imp) => {
+            let mut handle = AudioDecoder{ decoder: decoder, _lifetime: PhantomData };
+            unwind::guard(decoder as *mut GstObject, default, || f(&mut **imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+extern "C" fn decoder_start(decoder: *mut GstAudioDecoder) -> gboolean{
+    unsafe{
+        with_decoder(decoder, false, |imp, decoder| imp.start(decoder)) as gboolean
+    }
+}
+
+extern "C" fn decoder_stop(decoder: *mut GstAudioDecoder) -> gboolean{
+    unsafe{
+        with_decoder(decoder, false, |imp, decoder| imp.stop(decoder)) as gboolean
+    }
+}
+
+extern "C" fn decoder_set_format(decoder: *mut GstAudioDecoder, caps: *mut GstCaps) -> gboolean{
+    unsafe{
+        let caps = match Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps){
+            Some(caps) => caps,
+            None => return 0
+        };
+        with_decoder(decoder, false, |imp, decoder| imp.set_format(decoder, &caps)) as gboolean
+    }
+}
+
+extern "C" fn decoder_handle_frame(decoder: *mut GstAudioDecoder, buffer: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        let buffer = input_buffer(buffer);
+        with_decoder(decoder, GST_FLOW_ERROR, |imp, decoder| imp.handle_frame(decoder, buffer))
+    }
+}
+
+extern "C" fn decoder_flush(decoder: *mut GstAudioDecoder, hard: gboolean){
+    unsafe{
+        with_decoder(decoder, (), |imp, decoder| imp.flush(decoder, hard != 0))
+    }
+}
+
+extern "C" fn encoder_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewEncoder>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstAudioEncoderClass);
+        klass.start = Some(encoder_start);
+        klass.stop = Some(encoder_stop);
+        klass.set_format = Some(encoder_set_format);
+        klass.handle_frame = Some(encoder_handle_frame);
+        klass.flush = Some(encoder_flush);
+    }
+}
+
+extern "C" fn encoder_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewEncoder>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: EncoderImpl = Mutex::new((class_data.new)());
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_encoder<R, F>(encoder: *mut GstAudioEncoder, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn AudioEncoderImpl, &mut AudioEncoder) -> R{
+    match subclass::instance_impl::<EncoderImpl>(encoder as gpointer){
+        Some(imp) => {
+            let mut handle = AudioEncoder{ encoder: encoder, _lifetime: PhantomData };
+            unwind::guard(encoder as *mut GstObject, default, || f(&mut **imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+extern "C" fn encoder_start(encoder: *mut GstAudioEncoder) -> gboolean{
+    unsafe{
+        with_encoder(encoder, false, |imp, encoder| imp.start(encoder)) as gboolean
+    }
+}
+
+extern "C" fn encoder_stop(encoder: *mut GstAudioEncoder) -> gboolean{
+    unsafe{
+        with_encoder(encoder, false, |imp, encoder| imp.stop(encoder)) as gboolean
+    }
+}
+
+extern "C" fn encoder_set_format(encoder: *mut GstAudioEncoder, info: *mut GstAudioInfo) -> gboolean{
+    unsafe{
+        let caps = match Caps::new_from_gst_caps(gst_audio_info_to_caps(info)){
+            Some(caps) => caps,
+            None => return 0
+        };
+        with_encoder(encoder, false, |imp, encoder| imp.set_format(encoder, &caps)) as gboolean
+    }
+}
+
+extern "C" fn encoder_handle_frame(encoder: *mut GstAudioEncoder, buffer: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        let buffer = input_buffer(buffer);
+        with_encoder(encoder, GST_FLOW_ERROR, |imp, encoder| imp.handle_frame(encoder, buffer))
+    }
+}
+
+extern "C" fn encoder_flush(encoder: *mut GstAudioEncoder){
+    unsafe{
+        with_encoder(encoder, (), |imp, encoder| imp.flush(encoder))
+    }
+}
@@ -0,0 +1,187 @@
+use ffi::*;
+use util::*;
+use ::Caps;
+
+pub type AudioInfo = GstAudioInfo;
+
+impl AudioInfo{
+    /// Info of raw audio caps, None for caps that aren't raw audio or
+    /// aren't fixed
+    pub fn from_caps(caps: &Caps) -> Option<AudioInfo>{
+        caps.audio_info()
+    }
+
+    /// Info of interleaved audio in `format`, like GST_AUDIO_FORMAT_S16,
+    /// with the default positions for `channels`
+    pub fn new(format: GstAudioFormat, rate: i32, channels: i32) -> AudioInfo{
+        unsafe{
+            let mut info = mem::zeroed();
+            gst_audio_info_init(&mut info);
+            gst_audio_info_set_format(&mut info, format, rate, channels, ptr::null());
+            info
+        }
+    }
+
+    #[inline]
+    pub fn format_info(&self) -> &GstAudioFormatInfo{
+        unsafe{ &(*self.finfo) }
+    }
+
+    #[inline]
+    pub fn format(&self) -> GstAudioFormat{
+        self.format_info().format
+    }
+
+    #[inline]
+    pub fn format_name(&self) -> String{
+        unsafe{ from_c_str!(self.format_info().name).to_string() }
+    }
+
+    #[inline]
+    pub fn is_integer(&self) -> bool{
+        self.format_info().flags & GST_AUDIO_FORMAT_FLAG_INTEGER == GST_AUDIO_FORMAT_FLAG_INTEGER
+    }
+
+    #[inline]
+    pub fn is_float(&self) -> bool{
+        self.format_info().flags & GST_AUDIO_FORMAT_FLAG_FLOAT == GST_AUDIO_FORMAT_FLAG_FLOAT
+    }
+
+    #[inline]
+    pub fn is_signed(&self) -> bool{
+        self.format_info().flags & GST_AUDIO_FORMAT_FLAG_SIGNED == GST_AUDIO_FORMAT_FLAG_SIGNED
+    }
+
+    /// Bits used to store each sample, 32 for S24_32
+    #[inline]
+    pub fn width(&self) -> i32{
+        self.format_info().width
+    }
+
+    /// Bits of each sample that carry the signal, 24 for S24_32
+    #[inline]
+    pub fn depth(&self) -> i32{
+        self.format_info().depth
+    }
+
+    #[inline]
+    pub fn flags(&self) -> GstAudioFlags{
+        self.flags
+    }
+
+    #[inline]
+    pub fn layout(&self) -> GstAudioLayout{
+        self.layout
+    }
+
+    #[inline]
+    pub fn is_interleaved(&self) -> bool{
+        self.layout == GST_AUDIO_LAYOUT_INTERLEAVED
+    }
+
+    #[inline]
+    pub fn rate(&self) -> i32{
+        self.rate
+    }
+
+    #[inline]
+    pub fn channels(&self) -> i32{
+        self.channels
+    }
+
+    /// Bytes per frame, the size of one sample of every channel
+    #[inline]
+    pub fn bpf(&self) -> i32{
+        self.bpf
+    }
+
+    #[inline]
+    pub fn channel_position(&self, channel: usize) -> GstAudioChannelPosition{
+        self.position[channel]
+    }
+
+    /// Number of frames in `bytes` of audio
+    #[inline]
+    pub fn frames(&self, bytes: usize) -> usize{
+        if self.bpf > 0 { bytes / self.bpf as usize } else { 0 }
+    }
+
+    /// Duration in nanoseconds of `frames` frames
+    #[inline]
+    pub fn frames_to_ns(&self, frames: u64) -> u64{
+        if self.rate > 0 { frames * 1000000000 / self.rate as u64 } else { 0 }
+    }
+
+    /// The samples of a buffer in this format as a slice of `T`, like the
+    /// data of a `BufferMap` from a sample pulled from an appsink. None if
+    /// `T` doesn't match the format or the data isn't aligned for `T`.
+    /// Interleaved audio has the samples of every channel for each frame
+    /// one after the other, non interleaved audio all the samples of the
+    /// first channel followed by those of the next one
+    pub fn samples<'a, T: AudioSample>(&self, data: &'a [u8]) -> Option<&'a [T]>{
+        if self.format() != T::FORMAT || data.as_ptr() as usize % mem::align_of::<T>() != 0{
+            return None;
+        }
+        unsafe{
+            Some(::std::slice::from_raw_parts(data.as_ptr() as *const T, data.len() / mem::size_of::<T>()))
+        }
+    }
+
+    /// Mutable version of `samples`
+    pub fn samples_mut<'a, T: AudioSample>(&self, data: &'a mut [u8]) -> Option<&'a mut [T]>{
+        if self.format() != T::FORMAT || data.as_ptr() as usize % mem::align_of::<T>() != 0{
+            return None;
+        }
+        unsafe{
+            Some(::std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut T, data.len() / mem::size_of::<T>()))
+        }
+    }
+
+    pub fn to_caps(&self) -> Option<::Caps>{
+        unsafe{ Caps::new_from_gst_caps(gst_audio_info_to_caps(self)) }
+    }
+}
+
+impl PartialEq for AudioInfo{
+    fn eq(&self, other: &AudioInfo) -> bool{
+        unsafe{
+            gst_audio_info_is_equal(self, other) != 0
+        }
+    }
+}
+
+impl Eq for AudioInfo{}
+
+/// Rust types that raw audio samples can be read as, each matching the
+/// native endian audio format of the same size and type
+pub trait AudioSample: Copy{
+    const FORMAT: GstAudioFormat;
+}
+
+impl AudioSample for i8{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_S8;
+}
+
+impl AudioSample for u8{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_U8;
+}
+
+impl AudioSample for i16{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_S16;
+}
+
+impl AudioSample for u16{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_U16;
+}
+
+impl AudioSample for i32{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_S32;
+}
+
+impl AudioSample for f32{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_F32;
+}
+
+impl AudioSample for f64{
+    const FORMAT: GstAudioFormat = GST_AUDIO_FORMAT_F64;
+}
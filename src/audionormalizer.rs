@@ -0,0 +1,154 @@
+use ffi::*;
+use bin::Bin;
+use caps::Caps;
+use element::Element;
+use error::{Error, Result};
+use reference::Reference;
+
+use std::ops::{Deref, DerefMut};
+
+/// Dithering applied by the conversion when reducing the sample depth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Dithering{
+    None = 0,
+    /// Rectangular dithering
+    Rpdf = 1,
+    /// Triangular dithering, the default
+    Tpdf = 2,
+    /// High frequency triangular dithering
+    TpdfHf = 3,
+}
+
+/// Noise shaping applied by the conversion when reducing the sample depth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum NoiseShaping{
+    None = 0,
+    ErrorFeedback = 1,
+    Simple = 2,
+    Medium = 3,
+    High = 4,
+}
+
+/// Converts any raw audio stream to a fixed sample format, rate and
+/// number of channels so the application, usually through an appsink,
+/// always receives the same layout no matter the source.
+///
+/// It's a bin containing audioconvert, audioresample and a capsfilter
+/// with the target format that can be linked as any other element.
+pub struct AudioNormalizer{
+    bin: Bin,
+    audioconvert: Element,
+    audioresample: Element,
+    capsfilter: Element,
+}
+
+unsafe impl Sync for AudioNormalizer {}
+unsafe impl Send for AudioNormalizer {}
+
+impl AudioNormalizer{
+    /// Creates a normalizer producing interleaved samples in `format`,
+    /// like "S16LE" or "F32LE", at the given rate and number of channels
+    pub fn new(name: &str, format: &str, rate: i32, channels: i32) -> Result<AudioNormalizer>{
+        let mut bin = Bin::new(name)?;
+        let mut audioconvert = Element::new("audioconvert", "")?;
+        let audioresample = Element::new("audioresample", "")?;
+        let mut capsfilter = Element::new("capsfilter", "")?;
+        if !bin.add_and_link_many(vec![audioconvert.reference(), audioresample.reference(), capsfilter.reference()]) ||
+           !bin.add_ghost_pad(&mut audioconvert, "sink", "sink") ||
+           !bin.add_ghost_pad(&mut capsfilter, "src", "src"){
+            return Err(Error::new(0,0,"Couldn't link audio normalizer"));
+        }
+        let mut normalizer = AudioNormalizer{
+            bin: bin,
+            audioconvert: audioconvert,
+            audioresample: audioresample,
+            capsfilter: capsfilter,
+        };
+        if normalizer.set_target(format, rate, channels){
+            Ok(normalizer)
+        }else{
+            Err(Error::new(0,0,"Invalid audio format"))
+        }
+    }
+
+    /// Changes the output format, renegotiating with downstream if the
+    /// normalizer is already running. Returns false if the format isn't
+    /// valid
+    pub fn set_target(&mut self, format: &str, rate: i32, channels: i32) -> bool{
+        let caps = format!("audio/x-raw,format={},layout=interleaved,rate={},channels={}", format, rate, channels);
+        match Caps::from_string(&caps){
+            Some(caps) => {
+                self.set_target_caps(&caps);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Sets the output format from raw audio caps, any field not present
+    /// will be negotiated with downstream
+    pub fn set_target_caps(&mut self, caps: &Caps){
+        self.capsfilter.set("caps", caps);
+    }
+
+    pub fn target_caps(&self) -> Caps{
+        self.capsfilter.get("caps")
+    }
+
+    /// Resampling quality from 0, the fastest, to 10, the best. The
+    /// default is 4
+    pub fn set_resampler_quality(&mut self, quality: i32){
+        self.audioresample.set("quality", quality);
+    }
+
+    pub fn resampler_quality(&self) -> i32{
+        self.audioresample.get("quality")
+    }
+
+    pub fn set_dithering(&mut self, dithering: Dithering){
+        self.audioconvert.set("dithering", dithering as i32);
+    }
+
+    pub fn set_noise_shaping(&mut self, noise_shaping: NoiseShaping){
+        self.audioconvert.set("noise-shaping", noise_shaping as i32);
+    }
+}
+
+impl AsRef<Bin> for AudioNormalizer{
+    fn as_ref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl AsMut<Bin> for AudioNormalizer{
+    fn as_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl From<AudioNormalizer> for Element{
+    fn from(normalizer: AudioNormalizer) -> Element{
+        normalizer.bin.into()
+    }
+}
+
+impl Deref for AudioNormalizer{
+    type Target = Bin;
+    fn deref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl DerefMut for AudioNormalizer{
+    fn deref_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl ::Transfer for AudioNormalizer{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.bin.transfer()
+    }
+}
@@ -0,0 +1,222 @@
+use ffi::*;
+use appsink::{self, AppSink};
+use element::Element;
+use error::Error;
+use caps::Caps;
+use buffer::Buffer;
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{RecvError, TryRecvError};
+
+/// A fixed size block of interleaved f32 audio frames
+pub struct AudioChunk{
+    samples: Vec<f32>,
+    channels: u32,
+    rate: u32,
+    timestamp: u64,
+}
+
+impl AudioChunk{
+    /// Interleaved samples of the chunk
+    pub fn samples(&self) -> &[f32]{
+        &self.samples
+    }
+
+    pub fn channels(&self) -> u32{
+        self.channels
+    }
+
+    pub fn rate(&self) -> u32{
+        self.rate
+    }
+
+    /// Number of frames (samples per channel) in the chunk
+    pub fn n_frames(&self) -> usize{
+        self.samples.len() / self.channels as usize
+    }
+
+    /// Presentation timestamp in nanoseconds of the first frame in the
+    /// chunk, as set on the buffers by upstream, not the running time.
+    /// Timestamps of consecutive chunks never go backwards.
+    pub fn timestamp(&self) -> u64{
+        self.timestamp
+    }
+
+    /// Duration of the chunk in nanoseconds
+    pub fn duration(&self) -> u64{
+        frames_to_ns(self.n_frames() as u64, self.rate)
+    }
+}
+
+// jitter in the timestamps of consecutive buffers that isn't a gap, in
+// nanoseconds
+const GAP_TOLERANCE: u64 = 1000000;
+
+fn frames_to_ns(frames: u64, rate: u32) -> u64{
+    frames * 1000000000 / rate as u64
+}
+
+/// Low latency access to the audio flowing through a pipeline, meant
+/// for meters and DSP monitoring.
+///
+/// Wraps an appsink that doesn't sync to the clock and only keeps the
+/// latest buffer, dropping the older ones when the application doesn't
+/// keep up, and re-chunks whatever buffer sizes the upstream elements
+/// produce into blocks of exactly `chunk_frames` frames of interleaved
+/// f32 samples, so analysis code can work on fixed sizes. The frames
+/// pending before a dropped buffer are discarded so chunks never span a
+/// gap.
+pub struct LowLatencyAudioTap{
+    appsink: AppSink,
+    rate: u32,
+    channels: u32,
+    chunk_frames: usize,
+    pending: VecDeque<f32>,
+    next_timestamp: Option<u64>,
+    last_timestamp: u64,
+}
+
+impl LowLatencyAudioTap{
+    /// Creates a new tap named `name` that will deliver chunks of
+    /// `chunk_frames` frames at the given rate and number of channels.
+    /// Fails if `rate` or `channels` is 0
+    pub fn new(name: &str, rate: u32, channels: u32, chunk_frames: usize) -> Result<LowLatencyAudioTap, Error>{
+        Element::new("appsink", name)
+            .and_then(|appsink| LowLatencyAudioTap::new_from_element(appsink, rate, channels, chunk_frames))
+    }
+
+    /// Configures an already existing appsink, usually retrieved from a
+    /// pipeline with `Bin::get_by_name`, as a low latency tap. Upstream
+    /// should have an audioconvert ! audioresample so the requested
+    /// format can be negotiated. Fails if `rate` or `channels` is 0
+    pub fn new_from_element(element: Element, rate: u32, channels: u32, chunk_frames: usize) -> Result<LowLatencyAudioTap, Error>{
+        if rate == 0 || channels == 0{
+            return Err(Error::new(0, 0, "The rate and channels of an audio tap can't be 0"));
+        }
+        let mut appsink = AppSink::new_from_element(element);
+        // samples are read as native f32
+        let format = if cfg!(target_endian = "little") { "F32LE" } else { "F32BE" };
+        let caps = format!("audio/x-raw,format={},layout=interleaved,rate={},channels={}", format, rate, channels);
+        appsink.set_caps(Caps::from_string(&caps).unwrap());
+        appsink.set_max_buffers(1);
+        appsink.set_drop(true);
+        appsink.set("sync", 0 as gboolean);
+        Ok(LowLatencyAudioTap{
+            appsink: appsink,
+            rate: rate,
+            channels: channels,
+            chunk_frames: chunk_frames,
+            pending: VecDeque::new(),
+            next_timestamp: None,
+            last_timestamp: 0,
+        })
+    }
+
+    pub fn rate(&self) -> u32{
+        self.rate
+    }
+
+    pub fn channels(&self) -> u32{
+        self.channels
+    }
+
+    pub fn chunk_frames(&self) -> usize{
+        self.chunk_frames
+    }
+
+    /// Blocks until a full chunk is available. Returns an error once the
+    /// stream reached EOS or the pipeline was destroyed.
+    pub fn recv(&mut self) -> Result<AudioChunk, RecvError>{
+        loop{
+            if let Some(chunk) = self.pop_chunk(){
+                return Ok(chunk);
+            }
+            match self.appsink.recv()?{
+                appsink::Message::NewSample(sample) => {
+                    if let Some(buffer) = sample.buffer(){
+                        self.push_buffer(&buffer);
+                    }
+                }
+                appsink::Message::NewPreroll(_) => (),
+                appsink::Message::Eos => return Err(RecvError),
+            }
+        }
+    }
+
+    /// Returns a chunk if there's enough data available without blocking
+    pub fn try_recv(&mut self) -> Result<AudioChunk, TryRecvError>{
+        loop{
+            if let Some(chunk) = self.pop_chunk(){
+                return Ok(chunk);
+            }
+            match self.appsink.try_recv()?{
+                appsink::Message::NewSample(sample) => {
+                    if let Some(buffer) = sample.buffer(){
+                        self.push_buffer(&buffer);
+                    }
+                }
+                appsink::Message::NewPreroll(_) => (),
+                appsink::Message::Eos => return Err(TryRecvError::Disconnected),
+            }
+        }
+    }
+
+    fn push_buffer(&mut self, buffer: &Buffer){
+        let pts = unsafe{ (*buffer.gst_buffer()).pts };
+        let pending_duration = frames_to_ns((self.pending.len() / self.channels as usize) as u64, self.rate);
+        // buffers dropped by the appsink leave a gap after the pending
+        // frames, which are discarded
+        let gap = match self.next_timestamp{
+            Some(next) if pts != GST_CLOCK_TIME_NONE => pts > next + pending_duration + GAP_TOLERANCE,
+            _ => false
+        };
+        if gap{
+            self.pending.clear();
+        }
+        if pts != GST_CLOCK_TIME_NONE && (self.next_timestamp.is_none() || buffer.is_discont() || gap){
+            // timestamp of the first pending frame, never before the end
+            // of the last delivered chunk so timestamps stay monotonic
+            let pending_duration = frames_to_ns((self.pending.len() / self.channels as usize) as u64, self.rate);
+            let start = if pts > pending_duration { pts - pending_duration } else { 0 };
+            self.next_timestamp = Some(if start > self.last_timestamp { start } else { self.last_timestamp });
+        }
+        if let Some(mapping) = buffer.map_read(){
+            self.pending.extend(mapping.data::<f32>());
+        }
+    }
+
+    fn pop_chunk(&mut self) -> Option<AudioChunk>{
+        let chunk_len = self.chunk_frames * self.channels as usize;
+        if chunk_len == 0 || self.pending.len() < chunk_len{
+            return None;
+        }
+        let timestamp = self.next_timestamp.unwrap_or(self.last_timestamp);
+        let next = timestamp + frames_to_ns(self.chunk_frames as u64, self.rate);
+        self.next_timestamp = Some(next);
+        self.last_timestamp = next;
+        Some(AudioChunk{
+            samples: self.pending.drain(..chunk_len).collect(),
+            channels: self.channels,
+            rate: self.rate,
+            timestamp: timestamp,
+        })
+    }
+}
+
+impl AsRef<AppSink> for LowLatencyAudioTap{
+    fn as_ref(&self) -> &AppSink{
+        &self.appsink
+    }
+}
+
+impl AsMut<AppSink> for LowLatencyAudioTap{
+    fn as_mut(&mut self) -> &mut AppSink{
+        &mut self.appsink
+    }
+}
+
+impl From<LowLatencyAudioTap> for Element{
+    fn from(tap: LowLatencyAudioTap) -> Element{
+        tap.appsink.into()
+    }
+}
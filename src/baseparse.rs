@@ -0,0 +1,344 @@
+use ffi::*;
+use buffer::Buffer;
+use caps::Caps;
+use element::Element;
+use error::Result;
+use subclass::{self, ClassData, ElementMetadata};
+use unwind;
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::Mutex;
+
+/// The GstBaseParse base class of a parser registered with
+/// `register_base_parse`, passed to its implementation to configure it
+pub struct BaseParse<'a>{
+    parse: *mut GstBaseParse,
+    _lifetime: PhantomData<&'a mut GstBaseParse>,
+}
+
+impl<'a> BaseParse<'a>{
+    /// Sets the caps of the parsed stream on the src pad, usually once
+    /// the headers of the stream have been parsed in `handle_frame`, with
+    /// fields like the resolution or "parsed=true" that the input lacks
+    pub fn set_src_caps(&mut self, caps: &Caps) -> bool{
+        unsafe{
+            let event = gst_event_new_caps(caps.gst_caps() as *mut GstCaps);
+            gst_pad_push_event((*self.parse).srcpad, event) != 0
+        }
+    }
+
+    /// Least number of bytes `handle_frame` needs to find a frame, like the
+    /// size of the header of a packet. Less data isn't passed to it, other
+    /// than when draining at EOS
+    pub fn set_min_frame_size(&mut self, min_size: usize){
+        unsafe{
+            gst_base_parse_set_min_frame_size(self.parse, min_size as guint);
+        }
+    }
+
+    /// Whether the stream has sync points the parser can find from any
+    /// position, true by default. Formats that can only be parsed from the
+    /// start disable it so seeking is done in time by upstream
+    pub fn set_syncable(&mut self, syncable: bool){
+        unsafe{
+            gst_base_parse_set_syncable(self.parse, syncable as gboolean);
+        }
+    }
+
+    /// Whether the frames carry their own timestamps, set by the
+    /// implementation on the output buffers, instead of being timestamped
+    /// from the frame rate or the bitrate
+    pub fn set_has_timing_info(&mut self, has_timing: bool){
+        unsafe{
+            gst_base_parse_set_has_timing_info(self.parse, has_timing as gboolean);
+        }
+    }
+
+    /// Pushes the input downstream without calling `handle_frame`, when
+    /// the input is already parsed
+    pub fn set_passthrough(&mut self, passthrough: bool){
+        unsafe{
+            gst_base_parse_set_passthrough(self.parse, passthrough as gboolean);
+        }
+    }
+
+    /// Whether frames without a pts get one interpolated from the previous
+    /// frames. Disabled for streams with B-frames, whose pts don't grow
+    pub fn set_pts_interpolation(&mut self, pts_interpolate: bool){
+        unsafe{
+            gst_base_parse_set_pts_interpolation(self.parse, pts_interpolate as gboolean);
+        }
+    }
+
+    /// Whether frames without a timestamp get the one of the last frame
+    /// that had it, true by default
+    pub fn set_infer_ts(&mut self, infer_ts: bool){
+        unsafe{
+            gst_base_parse_set_infer_ts(self.parse, infer_ts as gboolean);
+        }
+    }
+
+    /// Frame rate of the stream, to timestamp the frames and seek in
+    /// formats without timestamps. `lead_in` and `lead_out` are the frames
+    /// needed before and after a position to decode it
+    pub fn set_frame_rate(&mut self, fps_num: u32, fps_den: u32, lead_in: u32, lead_out: u32){
+        unsafe{
+            gst_base_parse_set_frame_rate(self.parse, fps_num, fps_den, lead_in, lead_out);
+        }
+    }
+
+    /// Duration of the stream in `format`, when known from the headers.
+    /// `interval` is the number of frames after which an estimated
+    /// duration is updated, 0 when it's exact
+    pub fn set_duration(&mut self, format: GstFormat, duration: i64, interval: i32){
+        unsafe{
+            gst_base_parse_set_duration(self.parse, format, duration, interval);
+        }
+    }
+
+    /// Average bitrate of the stream in bits per second, to estimate the
+    /// duration and convert between bytes and time
+    pub fn set_average_bitrate(&mut self, bitrate: u32){
+        unsafe{
+            gst_base_parse_set_average_bitrate(self.parse, bitrate);
+        }
+    }
+
+    /// Latency introduced by the parser
+    pub fn set_latency(&mut self, min: GstClockTime, max: GstClockTime){
+        unsafe{
+            gst_base_parse_set_latency(self.parse, min, max);
+        }
+    }
+
+    /// Whether the parser lost the sync, at the start of the stream or
+    /// after a discontinuity, so the data may not start with a frame and
+    /// `handle_frame` should look for the next sync point
+    pub fn is_lost_sync(&self) -> bool{
+        unsafe{
+            (*self.parse).flags & GST_BASE_PARSE_FLAG_LOST_SYNC != 0
+        }
+    }
+
+    /// Whether the stream ended and `handle_frame` is called with the
+    /// remaining data, which may be a truncated frame
+    pub fn is_draining(&self) -> bool{
+        unsafe{
+            (*self.parse).flags & GST_BASE_PARSE_FLAG_DRAINING != 0
+        }
+    }
+
+    /// The parser as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.parse as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// The data passed to `BaseParseImpl::handle_frame`, starting at the
+/// current position of the stream
+pub struct ParseFrame<'a>{
+    frame: *mut GstBaseParseFrame,
+    // owned by the base class
+    buffer: mem::ManuallyDrop<Buffer>,
+    _lifetime: PhantomData<&'a mut GstBaseParseFrame>,
+}
+
+impl<'a> ParseFrame<'a>{
+    /// The data available from the current position, which may contain
+    /// several frames or only part of one
+    pub fn buffer(&self) -> &Buffer{
+        &self.buffer
+    }
+
+    /// Position of the data in the input in bytes
+    pub fn offset(&self) -> u64{
+        unsafe{
+            (*self.frame).offset
+        }
+    }
+
+    /// Whether this is the first call for this data, false if
+    /// `handle_frame` asked for more data last time
+    pub fn is_new_frame(&self) -> bool{
+        unsafe{
+            (*self.frame).flags & GST_BASE_PARSE_FRAME_FLAG_NEW_FRAME != 0
+        }
+    }
+
+    /// Drops the frame instead of pushing it, like the padding of a
+    /// container
+    pub fn set_drop(&mut self, drop: bool){
+        unsafe{
+            if drop{
+                (*self.frame).flags |= GST_BASE_PARSE_FRAME_FLAG_DROP;
+            }else{
+                (*self.frame).flags &= !GST_BASE_PARSE_FRAME_FLAG_DROP;
+            }
+        }
+    }
+
+    /// Pushes `buffer` instead of the bytes of the frame, like a frame
+    /// converted to another format. Its timestamps are set by the base
+    /// class like for the frame
+    pub fn set_output_buffer(&mut self, buffer: Buffer){
+        unsafe{
+            if (*self.frame).out_buffer != ptr::null_mut(){
+                gst_mini_object_unref((*self.frame).out_buffer as *mut GstMiniObject);
+            }
+            (*self.frame).out_buffer = ::Transfer::transfer(buffer);
+        }
+    }
+}
+
+/// What `BaseParseImpl::handle_frame` found at the start of the data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameDetection{
+    /// A frame of that many bytes starts at the current position, it's
+    /// pushed downstream and the position moves after it
+    Frame(usize),
+    /// The data doesn't start with a frame, the position moves that many
+    /// bytes to look for the next sync point
+    Skip(usize),
+    /// The frame at the current position isn't complete, `handle_frame` is
+    /// called again when more data arrives
+    NeedData,
+    /// Stops the stream with this flow return, like GST_FLOW_ERROR for
+    /// a stream that can't be parsed
+    Error(GstFlowReturn),
+}
+
+/// A parser written in rust, like one splitting a proprietary telemetry
+/// format or a niche codec into frames, registered with
+/// `register_base_parse`.
+///
+/// The base class accumulates the input and passes it to `handle_frame`,
+/// which finds the frame at the start of the data. It takes care of the
+/// timestamps, the duration and seeking. All the methods are called from
+/// the streaming thread or during state changes, one at a time.
+pub trait BaseParseImpl: Send + 'static{
+    /// Called when going to PAUSED. Parsers usually set their minimum
+    /// frame size here
+    fn start(&mut self, _parse: &mut BaseParse) -> bool{
+        true
+    }
+
+    /// Called when going back to READY
+    fn stop(&mut self, _parse: &mut BaseParse) -> bool{
+        true
+    }
+
+    /// Called with the caps of the input, before the first buffer and when
+    /// they change
+    fn set_sink_caps(&mut self, _parse: &mut BaseParse, _caps: &Caps) -> bool{
+        true
+    }
+
+    /// Finds the frame at the start of the data of `frame`. When the sync
+    /// is lost the data may start anywhere in the stream, and has to be
+    /// skipped up to the next sync point. The caps of the output are set
+    /// with `set_src_caps` before the first frame
+    fn handle_frame(&mut self, parse: &mut BaseParse, frame: &mut ParseFrame) -> FrameDetection;
+}
+
+type NewParse = dyn Fn() -> Box<dyn BaseParseImpl> + Send + Sync;
+type ParseImpl = Mutex<Box<dyn BaseParseImpl>>;
+
+/// Registers a GstBaseParse subclass as the element `name`, so the
+/// parser implemented by the objects returned by `new` can be used in
+/// pipelines and autoplugged by decodebin.
+///
+/// `sink_caps` are the formats it accepts and `src_caps` the parsed
+/// formats it outputs. `new` is called for each instance of the element.
+pub fn register_base_parse<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                 sink_caps: &Caps, src_caps: &Caps, new: F) -> Result<()>
+    where T: BaseParseImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewParse> = Box::new(move || Box::new(new()) as Box<dyn BaseParseImpl>);
+    let class_data = ClassData::new(metadata, sink_caps, src_caps, new);
+    unsafe{
+        subclass::register_element(gst_base_parse_get_type(), "GstRsBaseParse", name, rank, class_data,
+                                   Some(parse_class_init), Some(parse_instance_init)).map(|_| ())
+    }
+}
+
+extern "C" fn parse_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewParse>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstBaseParseClass);
+        klass.start = Some(parse_start);
+        klass.stop = Some(parse_stop);
+        klass.set_sink_caps = Some(parse_set_sink_caps);
+        klass.handle_frame = Some(parse_handle_frame);
+    }
+}
+
+extern "C" fn parse_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewParse>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: ParseImpl = Mutex::new((class_data.new)());
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_parse<R, F>(parse: *mut GstBaseParse, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn BaseParseImpl, &mut BaseParse) -> R{
+    match subclass::instance_impl::<ParseImpl>(parse as gpointer){
+        Some(imp) => {
+            let mut handle = BaseParse{ parse: parse, _lifetime: PhantomData };
+            // a panic in a previous call poisons the lock, but the element goes on
+            unwind::guard(parse as *mut GstObject, default, || f(&mut **imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+extern "C" fn parse_start(parse: *mut GstBaseParse) -> gboolean{
+    unsafe{
+        with_parse(parse, false, |imp, parse| imp.start(parse)) as gboolean
+    }
+}
+
+extern "C" fn parse_stop(parse: *mut GstBaseParse) -> gboolean{
+    unsafe{
+        with_parse(parse, false, |imp, parse| imp.stop(parse)) as gboolean
+    }
+}
+
+extern "C" fn parse_set_sink_caps(parse: *mut GstBaseParse, caps: *mut GstCaps) -> gboolean{
+    unsafe{
+        let caps = match Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps){
+            Some(caps) => caps,
+            None => return 0
+        };
+        with_parse(parse, false, |imp, parse| imp.set_sink_caps(parse, &caps)) as gboolean
+    }
+}
+
+extern "C" fn parse_handle_frame(parse: *mut GstBaseParse, frame: *mut GstBaseParseFrame, skipsize: *mut gint) -> GstFlowReturn{
+    unsafe{
+        let buffer = match Buffer::new((*frame).buffer){
+            Some(buffer) => mem::ManuallyDrop::new(buffer),
+            None => return GST_FLOW_ERROR
+        };
+        let mut rust_frame = ParseFrame{ frame: frame, buffer: buffer, _lifetime: PhantomData };
+        let available = rust_frame.buffer().size() as usize;
+        match with_parse(parse, FrameDetection::Error(GST_FLOW_ERROR), |imp, parse| imp.handle_frame(parse, &mut rust_frame)){
+            FrameDetection::Frame(size) if size > 0 && size <= available =>
+                gst_base_parse_finish_frame(parse, frame, size as gint),
+            FrameDetection::Frame(_) => GST_FLOW_ERROR,
+            FrameDetection::Skip(size) => {
+                *skipsize = size.min(available) as gint;
+                GST_FLOW_OK
+            }
+            FrameDetection::NeedData => GST_FLOW_OK,
+            FrameDetection::Error(ret) => ret
+        }
+    }
+}
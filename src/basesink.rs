@@ -0,0 +1,265 @@
+use ffi::*;
+use buffer::Buffer;
+use cancellable::CancellationToken;
+use caps::Caps;
+use element::Element;
+use error::Result;
+use subclass::{self, ClassData, ElementMetadata, PadTemplate};
+use unwind;
+
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+/// The GstBaseSink base class of a sink registered with
+/// `register_base_sink`, passed to its implementation to configure it
+pub struct BaseSink<'a>{
+    sink: *mut GstBaseSink,
+    unlock: CancellationToken,
+    _lifetime: PhantomData<&'a mut GstBaseSink>,
+}
+
+impl<'a> BaseSink<'a>{
+    /// Whether buffers are rendered when their timestamp is reached on
+    /// the pipeline clock, true by default. Sinks that consume the data
+    /// as fast as possible, like an encoder feeding a file, disable it
+    pub fn set_sync(&mut self, sync: bool){
+        unsafe{
+            gst_base_sink_set_sync(self.sink, sync as gboolean);
+        }
+    }
+
+    pub fn sync(&self) -> bool{
+        unsafe{
+            gst_base_sink_get_sync(self.sink) != 0
+        }
+    }
+
+    /// Buffers later than `max_lateness` nanoseconds are dropped instead
+    /// of rendered, -1 to render them all
+    pub fn set_max_lateness(&mut self, max_lateness: i64){
+        unsafe{
+            gst_base_sink_set_max_lateness(self.sink, max_lateness);
+        }
+    }
+
+    /// Whether QoS events are sent upstream so the elements before the
+    /// sink can drop late data
+    pub fn set_qos_enabled(&mut self, enabled: bool){
+        unsafe{
+            gst_base_sink_set_qos_enabled(self.sink, enabled as gboolean);
+        }
+    }
+
+    /// Whether the sink prerolls, so the pipeline only reaches PAUSED once
+    /// the first buffer arrived, true by default
+    pub fn set_async_enabled(&mut self, enabled: bool){
+        unsafe{
+            gst_base_sink_set_async_enabled(self.sink, enabled as gboolean);
+        }
+    }
+
+    /// Nanoseconds added to the timestamps of the buffers before waiting
+    /// for them on the clock, to synchronize with other sinks
+    pub fn set_ts_offset(&mut self, offset: GstClockTimeDiff){
+        unsafe{
+            gst_base_sink_set_ts_offset(self.sink, offset);
+        }
+    }
+
+    /// Time the sink takes to show a buffer once it's rendered, like the
+    /// frame a game engine takes to present a texture, so buffers are
+    /// rendered that much earlier
+    pub fn set_render_delay(&mut self, delay: GstClockTime){
+        unsafe{
+            gst_base_sink_set_render_delay(self.sink, delay);
+        }
+    }
+
+    /// Latency of the pipeline upstream of the sink, as configured after
+    /// the latency query
+    pub fn latency(&self) -> GstClockTime{
+        unsafe{
+            gst_base_sink_get_latency(self.sink)
+        }
+    }
+
+    /// Cancelled when the sink has to stop waiting, when flushing for a
+    /// seek or going back to READY. `render` blocking on something that can
+    /// take long, like waiting for a free texture, has to wait on it with
+    /// cancellable timeouts and return GST_FLOW_FLUSHING once cancelled.
+    /// A new token is used once the sink resumes
+    pub fn cancellation_token(&self) -> CancellationToken{
+        self.unlock.clone()
+    }
+
+    /// The sink as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.sink as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// A sink written in rust, like one pushing frames into the textures of
+/// a game engine, registered with `register_base_sink`.
+///
+/// The base class takes care of the events, the synchronization on the
+/// clock, prerolling and QoS, and calls `render` with each buffer when
+/// it's time to show it. All the methods are called from the streaming
+/// thread or during state changes, one at a time.
+pub trait BaseSinkImpl: Send + 'static{
+    /// Called when going to PAUSED, to open the device or connection
+    fn start(&mut self, _sink: &mut BaseSink) -> bool{
+        true
+    }
+
+    /// Called when going back to READY, to close the device or connection
+    fn stop(&mut self, _sink: &mut BaseSink) -> bool{
+        true
+    }
+
+    /// Called with the caps negotiated with upstream before the first
+    /// buffer and when they change
+    fn set_caps(&mut self, _sink: &mut BaseSink, _caps: &Caps) -> bool{
+        true
+    }
+
+    /// Called with the first buffer in PAUSED, before it's rendered when
+    /// going to PLAYING, and after flushing seeks. Video sinks usually show
+    /// it so the frame at the seek position is visible while paused
+    fn preroll(&mut self, _sink: &mut BaseSink, _buffer: &Buffer) -> GstFlowReturn{
+        GST_FLOW_OK
+    }
+
+    /// Renders `buffer`, when its timestamp is reached if syncing
+    fn render(&mut self, sink: &mut BaseSink, buffer: &Buffer) -> GstFlowReturn;
+}
+
+type NewSink = dyn Fn() -> Box<dyn BaseSinkImpl> + Send + Sync;
+
+struct SinkInstance{
+    imp: Mutex<Box<dyn BaseSinkImpl>>,
+    // cancelled by unlock, called from other threads while render runs
+    unlock: Mutex<CancellationToken>,
+}
+
+/// Registers a GstBaseSink subclass as the element `name`, so the sink
+/// implemented by the objects returned by `new` can be used in pipelines
+/// and by parse_launch.
+///
+/// `sink_caps` are the formats it accepts. `new` is called for each
+/// instance of the element.
+pub fn register_base_sink<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                sink_caps: &Caps, new: F) -> Result<()>
+    where T: BaseSinkImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewSink> = Box::new(move || Box::new(new()) as Box<dyn BaseSinkImpl>);
+    let class_data = ClassData::new_with_templates(metadata, &[PadTemplate::sink(sink_caps.clone())], new);
+    unsafe{
+        subclass::register_element(gst_base_sink_get_type(), "GstRsBaseSink", name, rank, class_data,
+                                   Some(sink_class_init), Some(sink_instance_init)).map(|_| ())
+    }
+}
+
+extern "C" fn sink_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewSink>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstBaseSinkClass);
+        klass.start = Some(sink_start);
+        klass.stop = Some(sink_stop);
+        klass.set_caps = Some(sink_set_caps);
+        klass.preroll = Some(sink_preroll);
+        klass.render = Some(sink_render);
+        klass.unlock = Some(sink_unlock);
+        klass.unlock_stop = Some(sink_unlock_stop);
+    }
+}
+
+extern "C" fn sink_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewSink>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp = SinkInstance{
+                    imp: Mutex::new((class_data.new)()),
+                    unlock: Mutex::new(CancellationToken::new()),
+                };
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_sink<R, F>(sink: *mut GstBaseSink, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn BaseSinkImpl, &mut BaseSink) -> R{
+    match subclass::instance_impl::<SinkInstance>(sink as gpointer){
+        Some(instance) => {
+            let unlock = instance.unlock.lock().unwrap().clone();
+            let mut handle = BaseSink{ sink: sink, unlock: unlock, _lifetime: PhantomData };
+            unwind::guard(sink as *mut GstObject, default, || f(&mut **instance.imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+// buffers passed to preroll and render are owned by the base class
+unsafe fn input_buffer(buffer: *mut GstBuffer) -> Option<Buffer>{
+    Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+}
+
+extern "C" fn sink_start(sink: *mut GstBaseSink) -> gboolean{
+    unsafe{
+        with_sink(sink, false, |imp, sink| imp.start(sink)) as gboolean
+    }
+}
+
+extern "C" fn sink_stop(sink: *mut GstBaseSink) -> gboolean{
+    unsafe{
+        with_sink(sink, false, |imp, sink| imp.stop(sink)) as gboolean
+    }
+}
+
+extern "C" fn sink_set_caps(sink: *mut GstBaseSink, caps: *mut GstCaps) -> gboolean{
+    unsafe{
+        let caps = match Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps){
+            Some(caps) => caps,
+            None => return 0
+        };
+        with_sink(sink, false, |imp, sink| imp.set_caps(sink, &caps)) as gboolean
+    }
+}
+
+extern "C" fn sink_preroll(sink: *mut GstBaseSink, buffer: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        match input_buffer(buffer){
+            Some(buffer) => with_sink(sink, GST_FLOW_ERROR, |imp, sink| imp.preroll(sink, &buffer)),
+            None => GST_FLOW_ERROR
+        }
+    }
+}
+
+extern "C" fn sink_render(sink: *mut GstBaseSink, buffer: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        match input_buffer(buffer){
+            Some(buffer) => with_sink(sink, GST_FLOW_ERROR, |imp, sink| imp.render(sink, &buffer)),
+            None => GST_FLOW_ERROR
+        }
+    }
+}
+
+extern "C" fn sink_unlock(sink: *mut GstBaseSink) -> gboolean{
+    unsafe{
+        if let Some(instance) = subclass::instance_impl::<SinkInstance>(sink as gpointer){
+            instance.unlock.lock().unwrap().cancel();
+        }
+        1
+    }
+}
+
+extern "C" fn sink_unlock_stop(sink: *mut GstBaseSink) -> gboolean{
+    unsafe{
+        if let Some(instance) = subclass::instance_impl::<SinkInstance>(sink as gpointer){
+            *instance.unlock.lock().unwrap() = CancellationToken::new();
+        }
+        1
+    }
+}
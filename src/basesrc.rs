@@ -0,0 +1,394 @@
+use ffi::*;
+use buffer::Buffer;
+use cancellable::CancellationToken;
+use caps::Caps;
+use element::Element;
+use error::Result;
+use subclass::{self, ClassData, ElementMetadata, PadTemplate};
+use unwind;
+
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::Mutex;
+
+/// The GstBaseSrc base class of a source registered with
+/// `register_push_src`, passed to its implementation to configure it and
+/// allocate buffers
+pub struct BaseSrc<'a>{
+    src: *mut GstBaseSrc,
+    unlock: CancellationToken,
+    _lifetime: PhantomData<&'a mut GstBaseSrc>,
+}
+
+impl<'a> BaseSrc<'a>{
+    /// Live sources, like a camera, a screen grabber or a network feed,
+    /// only produce data in PLAYING and don't preroll. Usually set in the
+    /// constructor or `start`
+    pub fn set_live(&mut self, live: bool){
+        unsafe{
+            gst_base_src_set_live(self.src, live as gboolean);
+        }
+    }
+
+    pub fn is_live(&self) -> bool{
+        unsafe{
+            gst_base_src_is_live(self.src) != 0
+        }
+    }
+
+    /// Format of the segment and of the seeks, GST_FORMAT_BYTES by default.
+    /// Sources that output timestamped buffers use GST_FORMAT_TIME
+    pub fn set_format(&mut self, format: GstFormat){
+        unsafe{
+            gst_base_src_set_format(self.src, format);
+        }
+    }
+
+    /// Timestamps the buffers with the running time of the pipeline clock
+    /// when they're created, for live sources that don't timestamp them
+    /// themselves
+    pub fn set_do_timestamp(&mut self, do_timestamp: bool){
+        unsafe{
+            gst_base_src_set_do_timestamp(self.src, do_timestamp as gboolean);
+        }
+    }
+
+    /// Size in bytes of the buffers allocated by `alloc_buffer`, 4096 by
+    /// default
+    pub fn set_blocksize(&mut self, blocksize: u32){
+        unsafe{
+            gst_base_src_set_blocksize(self.src, blocksize as guint);
+        }
+    }
+
+    pub fn blocksize(&self) -> u32{
+        unsafe{
+            gst_base_src_get_blocksize(self.src) as u32
+        }
+    }
+
+    /// Whether the size returned by `size` can grow while running, like a
+    /// file being written, so it's queried again before ending the stream
+    pub fn set_dynamic_size(&mut self, dynamic: bool){
+        unsafe{
+            gst_base_src_set_dynamic_size(self.src, dynamic as gboolean);
+        }
+    }
+
+    /// Negotiates `caps` with downstream and sends them before the next
+    /// buffer, to change the format while running, usually from `create`.
+    ///
+    /// Calls the `set_caps` of the implementation before returning, from
+    /// the same thread, so the lock of its state mustn't be held meanwhile
+    pub fn set_caps(&mut self, caps: &Caps) -> bool{
+        unsafe{
+            gst_base_src_set_caps(self.src, caps.gst_caps() as *mut GstCaps) != 0
+        }
+    }
+
+    /// For live sources, waits until the pipeline goes to PLAYING. Returns
+    /// GST_FLOW_FLUSHING if the source is stopped meanwhile, which `create`
+    /// should return as is
+    pub fn wait_playing(&mut self) -> GstFlowReturn{
+        unsafe{
+            gst_base_src_wait_playing(self.src)
+        }
+    }
+
+    /// Allocates a buffer of `blocksize` bytes from the pool negotiated
+    /// with downstream
+    pub fn alloc_buffer(&mut self) -> ::std::result::Result<Buffer, GstFlowReturn>{
+        unsafe{
+            let class = (*(self.src as *mut GTypeInstance)).g_class as *const GstBaseSrcClass;
+            let alloc = match (*class).alloc{
+                Some(alloc) => alloc,
+                None => return Err(GST_FLOW_NOT_SUPPORTED)
+            };
+            let mut buffer = ptr::null_mut();
+            let ret = alloc(self.src, u64::max_value(), gst_base_src_get_blocksize(self.src), &mut buffer);
+            if ret != GST_FLOW_OK{
+                return Err(ret);
+            }
+            Buffer::new(buffer).ok_or(GST_FLOW_ERROR)
+        }
+    }
+
+    /// Cancelled when the source has to stop waiting for data, when
+    /// flushing for a seek or going back to READY. `create` blocking on
+    /// something that can take long, like a socket, has to wait on it with
+    /// cancellable timeouts and return GST_FLOW_FLUSHING once cancelled.
+    /// A new token is used once the source resumes
+    pub fn cancellation_token(&self) -> CancellationToken{
+        self.unlock.clone()
+    }
+
+    /// The source as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.src as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// A source written in rust, like a screen grabber or a network feed,
+/// registered with `register_push_src`.
+///
+/// The base class runs a streaming thread that calls `create` for every
+/// buffer, and takes care of the segment, EOS, QoS and, for live sources,
+/// of waiting for PLAYING. Implementations either create their buffers
+/// in `create` or only `fill` the buffers allocated by the base class.
+///
+/// `create` runs in the streaming thread while the caps, seeking and size
+/// queries are answered from other threads, and `set_caps` is called from
+/// inside `BaseSrc::set_caps`, so the methods take `&self`. The state of
+/// the source goes behind a Mutex, which must not be held while blocking
+/// on data, so queries aren't delayed, nor while calling
+/// `BaseSrc::set_caps`.
+pub trait PushSrcImpl: Send + Sync + 'static{
+    /// Called when going to PAUSED, to open the device or connection
+    fn start(&self, _src: &mut BaseSrc) -> bool{
+        true
+    }
+
+    /// Called when going back to READY, to close the device or connection
+    fn stop(&self, _src: &mut BaseSrc) -> bool{
+        true
+    }
+
+    /// The caps the source can produce, intersected with `filter` if
+    /// any. None for the caps of the template
+    fn caps(&self, _src: &mut BaseSrc, _filter: Option<&Caps>) -> Option<Caps>{
+        None
+    }
+
+    /// Picks values for the fields of `caps` downstream left open, like
+    /// the resolution of a screen grabber. The fields still not fixed are
+    /// set to their nearest value to the defaults afterwards
+    fn fixate(&self, _src: &mut BaseSrc, caps: Caps) -> Caps{
+        caps
+    }
+
+    /// Called with the caps negotiated with downstream before the first
+    /// buffer and when they change
+    fn set_caps(&self, _src: &mut BaseSrc, _caps: &Caps) -> bool{
+        true
+    }
+
+    /// Whether `do_seek` is supported
+    fn is_seekable(&self, _src: &mut BaseSrc) -> bool{
+        false
+    }
+
+    /// Total size of the stream in the format of the source, bytes by
+    /// default, if known
+    fn size(&self, _src: &mut BaseSrc) -> Option<u64>{
+        None
+    }
+
+    /// Moves to the start of `segment`, already updated with the seek
+    fn do_seek(&self, _src: &mut BaseSrc, _segment: &mut GstSegment) -> bool{
+        true
+    }
+
+    /// Produces the next buffer. GST_FLOW_EOS ends the stream. By default
+    /// allocates a buffer with `alloc_buffer` and passes it to `fill`
+    fn create(&self, src: &mut BaseSrc) -> ::std::result::Result<Buffer, GstFlowReturn>{
+        let mut buffer = src.alloc_buffer()?;
+        match self.fill(src, &mut buffer){
+            GST_FLOW_OK => Ok(buffer),
+            ret => Err(ret)
+        }
+    }
+
+    /// Writes the next data into `buffer`, which can be resized to the
+    /// data actually read. Only called if `create` isn't implemented
+    fn fill(&self, _src: &mut BaseSrc, _buffer: &mut Buffer) -> GstFlowReturn{
+        GST_FLOW_NOT_SUPPORTED
+    }
+}
+
+type NewPushSrc = dyn Fn() -> Box<dyn PushSrcImpl> + Send + Sync;
+
+struct PushSrcInstance{
+    imp: Box<dyn PushSrcImpl>,
+    // cancelled by unlock, called from other threads while create runs
+    unlock: Mutex<CancellationToken>,
+}
+
+/// Registers a GstPushSrc subclass as the element `name`, so the source
+/// implemented by the objects returned by `new` can be used in pipelines
+/// and by parse_launch.
+///
+/// `src_caps` are the formats it can produce. `new` is called for each
+/// instance of the element.
+pub fn register_push_src<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                               src_caps: &Caps, new: F) -> Result<()>
+    where T: PushSrcImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewPushSrc> = Box::new(move || Box::new(new()) as Box<dyn PushSrcImpl>);
+    let class_data = ClassData::new_with_templates(metadata, &[PadTemplate::src(src_caps.clone())], new);
+    unsafe{
+        subclass::register_element(gst_push_src_get_type(), "GstRsPushSrc", name, rank, class_data,
+                                   Some(push_src_class_init), Some(push_src_instance_init)).map(|_| ())
+    }
+}
+
+extern "C" fn push_src_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewPushSrc>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstBaseSrcClass);
+        klass.start = Some(src_start);
+        klass.stop = Some(src_stop);
+        klass.get_caps = Some(src_get_caps);
+        klass.fixate = Some(src_fixate);
+        klass.set_caps = Some(src_set_caps);
+        klass.is_seekable = Some(src_is_seekable);
+        klass.get_size = Some(src_get_size);
+        klass.do_seek = Some(src_do_seek);
+        klass.unlock = Some(src_unlock);
+        klass.unlock_stop = Some(src_unlock_stop);
+        let klass = &mut *(g_class as *mut GstPushSrcClass);
+        klass.create = Some(push_src_create);
+    }
+}
+
+extern "C" fn push_src_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewPushSrc>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp = PushSrcInstance{
+                    imp: (class_data.new)(),
+                    unlock: Mutex::new(CancellationToken::new()),
+                };
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_src<R, F>(src: *mut GstBaseSrc, default: R, f: F) -> R
+    where F: FnOnce(&dyn PushSrcImpl, &mut BaseSrc) -> R{
+    match subclass::instance_impl::<PushSrcInstance>(src as gpointer){
+        Some(instance) => {
+            let unlock = instance.unlock.lock().unwrap().clone();
+            let mut handle = BaseSrc{ src: src, unlock: unlock, _lifetime: PhantomData };
+            unwind::guard(src as *mut GstObject, default, || f(&*instance.imp, &mut handle))
+        }
+        None => default
+    }
+}
+
+unsafe fn parent_class(src: *mut GstBaseSrc) -> *const GstBaseSrcClass{
+    let class = (*(src as *mut GTypeInstance)).g_class;
+    g_type_class_peek_parent(class as gpointer) as *const GstBaseSrcClass
+}
+
+extern "C" fn src_start(src: *mut GstBaseSrc) -> gboolean{
+    unsafe{
+        with_src(src, false, |imp, src| imp.start(src)) as gboolean
+    }
+}
+
+extern "C" fn src_stop(src: *mut GstBaseSrc) -> gboolean{
+    unsafe{
+        with_src(src, false, |imp, src| imp.stop(src)) as gboolean
+    }
+}
+
+extern "C" fn src_get_caps(src: *mut GstBaseSrc, filter: *mut GstCaps) -> *mut GstCaps{
+    unsafe{
+        let rust_filter = if filter == ptr::null_mut(){
+            None
+        }else{
+            Caps::new_from_gst_caps(gst_mini_object_ref(filter as *mut GstMiniObject) as *mut GstCaps)
+        };
+        match with_src(src, None, |imp, src| imp.caps(src, rust_filter.as_ref())){
+            Some(caps) => ::Transfer::transfer(caps),
+            None => match (*parent_class(src)).get_caps{
+                Some(get_caps) => get_caps(src, filter),
+                None => ptr::null_mut()
+            }
+        }
+    }
+}
+
+extern "C" fn src_fixate(src: *mut GstBaseSrc, caps: *mut GstCaps) -> *mut GstCaps{
+    unsafe{
+        let caps = match Caps::new_from_gst_caps(caps){
+            Some(caps) => caps,
+            None => return gst_caps_new_empty()
+        };
+        let caps = match with_src(src, None, |imp, src| Some(imp.fixate(src, caps))){
+            Some(caps) => ::Transfer::transfer(caps),
+            None => return gst_caps_new_empty()
+        };
+        match (*parent_class(src)).fixate{
+            Some(fixate) => fixate(src, caps),
+            None => caps
+        }
+    }
+}
+
+extern "C" fn src_set_caps(src: *mut GstBaseSrc, caps: *mut GstCaps) -> gboolean{
+    unsafe{
+        let caps = match Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps){
+            Some(caps) => caps,
+            None => return 0
+        };
+        with_src(src, false, |imp, src| imp.set_caps(src, &caps)) as gboolean
+    }
+}
+
+extern "C" fn src_is_seekable(src: *mut GstBaseSrc) -> gboolean{
+    unsafe{
+        with_src(src, false, |imp, src| imp.is_seekable(src)) as gboolean
+    }
+}
+
+extern "C" fn src_get_size(src: *mut GstBaseSrc, size: *mut guint64) -> gboolean{
+    unsafe{
+        match with_src(src, None, |imp, src| imp.size(src)){
+            Some(total) => {
+                *size = total;
+                1
+            }
+            None => 0
+        }
+    }
+}
+
+extern "C" fn src_do_seek(src: *mut GstBaseSrc, segment: *mut GstSegment) -> gboolean{
+    unsafe{
+        with_src(src, false, |imp, src| imp.do_seek(src, &mut *segment)) as gboolean
+    }
+}
+
+extern "C" fn src_unlock(src: *mut GstBaseSrc) -> gboolean{
+    unsafe{
+        if let Some(instance) = subclass::instance_impl::<PushSrcInstance>(src as gpointer){
+            instance.unlock.lock().unwrap().cancel();
+        }
+        1
+    }
+}
+
+extern "C" fn src_unlock_stop(src: *mut GstBaseSrc) -> gboolean{
+    unsafe{
+        if let Some(instance) = subclass::instance_impl::<PushSrcInstance>(src as gpointer){
+            *instance.unlock.lock().unwrap() = CancellationToken::new();
+        }
+        1
+    }
+}
+
+extern "C" fn push_src_create(src: *mut GstPushSrc, buf: *mut *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        match with_src(src as *mut GstBaseSrc, Err(GST_FLOW_ERROR), |imp, src| imp.create(src)){
+            Ok(buffer) => {
+                *buf = ::Transfer::transfer(buffer);
+                GST_FLOW_OK
+            }
+            Err(ret) => ret
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use ffi::*;
+use buffer::{Buffer, BufferRefMut};
+use caps::Caps;
+use element::Element;
+use error::Result;
+use subclass::{self, ClassData, ElementMetadata};
+use unwind;
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::Mutex;
+
+/// How the buffers of a transform registered with
+/// `register_base_transform` are processed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransformMode{
+    /// The buffers are modified in place by `transform_ip`, like a filter
+    /// drawing over video frames
+    InPlace,
+    /// Each input buffer is written into a new output buffer by
+    /// `transform`, like a scaler or a format converter
+    Copy,
+}
+
+/// The GstBaseTransform base class of a filter registered with
+/// `register_base_transform`, passed to its implementation to configure
+/// it
+pub struct BaseTransform<'a>{
+    trans: *mut GstBaseTransform,
+    _lifetime: PhantomData<&'a mut GstBaseTransform>,
+}
+
+impl<'a> BaseTransform<'a>{
+    /// Pushes the buffers downstream untouched without calling `transform`,
+    /// usually decided in `set_caps`, like a scaler with the same input and
+    /// output size. With TransformMode::InPlace `transform_ip_passthrough`
+    /// is called with the buffers instead of `transform_ip`
+    pub fn set_passthrough(&mut self, passthrough: bool){
+        unsafe{
+            gst_base_transform_set_passthrough(self.trans, passthrough as gboolean);
+        }
+    }
+
+    pub fn is_passthrough(&self) -> bool{
+        unsafe{
+            gst_base_transform_is_passthrough(self.trans) != 0
+        }
+    }
+
+    /// Whether late buffers are dropped following the QoS events of the
+    /// sinks, disabled by default
+    pub fn set_qos_enabled(&mut self, enabled: bool){
+        unsafe{
+            gst_base_transform_set_qos_enabled(self.trans, enabled as gboolean);
+        }
+    }
+
+    /// Whether gap buffers are passed to the implementation instead of
+    /// being pushed as is
+    pub fn set_gap_aware(&mut self, gap_aware: bool){
+        unsafe{
+            gst_base_transform_set_gap_aware(self.trans, gap_aware as gboolean);
+        }
+    }
+
+    /// Whether the caps negotiation prefers caps that allow passthrough,
+    /// true by default
+    pub fn set_prefer_passthrough(&mut self, prefer_passthrough: bool){
+        unsafe{
+            gst_base_transform_set_prefer_passthrough(self.trans, prefer_passthrough as gboolean);
+        }
+    }
+
+    /// Renegotiates the output caps before the next buffer, after a
+    /// change of the settings that changes `transform_caps`
+    pub fn reconfigure_src(&mut self){
+        unsafe{
+            gst_base_transform_reconfigure_src(self.trans);
+        }
+    }
+
+    /// The transform as an element, to post messages or read its
+    /// properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.trans as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// A filter written in rust, like an image processing element,
+/// registered with `register_base_transform`, with one sink and one src
+/// pad.
+///
+/// Depending on the `TransformMode` the buffers are passed to
+/// `transform_ip` or `transform`, the other one is never called. The base
+/// class takes care of the events, the allocation and the QoS. All the
+/// methods except `transform_caps` are called from the streaming thread or
+/// during state changes, one at a time.
+pub trait BaseTransformImpl: Send + 'static{
+    /// Called when going to PAUSED
+    fn start(&mut self, _trans: &mut BaseTransform) -> bool{
+        true
+    }
+
+    /// Called when going back to READY
+    fn stop(&mut self, _trans: &mut BaseTransform) -> bool{
+        true
+    }
+
+    /// The caps the pad of the other side of `direction` can have for
+    /// `caps` on this side, like any resolution on the src pad for a
+    /// scaler, intersected with `filter` if any. None for the same caps
+    fn transform_caps(&mut self, _trans: &mut BaseTransform, _direction: GstPadDirection, _caps: &Caps,
+                      _filter: Option<&Caps>) -> Option<Caps>{
+        None
+    }
+
+    /// Called with the negotiated input and output caps before the first
+    /// buffer and when they change. Transforms that don't need to modify
+    /// the buffers for some caps call `set_passthrough` here
+    fn set_caps(&mut self, _trans: &mut BaseTransform, _incaps: &Caps, _outcaps: &Caps) -> bool{
+        true
+    }
+
+    /// Size of the buffer on the pad of the other side of `direction` for
+    /// a buffer of `size` bytes with `caps` on this side. None for the same
+    /// size
+    fn transform_size(&mut self, _trans: &mut BaseTransform, _direction: GstPadDirection, _caps: &Caps,
+                      _size: usize, _othercaps: &Caps) -> Option<usize>{
+        None
+    }
+
+    /// Writes the result of processing `inbuf` into `outbuf`, allocated
+    /// with the size returned by `transform_size`. Used with
+    /// TransformMode::Copy
+    fn transform(&mut self, _trans: &mut BaseTransform, _inbuf: &Buffer, _outbuf: &mut BufferRefMut) -> GstFlowReturn{
+        GST_FLOW_NOT_SUPPORTED
+    }
+
+    /// Processes `buffer` in place. Used with TransformMode::InPlace
+    fn transform_ip(&mut self, _trans: &mut BaseTransform, _buffer: &mut BufferRefMut) -> GstFlowReturn{
+        GST_FLOW_NOT_SUPPORTED
+    }
+
+    /// Called instead of `transform_ip` in passthrough, with the buffer
+    /// being pushed, which can't be modified, like an analyzer that only
+    /// reads the frames
+    fn transform_ip_passthrough(&mut self, _trans: &mut BaseTransform, _buffer: &Buffer) -> GstFlowReturn{
+        GST_FLOW_OK
+    }
+}
+
+type NewTransform = dyn Fn() -> Box<dyn BaseTransformImpl> + Send + Sync;
+type TransformImpl = Mutex<Box<dyn BaseTransformImpl>>;
+
+struct TransformClassData{
+    mode: TransformMode,
+    new: Box<NewTransform>,
+}
+
+/// Registers a GstBaseTransform subclass as the element `name`, so the
+/// filter implemented by the objects returned by `new` can be used in
+/// pipelines and by parse_launch.
+///
+/// `sink_caps` are the formats it accepts and `src_caps` the formats it
+/// outputs. `new` is called for each instance of the element.
+pub fn register_base_transform<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                     sink_caps: &Caps, src_caps: &Caps, mode: TransformMode, new: F) -> Result<()>
+    where T: BaseTransformImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewTransform> = Box::new(move || Box::new(new()) as Box<dyn BaseTransformImpl>);
+    let class_data = ClassData::new(metadata, sink_caps, src_caps, Box::new(TransformClassData{
+        mode: mode,
+        new: new,
+    }));
+    unsafe{
+        subclass::register_element(gst_base_transform_get_type(), "GstRsBaseTransform", name, rank, class_data,
+                                   Some(transform_class_init), Some(transform_instance_init)).map(|_| ())
+    }
+}
+
+extern "C" fn transform_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<TransformClassData>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstBaseTransformClass);
+        klass.start = Some(transform_start);
+        klass.stop = Some(transform_stop);
+        klass.transform_caps = Some(transform_transform_caps);
+        klass.set_caps = Some(transform_set_caps);
+        klass.transform_size = Some(transform_transform_size);
+        // the base class decides the mode from the functions it's given
+        match class_data.new.mode{
+            TransformMode::InPlace => klass.transform_ip = Some(transform_transform_ip),
+            TransformMode::Copy => klass.transform = Some(transform_transform),
+        }
+    }
+}
+
+extern "C" fn transform_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<TransformClassData>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: TransformImpl = Mutex::new((class_data.new.new)());
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_transform<R, F>(trans: *mut GstBaseTransform, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn BaseTransformImpl, &mut BaseTransform) -> R{
+    match subclass::instance_impl::<TransformImpl>(trans as gpointer){
+        Some(imp) => {
+            let mut handle = BaseTransform{ trans: trans, _lifetime: PhantomData };
+            unwind::guard(trans as *mut GstObject, default, || f(&mut **imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+unsafe fn parent_class(trans: *mut GstBaseTransform) -> *const GstBaseTransformClass{
+    let class = (*(trans as *mut GTypeInstance)).g_class;
+    g_type_class_peek_parent(class as gpointer) as *const GstBaseTransformClass
+}
+
+unsafe fn borrowed_caps(caps: *mut GstCaps) -> Option<Caps>{
+    if caps == ptr::null_mut(){
+        None
+    }else{
+        Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps)
+    }
+}
+
+extern "C" fn transform_start(trans: *mut GstBaseTransform) -> gboolean{
+    unsafe{
+        with_transform(trans, false, |imp, trans| imp.start(trans)) as gboolean
+    }
+}
+
+extern "C" fn transform_stop(trans: *mut GstBaseTransform) -> gboolean{
+    unsafe{
+        with_transform(trans, false, |imp, trans| imp.stop(trans)) as gboolean
+    }
+}
+
+extern "C" fn transform_transform_caps(trans: *mut GstBaseTransform, direction: GstPadDirection,
+                                       caps: *mut GstCaps, filter: *mut GstCaps) -> *mut GstCaps{
+    unsafe{
+        let rust_caps = match borrowed_caps(caps){
+            Some(caps) => caps,
+            None => return gst_caps_new_empty()
+        };
+        let rust_filter = borrowed_caps(filter);
+        match with_transform(trans, None, |imp, trans| imp.transform_caps(trans, direction, &rust_caps, rust_filter.as_ref())){
+            Some(caps) => ::Transfer::transfer(caps),
+            None => match (*parent_class(trans)).transform_caps{
+                Some(transform_caps) => transform_caps(trans, direction, caps, filter),
+                None => gst_caps_new_empty()
+            }
+        }
+    }
+}
+
+extern "C" fn transform_set_caps(trans: *mut GstBaseTransform, incaps: *mut GstCaps, outcaps: *mut GstCaps) -> gboolean{
+    unsafe{
+        match (borrowed_caps(incaps), borrowed_caps(outcaps)){
+            (Some(incaps), Some(outcaps)) =>
+                with_transform(trans, false, |imp, trans| imp.set_caps(trans, &incaps, &outcaps)) as gboolean,
+            _ => 0
+        }
+    }
+}
+
+extern "C" fn transform_transform_size(trans: *mut GstBaseTransform, direction: GstPadDirection, caps: *mut GstCaps,
+                                       size: gsize, othercaps: *mut GstCaps, othersize: *mut gsize) -> gboolean{
+    unsafe{
+        let size_for_caps = match (borrowed_caps(caps), borrowed_caps(othercaps)){
+            (Some(rust_caps), Some(rust_othercaps)) => with_transform(trans, None, |imp, trans|
+                imp.transform_size(trans, direction, &rust_caps, size as usize, &rust_othercaps)),
+            _ => return 0
+        };
+        match size_for_caps{
+            Some(size) => {
+                *othersize = size as gsize;
+                1
+            }
+            None => match (*parent_class(trans)).transform_size{
+                Some(transform_size) => transform_size(trans, direction, caps, size, othercaps, othersize),
+                None => {
+                    *othersize = size;
+                    1
+                }
+            }
+        }
+    }
+}
+
+extern "C" fn transform_transform(trans: *mut GstBaseTransform, inbuf: *mut GstBuffer, outbuf: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        let inbuf = match Buffer::new(gst_mini_object_ref(inbuf as *mut GstMiniObject) as *mut GstBuffer){
+            Some(inbuf) => inbuf,
+            None => return GST_FLOW_ERROR
+        };
+        // the output buffer is owned by the base class, which allocates it
+        // writable, and can't be replaced
+        let mut outbuf = match BufferRefMut::new(outbuf){
+            Some(outbuf) => outbuf,
+            None => return GST_FLOW_ERROR
+        };
+        with_transform(trans, GST_FLOW_ERROR, |imp, trans| imp.transform(trans, &inbuf, &mut outbuf))
+    }
+}
+
+extern "C" fn transform_transform_ip(trans: *mut GstBaseTransform, buffer: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        if gst_base_transform_is_passthrough(trans) != 0{
+            // the buffer is owned by the base class and might be shared, it
+            // can only be read
+            let buffer = match Buffer::new(buffer){
+                Some(buffer) => mem::ManuallyDrop::new(buffer),
+                None => return GST_FLOW_ERROR
+            };
+            with_transform(trans, GST_FLOW_ERROR, |imp, trans| imp.transform_ip_passthrough(trans, &buffer))
+        }else{
+            // made writable by the base class before calling transform_ip
+            let mut buffer = match BufferRefMut::new(buffer){
+                Some(buffer) => buffer,
+                None => return GST_FLOW_ERROR
+            };
+            with_transform(trans, GST_FLOW_ERROR, |imp, trans| imp.transform_ip(trans, &mut buffer))
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use ffi::*;
 use element::Element;
+use pad::Pad;
+use ghostpad::GhostPad;
+use error::{Error, Result};
 use util::*;
 use iterator::Iter;
 use ::Transfer;
 use reference::Reference;
+use parse;
 
 use std::ops::{Deref, DerefMut};
 
@@ -87,7 +91,7 @@ unsafe impl Send for Bin {}
 
 impl Bin{
     /// Creates a new bin with the given name.
-    pub fn new(name: &str) -> Option<Bin>{
+    pub fn new(name: &str) -> Result<Bin>{
         unsafe{
             let cname = CString::new(name).unwrap();
             let name = if name != "" {
@@ -98,9 +102,9 @@ impl Bin{
             let bin = gst_bin_new(name);
             if bin != ptr::null_mut(){
 	            gst_object_ref_sink(mem::transmute(bin));
-	            Bin::new_from_gst_bin(bin as *mut GstBin)
+	            Ok(Bin::new_from_gst_bin(bin as *mut GstBin).unwrap())
 	        }else{
-	            None
+	            Err(Error::ElementCreation("bin".to_string()))
 	        }
         }
     }
@@ -114,6 +118,22 @@ impl Bin{
         }
     }
 
+    /// Creates a bin with the elements described by `description` in the
+    /// gst-launch-1.0 syntax, like "videoconvert ! videobalance saturation=0",
+    /// to insert user configurable filter chains in a pipeline.
+    ///
+    /// With `ghost_unlinked_pads` the unlinked pads at both ends of the
+    /// chain are exposed as ghost pads of the bin, so it can be linked like
+    /// any other element. Fails with an `Error::Parse` if the description
+    /// is wrong or uses elements that aren't installed, see
+    /// `parse_bin_from_description_full` for more control.
+    pub fn from_description(description: &str, ghost_unlinked_pads: bool) -> Result<Bin>{
+        let element = parse::parse_bin_from_description_full(description, ghost_unlinked_pads, None, GST_PARSE_FLAG_FATAL_ERRORS)?;
+        unsafe{
+            Ok(Bin::new_from_gst_bin(element.transfer() as *mut GstBin).unwrap())
+        }
+    }
+
 
     /// Adds the given element to the bin. Sets the element's parent,
     /// and thus adds a reference.
@@ -194,7 +214,8 @@ impl Bin{
         }
     }
 
-    // Gets an iterator for the elements in this bin.
+    // Gets an iterator for the elements in this bin and, recursively,
+    // in the bins it contains.
     pub fn iter_recurse(&self) -> Iter<Element>{
         unsafe{
             let bin = self.bin.gst_element() as *mut GstBin;
@@ -202,6 +223,66 @@ impl Bin{
         }
     }
 
+    /// Iterator over the elements of this bin in topological order, from
+    /// the sinks to the sources
+    pub fn iter_sorted(&self) -> Iter<Element>{
+        unsafe{
+            let bin = self.bin.gst_element() as *mut GstBin;
+            Iter::new_from_gst_iterator(gst_bin_iterate_sorted(bin)).unwrap()
+        }
+    }
+
+    /// Iterator over the sink elements of this bin, not recursive: the
+    /// sinks inside a child bin, like the playsink of a playbin, are only
+    /// reached through that bin, see `sinks_recurse`
+    pub fn iter_sinks(&self) -> Iter<Element>{
+        unsafe{
+            let bin = self.bin.gst_element() as *mut GstBin;
+            Iter::new_from_gst_iterator(gst_bin_iterate_sinks(bin)).unwrap()
+        }
+    }
+
+    /// Iterator over the source elements of this bin, not recursive
+    pub fn iter_sources(&self) -> Iter<Element>{
+        unsafe{
+            let bin = self.bin.gst_element() as *mut GstBin;
+            Iter::new_from_gst_iterator(gst_bin_iterate_sources(bin)).unwrap()
+        }
+    }
+
+    /// Iterator over the elements of this bin and its children bins that
+    /// implement the interface `iface`, like `gst_video_overlay_get_type()`
+    pub fn iter_all_by_interface(&self, iface: GType) -> Iter<Element>{
+        unsafe{
+            let bin = self.bin.gst_element() as *mut GstBin;
+            Iter::new_from_gst_iterator(gst_bin_iterate_all_by_interface(bin, iface)).unwrap()
+        }
+    }
+
+    /// The first element of this bin or its children bins that implements
+    /// the interface `iface`, like the video sink implementing GstVideoOverlay
+    /// inside a playbin, to embed it in a window.
+    ///
+    /// Returns None if no element implements it.
+    pub fn get_by_interface(&self, iface: GType) -> Option<Element>{
+        unsafe{
+            Element::new_from_gst_element(gst_bin_get_by_interface(self.gst_bin() as *mut GstBin, iface))
+        }
+    }
+
+    /// The sinks at any depth of this bin that aren't bins themselves,
+    /// like the actual audio and video sinks a playbin ends up using, to
+    /// read their stats or set their properties. playbin only creates them
+    /// once it goes to PAUSED
+    pub fn sinks_recurse(&self) -> Vec<Element>{
+        self.iter_recurse()
+            .filter_map(|element| element.ok())
+            .filter(|element| element.is_flag_set(GST_ELEMENT_FLAG_SINK) && unsafe{
+                g_type_check_instance_is_a(element.gst_element() as *mut GTypeInstance, gst_bin_get_type()) == 0
+            })
+            .collect()
+    }
+
     /// Query bin for the current latency using and reconfigures this latency
     /// to all the elements with a LATENCY event.
 	///
@@ -235,6 +316,41 @@ impl Bin{
         self.set("message-forward", forward);
     }
 
+    /// Adds a pad to the bin, usually a `GhostPad` exposing the pad of
+    /// one of its children. Fails if the bin already has a pad with the
+    /// same name or the pad belongs to another element
+    pub fn add_pad<P: Into<Pad>>(&mut self, pad: P) -> bool{
+        let mut pad = pad.into();
+        unsafe{
+            gst_element_add_pad(self.gst_bin_mut() as *mut GstElement, pad.gst_pad_mut()) == 1
+        }
+    }
+
+    /// Exposes the pad named `pad_name` of `element`, which has to be
+    /// a child of this bin, as a ghost pad of the bin named `name`, so
+    /// the bin can be linked as a single element.
+    pub fn add_ghost_pad(&mut self, element: &mut Element, pad_name: &str, name: &str) -> bool{
+        let ghost = element.static_pad(pad_name)
+            .and_then(|mut pad| GhostPad::new(name, &mut pad));
+        match ghost{
+            Some(ghost) => self.add_pad(ghost),
+            None => false
+        }
+    }
+
+    /// Links the pads `src` adds dynamically, like the streams of a
+    /// decodebin or a demuxer, to compatible sink pads of `sink`, both
+    /// elements being children of this bin. Each new pad is linked to the
+    /// first free pad of `sink` whose caps intersect with its own,
+    /// requesting a new pad from a template if needed.
+    ///
+    /// Pads that can't be linked are reported with a warning message on
+    /// the bus, posted by `src`.
+    pub fn autoconnect(&mut self, src: &mut Element, sink: &mut Element){
+        let mut sink = sink.reference();
+        src.connect_pad_added(move |src, pad| autoconnect_pad(src, pad, &mut sink));
+    }
+
     /// Returns a const raw pointer to the internal GstElement
     pub unsafe fn gst_bin(&self) -> *const GstBin{
         self.bin.gst_element() as *const GstBin
@@ -246,6 +362,31 @@ impl Bin{
     }
 }
 
+fn autoconnect_pad(src: &mut Element, pad: &mut Pad, sink: &mut Element){
+    unsafe{
+        let pad = pad.gst_pad_mut();
+        let caps = gst_pad_get_current_caps(pad);
+        let sink_pad = gst_element_get_compatible_pad(sink.gst_element_mut(), pad, caps);
+        if caps != ptr::null_mut(){
+            gst_mini_object_unref(caps as *mut GstMiniObject);
+        }
+        let linked = sink_pad != ptr::null_mut() && gst_pad_link(pad, sink_pad) == GST_PAD_LINK_OK;
+        if sink_pad != ptr::null_mut(){
+            gst_object_unref(sink_pad as gpointer);
+        }
+        if !linked{
+            let pad_name = gst_object_get_name(pad as *mut GstObject);
+            let msg = format!("Couldn't link pad {} to {}", from_c_str!(pad_name), sink.name());
+            g_free(pad_name as gpointer);
+            let cmsg = CString::new(msg).unwrap();
+            let error = g_error_new_literal(gst_stream_error_quark(), GST_STREAM_ERROR_FORMAT as gint, cmsg.as_ptr());
+            let message = gst_message_new_warning(src.gst_element_mut() as *mut GstObject, error, ptr::null());
+            g_error_free(error);
+            gst_element_post_message(src.gst_element_mut(), message);
+        }
+    }
+}
+
 impl AsRef<Element> for Bin{
     fn as_ref(&self) -> &Element{
         &self.bin
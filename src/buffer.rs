@@ -1,10 +1,15 @@
 use ffi::*;
 use reference::Reference;
 use miniobject::MiniObject;
+use videometa::{VideoRegionOfInterestMeta, VideoOverlayCompositionMeta, VideoCropMeta};
+use videooverlaycomposition::VideoOverlayComposition;
 
 use std::mem;
+use std::ptr;
+use std::ffi::CString;
 use std::fmt::{Debug, Formatter, Error};
 use std::ops::{Deref, DerefMut};
+use std::marker::PhantomData;
 
 #[derive(Clone)]
 pub struct Buffer{
@@ -40,30 +45,126 @@ impl Buffer{
             .map(|miniobject| Buffer{ buffer: miniobject })
     }
 
-    pub fn map_read<'a,F:FnMut(&::MapInfo)->U,U>(&'a self, mut f: F ) -> Result<U,()>{
+    /// Creates a buffer holding `data` without copying it. The vector is
+    /// dropped when GStreamer releases the buffer, so data produced by the
+    /// application can be pushed to an appsrc without a copy per frame
+    pub fn from_vec(data: Vec<u8>) -> Buffer{
+        let data = Box::new(data);
         unsafe{
-	        let mut mapinfo = mem::zeroed();
-	        if gst_buffer_map(self.gst_buffer() as *mut GstBuffer, &mut mapinfo, GST_MAP_READ) != 0{
-	        	let ret = f(&mapinfo);
-        		gst_buffer_unmap(self.gst_buffer() as *mut GstBuffer, &mut mapinfo);
-        		Ok(ret)
-        	}else{
-        	    Err(())
-        	}
-	    }
+            let ptr = data.as_ptr() as gpointer;
+            let size = data.len() as gsize;
+            Buffer::new(gst_buffer_new_wrapped_full(0, ptr, size, 0, size,
+                                                    Box::into_raw(data) as gpointer,
+                                                    Some(free_vec))).unwrap()
+        }
     }
 
-    pub fn map_write<'a,F:FnMut(&mut ::MapInfo)->U,U>(&'a mut self, mut f: F ) -> Result<U,()>{
+    /// Creates a buffer with a copy of `data`
+    pub fn from_slice(data: &[u8]) -> Buffer{
         unsafe{
-	        let mut mapinfo = mem::zeroed();
-	        if gst_buffer_map(self.gst_buffer_mut(), &mut mapinfo, GST_MAP_WRITE) != 0{
-	        	let ret = f(&mut mapinfo);
-        		gst_buffer_unmap(self.gst_buffer_mut(), &mut mapinfo);
-        		Ok(ret)
-        	}else{
-        	    Err(())
-        	}
-	    }
+            let mut buffer = Buffer::new(gst_buffer_new_allocate(ptr::null_mut(), data.len() as gsize, ptr::null_mut())).unwrap();
+            gst_buffer_fill(buffer.gst_buffer_mut(), 0, data.as_ptr() as gconstpointer, data.len() as gsize);
+            buffer
+        }
+    }
+
+    /// Maps the buffer for reading. The contents are accessible through
+    /// the returned guard, which derefs to a slice of bytes, and the
+    /// buffer is unmapped when it's dropped. None if the buffer can't be
+    /// mapped
+    pub fn map_read<'a>(&'a self) -> Option<BufferMap<'a>>{
+        unsafe{
+            let mut mapinfo = mem::zeroed();
+            if gst_buffer_map(self.gst_buffer() as *mut GstBuffer, &mut mapinfo, GST_MAP_READ) != 0{
+                Some(BufferMap{ buffer: self, mapinfo: mapinfo })
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Maps the buffer for reading and writing, copying it first if it's
+    /// shared so the changes aren't seen by other owners. The buffer is
+    /// unmapped when the returned guard is dropped
+    pub fn map_write<'a>(&'a mut self) -> Option<BufferMapMut<'a>>{
+        self.ensure_writable();
+        unsafe{
+            let mut mapinfo = mem::zeroed();
+            if gst_buffer_map(self.gst_buffer_mut(), &mut mapinfo, GST_MAP_READ | GST_MAP_WRITE) != 0{
+                Some(BufferMapMut{ buffer: self, mapinfo: mapinfo })
+            }else{
+                None
+            }
+        }
+    }
+
+    pub fn is_writable(&self) -> bool{
+        unsafe{
+            gst_mini_object_is_writable(self.gst_buffer() as *const GstMiniObject) != 0
+        }
+    }
+
+    // buffers can only be modified by their only owner, otherwise they are
+    // replaced by a copy
+    fn ensure_writable(&mut self){
+        if !self.is_writable(){
+            *self = self.clone();
+        }
+    }
+
+    /// Presentation timestamp in nanoseconds, None if not set
+    pub fn pts(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.gst_buffer()).pts })
+    }
+
+    /// Sets the presentation timestamp, `GST_CLOCK_TIME_NONE` to unset it.
+    /// Shared buffers are copied first
+    pub fn set_pts(&mut self, pts: GstClockTime){
+        self.ensure_writable();
+        unsafe{ (*self.gst_buffer_mut()).pts = pts }
+    }
+
+    /// Decoding timestamp in nanoseconds, None if not set, usually when
+    /// it's the same as the pts
+    pub fn dts(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.gst_buffer()).dts })
+    }
+
+    pub fn set_dts(&mut self, dts: GstClockTime){
+        self.ensure_writable();
+        unsafe{ (*self.gst_buffer_mut()).dts = dts }
+    }
+
+    /// Duration in nanoseconds, None if not set
+    pub fn duration(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.gst_buffer()).duration })
+    }
+
+    pub fn set_duration(&mut self, duration: GstClockTime){
+        self.ensure_writable();
+        unsafe{ (*self.gst_buffer_mut()).duration = duration }
+    }
+
+    /// Media specific offset, like the frame number of video or the
+    /// sample number of audio. None if not set
+    pub fn offset(&self) -> Option<u64>{
+        offset(unsafe{ (*self.gst_buffer()).offset })
+    }
+
+    /// Sets the offset, `GST_BUFFER_OFFSET_NONE` to unset it
+    pub fn set_offset(&mut self, offset: u64){
+        self.ensure_writable();
+        unsafe{ (*self.gst_buffer_mut()).offset = offset }
+    }
+
+    /// Media specific offset of the end of the buffer
+    pub fn offset_end(&self) -> Option<u64>{
+        offset(unsafe{ (*self.gst_buffer()).offset_end })
+    }
+
+    pub fn set_offset_end(&mut self, offset_end: u64){
+        self.ensure_writable();
+        unsafe{ (*self.gst_buffer_mut()).offset_end = offset_end }
     }
 
     pub fn map<'a,F:FnMut(&mut ::MapInfo)->U,U>(&'a mut self, flags: ::Map, mut f: F ) -> Result<U,()>{
@@ -87,6 +188,92 @@ impl Buffer{
 		(self.size() / mem::size_of::<T>() as u64)  as usize
 	}
 
+    /// Attaches a region of interest of type `roi_type` (like "face") with
+    /// the given bounds to this buffer. The buffer has to be writable.
+    pub fn add_video_region_of_interest_meta(&mut self, roi_type: &str, x: u32, y: u32, w: u32, h: u32) -> Option<&mut VideoRegionOfInterestMeta>{
+        let croi_type = CString::new(roi_type).unwrap();
+        unsafe{
+            let meta = gst_buffer_add_video_region_of_interest_meta(self.gst_buffer_mut(), croi_type.as_ptr(), x, y, w, h);
+            meta.as_mut()
+        }
+    }
+
+    /// Get the region of interest with the given id from this buffer
+    pub fn video_region_of_interest_meta_id(&self, id: i32) -> Option<&VideoRegionOfInterestMeta>{
+        unsafe{
+            let meta = gst_buffer_get_video_region_of_interest_meta_id(self.gst_buffer() as *mut GstBuffer, id);
+            meta.as_ref()
+        }
+    }
+
+    /// Get all the regions of interest attached to this buffer
+    pub fn video_regions_of_interest(&self) -> Vec<&VideoRegionOfInterestMeta>{
+        let mut rois = Vec::new();
+        unsafe{
+            let api = gst_video_region_of_interest_meta_api_get_type();
+            let mut state: gpointer = ptr::null_mut();
+            loop{
+                let meta = gst_buffer_iterate_meta(self.gst_buffer() as *mut GstBuffer, &mut state);
+                if meta == ptr::null_mut(){
+                    break;
+                }
+                if (*(*meta).info).api == api{
+                    rois.push(&*(meta as *const VideoRegionOfInterestMeta));
+                }
+            }
+        }
+        rois
+    }
+
+    /// Attaches an overlay composition to this buffer so downstream
+    /// elements or the sink blend it on top of the video.
+    /// The buffer has to be writable.
+    pub fn add_video_overlay_composition_meta(&mut self, composition: &VideoOverlayComposition) -> Option<&mut VideoOverlayCompositionMeta>{
+        unsafe{
+            let meta = gst_buffer_add_video_overlay_composition_meta(self.gst_buffer_mut(), composition.gst_composition() as *mut GstVideoOverlayComposition);
+            meta.as_mut()
+        }
+    }
+
+    /// Get the overlay composition meta attached to this buffer if any
+    pub fn video_overlay_composition_meta(&self) -> Option<&VideoOverlayCompositionMeta>{
+        unsafe{
+            let meta = gst_buffer_get_meta(self.gst_buffer() as *mut GstBuffer, gst_video_overlay_composition_meta_api_get_type());
+            (meta as *const VideoOverlayCompositionMeta).as_ref()
+        }
+    }
+
+    /// Marks the area of the frame at x, y of size w x h as the only
+    /// valid one, cropping the video without copying it.
+    /// The buffer has to be writable.
+    pub fn add_video_crop_meta(&mut self, x: u32, y: u32, w: u32, h: u32) -> Option<&mut VideoCropMeta>{
+        unsafe{
+            let meta = gst_buffer_add_meta(self.gst_buffer_mut(), gst_video_crop_meta_get_info(), ptr::null_mut());
+            let meta = (meta as *mut VideoCropMeta).as_mut();
+            meta.map(|meta| {
+                meta.set_rect(x, y, w, h);
+                meta
+            })
+        }
+    }
+
+    /// Get the crop meta attached to this buffer if any
+    pub fn video_crop_meta(&self) -> Option<&VideoCropMeta>{
+        unsafe{
+            let meta = gst_buffer_get_meta(self.gst_buffer() as *mut GstBuffer, gst_video_crop_meta_api_get_type());
+            (meta as *const VideoCropMeta).as_ref()
+        }
+    }
+
+    /// Get the crop meta attached to this buffer to modify it.
+    /// The buffer has to be writable.
+    pub fn video_crop_meta_mut(&mut self) -> Option<&mut VideoCropMeta>{
+        unsafe{
+            let meta = gst_buffer_get_meta(self.gst_buffer_mut(), gst_video_crop_meta_api_get_type());
+            (meta as *mut VideoCropMeta).as_mut()
+        }
+    }
+
     pub fn gst_buffer(&self) -> *const GstBuffer{
         self.buffer.gst_miniobject() as *const GstBuffer
     }
@@ -110,6 +297,190 @@ impl Buffer{
     gst_buffer_flag!(is_droppable, set_droppable, GST_BUFFER_FLAG_DROPPABLE);
     gst_buffer_flag!(is_delta_unit, set_delta_unit, GST_BUFFER_FLAG_DELTA_UNIT);
     gst_buffer_flag!(is_tag_memory, set_tag_memory, GST_BUFFER_FLAG_TAG_MEMORY);
+
+    /// True if the buffer can be decoded independently of the previous
+    /// ones, like an I-frame in an encoded video stream
+    pub fn is_keyframe(&self) -> bool {
+        !self.is_delta_unit()
+    }
+}
+
+extern "C" fn free_vec(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Vec<u8>));
+    }
+}
+
+fn clock_time(time: GstClockTime) -> Option<GstClockTime>{
+    if time != GST_CLOCK_TIME_NONE { Some(time) } else { None }
+}
+
+fn offset(offset: u64) -> Option<u64>{
+    if offset != GST_BUFFER_OFFSET_NONE { Some(offset) } else { None }
+}
+
+/// A writable buffer owned by someone else, like the output buffer a base
+/// class passes to a transform. It can be modified but not replaced or
+/// kept, and derefs to `Buffer` to read it.
+///
+/// Unlike the setters of `Buffer`, which copy shared buffers, the setters
+/// do nothing if the buffer stopped being writable, because a reference
+/// to it was taken in the meantime, since the owner would never see the
+/// copy.
+pub struct BufferRefMut<'a>{
+    buffer: mem::ManuallyDrop<Buffer>,
+    _lifetime: PhantomData<&'a mut GstBuffer>,
+}
+
+impl<'a> BufferRefMut<'a>{
+    /// Borrows `buffer` without taking a reference, None if it's null or
+    /// not writable. The buffer has to stay alive during `'a`
+    pub unsafe fn new(buffer: *mut GstBuffer) -> Option<BufferRefMut<'a>>{
+        if buffer == ptr::null_mut() || gst_mini_object_is_writable(buffer as *const GstMiniObject) == 0{
+            return None;
+        }
+        Buffer::new(buffer).map(|buffer| BufferRefMut{
+            buffer: mem::ManuallyDrop::new(buffer),
+            _lifetime: PhantomData,
+        })
+    }
+
+    /// Maps the buffer for reading and writing, None if it can't be mapped
+    /// or isn't writable anymore
+    pub fn map_write<'b>(&'b mut self) -> Option<BufferMapMut<'b>>{
+        if !self.buffer.is_writable(){
+            return None;
+        }
+        self.buffer.map_write()
+    }
+
+    pub fn set_pts(&mut self, pts: GstClockTime){
+        if self.buffer.is_writable(){
+            self.buffer.set_pts(pts);
+        }
+    }
+
+    pub fn set_dts(&mut self, dts: GstClockTime){
+        if self.buffer.is_writable(){
+            self.buffer.set_dts(dts);
+        }
+    }
+
+    pub fn set_duration(&mut self, duration: GstClockTime){
+        if self.buffer.is_writable(){
+            self.buffer.set_duration(duration);
+        }
+    }
+
+    pub fn set_offset(&mut self, offset: u64){
+        if self.buffer.is_writable(){
+            self.buffer.set_offset(offset);
+        }
+    }
+
+    pub fn set_offset_end(&mut self, offset_end: u64){
+        if self.buffer.is_writable(){
+            self.buffer.set_offset_end(offset_end);
+        }
+    }
+
+    /// See `Buffer::add_video_region_of_interest_meta`
+    pub fn add_video_region_of_interest_meta(&mut self, roi_type: &str, x: u32, y: u32, w: u32, h: u32) -> Option<&mut VideoRegionOfInterestMeta>{
+        if !self.buffer.is_writable(){
+            return None;
+        }
+        self.buffer.add_video_region_of_interest_meta(roi_type, x, y, w, h)
+    }
+
+    /// See `Buffer::add_video_overlay_composition_meta`
+    pub fn add_video_overlay_composition_meta(&mut self, composition: &VideoOverlayComposition) -> Option<&mut VideoOverlayCompositionMeta>{
+        if !self.buffer.is_writable(){
+            return None;
+        }
+        self.buffer.add_video_overlay_composition_meta(composition)
+    }
+
+    /// See `Buffer::add_video_crop_meta`
+    pub fn add_video_crop_meta(&mut self, x: u32, y: u32, w: u32, h: u32) -> Option<&mut VideoCropMeta>{
+        if !self.buffer.is_writable(){
+            return None;
+        }
+        self.buffer.add_video_crop_meta(x, y, w, h)
+    }
+}
+
+impl<'a> Deref for BufferRefMut<'a>{
+    type Target = Buffer;
+    fn deref(&self) -> &Buffer{
+        &self.buffer
+    }
+}
+
+/// Read access to the contents of a mapped buffer, see `Buffer::map_read`
+pub struct BufferMap<'a>{
+    buffer: &'a Buffer,
+    mapinfo: GstMapInfo,
+}
+
+impl<'a> BufferMap<'a>{
+    /// The contents as a slice of `T`, like f32 for raw audio. Trailing
+    /// bytes that don't make a whole `T` are left out
+    pub fn data<T>(&self) -> &[T]{
+        self.mapinfo.data::<T>()
+    }
+}
+
+impl<'a> Deref for BufferMap<'a>{
+    type Target = [u8];
+    fn deref(&self) -> &[u8]{
+        self.mapinfo.data::<u8>()
+    }
+}
+
+impl<'a> Drop for BufferMap<'a>{
+    fn drop(&mut self){
+        unsafe{
+            gst_buffer_unmap(self.buffer.gst_buffer() as *mut GstBuffer, &mut self.mapinfo);
+        }
+    }
+}
+
+/// Read and write access to the contents of a mapped buffer, see
+/// `Buffer::map_write`
+pub struct BufferMapMut<'a>{
+    buffer: &'a mut Buffer,
+    mapinfo: GstMapInfo,
+}
+
+impl<'a> BufferMapMut<'a>{
+    pub fn data<T>(&self) -> &[T]{
+        self.mapinfo.data::<T>()
+    }
+
+    pub fn data_mut<T>(&mut self) -> &mut [T]{
+        self.mapinfo.data_mut::<T>()
+    }
+}
+
+impl<'a> Deref for BufferMapMut<'a>{
+    type Target = [u8];
+    fn deref(&self) -> &[u8]{
+        self.mapinfo.data::<u8>()
+    }
+}
+
+impl<'a> DerefMut for BufferMapMut<'a>{
+    fn deref_mut(&mut self) -> &mut [u8]{
+        self.mapinfo.data_mut::<u8>()
+    }
+}
+
+impl<'a> Drop for BufferMapMut<'a>{
+    fn drop(&mut self){
+        unsafe{
+            gst_buffer_unmap(self.buffer.gst_buffer_mut(), &mut self.mapinfo);
+        }
+    }
 }
 
 impl ::Transfer<GstBuffer> for Buffer{
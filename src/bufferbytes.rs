@@ -0,0 +1,65 @@
+use ffi::*;
+use buffer::Buffer;
+use reference::Reference;
+
+use bytes::Bytes;
+use std::mem;
+
+impl Buffer{
+    /// Creates a read only buffer sharing the memory of `bytes`, which is
+    /// kept alive until GStreamer releases the buffer, so data received
+    /// from the network can be pushed into a pipeline without copying
+    pub fn from_bytes(bytes: Bytes) -> Buffer{
+        let bytes = Box::new(bytes);
+        unsafe{
+            let ptr = bytes.as_ptr() as gpointer;
+            let size = bytes.len() as gsize;
+            Buffer::new(gst_buffer_new_wrapped_full(GST_MEMORY_FLAG_READONLY, ptr, size, 0, size,
+                                                    Box::into_raw(bytes) as gpointer,
+                                                    Some(free_bytes))).unwrap()
+        }
+    }
+
+    /// The contents of the buffer as `Bytes` without copying them. The
+    /// buffer stays mapped and referenced until all the clones of the
+    /// returned `Bytes` are dropped. None if the buffer can't be mapped
+    pub fn to_bytes(&self) -> Option<Bytes>{
+        let mut buffer = self.reference();
+        unsafe{
+            let mut mapinfo: GstMapInfo = mem::zeroed();
+            if gst_buffer_map(buffer.gst_buffer_mut(), &mut mapinfo, GST_MAP_READ) == 0{
+                return None;
+            }
+            Some(Bytes::from_owner(MappedBuffer{ buffer: buffer, mapinfo: mapinfo }))
+        }
+    }
+}
+
+extern "C" fn free_bytes(data: gpointer){
+    unsafe{
+        mem::drop(Box::from_raw(data as *mut Bytes));
+    }
+}
+
+// a buffer mapped for reading for as long as the Bytes using it live
+struct MappedBuffer{
+    buffer: Buffer,
+    mapinfo: GstMapInfo,
+}
+
+// the buffer is only read while mapped, and its refcount is atomic
+unsafe impl Send for MappedBuffer {}
+
+impl AsRef<[u8]> for MappedBuffer{
+    fn as_ref(&self) -> &[u8]{
+        self.mapinfo.data::<u8>()
+    }
+}
+
+impl Drop for MappedBuffer{
+    fn drop(&mut self){
+        unsafe{
+            gst_buffer_unmap(self.buffer.gst_buffer_mut(), &mut self.mapinfo);
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use ffi::*;
 
 use std::sync::mpsc::{self,channel,Receiver};
+use std::thread;
+use std::time::Instant;
 
 use message::Message;
 use util::*;
 use reference::Reference;
 use object::Object;
+use unwind;
+use cancellable::{self, CancellationToken};
 
 static REMOVE_WATCH_MESSAGE_STR: &'static str = "gstreamer1.0-rs_remove_watch_message";
+static CANCEL_MESSAGE_STR: &'static str = "gstreamer1.0-rs_cancel_message";
 
 unsafe impl Sync for Bus {}
 unsafe impl Send for Bus {}
@@ -21,11 +26,27 @@ impl Bus{
         Object::new(bus as *mut GstObject).map(|obj| Bus{bus: obj})
     }
 
-    pub fn add_watch<W: Watch>(&mut self, watch: W) -> u32{
+    /// Calls `watch` with every message posted on the bus from the glib
+    /// main loop running on the default main context, like one run by
+    /// `MainLoop::run` or a GUI toolkit, until it returns false or the
+    /// returned guard is dropped
+    pub fn add_watch<W: Watch + 'static>(&mut self, watch: W) -> BusWatch{
         unsafe{
-            let watch: Box<Watch> = Box::new(watch);
-            let watch: *mut Box<Watch> = Box::into_raw(Box::new(watch));
-            gst_bus_add_watch (self.gst_bus_mut(), Some(bus_callback), mem::transmute(watch))
+            let id = self.add_watch_id(watch);
+            let source = g_main_context_find_source_by_id(ptr::null_mut(), id);
+            if source != ptr::null_mut(){
+                g_source_ref(source);
+            }
+            BusWatch{ source: source }
+        }
+    }
+
+    fn add_watch_id<W: Watch + 'static>(&mut self, watch: W) -> u32{
+        unsafe{
+            let watch: Box<dyn Watch> = Box::new(watch);
+            let watch: *mut Box<dyn Watch> = Box::into_raw(Box::new(watch));
+            gst_bus_add_watch_full(self.gst_bus_mut(), G_PRIORITY_DEFAULT, Some(bus_callback),
+                                   watch as gpointer, Some(free_watch))
         }
     }
 
@@ -38,12 +59,174 @@ impl Bus{
         }
     }
 
+    /// Calls `handler` with every message synchronously from the thread
+    /// posting it, usually a streaming thread, before it's queued on the
+    /// bus. Needed to handle messages that have to be answered before the
+    /// element continues, like the prepare-window-handle message of video
+    /// sinks. The handler returns GST_BUS_PASS to queue the message as
+    /// usual or GST_BUS_DROP to drop it.
+    ///
+    /// Replaces the previous sync handler, if any
+    pub fn set_sync_handler<F>(&mut self, handler: F)
+        where F: Fn(&Message) -> GstBusSyncReply + Send + Sync + 'static{
+        let handler: Box<SyncHandler> = Box::new(Box::new(handler));
+        unsafe{
+            gst_bus_set_sync_handler(self.gst_bus_mut(), Some(sync_handler),
+                                     Box::into_raw(handler) as gpointer, Some(free_sync_handler));
+        }
+    }
+
+    pub fn unset_sync_handler(&mut self){
+        unsafe{
+            gst_bus_set_sync_handler(self.gst_bus_mut(), None, ptr::null_mut(), None);
+        }
+    }
+
     pub fn receiver(&mut self) -> Receiver<Message>{
 		let (watch,receiver) = channel();
-		self.add_watch(watch);
+		self.add_watch_id(watch);
 		receiver
 	}
 
+    /// Returns a receiver for the messages posted on the bus, serviced by
+    /// a thread owned by the bus instead of a watch on the default main
+    /// context, so no glib main loop needs to run.
+    ///
+    /// The thread stops once the receiver is dropped and a new message
+    /// arrives, or once nothing else keeps the bus alive, usually when
+    /// the pipeline is destroyed. Messages are popped from the bus, so
+    /// don't combine it with `add_watch` or `receiver` on the same bus.
+    pub fn to_channel(&self) -> Receiver<Message>{
+        let (sender, receiver) = channel();
+        let mut bus = self.reference();
+        thread::spawn(move ||{
+            loop{
+                let msg = unsafe{ gst_bus_timed_pop(bus.gst_bus_mut(), 100_000_000) };
+                if msg == ptr::null_mut(){
+                    // we hold the last reference
+                    if bus.bus.refcount() == 1{
+                        break;
+                    }
+                    continue;
+                }
+                let message = unsafe{ Message::new(msg) };
+                unsafe{ gst_mini_object_unref(msg as *mut GstMiniObject) };
+                if let Some(message) = message{
+                    if sender.send(message).is_err(){
+                        break;
+                    }
+                }
+            }
+        });
+        receiver
+    }
+
+    /// Takes the next message from the bus without waiting, with its
+    /// fields already parsed, see `Message::parse`. None if the bus is
+    /// empty
+    pub fn pop(&mut self) -> Option<Message>{
+        unsafe{
+            let msg = gst_bus_pop(self.gst_bus_mut());
+            Bus::parsed(msg)
+        }
+    }
+
+    /// Takes the next message from the bus with its fields already
+    /// parsed, waiting up to `timeout` nanoseconds for one to be posted.
+    /// GST_CLOCK_TIME_NONE waits forever. None if the timeout expires
+    pub fn timed_pop(&mut self, timeout: GstClockTime) -> Option<Message>{
+        unsafe{
+            let msg = gst_bus_timed_pop(self.gst_bus_mut(), timeout);
+            Bus::parsed(msg)
+        }
+    }
+
+    /// Like `timed_pop` but only for messages of `types`, a combination
+    /// of GST_MESSAGE_* flags. Messages of other types are dropped
+    pub fn timed_pop_filtered(&mut self, timeout: GstClockTime, types: GstMessageType) -> Option<Message>{
+        unsafe{
+            let msg = gst_bus_timed_pop_filtered(self.gst_bus_mut(), timeout, types);
+            Bus::parsed(msg)
+        }
+    }
+
+    /// Like `timed_pop` but returns None as soon as `token` is cancelled
+    pub fn timed_pop_cancellable(&mut self, timeout: GstClockTime, token: &CancellationToken) -> Option<Message>{
+        self.timed_pop_filtered_cancellable(timeout, GST_MESSAGE_ANY, token)
+    }
+
+    /// Like `timed_pop_filtered` but returns None as soon as `token` is
+    /// cancelled.
+    ///
+    /// Cancelling posts an application message to wake up the pop, which
+    /// is never returned. If the pop returns for another message at the
+    /// same time, the wakeup message stays on the bus and is skipped by
+    /// the next cancellable pop, `timed_pop` and watches see it as a
+    /// regular application message
+    pub fn timed_pop_filtered_cancellable(&mut self, timeout: GstClockTime, types: GstMessageType,
+                                          token: &CancellationToken) -> Option<Message>{
+        let bus = unsafe{ self.gst_bus_mut() as usize };
+        let _waker = cancellable::on_cancel(token, move || unsafe{
+            let message_cstr = CString::new(CANCEL_MESSAGE_STR).unwrap();
+            let structure = gst_structure_new_empty(message_cstr.as_ptr());
+            let message = gst_message_new_application(ptr::null_mut(), structure);
+            gst_bus_post(bus as *mut GstBus, message);
+        });
+        let start = Instant::now();
+        loop{
+            if token.is_cancelled(){
+                return None;
+            }
+            let remaining = if timeout == GST_CLOCK_TIME_NONE{
+                GST_CLOCK_TIME_NONE
+            }else{
+                timeout.saturating_sub(start.elapsed().as_nanos() as GstClockTime)
+            };
+            let message = self.timed_pop_filtered(remaining, types | GST_MESSAGE_APPLICATION)?;
+            if message.ty() != GST_MESSAGE_APPLICATION{
+                return Some(message);
+            }
+            // application messages the caller didn't ask for are dropped
+            // like timed_pop_filtered would
+            let is_cancel = unsafe{
+                let message_cstr = CString::new(CANCEL_MESSAGE_STR).unwrap();
+                gst_message_has_name(message.gst_message() as *mut GstMessage, message_cstr.as_ptr()) != 0
+            };
+            if !is_cancel && types & GST_MESSAGE_APPLICATION != 0{
+                return Some(message);
+            }
+        }
+    }
+
+    /// Iterates over the messages already on the bus, without waiting
+    pub fn iter<'a>(&'a mut self) -> BusIter<'a>{
+        self.iter_timed(0)
+    }
+
+    /// Iterates over the messages of the bus, waiting up to `timeout`
+    /// nanoseconds for each one, GST_CLOCK_TIME_NONE to wait forever.
+    /// The iteration ends when the timeout expires, so with no timeout
+    /// it has to be stopped by breaking out of the loop, usually on EOS
+    /// or error
+    pub fn iter_timed<'a>(&'a mut self, timeout: GstClockTime) -> BusIter<'a>{
+        BusIter{ bus: self, timeout: timeout, types: GST_MESSAGE_ANY }
+    }
+
+    /// Like `iter_timed` but only for messages of `types`, dropping the
+    /// rest
+    pub fn iter_timed_filtered<'a>(&'a mut self, timeout: GstClockTime, types: GstMessageType) -> BusIter<'a>{
+        BusIter{ bus: self, timeout: timeout, types: types }
+    }
+
+    unsafe fn parsed(msg: *mut GstMessage) -> Option<Message>{
+        if msg == ptr::null_mut(){
+            return None;
+        }
+        let message = Message::new(msg);
+        gst_mini_object_unref(msg as *mut GstMiniObject);
+        message.map(|message| message.parse())
+    }
+
     pub unsafe fn gst_bus(&self) -> *const GstBus{
         self.bus.gst_object() as *const GstBus
     }
@@ -53,35 +236,104 @@ impl Bus{
     }
 }
 
-extern "C" fn bus_callback(_bus: *mut GstBus, msg: *mut GstMessage, data: gpointer) -> gboolean {
+extern "C" fn bus_callback(bus: *mut GstBus, msg: *mut GstMessage, data: gpointer) -> gboolean {
     unsafe{
-        let alive = {
-            let mut watch: &mut Box<Watch> = mem::transmute(data);
-            match Message::new(msg){
-                Some(Message::Application(app_msg)) => {
-                    let structure = gst_message_get_structure(app_msg);
-                    let cname = gst_structure_get_name(structure);
-                    if from_c_str!(cname) == REMOVE_WATCH_MESSAGE_STR{
-                        false
-                    }else{
-                        watch.call(Message::Application(app_msg))
-                    }
+        let watch = &mut *(data as *mut Box<dyn Watch>);
+        let alive = unwind::guard(bus as *mut GstObject, true, || match Message::new(msg){
+            Some(Message::Application(app_msg)) => {
+                let structure = gst_message_get_structure(app_msg);
+                let cname = gst_structure_get_name(structure);
+                if from_c_str!(cname) == REMOVE_WATCH_MESSAGE_STR{
+                    false
+                }else{
+                    watch.call(Message::Application(app_msg))
                 }
-    			Some(msg) => watch.call(msg),
-    			None => true,
             }
-        };
-        if !alive{
-            Box::from_raw(data);
-        }
+            Some(msg) => watch.call(msg),
+            None => true,
+        });
         if alive {1} else {0}
     }
 }
 
+extern "C" fn free_watch(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Box<dyn Watch>));
+    }
+}
+
+/// Iterator over the messages of a bus, see `Bus::iter_timed`
+pub struct BusIter<'a>{
+    bus: &'a mut Bus,
+    timeout: GstClockTime,
+    types: GstMessageType,
+}
+
+impl<'a> Iterator for BusIter<'a>{
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Message>{
+        self.bus.timed_pop_filtered(self.timeout, self.types)
+    }
+}
+
+type SyncHandler = Box<dyn Fn(&Message) -> GstBusSyncReply + Send + Sync>;
+
+extern "C" fn sync_handler(bus: *mut GstBus, msg: *mut GstMessage, data: gpointer) -> GstBusSyncReply{
+    unsafe{
+        let handler = &*(data as *const SyncHandler);
+        unwind::guard(bus as *mut GstObject, GST_BUS_PASS, || match Message::new(msg){
+            Some(message) => handler(&message),
+            None => GST_BUS_PASS
+        })
+    }
+}
+
+extern "C" fn free_sync_handler(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut SyncHandler));
+    }
+}
+
+/// Keeps a watch added with `Bus::add_watch` installed, removing it when
+/// dropped
+#[must_use = "the watch is removed as soon as the BusWatch is dropped"]
+pub struct BusWatch{
+    source: *mut GSource,
+}
+
+unsafe impl Send for BusWatch {}
+
+impl BusWatch{
+    /// Whether the watch is still installed, false once it returned false
+    pub fn is_active(&self) -> bool{
+        unsafe{
+            self.source != ptr::null_mut() && g_source_is_destroyed(self.source) == 0
+        }
+    }
+}
+
+impl Drop for BusWatch{
+    fn drop(&mut self){
+        unsafe{
+            if self.source != ptr::null_mut(){
+                g_source_destroy(self.source);
+                g_source_unref(self.source);
+            }
+        }
+    }
+}
+
 pub trait Watch: Send{
     fn call(&mut self, msg: Message) -> bool;
 }
 
+impl<F: FnMut(Message) -> bool + Send> Watch for F{
+    fn call(&mut self, msg: Message) -> bool{
+        self(msg)
+    }
+}
+
 impl Watch for mpsc::Sender<Message>{
 	fn call(&mut self, msg: Message) -> bool{
         self.send(msg).is_ok()
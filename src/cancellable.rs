@@ -0,0 +1,113 @@
+use ffi::*;
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+
+// longest a polled operation blocks without checking its token, 20ms
+const POLL_INTERVAL: GstClockTime = 20_000_000;
+
+/// Lets another thread stop the blocking operations it's passed to, like
+/// `Bus::timed_pop_cancellable`, `AppSink::pull_sample_cancellable`,
+/// `Element::get_state_cancellable` or `Pipeline::run_until_eos`, so a
+/// service can shut down without waiting for timeouts that may never
+/// expire.
+///
+/// Clones share the same state, cancelling any of them cancels all of
+/// them. Once cancelled, a token stays cancelled and every operation
+/// passed to it returns right away.
+#[derive(Clone, Default)]
+pub struct CancellationToken{
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner{
+    cancelled: AtomicBool,
+    next_waker: AtomicUsize,
+    wakers: Mutex<Vec<(usize, Box<dyn Fn() + Send>)>>,
+}
+
+impl CancellationToken{
+    pub fn new() -> CancellationToken{
+        CancellationToken::default()
+    }
+
+    /// Wakes the operations waiting on this token right away, from any
+    /// thread. Bus pops and state waits return immediately, sample pulls
+    /// within 20ms
+    pub fn cancel(&self){
+        if self.inner.cancelled.swap(true, Ordering::SeqCst){
+            return;
+        }
+        let wakers = self.inner.wakers.lock().unwrap_or_else(|err| err.into_inner());
+        for &(_, ref wake) in wakers.iter(){
+            wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool{
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for CancellationToken{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Keeps the function passed to `on_cancel` registered, until dropped
+pub struct WakerGuard{
+    inner: Arc<Inner>,
+    id: usize,
+}
+
+impl Drop for WakerGuard{
+    fn drop(&mut self){
+        let mut wakers = self.inner.wakers.lock().unwrap_or_else(|err| err.into_inner());
+        let id = self.id;
+        wakers.retain(|&(waker_id, _)| waker_id != id);
+    }
+}
+
+/// Calls `wake` from the thread cancelling `token` while the returned
+/// guard is alive, to interrupt a blocking call waiting for it. Once the
+/// guard is dropped `wake` isn't running and won't be called anymore, so
+/// it can use pointers that outlive the guard. Register before checking
+/// `is_cancelled` so a cancellation in between isn't missed
+pub fn on_cancel<F: Fn() + Send + 'static>(token: &CancellationToken, wake: F) -> WakerGuard{
+    let id = token.inner.next_waker.fetch_add(1, Ordering::SeqCst);
+    token.inner.wakers.lock().unwrap_or_else(|err| err.into_inner()).push((id, Box::new(wake)));
+    WakerGuard{ inner: token.inner.clone(), id: id }
+}
+
+/// Calls `f` with timeouts of at most 20ms until it returns Some, the
+/// `timeout` in nanoseconds expires or `token` is cancelled. `f` is
+/// called at least once unless the token was already cancelled.
+///
+/// Only for calls without a way to be woken up: the waiting thread wakes
+/// every 20ms while blocked and notices a cancellation up to 20ms late
+pub fn poll<T, F>(token: &CancellationToken, timeout: GstClockTime, mut f: F) -> Option<T>
+    where F: FnMut(GstClockTime) -> Option<T>{
+    let start = Instant::now();
+    loop{
+        if token.is_cancelled(){
+            return None;
+        }
+        let remaining = if timeout == GST_CLOCK_TIME_NONE{
+            GST_CLOCK_TIME_NONE
+        }else{
+            timeout.saturating_sub(start.elapsed().as_nanos() as GstClockTime)
+        };
+        if let Some(ret) = f(remaining.min(POLL_INTERVAL)){
+            return Some(ret);
+        }
+        if remaining <= POLL_INTERVAL{
+            return None;
+        }
+    }
+}
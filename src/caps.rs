@@ -3,7 +3,7 @@ use std::os::raw::c_void;
 use util::*;
 use std::ops::{Deref, DerefMut};
 
-use structure::Structure;
+use structure::{Structure, FieldValue};
 use reference::Reference;
 use object::{Property, FromProperty};
 use miniobject::MiniObject;
@@ -15,27 +15,41 @@ pub struct Caps{
 }
 
 impl Caps{
-	pub unsafe fn new(caps: *mut GstCaps) -> Option<Caps>{
+	pub unsafe fn new_from_gst_caps(caps: *mut GstCaps) -> Option<Caps>{
 		MiniObject::new_from_gst_miniobject(caps as *mut GstMiniObject)
 			.map(|miniobject| Caps{ caps: miniobject })
 	}
 
+	/// Starts building caps with a single structure of the given media
+	/// type, whose fields are added with `field`:
+	///
+	/// ```ignore
+	/// let caps = Caps::new("video/x-raw")
+	///     .field("format", "RGB")
+	///     .field("width", 640)
+	///     .field("height", 480)
+	///     .field("framerate", (30, 1));
+	/// ```
+	pub fn new(media_type: &str) -> Caps{
+		Caps::new_empty_simple(media_type)
+	}
+
 	pub fn new_empty() -> Caps{
 		unsafe{
-			Caps::new(gst_caps_new_empty()).unwrap()
+			Caps::new_from_gst_caps(gst_caps_new_empty()).unwrap()
 		}
 	}
 
 	pub fn new_empty_simple(media_type: &str) -> Caps{
 		unsafe{
 			let cmedia_type = CString::new(media_type).unwrap();
-			Caps::new(gst_caps_new_empty_simple(cmedia_type.as_ptr())).unwrap()
+			Caps::new_from_gst_caps(gst_caps_new_empty_simple(cmedia_type.as_ptr())).unwrap()
 		}
 	}
 
 	pub fn new_any() -> Caps{
 		unsafe{
-			Caps::new(gst_caps_new_any()).unwrap()
+			Caps::new_from_gst_caps(gst_caps_new_any()).unwrap()
 		}
 	}
 
@@ -48,13 +62,102 @@ impl Caps{
 	pub fn from_string(desc: &str) -> Option<Caps>{
 		let cdesc = CString::new(desc).unwrap();
 	    unsafe{
-	    	Caps::new(gst_caps_from_string(mem::transmute(cdesc.as_ptr())))
+	    	Caps::new_from_gst_caps(gst_caps_from_string(mem::transmute(cdesc.as_ptr())))
 	    }
 	}
 
-	pub fn to_string(&self) -> &str{
+	/// Serializes the caps to the same format `from_string` parses
+	pub fn to_string(&self) -> String{
+		unsafe{
+			let c_str = gst_caps_to_string(self.gst_caps());
+			let string = from_c_str!(c_str).to_string();
+			g_free(c_str as gpointer);
+			string
+		}
+	}
+
+	/// Sets a field in all the structures of the caps, and returns them
+	/// for chaining
+	pub fn field<V: FieldValue>(mut self, name: &str, value: V) -> Caps{
+		self.set_field(name, value);
+		self
+	}
+
+	/// Sets a field in all the structures of the caps. If the caps are
+	/// shared they are copied first
+	pub fn set_field<V: FieldValue>(&mut self, name: &str, value: V){
+		if !self.is_writable(){
+			*self = self.clone();
+		}
+		let cname = CString::new(name).unwrap();
+		unsafe{
+			let mut gvalue: GValue = mem::zeroed();
+			value.to_gvalue(&mut gvalue);
+			gst_caps_set_value(self.gst_caps_mut(), cname.as_ptr(), &gvalue);
+			g_value_unset(&mut gvalue);
+		}
+	}
+
+	/// Number of structures in the caps
+	pub fn size(&self) -> u32{
+		unsafe{
+			gst_caps_get_size(self.gst_caps())
+		}
+	}
+
+	/// Iterates over the structures of the caps, which are borrowed from
+	/// them
+	pub fn structures<'a>(&'a self) -> Structures<'a>{
+		Structures{ caps: self, index: 0 }
+	}
+
+	/// Caps with the formats common to both
+	pub fn intersect(&self, other: &Caps) -> Caps{
+		unsafe{
+			Caps::new_from_gst_caps(gst_caps_intersect(self.gst_caps() as *mut GstCaps, other.gst_caps() as *mut GstCaps)).unwrap()
+		}
+	}
+
+	/// Whether both have any format in common, cheaper than checking if
+	/// the intersection is empty
+	pub fn can_intersect(&self, other: &Caps) -> bool{
+		unsafe{
+			gst_caps_can_intersect(self.gst_caps(), other.gst_caps()) != 0
+		}
+	}
+
+	/// Whether all the formats of these caps are also in `superset`
+	pub fn is_subset(&self, superset: &Caps) -> bool{
+		unsafe{
+			gst_caps_is_subset(self.gst_caps(), superset.gst_caps()) != 0
+		}
+	}
+
+	/// Picks a single format out of the caps, taking the first structure
+	/// and the lowest value of ranges and lists
+	pub fn fixate(self) -> Caps{
 		unsafe{
-			from_c_str!(gst_caps_to_string(self.gst_caps()))
+			Caps::new_from_gst_caps(gst_caps_fixate(::Transfer::transfer(self))).unwrap()
+		}
+	}
+
+	/// Whether the caps describe a single format, with one structure
+	/// and no ranges or lists
+	pub fn is_fixed(&self) -> bool{
+		unsafe{
+			gst_caps_is_fixed(self.gst_caps()) != 0
+		}
+	}
+
+	pub fn is_empty(&self) -> bool{
+		unsafe{
+			gst_caps_is_empty(self.gst_caps()) != 0
+		}
+	}
+
+	pub fn is_any(&self) -> bool{
+		unsafe{
+			gst_caps_is_any(self.gst_caps()) != 0
 		}
 	}
 
@@ -69,6 +172,17 @@ impl Caps{
 		}
 	}
 
+	pub fn audio_info(&self) -> Option<::AudioInfo>{
+		unsafe{
+			let mut audioinfo = mem::zeroed();
+			if gst_audio_info_from_caps(&mut audioinfo, self.gst_caps()) == 1 {
+				Some(audioinfo)
+			}else{
+				None
+			}
+		}
+	}
+
 	pub unsafe fn gst_caps(&self) -> *const GstCaps{
 		self.caps.gst_miniobject() as *const GstCaps
 	}
@@ -85,6 +199,33 @@ impl Caps{
 	}
 }
 
+/// Iterator over the structures of some caps, see `Caps::structures`
+pub struct Structures<'a>{
+	caps: &'a Caps,
+	index: u32,
+}
+
+impl<'a> Iterator for Structures<'a>{
+	type Item = Structure;
+
+	fn next(&mut self) -> Option<Structure>{
+		if self.index >= self.caps.size(){
+			return None;
+		}
+		let structure = self.caps.structure(self.index);
+		self.index += 1;
+		structure
+	}
+}
+
+impl<'a> IntoIterator for &'a Caps{
+	type Item = Structure;
+	type IntoIter = Structures<'a>;
+
+	fn into_iter(self) -> Structures<'a>{
+		self.structures()
+	}
+}
 
 impl ::Transfer<GstCaps> for Caps{
     unsafe fn transfer(self) ->  *mut GstCaps{
@@ -101,6 +242,15 @@ impl Reference for Caps{
     }
 }
 
+/// Caps as a value, like the argument of the convert-sample signal of
+/// playbin
+impl FieldValue for Caps{
+	unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+		g_value_init(gvalue, gst_caps_get_type());
+		gst_value_set_caps(gvalue, self.gst_caps());
+	}
+}
+
 impl<'a> Property for &'a Caps{
     type Target = *mut GstCaps;
     #[inline]
@@ -126,7 +276,7 @@ impl Property for Caps{
 impl<'a> FromProperty for Caps{
     fn from_property(caps: *mut GstCaps) -> Caps{
         unsafe{
-            Caps::new(caps).unwrap()
+            Caps::new_from_gst_caps(caps).unwrap()
         }
     }
 }
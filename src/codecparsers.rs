@@ -0,0 +1,359 @@
+use ffi::*;
+use buffer::Buffer;
+
+use std::mem;
+
+/// Encoded video formats understood by the parameter set parsers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec{
+    H264,
+    H265,
+}
+
+pub const H264_NAL_SPS: u8 = 7;
+pub const H264_NAL_PPS: u8 = 8;
+pub const H265_NAL_VPS: u8 = 32;
+pub const H265_NAL_SPS: u8 = 33;
+pub const H265_NAL_PPS: u8 = 34;
+
+/// Returns the type of a NAL unit, without start code or length prefix
+pub fn nal_type(codec: Codec, nal: &[u8]) -> Option<u8>{
+    nal.first().map(|header| match codec{
+        Codec::H264 => header & 0x1f,
+        Codec::H265 => (header >> 1) & 0x3f,
+    })
+}
+
+/// Splits an annex B byte-stream into its NAL units, with the start
+/// codes removed
+pub fn nal_units(data: &[u8]) -> Vec<&[u8]>{
+    let mut nals = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+    while i + 3 <= data.len(){
+        if data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 1{
+            if let Some(start) = start{
+                // a 4 byte start code leaves a trailing zero on the previous nal
+                let end = if i > start && data[i - 1] == 0 { i - 1 } else { i };
+                nals.push(&data[start..end]);
+            }
+            i += 3;
+            start = Some(i);
+        }else{
+            i += 1;
+        }
+    }
+    if let Some(start) = start{
+        if start < data.len(){
+            nals.push(&data[start..]);
+        }
+    }
+    nals
+}
+
+/// Extracts the parameter set NAL units (VPS, SPS and PPS) stored in the
+/// codec_data of avc or hvc1/hev1 caps (avcC / hvcC boxes)
+pub fn codec_data_nal_units(codec: Codec, data: &[u8]) -> Vec<&[u8]>{
+    match codec{
+        Codec::H264 => avcc_nal_units(data),
+        Codec::H265 => hvcc_nal_units(data),
+    }.unwrap_or(Vec::new())
+}
+
+fn read_length_prefixed<'a>(data: &'a [u8], pos: &mut usize, nals: &mut Vec<&'a [u8]>) -> Option<()>{
+    if *pos + 2 > data.len(){
+        return None;
+    }
+    let len = (data[*pos] as usize) << 8 | data[*pos + 1] as usize;
+    *pos += 2;
+    if *pos + len > data.len(){
+        return None;
+    }
+    nals.push(&data[*pos..*pos + len]);
+    *pos += len;
+    Some(())
+}
+
+fn avcc_nal_units(data: &[u8]) -> Option<Vec<&[u8]>>{
+    if data.len() < 7 || data[0] != 1{
+        return None;
+    }
+    let mut nals = Vec::new();
+    let mut pos = 6;
+    let n_sps = data[5] & 0x1f;
+    for _ in 0..n_sps{
+        read_length_prefixed(data, &mut pos, &mut nals)?;
+    }
+    let n_pps = *data.get(pos)?;
+    pos += 1;
+    for _ in 0..n_pps{
+        read_length_prefixed(data, &mut pos, &mut nals)?;
+    }
+    Some(nals)
+}
+
+fn hvcc_nal_units(data: &[u8]) -> Option<Vec<&[u8]>>{
+    if data.len() < 23 || data[0] != 1{
+        return None;
+    }
+    let mut nals = Vec::new();
+    let n_arrays = data[22];
+    let mut pos = 23;
+    for _ in 0..n_arrays{
+        if pos + 3 > data.len(){
+            return None;
+        }
+        let n_nals = (data[pos + 1] as usize) << 8 | data[pos + 2] as usize;
+        pos += 3;
+        for _ in 0..n_nals{
+            read_length_prefixed(data, &mut pos, &mut nals)?;
+        }
+    }
+    Some(nals)
+}
+
+/// Stream properties parsed from a sequence parameter set, enough to know
+/// the resolution and profile of an encoded stream without decoding it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sps{
+    pub codec: Codec,
+    pub profile_idc: u8,
+    /// level_idc as coded in the stream: level * 10 for H.264 and
+    /// level * 30 for H.265
+    pub level_idc: u8,
+    pub chroma_format_idc: u32,
+    pub bit_depth_luma: u32,
+    pub bit_depth_chroma: u32,
+    /// Width in pixels after applying the cropping window
+    pub width: u32,
+    /// Height in pixels after applying the cropping window
+    pub height: u32,
+    pub interlaced: bool,
+}
+
+impl Sps{
+    /// Parses the SPS NAL unit of the given codec, without start code or
+    /// length prefix, with the gst-codecparsers h264 and h265 parsers
+    pub fn parse(codec: Codec, nal: &[u8]) -> Option<Sps>{
+        let sps_type = match codec{
+            Codec::H264 => H264_NAL_SPS,
+            Codec::H265 => H265_NAL_SPS,
+        };
+        if nal_type(codec, nal) != Some(sps_type){
+            return None;
+        }
+        // the parsers look for the start code of the nal
+        let mut data = vec![0, 0, 0, 1];
+        data.extend_from_slice(nal);
+        unsafe{
+            match codec{
+                Codec::H264 => Sps::parse_h264(&data),
+                Codec::H265 => Sps::parse_h265(&data),
+            }
+        }
+    }
+
+    /// Finds and parses the first SPS in an annex B byte-stream
+    pub fn from_byte_stream(codec: Codec, data: &[u8]) -> Option<Sps>{
+        Sps::find(codec, nal_units(data))
+    }
+
+    /// Finds and parses the first SPS in the codec_data of avc or
+    /// hvc1/hev1 caps
+    pub fn from_codec_data(codec: Codec, data: &[u8]) -> Option<Sps>{
+        Sps::find(codec, codec_data_nal_units(codec, data))
+    }
+
+    /// Finds and parses the first SPS in a buffer of a byte-stream
+    /// formatted stream, usually a keyframe
+    pub fn from_buffer(codec: Codec, buffer: &Buffer) -> Option<Sps>{
+        buffer.map_read()
+            .and_then(|mapping| Sps::from_byte_stream(codec, &mapping))
+    }
+
+    fn find(codec: Codec, nals: Vec<&[u8]>) -> Option<Sps>{
+        nals.into_iter()
+            .filter_map(|nal| Sps::parse(codec, nal))
+            .next()
+    }
+
+    unsafe fn parse_h264(data: &[u8]) -> Option<Sps>{
+        let parser = gst_h264_nal_parser_new();
+        let mut nalu: GstH264NalUnit = mem::zeroed();
+        // boxed, the struct is too big for the stack of some threads
+        let mut sps: Box<GstH264SPS> = Box::new(mem::zeroed());
+        let parsed = gst_h264_parser_identify_nalu_unchecked(parser, data.as_ptr(), 0, data.len() as gsize, &mut nalu) == GST_H264_PARSER_OK
+            && gst_h264_parser_parse_sps(parser, &mut nalu, &mut *sps) == GST_H264_PARSER_OK;
+        gst_h264_nal_parser_free(parser);
+        if !parsed{
+            return None;
+        }
+        // frees the extension of subset SPS, the fields read below stay
+        gst_h264_sps_clear(&mut *sps);
+
+        let frame_mbs_only = sps.frame_mbs_only_flag as u32;
+        let mut width = sps.pic_width_in_mbs_minus1.checked_add(1)?.checked_mul(16)?;
+        let mut height = sps.pic_height_in_map_units_minus1.checked_add(1)?.checked_mul(16)?.checked_mul(2 - frame_mbs_only)?;
+        if sps.frame_cropping_flag != 0{
+            let chroma_format_idc = sps.chroma_format_idc as u32;
+            let (crop_unit_x, crop_unit_y) = if sps.separate_colour_plane_flag != 0 || chroma_format_idc == 0{
+                (1, 2 - frame_mbs_only)
+            }else{
+                let sub_width = if chroma_format_idc == 3 { 1 } else { 2 };
+                let sub_height = if chroma_format_idc == 1 { 2 } else { 1 };
+                (sub_width, sub_height * (2 - frame_mbs_only))
+            };
+            width = width.saturating_sub(sps.frame_crop_left_offset.saturating_add(sps.frame_crop_right_offset).saturating_mul(crop_unit_x));
+            height = height.saturating_sub(sps.frame_crop_top_offset.saturating_add(sps.frame_crop_bottom_offset).saturating_mul(crop_unit_y));
+        }
+
+        Some(Sps{
+            codec: Codec::H264,
+            profile_idc: sps.profile_idc,
+            level_idc: sps.level_idc,
+            chroma_format_idc: sps.chroma_format_idc as u32,
+            bit_depth_luma: sps.bit_depth_luma_minus8 as u32 + 8,
+            bit_depth_chroma: sps.bit_depth_chroma_minus8 as u32 + 8,
+            width: width,
+            height: height,
+            interlaced: frame_mbs_only == 0,
+        })
+    }
+
+    unsafe fn parse_h265(data: &[u8]) -> Option<Sps>{
+        let parser = gst_h265_parser_new();
+        let mut nalu: GstH265NalUnit = mem::zeroed();
+        // boxed, the struct is too big for the stack of some threads
+        let mut sps: Box<GstH265SPS> = Box::new(mem::zeroed());
+        let parsed = gst_h265_parser_identify_nalu_unchecked(parser, data.as_ptr(), 0, data.len() as gsize, &mut nalu) == GST_H265_PARSER_OK
+            && gst_h265_parser_parse_sps(parser, &mut nalu, &mut *sps, 0) == GST_H265_PARSER_OK;
+        gst_h265_parser_free(parser);
+        if !parsed{
+            return None;
+        }
+
+        let chroma_format_idc = sps.chroma_format_idc as u32;
+        let mut width = sps.pic_width_in_luma_samples as u32;
+        let mut height = sps.pic_height_in_luma_samples as u32;
+        if sps.conformance_window_flag != 0{
+            let (sub_width, sub_height) = if sps.separate_colour_plane_flag != 0 || chroma_format_idc == 0{
+                (1, 1)
+            }else{
+                (if chroma_format_idc == 3 { 1 } else { 2 }, if chroma_format_idc == 1 { 2 } else { 1 })
+            };
+            width = width.saturating_sub(sps.conf_win_left_offset.saturating_add(sps.conf_win_right_offset).saturating_mul(sub_width));
+            height = height.saturating_sub(sps.conf_win_top_offset.saturating_add(sps.conf_win_bottom_offset).saturating_mul(sub_height));
+        }
+
+        Some(Sps{
+            codec: Codec::H265,
+            profile_idc: sps.profile_tier_level.profile_idc,
+            level_idc: sps.profile_tier_level.level_idc,
+            chroma_format_idc: chroma_format_idc,
+            bit_depth_luma: sps.bit_depth_luma_minus8 as u32 + 8,
+            bit_depth_chroma: sps.bit_depth_chroma_minus8 as u32 + 8,
+            width: width,
+            height: height,
+            interlaced: sps.profile_tier_level.interlaced_source_flag != 0,
+        })
+    }
+
+    /// Level as a decimal number, like 4.1
+    pub fn level(&self) -> f32{
+        match self.codec{
+            Codec::H264 => self.level_idc as f32 / 10.,
+            Codec::H265 => self.level_idc as f32 / 30.,
+        }
+    }
+
+    /// Name of the profile as used in caps, like "high" or "main-10"
+    pub fn profile(&self) -> Option<&'static str>{
+        match (self.codec, self.profile_idc){
+            (Codec::H264, 66) => Some("baseline"),
+            (Codec::H264, 77) => Some("main"),
+            (Codec::H264, 88) => Some("extended"),
+            (Codec::H264, 100) => Some("high"),
+            (Codec::H264, 110) => Some("high-10"),
+            (Codec::H264, 122) => Some("high-4:2:2"),
+            (Codec::H264, 244) => Some("high-4:4:4"),
+            (Codec::H265, 1) => Some("main"),
+            (Codec::H265, 2) => Some("main-10"),
+            (Codec::H265, 3) => Some("main-still-picture"),
+            (Codec::H265, 4) => Some("format-range-extensions"),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    // baseline, level 3.1, 1920x1088 cropped to 1080
+    const H264_SPS: [u8; 10] = [0x67, 0x42, 0x00, 0x1f, 0xf4, 0x03, 0xc0, 0x11, 0x3f, 0x2a];
+    // main, level 3.1, 1920x1088 cropped to 1080
+    const H265_SPS: [u8; 31] = [
+        0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0x80, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03,
+        0x00, 0x5d, 0xa0, 0x03, 0xc0, 0x80, 0x11, 0x07, 0xcb, 0x96, 0x57, 0x92, 0x4d, 0xac, 0x80
+    ];
+    const PPS: [u8; 4] = [0x68, 0xce, 0x38, 0x80];
+
+    #[test]
+    fn byte_stream_nal_units(){
+        let mut stream = vec![0, 0, 0, 1];
+        stream.extend_from_slice(&H264_SPS);
+        stream.extend_from_slice(&[0, 0, 1]);
+        stream.extend_from_slice(&PPS);
+        let nals = nal_units(&stream);
+        assert_eq!(nals, vec![&H264_SPS[..], &PPS[..]]);
+        assert_eq!(nal_type(Codec::H264, nals[0]), Some(H264_NAL_SPS));
+        assert_eq!(nal_type(Codec::H264, nals[1]), Some(H264_NAL_PPS));
+        assert_eq!(nal_type(Codec::H265, &H265_SPS), Some(H265_NAL_SPS));
+    }
+
+    #[test]
+    fn avcc_codec_data(){
+        let mut avcc = vec![1, 0x42, 0x00, 0x1f, 0xff, 0xe1, 0, H264_SPS.len() as u8];
+        avcc.extend_from_slice(&H264_SPS);
+        avcc.extend_from_slice(&[1, 0, PPS.len() as u8]);
+        avcc.extend_from_slice(&PPS);
+        assert_eq!(codec_data_nal_units(Codec::H264, &avcc), vec![&H264_SPS[..], &PPS[..]]);
+
+        // a length past the end invalidates the codec_data
+        let truncated = &avcc[..avcc.len() - 1];
+        assert!(codec_data_nal_units(Codec::H264, truncated).is_empty());
+    }
+
+    #[test]
+    fn hvcc_codec_data(){
+        let mut hvcc = vec![0; 22];
+        hvcc[0] = 1;
+        hvcc.extend_from_slice(&[1, 0x80 | H265_NAL_SPS, 0, 1, 0, H265_SPS.len() as u8]);
+        hvcc.extend_from_slice(&H265_SPS);
+        assert_eq!(codec_data_nal_units(Codec::H265, &hvcc), vec![&H265_SPS[..]]);
+
+        hvcc[22] = 2;
+        assert!(codec_data_nal_units(Codec::H265, &hvcc).is_empty());
+    }
+
+    #[test]
+    fn h264_sps(){
+        let sps = Sps::parse(Codec::H264, &H264_SPS).unwrap();
+        assert_eq!((sps.width, sps.height), (1920, 1080));
+        assert_eq!(sps.profile(), Some("baseline"));
+        assert_eq!(sps.level_idc, 31);
+        assert!(!sps.interlaced);
+        assert_eq!(Sps::parse(Codec::H264, &PPS), None);
+        assert_eq!(Sps::parse(Codec::H264, &H264_SPS[..4]), None);
+    }
+
+    #[test]
+    fn h265_sps(){
+        let sps = Sps::parse(Codec::H265, &H265_SPS).unwrap();
+        assert_eq!((sps.width, sps.height), (1920, 1080));
+        assert_eq!(sps.profile(), Some("main"));
+        assert_eq!(sps.level_idc, 93);
+        assert_eq!(sps.bit_depth_luma, 8);
+        assert_eq!(Sps::parse(Codec::H265, &H265_SPS[..8]), None);
+    }
+}
@@ -0,0 +1,227 @@
+use ffi::*;
+use util::*;
+use caps::Caps;
+
+/// Color information of a video stream: the range of the values, the
+/// matrix used to convert to RGB, the transfer function and the color
+/// primaries. It's the "colorimetry" field of raw video caps, like
+/// "bt709" or "bt2100-pq".
+pub type VideoColorimetry = GstVideoColorimetry;
+
+impl VideoColorimetry{
+    /// Parses a colorimetry string as found in caps, either a well known
+    /// name like "bt709" or the "range:matrix:transfer:primaries" form
+    pub fn from_string(colorimetry: &str) -> Option<VideoColorimetry>{
+        let ccolorimetry = CString::new(colorimetry).unwrap();
+        unsafe{
+            let mut cinfo: VideoColorimetry = mem::zeroed();
+            if gst_video_colorimetry_from_string(&mut cinfo, ccolorimetry.as_ptr()) != 0{
+                Some(cinfo)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Colorimetry of the raw video described by `caps`
+    pub fn from_caps(caps: &Caps) -> Option<VideoColorimetry>{
+        caps.video_info().map(|info| info.colorimetry())
+    }
+
+    pub fn to_string(&self) -> String{
+        unsafe{
+            let cstr = gst_video_colorimetry_to_string(mem::transmute(self));
+            if cstr != ptr::null_mut(){
+                let colorimetry = from_c_str!(cstr).to_string();
+                g_free(cstr as gpointer);
+                colorimetry
+            }else{
+                "".to_string()
+            }
+        }
+    }
+
+    /// Checks if this colorimetry is the one described by the string
+    pub fn matches(&self, colorimetry: &str) -> bool{
+        let ccolorimetry = CString::new(colorimetry).unwrap();
+        unsafe{
+            gst_video_colorimetry_matches(mem::transmute(self), ccolorimetry.as_ptr()) != 0
+        }
+    }
+
+    #[inline]
+    pub fn range(&self) -> GstVideoColorRange{
+        self.range
+    }
+
+    #[inline]
+    pub fn set_range(&mut self, range: GstVideoColorRange){
+        self.range = range;
+    }
+
+    #[inline]
+    pub fn matrix(&self) -> GstVideoColorMatrix{
+        self.matrix
+    }
+
+    #[inline]
+    pub fn set_matrix(&mut self, matrix: GstVideoColorMatrix){
+        self.matrix = matrix;
+    }
+
+    #[inline]
+    pub fn transfer(&self) -> GstVideoTransferFunction{
+        self.transfer
+    }
+
+    #[inline]
+    pub fn set_transfer(&mut self, transfer: GstVideoTransferFunction){
+        self.transfer = transfer;
+    }
+
+    #[inline]
+    pub fn primaries(&self) -> GstVideoColorPrimaries{
+        self.primaries
+    }
+
+    #[inline]
+    pub fn set_primaries(&mut self, primaries: GstVideoColorPrimaries){
+        self.primaries = primaries;
+    }
+
+    /// True if the transfer function is one of the HDR ones, PQ
+    /// (SMPTE ST 2084) or HLG (ARIB STD-B67)
+    #[inline]
+    pub fn is_hdr(&self) -> bool{
+        self.transfer == GST_VIDEO_TRANSFER_SMPTE2084 || self.transfer == GST_VIDEO_TRANSFER_ARIB_STD_B67
+    }
+
+    /// True if the color primaries are the BT.2020 wide color gamut
+    #[inline]
+    pub fn is_wide_gamut(&self) -> bool{
+        self.primaries == GST_VIDEO_COLOR_PRIMARIES_BT2020
+    }
+}
+
+impl PartialEq for VideoColorimetry{
+    fn eq(&self, other: &VideoColorimetry) -> bool{
+        self.range == other.range && self.matrix == other.matrix &&
+            self.transfer == other.transfer && self.primaries == other.primaries
+    }
+}
+
+impl Eq for VideoColorimetry{}
+
+fn caps_string_field(caps: &Caps, field: &str) -> Option<String>{
+    let cfield = CString::new(field).unwrap();
+    unsafe{
+        let structure = gst_caps_get_structure(caps.gst_caps(), 0);
+        if structure == ptr::null_mut(){
+            return None;
+        }
+        let value = gst_structure_get_string(structure, cfield.as_ptr());
+        if value != ptr::null(){
+            Some(from_c_str!(value).to_string())
+        }else{
+            None
+        }
+    }
+}
+
+
+/// SMPTE ST 2086 mastering display color volume, static HDR10 metadata
+/// describing the display the content was graded on.
+///
+/// Chromaticity coordinates are in units of 0.00002 and luminances in
+/// units of 0.0001 cd/m², as in the HEVC SEI and the
+/// "mastering-display-info" caps field. GStreamer carries this
+/// information only in the caps, there's no per buffer meta for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MasteringDisplayInfo{
+    /// x, y of the red, green and blue primaries
+    pub display_primaries: [(u16, u16); 3],
+    pub white_point: (u16, u16),
+    pub max_display_mastering_luminance: u32,
+    pub min_display_mastering_luminance: u32,
+}
+
+impl MasteringDisplayInfo{
+    /// Parses the "mastering-display-info" caps field format, 10 colon
+    /// separated values: red x:y, green x:y, blue x:y, white point x:y,
+    /// max and min luminance
+    pub fn from_string(info: &str) -> Option<MasteringDisplayInfo>{
+        let values = info.split(':').map(|v| v.trim().parse::<u32>().ok()).collect::<Option<Vec<u32>>>()?;
+        if values.len() != 10 || values[..8].iter().any(|&v| v > 0xffff){
+            return None;
+        }
+        Some(MasteringDisplayInfo{
+            display_primaries: [
+                (values[0] as u16, values[1] as u16),
+                (values[2] as u16, values[3] as u16),
+                (values[4] as u16, values[5] as u16),
+            ],
+            white_point: (values[6] as u16, values[7] as u16),
+            max_display_mastering_luminance: values[8],
+            min_display_mastering_luminance: values[9],
+        })
+    }
+
+    pub fn to_string(&self) -> String{
+        let p = &self.display_primaries;
+        format!("{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            p[0].0, p[0].1, p[1].0, p[1].1, p[2].0, p[2].1,
+            self.white_point.0, self.white_point.1,
+            self.max_display_mastering_luminance, self.min_display_mastering_luminance)
+    }
+
+    pub fn from_caps(caps: &Caps) -> Option<MasteringDisplayInfo>{
+        caps_string_field(caps, "mastering-display-info")
+            .and_then(|info| MasteringDisplayInfo::from_string(&info))
+    }
+
+    /// Sets the "mastering-display-info" field on `caps` which have to
+    /// be writable
+    pub fn add_to_caps(&self, caps: &mut Caps){
+        caps.set_field("mastering-display-info", self.to_string());
+    }
+}
+
+/// CEA 861.3 content light level, static HDR10 metadata with the
+/// brightest pixel and the brightest frame average of the content in
+/// cd/m², as in the "content-light-level" caps field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContentLightLevel{
+    /// MaxCLL
+    pub max_content_light_level: u16,
+    /// MaxFALL
+    pub max_frame_average_light_level: u16,
+}
+
+impl ContentLightLevel{
+    /// Parses the "content-light-level" caps field format, "MaxCLL:MaxFALL"
+    pub fn from_string(level: &str) -> Option<ContentLightLevel>{
+        let mut values = level.split(':').map(|v| v.trim().parse::<u16>().ok());
+        match (values.next(), values.next(), values.next()){
+            (Some(Some(max_cll)), Some(Some(max_fall)), None) => Some(ContentLightLevel{
+                max_content_light_level: max_cll,
+                max_frame_average_light_level: max_fall,
+            }),
+            _ => None
+        }
+    }
+
+    pub fn to_string(&self) -> String{
+        format!("{}:{}", self.max_content_light_level, self.max_frame_average_light_level)
+    }
+
+    pub fn from_caps(caps: &Caps) -> Option<ContentLightLevel>{
+        caps_string_field(caps, "content-light-level")
+            .and_then(|level| ContentLightLevel::from_string(&level))
+    }
+
+    /// Sets the "content-light-level" field on `caps` which have to be
+    /// writable
+    pub fn add_to_caps(&self, caps: &mut Caps){
+        caps.set_field("content-light-level", self.to_string());
+    }
+}
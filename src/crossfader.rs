@@ -0,0 +1,226 @@
+use ffi::*;
+use bin::Bin;
+use element::Element;
+use error::{Error, Result};
+use pad::Pad;
+use reference::Reference;
+
+use std::ptr;
+use std::ffi::CString;
+use std::ops::{Deref, DerefMut};
+
+// compositor background, black instead of the default checker pattern
+const COMPOSITOR_BACKGROUND_BLACK: i32 = 1;
+
+// time left between setting a ramp and its start, so the mixer hasn't
+// already produced output past its first point
+const RAMP_LEAD: GstClockTime = 40_000_000;
+
+// linear ramp of a property of a mixer pad, driven by a control source
+struct Ramp{
+    source: *mut GstControlSource,
+}
+
+impl Ramp{
+    fn new(pad: &mut Pad, property: &str) -> Ramp{
+        let cproperty = CString::new(property).unwrap();
+        let mode = CString::new("mode").unwrap();
+        unsafe{
+            let source = gst_interpolation_control_source_new();
+            gst_object_ref_sink(source as gpointer);
+            g_object_set(source as gpointer, mode.as_ptr(), GST_INTERPOLATION_MODE_LINEAR, ptr::null::<gchar>());
+            let binding = gst_direct_control_binding_new_absolute(pad.gst_pad_mut() as *mut GstObject, cproperty.as_ptr(), source);
+            gst_object_add_control_binding(pad.gst_pad_mut() as *mut GstObject, binding);
+            Ramp{ source: source }
+        }
+    }
+
+    fn set(&mut self, start: GstClockTime, from: f64, end: GstClockTime, to: f64){
+        unsafe{
+            let source = self.source as *mut GstTimedValueControlSource;
+            gst_timed_value_control_source_unset_all(source);
+            gst_timed_value_control_source_set(source, start, from);
+            gst_timed_value_control_source_set(source, end, to);
+        }
+    }
+}
+
+impl Drop for Ramp{
+    fn drop(&mut self){
+        unsafe{
+            gst_object_unref(self.source as gpointer);
+        }
+    }
+}
+
+// compositor or audiomixer with one pad per input
+struct Mixer{
+    mixer: Element,
+    pads: Vec<Pad>,
+    ramps: Vec<Ramp>,
+}
+
+impl Mixer{
+    fn new(bin: &mut Bin, factory: &str, property: &str, prefix: &str) -> Result<Mixer>{
+        let mut mixer = Element::new(factory, "")?;
+        if !bin.add(mixer.reference()){
+            return Err(Error::new(0,0,&format!("Couldn't add {}", factory)));
+        }
+        let mut pads = vec![];
+        let mut ramps = vec![];
+        for input in 0..2{
+            let mut pad = mixer.request_pad_simple("sink_%u")
+                .ok_or_else(|| Error::new(0,0,&format!("Couldn't request a pad from {}", factory)))?;
+            let name = pad.name();
+            if !bin.add_ghost_pad(&mut mixer, &name, &format!("{}_sink_{}", prefix, input)){
+                return Err(Error::new(0,0,"Couldn't add ghost pad"));
+            }
+            pad.set(property, if input == 0 { 1.0f64 } else { 0.0f64 });
+            ramps.push(Ramp::new(&mut pad, property));
+            pads.push(pad);
+        }
+        if !bin.add_ghost_pad(&mut mixer, "src", &format!("{}_src", prefix)){
+            return Err(Error::new(0,0,"Couldn't add ghost pad"));
+        }
+        Ok(Mixer{ mixer: mixer, pads: pads, ramps: ramps })
+    }
+}
+
+/// Switches between two live inputs fading from one to the other, with
+/// a compositor for video and an audiomixer for audio, whose pad alpha and
+/// volume are ramped by control sources.
+///
+/// The inputs are linked to the `video_sink_0`, `video_sink_1`,
+/// `audio_sink_0` and `audio_sink_1` pads of the bin, and the mixed
+/// output comes from `video_src` and `audio_src`. Input 0 is shown at
+/// start.
+pub struct Crossfader{
+    bin: Bin,
+    video: Option<Mixer>,
+    audio: Option<Mixer>,
+    active: usize,
+    fade_duration: GstClockTime,
+}
+
+unsafe impl Sync for Crossfader {}
+unsafe impl Send for Crossfader {}
+
+impl Crossfader{
+    /// Creates a crossfader for video, audio or both. The fade lasts one
+    /// second by default
+    pub fn new(name: &str, video: bool, audio: bool) -> Result<Crossfader>{
+        let mut bin = Bin::new(name)?;
+        let video = if video{
+            let mut mixer = Mixer::new(&mut bin, "compositor", "alpha", "video")?;
+            mixer.mixer.set("background", COMPOSITOR_BACKGROUND_BLACK);
+            Some(mixer)
+        }else{
+            None
+        };
+        let audio = if audio{
+            Some(Mixer::new(&mut bin, "audiomixer", "volume", "audio")?)
+        }else{
+            None
+        };
+        let mut crossfader = Crossfader{
+            bin: bin,
+            video: video,
+            audio: audio,
+            active: 0,
+            fade_duration: 1_000_000_000,
+        };
+        crossfader.set_zorder(0);
+        Ok(crossfader)
+    }
+
+    /// Duration of the fades in nanoseconds
+    pub fn set_fade_duration(&mut self, duration: GstClockTime){
+        self.fade_duration = duration;
+    }
+
+    pub fn fade_duration(&self) -> GstClockTime{
+        self.fade_duration
+    }
+
+    /// The input being shown, or being faded to
+    pub fn active(&self) -> usize{
+        self.active
+    }
+
+    /// Fades from the active input to `input`, 0 or 1
+    pub fn fade_to(&mut self, input: usize){
+        let duration = self.fade_duration;
+        self.switch_to(input, duration);
+    }
+
+    /// Switches to `input` without a fade
+    pub fn cut_to(&mut self, input: usize){
+        self.switch_to(input, 0);
+    }
+
+    fn switch_to(&mut self, input: usize, duration: GstClockTime){
+        if input > 1 || input == self.active{
+            return;
+        }
+        let start = self.bin.running_time().unwrap_or(0) + RAMP_LEAD;
+        let end = start + duration;
+        let previous = self.active;
+        // the video of the new input is blended over the previous one,
+        // which is only hidden once covered, so the fade doesn't dim
+        self.set_zorder(input);
+        if let Some(ref mut video) = self.video{
+            video.ramps[input].set(start, 0.0, end, 1.0);
+            video.ramps[previous].set(end, 1.0, end + 1, 0.0);
+        }
+        if let Some(ref mut audio) = self.audio{
+            audio.ramps[input].set(start, 0.0, end, 1.0);
+            audio.ramps[previous].set(start, 1.0, end, 0.0);
+        }
+        self.active = input;
+    }
+
+    fn set_zorder(&mut self, top: usize){
+        if let Some(ref mut video) = self.video{
+            for (input, pad) in video.pads.iter_mut().enumerate(){
+                pad.set("zorder", if input == top { 1u32 } else { 0u32 });
+            }
+        }
+    }
+}
+
+impl AsRef<Bin> for Crossfader{
+    fn as_ref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl AsMut<Bin> for Crossfader{
+    fn as_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl From<Crossfader> for Element{
+    fn from(crossfader: Crossfader) -> Element{
+        crossfader.bin.into()
+    }
+}
+
+impl Deref for Crossfader{
+    type Target = Bin;
+    fn deref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl DerefMut for Crossfader{
+    fn deref_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl ::Transfer for Crossfader{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.bin.transfer()
+    }
+}
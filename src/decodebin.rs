@@ -0,0 +1,176 @@
+use ffi::*;
+use caps::Caps;
+use element::Element;
+use elementfactory::ElementFactory;
+use error::Result;
+use pad::Pad;
+use reference::Reference;
+use unwind;
+
+use std::ffi::CString;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// What decodebin does with a factory it's about to autoplug
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum AutoplugSelect{
+    /// Creates an element from the factory and links it
+    Try = 0,
+    /// Stops autoplugging and exposes the pad as is
+    Expose = 1,
+    /// Ignores the factory and tries the next one
+    Skip = 2,
+}
+
+/// Typed wrapper for decodebin and uridecodebin, to configure their
+/// buffering and take autoplugging decisions, like avoiding a hardware
+/// decoder known to fail on some streams.
+pub struct DecodeBin{
+    decodebin: Element
+}
+
+unsafe impl Sync for DecodeBin {}
+unsafe impl Send for DecodeBin {}
+
+impl DecodeBin{
+    pub fn new(name: &str) -> Result<DecodeBin>{
+        Element::new("decodebin", name).map(DecodeBin::new_from_element)
+    }
+
+    /// Creates a uridecodebin, which also creates the source for `uri`
+    pub fn new_uri(name: &str, uri: &str) -> Result<DecodeBin>{
+        Element::new("uridecodebin", name).map(|element|{
+            let mut decodebin = DecodeBin::new_from_element(element);
+            decodebin.set("uri", uri);
+            decodebin
+        })
+    }
+
+    pub fn new_from_element(element: Element) -> DecodeBin{
+        DecodeBin{ decodebin: element }
+    }
+
+    /// Caps at which autoplugging stops and the pads are exposed, by
+    /// default raw audio and video
+    pub fn set_caps(&mut self, caps: &Caps){
+        self.decodebin.set("caps", caps);
+    }
+
+    /// Limits of the multiqueue after the demuxer, 0 to disable each one
+    pub fn set_max_size_bytes(&mut self, bytes: u32){
+        self.decodebin.set("max-size-bytes", bytes);
+    }
+
+    pub fn set_max_size_buffers(&mut self, buffers: u32){
+        self.decodebin.set("max-size-buffers", buffers);
+    }
+
+    /// Limit in nanoseconds
+    pub fn set_max_size_time(&mut self, time: GstClockTime){
+        self.decodebin.set("max-size-time", time);
+    }
+
+    /// Posts buffering messages based on the level of the queues, for
+    /// network streams
+    pub fn set_use_buffering(&mut self, use_buffering: bool){
+        self.decodebin.set("use-buffering", use_buffering as gboolean);
+    }
+
+    /// Calls `select` before autoplugging each factory, with the pad
+    /// being decoded and its caps, to decide whether to use the factory.
+    /// It's called from the streaming threads, one call at a time.
+    ///
+    /// Returns the id of the handler, to be used with `disconnect`.
+    pub fn on_autoplug_select<F>(&mut self, select: F) -> u64
+        where F: FnMut(&Pad, &Caps, &ElementFactory) -> AutoplugSelect + Send + 'static{
+        let select: Box<AutoplugSelectCallback> = Box::new(Mutex::new(Box::new(select)));
+        let signal = CString::new("autoplug-select").unwrap();
+        unsafe{
+            g_signal_connect_data(self.decodebin.gst_element_mut() as gpointer, signal.as_ptr(),
+                                  mem::transmute(autoplug_select as extern "C" fn(*mut GstElement, *mut GstPad, *mut GstCaps, *mut GstElementFactory, gpointer) -> i32),
+                                  Box::into_raw(select) as gpointer, Some(free_autoplug_select), 0) as u64
+        }
+    }
+
+    /// Never autoplugs the factories named in `factories`, like
+    /// "vaapih264dec", falling back to the next best one
+    pub fn skip_factories(&mut self, factories: &[&str]) -> u64{
+        let factories: Vec<String> = factories.iter().map(|factory| factory.to_string()).collect();
+        self.on_autoplug_select(move |_pad, _caps, factory|{
+            let name = factory.name();
+            if factories.iter().any(|skipped| *skipped == name){
+                AutoplugSelect::Skip
+            }else{
+                AutoplugSelect::Try
+            }
+        })
+    }
+}
+
+type AutoplugSelectCallback = Mutex<Box<dyn FnMut(&Pad, &Caps, &ElementFactory) -> AutoplugSelect + Send>>;
+
+extern "C" fn autoplug_select(bin: *mut GstElement, pad: *mut GstPad, caps: *mut GstCaps,
+                              factory: *mut GstElementFactory, data: gpointer) -> i32{
+    unsafe{
+        let select = &*(data as *const AutoplugSelectCallback);
+        let pad = Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad);
+        let caps = Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps);
+        let factory = ElementFactory::new(gst_object_ref(factory as gpointer) as *mut GstElementFactory);
+        match (pad, caps, factory){
+            (Some(pad), Some(caps), Some(factory)) => unwind::guard(bin as *mut GstObject, AutoplugSelect::Skip as i32,
+                || (&mut *select.lock().unwrap())(&pad, &caps, &factory) as i32),
+            _ => AutoplugSelect::Try as i32
+        }
+    }
+}
+
+extern "C" fn free_autoplug_select(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut AutoplugSelectCallback));
+    }
+}
+
+impl AsRef<Element> for DecodeBin{
+    fn as_ref(&self) -> &Element{
+        &self.decodebin
+    }
+}
+
+impl AsMut<Element> for DecodeBin{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.decodebin
+    }
+}
+
+impl From<DecodeBin> for Element{
+    fn from(decodebin: DecodeBin) -> Element{
+        decodebin.decodebin
+    }
+}
+
+impl Deref for DecodeBin{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.decodebin
+    }
+}
+
+impl DerefMut for DecodeBin{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.decodebin
+    }
+}
+
+impl ::Transfer for DecodeBin{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.decodebin.transfer()
+    }
+}
+
+impl Reference for DecodeBin{
+    fn reference(&self) -> DecodeBin{
+        DecodeBin{ decodebin: self.decodebin.reference() }
+    }
+}
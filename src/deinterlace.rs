@@ -0,0 +1,313 @@
+use ffi::*;
+use buffer::Buffer;
+use caps::Caps;
+use element::Element;
+use error::Result;
+use reference::Reference;
+
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+
+/// When the deinterlacer processes the frames
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum DeinterlaceMode{
+    /// Deinterlace when the caps or buffer flags say the content is
+    /// interlaced, the default
+    Auto = 0,
+    /// Always deinterlace, even if the content is flagged as progressive
+    Interlaced = 1,
+    /// Never deinterlace
+    Disabled = 2,
+    /// Like auto but refuse progressive-only caps
+    AutoStrict = 3,
+}
+
+/// Algorithm used to deinterlace
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum DeinterlaceMethod{
+    TomsMoComp = 0,
+    GreedyH = 1,
+    GreedyL = 2,
+    Vfir = 3,
+    Linear = 4,
+    LinearBlend = 5,
+    ScalerBob = 6,
+    Weave = 7,
+    WeaveTff = 8,
+    WeaveBff = 9,
+}
+
+/// Fields used to produce the output. Using all of them doubles the
+/// framerate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum DeinterlaceFields{
+    All = 0,
+    Top = 1,
+    Bottom = 2,
+    Auto = 3,
+}
+
+/// Pattern locking, used to detect telecine and reconstruct the
+/// original progressive frames instead of deinterlacing them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum DeinterlaceLocking{
+    None = 0,
+    /// Choose active for live sources and passive otherwise
+    Auto = 1,
+    Active = 2,
+    Passive = 3,
+}
+
+/// Typed wrapper for the deinterlace element
+pub struct Deinterlacer{
+    deinterlace: Element
+}
+
+unsafe impl Sync for Deinterlacer {}
+unsafe impl Send for Deinterlacer {}
+
+impl Deinterlacer{
+    pub fn new(name: &str) -> Result<Deinterlacer>{
+        Element::new("deinterlace", name).map(|deinterlace| Deinterlacer{ deinterlace: deinterlace })
+    }
+
+    pub fn new_from_element(element: Element) -> Deinterlacer{
+        Deinterlacer{ deinterlace: element }
+    }
+
+    pub fn set_mode(&mut self, mode: DeinterlaceMode){
+        self.deinterlace.set("mode", mode as i32);
+    }
+
+    pub fn set_method(&mut self, method: DeinterlaceMethod){
+        self.deinterlace.set("method", method as i32);
+    }
+
+    pub fn set_fields(&mut self, fields: DeinterlaceFields){
+        self.deinterlace.set("fields", fields as i32);
+    }
+
+    pub fn set_locking(&mut self, locking: DeinterlaceLocking){
+        self.deinterlace.set("locking", locking as i32);
+    }
+
+    /// Drop the duplicated frames found when undoing telecine so the
+    /// output has the original film framerate
+    pub fn set_drop_orphans(&mut self, drop_orphans: bool){
+        self.deinterlace.set("drop-orphans", drop_orphans as gboolean);
+    }
+
+    /// Configures the deinterlacer for the kind of content reported by
+    /// an `InterlaceDetector`. Telecined content is reconstructed using
+    /// pattern locking instead of being deinterlaced
+    pub fn configure_for(&mut self, content: ScanType){
+        match content{
+            ScanType::Progressive => {
+                self.set_mode(DeinterlaceMode::Auto);
+                self.set_locking(DeinterlaceLocking::None);
+            }
+            ScanType::Interlaced | ScanType::Mixed => {
+                self.set_mode(DeinterlaceMode::Interlaced);
+                self.set_locking(DeinterlaceLocking::None);
+            }
+            ScanType::Telecine => {
+                self.set_mode(DeinterlaceMode::Interlaced);
+                self.set_locking(DeinterlaceLocking::Auto);
+            }
+            ScanType::Unknown => {
+                self.set_mode(DeinterlaceMode::Auto);
+                self.set_locking(DeinterlaceLocking::Auto);
+            }
+        }
+    }
+}
+
+/// Kind of content found by an `InterlaceDetector`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScanType{
+    /// Not enough frames analyzed yet
+    Unknown,
+    Progressive,
+    Interlaced,
+    /// Progressive film content converted to an interlaced rate with
+    /// repeated fields, like 3:2 pulldown
+    Telecine,
+    /// Progressive and interlaced frames alternate
+    Mixed,
+}
+
+/// Detects interlaced and telecined content from the stream caps and
+/// the interlacing flags of the buffers.
+///
+/// Feed it the buffers of a stream, usually from a pad probe, and query
+/// `scan_type()`. Only the last `window` frames are taken into account so
+/// changes in the content are detected, and the history is reset on
+/// DISCONT buffers since the field pattern doesn't survive a discontinuity.
+pub struct InterlaceDetector{
+    caps_mode: Option<GstVideoInterlaceMode>,
+    window: usize,
+    frames: VecDeque<(bool, bool)>,
+}
+
+impl InterlaceDetector{
+    /// Creates a detector remembering the flags of the last `window`
+    /// frames. 30 is a good value, telecine patterns repeat every 5
+    pub fn new(window: usize) -> InterlaceDetector{
+        InterlaceDetector{
+            caps_mode: None,
+            window: if window > 0 { window } else { 1 },
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Sets the caps of the stream, which tell the interlace mode of
+    /// the whole stream
+    pub fn set_caps(&mut self, caps: &Caps){
+        self.caps_mode = caps.video_info().map(|info| info.interlace_mode());
+    }
+
+    /// Analyzes the flags of the next frame
+    pub fn push(&mut self, buffer: &Buffer){
+        if buffer.is_discont(){
+            self.frames.clear();
+        }
+        let flags = buffer.flags();
+        self.push_flags(flags & GST_VIDEO_BUFFER_FLAG_INTERLACED != 0, flags & GST_VIDEO_BUFFER_FLAG_RFF != 0);
+    }
+
+    fn push_flags(&mut self, interlaced: bool, rff: bool){
+        self.frames.push_back((interlaced, rff));
+        while self.frames.len() > self.window{
+            self.frames.pop_front();
+        }
+    }
+
+    /// Number of frames currently taken into account
+    pub fn n_frames(&self) -> usize{
+        self.frames.len()
+    }
+
+    pub fn scan_type(&self) -> ScanType{
+        match self.caps_mode{
+            Some(GST_VIDEO_INTERLACE_MODE_PROGRESSIVE) => return ScanType::Progressive,
+            Some(GST_VIDEO_INTERLACE_MODE_INTERLEAVED) | Some(GST_VIDEO_INTERLACE_MODE_FIELDS) => {
+                if !self.is_telecine(){
+                    return ScanType::Interlaced;
+                }
+            }
+            _ => ()
+        }
+
+        // at least a full telecine cycle is needed
+        if self.frames.len() < 5{
+            return ScanType::Unknown;
+        }
+        if self.is_telecine(){
+            return ScanType::Telecine;
+        }
+        let interlaced = self.frames.iter().filter(|&&(interlaced, _)| interlaced).count();
+        if interlaced == 0{
+            ScanType::Progressive
+        }else if interlaced == self.frames.len(){
+            ScanType::Interlaced
+        }else{
+            ScanType::Mixed
+        }
+    }
+
+    // 3:2 pulldown repeats a field in 2 out of every 5 frames when the
+    // telecined frames are flagged, and in 1 out of every 2 when the film
+    // frames are flagged to be displayed with repeated fields, as in
+    // MPEG-2 soft telecine
+    fn is_telecine(&self) -> bool{
+        if self.frames.len() < 5{
+            return false;
+        }
+        let rff = self.frames.iter().filter(|&&(_, rff)| rff).count();
+        let ratio = rff as f32 / self.frames.len() as f32;
+        ratio > 0.3 && ratio <= 0.55
+    }
+}
+
+impl AsRef<Element> for Deinterlacer{
+    fn as_ref(&self) -> &Element{
+        &self.deinterlace
+    }
+}
+
+impl AsMut<Element> for Deinterlacer{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.deinterlace
+    }
+}
+
+impl From<Deinterlacer> for Element{
+    fn from(deinterlacer: Deinterlacer) -> Element{
+        deinterlacer.deinterlace
+    }
+}
+
+impl Deref for Deinterlacer{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.deinterlace
+    }
+}
+
+impl DerefMut for Deinterlacer{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.deinterlace
+    }
+}
+
+impl ::Transfer for Deinterlacer{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.deinterlace.transfer()
+    }
+}
+
+impl Reference for Deinterlacer{
+    fn reference(&self) -> Deinterlacer{
+        Deinterlacer{ deinterlace: self.deinterlace.reference() }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{InterlaceDetector, ScanType};
+
+    fn detect<F: Fn(usize) -> bool>(frames: usize, rff: F) -> ScanType{
+        let mut detector = InterlaceDetector::new(30);
+        for i in 0..frames{
+            detector.push_flags(false, rff(i));
+        }
+        detector.scan_type()
+    }
+
+    #[test]
+    fn soft_telecine(){
+        // 24 fps film flagged to repeat a field every other frame
+        assert_eq!(detect(30, |i| i % 2 == 0), ScanType::Telecine);
+        assert_eq!(detect(100, |i| i % 2 == 1), ScanType::Telecine);
+    }
+
+    #[test]
+    fn hard_telecine(){
+        // 2 out of every 5 frames of the 3:2 cadence
+        assert_eq!(detect(30, |i| i % 5 < 2), ScanType::Telecine);
+    }
+
+    #[test]
+    fn progressive(){
+        assert_eq!(detect(30, |_| false), ScanType::Progressive);
+    }
+
+    #[test]
+    fn too_few_frames(){
+        assert_eq!(detect(4, |i| i % 2 == 0), ScanType::Unknown);
+    }
+}
@@ -0,0 +1,563 @@
+use ffi::*;
+use element::Element;
+use error::{Error, Result};
+use pipeline::Pipeline;
+use sample::Sample;
+use videoframe::VideoFrame;
+
+use std::ptr;
+
+#[cfg(target_endian = "little")]
+const F32_FORMAT: &'static str = "F32LE";
+#[cfg(target_endian = "big")]
+const F32_FORMAT: &'static str = "F32BE";
+
+/// What was detected in a segment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DetectionKind{
+    Silence,
+    Black,
+}
+
+/// A segment of silence or black frames, with its start and end in
+/// nanoseconds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Detection{
+    pub kind: DetectionKind,
+    pub start: GstClockTime,
+    pub end: GstClockTime,
+}
+
+impl Detection{
+    pub fn duration(&self) -> GstClockTime{
+        self.end - self.start
+    }
+}
+
+/// Finds the segments of audio where every sample stays below a level,
+/// fed with interleaved float samples and their timestamps.
+///
+/// Only segments lasting at least the minimum duration are reported, so
+/// short pauses in speech or music aren't.
+pub struct SilenceDetector{
+    rate: u32,
+    channels: usize,
+    threshold: f32,
+    min_duration: GstClockTime,
+    silence_start: Option<GstClockTime>,
+    // time of the sample after the last one pushed
+    next_time: GstClockTime,
+}
+
+impl SilenceDetector{
+    /// `threshold` is the level in dBFS below which audio is silent, like
+    /// -60.0, and `min_duration` in nanoseconds
+    pub fn new(rate: u32, channels: u32, threshold: f64, min_duration: GstClockTime) -> SilenceDetector{
+        SilenceDetector{
+            rate: rate,
+            channels: channels as usize,
+            threshold: 10f64.powf(threshold / 20.0) as f32,
+            min_duration: min_duration,
+            silence_start: None,
+            next_time: 0,
+        }
+    }
+
+    /// Analyzes interleaved samples starting at `time`, or following the
+    /// previous ones if it's `GST_CLOCK_TIME_NONE`, and returns the
+    /// segments of silence they end. A trailing incomplete frame is
+    /// ignored
+    pub fn push(&mut self, time: GstClockTime, samples: &[f32]) -> Vec<Detection>{
+        let start = if time == GST_CLOCK_TIME_NONE { self.next_time } else { time };
+        let mut detections = vec![];
+        let mut frames = 0;
+        for frame in samples.chunks(self.channels){
+            if frame.len() < self.channels{
+                break;
+            }
+            let frame_time = start + frames * 1_000_000_000 / self.rate as GstClockTime;
+            if frame.iter().all(|sample| sample.abs() < self.threshold){
+                self.silence_start.get_or_insert(frame_time);
+            }else if let Some(detection) = self.end_silence(frame_time){
+                detections.push(detection);
+            }
+            frames += 1;
+        }
+        self.next_time = start + frames * 1_000_000_000 / self.rate as GstClockTime;
+        detections
+    }
+
+    /// Ends the analysis, returning the silence the audio ended with if
+    /// it lasted long enough
+    pub fn finish(&mut self) -> Option<Detection>{
+        let end = self.next_time;
+        self.end_silence(end)
+    }
+
+    fn end_silence(&mut self, end: GstClockTime) -> Option<Detection>{
+        let start = self.silence_start.take()?;
+        if end.saturating_sub(start) >= self.min_duration{
+            Some(Detection{ kind: DetectionKind::Silence, start: start, end: end })
+        }else{
+            None
+        }
+    }
+}
+
+/// Whether a frame is black: at least `pixel_ratio` of its pixels have a
+/// luma at or below `luma_threshold`, with the luma in full range from 0
+/// to 255. None if the frame isn't 8 bit
+pub fn is_black(frame: &VideoFrame, luma_threshold: u8, pixel_ratio: f64) -> Option<bool>{
+    let luma = frame.component(0)?;
+    if luma.depth() != 8{
+        return None;
+    }
+    Some(plane_is_black(luma.data::<u8>(), luma.stride() as usize, luma.width() as usize, luma.height() as usize,
+                        luma_threshold, pixel_ratio))
+}
+
+fn plane_is_black(data: &[u8], stride: usize, width: usize, height: usize, luma_threshold: u8, pixel_ratio: f64) -> bool{
+    let mut dark = 0;
+    for y in 0..height{
+        dark += data[y * stride .. y * stride + width].iter()
+            .filter(|luma| **luma <= luma_threshold)
+            .count();
+    }
+    dark as f64 >= pixel_ratio * (width * height) as f64
+}
+
+/// Finds the segments of consecutive black frames, fed with the decoded
+/// frames in order. See `is_black` for the parameters that decide whether
+/// a frame is black.
+pub struct BlackDetector{
+    luma_threshold: u8,
+    pixel_ratio: f64,
+    min_duration: GstClockTime,
+    black_start: Option<GstClockTime>,
+    // end of the last frame pushed
+    end: GstClockTime,
+}
+
+impl BlackDetector{
+    pub fn new(luma_threshold: u8, pixel_ratio: f64, min_duration: GstClockTime) -> BlackDetector{
+        BlackDetector{
+            luma_threshold: luma_threshold,
+            pixel_ratio: pixel_ratio,
+            min_duration: min_duration,
+            black_start: None,
+            end: 0,
+        }
+    }
+
+    /// Analyzes the next frame and returns the segment of black frames it
+    /// ends, if any. Frames that aren't 8 bit are considered not black
+    pub fn push(&mut self, frame: &VideoFrame) -> Option<Detection>{
+        let (pts, duration) = unsafe{
+            let buffer = frame.buffer().gst_buffer();
+            ((*buffer).pts, (*buffer).duration)
+        };
+        let black = is_black(frame, self.luma_threshold, self.pixel_ratio).unwrap_or(false);
+        self.push_black(pts, duration, black)
+    }
+
+    fn push_black(&mut self, pts: GstClockTime, duration: GstClockTime, black: bool) -> Option<Detection>{
+        let pts = if pts == GST_CLOCK_TIME_NONE { self.end } else { pts };
+        self.end = if duration == GST_CLOCK_TIME_NONE { pts } else { pts + duration };
+        if black{
+            self.black_start.get_or_insert(pts);
+            None
+        }else{
+            self.end_black(pts)
+        }
+    }
+
+    /// Ends the analysis, returning the black segment the video ended with
+    /// if it lasted long enough
+    pub fn finish(&mut self) -> Option<Detection>{
+        let end = self.end;
+        self.end_black(end)
+    }
+
+    fn end_black(&mut self, end: GstClockTime) -> Option<Detection>{
+        let start = self.black_start.take()?;
+        if end.saturating_sub(start) >= self.min_duration{
+            Some(Detection{ kind: DetectionKind::Black, start: start, end: end })
+        }else{
+            None
+        }
+    }
+}
+
+/// A cut between two shots, detected at the first frame of the new one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneChange{
+    /// Timestamp of the first frame of the new scene
+    pub pts: GstClockTime,
+    /// Mean absolute difference of the luma with the previous frame, from
+    /// 0 for identical frames to 1
+    pub score: f64,
+}
+
+/// Finds cuts between shots by comparing the luma of each frame with the
+/// previous one, fed with the decoded frames in order, for example to pick
+/// thumbnails or index a video.
+///
+/// A frame starts a new scene when its difference with the previous one
+/// reaches the threshold, around 0.1 to 0.2 for usual content. Changes
+/// closer than the minimum interval to the last one, like flashes or fast
+/// motion, are ignored.
+pub struct SceneChangeDetector{
+    threshold: f64,
+    min_interval: GstClockTime,
+    // luma of the previous frame, without padding, and its size
+    previous: Vec<u8>,
+    size: (usize, usize),
+    last_change: Option<GstClockTime>,
+}
+
+impl SceneChangeDetector{
+    pub fn new(threshold: f64, min_interval: GstClockTime) -> SceneChangeDetector{
+        SceneChangeDetector{
+            threshold: threshold,
+            min_interval: min_interval,
+            previous: vec![],
+            size: (0, 0),
+            last_change: None,
+        }
+    }
+
+    /// Analyzes the next frame and returns the scene change it starts, if
+    /// any. Frames that aren't 8 bit are ignored, and a change of size
+    /// isn't considered a scene change
+    pub fn push(&mut self, frame: &VideoFrame) -> Option<SceneChange>{
+        let luma = frame.component(0)?;
+        if luma.depth() != 8{
+            return None;
+        }
+        let pts = unsafe{ (*frame.buffer().gst_buffer()).pts };
+        self.push_luma(pts, luma.data::<u8>(), luma.stride() as usize, luma.width() as usize, luma.height() as usize)
+    }
+
+    fn push_luma(&mut self, pts: GstClockTime, data: &[u8], stride: usize, width: usize, height: usize) -> Option<SceneChange>{
+        let compare = self.size == (width, height) && width * height > 0;
+        let mut difference = 0u64;
+        self.previous.resize(width * height, 0);
+        for y in 0..height{
+            let row = &data[y * stride .. y * stride + width];
+            let previous = &mut self.previous[y * width .. (y + 1) * width];
+            if compare{
+                difference += row.iter().zip(previous.iter())
+                    .map(|(a, b)| (*a as i64 - *b as i64).abs() as u64)
+                    .sum::<u64>();
+            }
+            previous.copy_from_slice(row);
+        }
+        self.size = (width, height);
+        if !compare{
+            return None;
+        }
+
+        let score = difference as f64 / (255 * width * height) as f64;
+        let too_close = match self.last_change{
+            Some(last_change) => pts != GST_CLOCK_TIME_NONE && pts.saturating_sub(last_change) < self.min_interval,
+            None => false
+        };
+        if score >= self.threshold && !too_close{
+            self.last_change = Some(pts);
+            Some(SceneChange{ pts: pts, score: score })
+        }else{
+            None
+        }
+    }
+}
+
+/// Decodes the audio of `uri` and finds its segments of silence, as with
+/// `SilenceDetector`, for example to find ad breaks or check the start and
+/// end of produced media. `on_detection` is called with each segment as
+/// soon as it ends, and all of them are returned at the end.
+pub fn detect_silence<F: FnMut(&Detection)>(uri: &str, threshold: f64, min_duration: GstClockTime, mut on_detection: F) -> Result<Vec<Detection>>{
+    let mut pipeline = Pipeline::new_from_str(&format!(
+        "uridecodebin uri=\"{}\" ! audioconvert ! audio/x-raw,format={},layout=interleaved ! appsink name=sink sync=false max-buffers=16",
+        uri, F32_FORMAT))?;
+    let mut appsink = start(&mut pipeline, uri)?;
+    let mut detector: Option<SilenceDetector> = None;
+    let mut detections = vec![];
+    while let Some(sample) = pull_sample(&mut appsink){
+        let (buffer, caps) = match (sample.buffer(), sample.caps()){
+            (Some(buffer), Some(caps)) => (buffer, caps),
+            _ => continue
+        };
+        if detector.is_none(){
+            let structure = match caps.structure(0){
+                Some(structure) => structure,
+                None => continue
+            };
+            let rate = structure.get::<i32>("rate").unwrap_or(0);
+            let channels = structure.get::<i32>("channels").unwrap_or(0);
+            if rate <= 0 || channels <= 0{
+                pipeline.set_state(GST_STATE_NULL);
+                return Err(Error::new(0,0,"Unknown audio format"));
+            }
+            detector = Some(SilenceDetector::new(rate as u32, channels as u32, threshold, min_duration));
+        }
+        let detector = detector.as_mut().unwrap();
+        let pts = unsafe{ (*buffer.gst_buffer()).pts };
+        let found = buffer.map_read().map(|map| detector.push(pts, map.data::<f32>())).unwrap_or(vec![]);
+        for detection in found{
+            on_detection(&detection);
+            detections.push(detection);
+        }
+    }
+    finish(&mut pipeline)?;
+    match detector.as_mut().map(|detector| detector.finish()){
+        Some(Some(detection)) => {
+            on_detection(&detection);
+            detections.push(detection);
+        }
+        Some(None) => (),
+        None => return Err(Error::new(0,0,"No audio to analyze"))
+    }
+    Ok(detections)
+}
+
+/// Decodes the video of `uri` and finds its segments of black frames, as
+/// with `BlackDetector`. `on_detection` is called with each segment as
+/// soon as it ends, and all of them are returned at the end.
+pub fn detect_black<F: FnMut(&Detection)>(uri: &str, luma_threshold: u8, pixel_ratio: f64, min_duration: GstClockTime, mut on_detection: F) -> Result<Vec<Detection>>{
+    let mut pipeline = Pipeline::new_from_str(&format!(
+        "uridecodebin uri=\"{}\" ! videoconvert ! video/x-raw,format=GRAY8 ! appsink name=sink sync=false max-buffers=4", uri))?;
+    let mut appsink = start(&mut pipeline, uri)?;
+    let mut detector = BlackDetector::new(luma_threshold, pixel_ratio, min_duration);
+    let mut detections = vec![];
+    let mut frames = 0;
+    while let Some(sample) = pull_sample(&mut appsink){
+        let frame = match sample.video_frame(){
+            Some(frame) => frame,
+            None => continue
+        };
+        frames += 1;
+        if let Some(detection) = detector.push(&frame){
+            on_detection(&detection);
+            detections.push(detection);
+        }
+    }
+    finish(&mut pipeline)?;
+    if frames == 0{
+        return Err(Error::new(0,0,"No video to analyze"));
+    }
+    if let Some(detection) = detector.finish(){
+        on_detection(&detection);
+        detections.push(detection);
+    }
+    Ok(detections)
+}
+
+/// Decodes the video of `uri` and finds its scene changes, as with
+/// `SceneChangeDetector`. `on_change` is called with each one as soon as
+/// it's found, and all of them are returned at the end.
+pub fn detect_scene_changes<F: FnMut(&SceneChange)>(uri: &str, threshold: f64, min_interval: GstClockTime, mut on_change: F) -> Result<Vec<SceneChange>>{
+    let mut pipeline = Pipeline::new_from_str(&format!(
+        "uridecodebin uri=\"{}\" ! videoconvert ! video/x-raw,format=GRAY8 ! appsink name=sink sync=false max-buffers=4", uri))?;
+    let mut appsink = start(&mut pipeline, uri)?;
+    let mut detector = SceneChangeDetector::new(threshold, min_interval);
+    let mut changes = vec![];
+    let mut frames = 0;
+    while let Some(sample) = pull_sample(&mut appsink){
+        let frame = match sample.video_frame(){
+            Some(frame) => frame,
+            None => continue
+        };
+        frames += 1;
+        if let Some(change) = detector.push(&frame){
+            on_change(&change);
+            changes.push(change);
+        }
+    }
+    finish(&mut pipeline)?;
+    if frames == 0{
+        return Err(Error::new(0,0,"No video to analyze"));
+    }
+    Ok(changes)
+}
+
+fn start(pipeline: &mut Pipeline, uri: &str) -> Result<Element>{
+    let appsink = match pipeline.get_by_name("sink"){
+        Some(appsink) => appsink,
+        None => return Err(Error::new(0,0,"Couldn't find appsink"))
+    };
+    if pipeline.play() == GST_STATE_CHANGE_FAILURE{
+        pipeline.set_state(GST_STATE_NULL);
+        return Err(Error::new(0,0,&format!("Couldn't play {}", uri)));
+    }
+    Ok(appsink)
+}
+
+// None at the end of the stream or on errors
+fn pull_sample(appsink: &mut Element) -> Option<Sample>{
+    unsafe{
+        Sample::new(gst_app_sink_pull_sample(appsink.gst_element_mut() as *mut GstAppSink))
+    }
+}
+
+// stops the pipeline, returning the error that ended the stream if any
+fn finish(pipeline: &mut Pipeline) -> Result<()>{
+    let error = match pipeline.bus(){
+        Some(mut bus) => unsafe{
+            let msg = gst_bus_pop_filtered(bus.gst_bus_mut(), GST_MESSAGE_ERROR);
+            if msg == ptr::null_mut(){
+                None
+            }else{
+                let error = Error::new_from_error_message(msg);
+                gst_mini_object_unref(msg as *mut GstMiniObject);
+                Some(error)
+            }
+        },
+        None => None
+    };
+    pipeline.set_state(GST_STATE_NULL);
+    error.map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const SECOND: GstClockTime = 1_000_000_000;
+    const RATE: u32 = 1000;
+
+    // one second of mono audio at `level`
+    fn second(level: f32) -> Vec<f32>{
+        vec![level; RATE as usize]
+    }
+
+    #[test]
+    fn silence_between_sounds(){
+        let mut detector = SilenceDetector::new(RATE, 1, -60.0, SECOND / 2);
+        assert_eq!(detector.push(0, &second(0.5)), vec![]);
+        // -80 dBFS
+        assert_eq!(detector.push(SECOND, &second(0.0001)), vec![]);
+        assert_eq!(detector.push(2 * SECOND, &second(0.5)),
+                   vec![Detection{ kind: DetectionKind::Silence, start: SECOND, end: 2 * SECOND }]);
+        assert_eq!(detector.finish(), None);
+    }
+
+    #[test]
+    fn silence_threshold(){
+        // -50 dBFS isn't below -60
+        let mut detector = SilenceDetector::new(RATE, 1, -60.0, SECOND / 2);
+        detector.push(0, &second(0.00316));
+        assert_eq!(detector.finish(), None);
+
+        // a single loud sample of any channel breaks the silence
+        let mut detector = SilenceDetector::new(RATE, 2, -60.0, SECOND / 2);
+        let mut samples = vec![0.0; 2 * RATE as usize];
+        samples[2 * 600 + 1] = 0.5;
+        let detections = detector.push(0, &samples);
+        assert_eq!(detections, vec![Detection{ kind: DetectionKind::Silence, start: 0, end: 600 * SECOND / RATE as GstClockTime }]);
+        assert_eq!(detector.finish(), None);
+    }
+
+    #[test]
+    fn silence_min_duration(){
+        let mut detector = SilenceDetector::new(RATE, 1, -60.0, SECOND / 2);
+        let mut samples = second(0.5);
+        for sample in &mut samples[200..400]{
+            *sample = 0.0;
+        }
+        assert_eq!(detector.push(0, &samples), vec![]);
+        // silence at the end of the stream, timestamps following the
+        // previous samples
+        assert_eq!(detector.push(GST_CLOCK_TIME_NONE, &second(0.0)), vec![]);
+        assert_eq!(detector.finish(), Some(Detection{ kind: DetectionKind::Silence, start: SECOND, end: 2 * SECOND }));
+    }
+
+    fn plane(dark: usize, width: usize, height: usize, stride: usize) -> Vec<u8>{
+        let mut plane = vec![0; stride * height];
+        for (i, luma) in plane.iter_mut().enumerate(){
+            let (x, y) = (i % stride, i / stride);
+            *luma = if x >= width { 0 } else if y * width + x < dark { 16 } else { 200 };
+        }
+        plane
+    }
+
+    #[test]
+    fn black_pixel_ratio(){
+        // 95 of 100 pixels dark, the padding isn't counted
+        let data = plane(95, 10, 10, 16);
+        assert!(plane_is_black(&data, 16, 10, 10, 16, 0.9));
+        assert!(plane_is_black(&data, 16, 10, 10, 16, 0.95));
+        assert!(!plane_is_black(&data, 16, 10, 10, 16, 0.98));
+        // the threshold is inclusive
+        assert!(!plane_is_black(&data, 16, 10, 10, 15, 0.9));
+    }
+
+    #[test]
+    fn black_segments(){
+        let frame = SECOND / 25;
+        let mut detector = BlackDetector::new(32, 0.98, SECOND / 10);
+        assert_eq!(detector.push_black(0, frame, false), None);
+        assert_eq!(detector.push_black(frame, frame, true), None);
+        assert_eq!(detector.push_black(2 * frame, frame, true), None);
+        // 2 frames are shorter than the minimum duration
+        assert_eq!(detector.push_black(3 * frame, frame, false), None);
+
+        for i in 4..8{
+            assert_eq!(detector.push_black(i * frame, frame, true), None);
+        }
+        assert_eq!(detector.push_black(8 * frame, frame, false),
+                   Some(Detection{ kind: DetectionKind::Black, start: 4 * frame, end: 8 * frame }));
+
+        // black until the end, frames without timestamps following the
+        // previous ones
+        for _ in 0..3{
+            detector.push_black(GST_CLOCK_TIME_NONE, frame, true);
+        }
+        assert_eq!(detector.finish(), Some(Detection{ kind: DetectionKind::Black, start: 9 * frame, end: 12 * frame }));
+    }
+
+    fn luma(value: u8, width: usize, height: usize) -> Vec<u8>{
+        vec![value; width * height]
+    }
+
+    #[test]
+    fn scene_change_score(){
+        let frame = SECOND / 25;
+        let mut detector = SceneChangeDetector::new(0.2, SECOND);
+        // nothing to compare the first frame with
+        assert_eq!(detector.push_luma(0, &luma(0, 8, 8), 8, 8, 8), None);
+        assert_eq!(detector.push_luma(frame, &luma(0, 8, 8), 8, 8, 8), None);
+        // a difference of 51 is a score of 0.2
+        assert_eq!(detector.push_luma(2 * frame, &luma(51, 8, 8), 8, 8, 8),
+                   Some(SceneChange{ pts: 2 * frame, score: 0.2 }));
+        assert_eq!(detector.push_luma(30 * frame, &luma(101, 8, 8), 8, 8, 8), None);
+        assert_eq!(detector.push_luma(60 * frame, &luma(255, 8, 8), 8, 8, 8),
+                   Some(SceneChange{ pts: 60 * frame, score: 154.0 / 255.0 }));
+    }
+
+    #[test]
+    fn scene_change_min_interval(){
+        let frame = SECOND / 25;
+        let mut detector = SceneChangeDetector::new(0.2, SECOND);
+        detector.push_luma(0, &luma(0, 8, 8), 8, 8, 8);
+        assert!(detector.push_luma(frame, &luma(255, 8, 8), 8, 8, 8).is_some());
+        // a flash right after the cut
+        assert_eq!(detector.push_luma(2 * frame, &luma(0, 8, 8), 8, 8, 8), None);
+        assert_eq!(detector.push_luma(25 * frame, &luma(255, 8, 8), 8, 8, 8), None);
+        assert!(detector.push_luma(26 * frame, &luma(0, 8, 8), 8, 8, 8).is_some());
+    }
+
+    #[test]
+    fn scene_change_size(){
+        let mut detector = SceneChangeDetector::new(0.2, 0);
+        detector.push_luma(0, &luma(0, 8, 8), 8, 8, 8);
+        // a new size isn't a scene change, the padding isn't compared
+        assert_eq!(detector.push_luma(SECOND, &luma(255, 16, 4), 16, 8, 4), None);
+        let mut padded = luma(255, 16, 4);
+        for row in padded.chunks_mut(16){
+            for luma in &mut row[8..]{
+                *luma = 0;
+            }
+        }
+        assert_eq!(detector.push_luma(2 * SECOND, &padded, 16, 8, 4), None);
+    }
+}
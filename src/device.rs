@@ -0,0 +1,261 @@
+use ffi::*;
+use util::*;
+use bus::Bus;
+use caps::Caps;
+use element::Element;
+use structure::Structure;
+use reference::Reference;
+use object::Object;
+
+use std::ops::{Deref, DerefMut};
+
+unsafe impl Sync for Device {}
+unsafe impl Send for Device {}
+
+/// A device available in the system, like a camera, a microphone or
+/// a sound card output, as reported by a `DeviceMonitor` or a
+/// DeviceAdded/DeviceRemoved message.
+pub struct Device{
+    device: Object
+}
+
+impl Device{
+    pub unsafe fn new(device: *mut GstDevice) -> Option<Device>{
+        Object::new(device as *mut GstObject).map(|obj| Device{ device: obj })
+    }
+
+    /// Human readable name of the device
+    pub fn display_name(&self) -> String{
+        unsafe{
+            let c_name = gst_device_get_display_name(self.gst_device() as *mut GstDevice);
+            let name = from_c_str!(c_name).to_string();
+            g_free(c_name as gpointer);
+            name
+        }
+    }
+
+    /// Class of the device, a "/" separated list of classes like
+    /// "Video/Source" or "Audio/Sink"
+    pub fn device_class(&self) -> String{
+        unsafe{
+            let c_class = gst_device_get_device_class(self.gst_device() as *mut GstDevice);
+            let class = from_c_str!(c_class).to_string();
+            g_free(c_class as gpointer);
+            class
+        }
+    }
+
+    /// Checks if the device is of all the "/" separated `classes`
+    pub fn has_classes(&self, classes: &str) -> bool{
+        let cclasses = CString::new(classes).unwrap();
+        unsafe{
+            gst_device_has_classes(self.gst_device() as *mut GstDevice, cclasses.as_ptr()) != 0
+        }
+    }
+
+    /// Caps the device can produce or consume
+    pub fn caps(&self) -> Option<Caps>{
+        unsafe{
+            Caps::new_from_gst_caps(gst_device_get_caps(self.gst_device() as *mut GstDevice))
+        }
+    }
+
+    /// Extra metadata of the device like its serial number, bus path
+    /// or the api used to access it.
+    ///
+    /// Requires GStreamer 1.6 or newer.
+    pub fn properties(&self) -> Option<Structure>{
+        unsafe{
+            Structure::new_from_owned_gst_structure(gst_device_get_properties(self.gst_device() as *mut GstDevice))
+        }
+    }
+
+    /// Creates an element already configured to use this device.
+    pub fn create_element(&self, name: &str) -> Option<Element>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let name = if name != "" {
+                cname.as_ptr()
+            } else {
+                ptr::null()
+            };
+            let element = gst_device_create_element(self.gst_device() as *mut GstDevice, name);
+            if element != ptr::null_mut(){
+                gst_object_ref_sink(element as gpointer);
+                Element::new_from_gst_element(element)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Changes an element that was created by another device to use
+    /// this one instead, which allows to rebind an existing pipeline to a
+    /// hot-plugged device without rebuilding it. The element has to be in
+    /// the NULL state.
+    ///
+    /// Returns false if the element can't be reconfigured, in which case a
+    /// new one has to be created with `create_element`.
+    pub fn reconfigure_element(&self, element: &mut Element) -> bool{
+        unsafe{
+            gst_device_reconfigure_element(self.gst_device() as *mut GstDevice, element.gst_element_mut()) != 0
+        }
+    }
+
+    pub unsafe fn gst_device(&self) -> *const GstDevice{
+        self.device.gst_object() as *const GstDevice
+    }
+
+    pub unsafe fn gst_device_mut(&mut self) -> *mut GstDevice{
+        self.device.gst_object_mut() as *mut GstDevice
+    }
+}
+
+impl ::Transfer<GstDevice> for Device{
+    unsafe fn transfer(self) -> *mut GstDevice{
+        self.device.transfer() as *mut GstDevice
+    }
+}
+
+impl Reference for Device{
+    fn reference(&self) -> Device{
+        Device{ device: self.device.reference() }
+    }
+}
+
+impl AsRef<Object> for Device{
+    fn as_ref(&self) -> &Object{
+        &self.device
+    }
+}
+
+impl AsMut<Object> for Device{
+    fn as_mut(&mut self) -> &mut Object{
+        &mut self.device
+    }
+}
+
+impl From<Device> for Object{
+    fn from(d: Device) -> Object{
+        d.device
+    }
+}
+
+impl Deref for Device{
+    type Target = Object;
+    fn deref(&self) -> &Object{
+        &self.device
+    }
+}
+
+impl DerefMut for Device{
+    fn deref_mut(&mut self) -> &mut Object{
+        &mut self.device
+    }
+}
+
+unsafe impl Sync for DeviceMonitor {}
+unsafe impl Send for DeviceMonitor {}
+
+/// Lists the devices available in the system and, once started, posts
+/// DeviceAdded and DeviceRemoved messages on its bus when devices are
+/// plugged or unplugged.
+pub struct DeviceMonitor{
+    monitor: Object
+}
+
+impl DeviceMonitor{
+    pub fn new() -> Option<DeviceMonitor>{
+        unsafe{
+            let monitor = gst_device_monitor_new();
+            if monitor != ptr::null_mut(){
+                gst_object_ref_sink(monitor as gpointer);
+                Object::new(monitor as *mut GstObject).map(|obj| DeviceMonitor{ monitor: obj })
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Only monitor devices of the "/" separated `classes` and matching
+    /// `caps`, any of them can be None to not filter by it.
+    /// Returns an id that can be used to remove the filter.
+    pub fn add_filter(&mut self, classes: Option<&str>, caps: Option<&Caps>) -> u32{
+        let cclasses = classes.map(|classes| CString::new(classes).unwrap());
+        unsafe{
+            let classes = cclasses.as_ref().map(|classes| classes.as_ptr()).unwrap_or(ptr::null());
+            let caps = caps.map(|caps| caps.gst_caps() as *mut GstCaps).unwrap_or(ptr::null_mut());
+            gst_device_monitor_add_filter(self.gst_device_monitor_mut(), classes, caps)
+        }
+    }
+
+    pub fn remove_filter(&mut self, filter_id: u32) -> bool{
+        unsafe{
+            gst_device_monitor_remove_filter(self.gst_device_monitor_mut(), filter_id) != 0
+        }
+    }
+
+    /// Starts monitoring, from now on DeviceAdded and DeviceRemoved
+    /// messages will be posted on the bus
+    pub fn start(&mut self) -> bool{
+        unsafe{
+            gst_device_monitor_start(self.gst_device_monitor_mut()) != 0
+        }
+    }
+
+    pub fn stop(&mut self){
+        unsafe{
+            gst_device_monitor_stop(self.gst_device_monitor_mut());
+        }
+    }
+
+    pub fn bus(&self) -> Option<Bus>{
+        unsafe{
+            Bus::new(gst_device_monitor_get_bus(self.gst_device_monitor() as *mut GstDeviceMonitor))
+        }
+    }
+
+    /// Currently available devices matching the filters
+    pub fn devices(&self) -> Vec<Device>{
+        let mut devices = Vec::new();
+        unsafe{
+            let list = gst_device_monitor_get_devices(self.gst_device_monitor() as *mut GstDeviceMonitor);
+            let mut node = list;
+            while node != ptr::null_mut(){
+                if let Some(device) = Device::new((*node).data as *mut GstDevice){
+                    devices.push(device);
+                }
+                node = (*node).next;
+            }
+            g_list_free(list);
+        }
+        devices
+    }
+
+    pub unsafe fn gst_device_monitor(&self) -> *const GstDeviceMonitor{
+        self.monitor.gst_object() as *const GstDeviceMonitor
+    }
+
+    pub unsafe fn gst_device_monitor_mut(&mut self) -> *mut GstDeviceMonitor{
+        self.monitor.gst_object_mut() as *mut GstDeviceMonitor
+    }
+}
+
+impl Reference for DeviceMonitor{
+    fn reference(&self) -> DeviceMonitor{
+        DeviceMonitor{ monitor: self.monitor.reference() }
+    }
+}
+
+impl Deref for DeviceMonitor{
+    type Target = Object;
+    fn deref(&self) -> &Object{
+        &self.monitor
+    }
+}
+
+impl DerefMut for DeviceMonitor{
+    fn deref_mut(&mut self) -> &mut Object{
+        &mut self.monitor
+    }
+}
@@ -0,0 +1,420 @@
+use ffi::*;
+use element::Element;
+use error::Result;
+use message::Message;
+use reference::Reference;
+
+use std::ffi::CString;
+use std::ops::{Deref, DerefMut};
+
+/// Broadcast standard the frontend has to tune to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum DeliverySystem{
+    Undefined = 0,
+    DvbCAnnexA = 1,
+    DvbCAnnexB = 2,
+    DvbT = 3,
+    Dss = 4,
+    DvbS = 5,
+    DvbS2 = 6,
+    DvbH = 7,
+    IsdbT = 8,
+    IsdbS = 9,
+    IsdbC = 10,
+    Atsc = 11,
+    AtscMh = 12,
+    Dtmb = 13,
+    Cmmb = 14,
+    Dab = 15,
+    DvbT2 = 16,
+    Turbo = 17,
+    DvbCAnnexC = 18,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Modulation{
+    Qpsk = 0,
+    Qam16 = 1,
+    Qam32 = 2,
+    Qam64 = 3,
+    Qam128 = 4,
+    Qam256 = 5,
+    QamAuto = 6,
+    Vsb8 = 7,
+    Vsb16 = 8,
+    Psk8 = 9,
+    Apsk16 = 10,
+    Apsk32 = 11,
+    Dqpsk = 12,
+    Qam4NR = 13,
+}
+
+/// Forward error correction rate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum CodeRate{
+    None = 0,
+    Fec1_2 = 1,
+    Fec2_3 = 2,
+    Fec3_4 = 3,
+    Fec4_5 = 4,
+    Fec5_6 = 5,
+    Fec6_7 = 6,
+    Fec7_8 = 7,
+    Fec8_9 = 8,
+    Auto = 9,
+    Fec3_5 = 10,
+    Fec9_10 = 11,
+    Fec2_5 = 12,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum GuardInterval{
+    Guard1_32 = 0,
+    Guard1_16 = 1,
+    Guard1_8 = 2,
+    Guard1_4 = 3,
+    Auto = 4,
+    Guard1_128 = 5,
+    Guard19_128 = 6,
+    Guard19_256 = 7,
+}
+
+/// Number of OFDM carriers of terrestrial systems
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum TransmissionMode{
+    Mode2k = 0,
+    Mode8k = 1,
+    Auto = 2,
+    Mode4k = 3,
+    Mode1k = 4,
+    Mode16k = 5,
+    Mode32k = 6,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Hierarchy{
+    None = 0,
+    Hierarchy1 = 1,
+    Hierarchy2 = 2,
+    Hierarchy4 = 3,
+    Auto = 4,
+}
+
+/// Spectral inversion
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Inversion{
+    Off = 0,
+    On = 1,
+    Auto = 2,
+}
+
+/// Polarization of a satellite transponder
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Polarity{
+    Horizontal,
+    Vertical,
+}
+
+/// Everything needed to tune to a multiplex. Parameters left to None
+/// keep the dvbsrc defaults, which let the driver detect them where the
+/// standard allows it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TuningParameters{
+    pub delivery_system: DeliverySystem,
+    /// Center frequency in Hz, or the intermediate frequency in kHz for
+    /// satellite systems as the frontend expects it
+    pub frequency: u32,
+    /// Symbol rate in kSymbols/s, for cable and satellite
+    pub symbol_rate: Option<u32>,
+    /// Channel bandwidth in Hz, for terrestrial systems
+    pub bandwidth: Option<u32>,
+    pub modulation: Option<Modulation>,
+    pub polarity: Option<Polarity>,
+    pub code_rate_hp: Option<CodeRate>,
+    pub code_rate_lp: Option<CodeRate>,
+    pub guard_interval: Option<GuardInterval>,
+    pub transmission_mode: Option<TransmissionMode>,
+    pub hierarchy: Option<Hierarchy>,
+    pub inversion: Option<Inversion>,
+}
+
+impl TuningParameters{
+    pub fn new(delivery_system: DeliverySystem, frequency: u32) -> TuningParameters{
+        TuningParameters{
+            delivery_system: delivery_system,
+            frequency: frequency,
+            symbol_rate: None,
+            bandwidth: None,
+            modulation: None,
+            polarity: None,
+            code_rate_hp: None,
+            code_rate_lp: None,
+            guard_interval: None,
+            transmission_mode: None,
+            hierarchy: None,
+            inversion: None,
+        }
+    }
+
+    /// DVB-T multiplex, bandwidth is usually 7 or 8 MHz
+    pub fn dvb_t(frequency: u32, bandwidth: u32) -> TuningParameters{
+        let mut params = TuningParameters::new(DeliverySystem::DvbT, frequency);
+        params.bandwidth = Some(bandwidth);
+        params
+    }
+
+    pub fn dvb_t2(frequency: u32, bandwidth: u32) -> TuningParameters{
+        let mut params = TuningParameters::dvb_t(frequency, bandwidth);
+        params.delivery_system = DeliverySystem::DvbT2;
+        params
+    }
+
+    pub fn dvb_c(frequency: u32, symbol_rate: u32, modulation: Modulation) -> TuningParameters{
+        let mut params = TuningParameters::new(DeliverySystem::DvbCAnnexA, frequency);
+        params.symbol_rate = Some(symbol_rate);
+        params.modulation = Some(modulation);
+        params
+    }
+
+    pub fn dvb_s(frequency: u32, symbol_rate: u32, polarity: Polarity) -> TuningParameters{
+        let mut params = TuningParameters::new(DeliverySystem::DvbS, frequency);
+        params.symbol_rate = Some(symbol_rate);
+        params.polarity = Some(polarity);
+        params
+    }
+
+    pub fn dvb_s2(frequency: u32, symbol_rate: u32, polarity: Polarity, modulation: Modulation) -> TuningParameters{
+        let mut params = TuningParameters::dvb_s(frequency, symbol_rate, polarity);
+        params.delivery_system = DeliverySystem::DvbS2;
+        params.modulation = Some(modulation);
+        params
+    }
+
+    /// ATSC terrestrial channel, always 8VSB
+    pub fn atsc(frequency: u32) -> TuningParameters{
+        let mut params = TuningParameters::new(DeliverySystem::Atsc, frequency);
+        params.modulation = Some(Modulation::Vsb8);
+        params
+    }
+}
+
+/// Frontend status as posted periodically by dvbsrc in
+/// "dvb-frontend-stats" element messages.
+///
+/// The scale of signal, snr and ber depends on the driver, most use
+/// 0 - 65535 for signal and snr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrontendStats{
+    /// Raw fe_status flags
+    pub status: i32,
+    pub signal: i32,
+    pub snr: i32,
+    /// Bit error rate
+    pub ber: i32,
+    /// Uncorrected blocks
+    pub unc: i32,
+    pub lock: bool,
+}
+
+impl FrontendStats{
+    /// Reads the stats from a "dvb-frontend-stats" message, None for
+    /// any other message
+    pub fn from_message(message: &Message) -> Option<FrontendStats>{
+        let name = CString::new("dvb-frontend-stats").unwrap();
+        unsafe{
+            let structure = message.structure();
+            if structure.is_null() || gst_structure_has_name(structure, name.as_ptr()) == 0{
+                return None;
+            }
+            let int_field = |field: &str| -> i32{
+                let cfield = CString::new(field).unwrap();
+                let mut value = 0;
+                gst_structure_get_int(structure, cfield.as_ptr(), &mut value);
+                value
+            };
+            let clock = CString::new("lock").unwrap();
+            let mut lock: gboolean = 0;
+            gst_structure_get_boolean(structure, clock.as_ptr(), &mut lock);
+            Some(FrontendStats{
+                status: int_field("status"),
+                signal: int_field("signal"),
+                snr: int_field("snr"),
+                ber: int_field("ber"),
+                unc: int_field("unc"),
+                lock: lock != 0,
+            })
+        }
+    }
+
+    /// Signal strength in percent, assuming the usual 16 bit scale
+    pub fn signal_percent(&self) -> f32{
+        (self.signal as u16) as f32 * 100. / 65535.
+    }
+
+    /// Signal to noise ratio in percent, assuming the usual 16 bit scale
+    pub fn snr_percent(&self) -> f32{
+        (self.snr as u16) as f32 * 100. / 65535.
+    }
+}
+
+/// Typed wrapper for the dvbsrc element, which tunes a DVB, ATSC or
+/// ISDB frontend and outputs the received transport stream
+pub struct DvbSrc{
+    dvbsrc: Element
+}
+
+unsafe impl Sync for DvbSrc {}
+unsafe impl Send for DvbSrc {}
+
+impl DvbSrc{
+    pub fn new(name: &str) -> Result<DvbSrc>{
+        Element::new("dvbsrc", name).map(|dvbsrc| DvbSrc{ dvbsrc: dvbsrc })
+    }
+
+    pub fn new_from_element(element: Element) -> DvbSrc{
+        DvbSrc{ dvbsrc: element }
+    }
+
+    /// Number of the adapter, /dev/dvb/adapterN
+    pub fn set_adapter(&mut self, adapter: i32){
+        self.dvbsrc.set("adapter", adapter);
+    }
+
+    pub fn adapter(&self) -> i32{
+        self.dvbsrc.get("adapter")
+    }
+
+    /// Number of the frontend of the adapter, /dev/dvb/adapterN/frontendM
+    pub fn set_frontend(&mut self, frontend: i32){
+        self.dvbsrc.set("frontend", frontend);
+    }
+
+    pub fn frontend(&self) -> i32{
+        self.dvbsrc.get("frontend")
+    }
+
+    pub fn set_delivery_system(&mut self, delivery_system: DeliverySystem){
+        self.dvbsrc.set("delsys", delivery_system as i32);
+    }
+
+    /// Sets all the tuning parameters. Takes effect when the element
+    /// starts, or after calling `tune()` while running
+    pub fn set_tuning(&mut self, params: &TuningParameters){
+        self.set_delivery_system(params.delivery_system);
+        self.dvbsrc.set("frequency", params.frequency);
+        if let Some(symbol_rate) = params.symbol_rate{
+            self.dvbsrc.set("symbol-rate", symbol_rate);
+        }
+        if let Some(bandwidth) = params.bandwidth{
+            self.dvbsrc.set("bandwidth-hz", bandwidth);
+        }
+        if let Some(modulation) = params.modulation{
+            self.dvbsrc.set("modulation", modulation as i32);
+        }
+        if let Some(polarity) = params.polarity{
+            self.dvbsrc.set("polarity", match polarity{
+                Polarity::Horizontal => "H",
+                Polarity::Vertical => "V",
+            });
+        }
+        if let Some(code_rate) = params.code_rate_hp{
+            self.dvbsrc.set("code-rate-hp", code_rate as i32);
+        }
+        if let Some(code_rate) = params.code_rate_lp{
+            self.dvbsrc.set("code-rate-lp", code_rate as i32);
+        }
+        if let Some(guard) = params.guard_interval{
+            self.dvbsrc.set("guard", guard as i32);
+        }
+        if let Some(mode) = params.transmission_mode{
+            self.dvbsrc.set("trans-mode", mode as i32);
+        }
+        if let Some(hierarchy) = params.hierarchy{
+            self.dvbsrc.set("hierarchy", hierarchy as i32);
+        }
+        if let Some(inversion) = params.inversion{
+            self.dvbsrc.set("inversion", inversion as i32);
+        }
+    }
+
+    /// Retunes the frontend with the current parameters while running
+    pub fn tune(&mut self){
+        let signal = CString::new("tune").unwrap();
+        unsafe{
+            g_signal_emit_by_name(self.dvbsrc.gst_element_mut() as gpointer, signal.as_ptr());
+        }
+    }
+
+    /// Only receive the given PIDs, usually the PAT, PMT and the streams
+    /// of the wanted program, to lower the bandwidth. An empty list
+    /// receives the whole multiplex.
+    pub fn set_pids(&mut self, pids: &[u16]){
+        let pids = if pids.is_empty(){
+            "8192".to_string()
+        }else{
+            pids.iter().map(|pid| pid.to_string()).collect::<Vec<_>>().join(":")
+        };
+        self.dvbsrc.set("pids", &pids[..]);
+    }
+
+    /// Interval between "dvb-frontend-stats" messages in milliseconds,
+    /// 0 disables them
+    pub fn set_stats_reporting_interval(&mut self, interval_ms: u32){
+        self.dvbsrc.set("stats-reporting-interval", interval_ms);
+    }
+
+    /// Time to wait for the frontend to lock when tuning
+    pub fn set_tuning_timeout(&mut self, timeout_ns: u64){
+        self.dvbsrc.set("tuning-timeout", timeout_ns);
+    }
+}
+
+impl AsRef<Element> for DvbSrc{
+    fn as_ref(&self) -> &Element{
+        &self.dvbsrc
+    }
+}
+
+impl AsMut<Element> for DvbSrc{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.dvbsrc
+    }
+}
+
+impl From<DvbSrc> for Element{
+    fn from(dvbsrc: DvbSrc) -> Element{
+        dvbsrc.dvbsrc
+    }
+}
+
+impl Deref for DvbSrc{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.dvbsrc
+    }
+}
+
+impl DerefMut for DvbSrc{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.dvbsrc
+    }
+}
+
+impl ::Transfer for DvbSrc{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.dvbsrc.transfer()
+    }
+}
+
+impl Reference for DvbSrc{
+    fn reference(&self) -> DvbSrc{
+        DvbSrc{ dvbsrc: self.dvbsrc.reference() }
+    }
+}
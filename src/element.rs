@@ -2,11 +2,19 @@ use ffi::*;
 use bus::Bus;
 use util::*;
 use pad::Pad;
+use caps::Caps;
+use structure::FromFieldValue;
+use event::Event;
+use query::Query;
+use error::{Error, Result};
 use reference::Reference;
 use object::{Object, Property, FromProperty};
+use unwind;
+use cancellable::{self, CancellationToken};
 
 use std::os::raw::c_void;
 use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
 
 unsafe impl Sync for GstElement {}
 unsafe impl Send for GstElement {}
@@ -19,7 +27,10 @@ pub struct Element{
 
 impl Element {
     /// Use a factory `factory_name` to create an element with name `element_name`.
-    pub fn new(factory_name: &str, element_name: &str) -> Option<Element> {
+    ///
+    /// Fails with `Error::MissingElement` if there's no such factory, usually
+    /// because its plugin isn't installed.
+    pub fn new(factory_name: &str, element_name: &str) -> Result<Element> {
         let cname = CString::new(element_name).unwrap();
         let element_cname = CString::new(factory_name).unwrap();
         unsafe{
@@ -31,15 +42,20 @@ impl Element {
             let element = gst_element_factory_make(element_cname.as_ptr(), element_name);
             if element != ptr::null_mut::<GstElement>() {
                 gst_object_ref_sink(mem::transmute(element));
-                Some( Element{element: Object::new(element as * mut GstObject).unwrap()} )
+                Ok( Element{element: Object::new(element as * mut GstObject).unwrap()} )
             } else {
-				println!("Error creating {} return {:?}", factory_name, element);
-                None
+                let factory = gst_element_factory_find(element_cname.as_ptr());
+                if factory == ptr::null_mut(){
+                    Err(Error::MissingElement(factory_name.to_string()))
+                }else{
+                    gst_object_unref(factory as gpointer);
+                    Err(Error::ElementCreation(factory_name.to_string()))
+                }
             }
         }
     }
 
-    pub fn factory_make(element: &str, name: &str) -> Option<Element>{
+    pub fn factory_make(element: &str, name: &str) -> Result<Element>{
 		Element::new(element,name)
 	}
 
@@ -90,7 +106,7 @@ impl Element {
 	///
 	/// If the link has been made using Element::link(), it could have
 	/// created a requestpad, which has to be released using
-	/// gst_element_release_request_pad().
+	/// release_request_pad().
     pub fn unlink(&mut self, dst: &mut Element){
         unsafe{
             gst_element_unlink(self.gst_element_mut(), dst.gst_element_mut());
@@ -168,14 +184,113 @@ impl Element {
         }
     }
 
+    /// Like `get_state` but returns as soon as `token` is cancelled, with
+    /// the state at that moment, usually with GST_STATE_CHANGE_ASYNC.
+    ///
+    /// Waits on the state condition of the element, which cancelling
+    /// signals. Other threads blocked in `get_state` on the same element
+    /// are woken up too and return GST_STATE_CHANGE_FAILURE if the state
+    /// change didn't finish meanwhile
+    pub fn get_state_cancellable(&self, timeout: GstClockTime, token: &CancellationToken) -> (GstState, GstState, GstStateChangeReturn){
+        unsafe{
+            let element = self.gst_element() as *mut GstElement;
+            let addr = element as usize;
+            let _waker = cancellable::on_cancel(token, move || {
+                let element = addr as *mut GstElement;
+                g_mutex_lock(&mut (*element).object.lock);
+                g_cond_broadcast(&mut (*element).state_cond);
+                g_mutex_unlock(&mut (*element).object.lock);
+            });
+            let end_time = if timeout == GST_CLOCK_TIME_NONE{
+                None
+            }else{
+                Some(g_get_monotonic_time().saturating_add((timeout / 1000).min(gint64::max_value() as u64) as gint64))
+            };
+            g_mutex_lock(&mut (*element).object.lock);
+            loop{
+                if token.is_cancelled() || (*element).last_return != GST_STATE_CHANGE_ASYNC{
+                    break;
+                }
+                if let GST_STATE_VOID_PENDING = (*element).pending_state{
+                    break;
+                }
+                match end_time{
+                    Some(end_time) => if g_cond_wait_until(&mut (*element).state_cond, &mut (*element).object.lock, end_time) == 0{
+                        break;
+                    },
+                    None => g_cond_wait(&mut (*element).state_cond, &mut (*element).object.lock),
+                }
+            }
+            g_mutex_unlock(&mut (*element).object.lock);
+        }
+        self.get_state(0)
+    }
+
+    /// Like `get_state` but never blocks, returns the state right now with
+    /// GST_STATE_CHANGE_ASYNC if a state change is still in progress
+    pub fn try_get_state(&self) -> (GstState, GstState, GstStateChangeReturn){
+        self.get_state(0)
+    }
+
+    /// The state the element is in right now, without waiting for a
+    /// state change in progress
+    pub fn current_state(&self) -> GstState{
+        self.try_get_state().0
+    }
+
+    /// The state the element is going to, if a state change is in
+    /// progress, without waiting for it
+    pub fn pending_state(&self) -> Option<GstState>{
+        match self.try_get_state().1{
+            GST_STATE_VOID_PENDING => None,
+            pending => Some(pending)
+        }
+    }
+
+    /// Tries to change the state of the element to the same as its parent,
+    /// used after adding an element to an already running bin
+    pub fn sync_state_with_parent(&mut self) -> bool{
+        unsafe{
+            gst_element_sync_state_with_parent(self.gst_element_mut()) != 0
+        }
+    }
+
+    /// Current running time of the element, the time of its clock minus
+    /// its base time. None if the element has no clock yet, which happens
+    /// until its pipeline goes to PLAYING
+    pub fn running_time(&self) -> Option<GstClockTime>{
+        unsafe{
+            let element = self.gst_element() as *mut GstElement;
+            let clock = gst_element_get_clock(element);
+            if clock == ptr::null_mut(){
+                return None;
+            }
+            let now = gst_clock_get_time(clock);
+            gst_object_unref(clock as gpointer);
+            Some(now.saturating_sub(gst_element_get_base_time(element)))
+        }
+    }
+
     /// Sends an event to an element. If the element doesn't implement an event
     /// handler, the event will be pushed on a random linked sink pad for
     /// downstream events or a random linked source pad for upstream events.
 	///
-	/// This function takes ownership of the provided event so you should
-	/// gst_event_ref() it if you want to reuse the event after this call.
-    pub unsafe fn send_event(&mut self, event: *mut GstEvent) -> bool{
-        gst_element_send_event(self.gst_element_mut(), event) == 1
+	/// The event is consumed, use `reference()` on it first to keep a copy
+	/// to send again.
+    pub fn send_event(&mut self, event: Event) -> bool{
+        unsafe{
+            gst_element_send_event(self.gst_element_mut(), ::Transfer::transfer(event)) == 1
+        }
+    }
+
+    /// Performs a query on the element, like `Query::new_seeking` to know
+    /// if the stream can be seeked. Bins and pipelines forward it to their
+    /// sinks. Returns true if it was answered, the answer is read from the
+    /// query with its parser.
+    pub fn query(&mut self, query: &mut Query) -> bool{
+        unsafe{
+            gst_element_query(self.gst_element_mut(), query.gst_query_mut()) == 1
+        }
     }
 
     /// Simple API to perform a seek on the given element, meaning it just
@@ -244,6 +359,21 @@ impl Element {
         }
     }
 
+    /// Converts `src_value` from `src_format` to `dest_format`, like a
+    /// byte offset in a file to time, using the stream information the
+    /// element has, like the bitrate. None if the element can't convert
+    /// between the formats.
+    pub fn query_convert(&self, src_format: GstFormat, src_value: i64, dest_format: GstFormat) -> Option<i64>{
+        unsafe{
+            let mut dest_value = 0;
+            if gst_element_query_convert(self.gst_element() as *mut GstElement, src_format, src_value, dest_format, &mut dest_value) == 1{
+                Some(dest_value)
+            }else{
+                None
+            }
+        }
+    }
+
     /// Shortcut for query_duration with format == TIME
     pub fn duration_ns(&self) -> Option<i64>{
         self.query_duration(GST_FORMAT_TIME)
@@ -340,6 +470,30 @@ impl Element {
         }
     }
 
+    /// Sets the "ts-offset" of a sink, in nanoseconds added to the
+    /// timestamps of the buffers before rendering them, to correct lip-sync
+    /// at runtime by delaying, with a positive offset, or advancing the
+    /// audio or video sink. Returns false if the element isn't a sink
+    /// with that property
+    pub fn set_ts_offset(&mut self, offset: GstClockTimeDiff) -> bool{
+        if self.has_property("ts-offset"){
+            self.set("ts-offset", offset);
+            true
+        }else{
+            false
+        }
+    }
+
+    /// The "ts-offset" of a sink in nanoseconds, None if the element
+    /// doesn't have that property
+    pub fn ts_offset(&self) -> Option<GstClockTimeDiff>{
+        if self.has_property("ts-offset"){
+            Some(self.get("ts-offset"))
+        }else{
+            None
+        }
+    }
+
     /// shortcut to set_state with state == NULL
     pub fn set_null_state(&mut self) -> GstStateChangeReturn{
         self.set_state(GST_STATE_NULL)
@@ -360,7 +514,9 @@ impl Element {
         self.set_state(GST_STATE_PLAYING)
     }
 
-    /// shortcut to query the state and returns state == PAUSED
+    /// shortcut to query the state and returns state == PAUSED. Waits
+    /// for any state change in progress, which can be forever if it's
+    /// stuck, `current_state` doesn't
     pub fn is_paused(&self) -> bool{
         if let (GST_STATE_PAUSED, _pending, GST_STATE_CHANGE_SUCCESS) = self.get_state(GST_CLOCK_TIME_NONE){
 			true
@@ -369,7 +525,9 @@ impl Element {
 		}
     }
 
-    /// shortcut to query the state and returns state == PLAYING
+    /// shortcut to query the state and returns state == PLAYING. Waits
+    /// for any state change in progress, which can be forever if it's
+    /// stuck, `current_state` doesn't
     pub fn is_playing(&self) -> bool{
         if let (GST_STATE_PLAYING, _pending, GST_STATE_CHANGE_SUCCESS) = self.get_state(GST_CLOCK_TIME_NONE){
 			true
@@ -378,7 +536,8 @@ impl Element {
 		}
     }
 
-    /// shortcut to query the state and returns state == NULL
+    /// shortcut to query the state and returns state == NULL. Waits for
+    /// any state change in progress, `current_state` doesn't
     pub fn is_null_state(&self) -> bool{
         if let (GST_STATE_NULL, _pending, GST_STATE_CHANGE_SUCCESS) = self.get_state(GST_CLOCK_TIME_NONE){
 			true
@@ -387,7 +546,8 @@ impl Element {
 		}
     }
 
-    /// shortcut to query the state and returns state == READY
+    /// shortcut to query the state and returns state == READY. Waits for
+    /// any state change in progress, `current_state` doesn't
     pub fn is_ready_state(&self) -> bool{
         if let (GST_STATE_READY, _pending, GST_STATE_CHANGE_SUCCESS) = self.get_state(GST_CLOCK_TIME_NONE){
 			true
@@ -406,6 +566,95 @@ impl Element {
         }
     }
 
+    /// Requests a new pad from the pad template `template_name`, like
+    /// "src_%u" for tee or "sink_%u" for compositor. `name` is the name of
+    /// the new pad, or None to let the element choose one, and `caps` the
+    /// caps it has to support, if any.
+    ///
+    /// Request pads have to be released with `release_request_pad` once
+    /// they aren't needed anymore, like when removing a tee branch.
+    pub fn request_pad(&mut self, template_name: &str, name: Option<&str>, caps: Option<&Caps>) -> Option<Pad>{
+        let ctemplate_name = CString::new(template_name).unwrap();
+        let cname = name.map(|name| CString::new(name).unwrap());
+        unsafe{
+            let element = self.gst_element_mut();
+            let class = (*(element as *mut GTypeInstance)).g_class as *mut GstElementClass;
+            let template = gst_element_class_get_pad_template(class, ctemplate_name.as_ptr());
+            if template == ptr::null_mut(){
+                return None;
+            }
+            let cname = cname.as_ref().map(|cname| cname.as_ptr()).unwrap_or(ptr::null());
+            let caps = caps.map(|caps| caps.gst_caps()).unwrap_or(ptr::null());
+            Pad::new(gst_element_request_pad(element, template, cname, caps))
+        }
+    }
+
+    /// Requests a new pad by name, which can be a template name like
+    /// "src_%u" or the name of a specific pad like "src_2"
+    ///
+    /// Request pads have to be released with `release_request_pad` once
+    /// they aren't needed anymore.
+    pub fn request_pad_simple(&mut self, name: &str) -> Option<Pad>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            Pad::new(gst_element_get_request_pad(self.gst_element_mut(), cname.as_ptr()))
+        }
+    }
+
+    /// Releases a pad obtained with `request_pad` or `request_pad_simple`.
+    /// The pad should be unlinked first, and is removed from the element.
+    pub fn release_request_pad(&mut self, mut pad: Pad){
+        unsafe{
+            gst_element_release_request_pad(self.gst_element_mut(), pad.gst_pad_mut());
+        }
+    }
+
+    /// Calls `callback` with the element and the new pad every time the
+    /// element adds a pad, like decodebin and demuxers do for each stream
+    /// they find. It's called from the streaming threads, one call at a
+    /// time.
+    ///
+    /// Returns the id of the handler, to be used with `disconnect`.
+    pub fn connect_pad_added<F>(&mut self, callback: F) -> u64
+        where F: FnMut(&mut Element, &mut Pad) + Send + 'static{
+        self.connect_pad_signal("pad-added", Box::new(callback))
+    }
+
+    /// Calls `callback` with the element and the pad every time the
+    /// element removes a pad.
+    ///
+    /// Returns the id of the handler, to be used with `disconnect`.
+    pub fn connect_pad_removed<F>(&mut self, callback: F) -> u64
+        where F: FnMut(&mut Element, &mut Pad) + Send + 'static{
+        self.connect_pad_signal("pad-removed", Box::new(callback))
+    }
+
+    /// Calls `callback` once the element has added all the pads it will
+    /// create for the current stream, like when decodebin has found all
+    /// the streams of a file.
+    ///
+    /// Returns the id of the handler, to be used with `disconnect`.
+    pub fn connect_no_more_pads<F>(&mut self, callback: F) -> u64
+        where F: FnMut(&mut Element) + Send + 'static{
+        let callback: Box<ElementCallback> = Box::new(Mutex::new(Box::new(callback)));
+        let signal = CString::new("no-more-pads").unwrap();
+        unsafe{
+            g_signal_connect_data(self.gst_element_mut() as gpointer, signal.as_ptr(),
+                                  mem::transmute(element_signal as extern "C" fn(*mut GstElement, gpointer)),
+                                  Box::into_raw(callback) as gpointer, Some(free_element_callback), 0) as u64
+        }
+    }
+
+    fn connect_pad_signal(&mut self, signal: &str, callback: Box<dyn FnMut(&mut Element, &mut Pad) + Send>) -> u64{
+        let callback: Box<PadCallback> = Box::new(Mutex::new(callback));
+        let signal = CString::new(signal).unwrap();
+        unsafe{
+            g_signal_connect_data(self.gst_element_mut() as gpointer, signal.as_ptr(),
+                                  mem::transmute(pad_signal as extern "C" fn(*mut GstElement, *mut GstPad, gpointer)),
+                                  Box::into_raw(callback) as gpointer, Some(free_pad_callback), 0) as u64
+        }
+    }
+
     /// Returns a const raw pointer to the internal GstElement
     pub unsafe fn gst_element(&self) -> *const GstElement{
         self.element.gst_object() as *const GstElement
@@ -417,6 +666,43 @@ impl Element {
     }
 }
 
+type PadCallback = Mutex<Box<dyn FnMut(&mut Element, &mut Pad) + Send>>;
+type ElementCallback = Mutex<Box<dyn FnMut(&mut Element) + Send>>;
+
+extern "C" fn pad_signal(element: *mut GstElement, pad: *mut GstPad, data: gpointer){
+    unsafe{
+        let callback = &*(data as *const PadCallback);
+        let element = Element::new_from_gst_element(gst_object_ref(element as gpointer) as *mut GstElement);
+        let pad = Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad);
+        if let (Some(mut element), Some(mut pad)) = (element, pad){
+            unwind::guard(element.gst_element_mut() as *mut GstObject, (),
+                          || (&mut *callback.lock().unwrap())(&mut element, &mut pad));
+        }
+    }
+}
+
+extern "C" fn free_pad_callback(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut PadCallback));
+    }
+}
+
+extern "C" fn element_signal(element: *mut GstElement, data: gpointer){
+    unsafe{
+        let callback = &*(data as *const ElementCallback);
+        if let Some(mut element) = Element::new_from_gst_element(gst_object_ref(element as gpointer) as *mut GstElement){
+            let src = element.gst_element_mut() as *mut GstObject;
+            unwind::guard(src, (), || (&mut *callback.lock().unwrap())(&mut element));
+        }
+    }
+}
+
+extern "C" fn free_element_callback(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut ElementCallback));
+    }
+}
+
 impl ::Transfer for Element{
     unsafe fn transfer(self) -> *mut GstElement{
         self.element.transfer() as *mut GstElement
@@ -475,6 +761,12 @@ impl ::FromGValue for Element{
 }
 
 
+impl FromFieldValue for Element{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<Element>{
+        <Element as ::FromGValue>::from_gvalue(gvalue)
+    }
+}
+
 impl<'a> Property for &'a Element{
     type Target = *mut GstElement;
     #[inline]
@@ -0,0 +1,300 @@
+use ffi::*;
+use util::*;
+use caps::Caps;
+use element::Element;
+use error::{Error, Result};
+use reference::Reference;
+use object::Object;
+
+use std::ops::{Deref, DerefMut};
+
+pub const ELEMENT_FACTORY_TYPE_DECODER: GstElementFactoryListType = 1 << 0;
+pub const ELEMENT_FACTORY_TYPE_ENCODER: GstElementFactoryListType = 1 << 1;
+pub const ELEMENT_FACTORY_TYPE_SINK: GstElementFactoryListType = 1 << 2;
+pub const ELEMENT_FACTORY_TYPE_SRC: GstElementFactoryListType = 1 << 3;
+pub const ELEMENT_FACTORY_TYPE_MUXER: GstElementFactoryListType = 1 << 4;
+pub const ELEMENT_FACTORY_TYPE_DEMUXER: GstElementFactoryListType = 1 << 5;
+pub const ELEMENT_FACTORY_TYPE_PARSER: GstElementFactoryListType = 1 << 6;
+pub const ELEMENT_FACTORY_TYPE_PAYLOADER: GstElementFactoryListType = 1 << 7;
+pub const ELEMENT_FACTORY_TYPE_DEPAYLOADER: GstElementFactoryListType = 1 << 8;
+pub const ELEMENT_FACTORY_TYPE_FORMATTER: GstElementFactoryListType = 1 << 9;
+pub const ELEMENT_FACTORY_TYPE_DECRYPTOR: GstElementFactoryListType = 1 << 10;
+pub const ELEMENT_FACTORY_TYPE_ENCRYPTOR: GstElementFactoryListType = 1 << 11;
+pub const ELEMENT_FACTORY_TYPE_MEDIA_VIDEO: GstElementFactoryListType = 1 << 49;
+pub const ELEMENT_FACTORY_TYPE_MEDIA_AUDIO: GstElementFactoryListType = 1 << 50;
+pub const ELEMENT_FACTORY_TYPE_MEDIA_IMAGE: GstElementFactoryListType = 1 << 51;
+pub const ELEMENT_FACTORY_TYPE_MEDIA_SUBTITLE: GstElementFactoryListType = 1 << 52;
+pub const ELEMENT_FACTORY_TYPE_MEDIA_METADATA: GstElementFactoryListType = 1 << 53;
+pub const ELEMENT_FACTORY_TYPE_ANY: GstElementFactoryListType = (1 << 49) - 1;
+pub const ELEMENT_FACTORY_TYPE_MEDIA_ANY: GstElementFactoryListType = !0 << 48;
+pub const ELEMENT_FACTORY_TYPE_DECODABLE: GstElementFactoryListType =
+    ELEMENT_FACTORY_TYPE_DECODER | ELEMENT_FACTORY_TYPE_DEMUXER | ELEMENT_FACTORY_TYPE_DEPAYLOADER |
+    ELEMENT_FACTORY_TYPE_PARSER | ELEMENT_FACTORY_TYPE_DECRYPTOR;
+
+unsafe impl Sync for ElementFactory {}
+unsafe impl Send for ElementFactory {}
+
+/// Factory used to create instances of an element type. Besides creating
+/// elements it allows to inspect what an element can do (its caps, class
+/// and rank) before instantiating it, which allows applications to take
+/// autoplugging-like decisions.
+pub struct ElementFactory{
+    factory: Object
+}
+
+impl ElementFactory{
+    pub unsafe fn new(factory: *mut GstElementFactory) -> Option<ElementFactory>{
+        Object::new(factory as *mut GstObject).map(|obj| ElementFactory{ factory: obj })
+    }
+
+    /// Search for an element factory with the given name
+    pub fn find(name: &str) -> Option<ElementFactory>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            ElementFactory::new(gst_element_factory_find(cname.as_ptr()))
+        }
+    }
+
+    /// Returns all the factories of the given type (a combination of
+    /// `ELEMENT_FACTORY_TYPE_*`) with at least `min_rank` sorted by
+    /// decreasing rank
+    pub fn list(list_type: GstElementFactoryListType, min_rank: GstRank) -> Vec<ElementFactory>{
+        unsafe{
+            let list = gst_element_factory_list_get_elements(list_type, min_rank);
+            let factories = ElementFactory::vec_from_glist(list);
+            gst_plugin_feature_list_free(list);
+            factories
+        }
+    }
+
+    /// Filters `factories` keeping only the ones that have a pad template
+    /// in `direction` that can handle `caps`. If `subset_only` is true only
+    /// the factories whose pads can handle all of `caps` are returned.
+    /// The order of the passed factories is preserved.
+    pub fn filter(factories: &[ElementFactory], caps: &Caps, direction: GstPadDirection, subset_only: bool) -> Vec<ElementFactory>{
+        unsafe{
+            let list = factories.iter().rev().fold(ptr::null_mut(), |list, factory| {
+                g_list_prepend(list, factory.gst_element_factory() as gpointer)
+            });
+            let filtered = gst_element_factory_list_filter(list, caps.gst_caps(), direction, subset_only as gboolean);
+            g_list_free(list);
+            let factories = ElementFactory::vec_from_glist(filtered);
+            gst_plugin_feature_list_free(filtered);
+            factories
+        }
+    }
+
+    /// All the factories, sorted by decreasing rank, that can handle
+    /// `caps` on a pad with the given direction
+    pub fn list_filtered(caps: &Caps, direction: GstPadDirection) -> Vec<ElementFactory>{
+        let factories = ElementFactory::list(ELEMENT_FACTORY_TYPE_ANY, GST_RANK_NONE);
+        ElementFactory::filter(&factories, caps, direction, false)
+    }
+
+    /// Highest ranked decoder that accepts `caps` as input
+    pub fn best_decoder_for(caps: &Caps) -> Option<ElementFactory>{
+        let decoders = ElementFactory::list(ELEMENT_FACTORY_TYPE_DECODER, GST_RANK_MARGINAL);
+        ElementFactory::filter(&decoders, caps, GST_PAD_SINK, false).into_iter().next()
+    }
+
+    /// Highest ranked encoder that can produce `caps`, for example
+    /// "video/x-h265" to get any available H.265 encoder
+    pub fn best_encoder_for(caps: &Caps) -> Option<ElementFactory>{
+        let encoders = ElementFactory::list(ELEMENT_FACTORY_TYPE_ENCODER, GST_RANK_MARGINAL);
+        ElementFactory::filter(&encoders, caps, GST_PAD_SRC, false).into_iter().next()
+    }
+
+    /// Highest ranked RTP payloader for encoded `caps`, like
+    /// "video/x-h264"
+    pub fn best_payloader_for(caps: &Caps) -> Option<ElementFactory>{
+        let payloaders = ElementFactory::list(ELEMENT_FACTORY_TYPE_PAYLOADER, GST_RANK_MARGINAL);
+        ElementFactory::filter(&payloaders, caps, GST_PAD_SINK, false).into_iter().next()
+    }
+
+    /// Highest ranked RTP depayloader for RTP `caps`, like
+    /// "application/x-rtp,media=video,encoding-name=H264"
+    pub fn best_depayloader_for(caps: &Caps) -> Option<ElementFactory>{
+        let depayloaders = ElementFactory::list(ELEMENT_FACTORY_TYPE_DEPAYLOADER, GST_RANK_MARGINAL);
+        ElementFactory::filter(&depayloaders, caps, GST_PAD_SINK, false).into_iter().next()
+    }
+
+    unsafe fn vec_from_glist(list: *mut GList) -> Vec<ElementFactory>{
+        let mut factories = Vec::new();
+        let mut node = list;
+        while node != ptr::null_mut(){
+            gst_object_ref((*node).data);
+            if let Some(factory) = ElementFactory::new((*node).data as *mut GstElementFactory){
+                factories.push(factory);
+            }
+            node = (*node).next;
+        }
+        factories
+    }
+
+    /// Creates a new element of this factory's type named `name`
+    pub fn create(&self, name: &str) -> Result<Element>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let name = if name != "" {
+                cname.as_ptr()
+            } else {
+                ptr::null()
+            };
+            let element = gst_element_factory_create(self.gst_element_factory() as *mut GstElementFactory, name);
+            if element != ptr::null_mut(){
+                gst_object_ref_sink(element as gpointer);
+                Ok(Element::new_from_gst_element(element).unwrap())
+            }else{
+                Err(Error::ElementCreation(self.name()))
+            }
+        }
+    }
+
+    pub fn rank(&self) -> u32{
+        unsafe{
+            gst_plugin_feature_get_rank(self.gst_element_factory() as *mut GstPluginFeature)
+        }
+    }
+
+    /// Changes the rank of this factory which modifies its priority
+    /// when autoplugging
+    pub fn set_rank(&mut self, rank: u32){
+        unsafe{
+            gst_plugin_feature_set_rank(self.gst_element_factory_mut() as *mut GstPluginFeature, rank);
+        }
+    }
+
+    /// Name of the plugin that provides this factory
+    pub fn plugin_name(&self) -> Option<String>{
+        unsafe{
+            let name = gst_plugin_feature_get_plugin_name(self.gst_element_factory() as *mut GstPluginFeature);
+            if name != ptr::null(){
+                Some(from_c_str!(name).to_string())
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Get a metadata entry like "long-name", "klass", "description"
+    /// or "author"
+    pub fn metadata(&self, key: &str) -> Option<String>{
+        let ckey = CString::new(key).unwrap();
+        unsafe{
+            let value = gst_element_factory_get_metadata(self.gst_element_factory() as *mut GstElementFactory, ckey.as_ptr());
+            if value != ptr::null(){
+                Some(from_c_str!(value).to_string())
+            }else{
+                None
+            }
+        }
+    }
+
+    pub fn long_name(&self) -> Option<String>{
+        self.metadata("long-name")
+    }
+
+    /// "/" separated classification of the element like
+    /// "Codec/Decoder/Video"
+    pub fn klass(&self) -> Option<String>{
+        self.metadata("klass")
+    }
+
+    pub fn description(&self) -> Option<String>{
+        self.metadata("description")
+    }
+
+    /// Checks if the factory is of the given type, a combination of
+    /// `ELEMENT_FACTORY_TYPE_*`
+    pub fn is_type(&self, list_type: GstElementFactoryListType) -> bool{
+        unsafe{
+            gst_element_factory_list_is_type(self.gst_element_factory() as *mut GstElementFactory, list_type) != 0
+        }
+    }
+
+    pub fn can_sink_any_caps(&self, caps: &Caps) -> bool{
+        unsafe{
+            gst_element_factory_can_sink_any_caps(self.gst_element_factory() as *mut GstElementFactory, caps.gst_caps()) != 0
+        }
+    }
+
+    pub fn can_sink_all_caps(&self, caps: &Caps) -> bool{
+        unsafe{
+            gst_element_factory_can_sink_all_caps(self.gst_element_factory() as *mut GstElementFactory, caps.gst_caps()) != 0
+        }
+    }
+
+    pub fn can_src_any_caps(&self, caps: &Caps) -> bool{
+        unsafe{
+            gst_element_factory_can_src_any_caps(self.gst_element_factory() as *mut GstElementFactory, caps.gst_caps()) != 0
+        }
+    }
+
+    pub fn can_src_all_caps(&self, caps: &Caps) -> bool{
+        unsafe{
+            gst_element_factory_can_src_all_caps(self.gst_element_factory() as *mut GstElementFactory, caps.gst_caps()) != 0
+        }
+    }
+
+    /// Caps of all the pad templates of the factory in `direction`, like
+    /// the formats a decoder accepts for GST_PAD_SINK
+    pub fn template_caps(&self, direction: GstPadDirection) -> Caps{
+        let mut caps = Caps::new_empty();
+        unsafe{
+            let mut node = gst_element_factory_get_static_pad_templates(self.gst_element_factory() as *mut GstElementFactory);
+            while node != ptr::null(){
+                let template = (*node).data as *mut GstStaticPadTemplate;
+                if (*template).direction == direction{
+                    gst_caps_append(caps.gst_caps_mut(), gst_static_caps_get(&mut (*template).static_caps));
+                }
+                node = (*node).next;
+            }
+        }
+        caps
+    }
+
+    pub unsafe fn gst_element_factory(&self) -> *const GstElementFactory{
+        self.factory.gst_object() as *const GstElementFactory
+    }
+
+    pub unsafe fn gst_element_factory_mut(&mut self) -> *mut GstElementFactory{
+        self.factory.gst_object_mut() as *mut GstElementFactory
+    }
+}
+
+impl ::Transfer<GstElementFactory> for ElementFactory{
+    unsafe fn transfer(self) -> *mut GstElementFactory{
+        self.factory.transfer() as *mut GstElementFactory
+    }
+}
+
+impl Reference for ElementFactory{
+    fn reference(&self) -> ElementFactory{
+        ElementFactory{ factory: self.factory.reference() }
+    }
+}
+
+impl AsRef<Object> for ElementFactory{
+    fn as_ref(&self) -> &Object{
+        &self.factory
+    }
+}
+
+impl AsMut<Object> for ElementFactory{
+    fn as_mut(&mut self) -> &mut Object{
+        &mut self.factory
+    }
+}
+
+impl Deref for ElementFactory{
+    type Target = Object;
+    fn deref(&self) -> &Object{
+        &self.factory
+    }
+}
+
+impl DerefMut for ElementFactory{
+    fn deref_mut(&mut self) -> &mut Object{
+        &mut self.factory
+    }
+}
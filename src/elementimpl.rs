@@ -0,0 +1,303 @@
+use ffi::*;
+use util::*;
+use buffer::Buffer;
+use element::Element;
+use error::Result;
+use event::Event;
+use pad::Pad;
+use query::Query;
+use subclass::{self, ClassData, ElementMetadata, PadTemplate};
+use unwind;
+use Transfer;
+
+use std::marker::PhantomData;
+
+/// The GstElement of an element registered with `register_element`,
+/// passed to its implementation to push data and events from its pads
+pub struct ElementHandle<'a>{
+    element: *mut GstElement,
+    _lifetime: PhantomData<&'a mut GstElement>,
+}
+
+impl<'a> ElementHandle<'a>{
+    /// The pad `name` of the element
+    pub fn pad(&self, name: &str) -> Option<Pad>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            Pad::new(gst_element_get_static_pad(self.element, cname.as_ptr()))
+        }
+    }
+
+    /// Pushes `buffer` downstream from the src pad `pad`
+    pub fn push(&mut self, pad: &str, buffer: Buffer) -> GstFlowReturn{
+        match self.pad(pad){
+            Some(mut pad) => unsafe{ gst_pad_push(pad.gst_pad_mut(), buffer.transfer()) },
+            None => GST_FLOW_NOT_LINKED
+        }
+    }
+
+    /// Pushes `event` from `pad`, downstream for src pads and upstream for
+    /// sink pads
+    pub fn push_event(&mut self, pad: &str, event: Event) -> bool{
+        match self.pad(pad){
+            Some(mut pad) => unsafe{ gst_pad_push_event(pad.gst_pad_mut(), event.transfer()) != 0 },
+            None => false
+        }
+    }
+
+    /// What GStreamer does with events the element doesn't handle:
+    /// forwards them through the pads of the other direction, and sets
+    /// the caps of the pad for caps events
+    pub fn event_default(&mut self, pad: &Pad, event: Event) -> bool{
+        unsafe{
+            gst_pad_event_default(pad.gst_pad() as *mut GstPad, self.element as *mut GstObject, event.transfer()) != 0
+        }
+    }
+
+    /// What GStreamer does with queries the element doesn't answer:
+    /// forwards them through the pads of the other direction
+    pub fn query_default(&mut self, pad: &Pad, query: &mut Query) -> bool{
+        unsafe{
+            gst_pad_query_default(pad.gst_pad() as *mut GstPad, self.element as *mut GstObject, query.gst_query_mut()) != 0
+        }
+    }
+
+    /// Creates a pad from the sometimes template `template`, like
+    /// "src_%u", named `name`, and adds it to the element, which
+    /// announces it with pad-added. Its data and events are handled by the
+    /// implementation as for the always pads
+    pub fn add_pad(&mut self, template: &str, name: &str) -> Option<Pad>{
+        let ctemplate = CString::new(template).unwrap();
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let class = (*(self.element as *mut GTypeInstance)).g_class as *mut GstElementClass;
+            let template = gst_element_class_get_pad_template(class, ctemplate.as_ptr());
+            if template == ptr::null_mut(){
+                return None;
+            }
+            let pad = new_pad(template, cname.as_ptr());
+            gst_pad_set_active(pad, 1);
+            let ret = Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad);
+            if gst_element_add_pad(self.element, pad) == 0{
+                return None;
+            }
+            ret
+        }
+    }
+
+    /// The element itself, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.element as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// An element written in rust, registered with `register_element`, like
+/// a filter that modifies the buffers going through it or a demuxer.
+///
+/// The buffers arriving at the sink pads are passed to `chain`, which
+/// pushes its output from the src pads with `ElementHandle::push`. Events
+/// and queries are handled by GStreamer unless the implementation
+/// overrides `sink_event`, `src_event` or `query`.
+///
+/// The methods are called at the same time from the streaming threads and
+/// from the application thread for state changes, seeks and queries, and
+/// again from inside a push when downstream sends events or queries back,
+/// so they take `&self`. The state of the element goes behind a Mutex,
+/// which must not be held while pushing: a push blocks while the sinks
+/// wait for preroll, and the seek or query that would unblock them would
+/// wait for the lock forever.
+pub trait ElementImpl: Send + Sync + 'static{
+    /// Called for every state change, before it's applied to the pads
+    /// when going up, from NULL to PLAYING, and after it when going down.
+    /// Resources are usually allocated going from NULL to READY or from
+    /// READY to PAUSED and released in the opposite change
+    fn change_state(&self, _element: &mut ElementHandle, _transition: GstStateChange) -> GstStateChangeReturn{
+        GST_STATE_CHANGE_SUCCESS
+    }
+
+    /// Processes a buffer arriving at the sink pad `pad`
+    fn chain(&self, element: &mut ElementHandle, pad: &Pad, buffer: Buffer) -> GstFlowReturn;
+
+    /// Handles an event arriving at the sink pad `pad` from upstream, like
+    /// caps, segment or EOS
+    fn sink_event(&self, element: &mut ElementHandle, pad: &Pad, event: Event) -> bool{
+        element.event_default(pad, event)
+    }
+
+    /// Handles an event arriving at the src pad `pad` from downstream,
+    /// like seeks or QoS
+    fn src_event(&self, element: &mut ElementHandle, pad: &Pad, event: Event) -> bool{
+        element.event_default(pad, event)
+    }
+
+    /// Answers a query arriving at `pad`, like the position, duration or
+    /// the caps it accepts
+    fn query(&self, element: &mut ElementHandle, pad: &Pad, query: &mut Query) -> bool{
+        element.query_default(pad, query)
+    }
+}
+
+type NewElement = dyn Fn() -> Box<dyn ElementImpl> + Send + Sync;
+
+type Instance = Box<dyn ElementImpl>;
+
+/// Registers a GstElement subclass as the element `name`, with the pads
+/// of `templates`, so the element implemented by the objects returned by
+/// `new` can be used in pipelines and by parse_launch.
+///
+/// The always pads are created with each instance, `new` is called for
+/// each of them.
+pub fn register_element<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                              templates: &[PadTemplate], new: F) -> Result<()>
+    where T: ElementImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewElement> = Box::new(move || Box::new(new()) as Box<dyn ElementImpl>);
+    let class_data = ClassData::new_with_templates(metadata, templates, new);
+    unsafe{
+        subclass::register_element(gst_element_get_type(), "GstRsElement", name, rank, class_data,
+                                   Some(class_init), Some(instance_init)).map(|_| ())
+    }
+}
+
+extern "C" fn class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewElement>);
+        class_data.install(g_class as *mut GstElementClass);
+        (*(g_class as *mut GstElementClass)).change_state = Some(change_state);
+    }
+}
+
+extern "C" fn instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewElement>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: Instance = (class_data.new)();
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+            for name in class_data.always_pads(){
+                let template = gst_element_class_get_pad_template(g_class as *mut GstElementClass, name.as_ptr());
+                gst_element_add_pad(instance as *mut GstElement, new_pad(template, name.as_ptr()));
+            }
+        }
+    }
+}
+
+unsafe fn new_pad(template: *mut GstPadTemplate, name: *const gchar) -> *mut GstPad{
+    let pad = gst_pad_new_from_template(template, name);
+    if (*pad).direction == GST_PAD_SINK{
+        gst_pad_set_chain_function_full(pad, Some(pad_chain), ptr::null_mut(), None);
+    }
+    gst_pad_set_event_function_full(pad, Some(pad_event), ptr::null_mut(), None);
+    gst_pad_set_query_function_full(pad, Some(pad_query), ptr::null_mut(), None);
+    pad
+}
+
+// None if the element has no implementation, `panicked` if the method
+// panics
+unsafe fn with_impl<R, F>(element: *mut GstElement, panicked: R, f: F) -> Option<R>
+    where F: FnOnce(&dyn ElementImpl, &mut ElementHandle) -> R{
+    let imp = subclass::instance_impl::<Instance>(element as gpointer)?;
+    let mut handle = ElementHandle{ element: element, _lifetime: PhantomData };
+    Some(unwind::guard(element as *mut GstObject, panicked, || f(&**imp, &mut handle)))
+}
+
+unsafe fn parent_change_state(element: *mut GstElement, transition: GstStateChange) -> GstStateChangeReturn{
+    let class = (*(element as *mut GTypeInstance)).g_class;
+    let parent = g_type_class_peek_parent(class as gpointer) as *const GstElementClass;
+    match (*parent).change_state{
+        Some(change_state) => change_state(element, transition),
+        None => GST_STATE_CHANGE_SUCCESS
+    }
+}
+
+extern "C" fn change_state(element: *mut GstElement, transition: GstStateChange) -> GstStateChangeReturn{
+    unsafe{
+        let imp_change_state = || with_impl(element, GST_STATE_CHANGE_FAILURE, |imp, element| imp.change_state(element, transition))
+            .unwrap_or(GST_STATE_CHANGE_SUCCESS);
+        let (ret, parent_ret) = match transition{
+            GST_STATE_CHANGE_NULL_TO_READY | GST_STATE_CHANGE_READY_TO_PAUSED | GST_STATE_CHANGE_PAUSED_TO_PLAYING => {
+                let ret = imp_change_state();
+                if ret == GST_STATE_CHANGE_FAILURE{
+                    return ret;
+                }
+                (ret, parent_change_state(element, transition))
+            }
+            _ => {
+                let parent_ret = parent_change_state(element, transition);
+                (imp_change_state(), parent_ret)
+            }
+        };
+        if ret == GST_STATE_CHANGE_FAILURE || parent_ret == GST_STATE_CHANGE_FAILURE{
+            GST_STATE_CHANGE_FAILURE
+        }else if ret == GST_STATE_CHANGE_NO_PREROLL{
+            // live elements don't preroll, whatever the pads say
+            ret
+        }else{
+            parent_ret
+        }
+    }
+}
+
+extern "C" fn pad_chain(pad: *mut GstPad, parent: *mut GstObject, buffer: *mut GstBuffer) -> GstFlowReturn{
+    unsafe{
+        let buffer = match Buffer::new(buffer){
+            Some(buffer) => buffer,
+            None => return GST_FLOW_ERROR
+        };
+        let pad = match Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad){
+            Some(pad) => pad,
+            None => return GST_FLOW_ERROR
+        };
+        with_impl(parent as *mut GstElement, GST_FLOW_ERROR, |imp, element| imp.chain(element, &pad, buffer))
+            .unwrap_or(GST_FLOW_ERROR)
+    }
+}
+
+extern "C" fn pad_event(pad: *mut GstPad, parent: *mut GstObject, event: *mut GstEvent) -> gboolean{
+    unsafe{
+        let mut event = Event::new(event);
+        let gst_pad = pad;
+        let pad = match Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad){
+            Some(pad) => pad,
+            None => return 0
+        };
+        let ret = with_impl(parent as *mut GstElement, false, |imp, element| match event.take(){
+            Some(event) => if (*gst_pad).direction == GST_PAD_SINK{
+                imp.sink_event(element, &pad, event)
+            }else{
+                imp.src_event(element, &pad, event)
+            },
+            None => false
+        });
+        match (ret, event){
+            (Some(ret), _) => ret as gboolean,
+            (None, Some(event)) => gst_pad_event_default(gst_pad, parent, event.transfer()),
+            (None, None) => 0
+        }
+    }
+}
+
+extern "C" fn pad_query(pad: *mut GstPad, parent: *mut GstObject, query: *mut GstQuery) -> gboolean{
+    unsafe{
+        // the query is borrowed, and has to stay writable to be answered
+        let mut query = match Query::new(query){
+            Some(query) => query,
+            None => return 0
+        };
+        let gst_pad = pad;
+        let pad = match Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad){
+            Some(pad) => pad,
+            None => {
+                query.transfer();
+                return 0
+            }
+        };
+        let ret = match with_impl(parent as *mut GstElement, false, |imp, element| imp.query(element, &pad, &mut query)){
+            Some(ret) => ret as gboolean,
+            None => gst_pad_query_default(gst_pad, parent, query.gst_query_mut())
+        };
+        query.transfer();
+        ret
+    }
+}
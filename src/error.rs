@@ -1,13 +1,27 @@
 use ffi::*;
 use std::result;
+use std::error;
 use util::*;
-use std::fmt::{self,Debug,Formatter};
+use std::fmt::{self,Debug,Display,Formatter};
 
 unsafe impl Send for GError {}
-unsafe impl Send for Error {}
 
-pub struct Error{
-    error: *mut GError
+/// Errors returned by the crate, either reported by GStreamer as a GError
+/// or found while creating elements and pipelines
+#[derive(Clone)]
+pub enum Error{
+    /// Error reported by GStreamer or GLib, with the domain, code and
+    /// message of its GError
+    GError{domain: u32, code: i32, message: String},
+    /// There's no element factory with this name, usually because the
+    /// plugin that provides it isn't installed
+    MissingElement(String),
+    /// The factory exists but couldn't create the element, for example
+    /// because a device it needs isn't available
+    ElementCreation(String),
+    /// A pipeline description couldn't be parsed or built. Contains the
+    /// code of the parse error and the elements missing to build it
+    Parse{code: i32, message: String, missing_elements: Vec<String>},
 }
 
 impl Debug for Error{
@@ -16,54 +30,77 @@ impl Debug for Error{
     }
 }
 
-impl Drop for Error{
-    fn drop(&mut self){
-        unsafe{
-            if self.error != ptr::null_mut(){
-                g_error_free(self.error);
-            }
+impl Display for Error{
+    fn fmt(&self, fmt: &mut Formatter) -> result::Result<(), fmt::Error>{
+        fmt.write_str(&self.message())
+    }
+}
+
+impl error::Error for Error{
+    fn description(&self) -> &str{
+        match *self{
+            Error::GError{ref message, ..} => message,
+            Error::MissingElement(_) => "missing element",
+            Error::ElementCreation(_) => "couldn't create element",
+            Error::Parse{ref message, ..} => message,
         }
     }
 }
 
 impl Error{
     pub fn new(domain: u32, code: i32, message: &str) -> Error{
-        let cmessage = CString::new(message).unwrap();
-        unsafe{
-            Error{error: g_error_new(domain, code, cmessage.as_ptr())}
-        }
+        Error::GError{ domain: domain, code: code, message: message.to_string() }
     }
 
+    /// Takes ownership of the GError and frees it
     pub unsafe fn new_from_g_error(err: *mut GError) -> Error{
-        Error{ error: err }
+        if err == ptr::null_mut(){
+            return Error::new(0, 0, "");
+        }
+        let message = if (*err).message != ptr::null_mut(){
+            from_c_str!(mem::transmute((*err).message)).to_string()
+        }else{
+            "".to_string()
+        };
+        let error = Error::new((*err).domain, (*err).code, &message);
+        g_error_free(err);
+        error
+    }
+
+    /// Takes the error out of an error message, the message is still owned
+    /// by the caller
+    pub unsafe fn new_from_error_message(msg: *mut GstMessage) -> Error{
+        let mut error = ptr::null_mut::<GError>();
+        let mut debug = ptr::null_mut::<gchar>();
+        gst_message_parse_error(msg, &mut error, &mut debug);
+        g_free(debug as gpointer);
+        Error::new_from_g_error(error)
     }
 
     pub fn message(&self) -> String{
-        unsafe{
-            if self.error != ptr::null_mut(){
-                from_c_str!(mem::transmute((*self.error).message)).to_string()
-            }else{
-                "".to_string()
-            }
+        match *self{
+            Error::GError{ref message, ..} => message.clone(),
+            Error::MissingElement(ref factory) => format!("No element factory named {}", factory),
+            Error::ElementCreation(ref factory) => format!("Couldn't create element of factory {}", factory),
+            Error::Parse{ref message, ..} => message.clone(),
         }
     }
 
     pub fn code(&self) -> i32{
-        unsafe{
-            if self.error !=ptr::null_mut(){
-                (*self.error).code
-            }else{
-                0
-            }
+        match *self{
+            Error::GError{code, ..} => code,
+            Error::MissingElement(_) => GST_CORE_ERROR_MISSING_PLUGIN as i32,
+            Error::ElementCreation(_) => GST_CORE_ERROR_FAILED as i32,
+            Error::Parse{code, ..} => code,
         }
     }
 
     pub fn domain(&self) -> u32{
         unsafe{
-            if self.error != ptr::null_mut(){
-                (*self.error).domain
-            }else{
-                0
+            match *self{
+                Error::GError{domain, ..} => domain,
+                Error::MissingElement(_) | Error::ElementCreation(_) => gst_core_error_quark(),
+                Error::Parse{..} => gst_parse_error_quark(),
             }
         }
     }
@@ -0,0 +1,173 @@
+use ffi::*;
+use util::*;
+
+use caps::Caps;
+use structure::Structure;
+use reference::Reference;
+use miniobject::MiniObject;
+
+unsafe impl Send for Event {}
+
+/// Events are passed between elements in parallel to the data stream,
+/// downstream in the direction of the data flow or upstream towards the
+/// sources, and can be sent to an element with `Element::send_event`.
+/// See [GstEvent](http://gstreamer.freedesktop.org/data/doc/gstreamer/head/gstreamer/html/GstEvent.html)
+#[derive(Clone)]
+pub struct Event{
+    event: MiniObject
+}
+
+impl Event{
+    pub unsafe fn new(event: *mut GstEvent) -> Option<Event>{
+        MiniObject::new_from_gst_miniobject(event as *mut GstMiniObject)
+            .map(|miniobject| Event{ event: miniobject })
+    }
+
+    /// Signals the end of the stream. Sent to a pipeline it makes its
+    /// sources finish, so muxers and sinks can finalize their output
+    pub fn new_eos() -> Event{
+        unsafe{
+            Event::new(gst_event_new_eos()).unwrap()
+        }
+    }
+
+    /// Makes all the pads it reaches discard their data and refuse any new
+    /// data until a flush stop event is received
+    pub fn new_flush_start() -> Event{
+        unsafe{
+            Event::new(gst_event_new_flush_start()).unwrap()
+        }
+    }
+
+    /// Ends a flush started by a flush start event. If `reset_time` is
+    /// true the running time of the pipeline is set back to 0
+    pub fn new_flush_stop(reset_time: bool) -> Event{
+        unsafe{
+            Event::new(gst_event_new_flush_stop(reset_time as gboolean)).unwrap()
+        }
+    }
+
+    /// Requests a new playback rate and range of the stream. See
+    /// `Element::seek` for the parameters
+    pub fn new_seek(rate: f64, format: GstFormat, flags: GstSeekFlags, start_type: GstSeekType, start: i64, stop_type: GstSeekType, stop: i64) -> Option<Event>{
+        unsafe{
+            Event::new(gst_event_new_seek(rate, format, flags, start_type, start, stop_type, stop))
+        }
+    }
+
+    /// Announces the segment of the buffers that follow, which relates
+    /// their timestamps to the running time
+    pub fn new_segment(segment: &GstSegment) -> Option<Event>{
+        unsafe{
+            Event::new(gst_event_new_segment(segment))
+        }
+    }
+
+    /// Announces the format of the buffers that follow
+    pub fn new_caps(caps: &Caps) -> Option<Event>{
+        unsafe{
+            Event::new(gst_event_new_caps(caps.gst_caps() as *mut GstCaps))
+        }
+    }
+
+    /// Application defined event travelling upstream, towards the sources
+    pub fn new_custom_upstream(structure: Structure) -> Option<Event>{
+        unsafe{
+            Event::new(gst_event_new_custom(GST_EVENT_CUSTOM_UPSTREAM, ::Transfer::transfer(structure)))
+        }
+    }
+
+    /// Application defined event travelling downstream, serialized with
+    /// the data flow
+    pub fn new_custom_downstream(structure: Structure) -> Option<Event>{
+        unsafe{
+            Event::new(gst_event_new_custom(GST_EVENT_CUSTOM_DOWNSTREAM, ::Transfer::transfer(structure)))
+        }
+    }
+
+    /// Application defined event travelling downstream, out of band with
+    /// the data flow
+    pub fn new_custom_downstream_oob(structure: Structure) -> Option<Event>{
+        unsafe{
+            Event::new(gst_event_new_custom(GST_EVENT_CUSTOM_DOWNSTREAM_OOB, ::Transfer::transfer(structure)))
+        }
+    }
+
+    pub fn event_type(&self) -> GstEventType{
+        unsafe{
+            (*self.gst_event())._type
+        }
+    }
+
+    pub fn type_name(&self) -> &str{
+        unsafe{
+            from_c_str!(gst_event_type_get_name(self.event_type()))
+        }
+    }
+
+    pub fn is_upstream(&self) -> bool{
+        self.event_type() & GST_EVENT_TYPE_UPSTREAM != 0
+    }
+
+    pub fn is_downstream(&self) -> bool{
+        self.event_type() & GST_EVENT_TYPE_DOWNSTREAM != 0
+    }
+
+    /// Whether the event is ordered with the buffers, or travels out of
+    /// band
+    pub fn is_serialized(&self) -> bool{
+        self.event_type() & GST_EVENT_TYPE_SERIALIZED != 0
+    }
+
+    /// The sequence number of the event, events caused by the same
+    /// original event share it
+    pub fn seqnum(&self) -> u32{
+        unsafe{
+            gst_event_get_seqnum(self.gst_event() as *mut GstEvent)
+        }
+    }
+
+    pub fn set_seqnum(&mut self, seqnum: u32){
+        unsafe{
+            gst_event_set_seqnum(self.gst_event_mut(), seqnum)
+        }
+    }
+
+    /// The structure holding the fields of the event, if any. For custom
+    /// events it's the one they were created with
+    pub fn structure(&self) -> Option<Structure>{
+        unsafe{
+            let structure = gst_event_get_structure(self.gst_event() as *mut GstEvent);
+            Structure::new_from_gst_structure(structure as *mut GstStructure)
+        }
+    }
+
+    pub fn has_name(&self, name: &str) -> bool{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            gst_event_has_name(self.gst_event() as *mut GstEvent, cname.as_ptr()) != 0
+        }
+    }
+
+    pub unsafe fn gst_event(&self) -> *const GstEvent{
+        self.event.gst_miniobject() as *const GstEvent
+    }
+
+    pub unsafe fn gst_event_mut(&mut self) -> *mut GstEvent{
+        self.event.gst_miniobject_mut() as *mut GstEvent
+    }
+}
+
+impl ::Transfer<GstEvent> for Event{
+    unsafe fn transfer(self) -> *mut GstEvent{
+        self.event.transfer() as *mut GstEvent
+    }
+}
+
+impl Reference for Event{
+    fn reference(&self) -> Event{
+        Event{
+            event: self.event.reference()
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use ffi::*;
+use bin::Bin;
+use caps::Caps;
+use element::Element;
+use elementfactory::ElementFactory;
+use error::{Error, Result};
+use pad::Pad;
+use pipeline::Pipeline;
+use reference::Reference;
+use unwind;
+
+use std::ptr;
+use std::mem;
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+
+// muxer and encoded formats used for each output extension
+struct Container{
+    muxer: &'static str,
+    video: &'static str,
+    audio: &'static str,
+}
+
+fn container_for(uri: &str) -> Option<Container>{
+    let extension = uri.rsplit('.').next().unwrap_or("").to_lowercase();
+    let (muxer, video, audio) = match &extension[..]{
+        "mp4" | "m4v" => ("mp4mux", "video/x-h264", "audio/mpeg,mpegversion=4"),
+        "mov" => ("qtmux", "video/x-h264", "audio/mpeg,mpegversion=4"),
+        "mkv" => ("matroskamux", "video/x-h264", "audio/x-opus"),
+        "webm" => ("webmmux", "video/x-vp8", "audio/x-vorbis"),
+        "ts" | "m2ts" => ("mpegtsmux", "video/x-h264", "audio/mpeg,mpegversion=4"),
+        _ => return None
+    };
+    Some(Container{ muxer: muxer, video: video, audio: audio })
+}
+
+struct Export{
+    container: Container,
+    reencode: bool,
+    // set right before the seek to the exported range
+    seeked: bool,
+    // set once a stream has started, from then the source can be seeked
+    started: bool,
+    // latest timestamp that reached the muxer after the seek
+    position: GstClockTime,
+}
+
+type SharedExport = Arc<Mutex<Export>>;
+
+// links the streams from the pad-added handler, the elements are found
+// through the pipeline so the shared state doesn't keep them alive
+struct StreamLinker<'a>{
+    export: &'a Export,
+    pipeline: Bin,
+    muxer: Element,
+}
+
+impl<'a> StreamLinker<'a>{
+    fn link_stream(&mut self, pad: &mut Pad) -> bool{
+        let media_type = pad.query_caps(None)
+            .and_then(|caps| caps.structure(0).map(|structure| structure.name().to_string()))
+            .unwrap_or_default();
+        if self.export.reencode{
+            if media_type.starts_with("video/x-raw"){
+                let caps = Caps::from_string(self.export.container.video);
+                self.link_encoder(pad, &["videoconvert"], caps)
+            }else if media_type.starts_with("audio/x-raw"){
+                let caps = Caps::from_string(self.export.container.audio);
+                self.link_encoder(pad, &["audioconvert", "audioresample"], caps)
+            }else{
+                false
+            }
+        }else{
+            self.link_muxer(pad)
+        }
+    }
+
+    fn link_encoder(&mut self, pad: &mut Pad, converters: &[&str], caps: Option<Caps>) -> bool{
+        let encoder = caps.and_then(|caps| ElementFactory::best_encoder_for(&caps))
+            .and_then(|factory| factory.create("").ok());
+        let mut elements = vec![];
+        for converter in converters{
+            match Element::new(converter, ""){
+                Ok(element) => elements.push(element),
+                Err(_) => return false
+            }
+        }
+        match encoder{
+            Some(encoder) => elements.push(encoder),
+            None => return false
+        }
+        for element in elements.iter(){
+            if !self.pipeline.add(element.reference()){
+                return false;
+            }
+        }
+        for i in 1..elements.len(){
+            let (previous, next) = elements.split_at_mut(i);
+            if !previous[i - 1].link(&mut next[0]){
+                return false;
+            }
+        }
+        let linked = elements[0].static_pad("sink")
+            .map(|mut sink| pad.link(&mut sink).is_ok())
+            .unwrap_or(false);
+        let muxed = match elements.last_mut().and_then(|encoder| encoder.static_pad("src")){
+            Some(mut src) => linked && self.link_muxer(&mut src),
+            None => false
+        };
+        for element in elements.iter_mut(){
+            element.sync_state_with_parent();
+        }
+        muxed
+    }
+
+    fn link_muxer(&mut self, pad: &mut Pad) -> bool{
+        unsafe{
+            let sink = gst_element_get_compatible_pad(self.muxer.gst_element_mut(), pad.gst_pad_mut(), ptr::null_mut());
+            match Pad::new(sink){
+                Some(mut sink) => pad.link(&mut sink).is_ok(),
+                None => false
+            }
+        }
+    }
+
+    // streams that can't be exported still need to be consumed
+    fn discard_stream(&mut self, pad: &mut Pad){
+        if let Ok(mut fakesink) = Element::new("fakesink", ""){
+            fakesink.set("async", 0 as gboolean);
+            if self.pipeline.add(fakesink.reference()){
+                if let Some(mut sink) = fakesink.static_pad("sink"){
+                    pad.link(&mut sink).ok();
+                }
+                fakesink.sync_state_with_parent();
+            }
+        }
+    }
+}
+
+/// Exports the range from `start` to `stop`, in nanoseconds, of the media
+/// at `uri_in` to `uri_out`, whose extension selects the container: mp4,
+/// m4v, mov, mkv, webm, ts or m2ts.
+///
+/// Without `reencode` the streams are copied as they are, which is fast
+/// and lossless but can only cut at keyframes: the export starts at the
+/// keyframe before `start`, and streams the container doesn't support are
+/// left out. With `reencode` only the exported range is decoded and
+/// encoded again, cut at the exact frames.
+///
+/// `progress` is called from this thread with the exported fraction, from
+/// 0 to 1. Returns once the file is finished.
+pub fn export_segment<F: FnMut(f64)>(uri_in: &str, start: GstClockTime, stop: GstClockTime,
+                                     uri_out: &str, reencode: bool, mut progress: F) -> Result<()>{
+    let container = container_for(uri_out).ok_or_else(|| Error::new(0,0,"Unknown output container"))?;
+    let mut pipeline = Pipeline::new("export")?;
+    let mut muxer = Element::new(container.muxer, "muxer")?;
+    let mut sink = element_from_uri(GST_URI_SINK, uri_out)?;
+    let (mut source, mut demuxer) = if reencode{
+        let mut decodebin = Element::new("uridecodebin", "")?;
+        decodebin.set("uri", uri_in);
+        (None, decodebin)
+    }else{
+        let source = element_from_uri(GST_URI_SRC, uri_in)?;
+        let parsebin = Element::new("parsebin", "")?;
+        (Some(source), parsebin)
+    };
+    if !pipeline.add(demuxer.reference()) || !pipeline.add(muxer.reference()) || !pipeline.add(sink.reference()) ||
+        !muxer.link(&mut sink){
+        return Err(Error::new(0,0,"Couldn't link muxer"));
+    }
+    if let Some(ref mut source) = source{
+        if !pipeline.add(source.reference()) || !source.link(&mut demuxer){
+            return Err(Error::new(0,0,"Couldn't link source"));
+        }
+    }
+
+    let export = Arc::new(Mutex::new(Export{
+        container: container,
+        reencode: reencode,
+        seeked: false,
+        started: false,
+        position: 0,
+    }));
+    unsafe{
+        let signal = CString::new("pad-added").unwrap();
+        let data = Box::new(export.clone());
+        g_signal_connect_data(demuxer.gst_element_mut() as gpointer, signal.as_ptr(),
+                              mem::transmute(on_pad_added as extern "C" fn(*mut GstElement, *mut GstPad, gpointer)),
+                              Box::into_raw(data) as gpointer, Some(free_export_closure), 0);
+    }
+
+    let ret = run_export(&mut pipeline, &mut muxer, &export, start, stop, reencode, &mut progress);
+    pipeline.set_state(GST_STATE_NULL);
+    ret
+}
+
+fn run_export<F: FnMut(f64)>(pipeline: &mut Pipeline, muxer: &mut Element, export: &SharedExport,
+                             start: GstClockTime, stop: GstClockTime, reencode: bool, progress: &mut F) -> Result<()>{
+    let mut bus = pipeline.bus().ok_or_else(|| Error::new(0,0,"Pipeline has no bus"))?;
+    pipeline.set_state(GST_STATE_PAUSED);
+
+    // the streams are held back until the source can be seeked to the
+    // start of the range
+    while !export.lock().unwrap().started{
+        unsafe{ pop_message(&mut bus)?; }
+    }
+    let flags = if reencode{
+        GST_SEEK_FLAG_FLUSH | GST_SEEK_FLAG_ACCURATE
+    }else{
+        GST_SEEK_FLAG_FLUSH | GST_SEEK_FLAG_KEY_UNIT | GST_SEEK_FLAG_SNAP_BEFORE
+    };
+    export.lock().unwrap().seeked = true;
+    // the muxer sends the seek upstream through one of its streams
+    if !muxer.seek(1.0, GST_FORMAT_TIME, flags, GST_SEEK_TYPE_SET, start as i64, GST_SEEK_TYPE_SET, stop as i64){
+        return Err(Error::new(0,0,"Couldn't seek to the start of the range"));
+    }
+    pipeline.set_state(GST_STATE_PLAYING);
+
+    let duration = stop.saturating_sub(start).max(1) as f64;
+    loop{
+        if unsafe{ pop_message(&mut bus)? }{
+            break;
+        }
+        let position = export.lock().unwrap().position;
+        progress((position.saturating_sub(start) as f64 / duration).min(1.0));
+    }
+    progress(1.0);
+    Ok(())
+}
+
+// waits up to 100ms for the EOS or an error, returns true on EOS
+unsafe fn pop_message(bus: &mut ::Bus) -> Result<bool>{
+    let msg = gst_bus_timed_pop_filtered(bus.gst_bus_mut(), 100_000_000, GST_MESSAGE_EOS | GST_MESSAGE_ERROR);
+    if msg == ptr::null_mut(){
+        return Ok(false);
+    }
+    let ret = if (*msg)._type == GST_MESSAGE_ERROR{
+        Err(Error::new_from_error_message(msg))
+    }else{
+        Ok(true)
+    };
+    gst_mini_object_unref(msg as *mut GstMiniObject);
+    ret
+}
+
+fn element_from_uri(uri_type: GstURIType, uri: &str) -> Result<Element>{
+    let curi = CString::new(uri).unwrap();
+    unsafe{
+        let mut error = ptr::null_mut::<GError>();
+        let element = gst_element_make_from_uri(uri_type, curi.as_ptr(), ptr::null(), &mut error);
+        if error != ptr::null_mut(){
+            return Err(Error::new_from_g_error(error));
+        }
+        if element == ptr::null_mut(){
+            return Err(Error::new(0,0,"No element for uri"));
+        }
+        gst_object_ref_sink(element as gpointer);
+        Element::new_from_gst_element(element).ok_or_else(|| Error::new(0,0,"No element for uri"))
+    }
+}
+
+extern "C" fn on_pad_added(element: *mut GstElement, pad: *mut GstPad, data: gpointer){
+    unsafe{
+        unwind::guard(element as *mut GstObject, (), || {
+            let export = &*(data as *const SharedExport);
+            let mut pad = match Pad::new(gst_object_ref(pad as gpointer) as *mut GstPad){
+                Some(pad) => pad,
+                None => return
+            };
+            let pipeline = match Bin::new_from_gst_bin(gst_object_get_parent(element as *mut GstObject) as *mut GstBin){
+                Some(pipeline) => pipeline,
+                None => return
+            };
+            let muxer = match pipeline.get_by_name("muxer"){
+                Some(muxer) => muxer,
+                None => return
+            };
+            {
+                let export = export.lock().unwrap();
+                let mut linker = StreamLinker{ export: &export, pipeline: pipeline, muxer: muxer };
+                if !linker.link_stream(&mut pad){
+                    linker.discard_stream(&mut pad);
+                }
+            }
+            // streams that appear after the seek already start in the range
+            let seeked = export.lock().unwrap().seeked;
+            let probe = Box::new(StreamProbe{ export: export.clone(), seeked: seeked });
+            gst_pad_add_probe(pad.gst_pad_mut(),
+                              GST_PAD_PROBE_TYPE_BUFFER | GST_PAD_PROBE_TYPE_EVENT_DOWNSTREAM | GST_PAD_PROBE_TYPE_EVENT_FLUSH,
+                              Some(stream_probe), Box::into_raw(probe) as gpointer, Some(free_stream_probe));
+        })
+    }
+}
+
+struct StreamProbe{
+    export: SharedExport,
+    // set when the flush of the seek has gone through this stream
+    seeked: bool,
+}
+
+// drops the data that arrives before the seek, so what the muxer receives
+// starts at the exported range, and tracks the progress after it
+extern "C" fn stream_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        unwind::guard(pad as *mut GstObject, GST_PAD_PROBE_OK, || {
+            let probe = &mut *(data as *mut StreamProbe);
+            let mut export = probe.export.lock().unwrap();
+            if (*info)._type & GST_PAD_PROBE_TYPE_BUFFER != 0{
+                if !probe.seeked{
+                    export.started = true;
+                    return GST_PAD_PROBE_DROP;
+                }
+                let buffer = (*info).data as *mut GstBuffer;
+                if (*buffer).pts != GST_CLOCK_TIME_NONE && (*buffer).pts > export.position{
+                    export.position = (*buffer).pts;
+                }
+                GST_PAD_PROBE_OK
+            }else{
+                let event = (*info).data as *mut GstEvent;
+                if (*event)._type == GST_EVENT_FLUSH_STOP && export.seeked{
+                    probe.seeked = true;
+                    GST_PAD_PROBE_OK
+                }else if !probe.seeked && ((*event)._type == GST_EVENT_EOS || (*event)._type == GST_EVENT_GAP){
+                    export.started = true;
+                    GST_PAD_PROBE_DROP
+                }else{
+                    GST_PAD_PROBE_OK
+                }
+            }
+        })
+    }
+}
+
+extern "C" fn free_export_closure(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut SharedExport));
+    }
+}
+
+extern "C" fn free_stream_probe(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut StreamProbe));
+    }
+}
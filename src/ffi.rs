@@ -87,6 +87,10 @@ pub type clockid_t = __clockid_t;
 pub type timer_t = __timer_t;
 
 pub const GST_CLOCK_TIME_NONE: guint64 = 18446744073709551615;
+pub const GST_BUFFER_OFFSET_NONE: guint64 = 18446744073709551615;
+pub const G_TYPE_NONE: GType = 4;
+pub const G_SIGNAL_TYPE_STATIC_SCOPE: GType = 1;
+pub const G_PRIORITY_DEFAULT: gint = 0;
 
 #[repr(C)]
 #[derive(Clone,Copy)]
@@ -4617,6 +4621,22 @@ impl ::std::default::Default for Struct__GstSegment {
 }
 pub enum Struct__GstSample { }
 pub type GstSample = Struct__GstSample;
+pub enum Struct__GstPromise { }
+pub type GstPromise = Struct__GstPromise;
+pub type Enum_GstPromiseResult = raw::c_uint;
+pub const GST_PROMISE_RESULT_PENDING: raw::c_uint = 0;
+pub const GST_PROMISE_RESULT_INTERRUPTED: raw::c_uint = 1;
+pub const GST_PROMISE_RESULT_REPLIED: raw::c_uint = 2;
+pub const GST_PROMISE_RESULT_EXPIRED: raw::c_uint = 3;
+pub type GstPromiseResult = Enum_GstPromiseResult;
+pub enum Struct__GstTimedValueControlSource { }
+pub type GstTimedValueControlSource = Struct__GstTimedValueControlSource;
+pub type Enum_GstInterpolationMode = raw::c_uint;
+pub const GST_INTERPOLATION_MODE_NONE: raw::c_uint = 0;
+pub const GST_INTERPOLATION_MODE_LINEAR: raw::c_uint = 1;
+pub const GST_INTERPOLATION_MODE_CUBIC: raw::c_uint = 2;
+pub const GST_INTERPOLATION_MODE_CUBIC_MONOTONIC: raw::c_uint = 3;
+pub type GstInterpolationMode = Enum_GstInterpolationMode;
 pub type Enum_Unnamed148 = raw::c_uint;
 pub const GST_TAG_MERGE_UNDEFINED: raw::c_uint = 0;
 pub const GST_TAG_MERGE_REPLACE_ALL: raw::c_uint = 1;
@@ -6683,6 +6703,7 @@ pub const GST_VIDEO_COLOR_MATRIX_FCC: raw::c_uint = 2;
 pub const GST_VIDEO_COLOR_MATRIX_BT709: raw::c_uint = 3;
 pub const GST_VIDEO_COLOR_MATRIX_BT601: raw::c_uint = 4;
 pub const GST_VIDEO_COLOR_MATRIX_SMPTE240M: raw::c_uint = 5;
+pub const GST_VIDEO_COLOR_MATRIX_BT2020: raw::c_uint = 6;
 pub type GstVideoColorMatrix = Enum_Unnamed216;
 pub type Enum_Unnamed217 = raw::c_uint;
 pub const GST_VIDEO_TRANSFER_UNKNOWN: raw::c_uint = 0;
@@ -6696,6 +6717,12 @@ pub const GST_VIDEO_TRANSFER_SRGB: raw::c_uint = 7;
 pub const GST_VIDEO_TRANSFER_GAMMA28: raw::c_uint = 8;
 pub const GST_VIDEO_TRANSFER_LOG100: raw::c_uint = 9;
 pub const GST_VIDEO_TRANSFER_LOG316: raw::c_uint = 10;
+pub const GST_VIDEO_TRANSFER_BT2020_12: raw::c_uint = 11;
+pub const GST_VIDEO_TRANSFER_ADOBERGB: raw::c_uint = 12;
+pub const GST_VIDEO_TRANSFER_BT2020_10: raw::c_uint = 13;
+pub const GST_VIDEO_TRANSFER_SMPTE2084: raw::c_uint = 14;
+pub const GST_VIDEO_TRANSFER_ARIB_STD_B67: raw::c_uint = 15;
+pub const GST_VIDEO_TRANSFER_BT601: raw::c_uint = 16;
 pub type GstVideoTransferFunction = Enum_Unnamed217;
 pub type Enum_Unnamed218 = raw::c_uint;
 pub const GST_VIDEO_COLOR_PRIMARIES_UNKNOWN: raw::c_uint = 0;
@@ -6705,6 +6732,12 @@ pub const GST_VIDEO_COLOR_PRIMARIES_BT470BG: raw::c_uint = 3;
 pub const GST_VIDEO_COLOR_PRIMARIES_SMPTE170M: raw::c_uint = 4;
 pub const GST_VIDEO_COLOR_PRIMARIES_SMPTE240M: raw::c_uint = 5;
 pub const GST_VIDEO_COLOR_PRIMARIES_FILM: raw::c_uint = 6;
+pub const GST_VIDEO_COLOR_PRIMARIES_BT2020: raw::c_uint = 7;
+pub const GST_VIDEO_COLOR_PRIMARIES_ADOBERGB: raw::c_uint = 8;
+pub const GST_VIDEO_COLOR_PRIMARIES_SMPTEST428: raw::c_uint = 9;
+pub const GST_VIDEO_COLOR_PRIMARIES_SMPTERP431: raw::c_uint = 10;
+pub const GST_VIDEO_COLOR_PRIMARIES_SMPTEEG432: raw::c_uint = 11;
+pub const GST_VIDEO_COLOR_PRIMARIES_EBU3213: raw::c_uint = 12;
 pub type GstVideoColorPrimaries = Enum_Unnamed218;
 #[repr(C)]
 #[derive(Clone,Copy)]
@@ -7143,6 +7176,720 @@ impl ::std::default::Default for Struct__GstVideoEncoderClass {
         unsafe { ::std::mem::zeroed() }
     }
 }
+pub type GstAudioFormat = raw::c_uint;
+pub const GST_AUDIO_FORMAT_UNKNOWN: raw::c_uint = 0;
+pub const GST_AUDIO_FORMAT_ENCODED: raw::c_uint = 1;
+pub const GST_AUDIO_FORMAT_S8: raw::c_uint = 2;
+pub const GST_AUDIO_FORMAT_U8: raw::c_uint = 3;
+pub const GST_AUDIO_FORMAT_S16LE: raw::c_uint = 4;
+pub const GST_AUDIO_FORMAT_S16BE: raw::c_uint = 5;
+pub const GST_AUDIO_FORMAT_U16LE: raw::c_uint = 6;
+pub const GST_AUDIO_FORMAT_U16BE: raw::c_uint = 7;
+pub const GST_AUDIO_FORMAT_S24_32LE: raw::c_uint = 8;
+pub const GST_AUDIO_FORMAT_S24_32BE: raw::c_uint = 9;
+pub const GST_AUDIO_FORMAT_U24_32LE: raw::c_uint = 10;
+pub const GST_AUDIO_FORMAT_U24_32BE: raw::c_uint = 11;
+pub const GST_AUDIO_FORMAT_S32LE: raw::c_uint = 12;
+pub const GST_AUDIO_FORMAT_S32BE: raw::c_uint = 13;
+pub const GST_AUDIO_FORMAT_U32LE: raw::c_uint = 14;
+pub const GST_AUDIO_FORMAT_U32BE: raw::c_uint = 15;
+pub const GST_AUDIO_FORMAT_S24LE: raw::c_uint = 16;
+pub const GST_AUDIO_FORMAT_S24BE: raw::c_uint = 17;
+pub const GST_AUDIO_FORMAT_U24LE: raw::c_uint = 18;
+pub const GST_AUDIO_FORMAT_U24BE: raw::c_uint = 19;
+pub const GST_AUDIO_FORMAT_S20LE: raw::c_uint = 20;
+pub const GST_AUDIO_FORMAT_S20BE: raw::c_uint = 21;
+pub const GST_AUDIO_FORMAT_U20LE: raw::c_uint = 22;
+pub const GST_AUDIO_FORMAT_U20BE: raw::c_uint = 23;
+pub const GST_AUDIO_FORMAT_S18LE: raw::c_uint = 24;
+pub const GST_AUDIO_FORMAT_S18BE: raw::c_uint = 25;
+pub const GST_AUDIO_FORMAT_U18LE: raw::c_uint = 26;
+pub const GST_AUDIO_FORMAT_U18BE: raw::c_uint = 27;
+pub const GST_AUDIO_FORMAT_F32LE: raw::c_uint = 28;
+pub const GST_AUDIO_FORMAT_F32BE: raw::c_uint = 29;
+pub const GST_AUDIO_FORMAT_F64LE: raw::c_uint = 30;
+pub const GST_AUDIO_FORMAT_F64BE: raw::c_uint = 31;
+#[cfg(target_endian = "little")]
+pub const GST_AUDIO_FORMAT_S16: raw::c_uint = GST_AUDIO_FORMAT_S16LE;
+#[cfg(target_endian = "little")]
+pub const GST_AUDIO_FORMAT_U16: raw::c_uint = GST_AUDIO_FORMAT_U16LE;
+#[cfg(target_endian = "little")]
+pub const GST_AUDIO_FORMAT_S32: raw::c_uint = GST_AUDIO_FORMAT_S32LE;
+#[cfg(target_endian = "little")]
+pub const GST_AUDIO_FORMAT_F32: raw::c_uint = GST_AUDIO_FORMAT_F32LE;
+#[cfg(target_endian = "little")]
+pub const GST_AUDIO_FORMAT_F64: raw::c_uint = GST_AUDIO_FORMAT_F64LE;
+#[cfg(target_endian = "big")]
+pub const GST_AUDIO_FORMAT_S16: raw::c_uint = GST_AUDIO_FORMAT_S16BE;
+#[cfg(target_endian = "big")]
+pub const GST_AUDIO_FORMAT_U16: raw::c_uint = GST_AUDIO_FORMAT_U16BE;
+#[cfg(target_endian = "big")]
+pub const GST_AUDIO_FORMAT_S32: raw::c_uint = GST_AUDIO_FORMAT_S32BE;
+#[cfg(target_endian = "big")]
+pub const GST_AUDIO_FORMAT_F32: raw::c_uint = GST_AUDIO_FORMAT_F32BE;
+#[cfg(target_endian = "big")]
+pub const GST_AUDIO_FORMAT_F64: raw::c_uint = GST_AUDIO_FORMAT_F64BE;
+pub type GstAudioFormatFlags = raw::c_uint;
+pub const GST_AUDIO_FORMAT_FLAG_INTEGER: raw::c_uint = 1;
+pub const GST_AUDIO_FORMAT_FLAG_FLOAT: raw::c_uint = 2;
+pub const GST_AUDIO_FORMAT_FLAG_SIGNED: raw::c_uint = 4;
+pub const GST_AUDIO_FORMAT_FLAG_COMPLEX: raw::c_uint = 16;
+pub const GST_AUDIO_FORMAT_FLAG_UNPACK: raw::c_uint = 32;
+pub type GstAudioLayout = raw::c_uint;
+pub const GST_AUDIO_LAYOUT_INTERLEAVED: raw::c_uint = 0;
+pub const GST_AUDIO_LAYOUT_NON_INTERLEAVED: raw::c_uint = 1;
+pub type GstAudioFlags = raw::c_uint;
+pub const GST_AUDIO_FLAG_NONE: raw::c_uint = 0;
+pub const GST_AUDIO_FLAG_UNPOSITIONED: raw::c_uint = 1;
+pub type GstAudioChannelPosition = raw::c_int;
+pub const GST_AUDIO_CHANNEL_POSITION_NONE: raw::c_int = -3;
+pub const GST_AUDIO_CHANNEL_POSITION_MONO: raw::c_int = -2;
+pub const GST_AUDIO_CHANNEL_POSITION_INVALID: raw::c_int = -1;
+pub const GST_AUDIO_CHANNEL_POSITION_FRONT_LEFT: raw::c_int = 0;
+pub const GST_AUDIO_CHANNEL_POSITION_FRONT_RIGHT: raw::c_int = 1;
+pub const GST_AUDIO_CHANNEL_POSITION_FRONT_CENTER: raw::c_int = 2;
+pub const GST_AUDIO_CHANNEL_POSITION_LFE1: raw::c_int = 3;
+pub const GST_AUDIO_CHANNEL_POSITION_REAR_LEFT: raw::c_int = 4;
+pub const GST_AUDIO_CHANNEL_POSITION_REAR_RIGHT: raw::c_int = 5;
+pub const GST_AUDIO_CHANNEL_POSITION_FRONT_LEFT_OF_CENTER: raw::c_int = 6;
+pub const GST_AUDIO_CHANNEL_POSITION_FRONT_RIGHT_OF_CENTER: raw::c_int = 7;
+pub const GST_AUDIO_CHANNEL_POSITION_REAR_CENTER: raw::c_int = 8;
+pub const GST_AUDIO_CHANNEL_POSITION_LFE2: raw::c_int = 9;
+pub const GST_AUDIO_CHANNEL_POSITION_SIDE_LEFT: raw::c_int = 10;
+pub const GST_AUDIO_CHANNEL_POSITION_SIDE_RIGHT: raw::c_int = 11;
+pub type GstAudioFormatInfo = Struct__GstAudioFormatInfo;
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct__GstAudioFormatInfo {
+    pub format: GstAudioFormat,
+    pub name: *const gchar,
+    pub description: *const gchar,
+    pub flags: GstAudioFormatFlags,
+    pub endianness: gint,
+    pub width: gint,
+    pub depth: gint,
+    pub silence: [guint8; 8usize],
+    pub unpack_format: GstAudioFormat,
+    pub unpack_func: gpointer,
+    pub pack_func: gpointer,
+    pub _gst_reserved: [gpointer; 4usize],
+}
+impl ::std::default::Default for Struct__GstAudioFormatInfo {
+    fn default() -> Struct__GstAudioFormatInfo {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type GstAudioInfo = Struct__GstAudioInfo;
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct__GstAudioInfo {
+    pub finfo: *const GstAudioFormatInfo,
+    pub flags: GstAudioFlags,
+    pub layout: GstAudioLayout,
+    pub rate: gint,
+    pub channels: gint,
+    pub bpf: gint,
+    pub position: [GstAudioChannelPosition; 64usize],
+    pub _gst_reserved: [gpointer; 4usize],
+}
+impl ::std::default::Default for Struct__GstAudioInfo {
+    fn default() -> Struct__GstAudioInfo {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type GstAudioDecoder = Struct__GstAudioDecoder;
+pub type GstAudioDecoderClass = Struct__GstAudioDecoderClass;
+pub enum Struct__GstAudioDecoderPrivate { }
+pub type GstAudioDecoderPrivate = Struct__GstAudioDecoderPrivate;
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct__GstAudioDecoder {
+    pub element: GstElement,
+    pub sinkpad: *mut GstPad,
+    pub srcpad: *mut GstPad,
+    pub stream_lock: GRecMutex,
+    pub input_segment: GstSegment,
+    pub output_segment: GstSegment,
+    pub _priv: *mut GstAudioDecoderPrivate,
+    pub _gst_reserved: [gpointer; 20usize],
+}
+impl ::std::default::Default for Struct__GstAudioDecoder {
+    fn default() -> Struct__GstAudioDecoder {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+#[repr(C)]
+pub struct Struct__GstAudioDecoderClass {
+    pub element_class: GstElementClass,
+    pub start: ::std::option::Option<extern "C" fn
+                                         (dec: *mut GstAudioDecoder)
+                                         -> gboolean>,
+    pub stop: ::std::option::Option<extern "C" fn
+                                        (dec: *mut GstAudioDecoder)
+                                        -> gboolean>,
+    pub set_format: ::std::option::Option<extern "C" fn
+                                              (dec: *mut GstAudioDecoder,
+                                               caps: *mut GstCaps)
+                                              -> gboolean>,
+    pub parse: ::std::option::Option<extern "C" fn
+                                         (dec: *mut GstAudioDecoder,
+                                          adapter: *mut GstAdapter,
+                                          offset: *mut gint,
+                                          length: *mut gint)
+                                         -> GstFlowReturn>,
+    pub handle_frame: ::std::option::Option<extern "C" fn
+                                                (dec: *mut GstAudioDecoder,
+                                                 buffer: *mut GstBuffer)
+                                                -> GstFlowReturn>,
+    pub flush: ::std::option::Option<extern "C" fn
+                                         (dec: *mut GstAudioDecoder,
+                                          hard: gboolean)>,
+    pub pre_push: ::std::option::Option<extern "C" fn
+                                            (dec: *mut GstAudioDecoder,
+                                             buffer: *mut *mut GstBuffer)
+                                            -> GstFlowReturn>,
+    pub sink_event: ::std::option::Option<extern "C" fn
+                                              (dec: *mut GstAudioDecoder,
+                                               event: *mut GstEvent)
+                                              -> gboolean>,
+    pub src_event: ::std::option::Option<extern "C" fn
+                                             (dec: *mut GstAudioDecoder,
+                                              event: *mut GstEvent)
+                                             -> gboolean>,
+    pub open: ::std::option::Option<extern "C" fn
+                                        (dec: *mut GstAudioDecoder)
+                                        -> gboolean>,
+    pub close: ::std::option::Option<extern "C" fn
+                                         (dec: *mut GstAudioDecoder)
+                                         -> gboolean>,
+    pub negotiate: ::std::option::Option<extern "C" fn
+                                             (dec: *mut GstAudioDecoder)
+                                             -> gboolean>,
+    pub decide_allocation: ::std::option::Option<extern "C" fn
+                                                     (dec:
+                                                          *mut GstAudioDecoder,
+                                                      query: *mut GstQuery)
+                                                     -> gboolean>,
+    pub propose_allocation: ::std::option::Option<extern "C" fn
+                                                      (dec:
+                                                           *mut GstAudioDecoder,
+                                                       query: *mut GstQuery)
+                                                      -> gboolean>,
+    pub sink_query: ::std::option::Option<extern "C" fn
+                                              (dec: *mut GstAudioDecoder,
+                                               query: *mut GstQuery)
+                                              -> gboolean>,
+    pub src_query: ::std::option::Option<extern "C" fn
+                                             (dec: *mut GstAudioDecoder,
+                                              query: *mut GstQuery)
+                                             -> gboolean>,
+    pub getcaps: ::std::option::Option<extern "C" fn
+                                           (dec: *mut GstAudioDecoder,
+                                            filter: *mut GstCaps)
+                                           -> *mut GstCaps>,
+    pub transform_meta: ::std::option::Option<extern "C" fn
+                                                  (dec: *mut GstAudioDecoder,
+                                                   outbuf: *mut GstBuffer,
+                                                   meta: *mut GstMeta,
+                                                   inbuf: *mut GstBuffer)
+                                                  -> gboolean>,
+    pub _gst_reserved: [gpointer; 16usize],
+}
+impl ::std::default::Default for Struct__GstAudioDecoderClass {
+    fn default() -> Struct__GstAudioDecoderClass {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type GstAudioEncoder = Struct__GstAudioEncoder;
+pub type GstAudioEncoderClass = Struct__GstAudioEncoderClass;
+pub enum Struct__GstAudioEncoderPrivate { }
+pub type GstAudioEncoderPrivate = Struct__GstAudioEncoderPrivate;
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct__GstAudioEncoder {
+    pub element: GstElement,
+    pub sinkpad: *mut GstPad,
+    pub srcpad: *mut GstPad,
+    pub stream_lock: GRecMutex,
+    pub input_segment: GstSegment,
+    pub output_segment: GstSegment,
+    pub _priv: *mut GstAudioEncoderPrivate,
+    pub _gst_reserved: [gpointer; 20usize],
+}
+impl ::std::default::Default for Struct__GstAudioEncoder {
+    fn default() -> Struct__GstAudioEncoder {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+#[repr(C)]
+pub struct Struct__GstAudioEncoderClass {
+    pub element_class: GstElementClass,
+    pub start: ::std::option::Option<extern "C" fn
+                                         (enc: *mut GstAudioEncoder)
+                                         -> gboolean>,
+    pub stop: ::std::option::Option<extern "C" fn
+                                        (enc: *mut GstAudioEncoder)
+                                        -> gboolean>,
+    pub set_format: ::std::option::Option<extern "C" fn
+                                              (enc: *mut GstAudioEncoder,
+                                               info: *mut GstAudioInfo)
+                                              -> gboolean>,
+    pub handle_frame: ::std::option::Option<extern "C" fn
+                                                (enc: *mut GstAudioEncoder,
+                                                 buffer: *mut GstBuffer)
+                                                -> GstFlowReturn>,
+    pub flush: ::std::option::Option<extern "C" fn
+                                         (enc: *mut GstAudioEncoder)>,
+    pub pre_push: ::std::option::Option<extern "C" fn
+                                            (enc: *mut GstAudioEncoder,
+                                             buffer: *mut *mut GstBuffer)
+                                            -> GstFlowReturn>,
+    pub sink_event: ::std::option::Option<extern "C" fn
+                                              (enc: *mut GstAudioEncoder,
+                                               event: *mut GstEvent)
+                                              -> gboolean>,
+    pub src_event: ::std::option::Option<extern "C" fn
+                                             (enc: *mut GstAudioEncoder,
+                                              event: *mut GstEvent)
+                                             -> gboolean>,
+    pub getcaps: ::std::option::Option<extern "C" fn
+                                           (enc: *mut GstAudioEncoder,
+                                            filter: *mut GstCaps)
+                                           -> *mut GstCaps>,
+    pub open: ::std::option::Option<extern "C" fn
+                                        (enc: *mut GstAudioEncoder)
+                                        -> gboolean>,
+    pub close: ::std::option::Option<extern "C" fn
+                                         (enc: *mut GstAudioEncoder)
+                                         -> gboolean>,
+    pub negotiate: ::std::option::Option<extern "C" fn
+                                             (enc: *mut GstAudioEncoder)
+                                             -> gboolean>,
+    pub decide_allocation: ::std::option::Option<extern "C" fn
+                                                     (enc:
+                                                          *mut GstAudioEncoder,
+                                                      query: *mut GstQuery)
+                                                     -> gboolean>,
+    pub propose_allocation: ::std::option::Option<extern "C" fn
+                                                      (enc:
+                                                           *mut GstAudioEncoder,
+                                                       query: *mut GstQuery)
+                                                      -> gboolean>,
+    pub transform_meta: ::std::option::Option<extern "C" fn
+                                                  (enc: *mut GstAudioEncoder,
+                                                   outbuf: *mut GstBuffer,
+                                                   meta: *mut GstMeta,
+                                                   inbuf: *mut GstBuffer)
+                                                  -> gboolean>,
+    pub sink_query: ::std::option::Option<extern "C" fn
+                                              (enc: *mut GstAudioEncoder,
+                                               query: *mut GstQuery)
+                                              -> gboolean>,
+    pub src_query: ::std::option::Option<extern "C" fn
+                                             (enc: *mut GstAudioEncoder,
+                                              query: *mut GstQuery)
+                                             -> gboolean>,
+    pub _gst_reserved: [gpointer; 17usize],
+}
+impl ::std::default::Default for Struct__GstAudioEncoderClass {
+    fn default() -> Struct__GstAudioEncoderClass {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub enum Struct__GDBusConnection { }
+pub type GDBusConnection = Struct__GDBusConnection;
+pub enum Struct__GDBusMethodInvocation { }
+pub type GDBusMethodInvocation = Struct__GDBusMethodInvocation;
+pub enum Struct__GDBusInterfaceInfo { }
+pub type GDBusInterfaceInfo = Struct__GDBusInterfaceInfo;
+pub enum Struct__GDBusNodeInfo { }
+pub type GDBusNodeInfo = Struct__GDBusNodeInfo;
+pub type GBusType = raw::c_int;
+pub const G_BUS_TYPE_STARTER: raw::c_int = -1;
+pub const G_BUS_TYPE_NONE: raw::c_int = 0;
+pub const G_BUS_TYPE_SYSTEM: raw::c_int = 1;
+pub const G_BUS_TYPE_SESSION: raw::c_int = 2;
+pub type GBusNameOwnerFlags = raw::c_uint;
+pub const G_BUS_NAME_OWNER_FLAGS_NONE: raw::c_uint = 0;
+pub const G_BUS_NAME_OWNER_FLAGS_ALLOW_REPLACEMENT: raw::c_uint = 1;
+pub const G_BUS_NAME_OWNER_FLAGS_REPLACE: raw::c_uint = 2;
+pub const G_BUS_NAME_OWNER_FLAGS_DO_NOT_QUEUE: raw::c_uint = 4;
+pub const G_DBUS_ERROR_FAILED: raw::c_int = 0;
+pub const G_DBUS_ERROR_NOT_SUPPORTED: raw::c_int = 7;
+pub type GBusAcquiredCallback =
+    ::std::option::Option<extern "C" fn(connection: *mut GDBusConnection,
+                                        name: *const gchar,
+                                        user_data: gpointer)>;
+pub type GBusNameAcquiredCallback =
+    ::std::option::Option<extern "C" fn(connection: *mut GDBusConnection,
+                                        name: *const gchar,
+                                        user_data: gpointer)>;
+pub type GBusNameLostCallback =
+    ::std::option::Option<extern "C" fn(connection: *mut GDBusConnection,
+                                        name: *const gchar,
+                                        user_data: gpointer)>;
+pub type GDBusInterfaceMethodCallFunc =
+    ::std::option::Option<extern "C" fn(connection: *mut GDBusConnection,
+                                        sender: *const gchar,
+                                        object_path: *const gchar,
+                                        interface_name: *const gchar,
+                                        method_name: *const gchar,
+                                        parameters: *mut GVariant,
+                                        invocation: *mut GDBusMethodInvocation,
+                                        user_data: gpointer)>;
+pub type GDBusInterfaceGetPropertyFunc =
+    ::std::option::Option<extern "C" fn(connection: *mut GDBusConnection,
+                                        sender: *const gchar,
+                                        object_path: *const gchar,
+                                        interface_name: *const gchar,
+                                        property_name: *const gchar,
+                                        error: *mut *mut GError,
+                                        user_data: gpointer)
+                              -> *mut GVariant>;
+pub type GDBusInterfaceSetPropertyFunc =
+    ::std::option::Option<extern "C" fn(connection: *mut GDBusConnection,
+                                        sender: *const gchar,
+                                        object_path: *const gchar,
+                                        interface_name: *const gchar,
+                                        property_name: *const gchar,
+                                        value: *mut GVariant,
+                                        error: *mut *mut GError,
+                                        user_data: gpointer)
+                              -> gboolean>;
+pub enum Struct__GstValidateRunner { }
+pub type GstValidateRunner = Struct__GstValidateRunner;
+pub enum Struct__GstValidateMonitor { }
+pub type GstValidateMonitor = Struct__GstValidateMonitor;
+pub enum Struct__GstValidateScenario { }
+pub type GstValidateScenario = Struct__GstValidateScenario;
+pub enum Struct__GstRTSPServer { }
+pub type GstRTSPServer = Struct__GstRTSPServer;
+pub enum Struct__GstRTSPMountPoints { }
+pub type GstRTSPMountPoints = Struct__GstRTSPMountPoints;
+pub enum Struct__GstRTSPClient { }
+pub type GstRTSPClient = Struct__GstRTSPClient;
+pub enum Struct__GstRTSPConnection { }
+pub type GstRTSPConnection = Struct__GstRTSPConnection;
+pub enum Struct__GstRTSPMessage { }
+pub type GstRTSPMessage = Struct__GstRTSPMessage;
+pub enum Struct__GstRTSPUrl { }
+pub type GstRTSPUrl = Struct__GstRTSPUrl;
+pub enum Struct__GstRTSPMedia { }
+pub type GstRTSPMedia = Struct__GstRTSPMedia;
+pub enum Struct__GstRTSPMediaFactoryPrivate { }
+pub type GstRTSPMediaFactoryPrivate = Struct__GstRTSPMediaFactoryPrivate;
+pub enum Struct__GstRTSPAuth { }
+pub type GstRTSPAuth = Struct__GstRTSPAuth;
+pub enum Struct__GstRTSPToken { }
+pub type GstRTSPToken = Struct__GstRTSPToken;
+pub enum Struct__GstRTSPSession { }
+pub type GstRTSPSession = Struct__GstRTSPSession;
+pub enum Struct__GstRTSPSessionMedia { }
+pub type GstRTSPSessionMedia = Struct__GstRTSPSessionMedia;
+pub enum Struct__GstRTSPStream { }
+pub type GstRTSPStream = Struct__GstRTSPStream;
+pub enum Struct__GstRTSPStreamTransport { }
+pub type GstRTSPStreamTransport = Struct__GstRTSPStreamTransport;
+pub type GstRTSPMethod = raw::c_uint;
+pub enum Struct__GstRTSPPermissions { }
+pub type GstRTSPPermissions = Struct__GstRTSPPermissions;
+pub type GstRTSPAuthMethod = raw::c_uint;
+pub const GST_RTSP_AUTH_NONE: raw::c_uint = 0;
+pub const GST_RTSP_AUTH_BASIC: raw::c_uint = 1;
+pub const GST_RTSP_AUTH_DIGEST: raw::c_uint = 2;
+pub type GstRTSPMediaFactory = Struct__GstRTSPMediaFactory;
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstRTSPMediaFactory {
+    pub parent: GObject,
+    pub _priv: *mut GstRTSPMediaFactoryPrivate,
+    pub _gst_reserved: [gpointer; 4usize],
+}
+impl ::std::clone::Clone for Struct__GstRTSPMediaFactory {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstRTSPMediaFactory {
+    fn default() -> Struct__GstRTSPMediaFactory { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstRTSPMediaFactoryClass = Struct__GstRTSPMediaFactoryClass;
+#[repr(C)]
+pub struct Struct__GstRTSPMediaFactoryClass {
+    pub parent_class: GObjectClass,
+    pub gen_key: ::std::option::Option<extern "C" fn
+                                           (factory: *mut GstRTSPMediaFactory,
+                                            url: *const GstRTSPUrl)
+                                           -> *mut gchar>,
+    pub create_element: ::std::option::Option<extern "C" fn
+                                                  (factory: *mut GstRTSPMediaFactory,
+                                                   url: *const GstRTSPUrl)
+                                                  -> *mut GstElement>,
+    pub construct: ::std::option::Option<extern "C" fn
+                                             (factory: *mut GstRTSPMediaFactory,
+                                              url: *const GstRTSPUrl)
+                                             -> *mut GstRTSPMedia>,
+    pub create_pipeline: ::std::option::Option<extern "C" fn
+                                                   (factory: *mut GstRTSPMediaFactory,
+                                                    media: *mut GstRTSPMedia)
+                                                   -> *mut GstElement>,
+    pub configure: ::std::option::Option<extern "C" fn
+                                             (factory: *mut GstRTSPMediaFactory,
+                                              media: *mut GstRTSPMedia)>,
+    pub media_constructed: ::std::option::Option<extern "C" fn
+                                                     (factory: *mut GstRTSPMediaFactory,
+                                                      media: *mut GstRTSPMedia)>,
+    pub media_configure: ::std::option::Option<extern "C" fn
+                                                   (factory: *mut GstRTSPMediaFactory,
+                                                    media: *mut GstRTSPMedia)>,
+    pub _gst_reserved: [gpointer; 20usize],
+}
+impl ::std::default::Default for Struct__GstRTSPMediaFactoryClass {
+    fn default() -> Struct__GstRTSPMediaFactoryClass { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstRTSPContext = Struct__GstRTSPContext;
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstRTSPContext {
+    pub server: *mut GstRTSPServer,
+    pub conn: *mut GstRTSPConnection,
+    pub client: *mut GstRTSPClient,
+    pub request: *mut GstRTSPMessage,
+    pub uri: *mut GstRTSPUrl,
+    pub method: GstRTSPMethod,
+    pub auth: *mut GstRTSPAuth,
+    pub token: *mut GstRTSPToken,
+    pub session: *mut GstRTSPSession,
+    pub sessmedia: *mut GstRTSPSessionMedia,
+    pub factory: *mut GstRTSPMediaFactory,
+    pub media: *mut GstRTSPMedia,
+    pub stream: *mut GstRTSPStream,
+    pub response: *mut GstRTSPMessage,
+    pub trans: *mut GstRTSPStreamTransport,
+    pub _gst_reserved: [gpointer; 3usize],
+}
+impl ::std::clone::Clone for Struct__GstRTSPContext {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstRTSPContext {
+    fn default() -> Struct__GstRTSPContext { unsafe { ::std::mem::zeroed() } }
+}
+pub enum Struct__GstH264NalParser { }
+pub type GstH264NalParser = Struct__GstH264NalParser;
+pub enum Struct__GstH265Parser { }
+pub type GstH265Parser = Struct__GstH265Parser;
+pub enum Struct__GstH265VPS { }
+pub type GstH265VPS = Struct__GstH265VPS;
+pub type GstH264ParserResult = raw::c_uint;
+pub const GST_H264_PARSER_OK: raw::c_uint = 0;
+pub const GST_H264_PARSER_BROKEN_DATA: raw::c_uint = 1;
+pub const GST_H264_PARSER_BROKEN_LINK: raw::c_uint = 2;
+pub const GST_H264_PARSER_ERROR: raw::c_uint = 3;
+pub const GST_H264_PARSER_NO_NAL: raw::c_uint = 4;
+pub const GST_H264_PARSER_NO_NAL_END: raw::c_uint = 5;
+pub type GstH265ParserResult = raw::c_uint;
+pub const GST_H265_PARSER_OK: raw::c_uint = 0;
+pub const GST_H265_PARSER_BROKEN_DATA: raw::c_uint = 1;
+pub const GST_H265_PARSER_BROKEN_LINK: raw::c_uint = 2;
+pub const GST_H265_PARSER_ERROR: raw::c_uint = 3;
+pub const GST_H265_PARSER_NO_NAL: raw::c_uint = 4;
+pub const GST_H265_PARSER_NO_NAL_END: raw::c_uint = 5;
+pub type GstH264NalUnitExtensionMVC = Struct__GstH264NalUnitExtensionMVC;
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstH264NalUnitExtensionMVC {
+    pub non_idr_flag: guint8,
+    pub priority_id: guint8,
+    pub view_id: guint16,
+    pub temporal_id: guint8,
+    pub anchor_pic_flag: guint8,
+    pub inter_view_flag: guint8,
+}
+impl ::std::clone::Clone for Struct__GstH264NalUnitExtensionMVC {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstH264NalUnitExtensionMVC {
+    fn default() -> Struct__GstH264NalUnitExtensionMVC { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstH264NalUnit = Struct__GstH264NalUnit;
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstH264NalUnit {
+    pub ref_idc: guint16,
+    pub _type: guint16,
+    pub idr_pic_flag: guint8,
+    pub size: guint,
+    pub offset: guint,
+    pub sc_offset: guint,
+    pub valid: gboolean,
+    pub data: *mut guint8,
+    pub header_bytes: guint8,
+    pub extension_type: guint8,
+    pub extension: GstH264NalUnitExtensionMVC,
+}
+impl ::std::clone::Clone for Struct__GstH264NalUnit {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstH264NalUnit {
+    fn default() -> Struct__GstH264NalUnit { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstH264SPS = Struct__GstH264SPS;
+// Only the syntax elements up to vui_parameters_present_flag are
+// declared. The VUI, the calculated values and the MVC extension that
+// follow are covered by _rest, larger than them
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstH264SPS {
+    pub id: gint,
+    pub profile_idc: guint8,
+    pub constraint_set0_flag: guint8,
+    pub constraint_set1_flag: guint8,
+    pub constraint_set2_flag: guint8,
+    pub constraint_set3_flag: guint8,
+    pub constraint_set4_flag: guint8,
+    pub constraint_set5_flag: guint8,
+    pub level_idc: guint8,
+    pub chroma_format_idc: guint8,
+    pub separate_colour_plane_flag: guint8,
+    pub bit_depth_luma_minus8: guint8,
+    pub bit_depth_chroma_minus8: guint8,
+    pub qpprime_y_zero_transform_bypass_flag: guint8,
+    pub scaling_matrix_present_flag: guint8,
+    pub scaling_lists_4x4: [[guint8; 16usize]; 6usize],
+    pub scaling_lists_8x8: [[guint8; 64usize]; 6usize],
+    pub log2_max_frame_num_minus4: guint8,
+    pub pic_order_cnt_type: guint8,
+    pub log2_max_pic_order_cnt_lsb_minus4: guint8,
+    pub delta_pic_order_always_zero_flag: guint8,
+    pub offset_for_non_ref_pic: gint32,
+    pub offset_for_top_to_bottom_field: gint32,
+    pub num_ref_frames_in_pic_order_cnt_cycle: guint8,
+    pub offset_for_ref_frame: [gint32; 255usize],
+    pub num_ref_frames: guint32,
+    pub gaps_in_frame_num_value_allowed_flag: guint8,
+    pub pic_width_in_mbs_minus1: guint32,
+    pub pic_height_in_map_units_minus1: guint32,
+    pub frame_mbs_only_flag: guint8,
+    pub mb_adaptive_frame_field_flag: guint8,
+    pub direct_8x8_inference_flag: guint8,
+    pub frame_cropping_flag: guint8,
+    pub frame_crop_left_offset: guint32,
+    pub frame_crop_right_offset: guint32,
+    pub frame_crop_top_offset: guint32,
+    pub frame_crop_bottom_offset: guint32,
+    pub vui_parameters_present_flag: guint8,
+    pub _rest: [guint64; 512usize],
+}
+impl ::std::clone::Clone for Struct__GstH264SPS {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstH264SPS {
+    fn default() -> Struct__GstH264SPS { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstH265NalUnit = Struct__GstH265NalUnit;
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstH265NalUnit {
+    pub _type: guint8,
+    pub layer_id: guint8,
+    pub temporal_id_plus1: guint8,
+    pub size: guint,
+    pub offset: guint,
+    pub sc_offset: guint,
+    pub valid: gboolean,
+    pub data: *mut guint8,
+    pub header_bytes: guint8,
+}
+impl ::std::clone::Clone for Struct__GstH265NalUnit {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstH265NalUnit {
+    fn default() -> Struct__GstH265NalUnit { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstH265ProfileTierLevel = Struct__GstH265ProfileTierLevel;
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstH265ProfileTierLevel {
+    pub profile_space: guint8,
+    pub tier_flag: guint8,
+    pub profile_idc: guint8,
+    pub profile_compatibility_flag: [guint8; 32usize],
+    pub progressive_source_flag: guint8,
+    pub interlaced_source_flag: guint8,
+    pub non_packed_constraint_flag: guint8,
+    pub frame_only_constraint_flag: guint8,
+    pub max_12bit_constraint_flag: guint8,
+    pub max_10bit_constraint_flag: guint8,
+    pub max_8bit_constraint_flag: guint8,
+    pub max_422chroma_constraint_flag: guint8,
+    pub max_420chroma_constraint_flag: guint8,
+    pub max_monochrome_constraint_flag: guint8,
+    pub intra_constraint_flag: guint8,
+    pub one_picture_only_constraint_flag: guint8,
+    pub lower_bit_rate_constraint_flag: guint8,
+    pub max_14bit_constraint_flag: guint8,
+    pub level_idc: guint8,
+    pub sub_layer_profile_present_flag: [guint8; 6usize],
+    pub sub_layer_level_present_flag: [guint8; 6usize],
+    pub sub_layer_profile_space: [guint8; 6usize],
+    pub sub_layer_tier_flag: [guint8; 6usize],
+    pub sub_layer_profile_idc: [guint8; 6usize],
+    pub sub_layer_profile_compatibility_flag: [[guint8; 32usize]; 6usize],
+    pub sub_layer_progressive_source_flag: [guint8; 6usize],
+    pub sub_layer_interlaced_source_flag: [guint8; 6usize],
+    pub sub_layer_non_packed_constraint_flag: [guint8; 6usize],
+    pub sub_layer_frame_only_constraint_flag: [guint8; 6usize],
+    pub sub_layer_level_idc: [guint8; 6usize],
+}
+impl ::std::clone::Clone for Struct__GstH265ProfileTierLevel {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstH265ProfileTierLevel {
+    fn default() -> Struct__GstH265ProfileTierLevel { unsafe { ::std::mem::zeroed() } }
+}
+pub type GstH265SPS = Struct__GstH265SPS;
+// Only the syntax elements up to bit_depth_chroma_minus8 are declared.
+// The ones that follow, including the scaling lists and the reference
+// picture sets, are covered by _rest, larger than them
+#[repr(C)]
+#[derive(Copy)]
+pub struct Struct__GstH265SPS {
+    pub id: guint8,
+    pub vps: *mut GstH265VPS,
+    pub max_sub_layers_minus1: guint8,
+    pub temporal_id_nesting_flag: guint8,
+    pub profile_tier_level: GstH265ProfileTierLevel,
+    pub chroma_format_idc: guint8,
+    pub separate_colour_plane_flag: guint8,
+    pub pic_width_in_luma_samples: guint16,
+    pub pic_height_in_luma_samples: guint16,
+    pub conformance_window_flag: guint8,
+    pub conf_win_left_offset: guint32,
+    pub conf_win_right_offset: guint32,
+    pub conf_win_top_offset: guint32,
+    pub conf_win_bottom_offset: guint32,
+    pub bit_depth_luma_minus8: guint8,
+    pub bit_depth_chroma_minus8: guint8,
+    pub _rest: [guint64; 8192usize],
+}
+impl ::std::clone::Clone for Struct__GstH265SPS {
+    fn clone(&self) -> Self { *self }
+}
+impl ::std::default::Default for Struct__GstH265SPS {
+    fn default() -> Struct__GstH265SPS { unsafe { ::std::mem::zeroed() } }
+}
+pub type GDBusInterfaceVTable = Struct__GDBusInterfaceVTable;
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct__GDBusInterfaceVTable {
+    pub method_call: GDBusInterfaceMethodCallFunc,
+    pub get_property: GDBusInterfaceGetPropertyFunc,
+    pub set_property: GDBusInterfaceSetPropertyFunc,
+    pub padding: [gpointer; 8usize],
+}
+impl ::std::default::Default for Struct__GDBusInterfaceVTable {
+    fn default() -> Struct__GDBusInterfaceVTable {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
 pub type GstBaseTransform = Struct__GstBaseTransform;
 pub type GstBaseTransformClass = Struct__GstBaseTransformClass;
 pub enum Struct__GstBaseTransformPrivate { }
@@ -7283,6 +8030,115 @@ impl ::std::default::Default for Struct__GstBaseTransformClass {
         unsafe { ::std::mem::zeroed() }
     }
 }
+pub type GstBaseParseFlags = raw::c_uint;
+pub const GST_BASE_PARSE_FLAG_LOST_SYNC: raw::c_uint = 1;
+pub const GST_BASE_PARSE_FLAG_DRAINING: raw::c_uint = 2;
+pub type GstBaseParseFrameFlags = raw::c_uint;
+pub const GST_BASE_PARSE_FRAME_FLAG_NONE: raw::c_uint = 0;
+pub const GST_BASE_PARSE_FRAME_FLAG_NEW_FRAME: raw::c_uint = 1;
+pub const GST_BASE_PARSE_FRAME_FLAG_NO_FRAME: raw::c_uint = 2;
+pub const GST_BASE_PARSE_FRAME_FLAG_CLIP: raw::c_uint = 4;
+pub const GST_BASE_PARSE_FRAME_FLAG_DROP: raw::c_uint = 8;
+pub const GST_BASE_PARSE_FRAME_FLAG_QUEUE: raw::c_uint = 16;
+pub type GstBaseParse = Struct__GstBaseParse;
+pub type GstBaseParseClass = Struct__GstBaseParseClass;
+pub enum Struct__GstBaseParsePrivate { }
+pub type GstBaseParsePrivate = Struct__GstBaseParsePrivate;
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct__GstBaseParse {
+    pub element: GstElement,
+    pub sinkpad: *mut GstPad,
+    pub srcpad: *mut GstPad,
+    pub flags: guint,
+    pub segment: GstSegment,
+    pub _gst_reserved: [gpointer; 20usize],
+    pub _priv: *mut GstBaseParsePrivate,
+}
+impl ::std::default::Default for Struct__GstBaseParse {
+    fn default() -> Struct__GstBaseParse {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+#[repr(C)]
+#[derive(Clone,Copy)]
+pub struct Struct_Unnamed_GstBaseParseFrame {
+    pub buffer: *mut GstBuffer,
+    pub out_buffer: *mut GstBuffer,
+    pub flags: guint,
+    pub offset: guint64,
+    pub overhead: gint,
+    pub size: gint,
+    pub _gst_reserved_i: [guint; 2usize],
+    pub _gst_reserved_p: [gpointer; 2usize],
+    pub _private_flags: guint,
+}
+impl ::std::default::Default for Struct_Unnamed_GstBaseParseFrame {
+    fn default() -> Struct_Unnamed_GstBaseParseFrame {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+pub type GstBaseParseFrame = Struct_Unnamed_GstBaseParseFrame;
+#[repr(C)]
+pub struct Struct__GstBaseParseClass {
+    pub parent_class: GstElementClass,
+    pub start: ::std::option::Option<extern "C" fn(parse: *mut GstBaseParse)
+                                         -> gboolean>,
+    pub stop: ::std::option::Option<extern "C" fn(parse: *mut GstBaseParse)
+                                        -> gboolean>,
+    pub set_sink_caps: ::std::option::Option<extern "C" fn
+                                                 (parse: *mut GstBaseParse,
+                                                  caps: *mut GstCaps)
+                                                 -> gboolean>,
+    pub handle_frame: ::std::option::Option<extern "C" fn
+                                                (parse: *mut GstBaseParse,
+                                                 frame:
+                                                     *mut GstBaseParseFrame,
+                                                 skipsize: *mut gint)
+                                                -> GstFlowReturn>,
+    pub pre_push_frame: ::std::option::Option<extern "C" fn
+                                                  (parse: *mut GstBaseParse,
+                                                   frame:
+                                                       *mut GstBaseParseFrame)
+                                                  -> GstFlowReturn>,
+    pub convert: ::std::option::Option<extern "C" fn
+                                           (parse: *mut GstBaseParse,
+                                            src_format: GstFormat,
+                                            src_value: gint64,
+                                            dest_format: GstFormat,
+                                            dest_value: *mut gint64)
+                                           -> gboolean>,
+    pub sink_event: ::std::option::Option<extern "C" fn
+                                              (parse: *mut GstBaseParse,
+                                               event: *mut GstEvent)
+                                              -> gboolean>,
+    pub src_event: ::std::option::Option<extern "C" fn
+                                             (parse: *mut GstBaseParse,
+                                              event: *mut GstEvent)
+                                             -> gboolean>,
+    pub get_sink_caps: ::std::option::Option<extern "C" fn
+                                                 (parse: *mut GstBaseParse,
+                                                  filter: *mut GstCaps)
+                                                 -> *mut GstCaps>,
+    pub detect: ::std::option::Option<extern "C" fn
+                                          (parse: *mut GstBaseParse,
+                                           buffer: *mut GstBuffer)
+                                          -> GstFlowReturn>,
+    pub sink_query: ::std::option::Option<extern "C" fn
+                                              (parse: *mut GstBaseParse,
+                                               query: *mut GstQuery)
+                                              -> gboolean>,
+    pub src_query: ::std::option::Option<extern "C" fn
+                                             (parse: *mut GstBaseParse,
+                                              query: *mut GstQuery)
+                                             -> gboolean>,
+    pub _gst_reserved: [gpointer; 18usize],
+}
+impl ::std::default::Default for Struct__GstBaseParseClass {
+    fn default() -> Struct__GstBaseParseClass {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
 pub type GstVideoFilter = Struct__GstVideoFilter;
 pub type GstVideoFilterClass = Struct__GstVideoFilterClass;
 #[repr(C)]
@@ -7431,6 +8287,7 @@ pub struct Struct_Unnamed231 {
     pub y: guint,
     pub w: guint,
     pub h: guint,
+    pub params: *mut GList,
 }
 impl ::std::default::Default for Struct_Unnamed231 {
     fn default() -> Struct_Unnamed231 { unsafe { ::std::mem::zeroed() } }
@@ -12169,6 +13026,18 @@ extern "C" {
     pub fn gst_object_remove_control_binding(object: *mut GstObject,
                                              binding: *mut GstControlBinding)
      -> gboolean;
+    pub fn gst_interpolation_control_source_new() -> *mut GstControlSource;
+    pub fn gst_timed_value_control_source_set(_self:
+                                                  *mut GstTimedValueControlSource,
+                                              timestamp: GstClockTime,
+                                              value: gdouble) -> gboolean;
+    pub fn gst_timed_value_control_source_unset_all(_self:
+                                                        *mut GstTimedValueControlSource);
+    pub fn gst_direct_control_binding_new_absolute(object: *mut GstObject,
+                                                   property_name:
+                                                       *const gchar,
+                                                   cs: *mut GstControlSource)
+     -> *mut GstControlBinding;
     pub fn gst_object_get_value(object: *mut GstObject,
                                 property_name: *const gchar,
                                 timestamp: GstClockTime) -> *mut GValue;
@@ -12812,6 +13681,11 @@ extern "C" {
                                stop_type: GstSeekType, stop: guint64,
                                update: *mut gboolean) -> gboolean;
     pub fn gst_sample_get_type() -> GType;
+    pub fn gst_promise_new() -> *mut GstPromise;
+    pub fn gst_promise_wait(promise: *mut GstPromise) -> GstPromiseResult;
+    pub fn gst_promise_interrupt(promise: *mut GstPromise);
+    pub fn gst_promise_get_reply(promise: *mut GstPromise)
+     -> *const GstStructure;
     pub fn gst_sample_new(buffer: *mut GstBuffer, caps: *mut GstCaps,
                           segment: *const GstSegment, info: *mut GstStructure)
      -> *mut GstSample;
@@ -13217,6 +14091,8 @@ extern "C" {
     pub fn gst_device_get_caps(device: *mut GstDevice) -> *mut GstCaps;
     pub fn gst_device_get_display_name(device: *mut GstDevice) -> *mut gchar;
     pub fn gst_device_get_device_class(device: *mut GstDevice) -> *mut gchar;
+    pub fn gst_device_get_properties(device: *mut GstDevice)
+     -> *mut GstStructure;
     pub fn gst_device_reconfigure_element(device: *mut GstDevice,
                                           element: *mut GstElement)
      -> gboolean;
@@ -14922,6 +15798,12 @@ extern "C" {
      -> *mut GstSample;
     pub fn gst_app_sink_pull_sample(appsink: *mut GstAppSink)
      -> *mut GstSample;
+    pub fn gst_app_sink_try_pull_preroll(appsink: *mut GstAppSink,
+                                         timeout: GstClockTime)
+     -> *mut GstSample;
+    pub fn gst_app_sink_try_pull_sample(appsink: *mut GstAppSink,
+                                        timeout: GstClockTime)
+     -> *mut GstSample;
     pub fn gst_app_sink_set_callbacks(appsink: *mut GstAppSink,
                                       callbacks: *mut GstAppSinkCallbacks,
                                       user_data: gpointer,
@@ -14984,6 +15866,9 @@ extern "C" {
     pub fn gst_app_src_get_emit_signals(appsrc: *mut GstAppSrc) -> gboolean;
     pub fn gst_app_src_push_buffer(appsrc: *mut GstAppSrc,
                                    buffer: *mut GstBuffer) -> GstFlowReturn;
+    pub fn gst_app_src_push_buffer_list(appsrc: *mut GstAppSrc,
+                                        buffer_list: *mut GstBufferList)
+     -> GstFlowReturn;
     pub fn gst_app_src_end_of_stream(appsrc: *mut GstAppSrc) -> GstFlowReturn;
     pub fn gst_app_src_set_callbacks(appsrc: *mut GstAppSrc,
                                      callbacks: *mut GstAppSrcCallbacks,
@@ -15305,6 +16190,198 @@ extern "C" {
     pub fn gst_video_encoder_get_allocator(encoder: *mut GstVideoEncoder,
                                            allocator: *mut *mut GstAllocator,
                                            params: *mut GstAllocationParams);
+    pub fn gst_audio_format_from_string(format: *const gchar) -> GstAudioFormat;
+    pub fn gst_audio_format_to_string(format: GstAudioFormat) -> *const gchar;
+    pub fn gst_audio_format_get_info(format: GstAudioFormat)
+     -> *const GstAudioFormatInfo;
+    pub fn gst_audio_info_init(info: *mut GstAudioInfo);
+    pub fn gst_audio_info_set_format(info: *mut GstAudioInfo,
+                                     format: GstAudioFormat, rate: gint,
+                                     channels: gint,
+                                     position: *const GstAudioChannelPosition);
+    pub fn gst_audio_info_from_caps(info: *mut GstAudioInfo,
+                                    caps: *const GstCaps) -> gboolean;
+    pub fn gst_audio_info_is_equal(info: *const GstAudioInfo,
+                                   other: *const GstAudioInfo) -> gboolean;
+    pub fn gst_audio_info_to_caps(info: *const GstAudioInfo) -> *mut GstCaps;
+    pub fn gst_audio_decoder_get_type() -> GType;
+    pub fn gst_audio_decoder_set_output_caps(dec: *mut GstAudioDecoder,
+                                             caps: *mut GstCaps) -> gboolean;
+    pub fn gst_audio_decoder_negotiate(dec: *mut GstAudioDecoder)
+     -> gboolean;
+    pub fn gst_audio_decoder_finish_frame(dec: *mut GstAudioDecoder,
+                                          buf: *mut GstBuffer, frames: gint)
+     -> GstFlowReturn;
+    pub fn gst_audio_decoder_allocate_output_buffer(dec: *mut GstAudioDecoder,
+                                                    size: gsize)
+     -> *mut GstBuffer;
+    pub fn gst_audio_decoder_set_latency(dec: *mut GstAudioDecoder,
+                                         min: GstClockTime,
+                                         max: GstClockTime);
+    pub fn gst_audio_decoder_set_drainable(dec: *mut GstAudioDecoder,
+                                           enabled: gboolean);
+    pub fn gst_audio_decoder_set_needs_format(dec: *mut GstAudioDecoder,
+                                              enabled: gboolean);
+    pub fn gst_audio_decoder_set_plc_aware(dec: *mut GstAudioDecoder,
+                                           plc: gboolean);
+    pub fn gst_audio_encoder_get_type() -> GType;
+    pub fn gst_audio_encoder_set_output_format(enc: *mut GstAudioEncoder,
+                                               caps: *mut GstCaps)
+     -> gboolean;
+    pub fn gst_audio_encoder_negotiate(enc: *mut GstAudioEncoder)
+     -> gboolean;
+    pub fn gst_audio_encoder_finish_frame(enc: *mut GstAudioEncoder,
+                                          buffer: *mut GstBuffer,
+                                          samples: gint) -> GstFlowReturn;
+    pub fn gst_audio_encoder_allocate_output_buffer(enc: *mut GstAudioEncoder,
+                                                    size: gsize)
+     -> *mut GstBuffer;
+    pub fn gst_audio_encoder_set_frame_samples_min(enc: *mut GstAudioEncoder,
+                                                   num: gint);
+    pub fn gst_audio_encoder_set_frame_samples_max(enc: *mut GstAudioEncoder,
+                                                   num: gint);
+    pub fn gst_audio_encoder_set_frame_max(enc: *mut GstAudioEncoder,
+                                           num: gint);
+    pub fn gst_audio_encoder_set_lookahead(enc: *mut GstAudioEncoder,
+                                           num: gint);
+    pub fn gst_audio_encoder_set_latency(enc: *mut GstAudioEncoder,
+                                         min: GstClockTime,
+                                         max: GstClockTime);
+    pub fn gst_audio_encoder_set_headers(enc: *mut GstAudioEncoder,
+                                         headers: *mut GList);
+    pub fn g_bus_own_name(bus_type: GBusType, name: *const gchar,
+                          flags: GBusNameOwnerFlags,
+                          bus_acquired_handler: GBusAcquiredCallback,
+                          name_acquired_handler: GBusNameAcquiredCallback,
+                          name_lost_handler: GBusNameLostCallback,
+                          user_data: gpointer,
+                          user_data_free_func: GDestroyNotify) -> guint;
+    pub fn g_bus_unown_name(owner_id: guint);
+    pub fn g_dbus_node_info_new_for_xml(xml_data: *const gchar,
+                                        error: *mut *mut GError)
+     -> *mut GDBusNodeInfo;
+    pub fn g_dbus_node_info_unref(info: *mut GDBusNodeInfo);
+    pub fn g_dbus_node_info_lookup_interface(info: *mut GDBusNodeInfo,
+                                             name: *const gchar)
+     -> *mut GDBusInterfaceInfo;
+    pub fn g_dbus_connection_register_object(connection: *mut GDBusConnection,
+                                             object_path: *const gchar,
+                                             interface_info: *mut GDBusInterfaceInfo,
+                                             vtable: *const GDBusInterfaceVTable,
+                                             user_data: gpointer,
+                                             user_data_free_func: GDestroyNotify,
+                                             error: *mut *mut GError) -> guint;
+    pub fn g_dbus_connection_unregister_object(connection: *mut GDBusConnection,
+                                               registration_id: guint)
+     -> gboolean;
+    pub fn g_dbus_connection_emit_signal(connection: *mut GDBusConnection,
+                                         destination_bus_name: *const gchar,
+                                         object_path: *const gchar,
+                                         interface_name: *const gchar,
+                                         signal_name: *const gchar,
+                                         parameters: *mut GVariant,
+                                         error: *mut *mut GError) -> gboolean;
+    pub fn g_dbus_method_invocation_return_value(invocation: *mut GDBusMethodInvocation,
+                                                 parameters: *mut GVariant);
+    pub fn g_dbus_error_quark() -> GQuark;
+    pub fn g_dbus_method_invocation_return_dbus_error(invocation: *mut GDBusMethodInvocation,
+                                                      error_name: *const gchar,
+                                                      error_message: *const gchar);
+    pub fn gst_validate_init();
+    pub fn gst_validate_runner_new() -> *mut GstValidateRunner;
+    pub fn gst_validate_runner_get_reports_count(runner: *mut GstValidateRunner)
+     -> guint;
+    pub fn gst_validate_runner_printf(runner: *mut GstValidateRunner) -> raw::c_int;
+    pub fn gst_validate_runner_exit(runner: *mut GstValidateRunner,
+                                    print_result: gboolean) -> raw::c_int;
+    pub fn gst_validate_monitor_factory_create(target: *mut GstObject,
+                                               runner: *mut GstValidateRunner,
+                                               parent: *mut GstValidateMonitor)
+     -> *mut GstValidateMonitor;
+    pub fn gst_validate_scenario_factory_create(runner: *mut GstValidateRunner,
+                                                pipeline: *mut GstElement,
+                                                scenario_name: *const gchar)
+     -> *mut GstValidateScenario;
+    pub fn gst_rtsp_server_new() -> *mut GstRTSPServer;
+    pub fn gst_rtsp_server_set_address(server: *mut GstRTSPServer,
+                                       address: *const gchar);
+    pub fn gst_rtsp_server_set_service(server: *mut GstRTSPServer,
+                                       service: *const gchar);
+    pub fn gst_rtsp_server_get_bound_port(server: *mut GstRTSPServer)
+     -> raw::c_int;
+    pub fn gst_rtsp_server_get_mount_points(server: *mut GstRTSPServer)
+     -> *mut GstRTSPMountPoints;
+    pub fn gst_rtsp_server_set_auth(server: *mut GstRTSPServer,
+                                    auth: *mut GstRTSPAuth);
+    pub fn gst_rtsp_server_attach(server: *mut GstRTSPServer,
+                                  context: *mut GMainContext) -> guint;
+    pub fn gst_rtsp_mount_points_add_factory(mounts: *mut GstRTSPMountPoints,
+                                             path: *const gchar,
+                                             factory: *mut GstRTSPMediaFactory);
+    pub fn gst_rtsp_mount_points_remove_factory(mounts: *mut GstRTSPMountPoints,
+                                                path: *const gchar);
+    pub fn gst_rtsp_media_factory_get_type() -> GType;
+    pub fn gst_rtsp_media_factory_new() -> *mut GstRTSPMediaFactory;
+    pub fn gst_rtsp_media_factory_set_launch(factory: *mut GstRTSPMediaFactory,
+                                             launch: *const gchar);
+    pub fn gst_rtsp_media_factory_set_shared(factory: *mut GstRTSPMediaFactory,
+                                             shared: gboolean);
+    pub fn gst_rtsp_media_factory_is_shared(factory: *mut GstRTSPMediaFactory)
+     -> gboolean;
+    pub fn gst_rtsp_media_factory_get_permissions(factory: *mut GstRTSPMediaFactory)
+     -> *mut GstRTSPPermissions;
+    pub fn gst_rtsp_media_factory_set_permissions(factory: *mut GstRTSPMediaFactory,
+                                                  permissions: *mut GstRTSPPermissions);
+    pub fn gst_rtsp_permissions_new() -> *mut GstRTSPPermissions;
+    pub fn gst_rtsp_permissions_add_role_from_structure(permissions: *mut GstRTSPPermissions,
+                                                        structure: *mut GstStructure);
+    pub fn gst_rtsp_auth_new() -> *mut GstRTSPAuth;
+    pub fn gst_rtsp_auth_make_basic(user: *const gchar, pass: *const gchar)
+     -> *mut gchar;
+    pub fn gst_rtsp_auth_add_basic(auth: *mut GstRTSPAuth, basic: *const gchar,
+                                   token: *mut GstRTSPToken);
+    pub fn gst_rtsp_auth_add_digest(auth: *mut GstRTSPAuth, user: *const gchar,
+                                    pass: *const gchar,
+                                    token: *mut GstRTSPToken);
+    pub fn gst_rtsp_auth_set_supported_methods(auth: *mut GstRTSPAuth,
+                                               methods: GstRTSPAuthMethod);
+    pub fn gst_rtsp_auth_set_default_token(auth: *mut GstRTSPAuth,
+                                           token: *mut GstRTSPToken);
+    pub fn gst_rtsp_token_new_empty() -> *mut GstRTSPToken;
+    pub fn gst_rtsp_token_writable_structure(token: *mut GstRTSPToken)
+     -> *mut GstStructure;
+    pub fn gst_rtsp_token_get_string(token: *mut GstRTSPToken,
+                                     field: *const gchar) -> *const gchar;
+    pub fn gst_rtsp_context_get_current() -> *mut GstRTSPContext;
+    pub fn gst_rtsp_client_get_connection(client: *mut GstRTSPClient)
+     -> *mut GstRTSPConnection;
+    pub fn gst_rtsp_connection_get_ip(conn: *const GstRTSPConnection)
+     -> *const gchar;
+    pub fn gst_rtsp_url_get_request_uri(url: *const GstRTSPUrl) -> *mut gchar;
+    pub fn gst_h264_nal_parser_new() -> *mut GstH264NalParser;
+    pub fn gst_h264_nal_parser_free(nalparser: *mut GstH264NalParser);
+    pub fn gst_h264_parser_identify_nalu_unchecked(nalparser: *mut GstH264NalParser,
+                                                   data: *const guint8,
+                                                   offset: guint, size: gsize,
+                                                   nalu: *mut GstH264NalUnit)
+     -> GstH264ParserResult;
+    pub fn gst_h264_parser_parse_sps(nalparser: *mut GstH264NalParser,
+                                     nalu: *mut GstH264NalUnit,
+                                     sps: *mut GstH264SPS)
+     -> GstH264ParserResult;
+    pub fn gst_h264_sps_clear(sps: *mut GstH264SPS);
+    pub fn gst_h265_parser_new() -> *mut GstH265Parser;
+    pub fn gst_h265_parser_free(parser: *mut GstH265Parser);
+    pub fn gst_h265_parser_identify_nalu_unchecked(parser: *mut GstH265Parser,
+                                                   data: *const guint8,
+                                                   offset: guint, size: gsize,
+                                                   nalu: *mut GstH265NalUnit)
+     -> GstH265ParserResult;
+    pub fn gst_h265_parser_parse_sps(parser: *mut GstH265Parser,
+                                     nalu: *mut GstH265NalUnit,
+                                     sps: *mut GstH265SPS,
+                                     parse_vui_params: gboolean)
+     -> GstH265ParserResult;
     pub fn gst_base_transform_get_type() -> GType;
     pub fn gst_base_transform_set_passthrough(trans: *mut GstBaseTransform,
                                               passthrough: gboolean);
@@ -15335,6 +16412,33 @@ extern "C" {
                                             params: *mut GstAllocationParams);
     pub fn gst_base_transform_reconfigure_sink(trans: *mut GstBaseTransform);
     pub fn gst_base_transform_reconfigure_src(trans: *mut GstBaseTransform);
+    pub fn gst_base_parse_get_type() -> GType;
+    pub fn gst_base_parse_finish_frame(parse: *mut GstBaseParse,
+                                       frame: *mut GstBaseParseFrame,
+                                       size: gint) -> GstFlowReturn;
+    pub fn gst_base_parse_set_duration(parse: *mut GstBaseParse,
+                                       fmt: GstFormat, duration: gint64,
+                                       interval: gint);
+    pub fn gst_base_parse_set_average_bitrate(parse: *mut GstBaseParse,
+                                              bitrate: guint);
+    pub fn gst_base_parse_set_min_frame_size(parse: *mut GstBaseParse,
+                                             min_size: guint);
+    pub fn gst_base_parse_set_has_timing_info(parse: *mut GstBaseParse,
+                                              has_timing: gboolean);
+    pub fn gst_base_parse_set_syncable(parse: *mut GstBaseParse,
+                                       syncable: gboolean);
+    pub fn gst_base_parse_set_passthrough(parse: *mut GstBaseParse,
+                                          passthrough: gboolean);
+    pub fn gst_base_parse_set_pts_interpolation(parse: *mut GstBaseParse,
+                                                pts_interpolate: gboolean);
+    pub fn gst_base_parse_set_infer_ts(parse: *mut GstBaseParse,
+                                       infer_ts: gboolean);
+    pub fn gst_base_parse_set_frame_rate(parse: *mut GstBaseParse,
+                                         fps_num: guint, fps_den: guint,
+                                         lead_in: guint, lead_out: guint);
+    pub fn gst_base_parse_set_latency(parse: *mut GstBaseParse,
+                                      min_latency: GstClockTime,
+                                      max_latency: GstClockTime);
     pub fn gst_video_filter_get_type() -> GType;
     pub fn gst_video_meta_api_get_type() -> GType;
     pub fn gst_video_meta_get_info() -> *const GstMetaInfo;
@@ -15399,6 +16503,13 @@ extern "C" {
                                                            x: guint, y: guint,
                                                            w: guint, h: guint)
      -> *mut GstVideoRegionOfInterestMeta;
+    pub fn gst_video_region_of_interest_meta_add_param(meta:
+                                                           *mut GstVideoRegionOfInterestMeta,
+                                                       s: *mut GstStructure);
+    pub fn gst_video_region_of_interest_meta_get_param(meta:
+                                                           *mut GstVideoRegionOfInterestMeta,
+                                                       name: *const gchar)
+     -> *mut GstStructure;
     pub fn gst_buffer_pool_config_set_video_alignment(config:
                                                           *mut GstStructure,
                                                       align:
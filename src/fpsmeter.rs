@@ -0,0 +1,155 @@
+use ffi::*;
+use pad::Pad;
+use reference::Reference;
+use unwind;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Rates measured by an `FpsMeter` over its window
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FpsStats{
+    /// Buffers per second, the frame rate for video streams
+    pub fps: f64,
+    /// Bits per second
+    pub bitrate: f64,
+    /// Buffers seen since the meter was attached
+    pub frames: u64,
+    /// Bytes seen since the meter was attached
+    pub bytes: u64,
+}
+
+struct Meter{
+    window: GstClockTime,
+    // arrival time and size of the buffers in the window
+    arrivals: VecDeque<(GstClockTime, u64)>,
+    window_bytes: u64,
+    frames: u64,
+    bytes: u64,
+    last_update: GstClockTime,
+    on_update: Option<Box<dyn FnMut(&FpsStats) + Send>>,
+}
+
+impl Meter{
+    fn push(&mut self, now: GstClockTime, size: u64){
+        self.arrivals.push_back((now, size));
+        self.window_bytes += size;
+        self.frames += 1;
+        self.bytes += size;
+    }
+
+    fn expire(&mut self, now: GstClockTime){
+        while let Some(&(time, size)) = self.arrivals.front(){
+            if now.saturating_sub(time) <= self.window{
+                break;
+            }
+            self.arrivals.pop_front();
+            self.window_bytes -= size;
+        }
+    }
+
+    fn stats(&mut self, now: GstClockTime) -> FpsStats{
+        self.expire(now);
+        let seconds = self.window as f64 / 1e9;
+        FpsStats{
+            fps: self.arrivals.len() as f64 / seconds,
+            bitrate: self.window_bytes as f64 * 8.0 / seconds,
+            frames: self.frames,
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// Measures the rate of the buffers going through a pad with a probe,
+/// without rendering them like fpsdisplaysink does, so it can be put
+/// anywhere in a pipeline, like after a decoder or before a network sink.
+///
+/// The rates are averaged over a rolling window of wall clock time. They
+/// can be queried with `stats` or received with `on_update` once per
+/// window. The probe is removed when the meter is dropped.
+pub struct FpsMeter{
+    pad: Pad,
+    probe: u64,
+    meter: Arc<Mutex<Meter>>,
+}
+
+impl FpsMeter{
+    /// Attaches a meter to `pad`, averaging over `window` nanoseconds
+    pub fn new(pad: &mut Pad, window: GstClockTime) -> FpsMeter{
+        let meter = Arc::new(Mutex::new(Meter{
+            window: window.max(1),
+            arrivals: VecDeque::new(),
+            window_bytes: 0,
+            frames: 0,
+            bytes: 0,
+            last_update: unsafe{ gst_util_get_timestamp() },
+            on_update: None,
+        }));
+        let data = Box::new(meter.clone());
+        let probe = unsafe{
+            gst_pad_add_probe(pad.gst_pad_mut(), GST_PAD_PROBE_TYPE_BUFFER | GST_PAD_PROBE_TYPE_BUFFER_LIST,
+                              Some(fps_probe), Box::into_raw(data) as gpointer,
+                              Some(free_fps_probe)) as u64
+        };
+        FpsMeter{ pad: pad.reference(), probe: probe, meter: meter }
+    }
+
+    /// Calls `callback` with the current rates once per window, from the
+    /// streaming thread. Nothing is reported while no buffers flow
+    pub fn on_update<F: FnMut(&FpsStats) + Send + 'static>(&mut self, callback: F){
+        self.meter.lock().unwrap().on_update = Some(Box::new(callback));
+    }
+
+    /// The rates over the last window
+    pub fn stats(&self) -> FpsStats{
+        let now = unsafe{ gst_util_get_timestamp() };
+        self.meter.lock().unwrap().stats(now)
+    }
+
+    pub fn fps(&self) -> f64{
+        self.stats().fps
+    }
+
+    pub fn bitrate(&self) -> f64{
+        self.stats().bitrate
+    }
+}
+
+impl Drop for FpsMeter{
+    fn drop(&mut self){
+        self.pad.remove_probe(self.probe);
+    }
+}
+
+extern "C" fn fps_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        let meter = &*(data as *const Arc<Mutex<Meter>>);
+        let mut meter = meter.lock().unwrap();
+        let now = gst_util_get_timestamp();
+        if (*info)._type & GST_PAD_PROBE_TYPE_BUFFER_LIST != 0{
+            let list = (*info).data as *mut GstBufferList;
+            for idx in 0..gst_buffer_list_length(list){
+                let size = gst_buffer_get_size(gst_buffer_list_get(list, idx)) as u64;
+                meter.push(now, size);
+            }
+        }else{
+            let size = gst_buffer_get_size((*info).data as *mut GstBuffer) as u64;
+            meter.push(now, size);
+        }
+
+        if now.saturating_sub(meter.last_update) >= meter.window{
+            meter.last_update = now;
+            let stats = meter.stats(now);
+            if let Some(ref mut on_update) = meter.on_update{
+                unwind::guard(pad as *mut GstObject, (), || on_update(&stats));
+            }
+        }
+        GST_PAD_PROBE_OK
+    }
+}
+
+extern "C" fn free_fps_probe(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Arc<Mutex<Meter>>));
+    }
+}
@@ -0,0 +1,113 @@
+use ffi::*;
+use appsink::AppSink;
+use element::Element;
+use error::Error;
+use structure::Structure;
+use videoframe::VideoFrame;
+
+use std::sync::{Arc, Mutex};
+
+/// Per frame analysis plugged into a `FrameAnalysisSink`, like barcode or
+/// QR code readers, face detection or other computer vision algorithms.
+///
+/// Analyzers are called from the streaming thread with every frame and its
+/// timestamp. The structure they return, if any, is posted on the bus as
+/// an element message with the timestamp added as a `pts` field, so
+/// results can be handled with the rest of the pipeline messages.
+pub trait FrameAnalyzer: Send{
+    fn analyze(&mut self, frame: &VideoFrame, pts: GstClockTime) -> Option<Structure>;
+}
+
+impl<F: FnMut(&VideoFrame, GstClockTime) -> Option<Structure> + Send> FrameAnalyzer for F{
+    fn analyze(&mut self, frame: &VideoFrame, pts: GstClockTime) -> Option<Structure>{
+        self(frame, pts)
+    }
+}
+
+type Analyzers = Arc<Mutex<Vec<Box<dyn FrameAnalyzer>>>>;
+
+// the appsink posting the results, which outlives its callbacks
+struct Poster(*mut GstElement);
+
+unsafe impl Send for Poster {}
+
+impl Poster{
+    fn post(&self, mut structure: Structure, pts: GstClockTime){
+        structure.set("pts", pts);
+        unsafe{
+            let msg = gst_message_new_element(self.0 as *mut GstObject, ::Transfer::transfer(structure));
+            gst_element_post_message(self.0, msg);
+        }
+    }
+}
+
+/// An appsink that runs a set of `FrameAnalyzer`s on every video frame it
+/// receives. The caps of the sink can be set through `as_mut` to get
+/// frames in the format the analyzers expect, like GRAY8 for most
+/// barcode readers.
+pub struct FrameAnalysisSink{
+    appsink: AppSink,
+    analyzers: Analyzers,
+}
+
+impl FrameAnalysisSink{
+    pub fn new(name: &str) -> Result<FrameAnalysisSink, Error>{
+        Element::new("appsink", name).map(FrameAnalysisSink::new_from_element)
+    }
+
+    /// Configures an already existing appsink, usually retrieved from a
+    /// pipeline with `Bin::get_by_name`, to run the analyzers
+    pub fn new_from_element(element: Element) -> FrameAnalysisSink{
+        let analyzers: Analyzers = Arc::new(Mutex::new(vec![]));
+        let mut appsink = AppSink::new_from_element(element);
+        let poster = Poster(unsafe{ appsink.gst_element() as *mut GstElement });
+        let callback_analyzers = analyzers.clone();
+        appsink.on_new_sample(move |sample|{
+            let frame = match sample.video_frame(){
+                Some(frame) => frame,
+                None => return GST_FLOW_OK
+            };
+            let pts = unsafe{ (*frame.buffer().gst_buffer()).pts };
+            for analyzer in callback_analyzers.lock().unwrap().iter_mut(){
+                if let Some(structure) = analyzer.analyze(&frame, pts){
+                    poster.post(structure, pts);
+                }
+            }
+            GST_FLOW_OK
+        });
+        FrameAnalysisSink{ appsink: appsink, analyzers: analyzers }
+    }
+
+    /// Adds an analyzer, run after the ones already added
+    pub fn add_analyzer<A: FrameAnalyzer + 'static>(&mut self, analyzer: A){
+        self.analyzers.lock().unwrap().push(Box::new(analyzer));
+    }
+
+    pub fn clear_analyzers(&mut self){
+        self.analyzers.lock().unwrap().clear();
+    }
+}
+
+impl AsRef<AppSink> for FrameAnalysisSink{
+    fn as_ref(&self) -> &AppSink{
+        &self.appsink
+    }
+}
+
+impl AsMut<AppSink> for FrameAnalysisSink{
+    fn as_mut(&mut self) -> &mut AppSink{
+        &mut self.appsink
+    }
+}
+
+impl From<FrameAnalysisSink> for Element{
+    fn from(sink: FrameAnalysisSink) -> Element{
+        sink.appsink.into()
+    }
+}
+
+impl ::Transfer for FrameAnalysisSink{
+    unsafe fn transfer(self) -> *mut GstElement{
+        ::Transfer::transfer(self.appsink)
+    }
+}
@@ -0,0 +1,158 @@
+use ffi::*;
+use bin::Bin;
+use caps::Caps;
+use element::Element;
+use error::{Error, Result};
+use reference::Reference;
+
+use std::ops::{Deref, DerefMut};
+
+/// Frame counters of a `FrameRateConverter`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameRateStats{
+    /// Frames received
+    pub input: u64,
+    /// Frames produced
+    pub output: u64,
+    /// Frames dropped to lower the framerate or because of timestamps
+    /// going backwards
+    pub dropped: u64,
+    /// Frames duplicated to raise the framerate or fill gaps
+    pub duplicated: u64,
+}
+
+/// Normalizes a video stream of any framerate, like the variable or
+/// unusual rates produced by many cameras, to a fixed target framerate
+/// by dropping or duplicating frames.
+///
+/// It's a bin containing a videorate followed by a capsfilter that can be
+/// linked as any other element. The target framerate can be changed
+/// while running, which triggers a renegotiation with downstream.
+pub struct FrameRateConverter{
+    bin: Bin,
+    videorate: Element,
+    capsfilter: Element,
+    framerate: (i32, i32),
+}
+
+unsafe impl Sync for FrameRateConverter {}
+unsafe impl Send for FrameRateConverter {}
+
+impl FrameRateConverter{
+    /// Creates a converter producing fps_n/fps_d frames per second. Fails
+    /// if the framerate isn't positive
+    pub fn new(name: &str, fps_n: i32, fps_d: i32) -> Result<FrameRateConverter>{
+        let mut bin = Bin::new(name)?;
+        let mut videorate = Element::new("videorate", "")?;
+        let mut capsfilter = Element::new("capsfilter", "")?;
+        if !bin.add_and_link_many(vec![videorate.reference(), capsfilter.reference()]) ||
+           !bin.add_ghost_pad(&mut videorate, "sink", "sink") ||
+           !bin.add_ghost_pad(&mut capsfilter, "src", "src"){
+            return Err(Error::new(0,0,"Couldn't link framerate converter"));
+        }
+        let mut converter = FrameRateConverter{
+            bin: bin,
+            videorate: videorate,
+            capsfilter: capsfilter,
+            framerate: (fps_n, fps_d),
+        };
+        converter.set_framerate(fps_n, fps_d)?;
+        Ok(converter)
+    }
+
+    /// Changes the target framerate, renegotiating with downstream if
+    /// the converter is already running. Fails and keeps the previous
+    /// framerate if fps_n or fps_d isn't positive
+    pub fn set_framerate(&mut self, fps_n: i32, fps_d: i32) -> Result<()>{
+        if fps_n <= 0 || fps_d <= 0{
+            return Err(Error::new(0,0,&format!("Invalid framerate {}/{}", fps_n, fps_d)));
+        }
+        let caps = Caps::from_string(&format!("video/x-raw,framerate={}/{}", fps_n, fps_d))
+            .ok_or(Error::new(0,0,"Couldn't create the framerate caps"))?;
+        self.capsfilter.set("caps", &caps);
+        self.framerate = (fps_n, fps_d);
+        Ok(())
+    }
+
+    /// Current target framerate as (numerator, denominator)
+    pub fn framerate(&self) -> (i32, i32){
+        self.framerate
+    }
+
+    /// Only drop frames, never duplicate them. Useful to limit the
+    /// framerate of a source without increasing it when it's lower
+    pub fn set_drop_only(&mut self, drop_only: bool){
+        self.videorate.set("drop-only", drop_only as gboolean);
+    }
+
+    /// Don't produce buffers before the first one received, instead of
+    /// filling from the segment start with duplicates of it
+    pub fn set_skip_to_first(&mut self, skip_to_first: bool){
+        self.videorate.set("skip-to-first", skip_to_first as gboolean);
+    }
+
+    /// Time in nanoseconds over which the input framerate is averaged
+    /// instead of matching frames one by one, 0 to disable
+    pub fn set_average_period(&mut self, period: u64){
+        self.videorate.set("average-period", period);
+    }
+
+    /// Whether to stop notifying the "drop" and "duplicate" properties
+    /// each time a frame is dropped or duplicated. videorate is silent by
+    /// default, pass false to get the notifications
+    pub fn set_silent(&mut self, silent: bool){
+        self.videorate.set("silent", silent as gboolean);
+    }
+
+    /// Frames received, produced, dropped and duplicated so far
+    pub fn stats(&self) -> FrameRateStats{
+        FrameRateStats{
+            input: self.videorate.get("in"),
+            output: self.videorate.get("out"),
+            dropped: self.videorate.get("drop"),
+            duplicated: self.videorate.get("duplicate"),
+        }
+    }
+
+    /// The inner videorate element
+    pub fn videorate(&self) -> &Element{
+        &self.videorate
+    }
+}
+
+impl AsRef<Bin> for FrameRateConverter{
+    fn as_ref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl AsMut<Bin> for FrameRateConverter{
+    fn as_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl From<FrameRateConverter> for Element{
+    fn from(converter: FrameRateConverter) -> Element{
+        converter.bin.into()
+    }
+}
+
+impl Deref for FrameRateConverter{
+    type Target = Bin;
+    fn deref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl DerefMut for FrameRateConverter{
+    fn deref_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl ::Transfer for FrameRateConverter{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.bin.transfer()
+    }
+}
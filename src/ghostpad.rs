@@ -0,0 +1,109 @@
+use ffi::*;
+use pad::Pad;
+use util::*;
+use reference::Reference;
+
+use std::ops::{Deref, DerefMut};
+
+/// A pad that proxies another pad, its target, usually to expose the pad
+/// of an element inside a bin as a pad of the bin itself. Add it to the
+/// bin with `Bin::add_pad`.
+pub struct GhostPad{
+    pad: Pad
+}
+
+impl GhostPad{
+    /// Creates a ghost pad named `name` for `target`, with the same
+    /// direction as the target
+    pub fn new(name: &str, target: &mut Pad) -> Option<GhostPad>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let pad = gst_ghost_pad_new(cname.as_ptr(), target.gst_pad_mut());
+            GhostPad::new_from_gst_ghost_pad(pad)
+        }
+    }
+
+    /// Creates a ghost pad without target, which can be set later with
+    /// `set_target`, like when the element it proxies isn't created yet
+    pub fn new_no_target(name: &str, direction: GstPadDirection) -> Option<GhostPad>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let pad = gst_ghost_pad_new_no_target(cname.as_ptr(), direction);
+            GhostPad::new_from_gst_ghost_pad(pad)
+        }
+    }
+
+    pub unsafe fn new_from_gst_ghost_pad(pad: *mut GstPad) -> Option<GhostPad>{
+        if pad != ptr::null_mut(){
+            gst_object_ref_sink(pad as gpointer);
+        }
+        Pad::new(pad).map(|pad| GhostPad{ pad: pad })
+    }
+
+    /// Sets the pad the ghost pad proxies, or clears it with None. Fails if
+    /// the target has a different direction than the ghost pad or can't be
+    /// linked to it
+    pub fn set_target(&mut self, target: Option<&mut Pad>) -> bool{
+        unsafe{
+            let target = target.map(|target| target.gst_pad_mut()).unwrap_or(ptr::null_mut());
+            gst_ghost_pad_set_target(self.gst_ghost_pad_mut(), target) == 1
+        }
+    }
+
+    pub fn get_target(&self) -> Option<Pad>{
+        unsafe{
+            Pad::new(gst_ghost_pad_get_target(self.gst_ghost_pad() as *mut GstGhostPad))
+        }
+    }
+
+    pub unsafe fn gst_ghost_pad(&self) -> *const GstGhostPad{
+        self.pad.gst_pad() as *const GstGhostPad
+    }
+
+    pub unsafe fn gst_ghost_pad_mut(&mut self) -> *mut GstGhostPad{
+        self.pad.gst_pad_mut() as *mut GstGhostPad
+    }
+}
+
+impl ::Transfer<GstPad> for GhostPad{
+    unsafe fn transfer(self) -> *mut GstPad{
+        self.pad.transfer()
+    }
+}
+
+impl Reference for GhostPad{
+    fn reference(&self) -> GhostPad{
+        GhostPad{ pad: self.pad.reference() }
+    }
+}
+
+impl AsRef<Pad> for GhostPad{
+    fn as_ref(&self) -> &Pad{
+        &self.pad
+    }
+}
+
+impl AsMut<Pad> for GhostPad{
+    fn as_mut(&mut self) -> &mut Pad{
+        &mut self.pad
+    }
+}
+
+impl From<GhostPad> for Pad{
+    fn from(b: GhostPad) -> Pad{
+        b.pad
+    }
+}
+
+impl Deref for GhostPad{
+    type Target = Pad;
+    fn deref(&self) -> &Pad{
+        &self.pad
+    }
+}
+
+impl DerefMut for GhostPad{
+    fn deref_mut(&mut self) -> &mut Pad{
+        &mut self.pad
+    }
+}
@@ -0,0 +1,164 @@
+use ffi::*;
+use elementfactory::{ElementFactory, ELEMENT_FACTORY_TYPE_DECODER, ELEMENT_FACTORY_TYPE_ENCODER};
+
+/// Hardware video acceleration APIs with GStreamer plugins
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HwApi{
+    /// VA-API on Linux, from the vaapi or the newer va plugin
+    Vaapi,
+    /// NVIDIA NVDEC and NVENC
+    Nvcodec,
+    /// V4L2 memory to memory codecs, common on ARM boards
+    V4l2M2m,
+    /// Apple VideoToolbox
+    VideoToolbox,
+    /// Direct3D 11 on Windows
+    D3d11,
+}
+
+impl HwApi{
+    fn from_factory(factory: &ElementFactory) -> Option<HwApi>{
+        let plugin = factory.plugin_name()?;
+        match plugin.as_str(){
+            "vaapi" | "va" => Some(HwApi::Vaapi),
+            "nvcodec" | "nvdec" | "nvenc" => Some(HwApi::Nvcodec),
+            "video4linux2" => Some(HwApi::V4l2M2m),
+            "applemedia" => Some(HwApi::VideoToolbox),
+            "d3d11" => Some(HwApi::D3d11),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HwCodecKind{
+    Decoder,
+    Encoder,
+}
+
+/// A hardware accelerated decoder or encoder found in the registry
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HwCodec{
+    pub api: HwApi,
+    pub kind: HwCodecKind,
+    /// Name of the element factory, like "vah264dec"
+    pub factory: String,
+    /// Media types of the encoded side, like "video/x-h264"
+    pub media_types: Vec<String>,
+    pub rank: u32,
+}
+
+/// The hardware accelerated codecs available on this system, detected
+/// from the plugins in the registry. Plugins only register the codecs
+/// the hardware supports, so a codec being listed means it can be used,
+/// although drivers can still fail at runtime.
+///
+/// Besides listing the codecs it can change their ranks, so decodebin,
+/// playbin and `ElementFactory::best_decoder_for` prefer or avoid an API.
+pub struct HwAccel{
+    codecs: Vec<HwCodec>,
+}
+
+impl HwAccel{
+    pub fn detect() -> HwAccel{
+        let mut codecs = vec![];
+        for &(list_type, kind, direction) in &[(ELEMENT_FACTORY_TYPE_DECODER, HwCodecKind::Decoder, GST_PAD_SINK),
+                                               (ELEMENT_FACTORY_TYPE_ENCODER, HwCodecKind::Encoder, GST_PAD_SRC)]{
+            for factory in ElementFactory::list(list_type, GST_RANK_NONE){
+                let api = match HwApi::from_factory(&factory){
+                    Some(api) => api,
+                    None => continue
+                };
+                let caps = factory.template_caps(direction);
+                let mut media_types: Vec<String> = caps.structures().map(|s| s.name().to_string()).collect();
+                media_types.sort();
+                media_types.dedup();
+                codecs.push(HwCodec{
+                    api: api,
+                    kind: kind,
+                    factory: factory.name(),
+                    media_types: media_types,
+                    rank: factory.rank(),
+                });
+            }
+        }
+        HwAccel{ codecs: codecs }
+    }
+
+    pub fn codecs(&self) -> &[HwCodec]{
+        &self.codecs
+    }
+
+    /// The APIs with at least one codec
+    pub fn apis(&self) -> Vec<HwApi>{
+        let mut apis: Vec<HwApi> = vec![];
+        for codec in self.codecs.iter(){
+            if !apis.contains(&codec.api){
+                apis.push(codec.api);
+            }
+        }
+        apis
+    }
+
+    /// Hardware decoders for `media_type`, like "video/x-h265", sorted by
+    /// decreasing rank
+    pub fn decoders_for(&self, media_type: &str) -> Vec<&HwCodec>{
+        self.codecs_for(HwCodecKind::Decoder, media_type)
+    }
+
+    /// Hardware encoders producing `media_type`, sorted by decreasing rank
+    pub fn encoders_for(&self, media_type: &str) -> Vec<&HwCodec>{
+        self.codecs_for(HwCodecKind::Encoder, media_type)
+    }
+
+    fn codecs_for(&self, kind: HwCodecKind, media_type: &str) -> Vec<&HwCodec>{
+        let mut codecs: Vec<&HwCodec> = self.codecs.iter()
+            .filter(|codec| codec.kind == kind && codec.media_types.iter().any(|m| m == media_type))
+            .collect();
+        codecs.sort_by(|a, b| b.rank.cmp(&a.rank));
+        codecs
+    }
+
+    /// Name of the factory to use to decode `media_type`, using the APIs
+    /// in `preference` order, None if none of them has a decoder for it
+    pub fn preferred_decoder(&self, media_type: &str, preference: &[HwApi]) -> Option<String>{
+        self.preferred(HwCodecKind::Decoder, media_type, preference)
+    }
+
+    /// Name of the factory to use to encode to `media_type`, using the
+    /// APIs in `preference` order
+    pub fn preferred_encoder(&self, media_type: &str, preference: &[HwApi]) -> Option<String>{
+        self.preferred(HwCodecKind::Encoder, media_type, preference)
+    }
+
+    fn preferred(&self, kind: HwCodecKind, media_type: &str, preference: &[HwApi]) -> Option<String>{
+        let codecs = self.codecs_for(kind, media_type);
+        preference.iter()
+            .filter_map(|api| codecs.iter().find(|codec| codec.api == *api))
+            .map(|codec| codec.factory.clone())
+            .next()
+    }
+
+    /// Sets the rank of all the codecs of `api`, GST_RANK_NONE to keep
+    /// autoplugging from using them
+    pub fn set_rank(&mut self, api: HwApi, rank: u32){
+        for codec in self.codecs.iter_mut().filter(|codec| codec.api == api){
+            if let Some(mut factory) = ElementFactory::find(&codec.factory){
+                factory.set_rank(rank);
+                codec.rank = rank;
+            }
+        }
+    }
+
+    /// Ranks the codecs of `api` above the software ones and the other
+    /// APIs so autoplugging picks them first
+    pub fn prefer(&mut self, api: HwApi){
+        self.set_rank(api, GST_RANK_PRIMARY + 1);
+    }
+
+    /// Keeps autoplugging from using the codecs of `api`, like a driver
+    /// known to fail
+    pub fn disable(&mut self, api: HwApi){
+        self.set_rank(api, GST_RANK_NONE);
+    }
+}
@@ -0,0 +1,92 @@
+use ffi::*;
+use appsink::{self, AppSink};
+use appsrc::AppSrc;
+use buffer::Buffer;
+use caps::Caps;
+use error::{Error, Result};
+use message::Message;
+use pipeline::Pipeline;
+use sample::Sample;
+
+use std::ptr;
+
+// images are small, a pipeline taking longer is stuck, for example
+// waiting for a caps it never gets
+const ONE_SHOT_TIMEOUT: GstClockTime = 10 * 1_000_000_000;
+
+/// Encodes a raw video frame, for example one pulled from an appsink,
+/// as a JPEG image. `quality` goes from 0 to 100.
+pub fn encode_frame_to_jpeg(sample: &Sample, quality: u32) -> Result<Vec<u8>>{
+    let description = format!("jpegenc quality={}", quality);
+    encode_frame(sample, &description)
+}
+
+/// Encodes a raw video frame as a PNG image. `compression_level` goes
+/// from 0 (no compression) to 9.
+pub fn encode_frame_to_png(sample: &Sample, compression_level: u32) -> Result<Vec<u8>>{
+    let description = format!("pngenc compression-level={}", compression_level);
+    encode_frame(sample, &description)
+}
+
+/// Decodes an encoded image, like a JPEG or PNG file, into a sample
+/// containing a raw video frame in the decoder's native format
+pub fn decode_image_to_sample(bytes: &[u8]) -> Result<Sample>{
+    let buffer = Buffer::from_slice(bytes);
+    run_one_shot("appsrc name=src ! decodebin ! videoconvert ! appsink name=sink sync=false", None, buffer)
+}
+
+fn encode_frame(sample: &Sample, encoder: &str) -> Result<Vec<u8>>{
+    let caps = sample.caps().ok_or(Error::new(0, 0, "Sample has no caps"))?;
+    let buffer = sample.buffer().ok_or(Error::new(0, 0, "Sample has no buffer"))?;
+    let description = format!("appsrc name=src ! videoconvert ! {} ! appsink name=sink sync=false", encoder);
+    let encoded = run_one_shot(&description, Some(caps), buffer)?;
+    let encoded = encoded.buffer().ok_or(Error::new(0, 0, "Encoder produced no buffer"))?;
+    encoded.map_read()
+        .map(|mapping| mapping.to_vec())
+        .ok_or(Error::new(0, 0, "Couldn't map encoded buffer"))
+}
+
+/// Pushes a single buffer through an ephemeral pipeline with an appsrc
+/// named src and an appsink named sink and returns the last sample
+/// produced before EOS. Fails if the pipeline doesn't finish within
+/// ONE_SHOT_TIMEOUT
+fn run_one_shot(description: &str, caps: Option<Caps>, buffer: Buffer) -> Result<Sample>{
+    let mut pipeline = Pipeline::new_from_str(description)?;
+    let src = pipeline.get_by_name("src").ok_or(Error::new(0, 0, "Pipeline has no element named src"))?;
+    let sink = pipeline.get_by_name("sink").ok_or(Error::new(0, 0, "Pipeline has no element named sink"))?;
+    let mut appsrc = AppSrc::new_from_element(src);
+    let appsink = AppSink::new_from_element(sink);
+    if let Some(caps) = caps{
+        appsrc.set_caps(&caps);
+    }
+
+    let bus = pipeline.bus().ok_or(Error::new(0, 0, "Couldn't get pipeline bus"))?;
+    pipeline.play();
+    appsrc.push_buffer(buffer);
+    appsrc.end_of_stream();
+
+    let result = unsafe{
+        let msg = gst_bus_timed_pop_filtered(bus.gst_bus() as *mut GstBus, ONE_SHOT_TIMEOUT,
+                                             GST_MESSAGE_ERROR | GST_MESSAGE_EOS);
+        let message = Message::new(msg);
+        if msg != ptr::null_mut(){
+            gst_mini_object_unref(msg as *mut GstMiniObject);
+        }
+        match message.map(|message| message.parse()){
+            Some(Message::ErrorParsed{ref error, ref debug, ..}) =>
+                Err(Error::new(error.domain(), error.code(), &format!("{} ({})", error.message(), debug))),
+            None => Err(Error::new(0, 0, "Timed out waiting for the pipeline to finish")),
+            _ => {
+                let mut last = None;
+                while let Ok(msg) = appsink.try_recv(){
+                    if let appsink::Message::NewSample(sample) = msg{
+                        last = Some(sample);
+                    }
+                }
+                last.ok_or(Error::new(0, 0, "Pipeline produced no sample"))
+            }
+        }
+    };
+    pipeline.set_null_state();
+    result
+}
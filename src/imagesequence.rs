@@ -0,0 +1,256 @@
+use ffi::*;
+use element::Element;
+use error::{Error, Result};
+use caps::Caps;
+use reference::Reference;
+
+use std::ops::{Deref, DerefMut};
+
+/// Reads a sequence of numbered image files, like the frames of a time
+/// lapse or the output of a render farm, as a video stream.
+///
+/// Wraps a multifilesrc, `location` is a printf style pattern with the
+/// index of the file, like "frames/frame-%05d.png". Since the files don't
+/// carry any timing information the caps set on the source specify the
+/// framerate of the resulting stream.
+pub struct ImageSequenceSrc{
+    src: Element
+}
+
+unsafe impl Sync for ImageSequenceSrc {}
+unsafe impl Send for ImageSequenceSrc {}
+
+impl ImageSequenceSrc{
+    /// Creates a source reading `location` with the given caps, like
+    /// "image/png,framerate=25/1"
+    pub fn new(name: &str, location: &str, caps: &Caps) -> Result<ImageSequenceSrc>{
+        Element::new("multifilesrc", name).map(|src| {
+            let mut src = ImageSequenceSrc{ src: src };
+            src.set_location(location);
+            src.set_caps(caps);
+            src
+        })
+    }
+
+    /// Creates a source reading `location` as images of `media_type`,
+    /// like "image/png" or "image/jpeg", played at fps_n/fps_d frames per
+    /// second
+    pub fn new_with_framerate(name: &str, location: &str, media_type: &str, fps_n: i32, fps_d: i32) -> Result<ImageSequenceSrc>{
+        match Caps::from_string(&format!("{},framerate={}/{}", media_type, fps_n, fps_d)){
+            Some(caps) => ImageSequenceSrc::new(name, location, &caps),
+            None => Err(Error::new(0,0,&format!("Invalid media type {}", media_type)))
+        }
+    }
+
+    pub fn new_from_element(element: Element) -> ImageSequenceSrc{
+        ImageSequenceSrc{ src: element }
+    }
+
+    pub fn set_location(&mut self, location: &str){
+        self.src.set("location", location);
+    }
+
+    pub fn location(&self) -> String{
+        self.src.get::<&str>("location").to_string()
+    }
+
+    pub fn set_caps(&mut self, caps: &Caps){
+        self.src.set("caps", caps);
+    }
+
+    /// Index of the first file to read
+    pub fn set_start_index(&mut self, index: i32){
+        self.src.set("start-index", index);
+    }
+
+    pub fn start_index(&self) -> i32{
+        self.src.get("start-index")
+    }
+
+    /// Index of the last file to read, -1 to read until a file is missing
+    pub fn set_stop_index(&mut self, index: i32){
+        self.src.set("stop-index", index);
+    }
+
+    pub fn stop_index(&self) -> i32{
+        self.src.get("stop-index")
+    }
+
+    /// Index of the next file that will be read
+    pub fn index(&self) -> i32{
+        self.src.get("index")
+    }
+
+    /// Start again from the start index once the last file is read
+    pub fn set_loop(&mut self, looping: bool){
+        self.src.set("loop", looping as gboolean);
+    }
+
+    pub fn is_loop(&self) -> bool{
+        self.src.get::<gboolean>("loop") != 0
+    }
+}
+
+/// When `ImageSequenceSink` starts writing to a new file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum NextFile{
+    /// A new file for every buffer, the default and what image sequences use
+    Buffer = 0,
+    Discont = 1,
+    KeyFrame = 2,
+    KeyUnitEvent = 3,
+    MaxSize = 4,
+    MaxDuration = 5,
+}
+
+/// Writes every frame of a stream to a numbered file, usually after an
+/// image encoder like pngenc or jpegenc, to turn a video into an image
+/// sequence.
+///
+/// Wraps a multifilesink, `location` is a printf style pattern with the
+/// index of the file, like "frames/frame-%05d.png".
+pub struct ImageSequenceSink{
+    sink: Element
+}
+
+unsafe impl Sync for ImageSequenceSink {}
+unsafe impl Send for ImageSequenceSink {}
+
+impl ImageSequenceSink{
+    pub fn new(name: &str, location: &str) -> Result<ImageSequenceSink>{
+        Element::new("multifilesink", name).map(|sink| {
+            let mut sink = ImageSequenceSink{ sink: sink };
+            sink.set_location(location);
+            sink
+        })
+    }
+
+    pub fn new_from_element(element: Element) -> ImageSequenceSink{
+        ImageSequenceSink{ sink: element }
+    }
+
+    pub fn set_location(&mut self, location: &str){
+        self.sink.set("location", location);
+    }
+
+    pub fn location(&self) -> String{
+        self.sink.get::<&str>("location").to_string()
+    }
+
+    /// Sets the index used for the next file
+    pub fn set_index(&mut self, index: i32){
+        self.sink.set("index", index);
+    }
+
+    /// Index of the next file that will be written
+    pub fn index(&self) -> i32{
+        self.sink.get("index")
+    }
+
+    pub fn set_next_file(&mut self, next_file: NextFile){
+        self.sink.set("next-file", next_file as i32);
+    }
+
+    /// Maximum number of files to keep on disk, older files are deleted.
+    /// 0 keeps all of them
+    pub fn set_max_files(&mut self, max_files: u32){
+        self.sink.set("max-files", max_files);
+    }
+
+    pub fn max_files(&self) -> u32{
+        self.sink.get("max-files")
+    }
+
+    /// Post an element message named "GstMultiFileSink" on the bus with
+    /// the filename and timestamps of every written file
+    pub fn set_post_messages(&mut self, post_messages: bool){
+        self.sink.set("post-messages", post_messages as gboolean);
+    }
+}
+
+impl AsRef<Element> for ImageSequenceSrc{
+    fn as_ref(&self) -> &Element{
+        &self.src
+    }
+}
+
+impl AsMut<Element> for ImageSequenceSrc{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.src
+    }
+}
+
+impl From<ImageSequenceSrc> for Element{
+    fn from(src: ImageSequenceSrc) -> Element{
+        src.src
+    }
+}
+
+impl Deref for ImageSequenceSrc{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.src
+    }
+}
+
+impl DerefMut for ImageSequenceSrc{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.src
+    }
+}
+
+impl ::Transfer for ImageSequenceSrc{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.src.transfer()
+    }
+}
+
+impl Reference for ImageSequenceSrc{
+    fn reference(&self) -> ImageSequenceSrc{
+        ImageSequenceSrc{ src: self.src.reference() }
+    }
+}
+
+impl AsRef<Element> for ImageSequenceSink{
+    fn as_ref(&self) -> &Element{
+        &self.sink
+    }
+}
+
+impl AsMut<Element> for ImageSequenceSink{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.sink
+    }
+}
+
+impl From<ImageSequenceSink> for Element{
+    fn from(sink: ImageSequenceSink) -> Element{
+        sink.sink
+    }
+}
+
+impl Deref for ImageSequenceSink{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.sink
+    }
+}
+
+impl DerefMut for ImageSequenceSink{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.sink
+    }
+}
+
+impl ::Transfer for ImageSequenceSink{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.sink.transfer()
+    }
+}
+
+impl Reference for ImageSequenceSink{
+    fn reference(&self) -> ImageSequenceSink{
+        ImageSequenceSink{ sink: self.sink.reference() }
+    }
+}
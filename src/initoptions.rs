@@ -0,0 +1,159 @@
+use ffi::*;
+use util::*;
+use error::{Error, Result};
+use registry::Registry;
+
+use std::env;
+
+/// Initialization profile that configures gstreamer explicitly instead
+/// of relying on the GST_* environment variables, so a deployment behaves
+/// the same no matter the environment it's started from.
+///
+/// The GST_* variables inherited from the environment are ignored unless
+/// `inherit_environment` is set, every option left as None or empty then
+/// keeps gstreamer's default behaviour. With `inherit_environment` the
+/// options left unset keep whatever the environment specifies.
+///
+/// gstreamer only reads the registry options from the environment, so
+/// `init` sets and removes the GST_* variables of the process. It has to
+/// run before starting any other thread, that could read or modify the
+/// environment at the same time.
+///
+/// ```ignore
+/// let options = gst::InitOptions{
+///     plugin_paths: vec!["/opt/myapp/plugins".to_string()],
+///     registry_fork: Some(false),
+///     debug: Some("*:2,myelement:5".to_string()),
+///     disabled_plugins: vec!["vaapi".to_string()],
+///     ..Default::default()
+/// };
+/// options.init().unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InitOptions{
+    /// Extra directories to scan for plugins, replaces GST_PLUGIN_PATH
+    pub plugin_paths: Vec<String>,
+    /// Directories where the system plugins are installed, replaces
+    /// GST_PLUGIN_SYSTEM_PATH. An empty vector keeps the default
+    pub system_plugin_paths: Vec<String>,
+    /// Path of the binary registry cache file, replaces GST_REGISTRY
+    pub registry_file: Option<String>,
+    /// Whether to check the plugin paths for changes on init,
+    /// replaces GST_REGISTRY_UPDATE
+    pub registry_update: Option<bool>,
+    /// Whether to rebuild the registry in a forked child process
+    pub registry_fork: Option<bool>,
+    /// Default debug level for all categories, one of the GST_LEVEL_*
+    pub debug_level: Option<GstDebugLevel>,
+    /// List of category:level pairs like "*:2,audio*:5", replaces GST_DEBUG
+    pub debug: Option<String>,
+    /// Names of plugins that will be removed from the registry after
+    /// init so their elements are never autoplugged or created
+    pub disabled_plugins: Vec<String>,
+    /// Whether to keep the GST_* variables of the environment for the
+    /// options left unset, false by default
+    pub inherit_environment: bool,
+}
+
+// variables read by gstreamer during init, the _1_0 versions taking
+// precedence over the unversioned ones
+const GST_VARIABLES: &'static [&'static str] = &[
+    "GST_PLUGIN_PATH", "GST_PLUGIN_PATH_1_0",
+    "GST_PLUGIN_SYSTEM_PATH", "GST_PLUGIN_SYSTEM_PATH_1_0",
+    "GST_PLUGIN_SCANNER", "GST_PLUGIN_SCANNER_1_0",
+    "GST_PLUGIN_LOADING_WHITELIST", "GST_PLUGIN_FEATURE_RANK",
+    "GST_REGISTRY", "GST_REGISTRY_1_0",
+    "GST_REGISTRY_UPDATE", "GST_REGISTRY_FORK", "GST_REGISTRY_REUSE_PLUGIN_SCANNER",
+    "GST_DEBUG", "GST_DEBUG_FILE", "GST_DEBUG_NO_COLOR", "GST_DEBUG_COLOR_MODE",
+];
+
+impl InitOptions{
+    pub fn new() -> InitOptions{
+        Default::default()
+    }
+
+    /// Applies the options and initializes gstreamer. Options that
+    /// affect the registry only have effect the first time gstreamer is
+    /// initialized in the process.
+    ///
+    /// Modifies the environment of the process, so it has to be called
+    /// before any other thread starts
+    pub fn init(&self) -> Result<()>{
+        self.apply_environment()?;
+        if let Some(fork) = self.registry_fork{
+            Registry::set_fork_enabled(fork);
+        }
+
+        unsafe{
+            let mut err: *mut GError = ptr::null_mut();
+            if gst_init_check(ptr::null_mut(), ptr::null_mut(), &mut err) == 0{
+                return if err != ptr::null_mut(){
+                    Err(Error::new_from_g_error(err))
+                }else{
+                    Err(Error::new(0, 0, "Couldn't initialize gstreamer"))
+                };
+            }
+        }
+
+        if let Some(level) = self.debug_level{
+            unsafe{ gst_debug_set_default_threshold(level) };
+        }
+        if let Some(ref debug) = self.debug{
+            let cdebug = CString::new(debug.as_str()).unwrap();
+            unsafe{ gst_debug_set_threshold_from_string(cdebug.as_ptr(), 1) };
+        }
+
+        if !self.disabled_plugins.is_empty(){
+            let mut registry = Registry::get();
+            for name in &self.disabled_plugins{
+                if let Some(mut plugin) = registry.find_plugin(name){
+                    registry.remove_plugin(&mut plugin);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // The registry options are only read by gstreamer from the
+    // environment during init so they are set there, after removing the
+    // inherited ones
+    fn apply_environment(&self) -> Result<()>{
+        let plugin_paths = if self.plugin_paths.is_empty(){
+            None
+        }else{
+            Some(env::join_paths(&self.plugin_paths)
+                .map_err(|e| Error::new(0, 0, &format!("Wrong plugin path: {}", e)))?)
+        };
+        let system_plugin_paths = if self.system_plugin_paths.is_empty(){
+            None
+        }else{
+            Some(env::join_paths(&self.system_plugin_paths)
+                .map_err(|e| Error::new(0, 0, &format!("Wrong system plugin path: {}", e)))?)
+        };
+
+        if !self.inherit_environment{
+            for name in GST_VARIABLES{
+                env::remove_var(name);
+            }
+        }
+        // the versioned variables are set too, so inherited ones don't
+        // take precedence
+        if let Some(paths) = plugin_paths{
+            env::set_var("GST_PLUGIN_PATH", &paths);
+            env::set_var("GST_PLUGIN_PATH_1_0", &paths);
+        }
+        if let Some(paths) = system_plugin_paths{
+            env::set_var("GST_PLUGIN_SYSTEM_PATH", &paths);
+            env::set_var("GST_PLUGIN_SYSTEM_PATH_1_0", &paths);
+        }
+        if let Some(ref registry_file) = self.registry_file{
+            env::set_var("GST_REGISTRY", registry_file);
+            env::set_var("GST_REGISTRY_1_0", registry_file);
+        }
+        if let Some(update) = self.registry_update{
+            env::set_var("GST_REGISTRY_UPDATE", if update { "yes" } else { "no" });
+        }
+        Ok(())
+    }
+}
@@ -1,19 +1,44 @@
 #![crate_type = "lib"]
 #![crate_name = "gst"]
 
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+#[cfg(feature = "bytes")]
+extern crate bytes;
+#[cfg(feature = "serde_json")]
+#[macro_use]
+extern crate serde_json;
+#[cfg(feature = "toml")]
+extern crate toml;
+
 pub use self::appsink::AppSink;
 pub use self::appsrc::AppSrc;
 pub use self::sample::Sample;
-pub use self::caps::Caps;
-pub use self::buffer::Buffer;
+pub use self::caps::{Caps, Structures};
+pub use self::buffer::{Buffer, BufferMap, BufferMapMut, BufferRefMut};
 pub use self::mapinfo::MapInfo;
 pub use self::mapinfo::Map;
 pub use self::element::Element;
-pub use self::bus::Bus;
+pub use self::elementfactory::ElementFactory;
+pub use self::elementfactory::{ELEMENT_FACTORY_TYPE_DECODER, ELEMENT_FACTORY_TYPE_ENCODER,
+    ELEMENT_FACTORY_TYPE_SINK, ELEMENT_FACTORY_TYPE_SRC, ELEMENT_FACTORY_TYPE_MUXER,
+    ELEMENT_FACTORY_TYPE_DEMUXER, ELEMENT_FACTORY_TYPE_PARSER, ELEMENT_FACTORY_TYPE_PAYLOADER,
+    ELEMENT_FACTORY_TYPE_DEPAYLOADER, ELEMENT_FACTORY_TYPE_FORMATTER, ELEMENT_FACTORY_TYPE_DECRYPTOR,
+    ELEMENT_FACTORY_TYPE_ENCRYPTOR, ELEMENT_FACTORY_TYPE_MEDIA_VIDEO, ELEMENT_FACTORY_TYPE_MEDIA_AUDIO,
+    ELEMENT_FACTORY_TYPE_MEDIA_IMAGE, ELEMENT_FACTORY_TYPE_MEDIA_SUBTITLE, ELEMENT_FACTORY_TYPE_MEDIA_METADATA,
+    ELEMENT_FACTORY_TYPE_ANY, ELEMENT_FACTORY_TYPE_MEDIA_ANY, ELEMENT_FACTORY_TYPE_DECODABLE};
+pub use self::bus::{Bus, BusIter, BusWatch, Watch};
 pub use self::bin::Bin;
 pub use self::pipeline::Pipeline;
+pub use self::pipelinepool::{PipelinePool, PooledPipeline};
+pub use self::parse::{ParseContext, parse_launch_full, parse_bin_from_description_full};
+pub use self::pipelineconfig::{PipelineConfig, ElementConfig, LinkConfig, ConfigValue};
 pub use self::playbin::PlayBin;
+pub use self::decodebin::{DecodeBin, AutoplugSelect};
 pub use self::message::Message;
+pub use self::event::Event;
+pub use self::query::Query;
+pub use self::taglist::{TagList, Date, DateTime};
 pub use self::mainloop::MainLoop;
 pub use self::error::Error;
 pub use self::error::Result;
@@ -21,9 +46,82 @@ pub use self::videoframe::VideoFrame;
 pub use self::videoframe::VideoPlane;
 pub use self::videoframe::VideoComponent;
 pub use self::videoinfo::VideoInfo;
+pub use self::audioinfo::{AudioInfo, AudioSample};
+pub use self::videooverlay::VideoOverlay;
+pub use self::unwind::{CallbackPanic, set_panic_hook, unset_panic_hook, set_abort_on_panic};
+pub use self::cancellable::CancellationToken;
+pub use self::colorimetry::{VideoColorimetry, MasteringDisplayInfo, ContentLightLevel};
+pub use self::audiotap::{LowLatencyAudioTap, AudioChunk};
+pub use self::timedmetadata::{TimedMetadataSink, TimedMetadata, MetadataKind};
+pub use self::videometa::VideoRegionOfInterestMeta;
+pub use self::videometa::VideoOverlayCompositionMeta;
+pub use self::videometa::VideoCropMeta;
+pub use self::videocrop::VideoCrop;
+pub use self::videoflip::{VideoFlip, VideoFlipMethod, ImageOrientation};
+pub use self::videooverlaycomposition::VideoOverlayComposition;
+pub use self::videooverlaycomposition::VideoOverlayRectangle;
+pub use self::videooverlaycomposition::VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB;
+#[cfg(feature = "drawing")]
+pub use self::overlaydrawing::OverlayCanvas;
+#[cfg(all(feature = "remote", unix))]
+pub use self::remotecontrol::RemoteControl;
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+pub use self::mpris::Mpris;
+#[cfg(feature = "validate")]
+pub use self::validate::{ValidateRunner, Scenario, run_scenario};
+#[cfg(feature = "rtsp-server")]
+pub use self::rtspserver::{RtspServer, MountPoints, MediaFactory, ClientInfo, RtspAuth, RtspToken};
+#[cfg(feature = "leak-tracking")]
+pub use self::tracking::{TrackedKind, TrackedType, tracked_types, live_wrappers, leaks_report, dump_leaks,
+    set_dump_leaks_on_exit};
 pub use self::buffer_pool::BufferPool;
 pub use self::pad::Pad;
-pub use self::structure::Structure;
+pub use self::ghostpad::GhostPad;
+pub use self::device::{Device, DeviceMonitor};
+pub use self::registry::{Registry, Plugin, plugin_register_static};
+pub use self::initoptions::InitOptions;
+pub use self::imagesequence::{ImageSequenceSrc, ImageSequenceSink};
+pub use self::framerateconverter::{FrameRateConverter, FrameRateStats};
+pub use self::audionormalizer::AudioNormalizer;
+pub use self::deinterlace::{Deinterlacer, InterlaceDetector, ScanType};
+pub use self::mpegts::{TsDemux, ProgramScanner, Program};
+pub use self::dvbsrc::{DvbSrc, TuningParameters, FrontendStats};
+pub use self::rtpbin::{RtpBin, JitterBufferStats, JitterBufferMode, RtxConfig, FecConfig, rtx_queue};
+pub use self::rtppayload::{payloader_for, depayloader_for, link_payloader, link_depayloader};
+pub use self::rtspsrc::{RtspSrc, Backchannel, RTSP_LOWER_TRANS_UDP, RTSP_LOWER_TRANS_UDP_MCAST,
+    RTSP_LOWER_TRANS_TCP, RTSP_LOWER_TRANS_HTTP, RTSP_LOWER_TRANS_TLS};
+pub use self::webrtc::{WebRtcBin, DataChannel, DataChannelOptions, DataChannelEvent,
+    StatsReport, RtpStreamStats, RtpStreamDirection, RtpTransceiver, TransceiverDirection,
+    IceTransportPolicy, BundlePolicy};
+pub use self::recording::RecordingBranch;
+pub use self::timeshift::TimeShift;
+pub use self::export::export_segment;
+pub use self::playlist::Playlist;
+pub use self::crossfader::Crossfader;
+pub use self::abcomparison::{AbComparison, Side};
+pub use self::quality::{measure_quality, psnr, ssim, FrameQuality, QualityReport, MAX_PSNR};
+pub use self::loudness::{measure_loudness, LoudnessMeter, Loudness, LoudnessReport};
+pub use self::detection::{detect_silence, detect_black, detect_scene_changes, is_black,
+    SilenceDetector, BlackDetector, SceneChangeDetector, Detection, DetectionKind, SceneChange};
+pub use self::frameanalyzer::{FrameAnalyzer, FrameAnalysisSink};
+pub use self::urihandler::register_uri_handler;
+pub use self::typefind::{TypeFind, register_typefind};
+pub use self::fpsmeter::{FpsMeter, FpsStats};
+pub use self::sinkstats::SinkStats;
+pub use self::hwaccel::{HwAccel, HwApi, HwCodec, HwCodecKind};
+pub use self::subclass::{ElementMetadata, PadTemplate};
+pub use self::elementimpl::{ElementHandle, ElementImpl, register_element};
+pub use self::basesrc::{BaseSrc, PushSrcImpl, register_push_src};
+pub use self::basesink::{BaseSink, BaseSinkImpl, register_base_sink};
+pub use self::basetransform::{BaseTransform, BaseTransformImpl, TransformMode, register_base_transform};
+pub use self::videocodec::{VideoCodecFrame, VideoCodecState, VideoDecoder, VideoDecoderImpl,
+    VideoEncoder, VideoEncoderImpl, register_video_decoder, register_video_encoder};
+pub use self::audiocodec::{AudioDecoder, AudioDecoderImpl, AudioEncoder, AudioEncoderImpl,
+    register_audio_decoder, register_audio_encoder};
+pub use self::baseparse::{BaseParse, BaseParseImpl, FrameDetection, ParseFrame, register_base_parse};
+pub use self::imageconvert::{encode_frame_to_jpeg, encode_frame_to_png, decode_image_to_sample};
+pub use self::structure::{Structure, FieldValue, FromFieldValue};
+pub use self::value::Value;
 pub use self::iterator::Iter;
 pub use self::reference::Ref;
 pub use self::miniobject::MiniObject;
@@ -49,19 +147,101 @@ mod sample;
 mod caps;
 mod buffer;
 mod element;
+mod elementfactory;
 pub mod bus;
 mod bin;
 mod pipeline;
+mod pipelineconfig;
+mod pipelinepool;
+mod parse;
 mod playbin;
+mod decodebin;
 mod message;
+mod event;
+mod query;
+mod taglist;
 pub mod mainloop;
 mod error;
 mod videoframe;
 mod videoinfo;
+mod audioinfo;
+mod videooverlay;
+mod unwind;
+mod cancellable;
+mod colorimetry;
+mod audiotap;
+
+/// Access to KLV and ID3 timed metadata streams.
+pub mod timedmetadata;
+mod videometa;
+mod videocrop;
+mod videoflip;
+mod videooverlaycomposition;
+#[cfg(feature = "drawing")]
+mod overlaydrawing;
+#[cfg(feature = "ndarray")]
+mod videoarray;
+#[cfg(feature = "bytes")]
+mod bufferbytes;
 mod mapinfo;
 mod buffer_pool;
 mod pad;
+mod ghostpad;
+mod device;
+mod registry;
+mod initoptions;
+mod imageconvert;
+pub mod imagesequence;
+mod framerateconverter;
+pub mod audionormalizer;
+pub mod deinterlace;
+
+/// MPEG transport stream program tables and tsdemux program selection.
+pub mod mpegts;
+pub mod dvbsrc;
+mod rtpbin;
+mod rtppayload;
+mod rtspsrc;
+mod webrtc;
+mod recording;
+mod timeshift;
+mod export;
+mod playlist;
+mod crossfader;
+mod abcomparison;
+mod quality;
+mod loudness;
+mod detection;
+mod frameanalyzer;
+mod urihandler;
+mod typefind;
+mod fpsmeter;
+mod sinkstats;
+mod hwaccel;
+mod subclass;
+mod elementimpl;
+mod basesrc;
+mod basesink;
+mod basetransform;
+mod videocodec;
+mod audiocodec;
+mod baseparse;
+#[cfg(all(feature = "remote", unix))]
+mod remotecontrol;
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+mod mpris;
+#[cfg(feature = "validate")]
+mod validate;
+#[cfg(feature = "rtsp-server")]
+mod rtspserver;
+#[cfg(feature = "leak-tracking")]
+mod tracking;
+
+/// Minimal parsers for the parameter sets of encoded video streams.
+#[cfg(feature = "codecparsers")]
+pub mod codecparsers;
 mod structure;
+mod value;
 mod iterator;
 mod reference;
 mod miniobject;
@@ -1,9 +1,22 @@
 #[link(name = "gstvideo-1.0")]
+#[link(name = "gstaudio-1.0")]
 #[link(name = "gstapp-1.0")]
 #[link(name = "gstbase-1.0")]
+#[link(name = "gstcontroller-1.0")]
 #[link(name = "gstreamer-1.0")]
 #[link(name = "gobject-2.0")]
 #[link(name = "glib-2.0")]
 extern {}
 
+#[cfg(feature = "validate")]
+#[link(name = "gstvalidate-1.0")]
+extern "C" {}
 
+#[cfg(feature = "rtsp-server")]
+#[link(name = "gstrtspserver-1.0")]
+#[link(name = "gstrtsp-1.0")]
+extern "C" {}
+
+#[cfg(feature = "codecparsers")]
+#[link(name = "gstcodecparsers-1.0")]
+extern "C" {}
@@ -0,0 +1,409 @@
+use ffi::*;
+use element::Element;
+use error::{Error, Result};
+use pipeline::Pipeline;
+use sample::Sample;
+
+use std::f64;
+use std::ptr;
+use std::collections::VecDeque;
+
+// loudness of silence, below the absolute gate of R128
+const SILENCE: f64 = f64::NEG_INFINITY;
+
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+
+// measurements advance in sub-blocks of 100ms, momentary loudness spans 4
+// of them and short term loudness 30
+const SUBBLOCKS_PER_SECOND: u32 = 10;
+const MOMENTARY_SUBBLOCKS: usize = 4;
+const SHORT_TERM_SUBBLOCKS: usize = 30;
+
+#[cfg(target_endian = "little")]
+const F32_FORMAT: &'static str = "F32LE";
+#[cfg(target_endian = "big")]
+const F32_FORMAT: &'static str = "F32BE";
+
+fn loudness(energy: f64) -> f64{
+    if energy > 0.0{
+        -0.691 + 10.0 * energy.log10()
+    }else{
+        SILENCE
+    }
+}
+
+// second order IIR filter, transposed direct form II
+#[derive(Clone, Copy)]
+struct Biquad{
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad{
+    fn process(&mut self, x: f64) -> f64{
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+// K-weighting filter of ITU-R BS.1770: a high shelf modelling the head
+// followed by a high pass, with coefficients computed for any sample rate
+fn k_weighting(rate: u32) -> [Biquad; 2]{
+    let rate = rate as f64;
+
+    let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad{
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad{
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
+
+// weight of each channel in the sum, assuming the usual L R C LFE Ls Rs
+// order for 5.1, where the LFE isn't measured and the surrounds weigh more
+fn channel_weight(channel: usize, channels: usize) -> f64{
+    match (channels, channel){
+        (6, 3) => 0.0,
+        (6, 4) | (6, 5) => 1.41,
+        _ => 1.0
+    }
+}
+
+/// Loudness measurement following EBU R128 and ITU-R BS.1770, fed with
+/// interleaved float samples.
+///
+/// Momentary loudness is measured over the last 400ms, short term over
+/// the last 3s and integrated over everything pushed so far, gated to
+/// leave out silence and quiet passages. All values are in LUFS, and are
+/// negative infinity until enough audio has been measured.
+pub struct LoudnessMeter{
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    subblock_len: usize,
+    // samples per channel and weighted sum of squares of the sub-block
+    // being measured
+    subblock_samples: usize,
+    subblock_sum: f64,
+    // mean square of the latest sub-blocks
+    subblocks: VecDeque<f64>,
+    // mean square of every 400ms block, for the integrated loudness
+    blocks: Vec<f64>,
+    measured_subblocks: u64,
+    peak: f32,
+}
+
+impl LoudnessMeter{
+    pub fn new(rate: u32, channels: u32) -> LoudnessMeter{
+        LoudnessMeter{
+            channels: channels as usize,
+            filters: vec![k_weighting(rate); channels as usize],
+            subblock_len: (rate / SUBBLOCKS_PER_SECOND) as usize,
+            subblock_samples: 0,
+            subblock_sum: 0.0,
+            subblocks: VecDeque::with_capacity(SHORT_TERM_SUBBLOCKS),
+            blocks: vec![],
+            measured_subblocks: 0,
+            peak: 0.0,
+        }
+    }
+
+    /// Measures interleaved samples, a trailing incomplete frame is
+    /// ignored
+    pub fn push(&mut self, samples: &[f32]){
+        for frame in samples.chunks(self.channels){
+            if frame.len() < self.channels{
+                break;
+            }
+            for (channel, sample) in frame.iter().enumerate(){
+                self.peak = self.peak.max(sample.abs());
+                let weight = channel_weight(channel, self.channels);
+                if weight == 0.0{
+                    continue;
+                }
+                let filters = &mut self.filters[channel];
+                let shelved = filters[0].process(*sample as f64);
+                let filtered = filters[1].process(shelved);
+                self.subblock_sum += weight * filtered * filtered;
+            }
+            self.subblock_samples += 1;
+            if self.subblock_samples == self.subblock_len{
+                self.end_subblock();
+            }
+        }
+    }
+
+    fn end_subblock(&mut self){
+        if self.subblocks.len() == SHORT_TERM_SUBBLOCKS{
+            self.subblocks.pop_front();
+        }
+        self.subblocks.push_back(self.subblock_sum / self.subblock_samples as f64);
+        self.subblock_sum = 0.0;
+        self.subblock_samples = 0;
+        self.measured_subblocks += 1;
+        if self.subblocks.len() >= MOMENTARY_SUBBLOCKS{
+            let energy = self.mean_energy(MOMENTARY_SUBBLOCKS);
+            self.blocks.push(energy);
+        }
+    }
+
+    // mean square of the latest `subblocks`, 0 if there aren't that many
+    fn mean_energy(&self, subblocks: usize) -> f64{
+        if self.subblocks.len() < subblocks{
+            return 0.0;
+        }
+        self.subblocks.iter().rev().take(subblocks).sum::<f64>() / subblocks as f64
+    }
+
+    /// Loudness of the last 400ms
+    pub fn momentary(&self) -> f64{
+        loudness(self.mean_energy(MOMENTARY_SUBBLOCKS))
+    }
+
+    /// Loudness of the last 3s
+    pub fn short_term(&self) -> f64{
+        loudness(self.mean_energy(SHORT_TERM_SUBBLOCKS))
+    }
+
+    /// Gated loudness of everything measured so far
+    pub fn integrated(&self) -> f64{
+        let gated_mean = |threshold: f64|{
+            let (sum, count) = self.blocks.iter()
+                .filter(|energy| loudness(**energy) > threshold)
+                .fold((0.0, 0), |(sum, count), energy| (sum + energy, count + 1));
+            if count > 0 { sum / count as f64 } else { 0.0 }
+        };
+        let relative_threshold = loudness(gated_mean(ABSOLUTE_GATE)) + RELATIVE_GATE;
+        loudness(gated_mean(relative_threshold.max(ABSOLUTE_GATE)))
+    }
+
+    /// Highest absolute sample value, 1.0 being full scale
+    pub fn sample_peak(&self) -> f32{
+        self.peak
+    }
+
+    /// Duration measured so far in nanoseconds, in whole 100ms sub-blocks
+    pub fn measured(&self) -> GstClockTime{
+        self.measured_subblocks * 1_000_000_000 / SUBBLOCKS_PER_SECOND as GstClockTime
+    }
+}
+
+/// Loudness at one point of a measured file
+#[derive(Clone, Copy, Debug)]
+pub struct Loudness{
+    /// Time since the start of the audio, in nanoseconds
+    pub time: GstClockTime,
+    pub momentary: f64,
+    pub short_term: f64,
+}
+
+/// Loudness of a whole file
+#[derive(Clone, Copy, Debug)]
+pub struct LoudnessReport{
+    /// Gated loudness of the whole file in LUFS, the value R128 targets at
+    /// -23 LUFS
+    pub integrated: f64,
+    /// Maximum short term loudness in LUFS
+    pub short_term_max: f64,
+    /// Maximum momentary loudness in LUFS
+    pub momentary_max: f64,
+    /// Highest absolute sample value in dBFS
+    pub sample_peak: f64,
+}
+
+/// Decodes the audio of `uri` and measures its loudness following EBU
+/// R128, for compliance checking of produced media. `progress` is called
+/// as the measurement advances, at most every 100ms of audio, with the
+/// momentary and short term loudness.
+pub fn measure_loudness<F: FnMut(&Loudness)>(uri: &str, mut progress: F) -> Result<LoudnessReport>{
+    let mut pipeline = Pipeline::new_from_str(&format!(
+        "uridecodebin uri=\"{}\" ! audioconvert ! audio/x-raw,format={},layout=interleaved ! appsink name=sink sync=false max-buffers=16",
+        uri, F32_FORMAT))?;
+    let mut appsink: Element = match pipeline.get_by_name("sink"){
+        Some(appsink) => appsink,
+        None => return Err(Error::new(0,0,"Couldn't find appsink"))
+    };
+    if pipeline.play() == GST_STATE_CHANGE_FAILURE{
+        pipeline.set_state(GST_STATE_NULL);
+        return Err(Error::new(0,0,&format!("Couldn't play {}", uri)));
+    }
+    let ret = measure_samples(&mut pipeline, &mut appsink, &mut progress);
+    pipeline.set_state(GST_STATE_NULL);
+    ret
+}
+
+fn measure_samples<F: FnMut(&Loudness)>(pipeline: &mut Pipeline, appsink: &mut Element, progress: &mut F) -> Result<LoudnessReport>{
+    let mut meter: Option<LoudnessMeter> = None;
+    let mut short_term_max = SILENCE;
+    let mut momentary_max = SILENCE;
+    loop{
+        let sample = unsafe{
+            Sample::new(gst_app_sink_pull_sample(appsink.gst_element_mut() as *mut GstAppSink))
+        };
+        let sample = match sample{
+            Some(sample) => sample,
+            None => break
+        };
+        let (buffer, caps) = match (sample.buffer(), sample.caps()){
+            (Some(buffer), Some(caps)) => (buffer, caps),
+            _ => continue
+        };
+        if meter.is_none(){
+            let structure = match caps.structure(0){
+                Some(structure) => structure,
+                None => continue
+            };
+            let rate = structure.get::<i32>("rate").unwrap_or(0);
+            let channels = structure.get::<i32>("channels").unwrap_or(0);
+            if rate <= 0 || channels <= 0{
+                return Err(Error::new(0,0,"Unknown audio format"));
+            }
+            meter = Some(LoudnessMeter::new(rate as u32, channels as u32));
+        }
+        let meter = meter.as_mut().unwrap();
+        let before = meter.measured();
+        if let Some(map) = buffer.map_read(){
+            meter.push(map.data::<f32>());
+        }
+        if meter.measured() > before{
+            let loudness = Loudness{
+                time: meter.measured(),
+                momentary: meter.momentary(),
+                short_term: meter.short_term(),
+            };
+            momentary_max = momentary_max.max(loudness.momentary);
+            short_term_max = short_term_max.max(loudness.short_term);
+            progress(&loudness);
+        }
+    }
+
+    if let Some(error) = pipeline_error(pipeline){
+        return Err(error);
+    }
+    match meter{
+        Some(meter) => Ok(LoudnessReport{
+            integrated: meter.integrated(),
+            short_term_max: short_term_max,
+            momentary_max: momentary_max,
+            sample_peak: 20.0 * (meter.sample_peak() as f64).log10(),
+        }),
+        None => Err(Error::new(0,0,"No audio to measure"))
+    }
+}
+
+fn pipeline_error(pipeline: &Pipeline) -> Option<Error>{
+    let mut bus = pipeline.bus()?;
+    unsafe{
+        let msg = gst_bus_pop_filtered(bus.gst_bus_mut(), GST_MESSAGE_ERROR);
+        if msg == ptr::null_mut(){
+            return None;
+        }
+        let error = Error::new_from_error_message(msg);
+        gst_mini_object_unref(msg as *mut GstMiniObject);
+        Some(error)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::LoudnessMeter;
+    use std::f64;
+
+    const RATE: u32 = 48000;
+
+    // stereo 1 kHz sine at `level` dBFS in both channels
+    fn sine(meter: &mut LoudnessMeter, level: f64, seconds: f64){
+        let amplitude = 10f64.powf(level / 20.0);
+        let frames = (RATE as f64 * seconds) as usize;
+        let samples: Vec<f32> = (0..frames).flat_map(|i|{
+            let sample = (amplitude * (2.0 * f64::consts::PI * 1000.0 * i as f64 / RATE as f64).sin()) as f32;
+            vec![sample, sample]
+        }).collect();
+        meter.push(&samples);
+    }
+
+    fn assert_lufs(measured: f64, expected: f64){
+        assert!((measured - expected).abs() < 0.1, "{} LUFS instead of {}", measured, expected);
+    }
+
+    #[test]
+    fn silence(){
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        meter.push(&vec![0.0; RATE as usize * 2]);
+        assert_eq!(meter.momentary(), f64::NEG_INFINITY);
+        assert_eq!(meter.integrated(), f64::NEG_INFINITY);
+        assert_eq!(meter.measured(), 1_000_000_000);
+    }
+
+    #[test]
+    fn not_enough_audio(){
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        sine(&mut meter, -23.0, 0.3);
+        assert_eq!(meter.momentary(), f64::NEG_INFINITY);
+        assert_eq!(meter.short_term(), f64::NEG_INFINITY);
+    }
+
+    // EBU Tech 3341 case 1
+    #[test]
+    fn sine_at_minus_23(){
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        sine(&mut meter, -23.0, 20.0);
+        assert_lufs(meter.momentary(), -23.0);
+        assert_lufs(meter.short_term(), -23.0);
+        assert_lufs(meter.integrated(), -23.0);
+        assert!((meter.sample_peak() - 10f32.powf(-23.0 / 20.0)).abs() < 1e-3);
+    }
+
+    // EBU Tech 3341 case 3, the quiet parts are below the relative gate
+    #[test]
+    fn relative_gate(){
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        sine(&mut meter, -36.0, 10.0);
+        sine(&mut meter, -23.0, 60.0);
+        sine(&mut meter, -36.0, 10.0);
+        assert_lufs(meter.integrated(), -23.0);
+    }
+
+    // EBU Tech 3341 case 5, the quiet parts are above the relative gate
+    #[test]
+    fn above_relative_gate(){
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        sine(&mut meter, -26.0, 20.0);
+        sine(&mut meter, -20.0, 20.1);
+        sine(&mut meter, -26.0, 20.0);
+        assert_lufs(meter.integrated(), -23.0);
+    }
+
+    #[test]
+    fn absolute_gate(){
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        sine(&mut meter, -23.0, 10.0);
+        meter.push(&vec![0.0; RATE as usize * 2 * 10]);
+        sine(&mut meter, -80.0, 10.0);
+        assert_lufs(meter.integrated(), -23.0);
+
+        // only audio below the absolute gate
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        sine(&mut meter, -75.0, 10.0);
+        assert_lufs(meter.momentary(), -75.0);
+        assert_eq!(meter.integrated(), f64::NEG_INFINITY);
+    }
+}
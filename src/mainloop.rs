@@ -52,16 +52,34 @@ impl MainLoop{
 				self.running = true;
 				let gst_loop = self.gst_loop.clone();
 				g_main_loop_run ( mem::transmute(gst_loop) );
+				// it might have been quit from a clone, which doesn't
+				// know about this handle
+				self.running = false;
 			}
 		}
 	}
 
+	/// Stops the loop, also from a clone of it, like one moved into a bus
+	/// watch to quit on EOS
 	pub fn quit(&mut self){
 		unsafe{
-			if self.running{
-				self.running = false;
-				g_main_loop_quit(mem::transmute(self.gst_loop));
-			}
+			self.running = false;
+			g_main_loop_quit(mem::transmute(self.gst_loop));
+		}
+	}
+
+	pub fn is_running(&self) -> bool{
+		unsafe{
+			g_main_loop_is_running(self.gst_loop) != 0
+		}
+	}
+}
+
+/// Clones are handles to the same loop, to quit it from callbacks
+impl Clone for MainLoop{
+	fn clone(&self) -> MainLoop{
+		unsafe{
+			MainLoop{ gst_loop: g_main_loop_ref(self.gst_loop), running: false }
 		}
 	}
 }
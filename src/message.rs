@@ -3,6 +3,10 @@ use util::*;
 use error::Error;
 use std::os::raw;
 use reference::Reference;
+use device::Device;
+use element::Element;
+use structure::Structure;
+use taglist::TagList;
 
 unsafe impl Send for GstMessage {}
 unsafe impl Send for GstTagList {}
@@ -38,13 +42,18 @@ pub enum Message{
     StructureChange(MessagePrivate),
     StreamStatus(MessagePrivate),
     Application(MessagePrivate),
+    ApplicationParsed{msg: MessagePrivate, structure: Structure},
     Element(MessagePrivate),
+    ElementParsed{msg: MessagePrivate, structure: Structure},
     SegmentStart(MessagePrivate),
+    SegmentStartParsed{msg: MessagePrivate, format: GstFormat, position: i64},
     SegmentDone(MessagePrivate),
+    SegmentDoneParsed{msg: MessagePrivate, format: GstFormat, position: i64},
     DurationChanged(MessagePrivate),
     Latency(MessagePrivate),
     AsyncStart(MessagePrivate),
     AsyncDone(MessagePrivate),
+    AsyncDoneParsed{msg: MessagePrivate, running_time: GstClockTime},
     RequestState(MessagePrivate),
     StepStart(MessagePrivate),
     Qos(MessagePrivate),
@@ -207,13 +216,18 @@ impl Message{
             Message::StructureChange(msg) => msg,
             Message::StreamStatus(msg) => msg,
             Message::Application(msg) => msg,
+            Message::ApplicationParsed{msg, ref structure} => msg,
             Message::Element(msg) => msg,
+            Message::ElementParsed{msg, ref structure} => msg,
             Message::SegmentStart(msg) => msg,
+            Message::SegmentStartParsed{msg, ref format, ref position} => msg,
             Message::SegmentDone(msg) => msg,
+            Message::SegmentDoneParsed{msg, ref format, ref position} => msg,
             Message::DurationChanged(msg) => msg,
             Message::Latency(msg) => msg,
             Message::AsyncStart(msg) => msg,
             Message::AsyncDone(msg) => msg,
+            Message::AsyncDoneParsed{msg, ref running_time} => msg,
             Message::RequestState(msg) => msg,
             Message::StepStart(msg) => msg,
             Message::Qos(msg) => msg,
@@ -255,13 +269,18 @@ impl Message{
             Message::StructureChange(msg) => msg,
             Message::StreamStatus(msg) => msg,
             Message::Application(msg) => msg,
+            Message::ApplicationParsed{msg, ref structure} => msg,
             Message::Element(msg) => msg,
+            Message::ElementParsed{msg, ref structure} => msg,
             Message::SegmentStart(msg) => msg,
+            Message::SegmentStartParsed{msg, ref format, ref position} => msg,
             Message::SegmentDone(msg) => msg,
+            Message::SegmentDoneParsed{msg, ref format, ref position} => msg,
             Message::DurationChanged(msg) => msg,
             Message::Latency(msg) => msg,
             Message::AsyncStart(msg) => msg,
             Message::AsyncDone(msg) => msg,
+            Message::AsyncDoneParsed{msg, ref running_time} => msg,
             Message::RequestState(msg) => msg,
             Message::StepStart(msg) => msg,
             Message::Qos(msg) => msg,
@@ -318,10 +337,60 @@ impl Message{
         }
     }
 
+    /// The element that posted the message, None if it was posted by
+    /// some other object, like the bus itself
+    pub fn src_element(&self) -> Option<Element>{
+        unsafe{
+            let src = self.src();
+            if src == ptr::null_mut() ||
+                g_type_check_instance_is_a(src as *mut GTypeInstance, gst_element_get_type()) == 0{
+                return None;
+            }
+            Element::new_from_gst_element(gst_object_ref(src as gpointer) as *mut GstElement)
+        }
+    }
+
     pub unsafe fn structure(&self) -> *const GstStructure{
         gst_message_get_structure(mem::transmute(self.gst_message()))
     }
 
+    /// The device that was added or removed for DeviceAdded and
+    /// DeviceRemoved messages, None for any other message type
+    pub fn device(&self) -> Option<Device>{
+        unsafe{
+            let mut device: *mut GstDevice = ptr::null_mut();
+            match *self{
+                Message::DeviceAdded(msg) => gst_message_parse_device_added(msg, &mut device),
+                Message::DeviceRemoved(msg) => gst_message_parse_device_removed(msg, &mut device),
+                _ => return None
+            }
+            Device::new(device)
+        }
+    }
+
+    /// Whether this is the element message a video sink posts to ask
+    /// for the window to render into, see VideoOverlay
+    pub fn is_prepare_window_handle(&self) -> bool{
+        unsafe{
+            gst_is_video_overlay_prepare_window_handle_message(self.gst_message() as *mut GstMessage) != 0
+        }
+    }
+
+    /// The tags of Tag and TagParsed messages, None for any other
+    /// message type
+    pub fn tags(&self) -> Option<TagList>{
+        unsafe{
+            match *self{
+                Message::Tag(msg) | Message::TagParsed{msg, ..} => {
+                    let mut tags: *mut GstTagList = ptr::null_mut();
+                    gst_message_parse_tag(msg, &mut tags);
+                    TagList::new(tags)
+                }
+                _ => None
+            }
+        }
+    }
+
     pub fn make_writable(&self) -> Option<Message>{
         unsafe{
             Message::new(gst_mini_object_make_writable(self.gst_message() as *mut GstMiniObject) as *mut GstMessage)
@@ -334,6 +403,10 @@ impl Message{
         }
     }
 
+    /// Returns the message as one of the `Parsed` variants, with the
+    /// fields of error, warning, info, tag, buffering, state changed,
+    /// application, element, segment and async done messages extracted.
+    /// Other message types are returned as they are
     pub fn parse(&self) -> Message{
         unsafe{
 			let ret = Message::new(gst_mini_object_copy(self.gst_message() as *mut GstMiniObject) as *const GstMessage).unwrap();
@@ -385,6 +458,44 @@ impl Message{
                     let message = gst_message_ref(message);
                     Message::StateChangedParsed{msg: message, old: old, new: new, pending: pending}
                 }
+                Message::Application(message) => {
+                    match Structure::new_from_gst_structure(gst_message_get_structure(message) as *mut GstStructure){
+                        Some(structure) => {
+                            let message = gst_message_ref(message);
+                            Message::ApplicationParsed{msg: message, structure: structure}
+                        }
+                        None => ret
+                    }
+                }
+                Message::Element(message) => {
+                    match Structure::new_from_gst_structure(gst_message_get_structure(message) as *mut GstStructure){
+                        Some(structure) => {
+                            let message = gst_message_ref(message);
+                            Message::ElementParsed{msg: message, structure: structure}
+                        }
+                        None => ret
+                    }
+                }
+                Message::SegmentStart(message) => {
+                    let mut format: GstFormat = GST_FORMAT_UNDEFINED;
+                    let mut position: i64 = 0;
+                    gst_message_parse_segment_start(message,&mut format,&mut position);
+                    let message = gst_message_ref(message);
+                    Message::SegmentStartParsed{msg: message, format: format, position: position}
+                }
+                Message::SegmentDone(message) => {
+                    let mut format: GstFormat = GST_FORMAT_UNDEFINED;
+                    let mut position: i64 = 0;
+                    gst_message_parse_segment_done(message,&mut format,&mut position);
+                    let message = gst_message_ref(message);
+                    Message::SegmentDoneParsed{msg: message, format: format, position: position}
+                }
+                Message::AsyncDone(message) => {
+                    let mut running_time: GstClockTime = GST_CLOCK_TIME_NONE;
+                    gst_message_parse_async_done(message,&mut running_time);
+                    let message = gst_message_ref(message);
+                    Message::AsyncDoneParsed{msg: message, running_time: running_time}
+                }
                 _ => {
                     ret
                 }
@@ -3,6 +3,11 @@ use std::mem;
 use std::ptr;
 
 use reference::Reference;
+use Transfer;
+#[cfg(feature = "leak-tracking")]
+use tracking::{self, TrackedKind};
+#[cfg(feature = "leak-tracking")]
+use std::os::raw::c_void;
 
 pub struct MiniObject{
     miniobject: *mut GstMiniObject,
@@ -11,6 +16,8 @@ pub struct MiniObject{
 impl Drop for MiniObject{
 	fn drop(&mut self){
         unsafe{
+			#[cfg(feature = "leak-tracking")]
+			tracking::untrack(self.miniobject as *const c_void);
 			gst_mini_object_unref(self.miniobject);
 		}
 	}
@@ -28,6 +35,8 @@ pub enum LockFlags{
 impl MiniObject{
     pub unsafe fn new_from_gst_miniobject(miniobject: *mut GstMiniObject) -> Option<MiniObject>{
         if miniobject != ptr::null_mut(){
+            #[cfg(feature = "leak-tracking")]
+            tracking::track(miniobject as *const c_void, TrackedKind::MiniObject);
             Some(MiniObject{
                 miniobject: miniobject
             })
@@ -51,9 +60,10 @@ impl MiniObject{
 
     pub fn make_writable(self) -> MiniObject{
         unsafe{
-            MiniObject{
-                miniobject: gst_mini_object_make_writable(self.miniobject)
-            }
+            // make_writable takes the reference of self and returns either
+            // the same object or a copy
+            let miniobject = gst_mini_object_make_writable(self.transfer());
+            MiniObject::new_from_gst_miniobject(miniobject).unwrap()
         }
     }
 
@@ -69,7 +79,7 @@ impl MiniObject{
 impl Clone for MiniObject{
 	fn clone(&self) -> MiniObject{
 		unsafe{
-			MiniObject{ miniobject: gst_mini_object_copy(self.miniobject as *mut GstMiniObject) }
+			MiniObject::new_from_gst_miniobject(gst_mini_object_copy(self.miniobject as *mut GstMiniObject)).unwrap()
 		}
 	}
 }
@@ -77,7 +87,7 @@ impl Clone for MiniObject{
 impl Reference for MiniObject{
     fn reference(&self) -> MiniObject{
         unsafe{
-			MiniObject{ miniobject: gst_mini_object_ref(self.miniobject) }
+			MiniObject::new_from_gst_miniobject(gst_mini_object_ref(self.miniobject)).unwrap()
 		}
     }
 }
@@ -85,6 +95,8 @@ impl Reference for MiniObject{
 impl ::Transfer<GstMiniObject> for MiniObject{
     unsafe fn transfer(self) ->  *mut GstMiniObject{
         let miniobject = self.miniobject;
+		#[cfg(feature = "leak-tracking")]
+		tracking::untrack(miniobject as *const c_void);
 		mem::forget(self);
         miniobject
     }
@@ -0,0 +1,693 @@
+use ffi::*;
+use buffer::Buffer;
+use element::Element;
+use error::Result;
+use reference::Reference;
+use unwind;
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+const TS_PACKET_SIZE: usize = 188;
+const TS_SYNC_BYTE: u8 = 0x47;
+const PAT_PID: u16 = 0x0000;
+const SDT_PID: u16 = 0x0011;
+const TABLE_ID_PAT: u8 = 0x00;
+const TABLE_ID_PMT: u8 = 0x02;
+const TABLE_ID_SDT_ACTUAL: u8 = 0x42;
+
+/// Broad kind of an elementary stream of a program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamKind{
+    Video,
+    Audio,
+    Subtitle,
+    /// Teletext, metadata and other private data
+    Data,
+}
+
+/// An elementary stream of a program, as listed in its PMT
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramStream{
+    /// stream_type of the PMT, like 0x1b for H.264
+    pub stream_type: u8,
+    pub pid: u16,
+    pub kind: StreamKind,
+    /// ISO 639 language code, like "eng"
+    pub language: Option<String>,
+}
+
+/// A program of a transport stream, a TV or radio service in DVB and
+/// ATSC terms
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program{
+    /// Number to set on tsdemux "program-number" to select it
+    pub program_number: u16,
+    pub pmt_pid: u16,
+    pub pcr_pid: u16,
+    /// Service name from the DVB SDT, if any
+    pub name: Option<String>,
+    /// Service provider from the DVB SDT, if any
+    pub provider: Option<String>,
+    pub streams: Vec<ProgramStream>,
+}
+
+impl Program{
+    pub fn has_video(&self) -> bool{
+        self.streams.iter().any(|stream| stream.kind == StreamKind::Video)
+    }
+
+    pub fn has_audio(&self) -> bool{
+        self.streams.iter().any(|stream| stream.kind == StreamKind::Audio)
+    }
+}
+
+fn stream_kind(stream_type: u8, descriptors: &[u8]) -> StreamKind{
+    match stream_type{
+        0x01 | 0x02 | 0x10 | 0x1b | 0x20 | 0x24 | 0x42 | 0xd1 | 0xea => StreamKind::Video,
+        0x03 | 0x04 | 0x0f | 0x11 | 0x1c | 0x81 | 0x87 => StreamKind::Audio,
+        // DVB signals the content of private PES streams with descriptors
+        0x06 => {
+            let tags = descriptor_tags(descriptors);
+            if tags.iter().any(|&tag| tag == 0x6a || tag == 0x7a || tag == 0x7b || tag == 0x7c){
+                StreamKind::Audio
+            }else if tags.iter().any(|&tag| tag == 0x59){
+                StreamKind::Subtitle
+            }else{
+                StreamKind::Data
+            }
+        }
+        _ => StreamKind::Data
+    }
+}
+
+fn descriptors(data: &[u8]) -> Vec<(u8, &[u8])>{
+    let mut descriptors = Vec::new();
+    let mut pos = 0;
+    while pos + 2 <= data.len(){
+        let len = data[pos + 1] as usize;
+        if pos + 2 + len > data.len(){
+            break;
+        }
+        descriptors.push((data[pos], &data[pos + 2..pos + 2 + len]));
+        pos += 2 + len;
+    }
+    descriptors
+}
+
+fn descriptor_tags(data: &[u8]) -> Vec<u8>{
+    descriptors(data).into_iter().map(|(tag, _)| tag).collect()
+}
+
+// DVB strings start with an optional character table selector, only
+// the default table and UTF-8 are decoded properly
+fn dvb_string(data: &[u8]) -> String{
+    let text = match data.first(){
+        Some(&0x10) if data.len() >= 3 => &data[3..],
+        Some(&0x1f) if data.len() >= 2 => &data[2..],
+        Some(&first) if first < 0x20 => &data[1..],
+        _ => data
+    };
+    String::from_utf8_lossy(text).into_owned()
+}
+
+fn section_length(section: &[u8]) -> Option<usize>{
+    if section.len() < 3{
+        None
+    }else{
+        Some((((section[1] & 0x0f) as usize) << 8 | section[2] as usize) + 3)
+    }
+}
+
+/// Parses a PAT section into (program_number, pmt_pid) pairs. The
+/// network PID entry is skipped.
+pub fn parse_pat(section: &[u8]) -> Option<Vec<(u16, u16)>>{
+    let len = section_length(section)?;
+    if section[0] != TABLE_ID_PAT || len < 12 || len > section.len(){
+        return None;
+    }
+    Some(section[8..len - 4].chunks(4).filter(|entry| entry.len() == 4).filter_map(|entry|{
+        let program_number = (entry[0] as u16) << 8 | entry[1] as u16;
+        let pid = ((entry[2] & 0x1f) as u16) << 8 | entry[3] as u16;
+        if program_number != 0 { Some((program_number, pid)) } else { None }
+    }).collect())
+}
+
+/// Parses a PMT section. The returned program has no name, that comes
+/// from the SDT.
+pub fn parse_pmt(section: &[u8], pmt_pid: u16) -> Option<Program>{
+    let len = section_length(section)?;
+    if section[0] != TABLE_ID_PMT || len < 16 || len > section.len(){
+        return None;
+    }
+    let end = len - 4;
+    let program_number = (section[3] as u16) << 8 | section[4] as u16;
+    let pcr_pid = ((section[8] & 0x1f) as u16) << 8 | section[9] as u16;
+    let program_info_length = ((section[10] & 0x0f) as usize) << 8 | section[11] as usize;
+    let mut pos = 12 + program_info_length;
+    let mut streams = Vec::new();
+    while pos + 5 <= end{
+        let stream_type = section[pos];
+        let pid = ((section[pos + 1] & 0x1f) as u16) << 8 | section[pos + 2] as u16;
+        let es_info_length = ((section[pos + 3] & 0x0f) as usize) << 8 | section[pos + 4] as usize;
+        pos += 5;
+        if pos + es_info_length > end{
+            break;
+        }
+        let es_info = &section[pos..pos + es_info_length];
+        let language = descriptors(es_info).into_iter()
+            .find(|&(tag, data)| tag == 0x0a && data.len() >= 3)
+            .map(|(_, data)| String::from_utf8_lossy(&data[..3]).into_owned());
+        streams.push(ProgramStream{
+            stream_type: stream_type,
+            pid: pid,
+            kind: stream_kind(stream_type, es_info),
+            language: language,
+        });
+        pos += es_info_length;
+    }
+    Some(Program{
+        program_number: program_number,
+        pmt_pid: pmt_pid,
+        pcr_pid: pcr_pid,
+        name: None,
+        provider: None,
+        streams: streams,
+    })
+}
+
+/// Parses a DVB SDT section into (service_id, provider, name) triples
+pub fn parse_sdt(section: &[u8]) -> Option<Vec<(u16, String, String)>>{
+    let len = section_length(section)?;
+    if section[0] != TABLE_ID_SDT_ACTUAL || len < 15 || len > section.len(){
+        return None;
+    }
+    let end = len - 4;
+    let mut pos = 11;
+    let mut services = Vec::new();
+    while pos + 5 <= end{
+        let service_id = (section[pos] as u16) << 8 | section[pos + 1] as u16;
+        let loop_length = ((section[pos + 3] & 0x0f) as usize) << 8 | section[pos + 4] as usize;
+        pos += 5;
+        if pos + loop_length > end{
+            break;
+        }
+        for (tag, data) in descriptors(&section[pos..pos + loop_length]){
+            // service_descriptor
+            if tag != 0x48 || data.len() < 3{
+                continue;
+            }
+            let provider_len = data[1] as usize;
+            if 2 + provider_len >= data.len(){
+                continue;
+            }
+            let provider = dvb_string(&data[2..2 + provider_len]);
+            let name_len = data[2 + provider_len] as usize;
+            let name_start = 3 + provider_len;
+            if name_start + name_len > data.len(){
+                continue;
+            }
+            services.push((service_id, provider, dvb_string(&data[name_start..name_start + name_len])));
+        }
+        pos += loop_length;
+    }
+    Some(services)
+}
+
+/// Finds the programs of a transport stream by parsing its PAT, PMTs
+/// and, for DVB streams, its SDT.
+///
+/// Feed it the raw stream, in packets of any size. Tables are tracked
+/// for the whole stream so programs added or removed later are noticed.
+pub struct ProgramScanner{
+    pending: Vec<u8>,
+    sections: HashMap<u16, Vec<u8>>,
+    pat: Option<Vec<(u16, u16)>>,
+    programs: BTreeMap<u16, Program>,
+    services: HashMap<u16, (String, String)>,
+}
+
+impl ProgramScanner{
+    pub fn new() -> ProgramScanner{
+        ProgramScanner{
+            pending: Vec::new(),
+            sections: HashMap::new(),
+            pat: None,
+            programs: BTreeMap::new(),
+            services: HashMap::new(),
+        }
+    }
+
+    /// Parses the next chunk of the transport stream
+    pub fn push(&mut self, data: &[u8]){
+        let mut pending = mem::replace(&mut self.pending, Vec::new());
+        pending.extend_from_slice(data);
+        let mut pos = 0;
+        while pos + TS_PACKET_SIZE <= pending.len(){
+            if pending[pos] != TS_SYNC_BYTE{
+                pos += 1;
+                continue;
+            }
+            self.push_packet(&pending[pos..pos + TS_PACKET_SIZE]);
+            pos += TS_PACKET_SIZE;
+        }
+        pending.drain(..pos);
+        self.pending = pending;
+    }
+
+    pub fn push_buffer(&mut self, buffer: &Buffer){
+        if let Some(mapping) = buffer.map_read(){
+            self.push(&mapping);
+        }
+    }
+
+    /// True once the PAT and the PMTs of all its programs were found
+    pub fn is_complete(&self) -> bool{
+        match self.pat{
+            Some(ref pat) => pat.iter().all(|&(number, _)| self.programs.contains_key(&number)),
+            None => false
+        }
+    }
+
+    /// Programs found so far, sorted by program number
+    pub fn programs(&self) -> Vec<Program>{
+        self.programs.values().map(|program| self.with_service(program)).collect()
+    }
+
+    pub fn program(&self, program_number: u16) -> Option<Program>{
+        self.programs.get(&program_number).map(|program| self.with_service(program))
+    }
+
+    fn with_service(&self, program: &Program) -> Program{
+        let mut program = program.clone();
+        if let Some(&(ref provider, ref name)) = self.services.get(&program.program_number){
+            program.provider = Some(provider.clone());
+            program.name = Some(name.clone());
+        }
+        program
+    }
+
+    fn is_table_pid(&self, pid: u16) -> bool{
+        pid == PAT_PID || pid == SDT_PID ||
+            self.pat.as_ref().map_or(false, |pat| pat.iter().any(|&(_, pmt_pid)| pmt_pid == pid))
+    }
+
+    fn push_packet(&mut self, packet: &[u8]){
+        let pid = ((packet[1] & 0x1f) as u16) << 8 | packet[2] as u16;
+        if packet[1] & 0x80 != 0 || !self.is_table_pid(pid){
+            return;
+        }
+        let payload_unit_start = packet[1] & 0x40 != 0;
+        let adaptation_field_control = (packet[3] >> 4) & 0x03;
+        if adaptation_field_control & 0x01 == 0{
+            return;
+        }
+        let mut start = 4;
+        if adaptation_field_control & 0x02 != 0{
+            start += 1 + packet[4] as usize;
+        }
+        if start >= packet.len(){
+            return;
+        }
+        let payload = &packet[start..];
+
+        if payload_unit_start{
+            let pointer = payload[0] as usize;
+            if 1 + pointer > payload.len(){
+                self.sections.remove(&pid);
+                return;
+            }
+            // the end of the previous section comes before the pointer
+            if let Some(mut section) = self.sections.remove(&pid){
+                section.extend_from_slice(&payload[1..1 + pointer]);
+                self.complete_sections(pid, section);
+            }
+            self.complete_sections(pid, payload[1 + pointer..].to_vec());
+        }else if let Some(mut section) = self.sections.remove(&pid){
+            section.extend_from_slice(payload);
+            self.complete_sections(pid, section);
+        }
+    }
+
+    // parses the complete sections at the start of `data`, keeping an
+    // incomplete one for the next packets
+    fn complete_sections(&mut self, pid: u16, mut data: Vec<u8>){
+        loop{
+            if data.first().map_or(true, |&table_id| table_id == 0xff){
+                return;
+            }
+            match section_length(&data){
+                Some(len) if len <= data.len() => {
+                    let rest = data.split_off(len);
+                    self.parse_section(pid, &data);
+                    data = rest;
+                }
+                _ => {
+                    self.sections.insert(pid, data);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn parse_section(&mut self, pid: u16, section: &[u8]){
+        match section[0]{
+            TABLE_ID_PAT if pid == PAT_PID => {
+                if let Some(pat) = parse_pat(section){
+                    self.programs.retain(|number, program|
+                        pat.iter().any(|&(n, pmt_pid)| n == *number && pmt_pid == program.pmt_pid));
+                    self.pat = Some(pat);
+                }
+            }
+            TABLE_ID_PMT => {
+                if let Some(program) = parse_pmt(section, pid){
+                    self.programs.insert(program.program_number, program);
+                }
+            }
+            TABLE_ID_SDT_ACTUAL if pid == SDT_PID => {
+                if let Some(services) = parse_sdt(section){
+                    for (service_id, provider, name) in services{
+                        self.services.insert(service_id, (provider, name));
+                    }
+                }
+            }
+            _ => ()
+        }
+    }
+}
+
+/// Typed wrapper for the tsdemux element with program selection
+pub struct TsDemux{
+    tsdemux: Element
+}
+
+unsafe impl Sync for TsDemux {}
+unsafe impl Send for TsDemux {}
+
+impl TsDemux{
+    pub fn new(name: &str) -> Result<TsDemux>{
+        Element::new("tsdemux", name).map(|tsdemux| TsDemux{ tsdemux: tsdemux })
+    }
+
+    pub fn new_from_element(element: Element) -> TsDemux{
+        TsDemux{ tsdemux: element }
+    }
+
+    /// Selects the program to demux, -1 for the first one in the PAT.
+    /// Can be changed while playing, the pads of the old program are
+    /// removed and the ones of the new program added
+    pub fn set_program_number(&mut self, program_number: i32){
+        self.tsdemux.set("program-number", program_number);
+    }
+
+    pub fn program_number(&self) -> i32{
+        self.tsdemux.get("program-number")
+    }
+
+    pub fn select_program(&mut self, program: &Program){
+        self.set_program_number(program.program_number as i32);
+    }
+
+    /// Starts scanning the stream arriving at the demuxer for its
+    /// programs. The returned scanner is updated from the streaming
+    /// thread until the scan is stopped with `stop_scan` and the id of
+    /// the probe returned with it, or for as long as the demuxer exists.
+    pub fn scan_programs(&mut self) -> Option<(Arc<Mutex<ProgramScanner>>, u64)>{
+        let mut sink = self.tsdemux.static_pad("sink")?;
+        let scanner = Arc::new(Mutex::new(ProgramScanner::new()));
+        let probe = unsafe{
+            let data = Box::new(scanner.clone());
+            gst_pad_add_probe(sink.gst_pad_mut(), GST_PAD_PROBE_TYPE_BUFFER | GST_PAD_PROBE_TYPE_BUFFER_LIST,
+                              Some(scanner_probe), Box::into_raw(data) as gpointer,
+                              Some(free_scanner_probe_data)) as u64
+        };
+        Some((scanner, probe))
+    }
+
+    /// Stops the scan started by `scan_programs` that returned `probe`.
+    /// The scanner keeps the programs found so far
+    pub fn stop_scan(&mut self, probe: u64){
+        if let Some(mut sink) = self.tsdemux.static_pad("sink"){
+            sink.remove_probe(probe);
+        }
+    }
+}
+
+extern "C" fn scanner_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        let scanner = &*(data as *const Arc<Mutex<ProgramScanner>>);
+        if (*info)._type & GST_PAD_PROBE_TYPE_BUFFER_LIST != 0{
+            let list = (*info).data as *mut GstBufferList;
+            for idx in 0..gst_buffer_list_length(list){
+                scan_buffer(pad, scanner, gst_buffer_list_get(list, idx));
+            }
+        }else{
+            scan_buffer(pad, scanner, (*info).data as *mut GstBuffer);
+        }
+        GST_PAD_PROBE_OK
+    }
+}
+
+unsafe fn scan_buffer(pad: *mut GstPad, scanner: &Arc<Mutex<ProgramScanner>>, buffer: *mut GstBuffer){
+    let mut map: GstMapInfo = mem::zeroed();
+    if gst_buffer_map(buffer, &mut map, GST_MAP_READ) != 0{
+        unwind::guard(pad as *mut GstObject, (), || if let Ok(mut scanner) = scanner.lock(){
+            scanner.push(::std::slice::from_raw_parts(map.data, map.size as usize));
+        });
+        gst_buffer_unmap(buffer, &mut map);
+    }
+}
+
+extern "C" fn free_scanner_probe_data(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Arc<Mutex<ProgramScanner>>));
+    }
+}
+
+impl AsRef<Element> for TsDemux{
+    fn as_ref(&self) -> &Element{
+        &self.tsdemux
+    }
+}
+
+impl AsMut<Element> for TsDemux{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.tsdemux
+    }
+}
+
+impl From<TsDemux> for Element{
+    fn from(tsdemux: TsDemux) -> Element{
+        tsdemux.tsdemux
+    }
+}
+
+impl Deref for TsDemux{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.tsdemux
+    }
+}
+
+impl DerefMut for TsDemux{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.tsdemux
+    }
+}
+
+impl ::Transfer for TsDemux{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.tsdemux.transfer()
+    }
+}
+
+impl Reference for TsDemux{
+    fn reference(&self) -> TsDemux{
+        TsDemux{ tsdemux: self.tsdemux.reference() }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    // section with the given table id and body, followed by a dummy crc
+    fn section(table_id: u8, body: &[u8]) -> Vec<u8>{
+        let len = body.len() + 4;
+        let mut section = vec![table_id, 0xb0 | (len >> 8) as u8, len as u8];
+        section.extend_from_slice(body);
+        section.extend_from_slice(&[0; 4]);
+        section
+    }
+
+    fn pat() -> Vec<u8>{
+        section(TABLE_ID_PAT, &[
+            0x00, 0x01, 0xc1, 0x00, 0x00,
+            0x00, 0x00, 0xe0, 0x10,     // network pid
+            0x00, 0x01, 0xe1, 0x00,
+            0x00, 0x02, 0xe2, 0x00,
+        ])
+    }
+
+    fn pmt(program_number: u16, streams: &[u8]) -> Vec<u8>{
+        let mut body = vec![(program_number >> 8) as u8, program_number as u8, 0xc1, 0x00, 0x00, 0xe1, 0x00, 0xf0, 0x00];
+        body.extend_from_slice(streams);
+        section(TABLE_ID_PMT, &body)
+    }
+
+    const STREAMS: [u8; 16] = [
+        0x1b, 0xe1, 0x00, 0xf0, 0x00,
+        0x0f, 0xe1, 0x01, 0xf0, 0x06, 0x0a, 0x04, b'e', b'n', b'g', 0x00,
+    ];
+
+    // a transport stream packet of `pid` carrying `payload`, padded
+    fn packet(pid: u16, payload_unit_start: bool, payload: &[u8]) -> Vec<u8>{
+        let mut packet = vec![TS_SYNC_BYTE, (payload_unit_start as u8) << 6 | (pid >> 8) as u8, pid as u8, 0x10];
+        packet.extend_from_slice(payload);
+        packet.resize(TS_PACKET_SIZE, 0xff);
+        packet
+    }
+
+    #[test]
+    fn pat_programs(){
+        assert_eq!(parse_pat(&pat()), Some(vec![(1, 0x100), (2, 0x200)]));
+    }
+
+    #[test]
+    fn pat_truncated(){
+        let pat = pat();
+        assert_eq!(parse_pat(&pat[..pat.len() - 1]), None);
+        assert_eq!(parse_pat(&pat[..2]), None);
+        assert_eq!(parse_pat(&section(TABLE_ID_PAT, &[0x00, 0x01, 0xc1])), None);
+    }
+
+    #[test]
+    fn pat_overlong_section_length(){
+        let mut pat = pat();
+        pat[1] |= 0x0f;
+        assert_eq!(parse_pat(&pat), None);
+    }
+
+    #[test]
+    fn pmt_streams(){
+        let program = parse_pmt(&pmt(1, &STREAMS), 0x100).unwrap();
+        assert_eq!(program.program_number, 1);
+        assert_eq!(program.pmt_pid, 0x100);
+        assert_eq!(program.pcr_pid, 0x100);
+        assert_eq!(program.streams, vec![
+            ProgramStream{ stream_type: 0x1b, pid: 0x100, kind: StreamKind::Video, language: None },
+            ProgramStream{ stream_type: 0x0f, pid: 0x101, kind: StreamKind::Audio, language: Some("eng".to_string()) },
+        ]);
+    }
+
+    #[test]
+    fn pmt_truncated(){
+        let pmt = pmt(1, &STREAMS);
+        assert_eq!(parse_pmt(&pmt[..pmt.len() - 1], 0x100), None);
+        assert_eq!(parse_pmt(&section(TABLE_ID_PMT, &[0x00, 0x01, 0xc1, 0x00, 0x00, 0xe1]), 0x100), None);
+    }
+
+    #[test]
+    fn pmt_overlong_section_length(){
+        let mut pmt = pmt(1, &STREAMS);
+        pmt[2] = 0xff;
+        assert_eq!(parse_pmt(&pmt, 0x100), None);
+    }
+
+    #[test]
+    fn pmt_es_info_past_the_end(){
+        let mut streams = STREAMS;
+        streams[9] = 0x20;
+        let program = parse_pmt(&pmt(1, &streams), 0x100).unwrap();
+        assert_eq!(program.streams.len(), 1);
+
+        // program_info_length past the end
+        let mut pmt = pmt(1, &STREAMS);
+        pmt[10] = 0xf0;
+        pmt[11] = 0xff;
+        assert_eq!(parse_pmt(&pmt, 0x100).unwrap().streams, vec![]);
+    }
+
+    fn sdt(descriptors: &[u8], loop_length: usize) -> Vec<u8>{
+        let mut body = vec![0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xff,
+                            0x00, 0x01, 0xfc, 0xf0 | (loop_length >> 8) as u8, loop_length as u8];
+        body.extend_from_slice(descriptors);
+        section(TABLE_ID_SDT_ACTUAL, &body)
+    }
+
+    #[test]
+    fn sdt_services(){
+        let descriptor = [0x48, 0x0c, 0x01, 0x04, b'A', b'C', b'M', b'E', 0x05, 0x15, b'N', b'e', b'w', b's'];
+        assert_eq!(parse_sdt(&sdt(&descriptor, descriptor.len())),
+                   Some(vec![(1, "ACME".to_string(), "News".to_string())]));
+
+        // name past the end of the descriptor
+        let mut truncated = descriptor;
+        truncated[8] = 0x10;
+        assert_eq!(parse_sdt(&sdt(&truncated, truncated.len())), Some(vec![]));
+
+        // descriptor loop past the end of the section
+        assert_eq!(parse_sdt(&sdt(&descriptor, 0x100)), Some(vec![]));
+
+        let mut overlong = sdt(&descriptor, descriptor.len());
+        overlong[2] += 1;
+        assert_eq!(parse_sdt(&overlong), None);
+    }
+
+    #[test]
+    fn scanner_finds_programs(){
+        // the pmt of program 1 spans two packets
+        let mut streams = Vec::new();
+        for i in 0..40u8{
+            streams.extend_from_slice(&[0x1b, 0xe1, i, 0xf0, 0x00]);
+        }
+        let mut pmt1 = vec![0];
+        pmt1.extend(pmt(1, &streams));
+        let mut pmt2 = vec![0];
+        pmt2.extend(pmt(2, &STREAMS));
+        let mut pat_payload = vec![0];
+        pat_payload.extend(pat());
+
+        // garbage before the first packet
+        let mut stream = vec![0x00, 0x12];
+        stream.extend(packet(PAT_PID, true, &pat_payload));
+        stream.extend(packet(0x100, true, &pmt1[..184]));
+        stream.extend(packet(0x100, false, &pmt1[184..]));
+        stream.extend(packet(0x200, true, &pmt2));
+
+        let mut scanner = ProgramScanner::new();
+        for chunk in stream.chunks(100){
+            scanner.push(chunk);
+        }
+        assert!(scanner.is_complete());
+        let programs = scanner.programs();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0].streams.len(), 40);
+        assert_eq!(programs[1].streams.len(), 2);
+    }
+
+    #[test]
+    fn scanner_ignores_broken_sections(){
+        let mut pat_payload = vec![0];
+        pat_payload.extend(pat());
+        // section length longer than the rest of the stream
+        let mut pmt1 = vec![0];
+        pmt1.extend(pmt(1, &STREAMS));
+        pmt1[3] = 0xff;
+        // pointer past the end of the packet
+        let pmt2 = [0xff];
+
+        let mut stream = packet(PAT_PID, true, &pat_payload);
+        stream.extend(packet(0x100, true, &pmt1));
+        stream.extend(packet(0x200, true, &pmt2));
+        // truncated packet
+        stream.extend(&packet(0x200, true, &[0])[..100]);
+
+        let mut scanner = ProgramScanner::new();
+        scanner.push(&stream);
+        assert!(!scanner.is_complete());
+        assert_eq!(scanner.programs(), vec![]);
+    }
+}
@@ -0,0 +1,512 @@
+use ffi::*;
+use util::*;
+use error::{Error, Result};
+use message::Message;
+use pipeline::Pipeline;
+use pipelineconfig::{self, ConfigValue};
+use query::Query;
+use reference::Reference;
+use taglist::TagList;
+use unwind;
+
+use std::os::raw::c_char;
+use std::sync::{Arc, Mutex};
+
+const OBJECT_PATH: &'static str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &'static str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &'static str = "org.mpris.MediaPlayer2.Player";
+
+const INTROSPECTION: &'static str = r#"
+<node>
+  <interface name="org.mpris.MediaPlayer2">
+    <method name="Raise"/>
+    <method name="Quit"/>
+    <property name="CanQuit" type="b" access="read"/>
+    <property name="CanRaise" type="b" access="read"/>
+    <property name="HasTrackList" type="b" access="read"/>
+    <property name="Identity" type="s" access="read"/>
+    <property name="SupportedUriSchemes" type="as" access="read"/>
+    <property name="SupportedMimeTypes" type="as" access="read"/>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2.Player">
+    <method name="Next"/>
+    <method name="Previous"/>
+    <method name="Pause"/>
+    <method name="PlayPause"/>
+    <method name="Stop"/>
+    <method name="Play"/>
+    <method name="Seek">
+      <arg direction="in" name="Offset" type="x"/>
+    </method>
+    <method name="SetPosition">
+      <arg direction="in" name="TrackId" type="o"/>
+      <arg direction="in" name="Position" type="x"/>
+    </method>
+    <method name="OpenUri">
+      <arg direction="in" name="Uri" type="s"/>
+    </method>
+    <signal name="Seeked">
+      <arg name="Position" type="x"/>
+    </signal>
+    <property name="PlaybackStatus" type="s" access="read"/>
+    <property name="Rate" type="d" access="read"/>
+    <property name="Metadata" type="a{sv}" access="read"/>
+    <property name="Volume" type="d" access="readwrite"/>
+    <property name="Position" type="x" access="read"/>
+    <property name="MinimumRate" type="d" access="read"/>
+    <property name="MaximumRate" type="d" access="read"/>
+    <property name="CanGoNext" type="b" access="read"/>
+    <property name="CanGoPrevious" type="b" access="read"/>
+    <property name="CanPlay" type="b" access="read"/>
+    <property name="CanPause" type="b" access="read"/>
+    <property name="CanSeek" type="b" access="read"/>
+    <property name="CanControl" type="b" access="read"/>
+  </interface>
+</node>
+"#;
+
+struct Player{
+    pipeline: Pipeline,
+    identity: String,
+    tags: TagList,
+    track: u32,
+    node_info: *mut GDBusNodeInfo,
+    connection: *mut GDBusConnection,
+    registrations: Vec<guint>,
+}
+
+unsafe impl Send for Player {}
+
+type Shared = Arc<Mutex<Player>>;
+
+/// Publishes a pipeline as an MPRIS2 media player on the session bus, so
+/// desktop media controls, like those of the lock screen or the keyboard
+/// media keys, can play, pause and seek it and show what's playing.
+///
+/// The D-Bus calls are served from the default glib main context, so a
+/// `MainLoop` has to be running. The metadata and playback status are
+/// updated from the messages of the pipeline passed to `handle_message`,
+/// usually from the same loop that handles the bus:
+///
+/// ```ignore
+/// let mpris = Mpris::new(&playbin, "myplayer", "My Player")?;
+/// for message in playbin.bus().unwrap().receiver().iter(){
+///     mpris.handle_message(&message);
+///     ...
+/// }
+/// ```
+///
+/// For PlayBin, or any pipeline with "uri" and "volume" properties,
+/// OpenUri and Volume are supported too.
+/// See [MPRIS](https://specifications.freedesktop.org/mpris-spec/latest/)
+pub struct Mpris{
+    owner_id: guint,
+    shared: Shared,
+}
+
+impl Mpris{
+    /// Requests the bus name org.mpris.MediaPlayer2.`name`, `identity` is
+    /// the name of the application shown to users
+    pub fn new(pipeline: &Pipeline, name: &str, identity: &str) -> Result<Mpris>{
+        unsafe{
+            let cxml = CString::new(INTROSPECTION).unwrap();
+            let mut error = ptr::null_mut();
+            let node_info = g_dbus_node_info_new_for_xml(cxml.as_ptr(), &mut error);
+            if node_info == ptr::null_mut(){
+                return Err(Error::new_from_g_error(error));
+            }
+
+            let shared = Arc::new(Mutex::new(Player{
+                pipeline: pipeline.reference(),
+                identity: identity.to_string(),
+                tags: TagList::new_empty(),
+                track: 0,
+                node_info: node_info,
+                connection: ptr::null_mut(),
+                registrations: vec![],
+            }));
+            let cname = CString::new(format!("{}.{}", ROOT_INTERFACE, name)).unwrap();
+            let owner_id = g_bus_own_name(G_BUS_TYPE_SESSION, cname.as_ptr(), G_BUS_NAME_OWNER_FLAGS_NONE,
+                                          Some(on_bus_acquired), None, None,
+                                          Box::into_raw(Box::new(shared.clone())) as gpointer,
+                                          Some(free_shared));
+            Ok(Mpris{
+                owner_id: owner_id,
+                shared: shared,
+            })
+        }
+    }
+
+    /// Updates the metadata and playback status from the tag, state
+    /// changed and duration changed messages of the pipeline. Any other
+    /// message is ignored
+    pub fn handle_message(&self, message: &Message){
+        let changed = {
+            let mut player = self.shared.lock().unwrap();
+            match *message{
+                Message::Tag(_) | Message::TagParsed{..} => {
+                    if let Some(tags) = message.tags(){
+                        player.tags.insert(&tags, GST_TAG_MERGE_REPLACE);
+                    }
+                    "Metadata"
+                }
+                Message::DurationChanged(_) => "Metadata",
+                Message::StateChanged(_) | Message::StateChangedParsed{..} => unsafe{
+                    if message.src() != player.pipeline.gst_element() as *mut GstObject{
+                        return;
+                    }
+                    "PlaybackStatus"
+                },
+                _ => return
+            }
+        };
+        self.notify(&[changed]);
+    }
+
+    /// Replaces the metadata with `tags`, for example when a new track
+    /// starts
+    pub fn set_tags(&self, tags: &TagList){
+        {
+            let mut player = self.shared.lock().unwrap();
+            player.tags = tags.clone();
+            player.track += 1;
+        }
+        self.notify(&["Metadata"]);
+    }
+
+    // emits PropertiesChanged for the properties of the player interface
+    fn notify(&self, properties: &[&str]){
+        let (pipeline, tags, track, connection) = {
+            let player = self.shared.lock().unwrap();
+            (player.pipeline.reference(), player.tags.clone(), player.track, player.connection)
+        };
+        if connection == ptr::null_mut(){
+            return;
+        }
+        unsafe{
+            let changed = properties.iter().filter_map(|property|
+                player_property(&pipeline, &tags, track, property).map(|value| (*property, value))
+            ).collect();
+            let invalidated: Vec<String> = vec![];
+            let parameters = tuple_variant(&[string_variant(PLAYER_INTERFACE),
+                                             dict_variant(changed),
+                                             strv_variant(&invalidated)]);
+            emit_signal(connection, "org.freedesktop.DBus.Properties", "PropertiesChanged", parameters);
+        }
+    }
+}
+
+impl Drop for Mpris{
+    fn drop(&mut self){
+        unsafe{
+            g_bus_unown_name(self.owner_id);
+            let mut player = self.shared.lock().unwrap();
+            let connection = player.connection;
+            if connection != ptr::null_mut(){
+                for registration in player.registrations.drain(..){
+                    g_dbus_connection_unregister_object(connection, registration);
+                }
+            }
+            g_dbus_node_info_unref(player.node_info);
+            player.node_info = ptr::null_mut();
+        }
+    }
+}
+
+extern "C" fn on_bus_acquired(connection: *mut GDBusConnection, _name: *const gchar, data: gpointer){
+    unsafe{
+        unwind::guard(ptr::null_mut(), (), || {
+            let shared = &*(data as *const Shared);
+            let mut player = shared.lock().unwrap();
+            if player.node_info == ptr::null_mut(){
+                return;
+            }
+            player.connection = connection;
+            let vtable = GDBusInterfaceVTable{
+                method_call: Some(on_method_call),
+                get_property: Some(on_get_property),
+                set_property: Some(on_set_property),
+                padding: [ptr::null_mut(); 8],
+            };
+            let cpath = CString::new(OBJECT_PATH).unwrap();
+            for interface in [ROOT_INTERFACE, PLAYER_INTERFACE].iter(){
+                let cinterface = CString::new(*interface).unwrap();
+                let info = g_dbus_node_info_lookup_interface(player.node_info, cinterface.as_ptr());
+                let registration = g_dbus_connection_register_object(connection, cpath.as_ptr(), info, &vtable,
+                                                                     Box::into_raw(Box::new(shared.clone())) as gpointer,
+                                                                     Some(free_shared), ptr::null_mut());
+                if registration != 0{
+                    player.registrations.push(registration);
+                }
+            }
+        })
+    }
+}
+
+extern "C" fn on_method_call(connection: *mut GDBusConnection, _sender: *const gchar, _object_path: *const gchar,
+                             _interface_name: *const gchar, method_name: *const gchar, parameters: *mut GVariant,
+                             invocation: *mut GDBusMethodInvocation, data: gpointer){
+    unsafe{
+        // the invocation is answered even if the method panics
+        let failed = ("org.freedesktop.DBus.Error.Failed", "The method failed".to_string());
+        let ret = unwind::guard(ptr::null_mut(), Err(failed), || {
+            let shared = &*(data as *const Shared);
+            let mut pipeline = shared.lock().unwrap().pipeline.reference();
+            match from_c_str!(method_name){
+                "Raise" | "Quit" | "Next" | "Previous" => Ok(()),
+                "Play" => state_result(pipeline.play()),
+                "Pause" => state_result(pipeline.pause()),
+                "PlayPause" => if pipeline.is_playing(){
+                    state_result(pipeline.pause())
+                }else{
+                    state_result(pipeline.play())
+                },
+                "Stop" => state_result(pipeline.set_ready_state()),
+                "Seek" => {
+                    let offset = int64_child(parameters, 0) * 1000;
+                    let position = pipeline.position_ns().unwrap_or(0) + offset;
+                    let position = match pipeline.duration_ns(){
+                        Some(duration) if position > duration => duration,
+                        _ => position.max(0),
+                    };
+                    seek(connection, &mut pipeline, position)
+                }
+                "SetPosition" => {
+                    let position = int64_child(parameters, 1) * 1000;
+                    match pipeline.duration_ns(){
+                        Some(duration) if position >= 0 && position <= duration => seek(connection, &mut pipeline, position),
+                        _ => Ok(()),
+                    }
+                }
+                "OpenUri" => {
+                    let uri = g_variant_get_child_value(parameters, 0);
+                    let value = ConfigValue::String(from_c_str!(g_variant_get_string(uri, ptr::null_mut())).to_string());
+                    g_variant_unref(uri);
+                    pipeline.set_ready_state();
+                    pipelineconfig::set_property(&mut pipeline, "uri", &value)
+                        .map_err(|_| ("org.freedesktop.DBus.Error.NotSupported", "Can't open uris".to_string()))
+                        .and_then(|_| state_result(pipeline.play()))
+                }
+                method => Err(("org.freedesktop.DBus.Error.UnknownMethod", format!("Unknown method {}", method)))
+            }
+        });
+        match ret{
+            Ok(()) => g_dbus_method_invocation_return_value(invocation, tuple_variant(&[])),
+            Err((name, message)) => {
+                let cname = CString::new(name).unwrap();
+                let cmessage = CString::new(message).unwrap();
+                g_dbus_method_invocation_return_dbus_error(invocation, cname.as_ptr(), cmessage.as_ptr());
+            }
+        }
+    }
+}
+
+extern "C" fn on_get_property(_connection: *mut GDBusConnection, _sender: *const gchar, _object_path: *const gchar,
+                              interface_name: *const gchar, property_name: *const gchar, error: *mut *mut GError,
+                              data: gpointer) -> *mut GVariant{
+    unsafe{
+        let value = unwind::guard(ptr::null_mut(), None, || {
+            let shared = &*(data as *const Shared);
+            let (pipeline, identity, tags, track) = {
+                let player = shared.lock().unwrap();
+                (player.pipeline.reference(), player.identity.clone(), player.tags.clone(), player.track)
+            };
+            let property = from_c_str!(property_name);
+            if from_c_str!(interface_name) == ROOT_INTERFACE{
+                match property{
+                    "CanQuit" | "CanRaise" | "HasTrackList" => Some(g_variant_new_boolean(0)),
+                    "Identity" => Some(string_variant(&identity)),
+                    "SupportedUriSchemes" => {
+                        let schemes: Vec<String> = if pipelineconfig::get_property(&pipeline, "uri").is_ok(){
+                            vec!["file".to_string(), "http".to_string(), "https".to_string()]
+                        }else{
+                            vec![]
+                        };
+                        Some(strv_variant(&schemes))
+                    }
+                    "SupportedMimeTypes" => Some(strv_variant(&[])),
+                    _ => None
+                }
+            }else{
+                player_property(&pipeline, &tags, track, property)
+            }
+        });
+        value.unwrap_or_else(||{
+            set_error(error, G_DBUS_ERROR_NOT_SUPPORTED, &format!("Unknown property {}", from_c_str!(property_name)));
+            ptr::null_mut()
+        })
+    }
+}
+
+extern "C" fn on_set_property(_connection: *mut GDBusConnection, _sender: *const gchar, _object_path: *const gchar,
+                              _interface_name: *const gchar, property_name: *const gchar, value: *mut GVariant,
+                              error: *mut *mut GError, data: gpointer) -> gboolean{
+    unsafe{
+        let ret = unwind::guard(ptr::null_mut(), false, || {
+            let shared = &*(data as *const Shared);
+            let mut pipeline = shared.lock().unwrap().pipeline.reference();
+            match from_c_str!(property_name){
+                "Volume" => {
+                    let volume = ConfigValue::Float(g_variant_get_double(value).max(0.));
+                    pipelineconfig::set_property(&mut pipeline, "volume", &volume).is_ok()
+                }
+                _ => false
+            }
+        });
+        if !ret{
+            set_error(error, G_DBUS_ERROR_NOT_SUPPORTED, "Can't set the property");
+        }
+        ret as gboolean
+    }
+}
+
+extern "C" fn free_shared(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Shared));
+    }
+}
+
+// value of a property of the org.mpris.MediaPlayer2.Player interface
+unsafe fn player_property(pipeline: &Pipeline, tags: &TagList, track: u32, property: &str) -> Option<*mut GVariant>{
+    let value = match property{
+        "PlaybackStatus" => {
+            let (state, pending, _) = pipeline.get_state(0);
+            let state = match pending{
+                GST_STATE_VOID_PENDING => state,
+                pending => pending,
+            };
+            string_variant(match state{
+                GST_STATE_PLAYING => "Playing",
+                GST_STATE_PAUSED => "Paused",
+                _ => "Stopped",
+            })
+        }
+        "Rate" | "MinimumRate" | "MaximumRate" => g_variant_new_double(1.),
+        "Metadata" => metadata(pipeline, tags, track),
+        "Volume" => {
+            let volume = match pipelineconfig::get_property(pipeline, "volume"){
+                Ok(ConfigValue::Float(volume)) => volume,
+                _ => 1.
+            };
+            g_variant_new_double(volume)
+        }
+        "Position" => g_variant_new_int64(pipeline.position_ns().unwrap_or(0) / 1000),
+        "CanGoNext" | "CanGoPrevious" => g_variant_new_boolean(0),
+        "CanPlay" | "CanPause" | "CanControl" => g_variant_new_boolean(1),
+        "CanSeek" => {
+            let mut query = Query::new_seeking(GST_FORMAT_TIME);
+            let seekable = pipeline.reference().query(&mut query) && query.is_seekable();
+            g_variant_new_boolean(seekable as gboolean)
+        }
+        _ => return None
+    };
+    Some(value)
+}
+
+// the tags mapped to the xesam properties used by mpris
+unsafe fn metadata(pipeline: &Pipeline, tags: &TagList, track: u32) -> *mut GVariant{
+    let ctrack = CString::new(format!("/org/gstreamer/track/{}", track)).unwrap();
+    let mut entries = vec![("mpris:trackid", g_variant_new_object_path(ctrack.as_ptr()))];
+    if let Some(duration) = pipeline.duration_ns(){
+        entries.push(("mpris:length", g_variant_new_int64(duration / 1000)));
+    }
+    if let Ok(ConfigValue::String(uri)) = pipelineconfig::get_property(pipeline, "current-uri"){
+        entries.push(("xesam:url", string_variant(&uri)));
+    }
+    for &(key, tag) in [("xesam:title", "title"), ("xesam:album", "album")].iter(){
+        if let Some(value) = tags.get::<String>(tag){
+            entries.push((key, string_variant(&value)));
+        }
+    }
+    for &(key, tag) in [("xesam:artist", "artist"), ("xesam:albumArtist", "album-artist"),
+                        ("xesam:genre", "genre"), ("xesam:comment", "comment")].iter(){
+        let values = tags.get_all::<String>(tag);
+        if !values.is_empty(){
+            entries.push((key, strv_variant(&values)));
+        }
+    }
+    for &(key, tag) in [("xesam:trackNumber", "track-number"), ("xesam:discNumber", "album-disc-number")].iter(){
+        if let Some(value) = tags.get::<u32>(tag){
+            entries.push((key, g_variant_new_int32(value as i32)));
+        }
+    }
+    if let Some(date) = tags.date_time("datetime"){
+        let mut created = format!("{:04}", date.year);
+        if let Some(month) = date.month{
+            created += &format!("-{:02}", month);
+            if let Some(day) = date.day{
+                created += &format!("-{:02}", day);
+            }
+        }
+        entries.push(("xesam:contentCreated", string_variant(&created)));
+    }else if let Some(date) = tags.date("date"){
+        let created = format!("{:04}-{:02}-{:02}", date.year, date.month, date.day);
+        entries.push(("xesam:contentCreated", string_variant(&created)));
+    }
+    dict_variant(entries)
+}
+
+fn state_result(ret: GstStateChangeReturn) -> ::std::result::Result<(), (&'static str, String)>{
+    if ret == GST_STATE_CHANGE_FAILURE{
+        Err(("org.freedesktop.DBus.Error.Failed", "Couldn't change the state of the pipeline".to_string()))
+    }else{
+        Ok(())
+    }
+}
+
+unsafe fn seek(connection: *mut GDBusConnection, pipeline: &mut Pipeline, position: i64)
+        -> ::std::result::Result<(), (&'static str, String)>{
+    if pipeline.set_position_ns(position){
+        emit_signal(connection, PLAYER_INTERFACE, "Seeked", tuple_variant(&[g_variant_new_int64(position / 1000)]));
+        Ok(())
+    }else{
+        Err(("org.freedesktop.DBus.Error.Failed", "Couldn't seek".to_string()))
+    }
+}
+
+unsafe fn emit_signal(connection: *mut GDBusConnection, interface: &str, signal: &str, parameters: *mut GVariant){
+    let cpath = CString::new(OBJECT_PATH).unwrap();
+    let cinterface = CString::new(interface).unwrap();
+    let csignal = CString::new(signal).unwrap();
+    g_dbus_connection_emit_signal(connection, ptr::null(), cpath.as_ptr(), cinterface.as_ptr(), csignal.as_ptr(),
+                                  parameters, ptr::null_mut());
+}
+
+unsafe fn set_error(error: *mut *mut GError, code: i32, message: &str){
+    let cmessage = CString::new(message).unwrap();
+    g_set_error_literal(error, g_dbus_error_quark(), code, cmessage.as_ptr());
+}
+
+unsafe fn int64_child(parameters: *mut GVariant, index: usize) -> i64{
+    let child = g_variant_get_child_value(parameters, index as gsize);
+    let ret = g_variant_get_int64(child);
+    g_variant_unref(child);
+    ret
+}
+
+unsafe fn string_variant(string: &str) -> *mut GVariant{
+    let cstring = CString::new(string).unwrap();
+    g_variant_new_string(cstring.as_ptr())
+}
+
+unsafe fn strv_variant(strings: &[String]) -> *mut GVariant{
+    let cstrings: Vec<CString> = strings.iter().map(|string| CString::new(string.as_str()).unwrap()).collect();
+    let pointers: Vec<*const c_char> = cstrings.iter().map(|string| string.as_ptr()).collect();
+    g_variant_new_strv(pointers.as_ptr(), pointers.len() as gssize)
+}
+
+unsafe fn tuple_variant(children: &[*mut GVariant]) -> *mut GVariant{
+    g_variant_new_tuple(children.as_ptr(), children.len() as gsize)
+}
+
+// a{sv} dictionary, takes the floating references of the values
+unsafe fn dict_variant(entries: Vec<(&str, *mut GVariant)>) -> *mut GVariant{
+    let entries: Vec<*mut GVariant> = entries.into_iter().map(|(key, value)|
+        g_variant_new_dict_entry(string_variant(key), g_variant_new_variant(value))
+    ).collect();
+    let ctype = CString::new("{sv}").unwrap();
+    let entry_type = g_variant_type_new(ctype.as_ptr());
+    let ret = g_variant_new_array(entry_type, entries.as_ptr(), entries.len() as gsize);
+    g_variant_type_free(entry_type);
+    ret
+}
@@ -1,8 +1,15 @@
 use ffi::*;
 use util::*;
 use reference::{Reference, Ref};
+use structure::FieldValue;
+use value::Value;
+use unwind;
+#[cfg(feature = "leak-tracking")]
+use tracking::{self, TrackedKind};
 
 use std::os::raw::{c_void, c_char};
+use std::cell::RefCell;
+use std::sync::Mutex;
 
 pub struct Object{
     object: *mut GstObject,
@@ -12,6 +19,8 @@ pub struct Object{
 impl Drop for Object{
 	fn drop(&mut self){
 		unsafe{
+			#[cfg(feature = "leak-tracking")]
+			tracking::untrack(self.object as *const c_void);
 			gst_object_unref(self.object as *mut c_void);
 		}
 	}
@@ -20,6 +29,8 @@ impl Drop for Object{
 impl Object{
     pub unsafe fn new(object: *mut GstObject) -> Option<Object>{
         if object != ptr::null_mut(){
+            #[cfg(feature = "leak-tracking")]
+            tracking::track(object as *const c_void, TrackedKind::Object);
             Some(Object{ object: object })
         }else{
             None
@@ -95,11 +106,8 @@ impl Object{
     }
 
     pub fn parent(&self) -> Option<Ref<Object>>{
-        let parent = unsafe{ gst_object_get_parent(self.object) };
-        if parent == ptr::null_mut(){
-            None
-        }else{
-            Some(Ref::new(&Object{ object: parent }))
+        unsafe{
+            Object::new(gst_object_get_parent(self.object)).map(Ref::from)
         }
     }
 
@@ -168,12 +176,113 @@ impl Object{
         }
     }
 
+    /// Whether the object has a property called `name`, to check before
+    /// setting properties that only some elements or versions have
+    pub fn has_property(&self, name: &str) -> bool{
+        unsafe{
+            let class = (*(self.gst_object() as *mut GTypeInstance)).g_class as *mut GObjectClass;
+            let cname = CString::new(name).unwrap();
+            g_object_class_find_property(class, cname.as_ptr()) != ptr::null_mut()
+        }
+    }
+
     pub unsafe fn signal_connect<T>(&mut self, signal: &str, callback: GCallback, data: &mut T)
         where Self:Sized{
         let csignal = CString::new(signal).unwrap();
         g_signal_connect_data(self.gst_object() as *mut c_void, csignal.as_ptr(), callback, mem::transmute(data), None, 0);
     }
 
+    /// Connects `callback` to the signal `signal`, which may include a
+    /// detail as in "notify::caps". The callback receives the arguments
+    /// of the signal, starting with the object emitting it, and returns
+    /// the return value of the signal, if it has one, like the location of
+    /// the next fragment for the "format-location" signal of splitmuxsink.
+    /// Return values are converted to the type of the signal if possible.
+    ///
+    /// The callback can be called from any thread, one call at a time.
+    /// If it emits the same signal again, the nested emission doesn't
+    /// call it and gets no return value, instead of deadlocking.
+    /// Returns the id of the handler, to be used with `disconnect`.
+    pub fn connect<F>(&mut self, signal: &str, callback: F) -> u64
+        where F: FnMut(&[Value]) -> Option<Value> + Send + 'static{
+        let callback: Box<SignalCallback> = Box::new(Mutex::new(Box::new(callback)));
+        let csignal = CString::new(signal).unwrap();
+        unsafe{
+            let data = Box::into_raw(callback) as gpointer;
+            let closure = g_closure_new_simple(mem::size_of::<GClosure>() as guint, data);
+            g_closure_set_marshal(closure, Some(signal_marshal));
+            g_closure_add_finalize_notifier(closure, data, Some(free_signal_callback));
+            g_signal_connect_closure(self.object as gpointer, csignal.as_ptr(), closure, 0) as u64
+        }
+    }
+
+    /// Disconnects a signal handler, like the ones returned by `connect`,
+    /// dropping its callback
+    pub fn disconnect(&mut self, handler_id: u64){
+        unsafe{
+            g_signal_handler_disconnect(self.object as gpointer, handler_id as gulong);
+        }
+    }
+
+    /// Emits the signal `signal` with `args`, like the action signals of
+    /// playbin, and returns its return value. The arguments are converted
+    /// to the types of the parameters of the signal if needed, like an
+    /// i32 for an unsigned parameter.
+    ///
+    /// None if the signal doesn't exist, the arguments don't match its
+    /// parameters or it doesn't return anything.
+    pub fn emit(&mut self, signal: &str, args: &[&dyn FieldValue]) -> Option<Value>{
+        let csignal = CString::new(signal).unwrap();
+        unsafe{
+            let instance_type = (*(*(self.object as *mut GTypeInstance)).g_class).g_type;
+            let mut signal_id = 0;
+            let mut detail = 0;
+            if g_signal_parse_name(csignal.as_ptr(), instance_type, &mut signal_id, &mut detail, 0) == 0{
+                return None;
+            }
+            let mut query: GSignalQuery = mem::zeroed();
+            g_signal_query(signal_id, &mut query);
+            if query.n_params as usize != args.len(){
+                return None;
+            }
+
+            let mut values: Vec<GValue> = vec![mem::zeroed(); args.len() + 1];
+            g_value_init(&mut values[0], instance_type);
+            g_value_set_object(&mut values[0], self.object as gpointer);
+            let mut converted = true;
+            for (i, arg) in args.iter().enumerate(){
+                let mut value: GValue = mem::zeroed();
+                arg.to_gvalue(&mut value);
+                let param_type = *query.param_types.offset(i as isize) & !G_SIGNAL_TYPE_STATIC_SCOPE;
+                g_value_init(&mut values[i + 1], param_type);
+                if g_value_transform(&value, &mut values[i + 1]) == 0{
+                    converted = false;
+                }
+                g_value_unset(&mut value);
+            }
+
+            let return_type = query.return_type & !G_SIGNAL_TYPE_STATIC_SCOPE;
+            let mut ret = None;
+            if converted{
+                if return_type == G_TYPE_NONE{
+                    g_signal_emitv(values.as_ptr(), signal_id, detail, ptr::null_mut());
+                }else{
+                    let mut return_value: GValue = mem::zeroed();
+                    g_value_init(&mut return_value, return_type);
+                    g_signal_emitv(values.as_ptr(), signal_id, detail, &mut return_value);
+                    ret = Some(Value::new_from_gvalue(&return_value));
+                    g_value_unset(&mut return_value);
+                }
+            }
+            for value in values.iter_mut(){
+                if value.g_type != 0{
+                    g_value_unset(value);
+                }
+            }
+            ret
+        }
+    }
+
     pub unsafe fn gst_object(&self) -> *const GstObject{
         self.object
     }
@@ -183,9 +292,55 @@ impl Object{
     }
 }
 
+type SignalCallback = Mutex<Box<dyn FnMut(&[Value]) -> Option<Value> + Send>>;
+
+thread_local!{
+    // callbacks running in this thread, to skip them when their signal is
+    // emitted again from inside them instead of locking them again
+    static RUNNING: RefCell<Vec<usize>> = RefCell::new(vec![]);
+}
+
+extern "C" fn signal_marshal(closure: *mut GClosure, return_value: *mut GValue, n_param_values: guint,
+                             param_values: *const GValue, _invocation_hint: gpointer, _marshal_data: gpointer){
+    unsafe{
+        let callback = &*((*closure).data as *const SignalCallback);
+        let id = callback as *const SignalCallback as usize;
+        if RUNNING.with(|running| running.borrow().contains(&id)){
+            return;
+        }
+        let args: Vec<Value> = (0..n_param_values as isize)
+            .map(|i| Value::new_from_gvalue(&*param_values.offset(i)))
+            .collect();
+        // the first parameter is the object that emitted the signal
+        let src = if n_param_values > 0{
+            g_value_get_object(param_values) as *mut GstObject
+        }else{
+            ptr::null_mut()
+        };
+        RUNNING.with(|running| running.borrow_mut().push(id));
+        let ret = unwind::guard(src, None, || (&mut *callback.lock().unwrap_or_else(|err| err.into_inner()))(&args));
+        RUNNING.with(|running| running.borrow_mut().retain(|&running| running != id));
+        if let Some(ret) = ret{
+            if return_value != ptr::null_mut() && (*return_value).g_type != 0{
+                g_value_transform(ret.gvalue(), return_value);
+            }
+        }
+    }
+}
+
+extern "C" fn free_signal_callback(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut SignalCallback));
+    }
+}
+
 impl Reference for Object{
     fn reference(&self) -> Object{
-        unsafe{ gst_object_ref(self.object as *mut c_void) };
+        unsafe{
+            gst_object_ref(self.object as *mut c_void);
+            #[cfg(feature = "leak-tracking")]
+            tracking::track(self.object as *const c_void, TrackedKind::Object);
+        }
         Object{ object: self.object }
     }
 }
@@ -193,6 +348,8 @@ impl Reference for Object{
 impl ::Transfer<GstObject> for Object{
     unsafe fn transfer(self) -> *mut GstObject{
         let object = self.object;
+        #[cfg(feature = "leak-tracking")]
+        tracking::untrack(object as *const c_void);
         mem::forget(self);
         object
     }
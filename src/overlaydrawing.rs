@@ -0,0 +1,98 @@
+use ffi::*;
+use buffer::Buffer;
+use videooverlaycomposition::{VideoOverlayComposition, VideoOverlayRectangle};
+
+use std::ptr;
+
+/// Bridge between 2D drawing libraries and video overlays.
+///
+/// Every call to `draw` allocates a transparent canvas of width x height
+/// pixels and passes it to a closure as `(data, width, height, stride)`.
+/// The pixels are 32 bits premultiplied ARGB in native endianness, which
+/// is the same layout used by cairo's `ImageSurface` in `Format::ARgb32`
+/// and raqote's `DrawTarget`, so their data can be copied as is.
+///
+/// The result is returned as a `VideoOverlayComposition` that can be
+/// attached to the video buffers, usually from a pad probe or an appsrc,
+/// and which will be blended by the sink or an overlay element.
+pub struct OverlayCanvas{
+    width: u32,
+    height: u32,
+    render_x: i32,
+    render_y: i32,
+    render_width: u32,
+    render_height: u32,
+}
+
+impl OverlayCanvas{
+    /// Creates a canvas that will be rendered at 0,0 at its original size
+    pub fn new(width: u32, height: u32) -> OverlayCanvas{
+        OverlayCanvas{
+            width: width,
+            height: height,
+            render_x: 0,
+            render_y: 0,
+            render_width: width,
+            render_height: height,
+        }
+    }
+
+    pub fn width(&self) -> u32{
+        self.width
+    }
+
+    pub fn height(&self) -> u32{
+        self.height
+    }
+
+    /// Bytes per row of the canvas data
+    pub fn stride(&self) -> u32{
+        self.width * 4
+    }
+
+    /// Sets the position and size of the video frame where the canvas will
+    /// be rendered, scaling it if it's different than the canvas size.
+    pub fn set_render_rectangle(&mut self, x: i32, y: i32, w: u32, h: u32){
+        self.render_x = x;
+        self.render_y = y;
+        self.render_width = w;
+        self.render_height = h;
+    }
+
+    /// Calls `f` with a cleared canvas and returns the drawing as an
+    /// overlay composition
+    pub fn draw<F: FnOnce(&mut [u8], u32, u32, u32)>(&self, f: F) -> Option<VideoOverlayComposition>{
+        let size = (self.stride() * self.height) as u64;
+        let mut pixels = match unsafe{ Buffer::new(gst_buffer_new_allocate(ptr::null_mut(), size, ptr::null_mut())) }{
+            Some(pixels) => pixels,
+            None => return None
+        };
+
+        let (width, height, stride) = (self.width, self.height, self.stride());
+        {
+            let mut mapping = match pixels.map_write(){
+                Some(mapping) => mapping,
+                None => return None
+            };
+            for c in mapping.iter_mut(){
+                *c = 0;
+            }
+            f(&mut mapping, width, height, stride);
+        }
+
+        let rectangle = VideoOverlayRectangle::new_raw(pixels, self.width, self.height,
+                                                       self.render_x, self.render_y,
+                                                       self.render_width, self.render_height,
+                                                       GST_VIDEO_OVERLAY_FORMAT_FLAG_PREMULTIPLIED_ALPHA);
+        rectangle.and_then(|rectangle| VideoOverlayComposition::new(&rectangle))
+    }
+
+    /// Draws using `f` and attaches the result to `buffer` which has
+    /// to be writable
+    pub fn draw_on<F: FnOnce(&mut [u8], u32, u32, u32)>(&self, buffer: &mut Buffer, f: F) -> bool{
+        match self.draw(f){
+            Some(composition) => buffer.add_video_overlay_composition_meta(&composition).is_some(),
+            None => false
+        }
+    }
+}
@@ -1,12 +1,18 @@
 use ffi::*;
 use caps::Caps;
+use query::Query;
+use structure::FromFieldValue;
 use reference::Reference;
 use object::Object;
+use unwind;
 
 use std::ptr;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 
+unsafe impl Sync for Pad {}
+unsafe impl Send for Pad {}
+
 pub struct Pad{
     pad: Object
 }
@@ -48,7 +54,70 @@ impl Pad{
     pub fn query_caps(&self, filter: Option<Caps>) -> Option<Caps>{
         unsafe{
             let caps = gst_pad_query_caps(self.gst_pad() as *mut GstPad, filter.map(|mut caps| caps.gst_caps_mut()).unwrap_or(ptr::null_mut()));
-            Caps::new(caps)
+            Caps::new_from_gst_caps(caps)
+        }
+    }
+
+    /// Drops every buffer flowing through this pad until the next
+    /// keyframe, asking upstream for one as soon as a delta unit is
+    /// dropped. Header buffers are let through. Used when attaching
+    /// a recording to a live encoded stream so the file starts decodable.
+    ///
+    /// The probe removes itself once the keyframe arrives, the returned id
+    /// can be used to cancel it earlier with `remove_probe`.
+    pub fn wait_for_keyframe(&mut self) -> u64{
+        unsafe{
+            let requested = Box::new(false);
+            gst_pad_add_probe(self.gst_pad_mut(), GST_PAD_PROBE_TYPE_BUFFER,
+                              Some(keyframe_probe), Box::into_raw(requested) as gpointer,
+                              Some(free_keyframe_probe_data)) as u64
+        }
+    }
+
+    pub fn remove_probe(&mut self, id: u64){
+        unsafe{
+            gst_pad_remove_probe(self.gst_pad_mut(), id as gulong);
+        }
+    }
+
+    /// Performs a query on the pad, answered by its element or forwarded
+    /// to the peers. Returns true if it was answered
+    pub fn query(&mut self, query: &mut Query) -> bool{
+        unsafe{
+            gst_pad_query(self.gst_pad_mut(), query.gst_query_mut()) == 1
+        }
+    }
+
+    /// Converts `src_value` from `src_format` to `dest_format` with the
+    /// information of the stream going through the pad, like frames to
+    /// time for a video stream. None if it can't be converted
+    pub fn query_convert(&self, src_format: GstFormat, src_value: i64, dest_format: GstFormat) -> Option<i64>{
+        unsafe{
+            let mut dest_value = 0;
+            if gst_pad_query_convert(self.gst_pad() as *mut GstPad, src_format, src_value, dest_format, &mut dest_value) == 1{
+                Some(dest_value)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Converts with the peer of the pad, like `query_convert`
+    pub fn peer_query_convert(&self, src_format: GstFormat, src_value: i64, dest_format: GstFormat) -> Option<i64>{
+        unsafe{
+            let mut dest_value = 0;
+            if gst_pad_peer_query_convert(self.gst_pad() as *mut GstPad, src_format, src_value, dest_format, &mut dest_value) == 1{
+                Some(dest_value)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Performs a query on the peer of the pad, false if it's not linked
+    pub fn peer_query(&mut self, query: &mut Query) -> bool{
+        unsafe{
+            gst_pad_peer_query(self.gst_pad_mut(), query.gst_query_mut()) == 1
         }
     }
 
@@ -61,12 +130,54 @@ impl Pad{
     }
 }
 
+extern "C" fn keyframe_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        unwind::guard(pad as *mut GstObject, GST_PAD_PROBE_OK, || {
+            let buffer = (*info).data as *mut GstBuffer;
+            let flags = (*buffer).mini_object.flags;
+            if flags & GST_BUFFER_FLAG_HEADER != 0{
+                GST_PAD_PROBE_OK
+            }else if flags & GST_BUFFER_FLAG_DELTA_UNIT != 0{
+                let requested = data as *mut bool;
+                if !*requested{
+                    *requested = true;
+                    let event = gst_video_event_new_upstream_force_key_unit(GST_CLOCK_TIME_NONE, 1, 0);
+                    if (*pad).direction == GST_PAD_SINK{
+                        gst_pad_push_event(pad, event);
+                    }else{
+                        gst_pad_send_event(pad, event);
+                    }
+                }
+                GST_PAD_PROBE_DROP
+            }else{
+                GST_PAD_PROBE_REMOVE
+            }
+        })
+    }
+}
+
+extern "C" fn free_keyframe_probe_data(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut bool));
+    }
+}
+
 impl ::Transfer<GstPad> for Pad{
     unsafe fn transfer(self) -> *mut GstPad{
         self.pad.transfer() as *mut GstPad
     }
 }
 
+impl FromFieldValue for Pad{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<Pad>{
+        if g_type_check_value_holds(gvalue as *const GValue as *mut GValue, gst_pad_get_type()) != 0{
+            Pad::new(g_value_dup_object(gvalue) as *mut GstPad)
+        }else{
+            None
+        }
+    }
+}
+
 impl Reference for Pad{
     fn reference(&self) -> Pad{
         Pad{ pad: self.pad.reference() }
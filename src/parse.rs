@@ -0,0 +1,120 @@
+use ffi::*;
+use util::*;
+use element::Element;
+use error::{Error, Result};
+
+/// Collects what went wrong while parsing a pipeline description with
+/// `parse_launch_full`, like the elements that don't exist because the
+/// plugin that provides them isn't installed.
+pub struct ParseContext{
+    context: *mut GstParseContext,
+}
+
+unsafe impl Send for ParseContext {}
+
+impl ParseContext{
+    pub fn new() -> ParseContext{
+        unsafe{
+            ParseContext{ context: gst_parse_context_new() }
+        }
+    }
+
+    /// Names of the elements of the description that couldn't be created,
+    /// like "x264enc", to tell the user which plugin to install
+    pub fn missing_elements(&self) -> Vec<String>{
+        let mut missing_elements = vec![];
+        unsafe{
+            let missing = gst_parse_context_get_missing_elements(self.context);
+            if missing != ptr::null_mut(){
+                let mut element = missing;
+                while *element != ptr::null_mut(){
+                    missing_elements.push(from_c_str!(mem::transmute(*element)).to_string());
+                    element = element.offset(1);
+                }
+                g_strfreev(missing);
+            }
+        }
+        missing_elements
+    }
+
+    pub unsafe fn gst_parse_context(&self) -> *const GstParseContext{
+        self.context
+    }
+
+    pub unsafe fn gst_parse_context_mut(&mut self) -> *mut GstParseContext{
+        self.context
+    }
+}
+
+impl Default for ParseContext{
+    fn default() -> ParseContext{
+        ParseContext::new()
+    }
+}
+
+impl Drop for ParseContext{
+    fn drop(&mut self){
+        unsafe{
+            gst_parse_context_free(self.context);
+        }
+    }
+}
+
+/// Creates the element, usually a pipeline, described by `description`
+/// in the gst-launch-1.0 syntax, like "videotestsrc ! x264enc ! fakesink".
+///
+/// If it can't be built the error is an `Error::Parse` with the details
+/// of the parser and, when it failed because of missing plugins, the
+/// names of the elements that couldn't be found, which are also kept in
+/// `context` if one is passed. Without GST_PARSE_FLAG_FATAL_ERRORS in
+/// `flags` descriptions with missing elements still return the rest of
+/// the pipeline, and the missing elements are only reported by `context`.
+/// GST_PARSE_FLAG_NO_SINGLE_ELEMENT_BINS returns descriptions of a single
+/// element as that element instead of a bin containing it.
+pub fn parse_launch_full(description: &str, context: Option<&mut ParseContext>, flags: GstParseFlags) -> Result<Element>{
+    let cdescription = CString::new(description).unwrap();
+    parse(context, |context, error| unsafe{
+        gst_parse_launch_full(cdescription.as_ptr(), context, flags, error)
+    })
+}
+
+/// Creates a bin with the elements described by `description`, like
+/// "videoconvert ! videobalance saturation=0 ! videoflip method=clockwise",
+/// to insert user configurable chains of filters in pipelines built
+/// programmatically.
+///
+/// With `ghost_unlinked_pads` the first unlinked sink pad and the first
+/// unlinked src pad of the chain are exposed as ghost pads of the bin, so
+/// it can be linked like any other element. `context` and `flags` work as
+/// in `parse_launch_full`.
+pub fn parse_bin_from_description_full(description: &str, ghost_unlinked_pads: bool, context: Option<&mut ParseContext>, flags: GstParseFlags) -> Result<Element>{
+    let cdescription = CString::new(description).unwrap();
+    parse(context, |context, error| unsafe{
+        gst_parse_bin_from_description_full(cdescription.as_ptr(), ghost_unlinked_pads as gboolean, context, flags, error)
+    })
+}
+
+fn parse<F>(context: Option<&mut ParseContext>, f: F) -> Result<Element>
+    where F: FnOnce(*mut GstParseContext, *mut *mut GError) -> *mut GstElement{
+    let mut own_context = None;
+    let context = match context{
+        Some(context) => context,
+        None => own_context.get_or_insert_with(ParseContext::new)
+    };
+    let mut error = ptr::null_mut::<GError>();
+    unsafe{
+        let element = f(context.gst_parse_context_mut(), &mut error);
+        if element != ptr::null_mut(){
+            // errors that didn't stop the parser, like missing elements
+            // without GST_PARSE_FLAG_FATAL_ERRORS
+            if error != ptr::null_mut(){
+                g_error_free(error);
+            }
+            gst_object_ref_sink(element as gpointer);
+            Element::new_from_gst_element(element).ok_or_else(|| Error::new(0,0,"Couldn't create the element"))
+        }else{
+            let error = Error::new_from_g_error(error);
+            Err(Error::Parse{ code: error.code(), message: error.message(), missing_elements: context.missing_elements() })
+        }
+    }
+}
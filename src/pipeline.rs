@@ -3,10 +3,20 @@ use bin::Bin;
 use bus::Bus;
 use error::Error;
 use error::Result;
+use event::Event;
 use util::*;
 use reference::Reference;
+use element::Element;
+use pad::Pad;
+use message::Message;
+use cancellable::CancellationToken;
+use unwind;
+use parse;
+use Transfer;
 
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 
 /** A GstPipeline is a special GstBin used as the toplevel container for the filter graph. The GstPipeline will manage the selection and distribution of a global GstClock as well as provide a GstBus to the application.
@@ -33,37 +43,28 @@ unsafe impl Send for Pipeline {}
 
 impl Pipeline{
     /// Create a new pipeline with the given name.
-    pub fn new(name: &str) -> Option<Pipeline>{
+    pub fn new(name: &str) -> Result<Pipeline>{
         let cname = CString::new(name).unwrap();
         unsafe{
             let pipeline = gst_pipeline_new(cname.as_ptr());
             if pipeline != ptr::null_mut(){
 		        gst_object_ref_sink(mem::transmute(pipeline));
-	            match Bin::new_from_gst_bin(pipeline as *mut GstBin){
-	                Some(bin) => Some(Pipeline{ pipeline: bin }),
-	                None => None
-	            }
+	            Ok(Pipeline{ pipeline: Bin::new_from_gst_bin(pipeline as *mut GstBin).unwrap() })
 	        }else{
-	            None
+	            Err(Error::ElementCreation("pipeline".to_string()))
 	        }
         }
     }
 
-    /// Creates a new pipeline based on the command-line syntax
+    /// Creates a new pipeline based on the command-line syntax.
+    ///
+    /// If it can't be built the error is an `Error::Parse` with the
+    /// details of the parser and, when it failed because of missing
+    /// plugins, the names of the elements that couldn't be found.
     pub fn new_from_str(string: &str) -> Result<Pipeline>{
-        let mut error = ptr::null_mut::<GError>();
-        let cstring = CString::new(string).unwrap();
+        let element = parse::parse_launch_full(string, None, GST_PARSE_FLAG_FATAL_ERRORS)?;
         unsafe{
-            let pipeline = gst_parse_launch (mem::transmute(cstring.as_ptr()), &mut error);
-            if error == ptr::null_mut(){
-	            gst_object_ref_sink(mem::transmute(pipeline));
-				match Bin::new_from_gst_bin(pipeline as *mut GstBin){
-					Some(bin) => Ok(Pipeline{ pipeline: bin }),
-					None => Err(Error::new(0,0,"Couldn't create bin"))
-				}
-			}else{
-				Err(Error::new_from_g_error(error))
-			}
+            Ok(Pipeline{ pipeline: Bin::new_from_gst_bin(element.transfer() as *mut GstBin).unwrap() })
         }
     }
 
@@ -102,6 +103,158 @@ impl Pipeline{
         }
     }
 
+    /// Stops the pipeline the right way for recordings: sends EOS, waits
+    /// up to `timeout` nanoseconds for it to reach the sinks so muxers
+    /// can write their headers and indexes, and then sets the pipeline to
+    /// NULL. The pipeline is set to NULL even if waiting fails.
+    ///
+    /// A paused pipeline, like a paused recording, is set to PLAYING first
+    /// so the EOS can flow through it. Pipelines in NULL or READY have no
+    /// data to finalize and are only set to NULL.
+    ///
+    /// Returns the error posted on the bus if one arrives before the EOS,
+    /// or an error if the timeout expires. Other messages on the bus
+    /// while waiting are dropped, so no other thread should be reading the
+    /// bus at the same time.
+    pub fn finish(&mut self, timeout: GstClockTime) -> Result<()>{
+        let (state, _, _) = self.get_state(0);
+        let ret = match state{
+            GST_STATE_PLAYING => self.wait_eos(timeout),
+            GST_STATE_PAUSED => {
+                // the EOS waits in the sinks of a paused pipeline
+                if self.set_state(GST_STATE_PLAYING) == GST_STATE_CHANGE_FAILURE{
+                    Err(Error::new(0,0,"Couldn't set the pipeline to PLAYING to finish it"))
+                }else{
+                    self.wait_eos(timeout)
+                }
+            }
+            // stopped pipelines have nothing to finalize
+            _ => Ok(())
+        };
+        self.set_state(GST_STATE_NULL);
+        ret
+    }
+
+    /// Plays the pipeline until it reaches the end of the stream, posts
+    /// an error or `token` is cancelled, and then sets it to NULL. Returns
+    /// the error posted on the bus if any, cancelling isn't an error.
+    /// Other messages on the bus are dropped, so no other thread should
+    /// be reading the bus at the same time.
+    pub fn run_until_eos(&mut self, token: &CancellationToken) -> Result<()>{
+        let mut bus = match self.bus(){
+            Some(bus) => bus,
+            None => return Err(Error::new(0,0,"Pipeline has no bus"))
+        };
+        if self.set_state(GST_STATE_PLAYING) == GST_STATE_CHANGE_FAILURE{
+            self.set_state(GST_STATE_NULL);
+            return Err(Error::new(0,0,"Couldn't start pipeline"));
+        }
+        let ret = match bus.timed_pop_filtered_cancellable(GST_CLOCK_TIME_NONE, GST_MESSAGE_EOS | GST_MESSAGE_ERROR, token){
+            Some(Message::ErrorParsed{ref error, ..}) => Err(error.clone()),
+            _ => Ok(())
+        };
+        self.set_state(GST_STATE_NULL);
+        ret
+    }
+
+    fn wait_eos(&mut self, timeout: GstClockTime) -> Result<()>{
+        unsafe{
+            let mut bus = match self.bus(){
+                Some(bus) => bus,
+                None => return Err(Error::new(0,0,"Pipeline has no bus"))
+            };
+            if !self.send_event(Event::new_eos()){
+                return Err(Error::new(0,0,"Couldn't send EOS"));
+            }
+            let msg = gst_bus_timed_pop_filtered(bus.gst_bus_mut(), timeout, GST_MESSAGE_EOS | GST_MESSAGE_ERROR);
+            if msg == ptr::null_mut(){
+                return Err(Error::new(0,0,"Timeout waiting for EOS"));
+            }
+            let ret = if (*msg)._type == GST_MESSAGE_ERROR{
+                Err(Error::new_from_error_message(msg))
+            }else{
+                Ok(())
+            };
+            gst_mini_object_unref(msg as *mut GstMiniObject);
+            ret
+        }
+    }
+
+    /// Replaces `old`, an element of this pipeline with a "sink" and a
+    /// "src" pad linked to other elements, like a video effect, with
+    /// `new`, which takes its place in the pipeline and is linked to the
+    /// same elements, while the pipeline keeps playing.
+    ///
+    /// In a playing pipeline the data upstream of `old` is blocked, an EOS
+    /// is sent through `old` to drain the data it still holds and, once the
+    /// EOS reaches its src pad, `old` is stopped and removed from the
+    /// pipeline and `new` linked and synced to the state of the pipeline
+    /// before unblocking the data. This happens in the streaming thread
+    /// after this function returns, so `old` has to run in the streaming
+    /// thread of its upstream element, like most filters do, as opposed to
+    /// queues. Errors at that point are posted on the bus. In a stopped or
+    /// paused pipeline the elements are swapped immediately.
+    pub fn replace_element(&mut self, old: &mut Element, new: Element) -> Result<()>{
+        let mut sink = old.static_pad("sink").ok_or_else(|| Error::new(0,0,"The element has no sink pad"))?;
+        let mut src = old.static_pad("src").ok_or_else(|| Error::new(0,0,"The element has no src pad"))?;
+        let (mut upstream, downstream) = unsafe{(
+            Pad::new(gst_pad_get_peer(sink.gst_pad_mut())).ok_or_else(|| Error::new(0,0,"The element sink pad is not linked"))?,
+            Pad::new(gst_pad_get_peer(src.gst_pad_mut())).ok_or_else(|| Error::new(0,0,"The element src pad is not linked"))?
+        )};
+        if !self.add(new.reference()){
+            return Err(Error::new(0,0,"Couldn't add the new element to the pipeline"));
+        }
+
+        let swap = Arc::new(Swap{
+            pipeline: self.pipeline.reference(),
+            old: old.reference(),
+            new: new,
+            upstream: upstream.reference(),
+            downstream: downstream,
+            started: AtomicBool::new(false),
+            block_probe: AtomicUsize::new(0),
+        });
+        let (state, _, _) = self.get_state(0);
+        if let GST_STATE_PLAYING = state{
+            unsafe{
+                gst_pad_add_probe(upstream.gst_pad_mut(), GST_PAD_PROBE_TYPE_BLOCK_DOWNSTREAM,
+                                  Some(swap_block_probe), Box::into_raw(Box::new(swap)) as gpointer,
+                                  Some(free_swap));
+            }
+            Ok(())
+        }else{
+            swap.run()
+        }
+    }
+
+    /// Offsets the audio relative to the video by `ms` milliseconds to
+    /// correct lip-sync at runtime, positive values delay the audio. Works
+    /// with playbin or pipelines containing a playsink, which have the
+    /// "av-offset" property, and returns false for other pipelines, where
+    /// `Element::set_ts_offset` can be used on the audio or video sink
+    pub fn set_av_offset_ms(&mut self, ms: i64) -> bool{
+        match self.av_offset_element(){
+            Some(mut element) => {
+                element.set("av-offset", ms * 1_000_000);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// The offset of the audio relative to the video in milliseconds, see
+    /// `set_av_offset_ms`. None if the pipeline has no "av-offset"
+    pub fn av_offset_ms(&self) -> Option<i64>{
+        self.av_offset_element().map(|element| element.get::<i64>("av-offset") / 1_000_000)
+    }
+
+    fn av_offset_element(&self) -> Option<Element>{
+        if self.has_property("av-offset"){
+            return Some(Element::from(self.pipeline.reference()));
+        }
+        self.iter_recurse().filter_map(|element| element.ok()).find(|element| element.has_property("av-offset"))
+    }
+
     /// Returns a const raw pointer to the internal GstElement
     pub unsafe fn gst_pipeline(&self) -> *const GstPipeline{
         self.pipeline.gst_element() as *const GstPipeline
@@ -113,6 +266,111 @@ impl Pipeline{
     }
 }
 
+// an element being replaced by replace_element
+struct Swap{
+    pipeline: Bin,
+    old: Element,
+    new: Element,
+    // peers of the sink and src pads of old
+    upstream: Pad,
+    downstream: Pad,
+    // the block probe is called again for every event while blocked
+    started: AtomicBool,
+    block_probe: AtomicUsize,
+}
+
+impl Swap{
+    fn run(&self) -> Result<()>{
+        let mut pipeline = self.pipeline.reference();
+        let mut old = self.old.reference();
+        let mut new = self.new.reference();
+        let mut upstream = self.upstream.reference();
+        let mut downstream = self.downstream.reference();
+        old.set_state(GST_STATE_NULL);
+        pipeline.remove(&old);
+        unsafe{
+            let new_sink = Pad::new(gst_element_get_compatible_pad(new.gst_element_mut(), upstream.gst_pad_mut(), ptr::null_mut()));
+            let new_src = Pad::new(gst_element_get_compatible_pad(new.gst_element_mut(), downstream.gst_pad_mut(), ptr::null_mut()));
+            match (new_sink, new_src){
+                (Some(mut new_sink), Some(mut new_src)) => {
+                    if upstream.link(&mut new_sink).is_err() || new_src.link(&mut downstream).is_err(){
+                        return Err(Error::new(0,0,"Couldn't link the new element"));
+                    }
+                }
+                _ => return Err(Error::new(0,0,"The new element has no compatible pads"))
+            }
+        }
+        if !new.sync_state_with_parent(){
+            return Err(Error::new(0,0,"Couldn't sync the new element state"));
+        }
+        Ok(())
+    }
+
+    fn post_error(&self, err: Error){
+        let mut new = self.new.reference();
+        unsafe{
+            let msg = CString::new(err.message()).unwrap_or(CString::new("").unwrap());
+            let error = g_error_new_literal(gst_core_error_quark(), GST_CORE_ERROR_PAD as gint, msg.as_ptr());
+            let message = gst_message_new_error(new.gst_element_mut() as *mut GstObject, error, ptr::null());
+            g_error_free(error);
+            gst_element_post_message(new.gst_element_mut(), message);
+        }
+    }
+}
+
+// upstream of the old element is blocked, drain it with an EOS
+extern "C" fn swap_block_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        unwind::guard(pad as *mut GstObject, GST_PAD_PROBE_REMOVE, || {
+            let swap = &*(data as *const Arc<Swap>);
+            if swap.started.swap(true, Ordering::SeqCst){
+                return GST_PAD_PROBE_OK;
+            }
+            swap.block_probe.store((*info).id as usize, Ordering::SeqCst);
+            let mut old = swap.old.reference();
+            match (old.static_pad("src"), old.static_pad("sink")){
+                (Some(mut src), Some(mut sink)) => {
+                    gst_pad_add_probe(src.gst_pad_mut(), GST_PAD_PROBE_TYPE_BLOCK | GST_PAD_PROBE_TYPE_EVENT_DOWNSTREAM,
+                                      Some(swap_eos_probe), Box::into_raw(Box::new(swap.clone())) as gpointer,
+                                      Some(free_swap));
+                    gst_pad_send_event(sink.gst_pad_mut(), gst_event_new_eos());
+                    GST_PAD_PROBE_OK
+                }
+                _ => {
+                    swap.post_error(Error::new(0,0,"The replaced element lost its pads"));
+                    GST_PAD_PROBE_REMOVE
+                }
+            }
+        })
+    }
+}
+
+// the old element is drained, swap it and unblock upstream
+extern "C" fn swap_eos_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        let event = (*info).data as *mut GstEvent;
+        if (*event)._type != GST_EVENT_EOS{
+            return GST_PAD_PROBE_PASS;
+        }
+        let swap = (*(data as *const Arc<Swap>)).clone();
+        gst_pad_remove_probe(pad, (*info).id);
+        // upstream is unblocked even if swapping panics
+        let swapped = unwind::guard(pad as *mut GstObject, Ok(()), || swap.run());
+        if let Err(err) = swapped{
+            swap.post_error(err);
+        }
+        let mut upstream = swap.upstream.reference();
+        gst_pad_remove_probe(upstream.gst_pad_mut(), swap.block_probe.load(Ordering::SeqCst) as gulong);
+        GST_PAD_PROBE_DROP
+    }
+}
+
+extern "C" fn free_swap(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Arc<Swap>));
+    }
+}
+
 impl ::Transfer for Pipeline{
     unsafe fn transfer(self) -> *mut GstElement{
         self.pipeline.transfer()
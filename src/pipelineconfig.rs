@@ -0,0 +1,572 @@
+use ffi::*;
+use util::*;
+use bin::Bin;
+use element::Element;
+use elementfactory::ElementFactory;
+use error::{Error, Result};
+use pipeline::Pipeline;
+use structure::FieldValue;
+
+#[cfg(feature = "serde_json")]
+use serde_json;
+#[cfg(feature = "toml")]
+use toml;
+
+/// Value of a property in a `PipelineConfig`. Enums, flags, caps and any
+/// other type GStreamer can serialize are stored as strings, like "ball"
+/// for the pattern of videotestsrc
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigValue{
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+/// An element of a `PipelineConfig` and the properties that differ from
+/// their defaults
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementConfig{
+    pub name: String,
+    /// Name of the factory, like "videotestsrc"
+    pub factory: String,
+    pub properties: Vec<(String, ConfigValue)>,
+}
+
+/// A link between two pads of the elements of a `PipelineConfig`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkConfig{
+    pub src: String,
+    pub src_pad: String,
+    pub sink: String,
+    pub sink_pad: String,
+}
+
+/// A declarative description of a pipeline, its elements, their
+/// properties and the links between them, that can be taken from a
+/// running pipeline with `from_bin` and built again with `build`.
+///
+/// With the serde_json and toml features it can be written to and read
+/// from JSON and TOML documents, so services can keep their pipelines in
+/// configuration files users can edit:
+///
+/// ```toml
+/// name = "camera"
+///
+/// [[elements]]
+/// name = "src"
+/// factory = "v4l2src"
+/// properties = { device = "/dev/video0" }
+///
+/// [[elements]]
+/// name = "sink"
+/// factory = "autovideosink"
+///
+/// [[links]]
+/// src = "src"
+/// src_pad = "src"
+/// sink = "sink"
+/// sink_pad = "sink"
+/// ```
+///
+/// Nested bins are stored as the element they were created from, so bins
+/// like decodebin that create their own children are rebuilt, but the
+/// children of bins built by the application are not.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PipelineConfig{
+    pub name: String,
+    pub elements: Vec<ElementConfig>,
+    pub links: Vec<LinkConfig>,
+}
+
+impl PipelineConfig{
+    /// Describes the elements of `bin`, in the order they were added, and
+    /// the links between them
+    pub fn from_bin(bin: &Bin) -> PipelineConfig{
+        let mut elements: Vec<Element> = bin.iter().filter_map(|element| element.ok()).collect();
+        elements.reverse();
+        let mut links = vec![];
+        for element in elements.iter(){
+            unsafe{
+                links.extend(element_links(bin, element));
+            }
+        }
+        PipelineConfig{
+            name: bin.name(),
+            elements: elements.iter().map(|element| unsafe{ element_config(element) }).collect(),
+            links: links,
+        }
+    }
+
+    /// Creates a pipeline with the elements and links of the description.
+    ///
+    /// Links from pads that don't exist yet, like the sometimes pads of
+    /// demuxers, are made when the pads are added. Fails with
+    /// `Error::Parse` if some element, property or link is not valid,
+    /// listing the elements that couldn't be created
+    pub fn build(&self) -> Result<Pipeline>{
+        let mut pipeline = Pipeline::new(&self.name)?;
+        self.build_into(&mut pipeline)?;
+        Ok(pipeline)
+    }
+
+    /// Adds the elements of the description to `bin` and links them
+    pub fn build_into(&self, bin: &mut Bin) -> Result<()>{
+        let mut missing_elements = vec![];
+        let mut elements = vec![];
+        for config in self.elements.iter(){
+            match Element::new(&config.factory, &config.name){
+                Ok(mut element) => {
+                    for &(ref name, ref value) in config.properties.iter(){
+                        unsafe{
+                            set_property(&mut element, name, value)?;
+                        }
+                    }
+                    elements.push(element);
+                }
+                Err(Error::MissingElement(factory)) => missing_elements.push(factory),
+                Err(err) => return Err(err)
+            }
+        }
+        if !missing_elements.is_empty(){
+            return Err(parse_error(GST_PARSE_ERROR_NO_SUCH_ELEMENT,
+                                   &format!("no element {}", missing_elements.join(", ")), missing_elements));
+        }
+
+        for element in elements{
+            if !bin.add(element){
+                return Err(parse_error(GST_PARSE_ERROR_LINK, "couldn't add an element, duplicated name?", vec![]));
+            }
+        }
+        for link in self.links.iter(){
+            link_config(bin, link)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde_json")]
+    pub fn to_json(&self) -> String{
+        use serde_json::{Map, Value};
+        let elements = self.elements.iter().map(|element|{
+            let mut properties = Map::new();
+            for &(ref name, ref value) in element.properties.iter(){
+                properties.insert(name.clone(), json_from_config(value));
+            }
+            json!({
+                "name": element.name,
+                "factory": element.factory,
+                "properties": properties,
+            })
+        }).collect::<Vec<Value>>();
+        let links = self.links.iter().map(|link| json!({
+            "src": link.src,
+            "src_pad": link.src_pad,
+            "sink": link.sink,
+            "sink_pad": link.sink_pad,
+        })).collect::<Vec<Value>>();
+        let document = json!({
+            "name": self.name,
+            "elements": elements,
+            "links": links,
+        });
+        serde_json::to_string_pretty(&document).unwrap()
+    }
+
+    #[cfg(feature = "serde_json")]
+    pub fn from_json(document: &str) -> Result<PipelineConfig>{
+        use serde_json::Value;
+        let document: Value = serde_json::from_str(document)
+            .map_err(|err| parse_error(GST_PARSE_ERROR_SYNTAX, &err.to_string(), vec![]))?;
+        let field = |value: &Value, field: &str| -> Result<String>{
+            value.get(field).and_then(|value| value.as_str()).map(|value| value.to_string())
+                .ok_or(parse_error(GST_PARSE_ERROR_SYNTAX, &format!("missing {}", field), vec![]))
+        };
+
+        let mut config = PipelineConfig::default();
+        config.name = document.get("name").and_then(|name| name.as_str()).unwrap_or("").to_string();
+        let empty = vec![];
+        for element in document.get("elements").and_then(|elements| elements.as_array()).unwrap_or(&empty){
+            let mut properties = vec![];
+            if let Some(map) = element.get("properties").and_then(|properties| properties.as_object()){
+                for (name, value) in map.iter(){
+                    let value = config_from_json(value)
+                        .ok_or(parse_error(GST_PARSE_ERROR_COULD_NOT_SET_PROPERTY,
+                                           &format!("unsupported value for {}", name), vec![]))?;
+                    properties.push((name.clone(), value));
+                }
+            }
+            config.elements.push(ElementConfig{
+                name: field(element, "name")?,
+                factory: field(element, "factory")?,
+                properties: properties,
+            });
+        }
+        for link in document.get("links").and_then(|links| links.as_array()).unwrap_or(&empty){
+            config.links.push(LinkConfig{
+                src: field(link, "src")?,
+                src_pad: field(link, "src_pad")?,
+                sink: field(link, "sink")?,
+                sink_pad: field(link, "sink_pad")?,
+            });
+        }
+        Ok(config)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> String{
+        use toml::{Table, Value};
+        let elements = self.elements.iter().map(|element|{
+            let mut properties = Table::new();
+            for &(ref name, ref value) in element.properties.iter(){
+                let value = match *value{
+                    ConfigValue::Bool(value) => Value::Boolean(value),
+                    ConfigValue::Int(value) => Value::Integer(value),
+                    ConfigValue::Float(value) => Value::Float(value),
+                    ConfigValue::String(ref value) => Value::String(value.clone()),
+                };
+                properties.insert(name.clone(), value);
+            }
+            let mut table = Table::new();
+            table.insert("name".to_string(), Value::String(element.name.clone()));
+            table.insert("factory".to_string(), Value::String(element.factory.clone()));
+            table.insert("properties".to_string(), Value::Table(properties));
+            Value::Table(table)
+        }).collect();
+        let links = self.links.iter().map(|link|{
+            let mut table = Table::new();
+            table.insert("src".to_string(), Value::String(link.src.clone()));
+            table.insert("src_pad".to_string(), Value::String(link.src_pad.clone()));
+            table.insert("sink".to_string(), Value::String(link.sink.clone()));
+            table.insert("sink_pad".to_string(), Value::String(link.sink_pad.clone()));
+            Value::Table(table)
+        }).collect();
+        let mut document = Table::new();
+        document.insert("name".to_string(), Value::String(self.name.clone()));
+        document.insert("elements".to_string(), Value::Array(elements));
+        document.insert("links".to_string(), Value::Array(links));
+        toml::to_string(&document).unwrap()
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(document: &str) -> Result<PipelineConfig>{
+        use toml::{Table, Value};
+        let document: Table = document.parse()
+            .map_err(|err: toml::de::Error| parse_error(GST_PARSE_ERROR_SYNTAX, err.message(), vec![]))?;
+        let field = |value: &Value, field: &str| -> Result<String>{
+            value.get(field).and_then(|value| value.as_str()).map(|value| value.to_string())
+                .ok_or(parse_error(GST_PARSE_ERROR_SYNTAX, &format!("missing {}", field), vec![]))
+        };
+
+        let mut config = PipelineConfig::default();
+        config.name = document.get("name").and_then(|name| name.as_str()).unwrap_or("").to_string();
+        let empty = vec![];
+        for element in document.get("elements").and_then(|elements| elements.as_array()).unwrap_or(&empty){
+            let mut properties = vec![];
+            if let Some(table) = element.get("properties").and_then(|properties| properties.as_table()){
+                for (name, value) in table.iter(){
+                    let value = match *value{
+                        Value::Boolean(value) => ConfigValue::Bool(value),
+                        Value::Integer(value) => ConfigValue::Int(value),
+                        Value::Float(value) => ConfigValue::Float(value),
+                        Value::String(ref value) => ConfigValue::String(value.clone()),
+                        _ => return Err(parse_error(GST_PARSE_ERROR_COULD_NOT_SET_PROPERTY,
+                                                    &format!("unsupported value for {}", name), vec![]))
+                    };
+                    properties.push((name.clone(), value));
+                }
+            }
+            config.elements.push(ElementConfig{
+                name: field(element, "name")?,
+                factory: field(element, "factory")?,
+                properties: properties,
+            });
+        }
+        for link in document.get("links").and_then(|links| links.as_array()).unwrap_or(&empty){
+            config.links.push(LinkConfig{
+                src: field(link, "src")?,
+                src_pad: field(link, "src_pad")?,
+                sink: field(link, "sink")?,
+                sink_pad: field(link, "sink_pad")?,
+            });
+        }
+        Ok(config)
+    }
+}
+
+fn parse_error(code: u32, message: &str, missing_elements: Vec<String>) -> Error{
+    Error::Parse{ code: code as i32, message: message.to_string(), missing_elements: missing_elements }
+}
+
+#[cfg(feature = "serde_json")]
+pub fn json_from_config(value: &ConfigValue) -> serde_json::Value{
+    match *value{
+        ConfigValue::Bool(value) => serde_json::Value::from(value),
+        ConfigValue::Int(value) => serde_json::Value::from(value),
+        ConfigValue::Float(value) => serde_json::Value::from(value),
+        ConfigValue::String(ref value) => serde_json::Value::from(value.clone()),
+    }
+}
+
+#[cfg(feature = "serde_json")]
+pub fn config_from_json(value: &serde_json::Value) -> Option<ConfigValue>{
+    use serde_json::Value;
+    match *value{
+        Value::Bool(value) => Some(ConfigValue::Bool(value)),
+        Value::Number(ref number) if number.is_f64() => Some(ConfigValue::Float(number.as_f64().unwrap())),
+        Value::Number(ref number) => Some(ConfigValue::Int(number.as_i64().unwrap_or(number.as_u64().unwrap_or(0) as i64))),
+        Value::String(ref value) => Some(ConfigValue::String(value.clone())),
+        _ => None
+    }
+}
+
+unsafe fn type_from_name(type_name: &str) -> GType{
+    let ctype = CString::new(type_name).unwrap();
+    g_type_from_name(ctype.as_ptr())
+}
+
+unsafe fn is_fundamental(gtype: GType, names: &[&str]) -> bool{
+    let fundamental = g_type_fundamental(gtype);
+    names.iter().any(|name| type_from_name(name) == fundamental)
+}
+
+const INTEGER_TYPES: &'static [&'static str] = &["gchar", "guchar", "gint", "guint", "glong", "gulong", "gint64", "guint64"];
+const FLOAT_TYPES: &'static [&'static str] = &["gfloat", "gdouble"];
+
+unsafe fn element_config(element: &Element) -> ElementConfig{
+    let factory = gst_element_get_factory(element.gst_element() as *mut GstElement);
+    let factory = if factory != ptr::null_mut(){
+        ElementFactory::new(gst_object_ref(factory as gpointer) as *mut GstElementFactory)
+            .map(|factory| factory.name())
+            .unwrap_or("bin".to_string())
+    }else{
+        "bin".to_string()
+    };
+
+    let object = element.gst_element() as *mut GObject;
+    let class = (*(object as *mut GTypeInstance)).g_class as *mut GObjectClass;
+    let mut n_properties = 0;
+    let pspecs = g_object_class_list_properties(class, &mut n_properties);
+    let mut properties = vec![];
+    for i in 0..n_properties as isize{
+        let pspec = *pspecs.offset(i);
+        let flags = (*pspec).flags;
+        if flags & G_PARAM_READABLE == 0 || flags & G_PARAM_WRITABLE == 0 || flags & G_PARAM_CONSTRUCT_ONLY != 0{
+            continue;
+        }
+        let name = from_c_str!((*pspec).name).to_string();
+        if name == "name" || name == "parent"{
+            continue;
+        }
+        if let Some(value) = property_value(object, pspec){
+            properties.push((name, value));
+        }
+    }
+    g_free(pspecs as gpointer);
+
+    ElementConfig{
+        name: element.name(),
+        factory: factory,
+        properties: properties,
+    }
+}
+
+// the value of the property as a ConfigValue, None if it has the default
+// value or a type that can't be serialized, like objects
+unsafe fn property_value(object: *mut GObject, pspec: *mut GParamSpec) -> Option<ConfigValue>{
+    let mut value: GValue = mem::zeroed();
+    g_value_init(&mut value, (*pspec).value_type);
+    g_object_get_property(object, (*pspec).name, &mut value);
+    let ret = if g_param_value_defaults(pspec, &mut value) != 0{
+        None
+    }else{
+        config_value(&value)
+    };
+    g_value_unset(&mut value);
+    ret
+}
+
+/// Current value of the property `name` of `element` as a ConfigValue
+#[cfg(any(feature = "remote", feature = "mpris"))]
+pub fn get_property(element: &Element, name: &str) -> Result<ConfigValue>{
+    unsafe{
+        let object = element.gst_element() as *mut GObject;
+        let class = (*(object as *mut GTypeInstance)).g_class as *mut GObjectClass;
+        let cname = CString::new(name).unwrap();
+        let pspec = g_object_class_find_property(class, cname.as_ptr());
+        if pspec == ptr::null_mut(){
+            return Err(parse_error(GST_PARSE_ERROR_NO_SUCH_PROPERTY,
+                                   &format!("no property {} in element {}", name, element.name()), vec![]));
+        }
+        let mut value: GValue = mem::zeroed();
+        g_value_init(&mut value, (*pspec).value_type);
+        g_object_get_property(object, cname.as_ptr(), &mut value);
+        let ret = config_value(&value);
+        g_value_unset(&mut value);
+        ret.ok_or(parse_error(GST_PARSE_ERROR_COULD_NOT_SET_PROPERTY,
+                              &format!("can't serialize property {} of element {}", name, element.name()), vec![]))
+    }
+}
+
+unsafe fn config_value(value: &GValue) -> Option<ConfigValue>{
+    let value_type = value.g_type;
+    if is_fundamental(value_type, &["gboolean"]){
+        Some(ConfigValue::Bool(g_value_get_boolean(value) != 0))
+    }else if is_fundamental(value_type, INTEGER_TYPES){
+        let mut int: GValue = mem::zeroed();
+        g_value_init(&mut int, type_from_name("gint64"));
+        g_value_transform(value, &mut int);
+        let ret = Some(ConfigValue::Int(g_value_get_int64(&int)));
+        g_value_unset(&mut int);
+        ret
+    }else if is_fundamental(value_type, FLOAT_TYPES){
+        let mut float: GValue = mem::zeroed();
+        g_value_init(&mut float, type_from_name("gdouble"));
+        g_value_transform(value, &mut float);
+        let ret = Some(ConfigValue::Float(g_value_get_double(&float)));
+        g_value_unset(&mut float);
+        ret
+    }else if is_fundamental(value_type, &["gchararray"]){
+        let string = g_value_get_string(value);
+        if string != ptr::null(){
+            Some(ConfigValue::String(from_c_str!(string).to_string()))
+        }else{
+            None
+        }
+    }else{
+        let string = gst_value_serialize(value);
+        if string != ptr::null_mut(){
+            let ret = Some(ConfigValue::String(from_c_str!(string).to_string()));
+            g_free(string as gpointer);
+            ret
+        }else{
+            None
+        }
+    }
+}
+
+/// Sets the property `name` of `element` from a ConfigValue, converting
+/// between numeric types and deserializing strings for other types
+pub unsafe fn set_property(element: &mut Element, name: &str, value: &ConfigValue) -> Result<()>{
+    let object = element.gst_element_mut() as *mut GObject;
+    let class = (*(object as *mut GTypeInstance)).g_class as *mut GObjectClass;
+    let cname = CString::new(name).unwrap();
+    let pspec = g_object_class_find_property(class, cname.as_ptr());
+    if pspec == ptr::null_mut(){
+        return Err(parse_error(GST_PARSE_ERROR_NO_SUCH_PROPERTY,
+                               &format!("no property {} in element {}", name, element.name()), vec![]));
+    }
+
+    let value_type = (*pspec).value_type;
+    let mut target: GValue = mem::zeroed();
+    g_value_init(&mut target, value_type);
+    let converted = match *value{
+        ConfigValue::String(ref string) => {
+            let cstring = CString::new(string.as_str()).unwrap();
+            if is_fundamental(value_type, &["gchararray"]){
+                g_value_set_string(&mut target, cstring.as_ptr());
+                true
+            }else{
+                gst_value_deserialize(&mut target, cstring.as_ptr()) != 0
+            }
+        }
+        ref other => {
+            let mut source: GValue = mem::zeroed();
+            match *other{
+                ConfigValue::Bool(value) => value.to_gvalue(&mut source),
+                ConfigValue::Int(value) => value.to_gvalue(&mut source),
+                ConfigValue::Float(value) => value.to_gvalue(&mut source),
+                ConfigValue::String(_) => unreachable!(),
+            }
+            let converted = g_value_transform(&source, &mut target) != 0;
+            g_value_unset(&mut source);
+            converted
+        }
+    };
+    if converted{
+        g_object_set_property(object, cname.as_ptr(), &target);
+    }
+    g_value_unset(&mut target);
+    if converted{
+        Ok(())
+    }else{
+        Err(parse_error(GST_PARSE_ERROR_COULD_NOT_SET_PROPERTY,
+                        &format!("invalid value {:?} for property {} of element {}", value, name, element.name()), vec![]))
+    }
+}
+
+unsafe fn object_name(object: *mut GstObject) -> String{
+    let name = gst_object_get_name(object);
+    let ret = from_c_str!(name).to_string();
+    g_free(name as gpointer);
+    ret
+}
+
+// links from the src pads of element to other children of bin
+unsafe fn element_links(bin: &Bin, element: &Element) -> Vec<LinkConfig>{
+    let mut links = vec![];
+    let gst_element = element.gst_element() as *mut GstElement;
+    let iter = gst_element_iterate_src_pads(gst_element);
+    let mut item: GValue = mem::zeroed();
+    while gst_iterator_next(iter, &mut item) == GST_ITERATOR_OK{
+        let pad = g_value_get_object(&item) as *mut GstPad;
+        let peer = gst_pad_get_peer(pad);
+        if peer != ptr::null_mut(){
+            let peer_element = gst_pad_get_parent_element(peer);
+            if peer_element != ptr::null_mut(){
+                let parent = gst_object_get_parent(peer_element as *mut GstObject);
+                if parent == bin.gst_bin() as *mut GstObject{
+                    links.push(LinkConfig{
+                        src: element.name(),
+                        src_pad: object_name(pad as *mut GstObject),
+                        sink: object_name(peer_element as *mut GstObject),
+                        sink_pad: object_name(peer as *mut GstObject),
+                    });
+                }
+                if parent != ptr::null_mut(){
+                    gst_object_unref(parent as gpointer);
+                }
+                gst_object_unref(peer_element as gpointer);
+            }
+            gst_object_unref(peer as gpointer);
+        }
+        g_value_reset(&mut item);
+    }
+    g_value_unset(&mut item);
+    gst_iterator_free(iter);
+    links
+}
+
+fn link_config(bin: &mut Bin, link: &LinkConfig) -> Result<()>{
+    let link_error = || parse_error(GST_PARSE_ERROR_LINK,
+                                    &format!("couldn't link {}:{} to {}:{}", link.src, link.src_pad, link.sink, link.sink_pad),
+                                    vec![]);
+    let (mut src, sink) = match (bin.get_by_name(&link.src), bin.get_by_name(&link.sink)){
+        (Some(src), Some(sink)) => (src, sink),
+        _ => return Err(link_error())
+    };
+    if link_pads(&mut src, &link.src_pad, &sink, &link.sink_pad){
+        return Ok(());
+    }
+
+    // sometimes pads are linked once they appear
+    if src.static_pad(&link.src_pad).is_some(){
+        return Err(link_error());
+    }
+    let src_pad = link.src_pad.clone();
+    let sink_pad = link.sink_pad.clone();
+    src.connect_pad_added(move |src, pad|{
+        if pad.name() == src_pad{
+            link_pads(src, &src_pad, &sink, &sink_pad);
+        }
+    });
+    Ok(())
+}
+
+fn link_pads(src: &mut Element, src_pad: &str, sink: &Element, sink_pad: &str) -> bool{
+    let csrc_pad = CString::new(src_pad).unwrap();
+    let csink_pad = CString::new(sink_pad).unwrap();
+    unsafe{
+        gst_element_link_pads(src.gst_element_mut(), csrc_pad.as_ptr(),
+                              sink.gst_element() as *mut GstElement, csink_pad.as_ptr()) != 0
+    }
+}
@@ -0,0 +1,268 @@
+use ffi::*;
+use error::{Error, Result};
+use message::Message;
+use pipeline::Pipeline;
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+type BuildPipeline = dyn Fn() -> Result<Pipeline> + Send + Sync;
+
+// how long building or returning a pipeline waits for it to preroll
+// before leaving it in READY instead
+const PREROLL_TIMEOUT: GstClockTime = 5 * 1_000_000_000;
+
+// an idle pipeline and the element name and uri it's prerolled with
+type IdlePipeline = (Pipeline, Option<(String, String)>);
+
+struct PoolInner{
+    build: Box<BuildPipeline>,
+    idle: Mutex<Vec<IdlePipeline>>,
+    max_idle: usize,
+}
+
+impl PoolInner{
+    fn new_pipeline(&self) -> Result<Pipeline>{
+        let mut pipeline = (self.build)()?;
+        if park(&mut pipeline).is_none(){
+            pipeline.set_state(GST_STATE_NULL);
+            return Err(Error::new(0,0,"Couldn't set the new pipeline to READY"));
+        }
+        Ok(pipeline)
+    }
+}
+
+// prerolls the pipeline in PAUSED so the next job starts producing data
+// right away. Pipelines that can't preroll yet, like a uridecodebin
+// without uri, wait in READY for load_uri instead. Returns whether it
+// prerolled, None if it can't even go to READY
+fn park(pipeline: &mut Pipeline) -> Option<bool>{
+    let prerolled = pipeline.set_state(GST_STATE_PAUSED) != GST_STATE_CHANGE_FAILURE &&
+        is_prerolled(pipeline.get_state(PREROLL_TIMEOUT).2);
+    let parked = if prerolled{
+        Some(true)
+    }else if pipeline.set_state(GST_STATE_READY) != GST_STATE_CHANGE_FAILURE{
+        Some(false)
+    }else{
+        None
+    };
+    // the errors of a failed preroll would confuse the next job
+    drain_bus(pipeline);
+    parked
+}
+
+fn is_prerolled(ret: GstStateChangeReturn) -> bool{
+    ret == GST_STATE_CHANGE_SUCCESS || ret == GST_STATE_CHANGE_NO_PREROLL
+}
+
+// pops the messages left on the bus, returns whether there was an error
+fn drain_bus(pipeline: &Pipeline) -> bool{
+    let mut errored = false;
+    if let Some(mut bus) = pipeline.bus(){
+        while let Some(message) = bus.pop(){
+            errored |= message.ty() == GST_MESSAGE_ERROR;
+        }
+    }
+    errored
+}
+
+/// Keeps pipelines built for short repeated jobs, like thumbnailers or
+/// format converters on a server, so each job doesn't pay for building
+/// the pipeline, loading the plugins and opening the devices again.
+///
+/// Idle pipelines are kept prerolled in PAUSED, rewound to the start
+/// after each job, so a job reading the same input starts with the first
+/// frame ready. Pipelines that can't preroll, like the ones whose input
+/// is set for each job with `load_uri`, or the ones that failed, are kept
+/// in READY instead. `get` hands one out, or builds a new one if none is
+/// idle, and it goes back to the pool when the `PooledPipeline` is
+/// dropped, which waits for it to preroll again. The pool can be cloned and shared between
+/// threads, clones share the same pipelines.
+#[derive(Clone)]
+pub struct PipelinePool{
+    inner: Arc<PoolInner>,
+}
+
+impl PipelinePool{
+    /// A pool of the pipelines returned by `build`, like
+    /// `|| Pipeline::new_from_str("uridecodebin name=src ! videoconvert ! appsink name=sink")`,
+    /// keeping at most `max_idle` of them when they're not in use
+    pub fn new<F>(max_idle: usize, build: F) -> PipelinePool
+        where F: Fn() -> Result<Pipeline> + Send + Sync + 'static{
+        PipelinePool{
+            inner: Arc::new(PoolInner{
+                build: Box::new(build),
+                idle: Mutex::new(vec![]),
+                max_idle: max_idle,
+            })
+        }
+    }
+
+    /// Builds pipelines until `count` of them, at most `max_idle`, are
+    /// idle, so the first jobs don't wait for them
+    pub fn warm_up(&self, count: usize) -> Result<()>{
+        let count = count.min(self.inner.max_idle);
+        while self.idle() < count{
+            let pipeline = self.inner.new_pipeline()?;
+            self.inner.idle.lock().unwrap().push((pipeline, None));
+        }
+        Ok(())
+    }
+
+    /// An idle pipeline, prerolled in PAUSED or in READY, or a new one if
+    /// none is idle
+    pub fn get(&self) -> Result<PooledPipeline>{
+        let idle = self.inner.idle.lock().unwrap().pop();
+        let (pipeline, uri) = match idle{
+            Some(idle) => idle,
+            None => (self.inner.new_pipeline()?, None)
+        };
+        Ok(PooledPipeline{
+            pipeline: Some(pipeline),
+            uri: uri,
+            pool: self.inner.clone(),
+        })
+    }
+
+    /// Number of pipelines waiting for a job
+    pub fn idle(&self) -> usize{
+        self.inner.idle.lock().unwrap().len()
+    }
+
+    /// Stops and drops the idle pipelines, the ones in use are still
+    /// returned to the pool
+    pub fn clear(&self){
+        let idle: Vec<IdlePipeline> = self.inner.idle.lock().unwrap().drain(..).collect();
+        for (mut pipeline, _) in idle{
+            pipeline.set_state(GST_STATE_NULL);
+        }
+    }
+}
+
+/// A pipeline taken from a `PipelinePool`, returned to it when dropped.
+/// Derefs to the pipeline, which is prerolled in PAUSED or in READY when
+/// handed out.
+pub struct PooledPipeline{
+    pipeline: Option<Pipeline>,
+    // element name and uri set by load_uri and still prerolled
+    uri: Option<(String, String)>,
+    pool: Arc<PoolInner>,
+}
+
+impl PooledPipeline{
+    /// Sets the "uri" property of the element `name`, like a uridecodebin,
+    /// and prerolls the pipeline in PAUSED waiting up to `timeout`
+    /// nanoseconds, so it's ready to produce the first frame. Does nothing
+    /// if the pipeline is still prerolled with that uri from a previous
+    /// job. Returns the error posted on the bus if the uri can't be played
+    pub fn load_uri(&mut self, name: &str, uri: &str, timeout: GstClockTime) -> Result<()>{
+        let loaded = match self.uri{
+            Some((ref loaded_name, ref loaded_uri)) => loaded_name == name && loaded_uri == uri,
+            None => false
+        };
+        if loaded{
+            if let (GST_STATE_PAUSED, GST_STATE_VOID_PENDING, GST_STATE_CHANGE_SUCCESS) = self.get_state(0){
+                return Ok(());
+            }
+        }
+        self.uri = None;
+        // sources can only change their uri in READY or NULL
+        if self.set_state(GST_STATE_READY) == GST_STATE_CHANGE_FAILURE{
+            return Err(Error::new(0,0,"Couldn't set the pipeline to READY"));
+        }
+        match self.get_by_name(name){
+            Some(mut element) => element.set("uri", uri),
+            None => return Err(Error::new(0,0,&format!("No element {} in the pipeline", name)))
+        }
+        self.preroll(timeout)?;
+        self.uri = Some((name.to_string(), uri.to_string()));
+        Ok(())
+    }
+
+    /// Sets the pipeline to PAUSED and waits up to `timeout` nanoseconds
+    /// for it to preroll. Returns the error posted on the bus if it fails
+    pub fn preroll(&mut self, timeout: GstClockTime) -> Result<()>{
+        self.set_state(GST_STATE_PAUSED);
+        match self.get_state(timeout).2{
+            GST_STATE_CHANGE_FAILURE => {
+                let error = self.bus().and_then(|mut bus| match bus.timed_pop_filtered(0, GST_MESSAGE_ERROR){
+                    Some(Message::ErrorParsed{ref error, ..}) => Some(error.clone()),
+                    _ => None
+                });
+                Err(error.unwrap_or_else(|| Error::new(0,0,"Couldn't preroll the pipeline")))
+            }
+            GST_STATE_CHANGE_ASYNC => Err(Error::new(0,0,"Timeout prerolling the pipeline")),
+            _ => Ok(())
+        }
+    }
+
+    /// Goes back to the start of the stream with a flushing seek, to run
+    /// the same input again without going through READY
+    pub fn rewind(&mut self) -> bool{
+        self.seek_simple(GST_FORMAT_TIME, GST_SEEK_FLAG_FLUSH | GST_SEEK_FLAG_KEY_UNIT, 0)
+    }
+
+    /// Drops the pipeline instead of returning it to the pool, for
+    /// pipelines left in a state they can't be reused from
+    pub fn discard(mut self){
+        if let Some(mut pipeline) = self.pipeline.take(){
+            pipeline.set_state(GST_STATE_NULL);
+        }
+    }
+}
+
+impl Drop for PooledPipeline{
+    fn drop(&mut self){
+        let mut pipeline = match self.pipeline.take(){
+            Some(pipeline) => pipeline,
+            None => return
+        };
+        // no need to reset a pipeline the full pool won't keep
+        if self.pool.idle.lock().unwrap().len() >= self.pool.max_idle{
+            pipeline.set_state(GST_STATE_NULL);
+            return;
+        }
+        // pipelines that ran fine are rewound with a flushing seek, which
+        // keeps them prerolled. The others go through READY, which resets
+        // the errors of the job, and preroll again if they can
+        let errored = drain_bus(&pipeline);
+        let running = match pipeline.get_state(0).0{
+            GST_STATE_PAUSED | GST_STATE_PLAYING => true,
+            _ => false
+        };
+        let rewound = !errored && running &&
+            pipeline.set_state(GST_STATE_PAUSED) != GST_STATE_CHANGE_FAILURE &&
+            pipeline.seek_simple(GST_FORMAT_TIME, GST_SEEK_FLAG_FLUSH | GST_SEEK_FLAG_KEY_UNIT, 0) &&
+            is_prerolled(pipeline.get_state(PREROLL_TIMEOUT).2);
+        let (reusable, uri) = if rewound{
+            drain_bus(&pipeline);
+            (true, self.uri.take())
+        }else{
+            pipeline.set_state(GST_STATE_READY);
+            match park(&mut pipeline){
+                Some(prerolled) => (true, if prerolled { self.uri.take() }else{ None }),
+                None => (false, None)
+            }
+        };
+        let mut idle = self.pool.idle.lock().unwrap();
+        if reusable && idle.len() < self.pool.max_idle{
+            idle.push((pipeline, uri));
+        }else{
+            drop(idle);
+            pipeline.set_state(GST_STATE_NULL);
+        }
+    }
+}
+
+impl Deref for PooledPipeline{
+    type Target = Pipeline;
+    fn deref(&self) -> &Pipeline{
+        self.pipeline.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledPipeline{
+    fn deref_mut(&mut self) -> &mut Pipeline{
+        self.pipeline.as_mut().unwrap()
+    }
+}
@@ -2,6 +2,10 @@ use ffi::*;
 
 use pipeline::Pipeline;
 use element::Element;
+use error::Result;
+use videoflip::VideoFlip;
+use caps::Caps;
+use sample::Sample;
 use ::Transfer;
 use reference::Reference;
 
@@ -15,16 +19,10 @@ pub struct PlayBin{
 }
 
 impl PlayBin{
-    pub fn new(name: &str) -> Option<PlayBin>{
-        let pipeline = Element::new("playbin",name);
-        match pipeline{
-            Some(p) => {
-                match unsafe{ Pipeline::new_from_gst_pipeline( p.transfer() as *mut GstPipeline) }{
-                    Some(p) => Some(PlayBin{ playbin: p }),
-                    None => None
-                }
-            }
-            None => None
+    pub fn new(name: &str) -> Result<PlayBin>{
+        let pipeline = Element::new("playbin",name)?;
+        unsafe{
+            Ok(PlayBin{ playbin: Pipeline::new_from_gst_pipeline(pipeline.transfer() as *mut GstPipeline).unwrap() })
         }
     }
 
@@ -44,6 +42,22 @@ impl PlayBin{
         self.set("video-sink", video_sink);
     }
 
+    /// Element applied to the decoded video before the sink
+    pub fn set_video_filter(&mut self, video_filter: &Element){
+        self.set("video-filter", video_filter);
+    }
+
+    /// Installs a videoflip as video filter and returns it, which rotates
+    /// the video according to the image-orientation tag, for example for
+    /// videos recorded with a phone.
+    pub fn auto_rotate(&mut self) -> Result<VideoFlip>{
+        VideoFlip::new("").map(|mut videoflip| {
+            videoflip.auto_rotate();
+            self.set_video_filter(&videoflip);
+            videoflip
+        })
+    }
+
     pub fn set_vis_plugin(&mut self, vis_plugin: &Element){
         self.set("vis-plugin", vis_plugin);
     }
@@ -123,6 +137,12 @@ impl PlayBin{
     pub fn set_flags(&mut self, flags: i32){
         self.set("flags", flags);
     }
+
+    /// The frame currently shown converted to `caps`, like
+    /// "image/png" or raw RGB video, for thumbnails or screenshots
+    pub fn convert_sample(&mut self, caps: &Caps) -> Option<Sample>{
+        self.emit("convert-sample", &[caps]).and_then(|value| value.get::<Sample>())
+    }
 }
 
 impl ::Transfer for PlayBin{
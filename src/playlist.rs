@@ -0,0 +1,226 @@
+use ffi::*;
+use playbin::PlayBin;
+use error::Result;
+use message::Message;
+use unwind;
+
+use std::ptr;
+use std::mem;
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+use std::ops::{Deref, DerefMut};
+
+struct State{
+    uris: Vec<String>,
+    // item being played
+    current: Option<usize>,
+    // item whose uri was set on playbin but hasn't started yet
+    queued: Option<usize>,
+}
+
+type SharedState = Arc<Mutex<State>>;
+
+/// Plays a list of uris one after another without gaps, using the
+/// "about-to-finish" signal of playbin to queue the next item while the
+/// current one is still playing.
+///
+/// The started and finished callbacks are called from `handle_message`,
+/// which has to be called with the messages of the playlist bus. An item
+/// that is skipped is also reported as finished.
+pub struct Playlist{
+    playbin: PlayBin,
+    state: SharedState,
+    on_started: Option<Box<dyn FnMut(usize, &str)>>,
+    on_finished: Option<Box<dyn FnMut(usize, &str)>>,
+}
+
+impl Playlist{
+    pub fn new(name: &str, uris: Vec<String>) -> Result<Playlist>{
+        let mut playbin = PlayBin::new(name)?;
+        let state = Arc::new(Mutex::new(State{ uris: uris, current: None, queued: None }));
+        unsafe{
+            let signal = CString::new("about-to-finish").unwrap();
+            let data = Box::new(state.clone());
+            g_signal_connect_data(playbin.gst_element_mut() as gpointer, signal.as_ptr(),
+                                  mem::transmute(on_about_to_finish as extern "C" fn(*mut GstElement, gpointer)),
+                                  Box::into_raw(data) as gpointer, Some(free_state), 0);
+        }
+        Ok(Playlist{
+            playbin: playbin,
+            state: state,
+            on_started: None,
+            on_finished: None,
+        })
+    }
+
+    /// Called with the index and uri of each item when it starts playing
+    pub fn on_item_started<F: FnMut(usize, &str) + 'static>(&mut self, f: F){
+        self.on_started = Some(Box::new(f));
+    }
+
+    /// Called with the index and uri of each item when it finishes playing
+    /// or is skipped
+    pub fn on_item_finished<F: FnMut(usize, &str) + 'static>(&mut self, f: F){
+        self.on_finished = Some(Box::new(f));
+    }
+
+    /// Adds an item at the end of the list, it will be played without a
+    /// gap if the list is already playing its last item
+    pub fn push(&mut self, uri: &str){
+        self.state.lock().unwrap().uris.push(uri.to_string());
+    }
+
+    pub fn items(&self) -> Vec<String>{
+        self.state.lock().unwrap().uris.clone()
+    }
+
+    pub fn len(&self) -> usize{
+        self.state.lock().unwrap().uris.len()
+    }
+
+    /// Index of the item being played
+    pub fn current(&self) -> Option<usize>{
+        self.state.lock().unwrap().current
+    }
+
+    /// Starts playing the item at `index`, interrupting the current one.
+    /// Returns false if there's no such item
+    pub fn play_item(&mut self, index: usize) -> bool{
+        let (uri, interrupted) = {
+            let mut state = self.state.lock().unwrap();
+            let uri = match state.uris.get(index){
+                Some(uri) => uri.clone(),
+                None => return false
+            };
+            let interrupted = state.current.take();
+            state.queued = Some(index);
+            (uri, interrupted)
+        };
+        self.playbin.set_state(GST_STATE_READY);
+        self.report_finished(interrupted);
+        self.playbin.set_uri(&uri);
+        self.playbin.play();
+        true
+    }
+
+    /// Plays the list from the first item
+    pub fn start(&mut self) -> bool{
+        self.play_item(0)
+    }
+
+    /// Skips to the next item, returns false if the current one is the last
+    pub fn next(&mut self) -> bool{
+        let next = self.current().map(|current| current + 1).unwrap_or(0);
+        self.play_item(next)
+    }
+
+    /// Goes back to the previous item, returns false if the current one is
+    /// the first
+    pub fn previous(&mut self) -> bool{
+        match self.current(){
+            Some(current) if current > 0 => self.play_item(current - 1),
+            _ => false
+        }
+    }
+
+    /// Stops playback, the current item is reported as finished
+    pub fn stop(&mut self){
+        let interrupted = {
+            let mut state = self.state.lock().unwrap();
+            state.queued = None;
+            state.current.take()
+        };
+        self.playbin.set_state(GST_STATE_NULL);
+        self.report_finished(interrupted);
+    }
+
+    /// Updates the playlist with a message from its bus, calling the
+    /// started and finished callbacks when the playing item changes.
+    /// Returns true if the message was a change of item or the end of the
+    /// list
+    pub fn handle_message(&mut self, message: &Message) -> bool{
+        unsafe{
+            if message.src() != self.playbin.gst_element() as *mut GstObject{
+                return false;
+            }
+        }
+        let (finished, started) = match *message{
+            Message::StreamStart(_) => {
+                let mut state = self.state.lock().unwrap();
+                match state.queued.take(){
+                    Some(queued) => (mem::replace(&mut state.current, Some(queued)), Some(queued)),
+                    None => return false
+                }
+            }
+            Message::Eos(_) => (self.state.lock().unwrap().current.take(), None),
+            _ => return false
+        };
+        self.report_finished(finished);
+        if let Some(started) = started{
+            let uri = self.state.lock().unwrap().uris[started].clone();
+            if let Some(ref mut on_started) = self.on_started{
+                on_started(started, &uri);
+            }
+        }
+        true
+    }
+
+    fn report_finished(&mut self, index: Option<usize>){
+        if let Some(index) = index{
+            let uri = self.state.lock().unwrap().uris[index].clone();
+            if let Some(ref mut on_finished) = self.on_finished{
+                on_finished(index, &uri);
+            }
+        }
+    }
+}
+
+extern "C" fn on_about_to_finish(playbin: *mut GstElement, data: gpointer){
+    unsafe{
+        unwind::guard(playbin as *mut GstObject, (), || {
+            let mut state = (*(data as *const SharedState)).lock().unwrap();
+            let next = match state.queued.or(state.current){
+                Some(playing) => playing + 1,
+                None => return
+            };
+            let uri = match state.uris.get(next){
+                Some(uri) => CString::new(&uri[..]).unwrap(),
+                None => return
+            };
+            let prop = CString::new("uri").unwrap();
+            g_object_set(playbin as gpointer, prop.as_ptr(), uri.as_ptr(), ptr::null::<gchar>());
+            state.queued = Some(next);
+        })
+    }
+}
+
+extern "C" fn free_state(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut SharedState));
+    }
+}
+
+impl AsRef<PlayBin> for Playlist{
+    fn as_ref(&self) -> &PlayBin{
+        &self.playbin
+    }
+}
+
+impl AsMut<PlayBin> for Playlist{
+    fn as_mut(&mut self) -> &mut PlayBin{
+        &mut self.playbin
+    }
+}
+
+impl Deref for Playlist{
+    type Target = PlayBin;
+    fn deref(&self) -> &PlayBin{
+        &self.playbin
+    }
+}
+
+impl DerefMut for Playlist{
+    fn deref_mut(&mut self) -> &mut PlayBin{
+        &mut self.playbin
+    }
+}
@@ -0,0 +1,336 @@
+use ffi::*;
+use element::Element;
+use error::{Error, Result};
+use pipeline::Pipeline;
+use sample::Sample;
+use videoframe::VideoFrame;
+
+use std::f64;
+use std::ptr;
+
+/// PSNR reported for identical frames, whose error is 0
+pub const MAX_PSNR: f64 = 100.0;
+
+// SSIM is computed on windows of this size, overlapping by half
+const SSIM_WINDOW: usize = 8;
+const SSIM_STEP: usize = 4;
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// Quality of one frame of the degraded stream compared to the reference
+#[derive(Clone, Copy, Debug)]
+pub struct FrameQuality{
+    /// Timestamp of the reference frame, relative to its first frame
+    pub pts: GstClockTime,
+    /// Peak signal to noise ratio of the luma in dB, at most `MAX_PSNR`
+    pub psnr: f64,
+    /// Structural similarity of the luma, 1 for identical frames
+    pub ssim: f64,
+}
+
+/// Aggregate quality of the whole stream
+#[derive(Clone, Copy, Debug)]
+pub struct QualityReport{
+    /// Number of frames compared
+    pub frames: usize,
+    /// Frames of either stream without a frame with the same timestamp on
+    /// the other, which weren't compared
+    pub unmatched: usize,
+    pub psnr_mean: f64,
+    pub psnr_min: f64,
+    pub ssim_mean: f64,
+    pub ssim_min: f64,
+}
+
+/// PSNR of the luma of `degraded` compared to `reference`, in dB. None if
+/// the frames have different sizes or aren't 8 bit
+pub fn psnr(reference: &VideoFrame, degraded: &VideoFrame) -> Option<f64>{
+    let (reference, degraded, width, height) = luma_planes(reference, degraded)?;
+    Some(plane_psnr(reference, degraded, width, height))
+}
+
+// planes are (data, stride)
+fn plane_psnr(reference: (&[u8], usize), degraded: (&[u8], usize), width: usize, height: usize) -> f64{
+    let mut squared_error = 0u64;
+    for y in 0..height{
+        let row_ref = &reference.0[y * reference.1 .. y * reference.1 + width];
+        let row_deg = &degraded.0[y * degraded.1 .. y * degraded.1 + width];
+        for (a, b) in row_ref.iter().zip(row_deg){
+            let diff = *a as i64 - *b as i64;
+            squared_error += (diff * diff) as u64;
+        }
+    }
+    if squared_error == 0{
+        return MAX_PSNR;
+    }
+    let mse = squared_error as f64 / (width * height) as f64;
+    (10.0 * (255.0 * 255.0 / mse).log10()).min(MAX_PSNR)
+}
+
+/// Mean SSIM of the luma of `degraded` compared to `reference`, over 8x8
+/// windows. None if the frames have different sizes or aren't 8 bit
+pub fn ssim(reference: &VideoFrame, degraded: &VideoFrame) -> Option<f64>{
+    let (reference, degraded, width, height) = luma_planes(reference, degraded)?;
+    plane_ssim(reference, degraded, width, height)
+}
+
+fn plane_ssim(reference: (&[u8], usize), degraded: (&[u8], usize), width: usize, height: usize) -> Option<f64>{
+    if width < SSIM_WINDOW || height < SSIM_WINDOW{
+        return None;
+    }
+    let mut total = 0.0;
+    let mut windows = 0;
+    let mut y = 0;
+    while y + SSIM_WINDOW <= height{
+        let mut x = 0;
+        while x + SSIM_WINDOW <= width{
+            total += ssim_window(reference, degraded, x, y);
+            windows += 1;
+            x += SSIM_STEP;
+        }
+        y += SSIM_STEP;
+    }
+    Some(total / windows as f64)
+}
+
+fn ssim_window(reference: (&[u8], usize), degraded: (&[u8], usize), x: usize, y: usize) -> f64{
+    let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) = (0u64, 0u64, 0u64, 0u64, 0u64);
+    for row in y..y + SSIM_WINDOW{
+        for column in x..x + SSIM_WINDOW{
+            let a = reference.0[row * reference.1 + column] as u64;
+            let b = degraded.0[row * degraded.1 + column] as u64;
+            sum_a += a;
+            sum_b += b;
+            sum_aa += a * a;
+            sum_bb += b * b;
+            sum_ab += a * b;
+        }
+    }
+    let n = (SSIM_WINDOW * SSIM_WINDOW) as f64;
+    let (mean_a, mean_b) = (sum_a as f64 / n, sum_b as f64 / n);
+    let var_a = sum_aa as f64 / n - mean_a * mean_a;
+    let var_b = sum_bb as f64 / n - mean_b * mean_b;
+    let covar = sum_ab as f64 / n - mean_a * mean_b;
+    ((2.0 * mean_a * mean_b + SSIM_C1) * (2.0 * covar + SSIM_C2)) /
+        ((mean_a * mean_a + mean_b * mean_b + SSIM_C1) * (var_a + var_b + SSIM_C2))
+}
+
+// first component of both frames, with their strides, and the common size
+fn luma_planes<'a>(reference: &'a VideoFrame, degraded: &'a VideoFrame) -> Option<((&'a [u8], usize), (&'a [u8], usize), usize, usize)>{
+    let luma_ref = reference.component(0)?;
+    let luma_deg = degraded.component(0)?;
+    if luma_ref.width() != luma_deg.width() || luma_ref.height() != luma_deg.height() ||
+        luma_ref.depth() != 8 || luma_deg.depth() != 8{
+        return None;
+    }
+    Some(((luma_ref.data::<u8>(), luma_ref.stride() as usize),
+          (luma_deg.data::<u8>(), luma_deg.stride() as usize),
+          luma_ref.width() as usize, luma_ref.height() as usize))
+}
+
+// decodes one of the compared uris to luma frames
+struct Decoder{
+    pipeline: Pipeline,
+    appsink: Element,
+    first_pts: Option<GstClockTime>,
+}
+
+impl Decoder{
+    fn new(uri: &str) -> Result<Decoder>{
+        let mut pipeline = Pipeline::new_from_str(&format!(
+            "uridecodebin uri=\"{}\" ! videoconvert ! video/x-raw,format=GRAY8 ! appsink name=sink sync=false max-buffers=4", uri))?;
+        let appsink = match pipeline.get_by_name("sink"){
+            Some(appsink) => appsink,
+            None => return Err(Error::new(0,0,"Couldn't find appsink"))
+        };
+        if pipeline.play() == GST_STATE_CHANGE_FAILURE{
+            return Err(Error::new(0,0,&format!("Couldn't play {}", uri)));
+        }
+        Ok(Decoder{ pipeline: pipeline, appsink: appsink, first_pts: None })
+    }
+
+    // next frame and its timestamp relative to the first one, None at the
+    // end of the stream
+    fn next(&mut self) -> Result<Option<(GstClockTime, VideoFrame)>>{
+        loop{
+            let sample = unsafe{
+                Sample::new(gst_app_sink_pull_sample(self.appsink.gst_element_mut() as *mut GstAppSink))
+            };
+            let sample = match sample{
+                Some(sample) => sample,
+                None => return self.error().map_or(Ok(None), Err)
+            };
+            let pts = match sample.buffer(){
+                Some(buffer) => unsafe{ (*buffer.gst_buffer()).pts },
+                None => continue
+            };
+            if let Some(frame) = sample.video_frame(){
+                let first_pts = *self.first_pts.get_or_insert(pts);
+                return Ok(Some((pts.saturating_sub(first_pts), frame)));
+            }
+        }
+    }
+
+    fn error(&self) -> Option<Error>{
+        let mut bus = self.pipeline.bus()?;
+        unsafe{
+            let msg = gst_bus_pop_filtered(bus.gst_bus_mut(), GST_MESSAGE_ERROR);
+            if msg == ptr::null_mut(){
+                return None;
+            }
+            let error = Error::new_from_error_message(msg);
+            gst_mini_object_unref(msg as *mut GstMiniObject);
+            Some(error)
+        }
+    }
+}
+
+impl Drop for Decoder{
+    fn drop(&mut self){
+        self.pipeline.set_state(GST_STATE_NULL);
+    }
+}
+
+/// Decodes a reference and a degraded version of the same video and
+/// computes the PSNR and SSIM of every frame of the degraded one, calling
+/// `per_frame` with each result.
+///
+/// Frames are matched by their timestamp relative to the first frame of
+/// each stream, so both need the same frame rate and size. Only the luma is
+/// compared.
+pub fn measure_quality<F: FnMut(&FrameQuality)>(reference_uri: &str, degraded_uri: &str, mut per_frame: F) -> Result<QualityReport>{
+    let mut reference = Decoder::new(reference_uri)?;
+    let mut degraded = Decoder::new(degraded_uri)?;
+    let mut report = QualityReport{
+        frames: 0,
+        unmatched: 0,
+        psnr_mean: 0.0,
+        psnr_min: f64::INFINITY,
+        ssim_mean: 0.0,
+        ssim_min: f64::INFINITY,
+    };
+    let mut next_ref = reference.next()?;
+    let mut next_deg = degraded.next()?;
+    loop{
+        let (pts_ref, pts_deg, tolerance) = match (&next_ref, &next_deg){
+            (&Some((pts_ref, ref frame)), &Some((pts_deg, _))) => {
+                // half a frame, frame timestamps can be rounded differently
+                let duration = unsafe{ (*frame.buffer().gst_buffer()).duration };
+                let tolerance = if duration == GST_CLOCK_TIME_NONE { 1_000_000 } else { duration / 2 };
+                (pts_ref, pts_deg, tolerance)
+            }
+            (&Some(_), &None) | (&None, &Some(_)) => {
+                report.unmatched += 1;
+                if next_ref.is_some(){
+                    next_ref = reference.next()?;
+                }else{
+                    next_deg = degraded.next()?;
+                }
+                continue;
+            }
+            (&None, &None) => break
+        };
+        if pts_ref + tolerance < pts_deg{
+            report.unmatched += 1;
+            next_ref = reference.next()?;
+        }else if pts_deg + tolerance < pts_ref{
+            report.unmatched += 1;
+            next_deg = degraded.next()?;
+        }else{
+            {
+                let frame_ref = &next_ref.as_ref().unwrap().1;
+                let frame_deg = &next_deg.as_ref().unwrap().1;
+                let quality = match (psnr(frame_ref, frame_deg), ssim(frame_ref, frame_deg)){
+                    (Some(psnr), Some(ssim)) => FrameQuality{ pts: pts_ref, psnr: psnr, ssim: ssim },
+                    _ => return Err(Error::new(0,0,"The reference and degraded videos have different sizes"))
+                };
+                report.frames += 1;
+                report.psnr_mean += quality.psnr;
+                report.psnr_min = report.psnr_min.min(quality.psnr);
+                report.ssim_mean += quality.ssim;
+                report.ssim_min = report.ssim_min.min(quality.ssim);
+                per_frame(&quality);
+            }
+            next_ref = reference.next()?;
+            next_deg = degraded.next()?;
+        }
+    }
+    if report.frames == 0{
+        return Err(Error::new(0,0,"No frames to compare"));
+    }
+    report.psnr_mean /= report.frames as f64;
+    report.ssim_mean /= report.frames as f64;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{plane_psnr, plane_ssim, MAX_PSNR};
+
+    const WIDTH: usize = 32;
+    const HEIGHT: usize = 16;
+    // rows padded like the planes of video frames
+    const STRIDE: usize = 36;
+
+    fn plane<F: Fn(usize, usize) -> u8>(luma: F) -> Vec<u8>{
+        let mut plane = vec![0; STRIDE * HEIGHT];
+        for y in 0..HEIGHT{
+            for x in 0..WIDTH{
+                plane[y * STRIDE + x] = luma(x, y);
+            }
+        }
+        plane
+    }
+
+    fn gradient(x: usize, y: usize) -> u8{
+        (x * 4 + y * 3) as u8
+    }
+
+    #[test]
+    fn identical_frames(){
+        let reference = plane(gradient);
+        let mut degraded = reference.clone();
+        // the padding isn't compared
+        degraded[WIDTH] = 255;
+        assert_eq!(plane_psnr((&reference, STRIDE), (&degraded, STRIDE), WIDTH, HEIGHT), MAX_PSNR);
+        let ssim = plane_ssim((&reference, STRIDE), (&degraded, STRIDE), WIDTH, HEIGHT).unwrap();
+        assert!((ssim - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn offset_psnr(){
+        // an error of 10 on every pixel is a MSE of 100
+        let reference = plane(gradient);
+        let degraded = plane(|x, y| gradient(x, y) + 10);
+        let psnr = plane_psnr((&reference, STRIDE), (&degraded, STRIDE), WIDTH, HEIGHT);
+        assert!((psnr - 10.0 * (255.0f64 * 255.0 / 100.0).log10()).abs() < 1e-9);
+        assert!((psnr - 28.13).abs() < 0.01);
+    }
+
+    #[test]
+    fn offset_ssim(){
+        // flat frames only differ in their luminance term
+        let reference = plane(|_, _| 100);
+        let degraded = plane(|_, _| 110);
+        let ssim = plane_ssim((&reference, STRIDE), (&degraded, STRIDE), WIDTH, HEIGHT).unwrap();
+        let c1 = (0.01f64 * 255.0) * (0.01 * 255.0);
+        let expected = (2.0 * 100.0 * 110.0 + c1) / (100.0 * 100.0 + 110.0 * 110.0 + c1);
+        assert!((ssim - expected).abs() < 1e-9);
+
+        // the same offset on a textured frame keeps its structure
+        let reference = plane(gradient);
+        let degraded = plane(|x, y| gradient(x, y) + 10);
+        let ssim = plane_ssim((&reference, STRIDE), (&degraded, STRIDE), WIDTH, HEIGHT).unwrap();
+        assert!(ssim > 0.98 && ssim < 1.0);
+
+        // inverted frames are structurally opposite
+        let degraded = plane(|x, y| 255 - gradient(x, y));
+        assert!(plane_ssim((&reference, STRIDE), (&degraded, STRIDE), WIDTH, HEIGHT).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn ssim_too_small(){
+        let reference = plane(gradient);
+        assert_eq!(plane_ssim((&reference, STRIDE), (&reference, STRIDE), 7, HEIGHT), None);
+    }
+}
@@ -0,0 +1,292 @@
+use ffi::*;
+use util::*;
+
+use structure::Structure;
+use reference::Reference;
+use miniobject::MiniObject;
+
+unsafe impl Send for Query {}
+
+/// Queries ask elements and pads for information about the stream, like
+/// its duration, whether it's seekable or the latency of a live pipeline.
+/// They're created with one of the constructors, run with `Element::query`
+/// or `Pad::query` and the answer read with the parser of the same kind,
+/// which returns None for queries of other kinds.
+/// See [GstQuery](http://gstreamer.freedesktop.org/data/doc/gstreamer/head/gstreamer/html/GstQuery.html)
+#[derive(Clone)]
+pub struct Query{
+    query: MiniObject
+}
+
+impl Query{
+    pub unsafe fn new(query: *mut GstQuery) -> Option<Query>{
+        MiniObject::new_from_gst_miniobject(query as *mut GstMiniObject)
+            .map(|miniobject| Query{ query: miniobject })
+    }
+
+    /// Asks for the current position in `format`
+    pub fn new_position(format: GstFormat) -> Query{
+        unsafe{
+            Query::new(gst_query_new_position(format)).unwrap()
+        }
+    }
+
+    /// Asks for the total duration in `format`
+    pub fn new_duration(format: GstFormat) -> Query{
+        unsafe{
+            Query::new(gst_query_new_duration(format)).unwrap()
+        }
+    }
+
+    /// Asks whether the pipeline is live and its minimum and maximum
+    /// latency
+    pub fn new_latency() -> Query{
+        unsafe{
+            Query::new(gst_query_new_latency()).unwrap()
+        }
+    }
+
+    /// Asks whether seeking in `format` is possible and in which range
+    pub fn new_seeking(format: GstFormat) -> Query{
+        unsafe{
+            Query::new(gst_query_new_seeking(format)).unwrap()
+        }
+    }
+
+    /// Asks to convert `value` from `src_format` to `dest_format`, like
+    /// bytes to time
+    pub fn new_convert(src_format: GstFormat, value: i64, dest_format: GstFormat) -> Query{
+        unsafe{
+            Query::new(gst_query_new_convert(src_format, value, dest_format)).unwrap()
+        }
+    }
+
+    /// Asks for the playback rate and range of the current segment
+    pub fn new_segment(format: GstFormat) -> Query{
+        unsafe{
+            Query::new(gst_query_new_segment(format)).unwrap()
+        }
+    }
+
+    /// Asks for the buffering level of queues and network sources
+    pub fn new_buffering(format: GstFormat) -> Query{
+        unsafe{
+            Query::new(gst_query_new_buffering(format)).unwrap()
+        }
+    }
+
+    /// Asks for the uri of the source
+    pub fn new_uri() -> Query{
+        unsafe{
+            Query::new(gst_query_new_uri()).unwrap()
+        }
+    }
+
+    /// Application defined query, answered by filling the fields of
+    /// `structure`
+    pub fn new_custom(structure: Structure) -> Option<Query>{
+        unsafe{
+            Query::new(gst_query_new_custom(GST_QUERY_CUSTOM, ::Transfer::transfer(structure)))
+        }
+    }
+
+    pub fn query_type(&self) -> GstQueryType{
+        unsafe{
+            (*self.gst_query())._type
+        }
+    }
+
+    pub fn type_name(&self) -> &str{
+        unsafe{
+            from_c_str!(gst_query_type_get_name(self.query_type()))
+        }
+    }
+
+    /// The format and current position of a position query
+    pub fn position(&self) -> Option<(GstFormat, i64)>{
+        if self.query_type() != GST_QUERY_POSITION{
+            return None;
+        }
+        unsafe{
+            let mut format = GST_FORMAT_UNDEFINED;
+            let mut position = -1;
+            gst_query_parse_position(self.gst_query() as *mut GstQuery, &mut format, &mut position);
+            Some((format, position))
+        }
+    }
+
+    pub fn set_position(&mut self, format: GstFormat, position: i64){
+        unsafe{
+            gst_query_set_position(self.gst_query_mut(), format, position);
+        }
+    }
+
+    /// The format and duration of a duration query, -1 if unknown
+    pub fn duration(&self) -> Option<(GstFormat, i64)>{
+        if self.query_type() != GST_QUERY_DURATION{
+            return None;
+        }
+        unsafe{
+            let mut format = GST_FORMAT_UNDEFINED;
+            let mut duration = -1;
+            gst_query_parse_duration(self.gst_query() as *mut GstQuery, &mut format, &mut duration);
+            Some((format, duration))
+        }
+    }
+
+    pub fn set_duration(&mut self, format: GstFormat, duration: i64){
+        unsafe{
+            gst_query_set_duration(self.gst_query_mut(), format, duration);
+        }
+    }
+
+    /// Whether the pipeline is live and its minimum and maximum latency
+    /// in nanoseconds, GST_CLOCK_TIME_NONE for an unlimited maximum
+    pub fn latency(&self) -> Option<(bool, GstClockTime, GstClockTime)>{
+        if self.query_type() != GST_QUERY_LATENCY{
+            return None;
+        }
+        unsafe{
+            let mut live = 0;
+            let mut min = 0;
+            let mut max = GST_CLOCK_TIME_NONE;
+            gst_query_parse_latency(self.gst_query() as *mut GstQuery, &mut live, &mut min, &mut max);
+            Some((live != 0, min, max))
+        }
+    }
+
+    pub fn set_latency(&mut self, live: bool, min: GstClockTime, max: GstClockTime){
+        unsafe{
+            gst_query_set_latency(self.gst_query_mut(), live as gboolean, min, max);
+        }
+    }
+
+    /// The format of a seeking query, whether seeking is possible and the
+    /// range it's possible in, -1 for unknown ends
+    pub fn seeking(&self) -> Option<(GstFormat, bool, i64, i64)>{
+        if self.query_type() != GST_QUERY_SEEKING{
+            return None;
+        }
+        unsafe{
+            let mut format = GST_FORMAT_UNDEFINED;
+            let mut seekable = 0;
+            let mut start = -1;
+            let mut end = -1;
+            gst_query_parse_seeking(self.gst_query() as *mut GstQuery, &mut format, &mut seekable, &mut start, &mut end);
+            Some((format, seekable != 0, start, end))
+        }
+    }
+
+    /// Shortcut for the seekable flag of a seeking query, false for other
+    /// queries
+    pub fn is_seekable(&self) -> bool{
+        self.seeking().map(|(_, seekable, _, _)| seekable).unwrap_or(false)
+    }
+
+    pub fn set_seeking(&mut self, format: GstFormat, seekable: bool, start: i64, end: i64){
+        unsafe{
+            gst_query_set_seeking(self.gst_query_mut(), format, seekable as gboolean, start, end);
+        }
+    }
+
+    /// The source format and value and the destination format and
+    /// converted value of a convert query
+    pub fn convert(&self) -> Option<(GstFormat, i64, GstFormat, i64)>{
+        if self.query_type() != GST_QUERY_CONVERT{
+            return None;
+        }
+        unsafe{
+            let mut src_format = GST_FORMAT_UNDEFINED;
+            let mut src_value = -1;
+            let mut dest_format = GST_FORMAT_UNDEFINED;
+            let mut dest_value = -1;
+            gst_query_parse_convert(self.gst_query() as *mut GstQuery, &mut src_format, &mut src_value,
+                                    &mut dest_format, &mut dest_value);
+            Some((src_format, src_value, dest_format, dest_value))
+        }
+    }
+
+    pub fn set_convert(&mut self, src_format: GstFormat, src_value: i64, dest_format: GstFormat, dest_value: i64){
+        unsafe{
+            gst_query_set_convert(self.gst_query_mut(), src_format, src_value, dest_format, dest_value);
+        }
+    }
+
+    /// The rate, format, start and stop of a segment query
+    pub fn segment(&self) -> Option<(f64, GstFormat, i64, i64)>{
+        if self.query_type() != GST_QUERY_SEGMENT{
+            return None;
+        }
+        unsafe{
+            let mut rate = 1.0;
+            let mut format = GST_FORMAT_UNDEFINED;
+            let mut start = -1;
+            let mut stop = -1;
+            gst_query_parse_segment(self.gst_query() as *mut GstQuery, &mut rate, &mut format, &mut start, &mut stop);
+            Some((rate, format, start, stop))
+        }
+    }
+
+    /// Whether buffering is in progress and the buffering level in percent
+    pub fn buffering_percent(&self) -> Option<(bool, i32)>{
+        if self.query_type() != GST_QUERY_BUFFERING{
+            return None;
+        }
+        unsafe{
+            let mut busy = 0;
+            let mut percent = 0;
+            gst_query_parse_buffering_percent(self.gst_query() as *mut GstQuery, &mut busy, &mut percent);
+            Some((busy != 0, percent))
+        }
+    }
+
+    /// The uri of a uri query, None if it wasn't answered
+    pub fn uri(&self) -> Option<String>{
+        if self.query_type() != GST_QUERY_URI{
+            return None;
+        }
+        unsafe{
+            let mut uri = ptr::null_mut();
+            gst_query_parse_uri(self.gst_query() as *mut GstQuery, &mut uri);
+            if uri == ptr::null_mut(){
+                None
+            }else{
+                let ret = from_c_str!(uri).to_string();
+                g_free(uri as gpointer);
+                Some(ret)
+            }
+        }
+    }
+
+    /// The structure holding the fields of the query. For custom queries
+    /// it's the one they were created with, with the fields set by the
+    /// element that answered
+    pub fn structure(&self) -> Option<Structure>{
+        unsafe{
+            let structure = gst_query_get_structure(self.gst_query() as *mut GstQuery);
+            Structure::new_from_gst_structure(structure as *mut GstStructure)
+        }
+    }
+
+    pub unsafe fn gst_query(&self) -> *const GstQuery{
+        self.query.gst_miniobject() as *const GstQuery
+    }
+
+    pub unsafe fn gst_query_mut(&mut self) -> *mut GstQuery{
+        self.query.gst_miniobject_mut() as *mut GstQuery
+    }
+}
+
+impl ::Transfer<GstQuery> for Query{
+    unsafe fn transfer(self) -> *mut GstQuery{
+        self.query.transfer() as *mut GstQuery
+    }
+}
+
+impl Reference for Query{
+    fn reference(&self) -> Query{
+        Query{
+            query: self.query.reference()
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use ffi::*;
+use bin::Bin;
+use element::Element;
+use error::Result;
+use reference::Reference;
+use unwind;
+
+use std::ptr;
+use std::ffi::CString;
+use std::ops::{Deref, DerefMut};
+
+/// Bin to put between a tee and the encoders or muxer of a live
+/// recording, that can pause and resume the recording without leaving a
+/// gap in the timestamps of the file.
+///
+/// Each stream of the recording goes through its own pair of
+/// `sink_%u`/`src_%u` pads, created with `add_stream`. While paused the
+/// buffers of every stream are dropped, and on resume all the streams are
+/// shifted back by the time spent paused, so they stay in sync and the
+/// recorded file plays continuously. Encoded video resumes on the next
+/// keyframe.
+///
+/// A paused branch lets EOS through, so `Pipeline::finish` still
+/// finalizes the file.
+pub struct RecordingBranch{
+    bin: Bin,
+    valves: Vec<Element>,
+    paused_at: Option<GstClockTime>,
+    paused_duration: GstClockTime,
+    paused: bool,
+}
+
+unsafe impl Sync for RecordingBranch {}
+unsafe impl Send for RecordingBranch {}
+
+impl RecordingBranch{
+    pub fn new(name: &str) -> Result<RecordingBranch>{
+        Bin::new(name).map(|bin| RecordingBranch{
+            bin: bin,
+            valves: vec![],
+            paused_at: None,
+            paused_duration: 0,
+            paused: false,
+        })
+    }
+
+    /// Adds a new stream to the branch and returns its index n, the stream
+    /// enters the branch through the `sink_n` pad and leaves it through
+    /// `src_n`. Streams have to be added before the branch is added to the
+    /// pipeline.
+    pub fn add_stream(&mut self) -> Option<u32>{
+        let index = self.valves.len() as u32;
+        let mut queue = Element::new("queue", "").ok()?;
+        let mut valve = Element::new("valve", "").ok()?;
+        valve.set("drop", self.paused as gboolean);
+        if !self.bin.add(queue.reference()) || !self.bin.add(valve.reference()) || !queue.link(&mut valve) ||
+            !self.bin.add_ghost_pad(&mut queue, "sink", &format!("sink_{}", index)) ||
+            !self.bin.add_ghost_pad(&mut valve, "src", &format!("src_{}", index)){
+            return None;
+        }
+        unsafe{
+            let mut sink = valve.static_pad("sink")?;
+            gst_pad_add_probe(sink.gst_pad_mut(), GST_PAD_PROBE_TYPE_EVENT_DOWNSTREAM,
+                              Some(eos_probe), valve.gst_element_mut() as gpointer, None);
+        }
+        let mut src = valve.static_pad("src")?;
+        src.wait_for_keyframe();
+        self.valves.push(valve);
+        Some(index)
+    }
+
+    /// Stops recording, the buffers arriving while paused are dropped
+    pub fn pause(&mut self){
+        if self.paused{
+            return;
+        }
+        for valve in self.valves.iter_mut(){
+            valve.set("drop", 1 as gboolean);
+        }
+        self.paused_at = self.bin.running_time();
+        self.paused = true;
+    }
+
+    /// Resumes recording, with the timestamps of every stream shifted so
+    /// they continue from where they were paused
+    pub fn resume(&mut self){
+        if !self.paused{
+            return;
+        }
+        if let (Some(paused_at), Some(now)) = (self.paused_at, self.bin.running_time()){
+            self.paused_duration += now.saturating_sub(paused_at);
+        }
+        for valve in self.valves.iter_mut(){
+            if let Some(mut src) = valve.static_pad("src"){
+                unsafe{
+                    gst_pad_set_offset(src.gst_pad_mut(), -(self.paused_duration as i64));
+                }
+                src.wait_for_keyframe();
+            }
+            valve.set("drop", 0 as gboolean);
+        }
+        self.paused_at = None;
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool{
+        self.paused
+    }
+
+    /// Total time in nanoseconds the recording has been paused, not
+    /// counting the current pause
+    pub fn paused_duration(&self) -> GstClockTime{
+        self.paused_duration
+    }
+}
+
+// valve drops EOS while closed, let it through so the file gets finalized
+extern "C" fn eos_probe(pad: *mut GstPad, info: *mut GstPadProbeInfo, data: gpointer) -> GstPadProbeReturn{
+    unsafe{
+        unwind::guard(pad as *mut GstObject, GST_PAD_PROBE_OK, || {
+            let event = (*info).data as *mut GstEvent;
+            if (*event)._type == GST_EVENT_EOS{
+                let drop = CString::new("drop").unwrap();
+                g_object_set(data, drop.as_ptr(), 0 as gboolean, ptr::null::<gchar>());
+            }
+            GST_PAD_PROBE_OK
+        })
+    }
+}
+
+impl AsRef<Bin> for RecordingBranch{
+    fn as_ref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl AsMut<Bin> for RecordingBranch{
+    fn as_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl From<RecordingBranch> for Element{
+    fn from(branch: RecordingBranch) -> Element{
+        branch.bin.into()
+    }
+}
+
+impl Deref for RecordingBranch{
+    type Target = Bin;
+    fn deref(&self) -> &Bin{
+        &self.bin
+    }
+}
+
+impl DerefMut for RecordingBranch{
+    fn deref_mut(&mut self) -> &mut Bin{
+        &mut self.bin
+    }
+}
+
+impl ::Transfer for RecordingBranch{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.bin.transfer()
+    }
+}
@@ -0,0 +1,304 @@
+use ffi::*;
+use util::*;
+use error::{Error, Result};
+use reference::Reference;
+use object::Object;
+use subclass;
+use unwind;
+
+use std::ops::{Deref, DerefMut};
+
+unsafe impl Sync for Plugin {}
+unsafe impl Send for Plugin {}
+
+/// A loadable module containing elements and other plugin features
+pub struct Plugin{
+    plugin: Object
+}
+
+impl Plugin{
+    pub unsafe fn new(plugin: *mut GstPlugin) -> Option<Plugin>{
+        Object::new(plugin as *mut GstObject).map(|obj| Plugin{ plugin: obj })
+    }
+
+    /// Loads the plugin from the .so/.dll/.dylib at `filename` and adds
+    /// it to the default registry so its elements can be created by name
+    pub fn load_file(filename: &str) -> Result<Plugin>{
+        let cfilename = CString::new(filename).unwrap();
+        unsafe{
+            let mut err: *mut GError = ptr::null_mut();
+            let plugin = gst_plugin_load_file(cfilename.as_ptr(), &mut err);
+            if err != ptr::null_mut(){
+                if plugin != ptr::null_mut(){
+                    gst_object_unref(plugin as gpointer);
+                }
+                Err(Error::new_from_g_error(err))
+            }else{
+                Plugin::new(plugin).ok_or(Error::new(0, 0, &format!("Couldn't load plugin {}", filename)))
+            }
+        }
+    }
+
+    /// Loads a plugin already known by the registry by its name
+    pub fn load_by_name(name: &str) -> Option<Plugin>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            Plugin::new(gst_plugin_load_by_name(cname.as_ptr()))
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool{
+        unsafe{
+            gst_plugin_is_loaded(self.gst_plugin() as *mut GstPlugin) != 0
+        }
+    }
+
+    pub fn plugin_name(&self) -> String{
+        unsafe{
+            from_c_str!(gst_plugin_get_name(self.gst_plugin() as *mut GstPlugin)).to_string()
+        }
+    }
+
+    pub fn description(&self) -> String{
+        unsafe{
+            from_c_str!(gst_plugin_get_description(self.gst_plugin() as *mut GstPlugin)).to_string()
+        }
+    }
+
+    /// Path to the file the plugin was loaded from, None for static
+    /// plugins
+    pub fn filename(&self) -> Option<String>{
+        unsafe{
+            let filename = gst_plugin_get_filename(self.gst_plugin() as *mut GstPlugin);
+            if filename != ptr::null(){
+                Some(from_c_str!(filename).to_string())
+            }else{
+                None
+            }
+        }
+    }
+
+    pub fn version(&self) -> String{
+        unsafe{
+            from_c_str!(gst_plugin_get_version(self.gst_plugin() as *mut GstPlugin)).to_string()
+        }
+    }
+
+    pub fn license(&self) -> String{
+        unsafe{
+            from_c_str!(gst_plugin_get_license(self.gst_plugin() as *mut GstPlugin)).to_string()
+        }
+    }
+
+    pub fn package(&self) -> String{
+        unsafe{
+            from_c_str!(gst_plugin_get_package(self.gst_plugin() as *mut GstPlugin)).to_string()
+        }
+    }
+
+    pub unsafe fn gst_plugin(&self) -> *const GstPlugin{
+        self.plugin.gst_object() as *const GstPlugin
+    }
+
+    pub unsafe fn gst_plugin_mut(&mut self) -> *mut GstPlugin{
+        self.plugin.gst_object_mut() as *mut GstPlugin
+    }
+}
+
+impl Reference for Plugin{
+    fn reference(&self) -> Plugin{
+        Plugin{ plugin: self.plugin.reference() }
+    }
+}
+
+impl Deref for Plugin{
+    type Target = Object;
+    fn deref(&self) -> &Object{
+        &self.plugin
+    }
+}
+
+impl DerefMut for Plugin{
+    fn deref_mut(&mut self) -> &mut Object{
+        &mut self.plugin
+    }
+}
+
+struct PluginInit<F>{
+    init: Option<F>,
+    result: Result<()>,
+}
+
+/// Registers a plugin `name` built into the application, whose `init`
+/// registers its features, like the elements written in rust with
+/// `register_element`, `register_push_src` or `register_base_transform`.
+/// The element factories registered from `init` belong to the plugin, so
+/// they're listed with it by gst-inspect-1.0 and the registry, and can be
+/// used by name from parse_launch like any other element.
+///
+/// Has to be called after init. Returns the error of `init` if it fails,
+/// in which case the plugin isn't registered.
+pub fn plugin_register_static<F>(name: &str, description: &str, init: F) -> Result<()>
+    where F: FnOnce() -> Result<()>{
+    let cname = CString::new(name).unwrap();
+    let cdescription = CString::new(description).unwrap();
+    let cversion = CString::new(env!("CARGO_PKG_VERSION")).unwrap();
+    let clicense = CString::new("unknown").unwrap();
+    let corigin = CString::new("").unwrap();
+    let mut data = PluginInit{
+        init: Some(init),
+        result: Err(Error::new(0, 0, &format!("Couldn't register plugin {}", name))),
+    };
+    unsafe{
+        gst_plugin_register_static_full(1, 0, cname.as_ptr(), cdescription.as_ptr(), Some(plugin_init::<F>),
+                                        cversion.as_ptr(), clicense.as_ptr(), cname.as_ptr(), cname.as_ptr(),
+                                        corigin.as_ptr(), &mut data as *mut PluginInit<F> as gpointer);
+    }
+    data.result
+}
+
+extern "C" fn plugin_init<F>(plugin: *mut GstPlugin, data: gpointer) -> gboolean
+    where F: FnOnce() -> Result<()>{
+    unsafe{
+        let data = &mut *(data as *mut PluginInit<F>);
+        if let Some(init) = data.init.take(){
+            let panicked = Err(Error::new(0, 0, "The init function of the plugin panicked"));
+            data.result = unwind::guard(plugin as *mut GstObject, panicked, || subclass::with_plugin(plugin, init));
+        }
+        data.result.is_ok() as gboolean
+    }
+}
+
+unsafe impl Sync for Registry {}
+unsafe impl Send for Registry {}
+
+/// The registry keeps track of the available plugins and the features
+/// they provide. It's built by scanning the plugin paths on init and
+/// cached in a binary file so later runs don't need to load every plugin.
+pub struct Registry{
+    registry: Object
+}
+
+impl Registry{
+    /// The default registry used by gstreamer
+    pub fn get() -> Registry{
+        unsafe{
+            let registry = gst_registry_get();
+            gst_object_ref(registry as gpointer);
+            Registry{ registry: Object::new(registry as *mut GstObject).unwrap() }
+        }
+    }
+
+    /// Scans `path` for plugins and adds them to the registry. Useful
+    /// for applications that ship their own plugins bundled outside of
+    /// the system plugin directories.
+    ///
+    /// Returns true if the registry changed
+    pub fn scan_path(&mut self, path: &str) -> bool{
+        let cpath = CString::new(path).unwrap();
+        unsafe{
+            gst_registry_scan_path(self.gst_registry_mut(), cpath.as_ptr()) != 0
+        }
+    }
+
+    pub fn find_plugin(&self, name: &str) -> Option<Plugin>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            Plugin::new(gst_registry_find_plugin(self.gst_registry() as *mut GstRegistry, cname.as_ptr()))
+        }
+    }
+
+    /// Looks up a plugin in the registry by the path of its file
+    pub fn lookup(&self, filename: &str) -> Option<Plugin>{
+        let cfilename = CString::new(filename).unwrap();
+        unsafe{
+            Plugin::new(gst_registry_lookup(self.gst_registry() as *mut GstRegistry, cfilename.as_ptr()))
+        }
+    }
+
+    /// Removes the plugin and all its features from the registry so
+    /// they can't be used anymore
+    pub fn remove_plugin(&mut self, plugin: &mut Plugin){
+        unsafe{
+            gst_registry_remove_plugin(self.gst_registry_mut(), plugin.gst_plugin_mut());
+        }
+    }
+
+    /// All the plugins currently in the registry
+    pub fn plugins(&self) -> Vec<Plugin>{
+        let mut plugins = Vec::new();
+        unsafe{
+            let list = gst_registry_get_plugin_list(self.gst_registry() as *mut GstRegistry);
+            let mut node = list;
+            while node != ptr::null_mut(){
+                gst_object_ref((*node).data);
+                if let Some(plugin) = Plugin::new((*node).data as *mut GstPlugin){
+                    plugins.push(plugin);
+                }
+                node = (*node).next;
+            }
+            gst_plugin_list_free(list);
+        }
+        plugins
+    }
+
+    /// Checks if a feature, like an element, is available with at least
+    /// the given version
+    pub fn check_feature_version(&self, feature: &str, major: u32, minor: u32, micro: u32) -> bool{
+        let cfeature = CString::new(feature).unwrap();
+        unsafe{
+            gst_registry_check_feature_version(self.gst_registry() as *mut GstRegistry, cfeature.as_ptr(), major, minor, micro) != 0
+        }
+    }
+
+    /// Rescans the plugin paths and updates the registry and its
+    /// binary cache if anything changed
+    pub fn update() -> bool{
+        unsafe{
+            gst_update_registry() != 0
+        }
+    }
+
+    /// Whether the registry is rebuilt in a forked child process, which
+    /// protects the application from plugins crashing while being loaded
+    pub fn fork_is_enabled() -> bool{
+        unsafe{
+            gst_registry_fork_is_enabled() != 0
+        }
+    }
+
+    /// Enables or disables forking when rebuilding the registry. Has to
+    /// be called before init to have effect on the initial scan
+    pub fn set_fork_enabled(enabled: bool){
+        unsafe{
+            gst_registry_fork_set_enabled(enabled as gboolean);
+        }
+    }
+
+    pub unsafe fn gst_registry(&self) -> *const GstRegistry{
+        self.registry.gst_object() as *const GstRegistry
+    }
+
+    pub unsafe fn gst_registry_mut(&mut self) -> *mut GstRegistry{
+        self.registry.gst_object_mut() as *mut GstRegistry
+    }
+}
+
+impl Reference for Registry{
+    fn reference(&self) -> Registry{
+        Registry{ registry: self.registry.reference() }
+    }
+}
+
+impl Deref for Registry{
+    type Target = Object;
+    fn deref(&self) -> &Object{
+        &self.registry
+    }
+}
+
+impl DerefMut for Registry{
+    fn deref_mut(&mut self) -> &mut Object{
+        &mut self.registry
+    }
+}
@@ -0,0 +1,249 @@
+use ffi::*;
+use util::*;
+use element::Element;
+use pipeline::Pipeline;
+use pipelineconfig::{self, ConfigValue};
+use query::Query;
+use reference::Reference;
+
+use serde_json::{self, Value};
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+/// Controls a pipeline from other processes through a unix socket, so
+/// headless services can be operated without writing custom rpc code.
+///
+/// Clients send one JSON object per line and receive one JSON object per
+/// line with `"ok": true` and the results of the command, or `"ok": false`
+/// and an `"error"` message:
+///
+/// ```text
+/// {"cmd": "get_state"}
+/// {"cmd": "set_state", "state": "playing"}
+/// {"cmd": "seek", "position": 5000000000}
+/// {"cmd": "set_property", "element": "volume", "property": "volume", "value": 0.5}
+/// {"cmd": "get_property", "element": "src", "property": "pattern"}
+/// {"cmd": "stats"}
+/// ```
+///
+/// States are "null", "ready", "paused" and "playing", positions and
+/// durations are in nanoseconds. Something like
+/// `echo '{"cmd": "stats"}' | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/player.sock` is
+/// enough to talk to it from a shell.
+///
+/// Clients aren't authenticated: anyone who can connect to the socket can
+/// set any property of the elements, like the location of a filesink,
+/// with the rights of the process. The socket is only accessible to the
+/// user running the process, put it in a directory only that user can
+/// write to, like `$XDG_RUNTIME_DIR`, rather than `/tmp`.
+pub struct RemoteControl{
+    path: PathBuf,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RemoteControl{
+    /// Starts listening on a unix socket at `path`, replacing a stale
+    /// socket left there by a previous run. Every connection is served
+    /// from its own thread until the client closes it
+    pub fn start<P: AsRef<Path>>(pipeline: &Pipeline, path: P) -> io::Result<RemoteControl>{
+        let path = path.as_ref().to_path_buf();
+        // only sockets nobody listens on are removed, never other files
+        let is_socket = fs::symlink_metadata(&path).map(|metadata| metadata.file_type().is_socket()).unwrap_or(false);
+        if is_socket && UnixStream::connect(&path).is_err(){
+            fs::remove_file(&path).ok();
+        }
+        let listener = UnixListener::bind(&path)?;
+        // whatever the umask, only the user running the process can connect
+        if let Err(err) = fs::set_permissions(&path, fs::Permissions::from_mode(0o600)){
+            fs::remove_file(&path).ok();
+            return Err(err);
+        }
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let pipeline = pipeline.reference();
+        let thread = thread::spawn(move || accept_loop(listener, pipeline, thread_running));
+        Ok(RemoteControl{
+            path: path,
+            running: running,
+            thread: Some(thread),
+        })
+    }
+
+    /// Path of the socket clients connect to
+    pub fn path(&self) -> &Path{
+        &self.path
+    }
+}
+
+impl Drop for RemoteControl{
+    fn drop(&mut self){
+        self.running.store(false, Ordering::SeqCst);
+        // wake up the listener blocked in accept
+        UnixStream::connect(&self.path).ok();
+        if let Some(thread) = self.thread.take(){
+            thread.join().ok();
+        }
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+fn accept_loop(listener: UnixListener, pipeline: Pipeline, running: Arc<AtomicBool>){
+    for stream in listener.incoming(){
+        if !running.load(Ordering::SeqCst){
+            break;
+        }
+        if let Ok(stream) = stream{
+            let pipeline = pipeline.reference();
+            thread::spawn(move || serve(stream, pipeline));
+        }
+    }
+}
+
+fn serve(stream: UnixStream, mut pipeline: Pipeline){
+    let mut writer = match stream.try_clone(){
+        Ok(writer) => writer,
+        Err(_) => return
+    };
+    for line in BufReader::new(stream).lines(){
+        let line = match line{
+            Ok(line) => line,
+            Err(_) => break
+        };
+        if line.trim().is_empty(){
+            continue;
+        }
+        let response = match handle(&mut pipeline, &line){
+            Ok(Value::Object(mut response)) => {
+                response.insert("ok".to_string(), Value::from(true));
+                Value::Object(response)
+            }
+            Ok(_) => json!({"ok": true}),
+            Err(message) => json!({"ok": false, "error": message}),
+        };
+        if writeln!(writer, "{}", response).is_err(){
+            break;
+        }
+    }
+}
+
+fn handle(pipeline: &mut Pipeline, request: &str) -> ::std::result::Result<Value, String>{
+    let request: Value = serde_json::from_str(request).map_err(|err| err.to_string())?;
+    let field = |name: &str| request.get(name).ok_or(format!("missing {}", name));
+    // names are passed to C, an embedded nul would make CString::new panic
+    let string_field = |name: &str| match field(name)?.as_str(){
+        Some(string) if string.contains('\0') => Err(format!("{} shouldn't contain nul characters", name)),
+        Some(string) => Ok(string),
+        None => Err(format!("{} should be a string", name)),
+    };
+
+    match string_field("cmd")?{
+        "get_state" => {
+            let (state, pending, _) = pipeline.get_state(0);
+            Ok(json!({
+                "state": state_name(state),
+                "pending": pending_name(pending),
+            }))
+        }
+        "set_state" => {
+            let state = state_from_name(string_field("state")?)?;
+            let ret = pipeline.set_state(state);
+            if ret == GST_STATE_CHANGE_FAILURE{
+                Err(format!("couldn't change state to {}", state_name(state)))
+            }else{
+                Ok(json!({"result": state_change_name(ret)}))
+            }
+        }
+        "seek" => {
+            let position = field("position")?.as_i64().ok_or("position should be an integer".to_string())?;
+            if pipeline.set_position_ns(position){
+                Ok(json!({}))
+            }else{
+                Err(format!("couldn't seek to {}", position))
+            }
+        }
+        "set_property" => {
+            let mut element = child(pipeline, string_field("element")?)?;
+            if field("value")?.as_str().map_or(false, |value| value.contains('\0')){
+                return Err("value shouldn't contain nul characters".to_string());
+            }
+            let value = pipelineconfig::config_from_json(field("value")?)
+                .ok_or("value should be a boolean, number or string".to_string())?;
+            unsafe{
+                pipelineconfig::set_property(&mut element, string_field("property")?, &value)
+                    .map_err(|err| err.message())?;
+            }
+            Ok(json!({}))
+        }
+        "get_property" => {
+            let element = child(pipeline, string_field("element")?)?;
+            let value: ConfigValue = pipelineconfig::get_property(&element, string_field("property")?)
+                .map_err(|err| err.message())?;
+            Ok(json!({"value": pipelineconfig::json_from_config(&value)}))
+        }
+        "stats" => Ok(stats(pipeline)),
+        cmd => Err(format!("unknown command {}", cmd))
+    }
+}
+
+fn stats(pipeline: &mut Pipeline) -> Value{
+    let (state, pending, _) = pipeline.get_state(0);
+    let mut latency = Query::new_latency();
+    let latency = if pipeline.query(&mut latency){
+        latency.latency().map(|(live, min, max)| json!({
+            "live": live,
+            "min": min,
+            "max": if max == GST_CLOCK_TIME_NONE{ Value::Null }else{ Value::from(max) },
+        }))
+    }else{
+        None
+    };
+    json!({
+        "state": state_name(state),
+        "pending": pending_name(pending),
+        "position": pipeline.position_ns(),
+        "duration": pipeline.duration_ns(),
+        "running_time": pipeline.running_time(),
+        "latency": latency,
+    })
+}
+
+fn child(pipeline: &Pipeline, name: &str) -> ::std::result::Result<Element, String>{
+    pipeline.get_by_name(name).ok_or(format!("no element {}", name))
+}
+
+fn state_from_name(name: &str) -> ::std::result::Result<GstState, String>{
+    match name.to_lowercase().as_str(){
+        "null" => Ok(GST_STATE_NULL),
+        "ready" => Ok(GST_STATE_READY),
+        "paused" => Ok(GST_STATE_PAUSED),
+        "playing" => Ok(GST_STATE_PLAYING),
+        _ => Err(format!("unknown state {}", name))
+    }
+}
+
+fn state_name(state: GstState) -> String{
+    unsafe{
+        from_c_str!(gst_element_state_get_name(state)).to_lowercase()
+    }
+}
+
+fn pending_name(pending: GstState) -> Option<String>{
+    match pending{
+        GST_STATE_VOID_PENDING => None,
+        pending => Some(state_name(pending))
+    }
+}
+
+fn state_change_name(ret: GstStateChangeReturn) -> String{
+    unsafe{
+        from_c_str!(gst_element_state_change_return_get_name(ret)).to_lowercase()
+    }
+}
@@ -0,0 +1,515 @@
+use ffi::*;
+use util::*;
+use bin::Bin;
+use element::Element;
+use error::Result;
+use reference::Reference;
+use unwind;
+use ::Transfer;
+
+use std::sync::{Arc, Mutex};
+use std::ops::{Deref, DerefMut};
+
+/// How the jitterbuffers compute the output timestamps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum JitterBufferMode{
+    /// Only use the RTP timestamps
+    None = 0,
+    /// Slave the receiver to the sender clock
+    Slave = 1,
+    /// Buffer up to the latency and then play at the receiver rate,
+    /// for non live sources
+    Buffer = 2,
+    Auto = 3,
+    /// Sender and receiver clocks are synchronized, for example with NTP
+    Synced = 4,
+}
+
+/// Statistics of the jitterbuffer of a received stream
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JitterBufferStats{
+    pub session: u32,
+    pub ssrc: u32,
+    /// Packets pushed downstream
+    pub pushed: u64,
+    /// Packets considered lost
+    pub lost: u64,
+    /// Packets that arrived after their playout time and were dropped
+    pub late: u64,
+    pub duplicates: u64,
+    /// Average jitter in nanoseconds
+    pub avg_jitter: u64,
+    /// Retransmission requests sent
+    pub rtx_count: u64,
+    /// Retransmission requests that got the packet back in time
+    pub rtx_success_count: u64,
+    /// Current latency of the jitterbuffer in milliseconds
+    pub latency: u32,
+}
+
+struct JitterBuffer{
+    session: u32,
+    ssrc: u32,
+    jitterbuffer: Element,
+}
+
+struct State{
+    jitterbuffers: Vec<JitterBuffer>,
+    rtx: Option<RtxConfig>,
+}
+
+type SharedState = Arc<Mutex<State>>;
+
+/// Retransmission settings. The sender keeps the packets it sent for a
+/// while and resends them, with a different payload type and SSRC, when
+/// the receiver jitterbuffer requests them with RTCP NACKs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RtxConfig{
+    /// Pairs of (media payload type, RTX payload type) as negotiated,
+    /// for example in SDP with a=rtpmap:97 rtx/90000 and a=fmtp:97 apt=96
+    pub payload_type_map: Vec<(u8, u8)>,
+    /// Sender: milliseconds of packets kept for retransmission
+    pub max_size_time: u32,
+    /// Sender: packets kept for retransmission, 0 for no limit
+    pub max_size_packets: u32,
+    /// Receiver: milliseconds to wait for a missing packet before
+    /// requesting it, None for the jitterbuffer default based on the
+    /// measured jitter
+    pub rtx_delay: Option<i32>,
+    /// Receiver: milliseconds to wait for a retransmission before
+    /// requesting it again, None for the default based on the RTT
+    pub rtx_retry_timeout: Option<i32>,
+    /// Receiver: times to request a packet, None for no limit other than
+    /// the latency
+    pub rtx_max_retries: Option<i32>,
+}
+
+impl RtxConfig{
+    pub fn new(payload_type_map: &[(u8, u8)]) -> RtxConfig{
+        RtxConfig{
+            payload_type_map: payload_type_map.to_vec(),
+            max_size_time: 0,
+            max_size_packets: 100,
+            rtx_delay: None,
+            rtx_retry_timeout: None,
+            rtx_max_retries: None,
+        }
+    }
+
+    // "payload-type-map" of rtprtxsend and rtprtxreceive
+    fn set_payload_type_map(&self, element: &mut Element){
+        let map = self.payload_type_map.iter()
+            .map(|&(pt, rtx_pt)| format!(", {}=(uint){}", pt, rtx_pt))
+            .collect::<String>();
+        let cmap = CString::new(format!("application/x-rtp-pt-map{}", map)).unwrap();
+        let property = CString::new("payload-type-map").unwrap();
+        unsafe{
+            let structure = gst_structure_from_string(cmap.as_ptr(), ptr::null_mut());
+            if structure != ptr::null_mut(){
+                g_object_set(element.gst_element_mut() as gpointer, property.as_ptr(), structure, ptr::null::<gchar>());
+                gst_structure_free(structure);
+            }
+        }
+    }
+
+    fn configure_jitterbuffer(&self, jitterbuffer: &mut Element){
+        jitterbuffer.set("do-retransmission", 1 as gboolean);
+        if let Some(delay) = self.rtx_delay{
+            jitterbuffer.set("rtx-delay", delay);
+        }
+        if let Some(timeout) = self.rtx_retry_timeout{
+            jitterbuffer.set("rtx-retry-timeout", timeout);
+        }
+        if let Some(retries) = self.rtx_max_retries{
+            jitterbuffer.set("rtx-max-retries", retries);
+        }
+    }
+}
+
+/// ULPFEC (RFC 5109) forward error correction settings of the sender
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FecConfig{
+    /// Payload type of the FEC packets
+    pub payload_type: u8,
+    /// Percentage of FEC packets relative to media packets
+    pub percentage: u32,
+    /// Percentage of FEC packets for the packets marked as important,
+    /// usually the ones of keyframes
+    pub percentage_important: u32,
+    /// Protect several packets with each FEC packet
+    pub multipacket: bool,
+}
+
+/// Creates an rtprtxqueue, which retransmits the original packets
+/// without a separate RTX payload type. Link it after the payloader when
+/// the receiver doesn't support RFC 4588 retransmission.
+pub fn rtx_queue(name: &str, max_size_time: u32, max_size_packets: u32) -> Result<Element>{
+    Element::new("rtprtxqueue", name).map(|mut queue|{
+        queue.set("max-size-time", max_size_time);
+        queue.set("max-size-packets", max_size_packets);
+        queue
+    })
+}
+
+/// Typed wrapper for the rtpbin element that keeps track of the
+/// jitterbuffers of the received streams, to read their statistics and
+/// adjust their latency while running, and sets up retransmission and
+/// forward error correction
+pub struct RtpBin{
+    rtpbin: Element,
+    state: SharedState,
+}
+
+unsafe impl Sync for RtpBin {}
+unsafe impl Send for RtpBin {}
+
+impl RtpBin{
+    pub fn new(name: &str) -> Result<RtpBin>{
+        Element::new("rtpbin", name).map(RtpBin::new_from_element)
+    }
+
+    pub fn new_from_element(element: Element) -> RtpBin{
+        let state = Arc::new(Mutex::new(State{ jitterbuffers: Vec::new(), rtx: None }));
+        let mut rtpbin = RtpBin{ rtpbin: element, state: state };
+        unsafe{
+            rtpbin.connect("new-jitterbuffer",
+                           mem::transmute(on_new_jitterbuffer as extern "C" fn(*mut GstElement, *mut GstElement, guint, guint, gpointer)));
+        }
+        rtpbin
+    }
+
+    unsafe fn connect(&mut self, signal: &str, callback: GCallback){
+        let csignal = CString::new(signal).unwrap();
+        let data = Box::new(self.state.clone());
+        g_signal_connect_data(self.rtpbin.gst_element_mut() as gpointer, csignal.as_ptr(), callback,
+                              Box::into_raw(data) as gpointer, Some(free_state), 0);
+    }
+
+    /// Retransmits the packets sent in every session when the receiver
+    /// requests them. Has to be called before the send pads are requested
+    pub fn enable_rtx_sender(&mut self, config: &RtxConfig){
+        self.state.lock().unwrap().rtx = Some(config.clone());
+        unsafe{
+            self.connect("request-aux-sender",
+                         mem::transmute(on_request_aux_sender as extern "C" fn(*mut GstElement, guint, gpointer) -> *mut GstElement));
+        }
+    }
+
+    /// Requests lost packets to the sender and merges the retransmitted
+    /// ones back into the stream. Has to be called before the receive pads
+    /// are requested
+    pub fn enable_rtx_receiver(&mut self, config: &RtxConfig){
+        {
+            let mut state = self.state.lock().unwrap();
+            for jb in state.jitterbuffers.iter_mut(){
+                config.configure_jitterbuffer(&mut jb.jitterbuffer);
+            }
+            state.rtx = Some(config.clone());
+        }
+        self.rtpbin.set("do-retransmission", 1 as gboolean);
+        unsafe{
+            self.connect("request-aux-receiver",
+                         mem::transmute(on_request_aux_receiver as extern "C" fn(*mut GstElement, guint, gpointer) -> *mut GstElement));
+        }
+    }
+
+    /// Adds ULPFEC packets to the sent streams of every session, so the
+    /// receiver can recover lost packets without waiting for a
+    /// retransmission. Needs GStreamer 1.14
+    pub fn enable_fec_encoder(&mut self, config: &FecConfig){
+        let signal = CString::new("request-fec-encoder").unwrap();
+        unsafe{
+            let data = Box::new(*config);
+            g_signal_connect_data(self.rtpbin.gst_element_mut() as gpointer, signal.as_ptr(),
+                                  mem::transmute(on_request_fec_encoder as extern "C" fn(*mut GstElement, guint, gpointer) -> *mut GstElement),
+                                  Box::into_raw(data) as gpointer, Some(free_fec_config), 0);
+        }
+    }
+
+    /// Recovers lost packets of the received streams from the ULPFEC
+    /// packets with the given payload type. The received packets are kept
+    /// `storage_time_ms` to recover the lost ones, usually the latency.
+    /// Needs GStreamer 1.14
+    pub fn enable_fec_decoder(&mut self, payload_type: u8, storage_time_ms: u32){
+        let fec = Box::new(FecConfig{
+            payload_type: payload_type,
+            percentage: 0,
+            percentage_important: 0,
+            multipacket: false,
+        });
+        let new_storage = CString::new("new-storage").unwrap();
+        let request_decoder = CString::new("request-fec-decoder").unwrap();
+        unsafe{
+            g_signal_connect_data(self.rtpbin.gst_element_mut() as gpointer, new_storage.as_ptr(),
+                                  mem::transmute(on_new_storage as extern "C" fn(*mut GstElement, *mut GObject, guint, gpointer)),
+                                  storage_time_ms as usize as gpointer, None, 0);
+            g_signal_connect_data(self.rtpbin.gst_element_mut() as gpointer, request_decoder.as_ptr(),
+                                  mem::transmute(on_request_fec_decoder as extern "C" fn(*mut GstElement, guint, gpointer) -> *mut GstElement),
+                                  Box::into_raw(fec) as gpointer, Some(free_fec_config), 0);
+        }
+    }
+
+    /// Latency of the jitterbuffers in milliseconds. Changing it while
+    /// running applies it to the existing jitterbuffers too
+    pub fn set_latency(&mut self, latency_ms: u32){
+        self.rtpbin.set("latency", latency_ms);
+    }
+
+    pub fn latency(&self) -> u32{
+        self.rtpbin.get("latency")
+    }
+
+    /// Changes the latency of a single received stream, for example to
+    /// grow it only for the stream with more jitter. Returns false if
+    /// there's no such stream
+    pub fn set_stream_latency(&mut self, session: u32, ssrc: u32, latency_ms: u32) -> bool{
+        let mut state = self.state.lock().unwrap();
+        match state.jitterbuffers.iter_mut().find(|jb| jb.session == session && jb.ssrc == ssrc){
+            Some(jb) => {
+                jb.jitterbuffer.set("latency", latency_ms);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Drop packets that arrive too late to be played within the latency
+    /// instead of growing it
+    pub fn set_drop_on_latency(&mut self, drop: bool){
+        self.rtpbin.set("drop-on-latency", drop as gboolean);
+    }
+
+    /// Send packet lost events downstream so depayloaders and decoders
+    /// can conceal the loss
+    pub fn set_do_lost(&mut self, do_lost: bool){
+        self.rtpbin.set("do-lost", do_lost as gboolean);
+    }
+
+    pub fn set_buffer_mode(&mut self, mode: JitterBufferMode){
+        self.rtpbin.set("buffer-mode", mode as i32);
+    }
+
+    /// Statistics of the jitterbuffers of the streams currently received
+    pub fn jitterbuffer_stats(&self) -> Vec<JitterBufferStats>{
+        let mut state = self.state.lock().unwrap();
+        // jitterbuffers of removed streams are no longer in rtpbin
+        state.jitterbuffers.retain(|jb| unsafe{ (*jb.jitterbuffer.gst_element()).object.parent != ptr::null_mut() });
+        state.jitterbuffers.iter().map(|jb|{
+            let mut stats = JitterBufferStats{
+                session: jb.session,
+                ssrc: jb.ssrc,
+                pushed: 0,
+                lost: 0,
+                late: 0,
+                duplicates: 0,
+                avg_jitter: 0,
+                rtx_count: 0,
+                rtx_success_count: 0,
+                latency: jb.jitterbuffer.get("latency"),
+            };
+            let property = CString::new("stats").unwrap();
+            unsafe{
+                let mut structure: *mut GstStructure = ptr::null_mut();
+                g_object_get(jb.jitterbuffer.gst_element() as gpointer, property.as_ptr(), &mut structure, ptr::null::<gchar>());
+                if structure != ptr::null_mut(){
+                    {
+                        let get = |field: &str, value: &mut u64|{
+                            let cfield = CString::new(field).unwrap();
+                            gst_structure_get_uint64(structure, cfield.as_ptr(), value);
+                        };
+                        get("num-pushed", &mut stats.pushed);
+                        get("num-lost", &mut stats.lost);
+                        get("num-late", &mut stats.late);
+                        get("num-duplicates", &mut stats.duplicates);
+                        get("avg-jitter", &mut stats.avg_jitter);
+                        get("rtx-count", &mut stats.rtx_count);
+                        get("rtx-success-count", &mut stats.rtx_success_count);
+                    }
+                    gst_structure_free(structure);
+                }
+            }
+            stats
+        }).collect()
+    }
+}
+
+extern "C" fn on_new_jitterbuffer(rtpbin: *mut GstElement, jitterbuffer: *mut GstElement, session: guint, ssrc: guint, data: gpointer){
+    unsafe{
+        unwind::guard(rtpbin as *mut GstObject, (), || {
+            let state = &*(data as *const SharedState);
+            gst_object_ref(jitterbuffer as gpointer);
+            if let Some(mut jitterbuffer) = Element::new_from_gst_element(jitterbuffer){
+                let mut state = state.lock().unwrap();
+                if let Some(ref rtx) = state.rtx{
+                    rtx.configure_jitterbuffer(&mut jitterbuffer);
+                }
+                state.jitterbuffers.push(JitterBuffer{
+                    session: session,
+                    ssrc: ssrc,
+                    jitterbuffer: jitterbuffer,
+                });
+            }
+        })
+    }
+}
+
+// rtpbin expects the aux elements as bins with sink_%u and src_%u pads
+// for the session
+unsafe fn aux_bin(element: &str, session: guint, rtx: &RtxConfig) -> *mut GstElement{
+    let mut rtx_element = match Element::new(element, ""){
+        Ok(rtx_element) => rtx_element,
+        Err(_) => return ptr::null_mut()
+    };
+    rtx.set_payload_type_map(&mut rtx_element);
+    if element == "rtprtxsend"{
+        rtx_element.set("max-size-time", rtx.max_size_time);
+        rtx_element.set("max-size-packets", rtx.max_size_packets);
+    }
+    let mut bin = match Bin::new(""){
+        Ok(bin) => bin,
+        Err(_) => return ptr::null_mut()
+    };
+    if !bin.add(rtx_element.reference()) ||
+        !bin.add_ghost_pad(&mut rtx_element, "sink", &format!("sink_{}", session)) ||
+        !bin.add_ghost_pad(&mut rtx_element, "src", &format!("src_{}", session)){
+        return ptr::null_mut();
+    }
+    into_floating(Element::from(bin))
+}
+
+// signal handlers returning elements have to return floating references
+unsafe fn into_floating(element: Element) -> *mut GstElement{
+    let element = element.transfer();
+    g_object_force_floating(element as *mut GObject);
+    element
+}
+
+extern "C" fn on_request_aux_sender(rtpbin: *mut GstElement, session: guint, data: gpointer) -> *mut GstElement{
+    unsafe{
+        unwind::guard(rtpbin as *mut GstObject, ptr::null_mut(), || {
+            let state = (*(data as *const SharedState)).lock().unwrap();
+            match state.rtx{
+                Some(ref rtx) => aux_bin("rtprtxsend", session, rtx),
+                None => ptr::null_mut()
+            }
+        })
+    }
+}
+
+extern "C" fn on_request_aux_receiver(rtpbin: *mut GstElement, session: guint, data: gpointer) -> *mut GstElement{
+    unsafe{
+        unwind::guard(rtpbin as *mut GstObject, ptr::null_mut(), || {
+            let state = (*(data as *const SharedState)).lock().unwrap();
+            match state.rtx{
+                Some(ref rtx) => aux_bin("rtprtxreceive", session, rtx),
+                None => ptr::null_mut()
+            }
+        })
+    }
+}
+
+extern "C" fn on_request_fec_encoder(rtpbin: *mut GstElement, _session: guint, data: gpointer) -> *mut GstElement{
+    unsafe{
+        unwind::guard(rtpbin as *mut GstObject, ptr::null_mut(), || {
+            let config = &*(data as *const FecConfig);
+            match Element::new("rtpulpfecenc", ""){
+                Ok(mut encoder) => {
+                    encoder.set("pt", config.payload_type as u32);
+                    encoder.set("percentage", config.percentage);
+                    encoder.set("percentage-important", config.percentage_important);
+                    encoder.set("multipacket", config.multipacket as gboolean);
+                    into_floating(encoder)
+                }
+                Err(_) => ptr::null_mut()
+            }
+        })
+    }
+}
+
+extern "C" fn on_request_fec_decoder(rtpbin: *mut GstElement, session: guint, data: gpointer) -> *mut GstElement{
+    unsafe{
+        unwind::guard(rtpbin as *mut GstObject, ptr::null_mut(), || {
+            let config = &*(data as *const FecConfig);
+            let mut decoder = match Element::new("rtpulpfecdec", ""){
+                Ok(decoder) => decoder,
+                Err(_) => return ptr::null_mut()
+            };
+            let get_storage = CString::new("get-storage").unwrap();
+            let mut storage: *mut GObject = ptr::null_mut();
+            g_signal_emit_by_name(rtpbin as gpointer, get_storage.as_ptr(), session, &mut storage);
+            if storage != ptr::null_mut(){
+                let property = CString::new("storage").unwrap();
+                g_object_set(decoder.gst_element_mut() as gpointer, property.as_ptr(), storage, ptr::null::<gchar>());
+                g_object_unref(storage as gpointer);
+            }
+            decoder.set("pt", config.payload_type as u32);
+            into_floating(decoder)
+        })
+    }
+}
+
+extern "C" fn on_new_storage(rtpbin: *mut GstElement, storage: *mut GObject, _session: guint, data: gpointer){
+    unsafe{
+        unwind::guard(rtpbin as *mut GstObject, (), || {
+            let storage_time_ms = data as usize as u64;
+            let property = CString::new("size-time").unwrap();
+            g_object_set(storage as gpointer, property.as_ptr(), storage_time_ms * 1_000_000, ptr::null::<gchar>());
+        })
+    }
+}
+
+extern "C" fn free_state(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut SharedState));
+    }
+}
+
+extern "C" fn free_fec_config(data: gpointer, _closure: *mut GClosure){
+    unsafe{
+        drop(Box::from_raw(data as *mut FecConfig));
+    }
+}
+
+impl AsRef<Element> for RtpBin{
+    fn as_ref(&self) -> &Element{
+        &self.rtpbin
+    }
+}
+
+impl AsMut<Element> for RtpBin{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.rtpbin
+    }
+}
+
+impl From<RtpBin> for Element{
+    fn from(rtpbin: RtpBin) -> Element{
+        rtpbin.rtpbin
+    }
+}
+
+impl Deref for RtpBin{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.rtpbin
+    }
+}
+
+impl DerefMut for RtpBin{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.rtpbin
+    }
+}
+
+impl ::Transfer for RtpBin{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.rtpbin.transfer()
+    }
+}
+
+impl Reference for RtpBin{
+    fn reference(&self) -> RtpBin{
+        RtpBin{ rtpbin: self.rtpbin.reference(), state: self.state.clone() }
+    }
+}
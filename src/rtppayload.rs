@@ -0,0 +1,57 @@
+use bin::Bin;
+use caps::Caps;
+use element::Element;
+use elementfactory::ElementFactory;
+use pad::Pad;
+use reference::Reference;
+
+/// Creates the RTP payloader for an encoded stream with the given caps,
+/// like "video/x-h264" or "audio/x-opus"
+pub fn payloader_for(caps: &Caps, name: &str) -> Option<Element>{
+    ElementFactory::best_payloader_for(caps).and_then(|factory| factory.create(name).ok())
+}
+
+/// Creates the RTP depayloader for a stream with the given RTP caps,
+/// like "application/x-rtp,media=video,encoding-name=H264"
+pub fn depayloader_for(caps: &Caps, name: &str) -> Option<Element>{
+    ElementFactory::best_depayloader_for(caps).and_then(|factory| factory.create(name).ok())
+}
+
+/// Adds a depayloader for the RTP stream of `pad` to `bin` and links
+/// them. Meant to be called from a "pad-added" handler, for example for
+/// the receive pads of rtpbin, so streams of any codec can be received.
+///
+/// Returns the depayloader, already running, so a decoder or muxer can
+/// be linked after it
+pub fn link_depayloader(bin: &mut Bin, pad: &mut Pad) -> Option<Element>{
+    let caps = pad.query_caps(None)?;
+    let mut depayloader = depayloader_for(&caps, "")?;
+    if !bin.add(depayloader.reference()){
+        return None;
+    }
+    let linked = depayloader.static_pad("sink")
+        .map(|mut sink| pad.link(&mut sink).is_ok())
+        .unwrap_or(false);
+    if !linked{
+        bin.remove(&depayloader);
+        return None;
+    }
+    depayloader.sync_state_with_parent();
+    Some(depayloader)
+}
+
+/// Adds a payloader for the encoded stream produced by `encoder` to
+/// `bin` and links them, choosing it from the caps the encoder outputs
+pub fn link_payloader(bin: &mut Bin, encoder: &mut Element) -> Option<Element>{
+    let caps = encoder.static_pad("src")?.query_caps(None)?;
+    let mut payloader = payloader_for(&caps, "")?;
+    if !bin.add(payloader.reference()){
+        return None;
+    }
+    if !encoder.link(&mut payloader){
+        bin.remove(&payloader);
+        return None;
+    }
+    payloader.sync_state_with_parent();
+    Some(payloader)
+}
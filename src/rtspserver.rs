@@ -0,0 +1,451 @@
+use ffi::*;
+use util::*;
+use error::{Error, Result};
+use element::Element;
+use structure::{Structure, FieldValue};
+use subclass;
+use unwind;
+use ::Transfer;
+
+use std::sync::Once;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+type CreateElement = dyn Fn(&ClientInfo) -> Result<Element> + Send + Sync;
+
+static REGISTER: Once = Once::new();
+static FACTORY_TYPE: AtomicUsize = AtomicUsize::new(0);
+
+// fields of the tokens and permissions checked by the default RtspAuth
+const TOKEN_ROLE: &'static str = "media.factory.role";
+const PERM_ACCESS: &'static str = "media.factory.access";
+const PERM_CONSTRUCT: &'static str = "media.factory.construct";
+
+/// RTSP server streaming the media of its mount points to the clients
+/// that connect to it, like `rtsp://host:8554/camera`.
+///
+/// The server listens once attached to the default main context, so a
+/// MainLoop has to run for it to answer the clients.
+/// See [gst-rtsp-server](https://gstreamer.freedesktop.org/documentation/gst-rtsp-server/index.html)
+pub struct RtspServer{
+    server: *mut GstRTSPServer,
+}
+
+unsafe impl Send for RtspServer {}
+
+impl Drop for RtspServer{
+    fn drop(&mut self){
+        unsafe{
+            g_object_unref(self.server as gpointer);
+        }
+    }
+}
+
+impl RtspServer{
+    /// A server on port 8554 of all the interfaces
+    pub fn new() -> RtspServer{
+        unsafe{
+            RtspServer{ server: gst_rtsp_server_new() }
+        }
+    }
+
+    /// Address to listen on, like "127.0.0.1"
+    pub fn set_address(&mut self, address: &str){
+        let caddress = CString::new(address).unwrap();
+        unsafe{
+            gst_rtsp_server_set_address(self.server, caddress.as_ptr());
+        }
+    }
+
+    /// Port to listen on, "0" to let the system choose one, see
+    /// `bound_port`
+    pub fn set_service(&mut self, service: &str){
+        let cservice = CString::new(service).unwrap();
+        unsafe{
+            gst_rtsp_server_set_service(self.server, cservice.as_ptr());
+        }
+    }
+
+    /// Port the server listens on once attached, None before
+    pub fn bound_port(&self) -> Option<u16>{
+        unsafe{
+            let port = gst_rtsp_server_get_bound_port(self.server);
+            if port > 0 { Some(port as u16) }else{ None }
+        }
+    }
+
+    /// The paths the server serves and their media factories
+    pub fn mount_points(&self) -> MountPoints{
+        unsafe{
+            MountPoints{ mounts: gst_rtsp_server_get_mount_points(self.server) }
+        }
+    }
+
+    /// Requires the clients to authenticate with the credentials of
+    /// `auth`. The media of a MediaFactory is then only available to the
+    /// roles added to it with `add_role`
+    pub fn set_auth(&mut self, auth: &RtspAuth){
+        unsafe{
+            gst_rtsp_server_set_auth(self.server, auth.auth);
+        }
+    }
+
+    /// Starts listening on the default main context. The server keeps
+    /// answering until that context stops being iterated
+    pub fn attach(&mut self) -> Result<()>{
+        unsafe{
+            if gst_rtsp_server_attach(self.server, ptr::null_mut()) == 0{
+                Err(Error::new(0, 0, "Couldn't listen on the address of the RTSP server"))
+            }else{
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Paths of an RtspServer, like "/camera", and the factories creating the
+/// media streamed to the clients requesting them
+pub struct MountPoints{
+    mounts: *mut GstRTSPMountPoints,
+}
+
+unsafe impl Send for MountPoints {}
+
+impl Drop for MountPoints{
+    fn drop(&mut self){
+        unsafe{
+            g_object_unref(self.mounts as gpointer);
+        }
+    }
+}
+
+impl MountPoints{
+    /// Serves the media of `factory` at `path`, replacing the factory
+    /// previously mounted there
+    pub fn add_factory(&mut self, path: &str, factory: MediaFactory){
+        let cpath = CString::new(path).unwrap();
+        unsafe{
+            gst_rtsp_mount_points_add_factory(self.mounts, cpath.as_ptr(), factory.transfer());
+        }
+    }
+
+    pub fn remove_factory(&mut self, path: &str){
+        let cpath = CString::new(path).unwrap();
+        unsafe{
+            gst_rtsp_mount_points_remove_factory(self.mounts, cpath.as_ptr());
+        }
+    }
+}
+
+/// The client a MediaFactory created with `new_with` creates the pipeline
+/// for
+#[derive(Clone, Debug)]
+pub struct ClientInfo{
+    /// IP address of the client
+    pub ip: Option<String>,
+    /// URI requested by the client, like "rtsp://host:8554/camera?size=hd"
+    pub uri: Option<String>,
+    /// Role of the token the client authenticated with, see RtspAuth
+    pub role: Option<String>,
+}
+
+/// Creates the pipeline of the media streamed at a mount point.
+///
+/// The pipeline is a bin with a payloader for each stream named "pay0",
+/// "pay1"... Either described by a launch line like
+/// "( videotestsrc ! x264enc ! rtph264pay name=pay0 )", or built by a
+/// closure for each client, to stream something different depending on
+/// who asks, see `new_with`.
+pub struct MediaFactory{
+    factory: *mut GstRTSPMediaFactory,
+}
+
+unsafe impl Send for MediaFactory {}
+
+impl Drop for MediaFactory{
+    fn drop(&mut self){
+        unsafe{
+            g_object_unref(self.factory as gpointer);
+        }
+    }
+}
+
+impl MediaFactory{
+    /// A factory creating its pipelines from the launch line set with
+    /// `set_launch`
+    pub fn new() -> MediaFactory{
+        unsafe{
+            MediaFactory{ factory: gst_rtsp_media_factory_new() }
+        }
+    }
+
+    /// A factory calling `create` each time a client requests the media,
+    /// with the address of that client, the URI it requested and its role
+    /// if it authenticated. Errors
+    /// returned by `create` are answered with an error to the client.
+    ///
+    /// The media isn't shared, so each client gets its own pipeline
+    pub fn new_with<F>(create: F) -> Result<MediaFactory>
+        where F: Fn(&ClientInfo) -> Result<Element> + Send + Sync + 'static{
+        unsafe{
+            REGISTER.call_once(|| {
+                let gtype = subclass::register_type(gst_rtsp_media_factory_get_type(), "GstRsRTSPMediaFactory", (),
+                                                    Some(factory_class_init), None);
+                FACTORY_TYPE.store(gtype.unwrap_or(0) as usize, Ordering::SeqCst);
+            });
+            let gtype = FACTORY_TYPE.load(Ordering::SeqCst) as GType;
+            if gtype == 0{
+                return Err(Error::new(0, 0, "Couldn't register the RTSP media factory type"));
+            }
+            let factory = g_object_new(gtype, ptr::null::<gchar>()) as *mut GstRTSPMediaFactory;
+            let create: Box<CreateElement> = Box::new(create);
+            subclass::set_instance_impl(factory as gpointer, create);
+            gst_rtsp_media_factory_set_shared(factory, 0);
+            Ok(MediaFactory{ factory: factory })
+        }
+    }
+
+    /// Launch line of the pipeline, with the payloaders named "pay0",
+    /// "pay1"...
+    pub fn set_launch(&mut self, launch: &str){
+        let claunch = CString::new(launch).unwrap();
+        unsafe{
+            gst_rtsp_media_factory_set_launch(self.factory, claunch.as_ptr());
+        }
+    }
+
+    /// Whether all the clients share the same pipeline instead of
+    /// getting their own. Shared media is created for the first client
+    /// only
+    pub fn set_shared(&mut self, shared: bool){
+        unsafe{
+            gst_rtsp_media_factory_set_shared(self.factory, shared as gboolean);
+        }
+    }
+
+    pub fn is_shared(&self) -> bool{
+        unsafe{
+            gst_rtsp_media_factory_is_shared(self.factory) != 0
+        }
+    }
+
+    /// Lets the clients authenticated with the role `role` see the media
+    /// with a DESCRIBE if `access`, and play it if `construct`. Replaces
+    /// the permissions previously added for that role
+    pub fn add_role(&mut self, role: &str, access: bool, construct: bool){
+        let mut role = Structure::new_empty(role)
+            .field(PERM_ACCESS, access)
+            .field(PERM_CONSTRUCT, construct);
+        unsafe{
+            let permissions = gst_rtsp_media_factory_get_permissions(self.factory);
+            let permissions = if permissions == ptr::null_mut(){
+                gst_rtsp_permissions_new()
+            }else{
+                gst_mini_object_make_writable(permissions as *mut GstMiniObject) as *mut GstRTSPPermissions
+            };
+            gst_rtsp_permissions_add_role_from_structure(permissions, role.gst_structure_mut());
+            gst_rtsp_media_factory_set_permissions(self.factory, permissions);
+            gst_mini_object_unref(permissions as *mut GstMiniObject);
+        }
+    }
+}
+
+impl ::Transfer<GstRTSPMediaFactory> for MediaFactory{
+    unsafe fn transfer(self) -> *mut GstRTSPMediaFactory{
+        let factory = self.factory;
+        mem::forget(self);
+        factory
+    }
+}
+
+/// Credentials of the clients of an RtspServer and the token each one
+/// gets once authenticated, see `RtspServer::set_auth`.
+///
+/// Basic authentication sends the password in clear, so only use it
+/// over trusted networks or TLS, and digest otherwise
+pub struct RtspAuth{
+    auth: *mut GstRTSPAuth,
+    methods: GstRTSPAuthMethod,
+}
+
+unsafe impl Send for RtspAuth {}
+
+impl Drop for RtspAuth{
+    fn drop(&mut self){
+        unsafe{
+            g_object_unref(self.auth as gpointer);
+        }
+    }
+}
+
+impl RtspAuth{
+    /// Without credentials, so all the clients are refused until some are
+    /// added or a default token is set
+    pub fn new() -> RtspAuth{
+        unsafe{
+            RtspAuth{
+                auth: gst_rtsp_auth_new(),
+                methods: GST_RTSP_AUTH_NONE,
+            }
+        }
+    }
+
+    /// Accepts `user` and `password` with basic authentication, giving
+    /// the client `token`
+    pub fn add_basic(&mut self, user: &str, password: &str, token: &RtspToken){
+        let cuser = CString::new(user).unwrap();
+        let cpassword = CString::new(password).unwrap();
+        unsafe{
+            let basic = gst_rtsp_auth_make_basic(cuser.as_ptr(), cpassword.as_ptr());
+            gst_rtsp_auth_add_basic(self.auth, basic, token.token);
+            g_free(basic as gpointer);
+        }
+        self.add_method(GST_RTSP_AUTH_BASIC);
+    }
+
+    /// Accepts `user` and `password` with digest authentication, giving
+    /// the client `token`
+    pub fn add_digest(&mut self, user: &str, password: &str, token: &RtspToken){
+        let cuser = CString::new(user).unwrap();
+        let cpassword = CString::new(password).unwrap();
+        unsafe{
+            gst_rtsp_auth_add_digest(self.auth, cuser.as_ptr(), cpassword.as_ptr(), token.token);
+        }
+        self.add_method(GST_RTSP_AUTH_DIGEST);
+    }
+
+    /// Token of the clients that don't authenticate, to give anonymous
+    /// clients access to some of the media
+    pub fn set_default_token(&mut self, token: &RtspToken){
+        unsafe{
+            gst_rtsp_auth_set_default_token(self.auth, token.token);
+        }
+    }
+
+    // the server only offers the methods credentials were added for
+    fn add_method(&mut self, method: GstRTSPAuthMethod){
+        self.methods |= method;
+        unsafe{
+            gst_rtsp_auth_set_supported_methods(self.auth, self.methods);
+        }
+    }
+}
+
+/// What a client authenticated by an RtspAuth is allowed to do, matched
+/// against the roles added to each MediaFactory
+pub struct RtspToken{
+    token: *mut GstRTSPToken,
+}
+
+unsafe impl Send for RtspToken {}
+
+impl Drop for RtspToken{
+    fn drop(&mut self){
+        unsafe{
+            gst_mini_object_unref(self.token as *mut GstMiniObject);
+        }
+    }
+}
+
+impl RtspToken{
+    /// A token with the role `role`
+    pub fn new(role: &str) -> RtspToken{
+        let mut token = RtspToken::new_empty();
+        token.set(TOKEN_ROLE, role);
+        token
+    }
+
+    pub fn new_empty() -> RtspToken{
+        unsafe{
+            RtspToken{ token: gst_rtsp_token_new_empty() }
+        }
+    }
+
+    /// Sets a field, for custom checks of the token. The RtspAuths the
+    /// token was already added to keep the previous fields
+    pub fn set<V: FieldValue>(&mut self, name: &str, value: V){
+        unsafe{
+            self.token = gst_mini_object_make_writable(self.token as *mut GstMiniObject) as *mut GstRTSPToken;
+            if let Some(mut structure) = Structure::new_from_gst_structure(gst_rtsp_token_writable_structure(self.token)){
+                structure.set(name, value);
+            }
+        }
+    }
+
+    pub fn role(&self) -> Option<&str>{
+        unsafe{ token_role(self.token) }
+    }
+}
+
+unsafe fn token_role<'a>(token: *mut GstRTSPToken) -> Option<&'a str>{
+    let crole = CString::new(TOKEN_ROLE).unwrap();
+    let role = gst_rtsp_token_get_string(token, crole.as_ptr());
+    if role != ptr::null(){ Some(from_c_str!(role)) }else{ None }
+}
+
+extern "C" fn factory_class_init(g_class: gpointer, _class_data: gpointer){
+    unsafe{
+        let klass = &mut *(g_class as *mut GstRTSPMediaFactoryClass);
+        klass.create_element = Some(factory_create_element);
+    }
+}
+
+// called while answering the DESCRIBE or SETUP of a client, which is the
+// current context
+unsafe fn client_info(url: *const GstRTSPUrl) -> ClientInfo{
+    let context = gst_rtsp_context_get_current();
+    let ip = if context != ptr::null_mut() && (*context).client != ptr::null_mut(){
+        let connection = gst_rtsp_client_get_connection((*context).client);
+        if connection != ptr::null_mut(){
+            let ip = gst_rtsp_connection_get_ip(connection);
+            if ip != ptr::null(){ Some(from_c_str!(ip).to_string()) }else{ None }
+        }else{
+            None
+        }
+    }else{
+        None
+    };
+    let uri = if url != ptr::null(){
+        let curi = gst_rtsp_url_get_request_uri(url);
+        if curi != ptr::null_mut(){
+            let uri = from_c_str!(curi).to_string();
+            g_free(curi as gpointer);
+            Some(uri)
+        }else{
+            None
+        }
+    }else{
+        None
+    };
+    let role = if context != ptr::null_mut() && (*context).token != ptr::null_mut(){
+        token_role((*context).token).map(|role| role.to_string())
+    }else{
+        None
+    };
+    ClientInfo{
+        ip: ip,
+        uri: uri,
+        role: role,
+    }
+}
+
+extern "C" fn factory_create_element(factory: *mut GstRTSPMediaFactory, url: *const GstRTSPUrl) -> *mut GstElement{
+    unsafe{
+        let create = match subclass::instance_impl::<Box<CreateElement>>(factory as gpointer){
+            Some(create) => create,
+            None => return ptr::null_mut()
+        };
+        let client = client_info(url);
+        unwind::guard(factory as *mut GstObject, ptr::null_mut(), || {
+            match create(&client){
+                Ok(element) => {
+                    // the media sinks the element it's created with, so
+                    // our reference is handed over as a floating one
+                    let element = element.transfer();
+                    g_object_force_floating(element as *mut GObject);
+                    element
+                }
+                Err(_) => ptr::null_mut()
+            }
+        })
+    }
+}
@@ -0,0 +1,169 @@
+use ffi::*;
+use element::Element;
+use error::Result;
+use sample::Sample;
+use reference::Reference;
+
+use std::ffi::CString;
+use std::ops::{Deref, DerefMut};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const RTSP_LOWER_TRANS_UDP: u32 = 1 << 0;
+pub const RTSP_LOWER_TRANS_UDP_MCAST: u32 = 1 << 1;
+pub const RTSP_LOWER_TRANS_TCP: u32 = 1 << 2;
+pub const RTSP_LOWER_TRANS_HTTP: u32 = 1 << 4;
+pub const RTSP_LOWER_TRANS_TLS: u32 = 1 << 5;
+
+// seconds between the NTP prime epoch, 1900-01-01, and the unix epoch
+const PRIME_EPOCH_OFFSET: u64 = 2208988800;
+
+/// Kind of backchannel to request from the server
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Backchannel{
+    None = 0,
+    /// ONVIF audio backchannel, to send audio to the camera speaker
+    Onvif = 1,
+}
+
+/// Typed wrapper for the rtspsrc element with the ONVIF extensions used
+/// by NVRs: audio backchannel and replay of recordings.
+///
+/// The ONVIF features need GStreamer 1.14 for the backchannel and 1.18
+/// for replay.
+pub struct RtspSrc{
+    rtspsrc: Element
+}
+
+unsafe impl Sync for RtspSrc {}
+unsafe impl Send for RtspSrc {}
+
+impl RtspSrc{
+    pub fn new(name: &str, location: &str) -> Result<RtspSrc>{
+        Element::new("rtspsrc", name).map(|rtspsrc|{
+            let mut rtspsrc = RtspSrc{ rtspsrc: rtspsrc };
+            rtspsrc.set_location(location);
+            rtspsrc
+        })
+    }
+
+    pub fn new_from_element(element: Element) -> RtspSrc{
+        RtspSrc{ rtspsrc: element }
+    }
+
+    pub fn set_location(&mut self, location: &str){
+        self.rtspsrc.set("location", location);
+    }
+
+    /// Size of the jitterbuffer in milliseconds
+    pub fn set_latency(&mut self, latency_ms: u32){
+        self.rtspsrc.set("latency", latency_ms);
+    }
+
+    /// Allowed transports, a combination of the RTSP_LOWER_TRANS_*
+    /// flags
+    pub fn set_protocols(&mut self, protocols: u32){
+        self.rtspsrc.set("protocols", protocols);
+    }
+
+    pub fn set_credentials(&mut self, user: &str, password: &str){
+        self.rtspsrc.set("user-id", user);
+        self.rtspsrc.set("user-pw", password);
+    }
+
+    /// Requests a backchannel when setting up the session. With an ONVIF
+    /// backchannel the server offers an extra sendonly stream, which
+    /// rtspsrc exposes through the "select-stream" signal.
+    pub fn set_backchannel(&mut self, backchannel: Backchannel){
+        self.rtspsrc.set("backchannel", backchannel as i32);
+    }
+
+    /// Sends a sample through the backchannel stream with the given id.
+    /// The sample caps have to match the ones offered by the server for
+    /// that stream
+    pub fn push_backchannel_sample(&mut self, stream_id: u32, sample: &Sample) -> GstFlowReturn{
+        let signal = CString::new("push-backchannel-buffer").unwrap();
+        let mut ret: GstFlowReturn = GST_FLOW_OK;
+        unsafe{
+            g_signal_emit_by_name(self.rtspsrc.gst_element_mut() as gpointer, signal.as_ptr(),
+                                  stream_id as guint, sample.gst_sample(), &mut ret);
+        }
+        ret
+    }
+
+    /// ONVIF replay mode: seeks are sent to the server as absolute UTC
+    /// clock ranges, and the timestamps of the recording are kept
+    pub fn set_onvif_mode(&mut self, onvif_mode: bool){
+        self.rtspsrc.set("onvif-mode", onvif_mode as gboolean);
+    }
+
+    /// When false, sends "Rate-Control: no" so the server sends the
+    /// recording as fast as possible instead of in real time, for
+    /// downloading or for reverse playback done by the client
+    pub fn set_onvif_rate_control(&mut self, rate_control: bool){
+        self.rtspsrc.set("onvif-rate-control", rate_control as gboolean);
+    }
+
+    /// Plays the recording from `start` to `end`, or to the end of the
+    /// recording if None, at the given rate. Negative rates play
+    /// backwards from `end`. Needs ONVIF mode.
+    pub fn seek_replay(&mut self, start: SystemTime, end: Option<SystemTime>, rate: f64) -> bool{
+        let start = prime_epoch_ns(start);
+        let (stop_type, stop) = match end{
+            Some(end) => (GST_SEEK_TYPE_SET, prime_epoch_ns(end)),
+            None => (GST_SEEK_TYPE_NONE, -1),
+        };
+        self.rtspsrc.seek(rate, GST_FORMAT_TIME, GST_SEEK_FLAG_FLUSH,
+                          GST_SEEK_TYPE_SET, start, stop_type, stop)
+    }
+}
+
+// ONVIF mode expects seek positions as the time since the prime epoch
+fn prime_epoch_ns(time: SystemTime) -> i64{
+    let since_unix = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_unix.as_secs() + PRIME_EPOCH_OFFSET;
+    (secs * 1_000_000_000 + since_unix.subsec_nanos() as u64) as i64
+}
+
+impl AsRef<Element> for RtspSrc{
+    fn as_ref(&self) -> &Element{
+        &self.rtspsrc
+    }
+}
+
+impl AsMut<Element> for RtspSrc{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.rtspsrc
+    }
+}
+
+impl From<RtspSrc> for Element{
+    fn from(rtspsrc: RtspSrc) -> Element{
+        rtspsrc.rtspsrc
+    }
+}
+
+impl Deref for RtspSrc{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.rtspsrc
+    }
+}
+
+impl DerefMut for RtspSrc{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.rtspsrc
+    }
+}
+
+impl ::Transfer for RtspSrc{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.rtspsrc.transfer()
+    }
+}
+
+impl Reference for RtspSrc{
+    fn reference(&self) -> RtspSrc{
+        RtspSrc{ rtspsrc: self.rtspsrc.reference() }
+    }
+}
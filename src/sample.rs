@@ -2,10 +2,12 @@ use ffi::*;
 use caps::Caps;
 use buffer::Buffer;
 use videoframe::VideoFrame;
+use audioinfo::AudioInfo;
 use std::mem;
 use std::ptr;
 use reference::Reference;
 use miniobject::MiniObject;
+use structure::{Structure, FromFieldValue};
 
 unsafe impl Send for Sample {}
 
@@ -37,7 +39,7 @@ impl Sample{
 		unsafe{
 			let caps = gst_sample_get_caps(mem::transmute(self.gst_sample()));
         	if caps != ptr::null_mut(){
-	            Caps::new(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps)
+	            Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps)
 	        }else{
 	            None
 	        }
@@ -51,6 +53,15 @@ impl Sample{
         }
     }
 
+    /// Get the extra information of the sample, like the stream time of
+    /// the frames returned by some elements, or None when there's none
+    pub fn info(&self) -> Option<Structure>{
+        unsafe{
+            let info = gst_sample_get_info(mem::transmute(self.gst_sample()));
+            Structure::new_from_gst_structure(info as *mut GstStructure)
+        }
+    }
+
     /// Get a video frame from this sample if it contains one
     pub fn video_frame(&self) -> Option<VideoFrame>{
         let buffer = match self.buffer(){
@@ -69,6 +80,12 @@ impl Sample{
         unsafe{ VideoFrame::new(vi, buffer) }
     }
 
+    /// Get the format of the audio in this sample if its caps are raw
+    /// audio, to read the samples of its buffer with `AudioInfo::samples`
+    pub fn audio_info(&self) -> Option<AudioInfo>{
+        self.caps().and_then(|caps| caps.audio_info())
+    }
+
     pub unsafe fn gst_sample(&self) -> *const GstSample{
 		self.sample.gst_miniobject() as *const GstSample
 	}
@@ -84,6 +101,20 @@ impl ::Transfer<GstSample> for Sample{
     }
 }
 
+impl FromFieldValue for Sample{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<Sample>{
+        if g_type_check_value_holds(gvalue as *const GValue as *mut GValue, gst_sample_get_type()) == 0{
+            return None;
+        }
+        let sample = g_value_get_boxed(gvalue) as *mut GstMiniObject;
+        if sample != ptr::null_mut(){
+            Sample::new(gst_mini_object_ref(sample) as *mut GstSample)
+        }else{
+            None
+        }
+    }
+}
+
 impl Reference for Sample{
     fn reference(&self) -> Sample{
         Sample{
@@ -0,0 +1,100 @@
+use ffi::*;
+use util::*;
+use element::Element;
+
+/// Frames rendered and dropped by a video sink, to report the playback
+/// performance in the application, as returned by `SinkStats::from_sink`.
+///
+/// Any sink based on GstBaseSink, like xvimagesink or glimagesink,
+/// reports the counters and the average rate. fpsdisplaysink also
+/// measures the frame rate, as long as its "silent" property is false,
+/// which is the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SinkStats{
+    /// Frames rendered since the sink started
+    pub rendered: u64,
+    /// Frames dropped because they arrived too late to be rendered
+    pub dropped: u64,
+    /// Speed at which the sink renders relative to real time, 1.0 when it
+    /// keeps up, lower when the frames arrive late. None for fpsdisplaysink
+    pub average_rate: Option<f64>,
+    /// Frames per second measured by fpsdisplaysink over its last interval
+    pub current_fps: Option<f64>,
+    /// Frames per second measured by fpsdisplaysink since it started
+    pub average_fps: Option<f64>,
+    /// Highest `current_fps` measured by fpsdisplaysink
+    pub max_fps: Option<f64>,
+    /// Lowest `current_fps` measured by fpsdisplaysink
+    pub min_fps: Option<f64>,
+}
+
+impl SinkStats{
+    /// The stats of `sink`, a fpsdisplaysink or a sink based on
+    /// GstBaseSink. None for other elements or versions of GStreamer older
+    /// than 1.18, whose sinks don't report them
+    pub fn from_sink(sink: &Element) -> Option<SinkStats>{
+        unsafe{
+            if sink.has_property("frames-rendered"){
+                Some(fps_display_sink_stats(sink))
+            }else if g_type_check_instance_is_a(sink.gst_element() as *mut GTypeInstance, gst_base_sink_get_type()) != 0 &&
+                sink.has_property("stats"){
+                Some(base_sink_stats(sink))
+            }else{
+                None
+            }
+        }
+    }
+}
+
+unsafe fn base_sink_stats(sink: &Element) -> SinkStats{
+    let mut stats = SinkStats::default();
+    let property = CString::new("stats").unwrap();
+    let mut structure: *mut GstStructure = ptr::null_mut();
+    g_object_get(sink.gst_element() as gpointer, property.as_ptr(), &mut structure, ptr::null::<gchar>());
+    if structure != ptr::null_mut(){
+        let crendered = CString::new("rendered").unwrap();
+        let cdropped = CString::new("dropped").unwrap();
+        let caverage_rate = CString::new("average-rate").unwrap();
+        let mut average_rate = 0.;
+        gst_structure_get_uint64(structure, crendered.as_ptr(), &mut stats.rendered);
+        gst_structure_get_uint64(structure, cdropped.as_ptr(), &mut stats.dropped);
+        if gst_structure_get_double(structure, caverage_rate.as_ptr(), &mut average_rate) != 0{
+            stats.average_rate = Some(average_rate);
+        }
+        gst_structure_free(structure);
+    }
+    stats
+}
+
+unsafe fn fps_display_sink_stats(sink: &Element) -> SinkStats{
+    let rendered: u32 = sink.get("frames-rendered");
+    let dropped: u32 = sink.get("frames-dropped");
+    let max_fps: f64 = sink.get("max-fps-measured");
+    let min_fps: f64 = sink.get("min-fps-measured");
+    let mut stats = SinkStats{
+        rendered: rendered as u64,
+        dropped: dropped as u64,
+        // -1 until the first measurement
+        max_fps: if max_fps >= 0. { Some(max_fps) }else{ None },
+        min_fps: if min_fps >= 0. { Some(min_fps) }else{ None },
+        .. SinkStats::default()
+    };
+
+    // the rates are only published in the message it shows, like
+    // "rendered: 120, dropped: 0, current: 29.97, average: 30.00"
+    let property = CString::new("last-message").unwrap();
+    let mut message: *mut gchar = ptr::null_mut();
+    g_object_get(sink.gst_element() as gpointer, property.as_ptr(), &mut message, ptr::null::<gchar>());
+    if message != ptr::null_mut(){
+        for field in from_c_str!(message).split(", "){
+            let mut key_value = field.splitn(2, ": ");
+            match (key_value.next(), key_value.next().and_then(|value| value.trim().parse().ok())){
+                (Some("current"), Some(fps)) => stats.current_fps = Some(fps),
+                (Some("average"), Some(fps)) => stats.average_fps = Some(fps),
+                _ => ()
+            }
+        }
+        g_free(message as gpointer);
+    }
+    stats
+}
@@ -1,25 +1,350 @@
 use ffi::*;
 use util::*;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Values that can be stored in the fields of structures and caps
+pub trait FieldValue{
+    /// Initializes `gvalue`, which has to be zeroed, with the type and
+    /// contents of the value. The caller unsets it when done
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue);
+}
+
+/// Values that can be read from the fields of structures
+pub trait FromFieldValue: Sized{
+    /// Converts the contents of `gvalue`, None if it holds some other type
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<Self>;
+}
+
+unsafe fn type_from_name(type_name: &str) -> GType{
+    let ctype = CString::new(type_name).unwrap();
+    g_type_from_name(ctype.as_ptr())
+}
+
+unsafe fn init_gvalue(gvalue: &mut GValue, type_name: &str){
+    g_value_init(gvalue, type_from_name(type_name));
+}
+
+unsafe fn holds(gvalue: &GValue, gtype: GType) -> bool{
+    gtype != 0 && g_type_check_value_holds(gvalue as *const GValue as *mut GValue, gtype) != 0
+}
+
+macro_rules! from_gvalue{
+    ($t: ty, $type_name: expr, $get: ident) => (
+        impl FromFieldValue for $t{
+            unsafe fn from_gvalue(gvalue: &GValue) -> Option<$t>{
+                if holds(gvalue, type_from_name($type_name)){
+                    Some($get(gvalue) as $t)
+                }else{
+                    None
+                }
+            }
+        }
+    )
+}
+
+from_gvalue!(i32, "gint", g_value_get_int);
+from_gvalue!(u32, "guint", g_value_get_uint);
+from_gvalue!(i64, "gint64", g_value_get_int64);
+from_gvalue!(u64, "guint64", g_value_get_uint64);
+from_gvalue!(f32, "gfloat", g_value_get_float);
+
+impl FromFieldValue for f64{
+    /// Floats are also read as doubles
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<f64>{
+        if holds(gvalue, type_from_name("gdouble")){
+            Some(g_value_get_double(gvalue))
+        }else{
+            f32::from_gvalue(gvalue).map(|value| value as f64)
+        }
+    }
+}
+
+impl FromFieldValue for bool{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<bool>{
+        if holds(gvalue, type_from_name("gboolean")){
+            Some(g_value_get_boolean(gvalue) != 0)
+        }else{
+            None
+        }
+    }
+}
+
+impl FromFieldValue for String{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<String>{
+        if !holds(gvalue, type_from_name("gchararray")){
+            return None;
+        }
+        let string = g_value_get_string(gvalue);
+        if string == ptr::null(){
+            None
+        }else{
+            Some(from_c_str!(string).to_string())
+        }
+    }
+}
+
+/// A fraction as numerator and denominator
+impl FromFieldValue for (i32, i32){
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<(i32, i32)>{
+        if holds(gvalue, gst_fraction_get_type()){
+            Some((gst_value_get_fraction_numerator(gvalue), gst_value_get_fraction_denominator(gvalue)))
+        }else{
+            None
+        }
+    }
+}
+
+/// Lists of alternatives as in caps, arrays, and the GValueArray used by
+/// some element messages like those of `level`. None if any of the
+/// values is of a different type
+impl<T: FromFieldValue> FromFieldValue for Vec<T>{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<Vec<T>>{
+        let values: Vec<*const GValue> = if holds(gvalue, gst_value_list_get_type()){
+            (0..gst_value_list_get_size(gvalue)).map(|i| gst_value_list_get_value(gvalue, i)).collect()
+        }else if holds(gvalue, gst_value_array_get_type()){
+            (0..gst_value_array_get_size(gvalue)).map(|i| gst_value_array_get_value(gvalue, i)).collect()
+        }else if holds(gvalue, g_value_array_get_type()){
+            let array = g_value_get_boxed(gvalue) as *mut GValueArray;
+            if array == ptr::null_mut(){
+                return Some(vec![]);
+            }
+            (0..(*array).n_values).map(|i| g_value_array_get_nth(array, i) as *const GValue).collect()
+        }else{
+            return None;
+        };
+        values.into_iter().map(|value| T::from_gvalue(&*value)).collect()
+    }
+}
+
+impl FieldValue for i32{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        init_gvalue(gvalue, "gint");
+        g_value_set_int(gvalue, *self);
+    }
+}
+
+impl FieldValue for u32{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        init_gvalue(gvalue, "guint");
+        g_value_set_uint(gvalue, *self);
+    }
+}
+
+impl FieldValue for i64{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        init_gvalue(gvalue, "gint64");
+        g_value_set_int64(gvalue, *self);
+    }
+}
+
+impl FieldValue for u64{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        init_gvalue(gvalue, "guint64");
+        g_value_set_uint64(gvalue, *self);
+    }
+}
+
+impl FieldValue for bool{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        init_gvalue(gvalue, "gboolean");
+        g_value_set_boolean(gvalue, *self as gboolean);
+    }
+}
+
+impl FieldValue for f64{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        init_gvalue(gvalue, "gdouble");
+        g_value_set_double(gvalue, *self);
+    }
+}
+
+impl<'a> FieldValue for &'a str{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        let cvalue = CString::new(*self).unwrap();
+        init_gvalue(gvalue, "gchararray");
+        g_value_set_string(gvalue, cvalue.as_ptr());
+    }
+}
+
+impl FieldValue for String{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        self.as_str().to_gvalue(gvalue)
+    }
+}
+
+/// A fraction, like a framerate, as numerator and denominator
+impl FieldValue for (i32, i32){
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        g_value_init(gvalue, gst_fraction_get_type());
+        gst_value_set_fraction(gvalue, self.0, self.1);
+    }
+}
+
+/// A list of alternatives, like the formats accepted by some caps
+impl<T: FieldValue> FieldValue for Vec<T>{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        g_value_init(gvalue, gst_value_list_get_type());
+        for value in self{
+            let mut item: GValue = mem::zeroed();
+            value.to_gvalue(&mut item);
+            gst_value_list_append_value(gvalue, &item);
+            g_value_unset(&mut item);
+        }
+    }
+}
 
 pub struct Structure{
     structure: *mut GstStructure,
+    owned: bool,
+}
+
+impl Drop for Structure{
+    fn drop(&mut self){
+        if self.owned{
+            unsafe{
+                gst_structure_free(self.structure);
+            }
+        }
+    }
 }
 
 impl Structure{
     pub unsafe fn new_from_gst_structure(structure: *mut GstStructure) -> Option<Structure>{
         if structure!=ptr::null_mut(){
             Some(Structure{
-                structure: structure
+                structure: structure,
+                owned: false,
             })
         }else{
             None
         }
     }
 
+    /// Creates a new Structure that takes ownership of the passed
+    /// GstStructure and frees it when dropped
+    pub unsafe fn new_from_owned_gst_structure(structure: *mut GstStructure) -> Option<Structure>{
+        Structure::new_from_gst_structure(structure).map(|mut structure| {
+            structure.owned = true;
+            structure
+        })
+    }
+
+    /// Creates a new empty structure with the given name
+    pub fn new_empty(name: &str) -> Structure{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            Structure::new_from_owned_gst_structure(gst_structure_new_empty(cname.as_ptr())).unwrap()
+        }
+    }
+
     pub fn name(&self) -> &str{
         unsafe{
             let cname = gst_structure_get_name(self.structure);
             from_c_str!(cname)
         }
     }
+
+    /// Value of a field converted to `T`, like `get::<i32>("width")`. None
+    /// if there's no such field or it holds a different type
+    pub fn get<T: FromFieldValue>(&self, name: &str) -> Option<T>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let gvalue = gst_structure_get_value(self.structure, cname.as_ptr());
+            if gvalue == ptr::null(){
+                None
+            }else{
+                T::from_gvalue(&*gvalue)
+            }
+        }
+    }
+
+    /// Sets a field, replacing any previous value. Structures that belong
+    /// to another object, like the caps they came from, are modified in
+    /// place, so that object has to be writable
+    pub fn set<V: FieldValue>(&mut self, name: &str, value: V){
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let mut gvalue: GValue = mem::zeroed();
+            value.to_gvalue(&mut gvalue);
+            gst_structure_set_value(self.structure, cname.as_ptr(), &gvalue);
+            g_value_unset(&mut gvalue);
+        }
+    }
+
+    /// Sets a field and returns the structure for chaining, to build new
+    /// structures
+    pub fn field<V: FieldValue>(mut self, name: &str, value: V) -> Structure{
+        self.set(name, value);
+        self
+    }
+
+    pub fn has_field(&self, name: &str) -> bool{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            gst_structure_has_field(self.structure, cname.as_ptr()) != 0
+        }
+    }
+
+    pub fn remove_field(&mut self, name: &str){
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            gst_structure_remove_field(self.structure, cname.as_ptr())
+        }
+    }
+
+    pub fn n_fields(&self) -> u32{
+        unsafe{
+            gst_structure_n_fields(self.structure) as u32
+        }
+    }
+
+    /// Names of all the fields in order
+    pub fn field_names(&self) -> Vec<String>{
+        unsafe{
+            (0..self.n_fields())
+                .map(|i| from_c_str!(gst_structure_nth_field_name(self.structure, i)).to_string())
+                .collect()
+        }
+    }
+
+    pub unsafe fn gst_structure(&self) -> *const GstStructure{
+        self.structure
+    }
+
+    pub unsafe fn gst_structure_mut(&mut self) -> *mut GstStructure{
+        self.structure
+    }
+}
+
+/// Serializes the structure to a human readable string, like
+/// `level, rms=(double){ -20.5, -21.0 }`
+impl Display for Structure{
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result{
+        unsafe{
+            let c_str = gst_structure_to_string(self.structure);
+            let ret = fmt.write_str(from_c_str!(c_str));
+            g_free(c_str as gpointer);
+            ret
+        }
+    }
+}
+
+impl Debug for Structure{
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result{
+        write!(fmt, "Structure({})", self)
+    }
+}
+
+impl ::Transfer<GstStructure> for Structure{
+    /// Structures that aren't owned are copied, since the original belongs
+    /// to some other object
+    unsafe fn transfer(self) -> *mut GstStructure{
+        let structure = if self.owned{
+            self.structure
+        }else{
+            gst_structure_copy(self.structure)
+        };
+        mem::forget(self);
+        structure
+    }
 }
@@ -0,0 +1,199 @@
+use ffi::*;
+use util::*;
+use caps::Caps;
+use error::{Error, Result};
+
+use std::cell::Cell;
+
+// key of the registration in the qdata of the registered types and of
+// the rust implementation in the data of each instance
+const CLASS_KEY: &'static str = "gst-rs-subclass";
+const IMPL_KEY: &'static str = "gst-rs-subclass-impl";
+
+thread_local!{
+    // plugin being initialized by plugin_register_static in this thread,
+    // which owns the features registered meanwhile
+    static PLUGIN: Cell<*mut GstPlugin> = Cell::new(ptr::null_mut());
+}
+
+/// Description of an element registered from rust, shown by
+/// gst-inspect-1.0 and used by autoplugging through the classification,
+/// like "Codec/Decoder/Video"
+#[derive(Clone, Debug)]
+pub struct ElementMetadata<'a>{
+    pub longname: &'a str,
+    pub classification: &'a str,
+    pub description: &'a str,
+    pub author: &'a str,
+}
+
+/// Template of the pads of an element registered from rust. Always
+/// pads are created with each instance, sometimes pads by the element
+/// itself while running, like the pads of a demuxer for each stream
+#[derive(Clone)]
+pub struct PadTemplate<'a>{
+    /// Name of the pads, or a pattern like "src_%u" for sometimes pads
+    pub name: &'a str,
+    pub direction: GstPadDirection,
+    pub presence: GstPadPresence,
+    pub caps: Caps,
+}
+
+impl<'a> PadTemplate<'a>{
+    /// An always pad named "sink"
+    pub fn sink(caps: Caps) -> PadTemplate<'static>{
+        PadTemplate{ name: "sink", direction: GST_PAD_SINK, presence: GST_PAD_ALWAYS, caps: caps }
+    }
+
+    /// An always pad named "src"
+    pub fn src(caps: Caps) -> PadTemplate<'static>{
+        PadTemplate{ name: "src", direction: GST_PAD_SRC, presence: GST_PAD_ALWAYS, caps: caps }
+    }
+}
+
+/// Owned copy of the metadata and pad templates of a registered element,
+/// kept with the type to be installed by its class_init
+pub struct ClassData<T: ?Sized>{
+    longname: CString,
+    classification: CString,
+    description: CString,
+    author: CString,
+    templates: Vec<(CString, GstPadDirection, GstPadPresence, Caps)>,
+    pub new: Box<T>,
+}
+
+impl<T: ?Sized> ClassData<T>{
+    /// With always "sink" and "src" pad templates, which is what the base
+    /// classes expect
+    pub fn new(metadata: &ElementMetadata, sink_caps: &Caps, src_caps: &Caps, new: Box<T>) -> ClassData<T>{
+        ClassData::new_with_templates(metadata, &[PadTemplate::sink(sink_caps.clone()), PadTemplate::src(src_caps.clone())], new)
+    }
+
+    pub fn new_with_templates(metadata: &ElementMetadata, templates: &[PadTemplate], new: Box<T>) -> ClassData<T>{
+        ClassData{
+            longname: CString::new(metadata.longname).unwrap(),
+            classification: CString::new(metadata.classification).unwrap(),
+            description: CString::new(metadata.description).unwrap(),
+            author: CString::new(metadata.author).unwrap(),
+            templates: templates.iter().map(|template|
+                (CString::new(template.name).unwrap(), template.direction, template.presence, template.caps.clone())
+            ).collect(),
+            new: new,
+        }
+    }
+
+    /// Sets the metadata and adds the pad templates
+    pub unsafe fn install(&self, element_class: *mut GstElementClass){
+        gst_element_class_set_metadata(element_class, self.longname.as_ptr(), self.classification.as_ptr(),
+                                       self.description.as_ptr(), self.author.as_ptr());
+        for &(ref name, direction, presence, ref caps) in self.templates.iter(){
+            let template = gst_pad_template_new(name.as_ptr(), direction, presence, caps.gst_caps() as *mut GstCaps);
+            gst_element_class_add_pad_template(element_class, template);
+        }
+    }
+
+    /// Names of the templates of always pads
+    pub fn always_pads(&self) -> Vec<&CStr>{
+        self.templates.iter()
+            .filter(|&&(_, _, presence, _)| presence == GST_PAD_ALWAYS)
+            .map(|&(ref name, _, _, _)| name.as_c_str())
+            .collect()
+    }
+}
+
+/// Registers a subclass of `parent` named from `name` and an element
+/// factory `name` creating it. `class_data` is kept forever with the type
+/// and passed to `class_init`
+pub unsafe fn register_element<T: ?Sized>(parent: GType, prefix: &str, name: &str, rank: GstRank,
+                                          class_data: ClassData<T>, class_init: GClassInitFunc,
+                                          instance_init: GInstanceInitFunc) -> Result<GType>{
+    let type_name: String = name.chars().filter(|c| c.is_alphanumeric()).collect();
+    let type_name = format!("{}{}", prefix, type_name);
+    let gtype = register_type(parent, &type_name, class_data, class_init, instance_init)?;
+
+    let cname = CString::new(name).unwrap();
+    if gst_element_register(current_plugin(), cname.as_ptr(), rank as guint, gtype) == 0{
+        return Err(Error::new(0, 0, &format!("Couldn't register element {}", name)));
+    }
+    Ok(gtype)
+}
+
+/// Registers a subclass of `parent` named `type_name`, for the subclasses
+/// that aren't elements. `class_data` is kept forever with the type and
+/// passed to `class_init`
+pub unsafe fn register_type<T>(parent: GType, type_name: &str, class_data: T, class_init: GClassInitFunc,
+                               instance_init: GInstanceInitFunc) -> Result<GType>{
+    let class_data = Box::into_raw(Box::new(class_data));
+
+    let mut query: GTypeQuery = mem::zeroed();
+    g_type_query(parent, &mut query);
+    let info = GTypeInfo{
+        class_size: query.class_size as guint16,
+        base_init: None,
+        base_finalize: None,
+        class_init: class_init,
+        class_finalize: None,
+        class_data: class_data as gconstpointer,
+        instance_size: query.instance_size as guint16,
+        n_preallocs: 0,
+        instance_init: instance_init,
+        value_table: ptr::null(),
+    };
+    let ctype_name = CString::new(type_name).unwrap();
+    let gtype = g_type_register_static(parent, ctype_name.as_ptr(), &info, 0);
+    if gtype == 0{
+        drop(Box::from_raw(class_data));
+        return Err(Error::new(0, 0, &format!("Couldn't register type {}", type_name)));
+    }
+
+    // registered types live forever, and so does their class data
+    let cclass_key = CString::new(CLASS_KEY).unwrap();
+    g_type_set_qdata(gtype, g_quark_from_string(cclass_key.as_ptr()), class_data as gpointer);
+    Ok(gtype)
+}
+
+/// The plugin the element factories and typefinders registered now
+/// belong to, null outside of the init function of a plugin
+pub fn current_plugin() -> *mut GstPlugin{
+    PLUGIN.with(|plugin| plugin.get())
+}
+
+/// Runs `f` with `plugin` as the current plugin
+pub fn with_plugin<R, F: FnOnce() -> R>(plugin: *mut GstPlugin, f: F) -> R{
+    let previous = PLUGIN.with(|current| current.replace(plugin));
+    // restored even if f panics, the panic is caught by the caller
+    struct Restore(*mut GstPlugin);
+    impl Drop for Restore{
+        fn drop(&mut self){
+            PLUGIN.with(|current| current.set(self.0));
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Class data of the registered type of `g_class`
+pub unsafe fn class_data<'a, T: ?Sized>(g_class: gpointer) -> Option<&'a ClassData<T>>{
+    let gtype = (*(g_class as *mut GTypeClass)).g_type;
+    let cclass_key = CString::new(CLASS_KEY).unwrap();
+    (g_type_get_qdata(gtype, g_quark_from_string(cclass_key.as_ptr())) as *const ClassData<T>).as_ref()
+}
+
+/// Attaches the rust implementation to a new instance, dropped with it
+pub unsafe fn set_instance_impl<T>(instance: gpointer, imp: T){
+    let cimpl_key = CString::new(IMPL_KEY).unwrap();
+    g_object_set_data_full(instance as *mut GObject, cimpl_key.as_ptr(),
+                           Box::into_raw(Box::new(imp)) as gpointer, Some(free_impl::<T>));
+}
+
+/// The rust implementation of an instance
+pub unsafe fn instance_impl<'a, T>(instance: gpointer) -> Option<&'a T>{
+    let cimpl_key = CString::new(IMPL_KEY).unwrap();
+    (g_object_get_data(instance as *mut GObject, cimpl_key.as_ptr()) as *const T).as_ref()
+}
+
+extern "C" fn free_impl<T>(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut T));
+    }
+}
@@ -0,0 +1,347 @@
+use ffi::*;
+use util::*;
+
+use miniobject::MiniObject;
+use reference::Reference;
+use structure::{FieldValue, FromFieldValue};
+use value::Value;
+
+use std::fmt::{self, Debug, Display, Formatter};
+
+unsafe impl Send for TagList {}
+
+/// Metadata of a stream, like its title, artist or bitrate, as posted by
+/// demuxers and decoders in tag messages and events. Tags are identified
+/// by their name, like "title", "artist", "album", "bitrate", "date" or
+/// "datetime", and can have several values.
+/// See [GstTagList](http://gstreamer.freedesktop.org/data/doc/gstreamer/head/gstreamer/html/GstTagList.html)
+#[derive(Clone)]
+pub struct TagList{
+    taglist: MiniObject
+}
+
+impl TagList{
+    pub unsafe fn new(taglist: *mut GstTagList) -> Option<TagList>{
+        MiniObject::new_from_gst_miniobject(taglist as *mut GstMiniObject)
+            .map(|miniobject| TagList{ taglist: miniobject })
+    }
+
+    pub fn new_empty() -> TagList{
+        unsafe{
+            TagList::new(gst_tag_list_new_empty()).unwrap()
+        }
+    }
+
+    /// Parses the serialized form returned by `to_string`
+    pub fn new_from_string(string: &str) -> Option<TagList>{
+        let cstring = CString::new(string).unwrap();
+        unsafe{
+            TagList::new(gst_tag_list_new_from_string(cstring.as_ptr()))
+        }
+    }
+
+    /// The value of `tag`, like `get::<String>("title")` or
+    /// `get::<u32>("bitrate")`. Tags with several values are merged as
+    /// GStreamer does, strings are joined with commas and for other types
+    /// the first one is returned. None if the tag isn't set or is of
+    /// another type
+    pub fn get<T: FromFieldValue>(&self, tag: &str) -> Option<T>{
+        let ctag = CString::new(tag).unwrap();
+        unsafe{
+            let mut value: GValue = mem::zeroed();
+            if gst_tag_list_copy_value(&mut value, self.gst_taglist(), ctag.as_ptr()) == 0{
+                return None;
+            }
+            let ret = T::from_gvalue(&value);
+            g_value_unset(&mut value);
+            ret
+        }
+    }
+
+    /// The value number `index` of `tag`
+    pub fn get_index<T: FromFieldValue>(&self, tag: &str, index: u32) -> Option<T>{
+        let ctag = CString::new(tag).unwrap();
+        unsafe{
+            let value = gst_tag_list_get_value_index(self.gst_taglist(), ctag.as_ptr(), index);
+            if value == ptr::null(){
+                None
+            }else{
+                T::from_gvalue(&*value)
+            }
+        }
+    }
+
+    /// All the values of `tag`, like the several artists of a track
+    pub fn get_all<T: FromFieldValue>(&self, tag: &str) -> Vec<T>{
+        (0..self.size(tag)).filter_map(|index| self.get_index(tag, index)).collect()
+    }
+
+    /// Shortcut for `get::<Date>`, the type of the "date" tag
+    pub fn date(&self, tag: &str) -> Option<Date>{
+        self.get(tag)
+    }
+
+    /// Shortcut for `get::<DateTime>`, the type of the "datetime" tag
+    pub fn date_time(&self, tag: &str) -> Option<DateTime>{
+        self.get(tag)
+    }
+
+    /// Number of values of `tag`, 0 if it isn't set
+    pub fn size(&self, tag: &str) -> u32{
+        let ctag = CString::new(tag).unwrap();
+        unsafe{
+            gst_tag_list_get_tag_size(self.gst_taglist(), ctag.as_ptr())
+        }
+    }
+
+    /// Adds `value` to `tag`. With GST_TAG_MERGE_APPEND it's added after
+    /// the existing values, with GST_TAG_MERGE_REPLACE it replaces them.
+    /// The type of the value has to be that of the tag, like a String for
+    /// "title" or a u32 for "bitrate"
+    pub fn add<V: FieldValue>(&mut self, tag: &str, value: V, mode: GstTagMergeMode){
+        let ctag = CString::new(tag).unwrap();
+        unsafe{
+            let mut gvalue: GValue = mem::zeroed();
+            value.to_gvalue(&mut gvalue);
+            gst_tag_list_add_value(self.gst_taglist_mut(), mode, ctag.as_ptr(), &gvalue);
+            g_value_unset(&mut gvalue);
+        }
+    }
+
+    pub fn remove(&mut self, tag: &str){
+        let ctag = CString::new(tag).unwrap();
+        unsafe{
+            gst_tag_list_remove_tag(self.gst_taglist_mut(), ctag.as_ptr());
+        }
+    }
+
+    /// Adds the tags of `from` to this list, `mode` decides what happens
+    /// with tags present in both, like GST_TAG_MERGE_KEEP to keep the
+    /// values of this list or GST_TAG_MERGE_REPLACE to use those of `from`
+    pub fn insert(&mut self, from: &TagList, mode: GstTagMergeMode){
+        unsafe{
+            gst_tag_list_insert(self.gst_taglist_mut(), from.gst_taglist(), mode);
+        }
+    }
+
+    /// A new list with the tags of both lists, as `insert` would leave
+    /// this one
+    pub fn merge(&self, other: &TagList, mode: GstTagMergeMode) -> TagList{
+        unsafe{
+            TagList::new(gst_tag_list_merge(self.gst_taglist(), other.gst_taglist(), mode))
+                .unwrap_or(TagList::new_empty())
+        }
+    }
+
+    /// Names of the tags in the list
+    pub fn tags(&self) -> Vec<String>{
+        unsafe{
+            (0..self.n_tags()).map(|index|
+                from_c_str!(gst_tag_list_nth_tag_name(self.gst_taglist(), index)).to_string()
+            ).collect()
+        }
+    }
+
+    /// Iterates over every value of every tag as name and value pairs, a
+    /// tag with several values appears once for each of them
+    pub fn iter(&self) -> Iter<'_>{
+        Iter{
+            taglist: self,
+            tag: 0,
+            index: 0,
+        }
+    }
+
+    pub fn n_tags(&self) -> u32{
+        unsafe{
+            gst_tag_list_n_tags(self.gst_taglist()).max(0) as u32
+        }
+    }
+
+    pub fn is_empty(&self) -> bool{
+        unsafe{
+            gst_tag_list_is_empty(self.gst_taglist()) != 0
+        }
+    }
+
+    /// GST_TAG_SCOPE_STREAM for tags that only apply to one stream, like
+    /// its bitrate, GST_TAG_SCOPE_GLOBAL for those of the whole file, like
+    /// the title of the album
+    pub fn scope(&self) -> GstTagScope{
+        unsafe{
+            gst_tag_list_get_scope(self.gst_taglist())
+        }
+    }
+
+    pub fn set_scope(&mut self, scope: GstTagScope){
+        unsafe{
+            gst_tag_list_set_scope(self.gst_taglist_mut(), scope);
+        }
+    }
+
+    pub unsafe fn gst_taglist(&self) -> *const GstTagList{
+        self.taglist.gst_miniobject() as *const GstTagList
+    }
+
+    /// Makes the list writable, copying it if it's shared, for example
+    /// with the message it was taken from
+    pub unsafe fn gst_taglist_mut(&mut self) -> *mut GstTagList{
+        let taglist = ptr::read(&self.taglist);
+        ptr::write(&mut self.taglist, taglist.make_writable());
+        self.taglist.gst_miniobject_mut() as *mut GstTagList
+    }
+}
+
+impl PartialEq for TagList{
+    fn eq(&self, other: &TagList) -> bool{
+        unsafe{
+            gst_tag_list_is_equal(self.gst_taglist(), other.gst_taglist()) != 0
+        }
+    }
+}
+
+impl Display for TagList{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        unsafe{
+            let string = gst_tag_list_to_string(self.gst_taglist());
+            let ret = f.write_str(from_c_str!(string));
+            g_free(string as gpointer);
+            ret
+        }
+    }
+}
+
+impl Debug for TagList{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        Display::fmt(self, f)
+    }
+}
+
+impl ::Transfer<GstTagList> for TagList{
+    unsafe fn transfer(self) -> *mut GstTagList{
+        self.taglist.transfer() as *mut GstTagList
+    }
+}
+
+impl Reference for TagList{
+    fn reference(&self) -> TagList{
+        TagList{
+            taglist: self.taglist.reference()
+        }
+    }
+}
+
+pub struct Iter<'a>{
+    taglist: &'a TagList,
+    tag: u32,
+    index: u32,
+}
+
+impl<'a> Iterator for Iter<'a>{
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<(String, Value)>{
+        unsafe{
+            while self.tag < self.taglist.n_tags(){
+                let name = gst_tag_list_nth_tag_name(self.taglist.gst_taglist(), self.tag);
+                let value = gst_tag_list_get_value_index(self.taglist.gst_taglist(), name, self.index);
+                if value != ptr::null(){
+                    self.index += 1;
+                    return Some((from_c_str!(name).to_string(), Value::new_from_gvalue(&*value)));
+                }
+                self.tag += 1;
+                self.index = 0;
+            }
+            None
+        }
+    }
+}
+
+/// A calendar date, the type of the "date" tag
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date{
+    pub year: u16,
+    /// 1 to 12
+    pub month: u8,
+    pub day: u8,
+}
+
+impl FromFieldValue for Date{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<Date>{
+        if g_type_check_value_holds(gvalue as *const GValue as *mut GValue, g_date_get_type()) == 0{
+            return None;
+        }
+        let date = g_value_get_boxed(gvalue) as *const GDate;
+        if date == ptr::null() || g_date_valid(date) == 0{
+            return None;
+        }
+        Some(Date{
+            year: g_date_get_year(date),
+            month: g_date_get_month(date) as u8,
+            day: g_date_get_day(date),
+        })
+    }
+}
+
+impl FieldValue for Date{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        g_value_init(gvalue, g_date_get_type());
+        g_value_take_boxed(gvalue, g_date_new_dmy(self.day, self.month as GDateMonth, self.year) as gconstpointer);
+    }
+}
+
+/// A date and time, the type of the "datetime" tag. Except for the year
+/// any field can be missing, like the time of a recording of which only
+/// the day is known
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DateTime{
+    pub year: i32,
+    /// 1 to 12
+    pub month: Option<i32>,
+    pub day: Option<i32>,
+    /// Hour and minute
+    pub time: Option<(i32, i32)>,
+    /// Seconds including the fraction of a second
+    pub seconds: Option<f64>,
+    /// Offset from UTC in hours
+    pub tz_offset: f32,
+}
+
+impl FromFieldValue for DateTime{
+    unsafe fn from_gvalue(gvalue: &GValue) -> Option<DateTime>{
+        if g_type_check_value_holds(gvalue as *const GValue as *mut GValue, gst_date_time_get_type()) == 0{
+            return None;
+        }
+        let datetime = g_value_get_boxed(gvalue) as *const GstDateTime;
+        if datetime == ptr::null() || gst_date_time_has_year(datetime) == 0{
+            return None;
+        }
+        let has = |has: unsafe extern "C" fn(*const GstDateTime) -> gboolean| has(datetime) != 0;
+        Some(DateTime{
+            year: gst_date_time_get_year(datetime),
+            month: if has(gst_date_time_has_month){ Some(gst_date_time_get_month(datetime)) }else{ None },
+            day: if has(gst_date_time_has_day){ Some(gst_date_time_get_day(datetime)) }else{ None },
+            time: if has(gst_date_time_has_time){
+                Some((gst_date_time_get_hour(datetime), gst_date_time_get_minute(datetime)))
+            }else{
+                None
+            },
+            seconds: if has(gst_date_time_has_second){
+                Some(gst_date_time_get_second(datetime) as f64 + gst_date_time_get_microsecond(datetime) as f64 / 1_000_000.)
+            }else{
+                None
+            },
+            tz_offset: if has(gst_date_time_has_time){ gst_date_time_get_time_zone_offset(datetime) }else{ 0. },
+        })
+    }
+}
+
+impl FieldValue for DateTime{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        let (hour, minute) = self.time.unwrap_or((-1, -1));
+        let datetime = gst_date_time_new(self.tz_offset, self.year, self.month.unwrap_or(-1),
+                                         self.day.unwrap_or(-1), hour, minute, self.seconds.unwrap_or(-1.));
+        g_value_init(gvalue, gst_date_time_get_type());
+        g_value_take_boxed(gvalue, datetime as gconstpointer);
+    }
+}
@@ -0,0 +1,408 @@
+use ffi::*;
+use appsink::{self, AppSink};
+use caps::Caps;
+use element::Element;
+use error::Error;
+use sample::Sample;
+
+use std::sync::mpsc::{RecvError, TryRecvError};
+
+const TIMED_METADATA_CAPS: &'static str = "meta/x-klv; meta/x-id3; application/x-id3";
+
+/// Format of a timed metadata stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetadataKind{
+    /// SMPTE 336M KLV, used for example by drones for MISB ST 0601
+    /// telemetry
+    Klv,
+    /// ID3 tags, used by broadcasters and HLS for timed metadata
+    Id3,
+}
+
+impl MetadataKind{
+    /// Kind of metadata a stream with the given caps contains
+    pub fn from_caps(caps: &Caps) -> Option<MetadataKind>{
+        caps.structure(0).and_then(|structure| match structure.name(){
+            "meta/x-klv" => Some(MetadataKind::Klv),
+            "meta/x-id3" | "application/x-id3" => Some(MetadataKind::Id3),
+            _ => None
+        })
+    }
+}
+
+/// A metadata packet and the time it applies to
+pub struct TimedMetadata{
+    kind: MetadataKind,
+    pts: Option<u64>,
+    running_time: Option<u64>,
+    data: Vec<u8>,
+}
+
+impl TimedMetadata{
+    fn new(kind: MetadataKind, sample: &Sample) -> Option<TimedMetadata>{
+        let buffer = sample.buffer()?;
+        let pts = unsafe{ (*buffer.gst_buffer()).pts };
+        let pts = if pts != GST_CLOCK_TIME_NONE { Some(pts) } else { None };
+        let running_time = pts.and_then(|pts| unsafe{
+            let mut segment = sample.segment();
+            let running_time = gst_segment_to_running_time(&mut segment, GST_FORMAT_TIME, pts);
+            if running_time != GST_CLOCK_TIME_NONE { Some(running_time) } else { None }
+        });
+        let data = buffer.map_read()?.to_vec();
+        Some(TimedMetadata{
+            kind: kind,
+            pts: pts,
+            running_time: running_time,
+            data: data,
+        })
+    }
+
+    pub fn kind(&self) -> MetadataKind{
+        self.kind
+    }
+
+    /// Presentation timestamp of the packet in nanoseconds, the same
+    /// timeline as the PTS of the video frames from the same demuxer
+    pub fn pts(&self) -> Option<u64>{
+        self.pts
+    }
+
+    /// Running time of the packet in nanoseconds, which can be compared
+    /// with the running time of the frames from other sinks
+    pub fn running_time(&self) -> Option<u64>{
+        self.running_time
+    }
+
+    pub fn data(&self) -> &[u8]{
+        &self.data
+    }
+
+    /// Splits KLV data into its items
+    pub fn klv_items<'a>(&'a self) -> Vec<KlvItem<'a>>{
+        klv_items(&self.data)
+    }
+
+    /// Splits ID3 data into its frames
+    pub fn id3_frames<'a>(&'a self) -> Vec<Id3Frame<'a>>{
+        id3_frames(&self.data)
+    }
+}
+
+/// Receives the packets of a KLV or ID3 metadata stream, usually linked
+/// to a demuxer pad with those caps, with their timestamps.
+///
+/// By default the sink syncs to the clock so packets are delivered at the
+/// same time the video frames with the same timestamps are rendered,
+/// either to `recv` or to the closure set with `on_metadata`.
+pub struct TimedMetadataSink{
+    appsink: AppSink,
+}
+
+impl TimedMetadataSink{
+    pub fn new(name: &str) -> Result<TimedMetadataSink, Error>{
+        Element::new("appsink", name).map(TimedMetadataSink::new_from_element)
+    }
+
+    pub fn new_from_element(element: Element) -> TimedMetadataSink{
+        let mut appsink = AppSink::new_from_element(element);
+        appsink.set_caps(Caps::from_string(TIMED_METADATA_CAPS).unwrap());
+        // metadata streams are sparse, don't hold the pipeline preroll
+        appsink.set("async", 0 as gboolean);
+        TimedMetadataSink{ appsink: appsink }
+    }
+
+    /// True if a stream with the given caps, for example the ones of a
+    /// new demuxer pad, can be linked to this sink
+    pub fn accepts(caps: &Caps) -> bool{
+        MetadataKind::from_caps(caps).is_some()
+    }
+
+    /// Deliver packets as soon as they arrive instead of at their
+    /// presentation time
+    pub fn set_sync(&mut self, sync: bool){
+        self.appsink.set("sync", sync as gboolean);
+    }
+
+    /// Blocks until the next packet. Returns an error once the stream
+    /// reached EOS or the pipeline was destroyed.
+    pub fn recv(&self) -> Result<TimedMetadata, RecvError>{
+        loop{
+            match self.appsink.recv()?{
+                appsink::Message::NewSample(sample) => {
+                    if let Some(metadata) = TimedMetadataSink::from_sample(&sample){
+                        return Ok(metadata);
+                    }
+                }
+                appsink::Message::NewPreroll(_) => (),
+                appsink::Message::Eos => return Err(RecvError),
+            }
+        }
+    }
+
+    /// Returns the next packet if one is available without blocking
+    pub fn try_recv(&self) -> Result<TimedMetadata, TryRecvError>{
+        loop{
+            match self.appsink.try_recv()?{
+                appsink::Message::NewSample(sample) => {
+                    if let Some(metadata) = TimedMetadataSink::from_sample(&sample){
+                        return Ok(metadata);
+                    }
+                }
+                appsink::Message::NewPreroll(_) => (),
+                appsink::Message::Eos => return Err(TryRecvError::Disconnected),
+            }
+        }
+    }
+
+    /// Calls `f` from the streaming thread with every packet when its
+    /// presentation time is reached, or as soon as it arrives if not
+    /// syncing, instead of delivering it to `recv`. `f` blocking delays
+    /// the following packets
+    pub fn on_metadata<F: FnMut(TimedMetadata) + Send + 'static>(&mut self, mut f: F){
+        self.appsink.on_new_sample(move |sample|{
+            if let Some(metadata) = TimedMetadataSink::from_sample(&sample){
+                f(metadata);
+            }
+            GST_FLOW_OK
+        });
+    }
+
+    /// Calls `f` with every packet until EOS, on the calling thread
+    pub fn for_each<F: FnMut(TimedMetadata)>(&self, mut f: F){
+        while let Ok(metadata) = self.recv(){
+            f(metadata);
+        }
+    }
+
+    fn from_sample(sample: &Sample) -> Option<TimedMetadata>{
+        let kind = sample.caps().and_then(|caps| MetadataKind::from_caps(&caps))?;
+        TimedMetadata::new(kind, sample)
+    }
+}
+
+impl AsRef<AppSink> for TimedMetadataSink{
+    fn as_ref(&self) -> &AppSink{
+        &self.appsink
+    }
+}
+
+impl AsMut<AppSink> for TimedMetadataSink{
+    fn as_mut(&mut self) -> &mut AppSink{
+        &mut self.appsink
+    }
+}
+
+impl From<TimedMetadataSink> for Element{
+    fn from(sink: TimedMetadataSink) -> Element{
+        sink.appsink.into()
+    }
+}
+
+/// A key-length-value item
+pub struct KlvItem<'a>{
+    /// 16 bytes SMPTE universal label
+    pub key: &'a [u8],
+    pub value: &'a [u8],
+}
+
+/// Splits SMPTE 336M KLV data with 16 byte keys and BER encoded lengths
+/// into its items. Stops at the first malformed item.
+pub fn klv_items<'a>(data: &'a [u8]) -> Vec<KlvItem<'a>>{
+    let mut items = Vec::new();
+    let mut pos = 0;
+    while pos + 17 <= data.len(){
+        let key = &data[pos..pos + 16];
+        pos += 16;
+        let first = data[pos] as usize;
+        pos += 1;
+        let len = if first & 0x80 == 0{
+            first
+        }else{
+            let n = first & 0x7f;
+            if n == 0 || n > 8 || pos + n > data.len(){
+                break;
+            }
+            let len = data[pos..pos + n].iter().fold(0u64, |len, &b| len << 8 | b as u64) as usize;
+            pos += n;
+            len
+        };
+        // lengths of up to 8 bytes can overflow the position
+        if pos.checked_add(len).map_or(true, |end| end > data.len()){
+            break;
+        }
+        items.push(KlvItem{ key: key, value: &data[pos..pos + len] });
+        pos += len;
+    }
+    items
+}
+
+/// A frame of an ID3v2 tag
+pub struct Id3Frame<'a>{
+    /// 4 characters identifier, like "TXXX" or "PRIV"
+    pub id: &'a str,
+    pub data: &'a [u8],
+}
+
+fn syncsafe(bytes: &[u8]) -> usize{
+    bytes.iter().fold(0, |value, &b| value << 7 | (b & 0x7f) as usize)
+}
+
+/// Splits an ID3v2.3 or v2.4 tag into its frames. Stops at the padding
+/// or at the first malformed frame.
+pub fn id3_frames<'a>(data: &'a [u8]) -> Vec<Id3Frame<'a>>{
+    let mut frames = Vec::new();
+    if data.len() < 10 || &data[0..3] != b"ID3"{
+        return frames;
+    }
+    let version = data[3];
+    let flags = data[5];
+    let end = (10 + syncsafe(&data[6..10])).min(data.len());
+    let mut pos = 10;
+    if flags & 0x40 != 0 && pos + 4 <= end{
+        // skip the extended header
+        pos += if version >= 4 { syncsafe(&data[pos..pos + 4]) } else {
+            4 + data[pos..pos + 4].iter().fold(0, |value, &b| value << 8 | b as usize)
+        };
+    }
+    while pos + 10 <= end && data[pos] != 0{
+        let id = match ::std::str::from_utf8(&data[pos..pos + 4]){
+            Ok(id) => id,
+            Err(_) => break
+        };
+        let size = if version >= 4{
+            syncsafe(&data[pos + 4..pos + 8])
+        }else{
+            data[pos + 4..pos + 8].iter().fold(0, |value, &b| value << 8 | b as usize)
+        };
+        pos += 10;
+        if pos + size > end{
+            break;
+        }
+        frames.push(Id3Frame{ id: id, data: &data[pos..pos + size] });
+        pos += size;
+    }
+    frames
+}
+
+#[cfg(test)]
+mod tests{
+    use super::{klv_items, id3_frames};
+
+    const KEY: [u8; 16] = [0x06, 0x0e, 0x2b, 0x34, 0x02, 0x0b, 0x01, 0x01,
+                           0x0e, 0x01, 0x03, 0x01, 0x01, 0x00, 0x00, 0x00];
+
+    fn klv(length: &[u8], value: &[u8]) -> Vec<u8>{
+        let mut item = KEY.to_vec();
+        item.extend_from_slice(length);
+        item.extend_from_slice(value);
+        item
+    }
+
+    #[test]
+    fn klv_ber_lengths(){
+        let mut data = klv(&[0x03], &[1, 2, 3]);
+        data.extend(klv(&[0x82, 0x01, 0x00], &[7; 256]));
+        data.extend(klv(&[0x88, 0, 0, 0, 0, 0, 0, 0, 0x02], &[4, 5]));
+        let items = klv_items(&data);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].key, &KEY[..]);
+        assert_eq!(items[0].value, &[1, 2, 3][..]);
+        assert_eq!(items[1].value.len(), 256);
+        assert_eq!(items[2].value, &[4, 5][..]);
+    }
+
+    #[test]
+    fn klv_malformed_lengths(){
+        let first = klv(&[0x01], &[1]);
+
+        // 8 bytes length overflowing the position
+        let mut data = first.clone();
+        data.extend(klv(&[0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], &[0; 4]));
+        assert_eq!(klv_items(&data).len(), 1);
+
+        // more than 8 bytes of length
+        let mut data = first.clone();
+        data.extend(klv(&[0x89, 0, 0, 0, 0, 0, 0, 0, 0, 0x01], &[0]));
+        assert_eq!(klv_items(&data).len(), 1);
+
+        // value past the end
+        let mut data = first.clone();
+        data.extend(klv(&[0x81, 0x10], &[0; 4]));
+        assert_eq!(klv_items(&data).len(), 1);
+
+        // length bytes past the end
+        let mut data = first.clone();
+        data.extend(klv(&[0x84, 0x00], &[]));
+        assert_eq!(klv_items(&data).len(), 1);
+    }
+
+    fn syncsafe(size: usize) -> [u8; 4]{
+        [(size >> 21) as u8 & 0x7f, (size >> 14) as u8 & 0x7f, (size >> 7) as u8 & 0x7f, size as u8 & 0x7f]
+    }
+
+    fn id3(version: u8, extended_header: &[u8], frames: &[(&str, usize)], padding: usize) -> Vec<u8>{
+        let mut body = extended_header.to_vec();
+        for &(id, size) in frames{
+            body.extend_from_slice(id.as_bytes());
+            if version >= 4{
+                body.extend_from_slice(&syncsafe(size));
+            }else{
+                body.extend_from_slice(&[(size >> 24) as u8, (size >> 16) as u8, (size >> 8) as u8, size as u8]);
+            }
+            body.extend_from_slice(&[0, 0]);
+            body.extend(vec![0x41; size]);
+        }
+        body.extend(vec![0; padding]);
+        let flags = if extended_header.is_empty() { 0 } else { 0x40 };
+        let mut tag = vec![b'I', b'D', b'3', version, 0, flags];
+        tag.extend_from_slice(&syncsafe(body.len()));
+        tag.extend(body);
+        tag
+    }
+
+    #[test]
+    fn id3_syncsafe_sizes(){
+        // 200 is 0x00 0x00 0x01 0x48 syncsafe, 0x148 if misread
+        let tag = id3(4, &[], &[("PRIV", 200), ("TXXX", 3)], 10);
+        let frames = id3_frames(&tag);
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].id, frames[0].data.len()), ("PRIV", 200));
+        assert_eq!((frames[1].id, frames[1].data.len()), ("TXXX", 3));
+
+        let tag = id3(3, &[], &[("PRIV", 200), ("TXXX", 3)], 10);
+        let frames = id3_frames(&tag);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data.len(), 200);
+    }
+
+    #[test]
+    fn id3_extended_header(){
+        // v2.4: syncsafe size including the size itself
+        let tag = id3(4, &[0, 0, 0, 6, 1, 0], &[("PRIV", 4)], 0);
+        let frames = id3_frames(&tag);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].id, "PRIV");
+
+        // v2.3: size not including the size itself
+        let tag = id3(3, &[0, 0, 0, 6, 0, 0, 0, 0, 0, 0], &[("PRIV", 4)], 0);
+        let frames = id3_frames(&tag);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].id, "PRIV");
+    }
+
+    #[test]
+    fn id3_truncated(){
+        let mut tag = id3(4, &[], &[("PRIV", 4), ("TXXX", 100)], 0);
+        tag.truncate(60);
+        let frames = id3_frames(&tag);
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].id, "PRIV");
+
+        // tag size past the end of the data
+        let tag = id3(4, &[], &[("PRIV", 4)], 0);
+        assert_eq!(id3_frames(&tag[..tag.len() - 1]).len(), 0);
+
+        assert_eq!(id3_frames(b"ID3").len(), 0);
+        assert_eq!(id3_frames(b"TAG\x04\x00\x00\x00\x00\x00\x00").len(), 0);
+    }
+}
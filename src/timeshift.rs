@@ -0,0 +1,384 @@
+use ffi::*;
+use element::Element;
+use error::Result;
+use appsrc::AppSrc;
+use sample::Sample;
+use caps::Caps;
+use reference::Reference;
+use unwind;
+use ::Transfer;
+
+use std::ptr;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Condvar};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// latency announced by the output, so the buffers pushed at their running
+// time still have time to be decoded before the sink renders them
+const OUTPUT_LATENCY: i64 = 200_000_000;
+
+struct Entry{
+    sample: Sample,
+    time: GstClockTime,
+    keyframe: bool,
+}
+
+struct State{
+    entries: VecDeque<Entry>,
+    window: GstClockTime,
+    // index of the next entry to output
+    next: usize,
+    // time of the last entry sent to the output
+    position: Option<GstClockTime>,
+    paused: bool,
+    resync: bool,
+    eos: bool,
+    stopped: bool,
+}
+
+type Shared = Arc<(Mutex<State>, Condvar)>;
+
+impl State{
+    fn newest(&self) -> Option<GstClockTime>{
+        self.entries.back().map(|entry| entry.time)
+    }
+
+    fn oldest_keyframe(&self) -> usize{
+        self.entries.iter().position(|entry| entry.keyframe).unwrap_or(self.entries.len())
+    }
+
+    // last keyframe at or before `time`, or the oldest one if there's none
+    fn keyframe_before(&self, time: GstClockTime) -> usize{
+        self.entries.iter().rposition(|entry| entry.keyframe && entry.time <= time)
+            .unwrap_or_else(|| self.oldest_keyframe())
+    }
+
+    fn seek(&mut self, next: usize){
+        self.next = next;
+        self.resync = true;
+    }
+
+    fn push(&mut self, entry: Entry){
+        self.entries.push_back(entry);
+        let newest = entry_time(self.entries.back());
+        let mut overrun = false;
+        while self.entries.len() > 1 && newest.saturating_sub(entry_time(self.entries.front())) > self.window{
+            self.entries.pop_front();
+            if self.next > 0{
+                self.next -= 1;
+            }else{
+                overrun = true;
+            }
+        }
+        // the output fell out of the window while paused, restart it from
+        // the oldest point that can be decoded
+        if overrun{
+            let next = self.oldest_keyframe();
+            self.seek(next);
+        }
+    }
+}
+
+fn entry_time(entry: Option<&Entry>) -> GstClockTime{
+    entry.map(|entry| entry.time).unwrap_or(0)
+}
+
+/// Time-shift buffer for live streams: keeps the last `window`
+/// nanoseconds of a stream in memory so playback can be paused, rewound
+/// within that window and brought back to live, while the live stream
+/// keeps being received.
+///
+/// The input element, an appsink, goes at the end of the live pipeline,
+/// usually after a parser so encoded data is buffered. The output
+/// element, an appsrc, starts the playback pipeline, which can be the same
+/// one. The output sends the buffers at the pace they were received and
+/// retimestamps them to the running time of its pipeline, so seeking in
+/// the buffer doesn't need seeking the pipelines.
+///
+/// Rewinding and returning to live resume the playback on a keyframe.
+pub struct TimeShift{
+    input: Element,
+    output: Element,
+    shared: Shared,
+    thread: Option<JoinHandle<()>>,
+}
+
+unsafe impl Sync for TimeShift {}
+unsafe impl Send for TimeShift {}
+
+impl TimeShift{
+    /// Creates a time-shift buffer keeping `window` nanoseconds of the
+    /// stream
+    pub fn new(name: &str, window: GstClockTime) -> Result<TimeShift>{
+        let mut input = Element::new("appsink", &format!("{}_input", name))?;
+        let mut output = Element::new("appsrc", &format!("{}_output", name))?;
+        input.set("sync", 0 as gboolean);
+        input.set("async", 0 as gboolean);
+        output.set("is-live", 1 as gboolean);
+        output.set("format", GST_FORMAT_TIME as i32);
+        output.set("min-latency", OUTPUT_LATENCY);
+
+        let shared: Shared = Arc::new((Mutex::new(State{
+            entries: VecDeque::new(),
+            window: window,
+            next: 0,
+            position: None,
+            paused: false,
+            resync: true,
+            eos: false,
+            stopped: false,
+        }), Condvar::new()));
+
+        unsafe{
+            let mut callbacks = GstAppSinkCallbacks{
+                eos: Some(on_eos),
+                new_preroll: None,
+                new_sample: Some(on_new_sample),
+                _gst_reserved: [ptr::null_mut(); 4]
+            };
+            let data = Box::new(shared.clone());
+            gst_app_sink_set_callbacks(input.gst_element_mut() as *mut GstAppSink, &mut callbacks,
+                                       Box::into_raw(data) as gpointer, Some(free_shared));
+        }
+
+        let appsrc = AppSrc::new_from_element(output.reference());
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || output_loop(appsrc, thread_shared));
+
+        Ok(TimeShift{
+            input: input,
+            output: output,
+            shared: shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// The appsink receiving the live stream
+    pub fn input(&self) -> Element{
+        self.input.reference()
+    }
+
+    /// The appsrc producing the time-shifted stream
+    pub fn output(&self) -> Element{
+        self.output.reference()
+    }
+
+    fn with_state<F: FnOnce(&mut State) -> U, U>(&self, f: F) -> U{
+        let &(ref lock, ref cond) = &*self.shared;
+        let ret = f(&mut lock.lock().unwrap());
+        cond.notify_all();
+        ret
+    }
+
+    /// Stops the output while the live stream keeps being buffered
+    pub fn pause(&mut self){
+        self.with_state(|state| state.paused = true)
+    }
+
+    /// Continues the output from where it was paused, or from the oldest
+    /// buffered keyframe if it was paused longer than the window
+    pub fn resume(&mut self){
+        self.with_state(|state| if state.paused{
+            state.paused = false;
+            state.resync = true;
+        })
+    }
+
+    pub fn is_paused(&self) -> bool{
+        self.with_state(|state| state.paused)
+    }
+
+    /// Moves the output `time` nanoseconds back, limited to the start of
+    /// the window
+    pub fn seek_back(&mut self, time: GstClockTime){
+        self.with_state(|state|{
+            let current = state.position.or(state.newest()).unwrap_or(0);
+            let next = state.keyframe_before(current.saturating_sub(time));
+            state.seek(next);
+        })
+    }
+
+    /// Moves the output `time` nanoseconds forward, returning to live if
+    /// that goes past it
+    pub fn seek_forward(&mut self, time: GstClockTime){
+        self.with_state(|state|{
+            let newest = state.newest().unwrap_or(0);
+            let current = state.position.unwrap_or(newest);
+            let next = state.keyframe_before(current.saturating_add(time).min(newest));
+            state.seek(next);
+        })
+    }
+
+    /// Sets the output to play `delay` nanoseconds behind live
+    pub fn set_delay(&mut self, delay: GstClockTime){
+        self.with_state(|state|{
+            let newest = state.newest().unwrap_or(0);
+            let next = state.keyframe_before(newest.saturating_sub(delay));
+            state.seek(next);
+        })
+    }
+
+    /// Returns the output to live, from the latest keyframe, and resumes it
+    /// if paused
+    pub fn go_live(&mut self){
+        self.with_state(|state|{
+            let next = state.keyframe_before(GST_CLOCK_TIME_NONE);
+            state.seek(next);
+            state.paused = false;
+        })
+    }
+
+    /// How far behind live the output is, in nanoseconds
+    pub fn delay(&self) -> GstClockTime{
+        self.with_state(|state| match (state.newest(), state.position){
+            (Some(newest), Some(position)) => newest.saturating_sub(position),
+            _ => 0
+        })
+    }
+
+    /// True when the output is sending the buffers as they arrive
+    pub fn is_live(&self) -> bool{
+        self.with_state(|state| !state.paused && state.next >= state.entries.len())
+    }
+
+    /// How much of the stream is buffered, in nanoseconds. It's the
+    /// farthest the output can go back, and grows up to the window
+    pub fn available(&self) -> GstClockTime{
+        self.with_state(|state| state.newest().unwrap_or(0).saturating_sub(entry_time(state.entries.front())))
+    }
+
+    pub fn set_window(&mut self, window: GstClockTime){
+        self.with_state(|state| state.window = window)
+    }
+
+    pub fn window(&self) -> GstClockTime{
+        self.with_state(|state| state.window)
+    }
+}
+
+impl Drop for TimeShift{
+    fn drop(&mut self){
+        self.with_state(|state| state.stopped = true);
+        if let Some(thread) = self.thread.take(){
+            thread.join().ok();
+        }
+    }
+}
+
+fn output_loop(mut appsrc: AppSrc, shared: Shared){
+    let &(ref lock, ref cond) = &*shared;
+    let mut state = lock.lock().unwrap();
+    // stream time of the buffer the output was resynced on, and the
+    // running time it was sent at
+    let mut anchor: Option<(GstClockTime, GstClockTime)> = None;
+    let mut discont = false;
+    let mut eos_sent = false;
+    let mut caps: Option<Caps> = None;
+    while !state.stopped{
+        if state.resync{
+            state.resync = false;
+            anchor = None;
+            discont = true;
+        }
+        if state.paused || state.next >= state.entries.len(){
+            if state.eos && !state.paused && !eos_sent{
+                appsrc.end_of_stream();
+                eos_sent = true;
+            }
+            state = cond.wait(state).unwrap();
+            continue;
+        }
+
+        let now = match appsrc.running_time(){
+            Some(now) => now,
+            None => {
+                // not playing yet
+                state = cond.wait_timeout(state, Duration::from_millis(100)).unwrap().0;
+                continue;
+            }
+        };
+        let (sample, time) = {
+            let entry = &state.entries[state.next];
+            (entry.sample.clone(), entry.time)
+        };
+        let (anchor_time, anchor_running_time) = *anchor.get_or_insert((time, now));
+        let target = (time + anchor_running_time).saturating_sub(anchor_time);
+        if target > now{
+            state = cond.wait_timeout(state, Duration::from_nanos(target - now)).unwrap().0;
+            continue;
+        }
+        state.next += 1;
+        state.position = Some(time);
+        drop(state);
+
+        if let Some(sample_caps) = sample.caps(){
+            let changed = caps.as_ref().map(|caps| unsafe{ caps.gst_caps() != sample_caps.gst_caps() }).unwrap_or(true);
+            if changed{
+                appsrc.set_caps(&sample_caps);
+                caps = Some(sample_caps);
+            }
+        }
+        if let Some(buffer) = sample.buffer(){
+            unsafe{
+                let buffer = gst_mini_object_make_writable(buffer.transfer() as *mut GstMiniObject) as *mut GstBuffer;
+                let retimestamp = |ts: GstClockTime| if ts == GST_CLOCK_TIME_NONE{
+                    ts
+                }else{
+                    (ts + anchor_running_time).saturating_sub(anchor_time)
+                };
+                (*buffer).pts = retimestamp((*buffer).pts);
+                (*buffer).dts = retimestamp((*buffer).dts);
+                if discont{
+                    (*buffer).mini_object.flags |= GST_BUFFER_FLAG_DISCONT;
+                    discont = false;
+                }
+                gst_app_src_push_buffer(appsrc.gst_appsrc_mut(), buffer);
+            }
+        }
+        state = lock.lock().unwrap();
+    }
+}
+
+extern "C" fn on_new_sample(appsink: *mut GstAppSink, data: gpointer) -> GstFlowReturn{
+    unsafe{
+        unwind::guard(appsink as *mut GstObject, GST_FLOW_ERROR, || {
+            let &(ref lock, ref cond) = &**(data as *const Shared);
+            let sample = match Sample::new(gst_app_sink_pull_sample(appsink)){
+                Some(sample) => sample,
+                None => return GST_FLOW_FLUSHING
+            };
+            let (pts, dts, keyframe) = match sample.buffer(){
+                Some(buffer) => ((*buffer.gst_buffer()).pts, (*buffer.gst_buffer()).dts, buffer.is_keyframe()),
+                None => return GST_FLOW_OK
+            };
+            let mut state = lock.lock().unwrap();
+            let time = if dts != GST_CLOCK_TIME_NONE{
+                dts
+            }else if pts != GST_CLOCK_TIME_NONE{
+                pts
+            }else{
+                state.newest().unwrap_or(0)
+            };
+            state.push(Entry{ sample: sample, time: time, keyframe: keyframe });
+            cond.notify_all();
+            GST_FLOW_OK
+        })
+    }
+}
+
+extern "C" fn on_eos(appsink: *mut GstAppSink, data: gpointer){
+    unsafe{
+        unwind::guard(appsink as *mut GstObject, (), || {
+            let &(ref lock, ref cond) = &**(data as *const Shared);
+            lock.lock().unwrap().eos = true;
+            cond.notify_all();
+        })
+    }
+}
+
+extern "C" fn free_shared(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut Shared));
+    }
+}
@@ -0,0 +1,170 @@
+use ffi::*;
+use util::*;
+
+use std::collections::HashMap;
+use std::os::raw::{c_int, c_void};
+use std::sync::{Mutex, Once};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REGISTRY: Mutex<Option<Registry>> = Mutex::new(None);
+static AT_EXIT: Once = Once::new();
+static DUMP_ON_EXIT: AtomicBool = AtomicBool::new(true);
+
+extern "C" {
+    fn atexit(cb: extern "C" fn()) -> c_int;
+}
+
+/// Whether a tracked pointer is a GstObject, like elements, pads or
+/// buses, or a GstMiniObject, like buffers, caps, samples or events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TrackedKind{
+    Object,
+    MiniObject,
+}
+
+/// Wrappers of one GType created and still alive, as returned by
+/// `tracked_types`
+#[derive(Clone, Debug)]
+pub struct TrackedType{
+    pub type_name: String,
+    pub kind: TrackedKind,
+    /// Wrappers alive right now, each of them holds a reference
+    pub live: usize,
+    /// Wrappers created since the start of the program
+    pub created: usize,
+}
+
+struct Live{
+    kind: TrackedKind,
+    type_name: String,
+    wrappers: usize,
+}
+
+#[derive(Default)]
+struct Registry{
+    live: HashMap<usize, Live>,
+    created: HashMap<(String, TrackedKind), usize>,
+}
+
+unsafe fn type_name(ptr: *const c_void, kind: TrackedKind) -> String{
+    let gtype = match kind{
+        TrackedKind::Object => (*(*(ptr as *const GTypeInstance)).g_class).g_type,
+        TrackedKind::MiniObject => (*(ptr as *const GstMiniObject))._type,
+    };
+    let name = g_type_name(gtype);
+    if name == ptr::null(){
+        "<unknown>".to_string()
+    }else{
+        from_c_str!(name).to_string()
+    }
+}
+
+unsafe fn refcount(ptr: usize, kind: TrackedKind) -> i64{
+    match kind{
+        TrackedKind::Object => (*(ptr as *const GObject)).ref_count as i64,
+        TrackedKind::MiniObject => (*(ptr as *const GstMiniObject)).refcount as i64,
+    }
+}
+
+/// Called by the wrappers when they take a reference to `ptr`
+pub unsafe fn track(ptr: *const c_void, kind: TrackedKind){
+    AT_EXIT.call_once(|| { atexit(dump_at_exit); });
+    let name = type_name(ptr, kind);
+    let mut registry = REGISTRY.lock().unwrap();
+    let registry = registry.get_or_insert_with(Registry::default);
+    *registry.created.entry((name.clone(), kind)).or_insert(0) += 1;
+    let live = registry.live.entry(ptr as usize).or_insert(Live{
+        kind: kind,
+        type_name: name,
+        wrappers: 0,
+    });
+    live.wrappers += 1;
+}
+
+/// Called by the wrappers when they release or transfer their reference
+/// to `ptr`
+pub fn untrack(ptr: *const c_void){
+    let mut registry = REGISTRY.lock().unwrap();
+    if let Some(registry) = registry.as_mut(){
+        let remove = match registry.live.get_mut(&(ptr as usize)){
+            Some(live) => {
+                live.wrappers -= 1;
+                live.wrappers == 0
+            }
+            None => false
+        };
+        if remove{
+            registry.live.remove(&(ptr as usize));
+        }
+    }
+}
+
+/// Live and created wrappers of every type seen so far, the types with
+/// most live wrappers first
+pub fn tracked_types() -> Vec<TrackedType>{
+    let registry = REGISTRY.lock().unwrap();
+    let registry = match registry.as_ref(){
+        Some(registry) => registry,
+        None => return vec![]
+    };
+    let mut live = HashMap::new();
+    for object in registry.live.values(){
+        *live.entry((object.type_name.clone(), object.kind)).or_insert(0) += object.wrappers;
+    }
+    let mut types: Vec<TrackedType> = registry.created.iter().map(|(&(ref type_name, kind), &created)| TrackedType{
+        type_name: type_name.clone(),
+        kind: kind,
+        live: live.get(&(type_name.clone(), kind)).cloned().unwrap_or(0),
+        created: created,
+    }).collect();
+    types.sort_by(|a, b| b.live.cmp(&a.live).then_with(|| a.type_name.cmp(&b.type_name)));
+    types
+}
+
+/// Number of wrapped objects and miniobjects alive right now
+pub fn live_wrappers() -> usize{
+    let registry = REGISTRY.lock().unwrap();
+    registry.as_ref().map(|registry| registry.live.values().map(|live| live.wrappers).sum()).unwrap_or(0)
+}
+
+/// A report of the wrappers alive right now, by type and then every
+/// pointer with the number of wrappers holding it and its current
+/// refcount. A refcount lower than the number of wrappers means some
+/// wrapper didn't own the reference it releases on drop, a higher one
+/// that something else, like a pipeline or a leaked raw pointer, still
+/// holds it
+pub fn leaks_report() -> String{
+    let mut report = format!("{} wrapped objects alive\n", live_wrappers());
+    for tracked in tracked_types().into_iter().filter(|tracked| tracked.live > 0){
+        report += &format!("  {:?} {}: {} alive, {} created\n", tracked.kind, tracked.type_name,
+                           tracked.live, tracked.created);
+    }
+    let registry = REGISTRY.lock().unwrap();
+    if let Some(registry) = registry.as_ref(){
+        let mut live: Vec<_> = registry.live.iter().collect();
+        live.sort_by(|a, b| a.1.type_name.cmp(&b.1.type_name).then_with(|| a.0.cmp(b.0)));
+        for (&ptr, live) in live{
+            report += &format!("  {:#x} {}: {} wrappers, refcount {}\n", ptr, live.type_name, live.wrappers,
+                               unsafe{ refcount(ptr, live.kind) });
+        }
+    }
+    report
+}
+
+/// Prints `leaks_report` to stderr
+pub fn dump_leaks(){
+    eprint!("{}", leaks_report());
+}
+
+/// The report of the wrappers still alive is printed to stderr when the
+/// program exits, unless disabled with this
+pub fn set_dump_leaks_on_exit(dump: bool){
+    DUMP_ON_EXIT.store(dump, Ordering::SeqCst);
+}
+
+extern "C" fn dump_at_exit(){
+    if DUMP_ON_EXIT.load(Ordering::SeqCst) && live_wrappers() > 0{
+        eprint!("gstreamer leak tracking: ");
+        dump_leaks();
+    }
+}
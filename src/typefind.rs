@@ -0,0 +1,94 @@
+use ffi::*;
+use util::*;
+use caps::Caps;
+use error::{Error, Result};
+use subclass;
+use unwind;
+
+use std::ptr;
+use std::slice;
+use std::marker::PhantomData;
+
+/// The stream being typefound, passed to the functions registered with
+/// `register_typefind`
+pub struct TypeFind<'a>{
+    find: *mut GstTypeFind,
+    _lifetime: PhantomData<&'a mut GstTypeFind>,
+}
+
+impl<'a> TypeFind<'a>{
+    /// `size` bytes of the stream starting at `offset`, negative offsets
+    /// counting from the end of the stream. None if that much data is not
+    /// available
+    pub fn peek(&mut self, offset: i64, size: u32) -> Option<&[u8]>{
+        unsafe{
+            let data = gst_type_find_peek(self.find, offset, size);
+            if data == ptr::null(){
+                None
+            }else{
+                Some(slice::from_raw_parts(data, size as usize))
+            }
+        }
+    }
+
+    /// Length of the stream in bytes, None if unknown
+    pub fn length(&mut self) -> Option<u64>{
+        unsafe{
+            match gst_type_find_get_length(self.find){
+                0 => None,
+                length => Some(length)
+            }
+        }
+    }
+
+    /// Suggests `caps` as the type of the stream with the given
+    /// probability, from GST_TYPE_FIND_MINIMUM to GST_TYPE_FIND_MAXIMUM.
+    /// The suggestion with the highest probability among all the
+    /// registered typefinders wins
+    pub fn suggest(&mut self, probability: GstTypeFindProbability, caps: &Caps){
+        unsafe{
+            gst_type_find_suggest(self.find, probability, caps.gst_caps() as *mut GstCaps);
+        }
+    }
+}
+
+type TypeFindCallback = Box<dyn Fn(&mut TypeFind) + Send + Sync>;
+
+/// Registers a typefind function so streams in a custom container or
+/// codec format are recognized by typefind, decodebin and playbin.
+///
+/// `typefind` is called with the beginning of streams of unknown type and
+/// calls `TypeFind::suggest` with the caps of the stream if it recognizes
+/// it. `extensions` are the usual file extensions of the format, without
+/// dot, and `caps` the caps it can suggest, if known, which are used to
+/// select the typefinders to try first.
+pub fn register_typefind<F>(name: &str, rank: GstRank, extensions: &[&str], caps: Option<&Caps>, typefind: F) -> Result<()>
+    where F: Fn(&mut TypeFind) + Send + Sync + 'static{
+
+    let cname = CString::new(name).unwrap();
+    let cextensions = CString::new(extensions.join(",")).unwrap();
+    let callback: Box<TypeFindCallback> = Box::new(Box::new(typefind));
+    unsafe{
+        let possible_caps = caps.map(|caps| caps.gst_caps() as *mut GstCaps).unwrap_or(ptr::null_mut());
+        let cextensions = if extensions.is_empty(){ ptr::null() }else{ cextensions.as_ptr() };
+        if gst_type_find_register(subclass::current_plugin(), cname.as_ptr(), rank, Some(typefind_function),
+                                  cextensions, possible_caps,
+                                  Box::into_raw(callback) as gpointer, Some(free_typefind)) == 0{
+            return Err(Error::new(0, 0, &format!("Couldn't register typefind {}", name)));
+        }
+    }
+    Ok(())
+}
+
+extern "C" fn typefind_function(find: *mut GstTypeFind, data: gpointer){
+    unsafe{
+        let callback = &*(data as *const TypeFindCallback);
+        unwind::guard(ptr::null_mut(), (), || callback(&mut TypeFind{ find: find, _lifetime: PhantomData }));
+    }
+}
+
+extern "C" fn free_typefind(data: gpointer){
+    unsafe{
+        drop(Box::from_raw(data as *mut TypeFindCallback));
+    }
+}
@@ -0,0 +1,131 @@
+use ffi::*;
+use util::*;
+
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+type PanicHook = Box<dyn Fn(&CallbackPanic) + Send + Sync>;
+
+static HOOK: RwLock<Option<PanicHook>> = RwLock::new(None);
+static ABORT: AtomicBool = AtomicBool::new(false);
+
+thread_local!{
+    // set while posting the error of a panic, a sync handler that panics
+    // with every message would otherwise be called again with the error
+    static POSTING: Cell<bool> = Cell::new(false);
+}
+
+/// A panic caught in a callback called from GStreamer, like a bus watch,
+/// an appsink or appsrc callback, a pad probe or a signal handler
+#[derive(Clone, Debug)]
+pub struct CallbackPanic{
+    /// The message passed to panic!
+    pub message: String,
+    /// Name of the element, pad or bus the callback was called for, if
+    /// known
+    pub source: Option<String>,
+}
+
+/// Calls `hook` with every panic caught in a callback called from
+/// GStreamer, after the error message is posted. Panics can't unwind into
+/// C, so they are caught, an error message is posted on the bus of the
+/// pipeline and the callback returns a value that stops the streaming
+/// thread, like GST_FLOW_ERROR. Replaces the previous hook
+pub fn set_panic_hook<F>(hook: F)
+    where F: Fn(&CallbackPanic) + Send + Sync + 'static{
+    *HOOK.write().unwrap() = Some(Box::new(hook));
+}
+
+pub fn unset_panic_hook(){
+    *HOOK.write().unwrap() = None;
+}
+
+/// Aborts the process when a callback called from GStreamer panics,
+/// after calling the hook, instead of posting an error message and going
+/// on
+pub fn set_abort_on_panic(abort: bool){
+    ABORT.store(abort, Ordering::SeqCst);
+}
+
+/// Runs `f`, called from a C callback, returning `default` if it panics
+/// after reporting the panic as an error of `src`, an element, a pad or a
+/// bus. `src` can be null or any other GObject if the callback has none
+pub unsafe fn guard<R, F: FnOnce() -> R>(src: *mut GstObject, default: R, f: F) -> R{
+    match panic::catch_unwind(AssertUnwindSafe(f)){
+        Ok(ret) => ret,
+        Err(payload) => {
+            report(src, payload);
+            default
+        }
+    }
+}
+
+unsafe fn report(src: *mut GstObject, payload: Box<dyn Any + Send>){
+    let message = if let Some(message) = payload.downcast_ref::<&str>(){
+        message.to_string()
+    }else if let Some(message) = payload.downcast_ref::<String>(){
+        message.clone()
+    }else{
+        "unknown panic".to_string()
+    };
+    let src = if src != ptr::null_mut() &&
+        g_type_check_instance_is_a(src as *mut GTypeInstance, gst_object_get_type()) != 0{
+        src
+    }else{
+        ptr::null_mut()
+    };
+    let source = if src != ptr::null_mut() && (*src).name != ptr::null_mut(){
+        Some(from_c_str!((*src).name).to_string())
+    }else{
+        None
+    };
+    post_error(src, &message);
+    let panic = CallbackPanic{
+        message: message,
+        source: source,
+    };
+    if let Ok(hook) = HOOK.read(){
+        if let Some(ref hook) = *hook{
+            // a panicking hook can't unwind into C either
+            panic::catch_unwind(AssertUnwindSafe(|| hook(&panic))).ok();
+        }
+    }
+    if ABORT.load(Ordering::SeqCst){
+        process::abort();
+    }
+}
+
+unsafe fn post_error(src: *mut GstObject, message: &str){
+    if src == ptr::null_mut() || POSTING.with(|posting| posting.get()){
+        return;
+    }
+    let instance = src as *mut GTypeInstance;
+    let element = if g_type_check_instance_is_a(instance, gst_element_get_type()) != 0{
+        gst_object_ref(src as gpointer) as *mut GstElement
+    }else if g_type_check_instance_is_a(instance, gst_pad_get_type()) != 0{
+        gst_pad_get_parent_element(src as *mut GstPad)
+    }else{
+        ptr::null_mut()
+    };
+    let is_bus = g_type_check_instance_is_a(instance, gst_bus_get_type()) != 0;
+    if element == ptr::null_mut() && !is_bus{
+        return;
+    }
+
+    let cmessage = CString::new(format!("Panic in callback: {}", message).replace('\0', "")).unwrap();
+    let error = g_error_new_literal(gst_core_error_quark(), GST_CORE_ERROR_FAILED as gint, cmessage.as_ptr());
+    let msg = gst_message_new_error(src, error, cmessage.as_ptr());
+    POSTING.with(|posting| posting.set(true));
+    if element != ptr::null_mut(){
+        gst_element_post_message(element, msg);
+        gst_object_unref(element as gpointer);
+    }else{
+        gst_bus_post(src as *mut GstBus, msg);
+    }
+    POSTING.with(|posting| posting.set(false));
+    g_error_free(error);
+}
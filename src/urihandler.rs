@@ -0,0 +1,210 @@
+use ffi::*;
+use util::*;
+use element::Element;
+use error::{Error, Result};
+use subclass;
+use unwind;
+
+use std::ptr;
+use std::mem;
+
+// key of the handler in the qdata of the registered types and of the
+// source and uri in the data of each instance
+const HANDLER_KEY: &'static str = "gst-rs-uri-handler";
+const SOURCE_KEY: &'static str = "gst-rs-uri-source";
+const URI_KEY: &'static str = "gst-rs-uri";
+
+struct UriHandler{
+    scheme: String,
+    // null terminated list of the protocols for get_protocols
+    protocols: [*const gchar; 2],
+    _cprotocol: CString,
+    factory: Box<dyn Fn(&str) -> Result<Element> + Send + Sync>,
+}
+
+/// Registers a source element handling uris of `scheme`, like "myapp"
+/// for "myapp://..." uris, so playbin and uridecodebin can play them.
+///
+/// When the element is given a uri, `factory` is called with it to create
+/// the element that actually produces the data, like an appsrc fed by the
+/// application or a standard source for a translated location. It has to
+/// have an always "src" pad, which is exposed as the src pad of the
+/// registered element. Errors returned by the factory are reported as
+/// invalid uri errors.
+///
+/// Registering the same scheme twice fails.
+pub fn register_uri_handler<F>(scheme: &str, factory: F) -> Result<()>
+    where F: Fn(&str) -> Result<Element> + Send + Sync + 'static{
+
+    let type_name: String = scheme.chars().filter(|c| c.is_alphanumeric()).collect();
+    let type_name = format!("GstRsUriSrc{}", type_name);
+    let element_name = format!("rsurisrc-{}", scheme.to_lowercase());
+    let cprotocol = CString::new(scheme).unwrap();
+    let handler = Box::new(UriHandler{
+        scheme: scheme.to_string(),
+        protocols: [cprotocol.as_ptr(), ptr::null()],
+        _cprotocol: cprotocol,
+        factory: Box::new(factory),
+    });
+
+    unsafe{
+        let mut query: GTypeQuery = mem::zeroed();
+        g_type_query(gst_bin_get_type(), &mut query);
+        let info = GTypeInfo{
+            class_size: query.class_size as guint16,
+            base_init: None,
+            base_finalize: None,
+            class_init: Some(class_init),
+            class_finalize: None,
+            class_data: &*handler as *const UriHandler as gconstpointer,
+            instance_size: query.instance_size as guint16,
+            n_preallocs: 0,
+            instance_init: Some(instance_init),
+            value_table: ptr::null(),
+        };
+        let ctype_name = CString::new(type_name.clone()).unwrap();
+        let gtype = g_type_register_static(gst_bin_get_type(), ctype_name.as_ptr(), &info, 0);
+        if gtype == 0{
+            return Err(Error::new(0, 0, &format!("Couldn't register a type for the {} uri scheme", scheme)));
+        }
+
+        let interface_info = GInterfaceInfo{
+            interface_init: Some(uri_handler_init),
+            interface_finalize: None,
+            interface_data: ptr::null_mut(),
+        };
+        g_type_add_interface_static(gtype, gst_uri_handler_get_type(), &interface_info);
+
+        // registered types live forever, and so does their handler
+        let chandler_key = CString::new(HANDLER_KEY).unwrap();
+        g_type_set_qdata(gtype, g_quark_from_string(chandler_key.as_ptr()), Box::into_raw(handler) as gpointer);
+
+        let celement_name = CString::new(element_name.clone()).unwrap();
+        if gst_element_register(subclass::current_plugin(), celement_name.as_ptr(), GST_RANK_PRIMARY, gtype) == 0{
+            return Err(Error::new(0, 0, &format!("Couldn't register element {}", element_name)));
+        }
+    }
+    Ok(())
+}
+
+unsafe fn handler<'a>(gtype: GType) -> Option<&'a UriHandler>{
+    let chandler_key = CString::new(HANDLER_KEY).unwrap();
+    (g_type_get_qdata(gtype, g_quark_from_string(chandler_key.as_ptr())) as *const UriHandler).as_ref()
+}
+
+unsafe fn instance_type(instance: gpointer) -> GType{
+    (*(*(instance as *mut GTypeInstance)).g_class).g_type
+}
+
+extern "C" fn class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let handler = &*(class_data as *const UriHandler);
+        let element_class = g_class as *mut GstElementClass;
+        let longname = CString::new(format!("{} uri source", handler.scheme)).unwrap();
+        let classification = CString::new("Source").unwrap();
+        let description = CString::new(format!("Application defined source for {}:// uris", handler.scheme)).unwrap();
+        let author = CString::new("gstreamer1.0-rs").unwrap();
+        gst_element_class_set_metadata(element_class, longname.as_ptr(), classification.as_ptr(),
+                                       description.as_ptr(), author.as_ptr());
+        let csrc = CString::new("src").unwrap();
+        let template = gst_pad_template_new(csrc.as_ptr(), GST_PAD_SRC, GST_PAD_ALWAYS, gst_caps_new_any());
+        gst_element_class_add_pad_template(element_class, template);
+    }
+}
+
+extern "C" fn instance_init(instance: *mut GTypeInstance, _g_class: gpointer){
+    unsafe{
+        let csrc = CString::new("src").unwrap();
+        let pad = gst_ghost_pad_new_no_target(csrc.as_ptr(), GST_PAD_SRC);
+        gst_element_add_pad(instance as *mut GstElement, pad);
+    }
+}
+
+extern "C" fn uri_handler_init(g_iface: gpointer, _iface_data: gpointer){
+    unsafe{
+        let iface = &mut *(g_iface as *mut GstURIHandlerInterface);
+        iface.get_type = Some(uri_type);
+        iface.get_protocols = Some(uri_protocols);
+        iface.get_uri = Some(get_uri);
+        iface.set_uri = Some(set_uri);
+    }
+}
+
+extern "C" fn uri_type(_gtype: GType) -> GstURIType{
+    GST_URI_SRC
+}
+
+extern "C" fn uri_protocols(gtype: GType) -> *const *const gchar{
+    unsafe{
+        match handler(gtype){
+            Some(handler) => handler.protocols.as_ptr(),
+            None => ptr::null()
+        }
+    }
+}
+
+extern "C" fn get_uri(uri_handler: *mut GstURIHandler) -> *mut gchar{
+    unsafe{
+        let curi_key = CString::new(URI_KEY).unwrap();
+        let uri = g_object_get_data(uri_handler as *mut GObject, curi_key.as_ptr());
+        if uri == ptr::null_mut(){
+            ptr::null_mut()
+        }else{
+            g_strdup(uri as *const gchar)
+        }
+    }
+}
+
+extern "C" fn set_uri(uri_handler: *mut GstURIHandler, uri: *const gchar, error: *mut *mut GError) -> gboolean{
+    unsafe{
+        let handler = match handler(instance_type(uri_handler as gpointer)){
+            Some(handler) => handler,
+            None => return 0
+        };
+        let bin = uri_handler as *mut GstBin;
+        let ruri = from_c_str!(uri);
+        let panicked = Err(Error::new(0, 0, &format!("The source for {} panicked", ruri)));
+        let mut source = match unwind::guard(bin as *mut GstObject, panicked, || (handler.factory)(ruri)){
+            Ok(source) => source,
+            Err(err) => {
+                let message = CString::new(err.message()).unwrap_or(CString::new("").unwrap());
+                g_set_error_literal(error, gst_uri_error_quark(), GST_URI_ERROR_BAD_URI as gint, message.as_ptr());
+                return 0;
+            }
+        };
+
+        let csrc = CString::new("src").unwrap();
+        let source_pad = gst_element_get_static_pad(source.gst_element_mut(), csrc.as_ptr());
+        if source_pad == ptr::null_mut(){
+            let message = CString::new(format!("The source for {} has no src pad", ruri)).unwrap();
+            g_set_error_literal(error, gst_uri_error_quark(), GST_URI_ERROR_BAD_URI as gint, message.as_ptr());
+            return 0;
+        }
+
+        // replace the source of a previous uri
+        let csource_key = CString::new(SOURCE_KEY).unwrap();
+        let previous = g_object_get_data(bin as *mut GObject, csource_key.as_ptr()) as *mut GstElement;
+        if previous != ptr::null_mut(){
+            gst_element_set_state(previous, GST_STATE_NULL);
+            gst_bin_remove(bin, previous);
+        }
+        let source = ::Transfer::transfer(source);
+        gst_bin_add(bin, source);
+        g_object_set_data(bin as *mut GObject, csource_key.as_ptr(), source as gpointer);
+
+        let ghost_pad = gst_element_get_static_pad(bin as *mut GstElement, csrc.as_ptr());
+        gst_ghost_pad_set_target(ghost_pad as *mut GstGhostPad, source_pad);
+        gst_object_unref(ghost_pad as gpointer);
+        gst_object_unref(source_pad as gpointer);
+
+        let curi_key = CString::new(URI_KEY).unwrap();
+        g_object_set_data_full(bin as *mut GObject, curi_key.as_ptr(), g_strdup(uri) as gpointer, Some(free_uri));
+        1
+    }
+}
+
+extern "C" fn free_uri(uri: gpointer){
+    unsafe{
+        g_free(uri);
+    }
+}
@@ -0,0 +1,159 @@
+use ffi::*;
+use util::*;
+use error::{Error, Result};
+use object::Object;
+use pipeline::Pipeline;
+
+use std::sync::{Arc, Once};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static INIT: Once = Once::new();
+
+/// Collects the issues found by gst-validate in the pipelines it monitors,
+/// like buffers out of their segment, wrong timestamps or seeks that aren't
+/// honored, while a scenario or the application drives them.
+///
+/// Create the runner and the monitors before setting the pipeline to
+/// playing, and check `reports_count` once it's done, usually in test
+/// binaries. `run_scenario` does all of that for the scenarios shipped with
+/// gst-validate, like "seek_forward" or "change_state_intensive".
+/// See [gst-validate](https://gstreamer.freedesktop.org/documentation/gst-devtools/gst-validate.html)
+pub struct ValidateRunner{
+    runner: *mut GstValidateRunner,
+    monitors: Vec<*mut GstValidateMonitor>,
+}
+
+unsafe impl Send for ValidateRunner {}
+
+impl ValidateRunner{
+    pub fn new() -> ValidateRunner{
+        unsafe{
+            INIT.call_once(|| gst_validate_init());
+            ValidateRunner{
+                runner: gst_validate_runner_new(),
+                monitors: vec![],
+            }
+        }
+    }
+
+    /// Starts reporting the issues of `pipeline` and all its elements
+    pub fn monitor(&mut self, pipeline: &Pipeline) -> Result<()>{
+        unsafe{
+            let monitor = gst_validate_monitor_factory_create(pipeline.gst_element() as *mut GstObject,
+                                                              self.runner, ptr::null_mut());
+            if monitor == ptr::null_mut(){
+                return Err(Error::new(0, 0, "Couldn't monitor pipeline"));
+            }
+            self.monitors.push(monitor);
+            Ok(())
+        }
+    }
+
+    /// Loads the scenario `name`, from the scenarios installed with
+    /// gst-validate or the GST_VALIDATE_SCENARIOS_PATH, to run its actions
+    /// on `pipeline` once it starts playing
+    pub fn scenario(&mut self, pipeline: &Pipeline, name: &str) -> Result<Scenario>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let scenario = gst_validate_scenario_factory_create(self.runner, pipeline.gst_element() as *mut GstElement,
+                                                                cname.as_ptr());
+            let mut object = Object::new(scenario as *mut GstObject)
+                .ok_or(Error::new(0, 0, &format!("Couldn't load scenario {}", name)))?;
+            let done = Arc::new(AtomicBool::new(false));
+            let done_flag = done.clone();
+            object.connect("done", move |_| {
+                done_flag.store(true, Ordering::SeqCst);
+                None
+            });
+            Ok(Scenario{
+                scenario: object,
+                done: done,
+            })
+        }
+    }
+
+    /// Number of issues reported so far
+    pub fn reports_count(&self) -> u32{
+        unsafe{
+            gst_validate_runner_get_reports_count(self.runner)
+        }
+    }
+
+    /// Prints the issues reported so far and returns the exit code a
+    /// test should finish with, non 0 if any of them is critical
+    pub fn print_reports(&mut self) -> i32{
+        unsafe{
+            gst_validate_runner_printf(self.runner)
+        }
+    }
+
+    /// The exit code a test should finish with, non 0 if any of the
+    /// issues is critical, printing them if `print` is true
+    pub fn exit_code(&mut self, print: bool) -> i32{
+        unsafe{
+            gst_validate_runner_exit(self.runner, print as gboolean)
+        }
+    }
+}
+
+impl Drop for ValidateRunner{
+    fn drop(&mut self){
+        unsafe{
+            for monitor in self.monitors.drain(..){
+                gst_object_unref(monitor as gpointer);
+            }
+            gst_object_unref(self.runner as gpointer);
+        }
+    }
+}
+
+/// A validation scenario running on a pipeline, created with
+/// `ValidateRunner::scenario`
+pub struct Scenario{
+    scenario: Object,
+    done: Arc<AtomicBool>,
+}
+
+impl Scenario{
+    /// Whether all the actions of the scenario have been executed
+    pub fn is_done(&self) -> bool{
+        self.done.load(Ordering::SeqCst)
+    }
+
+    pub fn name(&self) -> String{
+        self.scenario.name()
+    }
+}
+
+/// Runs the scenario `name` on `pipeline` until its actions are done, the
+/// pipeline reaches EOS or posts an error, and returns the number of
+/// issues found. The actions of the scenario run from the default glib
+/// main context, which is iterated while waiting. The pipeline is set to
+/// NULL at the end, and an error is returned if `timeout` expires first.
+pub fn run_scenario(pipeline: &mut Pipeline, name: &str, timeout: Duration) -> Result<u32>{
+    let mut runner = ValidateRunner::new();
+    runner.monitor(pipeline)?;
+    let scenario = runner.scenario(pipeline, name)?;
+    let mut bus = pipeline.bus().ok_or(Error::new(0, 0, "Pipeline has no bus"))?;
+    if pipeline.play() == GST_STATE_CHANGE_FAILURE{
+        pipeline.set_null_state();
+        return Err(Error::new(0, 0, "Couldn't start pipeline"));
+    }
+
+    let start = Instant::now();
+    let mut finished = false;
+    while !finished && !scenario.is_done(){
+        if start.elapsed() > timeout{
+            pipeline.set_null_state();
+            return Err(Error::new(0, 0, &format!("Timeout running scenario {}", name)));
+        }
+        unsafe{
+            while g_main_context_iteration(ptr::null_mut(), 0) != 0{}
+        }
+        // 10ms, so the scenario actions keep running
+        finished = bus.timed_pop_filtered(10_000_000, GST_MESSAGE_EOS | GST_MESSAGE_ERROR).is_some();
+    }
+    pipeline.set_null_state();
+    Ok(runner.reports_count())
+}
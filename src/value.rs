@@ -0,0 +1,84 @@
+use ffi::*;
+use util::*;
+use structure::{FieldValue, FromFieldValue};
+
+use std::fmt::{self, Debug, Formatter};
+
+/// An owned GValue of any type, like the arguments and return values of
+/// signals. Its contents are read with `get`
+pub struct Value{
+    value: GValue,
+}
+
+impl Value{
+    pub fn new<V: FieldValue>(value: V) -> Value{
+        unsafe{
+            let mut gvalue: GValue = mem::zeroed();
+            value.to_gvalue(&mut gvalue);
+            Value{ value: gvalue }
+        }
+    }
+
+    /// Creates a value with a copy of the contents of `gvalue`
+    pub unsafe fn new_from_gvalue(gvalue: &GValue) -> Value{
+        let mut value: GValue = mem::zeroed();
+        g_value_init(&mut value, gvalue.g_type);
+        g_value_copy(gvalue, &mut value);
+        Value{ value: value }
+    }
+
+    /// The contents of the value, None if it holds some other type
+    pub fn get<T: FromFieldValue>(&self) -> Option<T>{
+        unsafe{
+            T::from_gvalue(&self.value)
+        }
+    }
+
+    pub fn type_name(&self) -> String{
+        unsafe{
+            from_c_str!(g_type_name(self.value.g_type)).to_string()
+        }
+    }
+
+    pub unsafe fn gvalue(&self) -> *const GValue{
+        &self.value
+    }
+
+    pub unsafe fn gvalue_mut(&mut self) -> *mut GValue{
+        &mut self.value
+    }
+}
+
+impl Drop for Value{
+    fn drop(&mut self){
+        unsafe{
+            g_value_unset(&mut self.value);
+        }
+    }
+}
+
+impl Clone for Value{
+    fn clone(&self) -> Value{
+        unsafe{
+            Value::new_from_gvalue(&self.value)
+        }
+    }
+}
+
+impl FieldValue for Value{
+    unsafe fn to_gvalue(&self, gvalue: &mut GValue){
+        g_value_init(gvalue, self.value.g_type);
+        g_value_copy(&self.value, gvalue);
+    }
+}
+
+impl Debug for Value{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        unsafe{
+            let contents = g_strdup_value_contents(&self.value);
+            let ret = write!(f, "{}({})", self.type_name(), from_c_str!(contents));
+            g_free(contents as gpointer);
+            ret
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use buffer::Buffer;
+use videoframe::VideoFrame;
+
+use ndarray::{Array3, ArrayView3, ShapeBuilder};
+
+impl VideoFrame{
+    /// View of the pixels of a packed 8 bit format like RGB, RGBA, BGRx
+    /// or GRAY8 as an array of shape (height, width, channels), where the
+    /// channels are the bytes of each pixel in memory order, including
+    /// padding bytes like the x of RGBx. The row stride of the frame is
+    /// respected, so no data is copied.
+    ///
+    /// None for planar formats like I420, formats with more than 8 bits
+    /// per component and subsampled packed formats like YUY2
+    pub fn as_array<'a>(&'a self) -> Option<ArrayView3<'a, u8>>{
+        let finfo = self.format_info();
+        if self.n_planes() != 1{
+            return None;
+        }
+        let packed_8bit = (0..self.n_components() as usize).all(|c|
+            finfo.depth[c] == 8 && finfo.w_sub[c] == 0 && finfo.h_sub[c] == 0);
+        if !packed_8bit{
+            return None;
+        }
+        let channels = finfo.pixel_stride[0] as usize;
+        let plane = self.plane(0)?;
+        let (width, height, stride) = (self.width() as usize, self.height() as usize, plane.stride() as usize);
+        if channels == 0 || stride < width * channels{
+            return None;
+        }
+        let shape = (height, width, channels).strides((stride, channels, 1));
+        ArrayView3::from_shape(shape, plane.data::<u8>()).ok()
+    }
+}
+
+impl Buffer{
+    /// Creates a buffer holding the pixels of an array of shape (height,
+    /// width, channels), as packed rows without padding. Arrays in the
+    /// standard layout are wrapped without copying their data.
+    ///
+    /// The caps of the stream the buffer is pushed to have to describe a
+    /// packed format with as many bytes per pixel as channels and a
+    /// stride of width * channels, rounded to a multiple of 4 by
+    /// GStreamer for most formats, so widths that don't give an aligned
+    /// stride need padding first.
+    pub fn from_array(array: Array3<u8>) -> Buffer{
+        let data = if array.is_standard_layout(){
+            array.into_raw_vec()
+        }else{
+            array.iter().cloned().collect()
+        };
+        Buffer::from_vec(data)
+    }
+}
@@ -0,0 +1,696 @@
+use ffi::*;
+use buffer::Buffer;
+use caps::Caps;
+use element::Element;
+use error::Result;
+use subclass::{self, ClassData, ElementMetadata};
+use unwind;
+use videoinfo::VideoInfo;
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::Mutex;
+
+fn clock_time(time: GstClockTime) -> Option<GstClockTime>{
+    if time != GST_CLOCK_TIME_NONE { Some(time) } else { None }
+}
+
+/// A frame being decoded or encoded by a `VideoDecoderImpl` or a
+/// `VideoEncoderImpl`, with the input buffer and, once it's done, the
+/// output buffer. Frames passed to `handle_frame` have to be given back
+/// to the base class with `finish_frame`, `drop_frame` or `release_frame`,
+/// right away or later, like when the codec has some delay.
+pub struct VideoCodecFrame{
+    frame: *mut GstVideoCodecFrame
+}
+
+unsafe impl Send for VideoCodecFrame {}
+
+impl VideoCodecFrame{
+    pub unsafe fn new_from_gst_video_codec_frame(frame: *mut GstVideoCodecFrame) -> Option<VideoCodecFrame>{
+        if frame != ptr::null_mut(){
+            Some(VideoCodecFrame{ frame: frame })
+        }else{
+            None
+        }
+    }
+
+    /// Number of the frame in the order it entered the element, used to
+    /// find it again with `frame`
+    pub fn system_frame_number(&self) -> u32{
+        unsafe{ (*self.frame).system_frame_number }
+    }
+
+    /// Presentation timestamp in nanoseconds, None if not set
+    pub fn pts(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.frame).pts })
+    }
+
+    pub fn set_pts(&mut self, pts: GstClockTime){
+        unsafe{ (*self.frame).pts = pts }
+    }
+
+    pub fn dts(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.frame).dts })
+    }
+
+    pub fn set_dts(&mut self, dts: GstClockTime){
+        unsafe{ (*self.frame).dts = dts }
+    }
+
+    pub fn duration(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.frame).duration })
+    }
+
+    pub fn set_duration(&mut self, duration: GstClockTime){
+        unsafe{ (*self.frame).duration = duration }
+    }
+
+    /// Running time by which a decoded frame should be ready to be
+    /// displayed in time, None if unknown
+    pub fn deadline(&self) -> Option<GstClockTime>{
+        clock_time(unsafe{ (*self.frame).deadline })
+    }
+
+    /// Whether the frame can be decoded without the previous ones, a
+    /// keyframe
+    pub fn is_sync_point(&self) -> bool{
+        unsafe{ (*self.frame).flags & GST_VIDEO_CODEC_FRAME_FLAG_SYNC_POINT != 0 }
+    }
+
+    /// Marks the output of an encoder as a keyframe
+    pub fn set_sync_point(&mut self, sync_point: bool){
+        unsafe{
+            if sync_point{
+                (*self.frame).flags |= GST_VIDEO_CODEC_FRAME_FLAG_SYNC_POINT;
+            }else{
+                (*self.frame).flags &= !GST_VIDEO_CODEC_FRAME_FLAG_SYNC_POINT;
+            }
+        }
+    }
+
+    /// Whether the frame has to be decoded but not output, like the frames
+    /// before the seek position
+    pub fn is_decode_only(&self) -> bool{
+        unsafe{ (*self.frame).flags & GST_VIDEO_CODEC_FRAME_FLAG_DECODE_ONLY != 0 }
+    }
+
+    /// Whether downstream requested this frame to be encoded as a keyframe
+    pub fn is_force_keyframe(&self) -> bool{
+        unsafe{ (*self.frame).flags & GST_VIDEO_CODEC_FRAME_FLAG_FORCE_KEYFRAME != 0 }
+    }
+
+    /// The encoded data for a decoder or the raw picture for an encoder
+    pub fn input_buffer(&self) -> Option<Buffer>{
+        unsafe{
+            let buffer = (*self.frame).input_buffer;
+            if buffer == ptr::null_mut(){
+                None
+            }else{
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }
+        }
+    }
+
+    pub fn output_buffer(&self) -> Option<Buffer>{
+        unsafe{
+            let buffer = (*self.frame).output_buffer;
+            if buffer == ptr::null_mut(){
+                None
+            }else{
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }
+        }
+    }
+
+    /// Sets the result of decoding or encoding the frame, to be pushed by
+    /// `finish_frame`. Decoders usually fill the buffer allocated by
+    /// `VideoDecoder::allocate_output_frame` instead
+    pub fn set_output_buffer(&mut self, buffer: Buffer){
+        unsafe{
+            let previous = (*self.frame).output_buffer;
+            if previous != ptr::null_mut(){
+                gst_mini_object_unref(previous as *mut GstMiniObject);
+            }
+            (*self.frame).output_buffer = ::Transfer::transfer(buffer);
+        }
+    }
+
+    pub unsafe fn gst_video_codec_frame(&self) -> *const GstVideoCodecFrame{
+        self.frame
+    }
+
+    pub unsafe fn gst_video_codec_frame_mut(&mut self) -> *mut GstVideoCodecFrame{
+        self.frame
+    }
+
+    unsafe fn into_raw(self) -> *mut GstVideoCodecFrame{
+        let frame = self.frame;
+        mem::forget(self);
+        frame
+    }
+}
+
+impl Drop for VideoCodecFrame{
+    fn drop(&mut self){
+        unsafe{
+            gst_video_codec_frame_unref(self.frame);
+        }
+    }
+}
+
+/// Format of the input or output of a codec, the caps and the video info
+/// parsed from them
+pub struct VideoCodecState{
+    state: *mut GstVideoCodecState
+}
+
+unsafe impl Send for VideoCodecState {}
+unsafe impl Sync for VideoCodecState {}
+
+impl VideoCodecState{
+    pub unsafe fn new_from_gst_video_codec_state(state: *mut GstVideoCodecState) -> Option<VideoCodecState>{
+        if state != ptr::null_mut(){
+            Some(VideoCodecState{ state: state })
+        }else{
+            None
+        }
+    }
+
+    pub fn info(&self) -> &VideoInfo{
+        unsafe{ &(*self.state).info }
+    }
+
+    /// Info of an output state, to set what `set_output_state` can't, like
+    /// the pixel aspect ratio or the colorimetry, before negotiating
+    pub fn info_mut(&mut self) -> &mut VideoInfo{
+        unsafe{ &mut (*self.state).info }
+    }
+
+    pub fn caps(&self) -> Option<Caps>{
+        unsafe{
+            let caps = (*self.state).caps;
+            if caps == ptr::null_mut(){
+                None
+            }else{
+                Caps::new_from_gst_caps(gst_mini_object_ref(caps as *mut GstMiniObject) as *mut GstCaps)
+            }
+        }
+    }
+
+    /// Codec specific setup data from the caps, like the avcC of h264 in
+    /// mp4
+    pub fn codec_data(&self) -> Option<Buffer>{
+        unsafe{
+            let buffer = (*self.state).codec_data;
+            if buffer == ptr::null_mut(){
+                None
+            }else{
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }
+        }
+    }
+
+    pub unsafe fn gst_video_codec_state(&self) -> *const GstVideoCodecState{
+        self.state
+    }
+
+    pub unsafe fn gst_video_codec_state_mut(&mut self) -> *mut GstVideoCodecState{
+        self.state
+    }
+}
+
+impl Clone for VideoCodecState{
+    fn clone(&self) -> VideoCodecState{
+        unsafe{
+            VideoCodecState{ state: gst_video_codec_state_ref(self.state) }
+        }
+    }
+}
+
+impl Drop for VideoCodecState{
+    fn drop(&mut self){
+        unsafe{
+            gst_video_codec_state_unref(self.state);
+        }
+    }
+}
+
+/// The GstVideoDecoder base class of an element registered with
+/// `register_video_decoder`, passed to its implementation to output
+/// frames and negotiate
+pub struct VideoDecoder<'a>{
+    decoder: *mut GstVideoDecoder,
+    _lifetime: PhantomData<&'a mut GstVideoDecoder>,
+}
+
+impl<'a> VideoDecoder<'a>{
+    /// Pushes the output buffer of `frame` downstream, negotiating first
+    /// if the output state changed
+    pub fn finish_frame(&mut self, frame: VideoCodecFrame) -> GstFlowReturn{
+        unsafe{
+            gst_video_decoder_finish_frame(self.decoder, frame.into_raw())
+        }
+    }
+
+    /// Discards `frame` without output, like a frame that failed to decode,
+    /// posting a QoS message
+    pub fn drop_frame(&mut self, frame: VideoCodecFrame) -> GstFlowReturn{
+        unsafe{
+            gst_video_decoder_drop_frame(self.decoder, frame.into_raw())
+        }
+    }
+
+    /// Discards `frame` silently, like a frame that only carried headers
+    pub fn release_frame(&mut self, frame: VideoCodecFrame){
+        unsafe{
+            gst_video_decoder_release_frame(self.decoder, frame.into_raw())
+        }
+    }
+
+    /// Allocates the output buffer of `frame` from the negotiated pool,
+    /// with the size of the output state
+    pub fn allocate_output_frame(&mut self, frame: &mut VideoCodecFrame) -> GstFlowReturn{
+        unsafe{
+            gst_video_decoder_allocate_output_frame(self.decoder, frame.gst_video_codec_frame_mut())
+        }
+    }
+
+    /// Sets the format of the decoded frames, copying the rest of the info
+    /// from `reference`, usually the input state. It's negotiated with
+    /// downstream by `negotiate` or the next `finish_frame`
+    pub fn set_output_state(&mut self, format: GstVideoFormat, width: u32, height: u32,
+                            reference: Option<&VideoCodecState>) -> Option<VideoCodecState>{
+        unsafe{
+            let reference = reference.map(|state| state.state).unwrap_or(ptr::null_mut());
+            VideoCodecState::new_from_gst_video_codec_state(
+                gst_video_decoder_set_output_state(self.decoder, format, width, height, reference))
+        }
+    }
+
+    pub fn output_state(&self) -> Option<VideoCodecState>{
+        unsafe{
+            VideoCodecState::new_from_gst_video_codec_state(gst_video_decoder_get_output_state(self.decoder))
+        }
+    }
+
+    /// Negotiates the output state with downstream, false if it's not
+    /// accepted
+    pub fn negotiate(&mut self) -> bool{
+        unsafe{
+            gst_video_decoder_negotiate(self.decoder) != 0
+        }
+    }
+
+    /// A pending frame by its system frame number
+    pub fn frame(&self, system_frame_number: u32) -> Option<VideoCodecFrame>{
+        unsafe{
+            VideoCodecFrame::new_from_gst_video_codec_frame(
+                gst_video_decoder_get_frame(self.decoder, system_frame_number as i32))
+        }
+    }
+
+    /// The pending frame that entered the element first
+    pub fn oldest_frame(&self) -> Option<VideoCodecFrame>{
+        unsafe{
+            VideoCodecFrame::new_from_gst_video_codec_frame(gst_video_decoder_get_oldest_frame(self.decoder))
+        }
+    }
+
+    /// Latency introduced by the decoder, like the frames it buffers
+    /// before outputting the first one
+    pub fn set_latency(&mut self, min: GstClockTime, max: GstClockTime){
+        unsafe{
+            gst_video_decoder_set_latency(self.decoder, min, max);
+        }
+    }
+
+    /// Whether the input caps have to be received before any data, true
+    /// for formats that can't be decoded without their codec_data
+    pub fn set_needs_format(&mut self, needs_format: bool){
+        unsafe{
+            gst_video_decoder_set_needs_format(self.decoder, needs_format as gboolean);
+        }
+    }
+
+    /// The decoder as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.decoder as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// A video decoder written in rust, like a wrapper for the dav1d crate,
+/// registered with `register_video_decoder`.
+///
+/// The input is expected to be packetized, one encoded frame per buffer,
+/// as produced by the parsers and demuxers. All the methods are called
+/// from the streaming thread or during state changes, one at a time.
+pub trait VideoDecoderImpl: Send + 'static{
+    /// Called when going to PAUSED, to open the decoder
+    fn start(&mut self, _decoder: &mut VideoDecoder) -> bool{
+        true
+    }
+
+    /// Called when going back to READY, to close the decoder
+    fn stop(&mut self, _decoder: &mut VideoDecoder) -> bool{
+        true
+    }
+
+    /// Called with the format of the input, before the first frame and
+    /// when the caps change. Decoders that know the output format from the
+    /// caps call `set_output_state` here, the others once they've decoded
+    /// the headers
+    fn set_format(&mut self, decoder: &mut VideoDecoder, state: &VideoCodecState) -> bool;
+
+    /// Decodes `frame`. The frame is owned by the implementation until
+    /// it's given back with `finish_frame`, `drop_frame` or `release_frame`
+    fn handle_frame(&mut self, decoder: &mut VideoDecoder, frame: VideoCodecFrame) -> GstFlowReturn;
+
+    /// Called at EOS to output the frames still in the decoder
+    fn finish(&mut self, _decoder: &mut VideoDecoder) -> GstFlowReturn{
+        GST_FLOW_OK
+    }
+
+    /// Called when seeking, to discard the frames in the decoder
+    fn flush(&mut self, _decoder: &mut VideoDecoder) -> bool{
+        true
+    }
+}
+
+/// The GstVideoEncoder base class of an element registered with
+/// `register_video_encoder`, passed to its implementation to output
+/// frames and negotiate
+pub struct VideoEncoder<'a>{
+    encoder: *mut GstVideoEncoder,
+    _lifetime: PhantomData<&'a mut GstVideoEncoder>,
+}
+
+impl<'a> VideoEncoder<'a>{
+    /// Pushes the output buffer of `frame` downstream, with the timestamps
+    /// of the input frame. Frames without output buffer are dropped
+    pub fn finish_frame(&mut self, frame: VideoCodecFrame) -> GstFlowReturn{
+        unsafe{
+            gst_video_encoder_finish_frame(self.encoder, frame.into_raw())
+        }
+    }
+
+    /// Allocates an output buffer of `size` bytes for `frame`
+    pub fn allocate_output_frame(&mut self, frame: &mut VideoCodecFrame, size: usize) -> GstFlowReturn{
+        unsafe{
+            gst_video_encoder_allocate_output_frame(self.encoder, frame.gst_video_codec_frame_mut(), size as gsize)
+        }
+    }
+
+    /// Sets the caps of the encoded stream, like "video/x-av1" with its
+    /// profile, copying the rest from `reference`, usually the input state.
+    /// It's negotiated with downstream by `negotiate` or the next
+    /// `finish_frame`
+    pub fn set_output_state(&mut self, caps: Caps, reference: Option<&VideoCodecState>) -> Option<VideoCodecState>{
+        unsafe{
+            let reference = reference.map(|state| state.state).unwrap_or(ptr::null_mut());
+            VideoCodecState::new_from_gst_video_codec_state(
+                gst_video_encoder_set_output_state(self.encoder, ::Transfer::transfer(caps), reference))
+        }
+    }
+
+    pub fn output_state(&self) -> Option<VideoCodecState>{
+        unsafe{
+            VideoCodecState::new_from_gst_video_codec_state(gst_video_encoder_get_output_state(self.encoder))
+        }
+    }
+
+    /// Negotiates the output state with downstream, false if it's not
+    /// accepted
+    pub fn negotiate(&mut self) -> bool{
+        unsafe{
+            gst_video_encoder_negotiate(self.encoder) != 0
+        }
+    }
+
+    /// A pending frame by its system frame number, for encoders that
+    /// output packets some frames later
+    pub fn frame(&self, system_frame_number: u32) -> Option<VideoCodecFrame>{
+        unsafe{
+            VideoCodecFrame::new_from_gst_video_codec_frame(
+                gst_video_encoder_get_frame(self.encoder, system_frame_number as i32))
+        }
+    }
+
+    /// The pending frame that entered the element first
+    pub fn oldest_frame(&self) -> Option<VideoCodecFrame>{
+        unsafe{
+            VideoCodecFrame::new_from_gst_video_codec_frame(gst_video_encoder_get_oldest_frame(self.encoder))
+        }
+    }
+
+    /// Latency introduced by the encoder, like its lookahead
+    pub fn set_latency(&mut self, min: GstClockTime, max: GstClockTime){
+        unsafe{
+            gst_video_encoder_set_latency(self.encoder, min, max);
+        }
+    }
+
+    /// The encoder as an element, to post messages or read its properties
+    pub fn element(&self) -> Element{
+        unsafe{
+            Element::new_from_gst_element(gst_object_ref(self.encoder as gpointer) as *mut GstElement).unwrap()
+        }
+    }
+}
+
+/// A video encoder written in rust, like a wrapper for the rav1e crate,
+/// registered with `register_video_encoder`. All the methods are called
+/// from the streaming thread or during state changes, one at a time.
+pub trait VideoEncoderImpl: Send + 'static{
+    /// Called when going to PAUSED, to open the encoder
+    fn start(&mut self, _encoder: &mut VideoEncoder) -> bool{
+        true
+    }
+
+    /// Called when going back to READY, to close the encoder
+    fn stop(&mut self, _encoder: &mut VideoEncoder) -> bool{
+        true
+    }
+
+    /// Called with the format of the raw input, before the first frame and
+    /// when the caps change. The encoder is configured here and calls
+    /// `set_output_state` with the caps of the encoded stream
+    fn set_format(&mut self, encoder: &mut VideoEncoder, state: &VideoCodecState) -> bool;
+
+    /// Encodes `frame`. The frame is owned by the implementation until
+    /// it's given back with `finish_frame`, which can happen some frames
+    /// later, retrieving it with `VideoEncoder::frame`
+    fn handle_frame(&mut self, encoder: &mut VideoEncoder, frame: VideoCodecFrame) -> GstFlowReturn;
+
+    /// Called at EOS to output the frames still in the encoder
+    fn finish(&mut self, _encoder: &mut VideoEncoder) -> GstFlowReturn{
+        GST_FLOW_OK
+    }
+
+    /// Called when seeking, to discard the frames in the encoder
+    fn flush(&mut self, _encoder: &mut VideoEncoder) -> bool{
+        true
+    }
+}
+
+type NewDecoder = dyn Fn() -> Box<dyn VideoDecoderImpl> + Send + Sync;
+type DecoderImpl = Mutex<Box<dyn VideoDecoderImpl>>;
+type NewEncoder = dyn Fn() -> Box<dyn VideoEncoderImpl> + Send + Sync;
+type EncoderImpl = Mutex<Box<dyn VideoEncoderImpl>>;
+
+/// Registers a GstVideoDecoder subclass as the element `name`, so the
+/// decoder implemented by the objects returned by `new` can be used in
+/// pipelines and autoplugged by decodebin.
+///
+/// `sink_caps` are the encoded formats it accepts and `src_caps` the raw
+/// formats it outputs. `new` is called for each instance of the element.
+pub fn register_video_decoder<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                    sink_caps: &Caps, src_caps: &Caps, new: F) -> Result<()>
+    where T: VideoDecoderImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewDecoder> = Box::new(move || Box::new(new()) as Box<dyn VideoDecoderImpl>);
+    let class_data = ClassData::new(metadata, sink_caps, src_caps, new);
+    unsafe{
+        subclass::register_element(gst_video_decoder_get_type(), "GstRsVideoDecoder", name, rank, class_data,
+                                   Some(decoder_class_init), Some(decoder_instance_init)).map(|_| ())
+    }
+}
+
+/// Registers a GstVideoEncoder subclass as the element `name`, so the
+/// encoder implemented by the objects returned by `new` can be used in
+/// pipelines and by encodebin.
+///
+/// `sink_caps` are the raw formats it accepts and `src_caps` the encoded
+/// formats it outputs. `new` is called for each instance of the element.
+pub fn register_video_encoder<T, F>(name: &str, rank: GstRank, metadata: &ElementMetadata,
+                                    sink_caps: &Caps, src_caps: &Caps, new: F) -> Result<()>
+    where T: VideoEncoderImpl, F: Fn() -> T + Send + Sync + 'static{
+    let new: Box<NewEncoder> = Box::new(move || Box::new(new()) as Box<dyn VideoEncoderImpl>);
+    let class_data = ClassData::new(metadata, sink_caps, src_caps, new);
+    unsafe{
+        subclass::register_element(gst_video_encoder_get_type(), "GstRsVideoEncoder", name, rank, class_data,
+                                   Some(encoder_class_init), Some(encoder_instance_init)).map(|_| ())
+    }
+}
+
+extern "C" fn decoder_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewDecoder>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstVideoDecoderClass);
+        klass.start = Some(decoder_start);
+        klass.stop = Some(decoder_stop);
+        klass.set_format = Some(decoder_set_format);
+        klass.handle_frame = Some(decoder_handle_frame);
+        klass.finish = Some(decoder_finish);
+        klass.flush = Some(decoder_flush);
+    }
+}
+
+extern "C" fn decoder_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewDecoder>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: DecoderImpl = Mutex::new((class_data.new)());
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_decoder<R, F>(decoder: *mut GstVideoDecoder, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn VideoDecoderImpl, &mut VideoDecoder) -> R{
+    match subclass::instance_impl::<DecoderImpl>(decoder as gpointer){
+        Some(imp) => {
+            let mut handle = VideoDecoder{ decoder: decoder, _lifetime: PhantomData };
+            unwind::guard(decoder as *mut GstObject, default, || f(&mut **imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+extern "C" fn decoder_start(decoder: *mut GstVideoDecoder) -> gboolean{
+    unsafe{
+        with_decoder(decoder, false, |imp, decoder| imp.start(decoder)) as gboolean
+    }
+}
+
+extern "C" fn decoder_stop(decoder: *mut GstVideoDecoder) -> gboolean{
+    unsafe{
+        with_decoder(decoder, false, |imp, decoder| imp.stop(decoder)) as gboolean
+    }
+}
+
+extern "C" fn decoder_set_format(decoder: *mut GstVideoDecoder, state: *mut GstVideoCodecState) -> gboolean{
+    unsafe{
+        let state = match VideoCodecState::new_from_gst_video_codec_state(gst_video_codec_state_ref(state)){
+            Some(state) => state,
+            None => return 0
+        };
+        with_decoder(decoder, false, |imp, decoder| imp.set_format(decoder, &state)) as gboolean
+    }
+}
+
+extern "C" fn decoder_handle_frame(decoder: *mut GstVideoDecoder, frame: *mut GstVideoCodecFrame) -> GstFlowReturn{
+    unsafe{
+        let frame = match VideoCodecFrame::new_from_gst_video_codec_frame(frame){
+            Some(frame) => frame,
+            None => return GST_FLOW_ERROR
+        };
+        with_decoder(decoder, GST_FLOW_ERROR, |imp, decoder| imp.handle_frame(decoder, frame))
+    }
+}
+
+extern "C" fn decoder_finish(decoder: *mut GstVideoDecoder) -> GstFlowReturn{
+    unsafe{
+        with_decoder(decoder, GST_FLOW_OK, |imp, decoder| imp.finish(decoder))
+    }
+}
+
+extern "C" fn decoder_flush(decoder: *mut GstVideoDecoder) -> gboolean{
+    unsafe{
+        with_decoder(decoder, true, |imp, decoder| imp.flush(decoder)) as gboolean
+    }
+}
+
+extern "C" fn encoder_class_init(g_class: gpointer, class_data: gpointer){
+    unsafe{
+        let class_data = &*(class_data as *const ClassData<NewEncoder>);
+        class_data.install(g_class as *mut GstElementClass);
+        let klass = &mut *(g_class as *mut GstVideoEncoderClass);
+        klass.start = Some(encoder_start);
+        klass.stop = Some(encoder_stop);
+        klass.set_format = Some(encoder_set_format);
+        klass.handle_frame = Some(encoder_handle_frame);
+        klass.finish = Some(encoder_finish);
+        klass.flush = Some(encoder_flush);
+    }
+}
+
+extern "C" fn encoder_instance_init(instance: *mut GTypeInstance, g_class: gpointer){
+    unsafe{
+        if let Some(class_data) = subclass::class_data::<NewEncoder>(g_class){
+            unwind::guard(instance as *mut GstObject, (), || {
+                let imp: EncoderImpl = Mutex::new((class_data.new)());
+                subclass::set_instance_impl(instance as gpointer, imp);
+            });
+        }
+    }
+}
+
+unsafe fn with_encoder<R, F>(encoder: *mut GstVideoEncoder, default: R, f: F) -> R
+    where F: FnOnce(&mut dyn VideoEncoderImpl, &mut VideoEncoder) -> R{
+    match subclass::instance_impl::<EncoderImpl>(encoder as gpointer){
+        Some(imp) => {
+            let mut handle = VideoEncoder{ encoder: encoder, _lifetime: PhantomData };
+            unwind::guard(encoder as *mut GstObject, default, || f(&mut **imp.lock().unwrap_or_else(|err| err.into_inner()), &mut handle))
+        }
+        None => default
+    }
+}
+
+extern "C" fn encoder_start(encoder: *mut GstVideoEncoder) -> gboolean{
+    unsafe{
+        with_encoder(encoder, false, |imp, encoder| imp.start(encoder)) as gboolean
+    }
+}
+
+extern "C" fn encoder_stop(encoder: *mut GstVideoEncoder) -> gboolean{
+    unsafe{
+        with_encoder(encoder, false, |imp, encoder| imp.stop(encoder)) as gboolean
+    }
+}
+
+extern "C" fn encoder_set_format(encoder: *mut GstVideoEncoder, state: *mut GstVideoCodecState) -> gboolean{
+    unsafe{
+        let state = match VideoCodecState::new_from_gst_video_codec_state(gst_video_codec_state_ref(state)){
+            Some(state) => state,
+            None => return 0
+        };
+        with_encoder(encoder, false, |imp, encoder| imp.set_format(encoder, &state)) as gboolean
+    }
+}
+
+extern "C" fn encoder_handle_frame(encoder: *mut GstVideoEncoder, frame: *mut GstVideoCodecFrame) -> GstFlowReturn{
+    unsafe{
+        let frame = match VideoCodecFrame::new_from_gst_video_codec_frame(frame){
+            Some(frame) => frame,
+            None => return GST_FLOW_ERROR
+        };
+        with_encoder(encoder, GST_FLOW_ERROR, |imp, encoder| imp.handle_frame(encoder, frame))
+    }
+}
+
+extern "C" fn encoder_finish(encoder: *mut GstVideoEncoder) -> GstFlowReturn{
+    unsafe{
+        with_encoder(encoder, GST_FLOW_OK, |imp, encoder| imp.finish(encoder))
+    }
+}
+
+extern "C" fn encoder_flush(encoder: *mut GstVideoEncoder) -> gboolean{
+    unsafe{
+        with_encoder(encoder, true, |imp, encoder| imp.flush(encoder)) as gboolean
+    }
+}
@@ -0,0 +1,102 @@
+use ffi::*;
+use element::Element;
+use error::Result;
+use reference::Reference;
+
+use std::ops::{Deref, DerefMut};
+
+/// Typed wrapper for the videocrop element.
+///
+/// When downstream supports `VideoCropMeta`, videocrop only attaches
+/// the meta with the cropped area instead of copying it, so cropping a
+/// region of interest is free.
+pub struct VideoCrop{
+    videocrop: Element
+}
+
+unsafe impl Sync for VideoCrop {}
+unsafe impl Send for VideoCrop {}
+
+impl VideoCrop{
+    pub fn new(name: &str) -> Result<VideoCrop>{
+        Element::new("videocrop", name).map(|videocrop| VideoCrop{ videocrop: videocrop })
+    }
+
+    pub fn new_from_element(element: Element) -> VideoCrop{
+        VideoCrop{ videocrop: element }
+    }
+
+    /// Pixels to remove from each side of the frame. A value of -1
+    /// crops that side as needed to match the size negotiated with
+    /// downstream, for example by a capsfilter
+    pub fn set_crop(&mut self, left: i32, right: i32, top: i32, bottom: i32){
+        self.videocrop.set("left", left);
+        self.videocrop.set("right", right);
+        self.videocrop.set("top", top);
+        self.videocrop.set("bottom", bottom);
+    }
+
+    /// Returns the pixels removed from each side as
+    /// (left, right, top, bottom)
+    pub fn crop(&self) -> (i32, i32, i32, i32){
+        (self.videocrop.get("left"), self.videocrop.get("right"),
+         self.videocrop.get("top"), self.videocrop.get("bottom"))
+    }
+
+    /// Keeps only the region at x, y of size w x h of frames of
+    /// frame_width x frame_height
+    pub fn set_region(&mut self, x: u32, y: u32, w: u32, h: u32, frame_width: u32, frame_height: u32){
+        let right = frame_width.saturating_sub(x + w);
+        let bottom = frame_height.saturating_sub(y + h);
+        self.set_crop(x as i32, right as i32, y as i32, bottom as i32);
+    }
+
+    /// Centers the crop and takes the size from the caps negotiated
+    /// with downstream
+    pub fn set_auto(&mut self){
+        self.set_crop(-1, -1, -1, -1);
+    }
+}
+
+impl AsRef<Element> for VideoCrop{
+    fn as_ref(&self) -> &Element{
+        &self.videocrop
+    }
+}
+
+impl AsMut<Element> for VideoCrop{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.videocrop
+    }
+}
+
+impl From<VideoCrop> for Element{
+    fn from(videocrop: VideoCrop) -> Element{
+        videocrop.videocrop
+    }
+}
+
+impl Deref for VideoCrop{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.videocrop
+    }
+}
+
+impl DerefMut for VideoCrop{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.videocrop
+    }
+}
+
+impl ::Transfer for VideoCrop{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.videocrop.transfer()
+    }
+}
+
+impl Reference for VideoCrop{
+    fn reference(&self) -> VideoCrop{
+        VideoCrop{ videocrop: self.videocrop.reference() }
+    }
+}
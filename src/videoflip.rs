@@ -0,0 +1,221 @@
+use ffi::*;
+use util::*;
+use element::Element;
+use error::Result;
+use message::Message;
+use reference::Reference;
+
+use std::ops::{Deref, DerefMut};
+
+/// Value of the "image-orientation" tag: how the frames have to be
+/// rotated clockwise, and flipped horizontally before rotating, to be
+/// displayed upright. Phones usually record rotated video and only tag
+/// the right orientation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageOrientation{
+    Rotate0,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipRotate0,
+    FlipRotate90,
+    FlipRotate180,
+    FlipRotate270,
+}
+
+impl ImageOrientation{
+    /// Parses the value of the tag, like "rotate-90"
+    pub fn from_str(orientation: &str) -> Option<ImageOrientation>{
+        match orientation{
+            "rotate-0" => Some(ImageOrientation::Rotate0),
+            "rotate-90" => Some(ImageOrientation::Rotate90),
+            "rotate-180" => Some(ImageOrientation::Rotate180),
+            "rotate-270" => Some(ImageOrientation::Rotate270),
+            "flip-rotate-0" => Some(ImageOrientation::FlipRotate0),
+            "flip-rotate-90" => Some(ImageOrientation::FlipRotate90),
+            "flip-rotate-180" => Some(ImageOrientation::FlipRotate180),
+            "flip-rotate-270" => Some(ImageOrientation::FlipRotate270),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str{
+        match *self{
+            ImageOrientation::Rotate0 => "rotate-0",
+            ImageOrientation::Rotate90 => "rotate-90",
+            ImageOrientation::Rotate180 => "rotate-180",
+            ImageOrientation::Rotate270 => "rotate-270",
+            ImageOrientation::FlipRotate0 => "flip-rotate-0",
+            ImageOrientation::FlipRotate90 => "flip-rotate-90",
+            ImageOrientation::FlipRotate180 => "flip-rotate-180",
+            ImageOrientation::FlipRotate270 => "flip-rotate-270",
+        }
+    }
+
+    /// Reads the "image-orientation" tag from a tag list
+    pub unsafe fn from_tag_list(tags: *const GstTagList) -> Option<ImageOrientation>{
+        let ctag = CString::new("image-orientation").unwrap();
+        let mut value: *const gchar = ptr::null();
+        if gst_tag_list_peek_string_index(tags, ctag.as_ptr(), 0, &mut value) != 0 && value != ptr::null(){
+            ImageOrientation::from_str(from_c_str!(value))
+        }else{
+            None
+        }
+    }
+
+    /// Reads the orientation from a tag message, None if the message
+    /// isn't a tag message or doesn't have the tag
+    pub fn from_message(message: &Message) -> Option<ImageOrientation>{
+        unsafe{
+            let msg = message.gst_message() as *mut GstMessage;
+            if (*msg)._type != GST_MESSAGE_TAG{
+                return None;
+            }
+            let mut tags: *mut GstTagList = ptr::null_mut();
+            gst_message_parse_tag(msg, &mut tags);
+            if tags == ptr::null_mut(){
+                return None;
+            }
+            let orientation = ImageOrientation::from_tag_list(tags);
+            gst_mini_object_unref(tags as *mut GstMiniObject);
+            orientation
+        }
+    }
+
+    /// videoflip method that displays frames with this orientation
+    /// upright
+    pub fn flip_method(&self) -> VideoFlipMethod{
+        match *self{
+            ImageOrientation::Rotate0 => VideoFlipMethod::Identity,
+            ImageOrientation::Rotate90 => VideoFlipMethod::Clockwise,
+            ImageOrientation::Rotate180 => VideoFlipMethod::Rotate180,
+            ImageOrientation::Rotate270 => VideoFlipMethod::CounterClockwise,
+            ImageOrientation::FlipRotate0 => VideoFlipMethod::HorizontalFlip,
+            ImageOrientation::FlipRotate90 => VideoFlipMethod::UpperLeftDiagonal,
+            ImageOrientation::FlipRotate180 => VideoFlipMethod::VerticalFlip,
+            ImageOrientation::FlipRotate270 => VideoFlipMethod::UpperRightDiagonal,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum VideoFlipMethod{
+    Identity = 0,
+    /// Rotate 90 degrees clockwise
+    Clockwise = 1,
+    Rotate180 = 2,
+    /// Rotate 90 degrees counter clockwise
+    CounterClockwise = 3,
+    HorizontalFlip = 4,
+    VerticalFlip = 5,
+    /// Flip across the upper left / lower right diagonal
+    UpperLeftDiagonal = 6,
+    /// Flip across the upper right / lower left diagonal
+    UpperRightDiagonal = 7,
+    /// Rotate and flip according to the image-orientation tag of the
+    /// stream, which videoflip follows by itself
+    Automatic = 8,
+}
+
+impl VideoFlipMethod{
+    /// The method with the value of the "method" property, None for
+    /// values this version doesn't know
+    pub fn from_i32(method: i32) -> Option<VideoFlipMethod>{
+        match method{
+            0 => Some(VideoFlipMethod::Identity),
+            1 => Some(VideoFlipMethod::Clockwise),
+            2 => Some(VideoFlipMethod::Rotate180),
+            3 => Some(VideoFlipMethod::CounterClockwise),
+            4 => Some(VideoFlipMethod::HorizontalFlip),
+            5 => Some(VideoFlipMethod::VerticalFlip),
+            6 => Some(VideoFlipMethod::UpperLeftDiagonal),
+            7 => Some(VideoFlipMethod::UpperRightDiagonal),
+            8 => Some(VideoFlipMethod::Automatic),
+            _ => None
+        }
+    }
+}
+
+/// Typed wrapper for the videoflip element, which rotates and flips
+/// video frames
+pub struct VideoFlip{
+    videoflip: Element
+}
+
+unsafe impl Sync for VideoFlip {}
+unsafe impl Send for VideoFlip {}
+
+impl VideoFlip{
+    pub fn new(name: &str) -> Result<VideoFlip>{
+        Element::new("videoflip", name).map(|videoflip| VideoFlip{ videoflip: videoflip })
+    }
+
+    pub fn new_from_element(element: Element) -> VideoFlip{
+        VideoFlip{ videoflip: element }
+    }
+
+    pub fn set_method(&mut self, method: VideoFlipMethod){
+        self.videoflip.set("method", method as i32);
+    }
+
+    /// Identity for methods this version doesn't know, like custom
+    /// matrices
+    pub fn method(&self) -> VideoFlipMethod{
+        VideoFlipMethod::from_i32(self.videoflip.get("method")).unwrap_or(VideoFlipMethod::Identity)
+    }
+
+    /// Displays frames with the given orientation upright
+    pub fn set_orientation(&mut self, orientation: ImageOrientation){
+        self.set_method(orientation.flip_method());
+    }
+
+    /// Keeps the video upright according to the image-orientation tag of
+    /// the stream, even if it changes while playing
+    pub fn auto_rotate(&mut self){
+        self.set_method(VideoFlipMethod::Automatic);
+    }
+}
+
+impl AsRef<Element> for VideoFlip{
+    fn as_ref(&self) -> &Element{
+        &self.videoflip
+    }
+}
+
+impl AsMut<Element> for VideoFlip{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.videoflip
+    }
+}
+
+impl From<VideoFlip> for Element{
+    fn from(videoflip: VideoFlip) -> Element{
+        videoflip.videoflip
+    }
+}
+
+impl Deref for VideoFlip{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.videoflip
+    }
+}
+
+impl DerefMut for VideoFlip{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.videoflip
+    }
+}
+
+impl ::Transfer for VideoFlip{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.videoflip.transfer()
+    }
+}
+
+impl Reference for VideoFlip{
+    fn reference(&self) -> VideoFlip{
+        VideoFlip{ videoflip: self.videoflip.reference() }
+    }
+}
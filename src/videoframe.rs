@@ -1,4 +1,5 @@
 use buffer::Buffer;
+use reference::Reference;
 use ffi::*;
 use std::mem;
 use std::slice::from_raw_parts;
@@ -142,6 +143,15 @@ impl VideoFrame{
         }
     }
 
+    /// Maps `buffer` for reading as a frame described by `info`, usually
+    /// obtained with `VideoInfo::from_caps` from the caps of the sample.
+    /// None if the buffer is too small for the format
+    pub fn map(buffer: &Buffer, info: &::VideoInfo) -> Option<VideoFrame>{
+        unsafe{
+            VideoFrame::new(*info, buffer.reference())
+        }
+    }
+
     #[inline]
     pub fn info(&self) -> &::VideoInfo{
         &self.vf.info
@@ -224,6 +234,19 @@ impl VideoFrame{
 	    }
     }
 
+    /// The bytes of plane `p`, like the Y, U and V planes of I420 or the
+    /// Y and interleaved UV planes of NV12. Rows are `plane_stride(p)`
+    /// bytes apart, which can be more than the width of the plane
+    #[inline]
+    pub fn plane_data(&self, p: u32) -> Option<&[u8]>{
+        self.plane(p).map(|plane| plane.data::<u8>())
+    }
+
+    #[inline]
+    pub fn plane_stride(&self, p: u32) -> Option<i32>{
+        self.plane(p).map(|plane| plane.stride())
+    }
+
 	#[inline]
 	pub fn n_components(&self) -> u32{
 	    self.format_info().n_components
@@ -5,6 +5,23 @@ use ::Caps;
 pub type VideoInfo = GstVideoInfo;
 
 impl VideoInfo{
+    /// Info of raw video caps, None for caps that aren't raw video or
+    /// aren't fixed
+    pub fn from_caps(caps: &Caps) -> Option<VideoInfo>{
+        caps.video_info()
+    }
+
+    /// Info of `format`, like GST_VIDEO_FORMAT_I420, with the default
+    /// strides and offsets of its planes for `width` x `height`
+    pub fn new(format: GstVideoFormat, width: u32, height: u32) -> VideoInfo{
+        unsafe{
+            let mut info = mem::zeroed();
+            gst_video_info_init(&mut info);
+            gst_video_info_set_format(&mut info, format, width, height);
+            info
+        }
+    }
+
     #[inline]
     pub fn format_info(&self) -> &GstVideoFormatInfo{
         unsafe{ &(*self.finfo) }
@@ -50,6 +67,16 @@ impl VideoInfo{
         self.interlace_mode != GST_VIDEO_INTERLACE_MODE_PROGRESSIVE
     }
 
+    #[inline]
+    pub fn colorimetry(&self) -> ::VideoColorimetry{
+        self.colorimetry
+    }
+
+    #[inline]
+    pub fn set_colorimetry(&mut self, colorimetry: ::VideoColorimetry){
+        self.colorimetry = colorimetry;
+    }
+
     #[inline]
     pub fn flags(&self) -> GstVideoFlags{
         self.flags
@@ -111,7 +138,7 @@ impl VideoInfo{
     }
 
     pub fn to_caps(&self) -> Option<::Caps>{
-        unsafe{ Caps::new(gst_video_info_to_caps(mem::transmute(self))) }
+        unsafe{ Caps::new_from_gst_caps(gst_video_info_to_caps(mem::transmute(self))) }
     }
 }
 
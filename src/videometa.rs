@@ -0,0 +1,152 @@
+use ffi::*;
+use util::*;
+use structure::Structure;
+use ::Transfer;
+use videooverlaycomposition::VideoOverlayComposition;
+
+/// Extra buffer metadata describing an image region of interest, for
+/// example the bounding box of an object detected by a computer vision
+/// algorithm. Downstream elements (like overlays or encoders) can use it
+/// to draw or treat the region differently.
+pub type VideoRegionOfInterestMeta = GstVideoRegionOfInterestMeta;
+
+impl VideoRegionOfInterestMeta{
+    /// Type of the region of interest, like "face" or "car"
+    pub fn roi_type(&self) -> &str{
+        unsafe{ from_c_str!(g_quark_to_string(self.roi_type)) }
+    }
+
+    #[inline]
+    pub fn id(&self) -> i32{
+        self.id
+    }
+
+    #[inline]
+    pub fn set_id(&mut self, id: i32){
+        self.id = id;
+    }
+
+    /// Identifier of the parent ROI or -1 if it has no parent
+    #[inline]
+    pub fn parent_id(&self) -> i32{
+        self.parent_id
+    }
+
+    #[inline]
+    pub fn set_parent_id(&mut self, parent_id: i32){
+        self.parent_id = parent_id;
+    }
+
+    #[inline]
+    pub fn x(&self) -> u32{
+        self.x
+    }
+
+    #[inline]
+    pub fn y(&self) -> u32{
+        self.y
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32{
+        self.w
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32{
+        self.h
+    }
+
+    /// Returns the bounds of the region as (x, y, width, height)
+    #[inline]
+    pub fn rect(&self) -> (u32, u32, u32, u32){
+        (self.x, self.y, self.w, self.h)
+    }
+
+    #[inline]
+    pub fn set_rect(&mut self, x: u32, y: u32, w: u32, h: u32){
+        self.x = x;
+        self.y = y;
+        self.w = w;
+        self.h = h;
+    }
+
+    /// Attach element-specific parameters, like the confidence of a
+    /// detection, to the region.
+    ///
+    /// Requires GStreamer 1.14 or newer.
+    pub fn add_param(&mut self, param: Structure){
+        unsafe{
+            gst_video_region_of_interest_meta_add_param(self, param.transfer());
+        }
+    }
+
+    /// Get the parameter structure named `name` attached to the region.
+    ///
+    /// Requires GStreamer 1.14 or newer.
+    pub fn param(&self, name: &str) -> Option<Structure>{
+        let cname = CString::new(name).unwrap();
+        unsafe{
+            let param = gst_video_region_of_interest_meta_get_param(mem::transmute(self), cname.as_ptr());
+            Structure::new_from_gst_structure(param)
+        }
+    }
+}
+
+/// Extra buffer metadata carrying a `VideoOverlayComposition` that
+/// should be blended on top of the buffer
+pub type VideoOverlayCompositionMeta = GstVideoOverlayCompositionMeta;
+
+impl VideoOverlayCompositionMeta{
+    pub fn overlay(&self) -> Option<VideoOverlayComposition>{
+        unsafe{
+            if self.overlay != ptr::null_mut(){
+                VideoOverlayComposition::new_from_gst_composition(gst_mini_object_ref(self.overlay as *mut GstMiniObject) as *mut GstVideoOverlayComposition)
+            }else{
+                None
+            }
+        }
+    }
+}
+
+/// Extra buffer metadata describing the valid area of a video frame.
+/// Instead of copying the cropped region, elements can attach this meta
+/// and let downstream elements that support it (like most sinks and
+/// hardware encoders) only use that part of the frame.
+pub type VideoCropMeta = GstVideoCropMeta;
+
+impl VideoCropMeta{
+    #[inline]
+    pub fn x(&self) -> u32{
+        self.x
+    }
+
+    #[inline]
+    pub fn y(&self) -> u32{
+        self.y
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32{
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32{
+        self.height
+    }
+
+    /// Returns the cropped area as (x, y, width, height)
+    #[inline]
+    pub fn rect(&self) -> (u32, u32, u32, u32){
+        (self.x, self.y, self.width, self.height)
+    }
+
+    #[inline]
+    pub fn set_rect(&mut self, x: u32, y: u32, w: u32, h: u32){
+        self.x = x;
+        self.y = y;
+        self.width = w;
+        self.height = h;
+    }
+}
@@ -0,0 +1,142 @@
+use ffi::*;
+use bus::Bus;
+use element::Element;
+use message::Message;
+
+use std::ops::{Deref, DerefMut};
+
+unsafe impl Send for VideoOverlay {}
+
+/// Interface of the video sinks that can render into a window of the
+/// application, like xvimagesink, glimagesink, d3dvideosink or
+/// osxvideosink, instead of opening their own. playbin implements it too
+/// and forwards it to its video sink.
+///
+/// The handle of the window, an X11 Window, a Win32 HWND or a Cocoa
+/// NSView, can be set before the pipeline starts if the sink is known.
+/// Otherwise the sink asks for it with a prepare-window-handle element
+/// message, posted from the streaming thread, which has to be answered
+/// from a sync handler of the bus, like `set_window_handle_on_prepare`
+/// does.
+/// See [GstVideoOverlay](https://gstreamer.freedesktop.org/documentation/video/gstvideooverlay.html)
+pub struct VideoOverlay{
+    overlay: Element
+}
+
+impl VideoOverlay{
+    /// None if `element` doesn't implement the video overlay interface
+    pub fn new_from_element(element: Element) -> Option<VideoOverlay>{
+        unsafe{
+            if g_type_check_instance_is_a(element.gst_element() as *mut GTypeInstance, gst_video_overlay_get_type()) != 0{
+                Some(VideoOverlay{ overlay: element })
+            }else{
+                None
+            }
+        }
+    }
+
+    /// The sink that posted `message` if it's a prepare-window-handle
+    /// message, None for any other message
+    pub fn from_prepare_window_handle(message: &Message) -> Option<VideoOverlay>{
+        if message.is_prepare_window_handle(){
+            message.src_element().and_then(VideoOverlay::new_from_element)
+        }else{
+            None
+        }
+    }
+
+    /// Answers the prepare-window-handle messages posted on `bus` setting
+    /// `handle` as the window of the sink that posted it. Replaces the sync
+    /// handler of the bus, other messages are queued as usual
+    pub fn set_window_handle_on_prepare(bus: &mut Bus, handle: usize){
+        bus.set_sync_handler(move |message| {
+            match VideoOverlay::from_prepare_window_handle(message){
+                Some(mut overlay) => {
+                    overlay.set_window_handle(handle);
+                    GST_BUS_DROP
+                }
+                None => GST_BUS_PASS
+            }
+        });
+    }
+
+    /// Renders into the window `handle`, 0 to go back to a window
+    /// created by the sink. The window has to stay alive while the sink
+    /// uses it
+    pub fn set_window_handle(&mut self, handle: usize){
+        unsafe{
+            gst_video_overlay_set_window_handle(self.gst_video_overlay_mut(), handle as guintptr);
+        }
+    }
+
+    /// Redraws the last frame, for example when the window is exposed or
+    /// resized while the pipeline is paused
+    pub fn expose(&mut self){
+        unsafe{
+            gst_video_overlay_expose(self.gst_video_overlay_mut());
+        }
+    }
+
+    /// Whether the sink handles the mouse and keyboard events of the
+    /// window, to send them upstream as navigation events. Disable it when
+    /// the application handles them
+    pub fn handle_events(&mut self, handle_events: bool){
+        unsafe{
+            gst_video_overlay_handle_events(self.gst_video_overlay_mut(), handle_events as gboolean);
+        }
+    }
+
+    /// Renders in the area of `width` x `height` at `x`, `y` of the window
+    /// instead of the whole window, -1 for all of them to use the whole
+    /// window again. False if the sink doesn't support it
+    pub fn set_render_rectangle(&mut self, x: i32, y: i32, width: i32, height: i32) -> bool{
+        unsafe{
+            gst_video_overlay_set_render_rectangle(self.gst_video_overlay_mut(), x, y, width, height) != 0
+        }
+    }
+
+    pub unsafe fn gst_video_overlay(&self) -> *const GstVideoOverlay{
+        self.overlay.gst_element() as *const GstVideoOverlay
+    }
+
+    pub unsafe fn gst_video_overlay_mut(&mut self) -> *mut GstVideoOverlay{
+        self.overlay.gst_element_mut() as *mut GstVideoOverlay
+    }
+}
+
+impl AsRef<Element> for VideoOverlay{
+    fn as_ref(&self) -> &Element{
+        &self.overlay
+    }
+}
+
+impl AsMut<Element> for VideoOverlay{
+    fn as_mut(&mut self) -> &mut Element{
+        &mut self.overlay
+    }
+}
+
+impl From<VideoOverlay> for Element{
+    fn from(overlay: VideoOverlay) -> Element{
+        overlay.overlay
+    }
+}
+
+impl Deref for VideoOverlay{
+    type Target = Element;
+    fn deref(&self) -> &Element{
+        &self.overlay
+    }
+}
+
+impl DerefMut for VideoOverlay{
+    fn deref_mut(&mut self) -> &mut Element{
+        &mut self.overlay
+    }
+}
+
+impl ::Transfer for VideoOverlay{
+    unsafe fn transfer(self) -> *mut GstElement{
+        self.overlay.transfer()
+    }
+}
@@ -0,0 +1,267 @@
+use ffi::*;
+use buffer::Buffer;
+use reference::Reference;
+use miniobject::MiniObject;
+
+use std::ptr;
+use std::ops::{Deref, DerefMut};
+
+/// Pixel format of the raw overlay rectangles, ARGB in memory order
+/// which is BGRA in little endian machines
+#[cfg(target_endian = "little")]
+pub const VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB: GstVideoFormat = GST_VIDEO_FORMAT_BGRA;
+#[cfg(target_endian = "big")]
+pub const VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB: GstVideoFormat = GST_VIDEO_FORMAT_ARGB;
+
+unsafe impl Send for VideoOverlayRectangle {}
+unsafe impl Send for VideoOverlayComposition {}
+
+/// A rectangle of ARGB pixel data that has to be blended on top of a
+/// video frame at a given position and size.
+#[derive(Clone)]
+pub struct VideoOverlayRectangle{
+    rectangle: MiniObject
+}
+
+impl VideoOverlayRectangle{
+    pub unsafe fn new_from_gst_rectangle(rectangle: *mut GstVideoOverlayRectangle) -> Option<VideoOverlayRectangle>{
+        MiniObject::new_from_gst_miniobject(rectangle as *mut GstMiniObject)
+            .map(|miniobject| VideoOverlayRectangle{ rectangle: miniobject })
+    }
+
+    /// Creates a new rectangle from `pixels` which has to contain
+    /// `width` x `height` pixels in `VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB`
+    /// format with no padding between rows.
+    ///
+    /// The rectangle will be rendered at render_x, render_y scaled to
+    /// render_width x render_height. `flags` specifies if the pixel
+    /// data has premultiplied alpha.
+    pub fn new_raw(mut pixels: Buffer, width: u32, height: u32, render_x: i32, render_y: i32, render_width: u32, render_height: u32, flags: GstVideoOverlayFormatFlags) -> Option<VideoOverlayRectangle>{
+        unsafe{
+            if gst_buffer_add_video_meta(pixels.gst_buffer_mut(), GST_VIDEO_FRAME_FLAG_NONE, VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB, width, height) == ptr::null_mut(){
+                return None;
+            }
+            VideoOverlayRectangle::new_from_gst_rectangle(gst_video_overlay_rectangle_new_raw(pixels.gst_buffer_mut(), render_x, render_y, render_width, render_height, flags))
+        }
+    }
+
+    /// Returns the position and size where the rectangle will be rendered
+    /// as (x, y, width, height)
+    pub fn render_rectangle(&self) -> (i32, i32, u32, u32){
+        let mut x = 0;
+        let mut y = 0;
+        let mut w = 0;
+        let mut h = 0;
+        unsafe{
+            gst_video_overlay_rectangle_get_render_rectangle(self.gst_rectangle() as *mut GstVideoOverlayRectangle, &mut x, &mut y, &mut w, &mut h);
+        }
+        (x, y, w, h)
+    }
+
+    /// Sets the position and size where the rectangle will be rendered.
+    /// The rectangle has to be writable.
+    pub fn set_render_rectangle(&mut self, x: i32, y: i32, w: u32, h: u32){
+        unsafe{
+            gst_video_overlay_rectangle_set_render_rectangle(self.gst_rectangle_mut(), x, y, w, h);
+        }
+    }
+
+    pub fn seqnum(&self) -> u32{
+        unsafe{
+            gst_video_overlay_rectangle_get_seqnum(self.gst_rectangle() as *mut GstVideoOverlayRectangle)
+        }
+    }
+
+    pub fn flags(&self) -> GstVideoOverlayFormatFlags{
+        unsafe{
+            gst_video_overlay_rectangle_get_flags(self.gst_rectangle() as *mut GstVideoOverlayRectangle)
+        }
+    }
+
+    pub fn global_alpha(&self) -> f32{
+        unsafe{
+            gst_video_overlay_rectangle_get_global_alpha(self.gst_rectangle() as *mut GstVideoOverlayRectangle)
+        }
+    }
+
+    /// Sets a global alpha value between 0 and 1 that will be
+    /// multiplied with the alpha of each pixel
+    pub fn set_global_alpha(&mut self, alpha: f32){
+        unsafe{
+            gst_video_overlay_rectangle_set_global_alpha(self.gst_rectangle_mut(), alpha);
+        }
+    }
+
+    /// Returns the pixel data scaled to the render size in
+    /// `VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB` format
+    pub fn pixels_raw(&self, flags: GstVideoOverlayFormatFlags) -> Option<Buffer>{
+        unsafe{
+            let buffer = gst_video_overlay_rectangle_get_pixels_raw(self.gst_rectangle() as *mut GstVideoOverlayRectangle, flags);
+            if buffer != ptr::null_mut(){
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Returns the pixel data scaled to the render size in ARGB format
+    pub fn pixels_argb(&self, flags: GstVideoOverlayFormatFlags) -> Option<Buffer>{
+        unsafe{
+            let buffer = gst_video_overlay_rectangle_get_pixels_argb(self.gst_rectangle() as *mut GstVideoOverlayRectangle, flags);
+            if buffer != ptr::null_mut(){
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Returns the pixel data scaled to the render size in AYUV format
+    pub fn pixels_ayuv(&self, flags: GstVideoOverlayFormatFlags) -> Option<Buffer>{
+        unsafe{
+            let buffer = gst_video_overlay_rectangle_get_pixels_ayuv(self.gst_rectangle() as *mut GstVideoOverlayRectangle, flags);
+            if buffer != ptr::null_mut(){
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }else{
+                None
+            }
+        }
+    }
+
+    /// Returns the pixel data at its original size in
+    /// `VIDEO_OVERLAY_COMPOSITION_FORMAT_RGB` format
+    pub fn pixels_unscaled_raw(&self, flags: GstVideoOverlayFormatFlags) -> Option<Buffer>{
+        unsafe{
+            let buffer = gst_video_overlay_rectangle_get_pixels_unscaled_raw(self.gst_rectangle() as *mut GstVideoOverlayRectangle, flags);
+            if buffer != ptr::null_mut(){
+                Buffer::new(gst_mini_object_ref(buffer as *mut GstMiniObject) as *mut GstBuffer)
+            }else{
+                None
+            }
+        }
+    }
+
+    pub unsafe fn gst_rectangle(&self) -> *const GstVideoOverlayRectangle{
+        self.rectangle.gst_miniobject() as *const GstVideoOverlayRectangle
+    }
+
+    pub unsafe fn gst_rectangle_mut(&mut self) -> *mut GstVideoOverlayRectangle{
+        self.rectangle.gst_miniobject_mut() as *mut GstVideoOverlayRectangle
+    }
+}
+
+/// A list of rectangles to blend on top of a video frame.
+///
+/// Attached to a buffer through its meta, sinks that support it can
+/// do the blending themselves, usually on the GPU, otherwise an element
+/// upstream will blend it in software.
+#[derive(Clone)]
+pub struct VideoOverlayComposition{
+    composition: MiniObject
+}
+
+impl VideoOverlayComposition{
+    pub unsafe fn new_from_gst_composition(composition: *mut GstVideoOverlayComposition) -> Option<VideoOverlayComposition>{
+        MiniObject::new_from_gst_miniobject(composition as *mut GstMiniObject)
+            .map(|miniobject| VideoOverlayComposition{ composition: miniobject })
+    }
+
+    /// Creates a new composition containing the passed rectangle
+    pub fn new(rectangle: &VideoOverlayRectangle) -> Option<VideoOverlayComposition>{
+        unsafe{
+            VideoOverlayComposition::new_from_gst_composition(gst_video_overlay_composition_new(rectangle.gst_rectangle() as *mut GstVideoOverlayRectangle))
+        }
+    }
+
+    /// Adds a rectangle to the composition, which has to be writable
+    pub fn add_rectangle(&mut self, rectangle: &VideoOverlayRectangle){
+        unsafe{
+            gst_video_overlay_composition_add_rectangle(self.gst_composition_mut(), rectangle.gst_rectangle() as *mut GstVideoOverlayRectangle);
+        }
+    }
+
+    pub fn n_rectangles(&self) -> u32{
+        unsafe{
+            gst_video_overlay_composition_n_rectangles(self.gst_composition() as *mut GstVideoOverlayComposition)
+        }
+    }
+
+    pub fn rectangle(&self, n: u32) -> Option<VideoOverlayRectangle>{
+        unsafe{
+            let rectangle = gst_video_overlay_composition_get_rectangle(self.gst_composition() as *mut GstVideoOverlayComposition, n);
+            if rectangle != ptr::null_mut(){
+                VideoOverlayRectangle::new_from_gst_rectangle(gst_mini_object_ref(rectangle as *mut GstMiniObject) as *mut GstVideoOverlayRectangle)
+            }else{
+                None
+            }
+        }
+    }
+
+    pub fn rectangles(&self) -> Vec<VideoOverlayRectangle>{
+        (0..self.n_rectangles()).filter_map(|n| self.rectangle(n)).collect()
+    }
+
+    pub fn seqnum(&self) -> u32{
+        unsafe{
+            gst_video_overlay_composition_get_seqnum(self.gst_composition() as *mut GstVideoOverlayComposition)
+        }
+    }
+
+    pub unsafe fn gst_composition(&self) -> *const GstVideoOverlayComposition{
+        self.composition.gst_miniobject() as *const GstVideoOverlayComposition
+    }
+
+    pub unsafe fn gst_composition_mut(&mut self) -> *mut GstVideoOverlayComposition{
+        self.composition.gst_miniobject_mut() as *mut GstVideoOverlayComposition
+    }
+}
+
+impl ::Transfer<GstVideoOverlayRectangle> for VideoOverlayRectangle{
+    unsafe fn transfer(self) -> *mut GstVideoOverlayRectangle{
+        self.rectangle.transfer() as *mut GstVideoOverlayRectangle
+    }
+}
+
+impl Reference for VideoOverlayRectangle{
+    fn reference(&self) -> VideoOverlayRectangle{
+        VideoOverlayRectangle{ rectangle: self.rectangle.reference() }
+    }
+}
+
+impl ::Transfer<GstVideoOverlayComposition> for VideoOverlayComposition{
+    unsafe fn transfer(self) -> *mut GstVideoOverlayComposition{
+        self.composition.transfer() as *mut GstVideoOverlayComposition
+    }
+}
+
+impl Reference for VideoOverlayComposition{
+    fn reference(&self) -> VideoOverlayComposition{
+        VideoOverlayComposition{ composition: self.composition.reference() }
+    }
+}
+
+impl AsRef<MiniObject> for VideoOverlayComposition{
+    fn as_ref(&self) -> &MiniObject{
+        &self.composition
+    }
+}
+
+impl AsMut<MiniObject> for VideoOverlayComposition{
+    fn as_mut(&mut self) -> &mut MiniObject{
+        &mut self.composition
+    }
+}
+
+impl Deref for VideoOverlayComposition{
+    type Target = MiniObject;
+    fn deref(&self) -> &MiniObject{
+        &self.composition
+    }
+}
+
+impl DerefMut for VideoOverlayComposition{
+    fn deref_mut(&mut self) -> &mut MiniObject{
+        &mut self.composition
+    }
+}